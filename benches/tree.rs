@@ -0,0 +1,64 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use dtree_tui::file_viewer::wrap_ranges;
+use dtree_tui::navigation::Navigation;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// 50 directories with 40 files each
+fn create_tree(root: &Path) {
+    for dir in 0..50 {
+        let dir_path = root.join(format!("dir_{:02}", dir));
+        fs::create_dir(&dir_path).unwrap();
+        for file in 0..40 {
+            fs::write(dir_path.join(format!("file_{:02}.txt", file)), "").unwrap();
+        }
+    }
+}
+
+/// Navigation with every directory expanded (about 2000 visible nodes)
+fn expanded_navigation(root: &Path) -> Navigation {
+    let mut nav = Navigation::new(root.to_path_buf(), true, false, false).unwrap();
+    for dir in 0..50 {
+        nav.toggle_node(&root.join(format!("dir_{:02}", dir)), true)
+            .unwrap();
+    }
+    nav
+}
+
+fn bench_tree_loading(c: &mut Criterion) {
+    let temp_dir = TempDir::new().unwrap();
+    create_tree(temp_dir.path());
+
+    c.bench_function("load root directory", |b| {
+        b.iter(|| Navigation::new(temp_dir.path().to_path_buf(), true, false, false).unwrap())
+    });
+    c.bench_function("load and expand all directories", |b| {
+        b.iter(|| expanded_navigation(temp_dir.path()))
+    });
+}
+
+fn bench_flat_list(c: &mut Criterion) {
+    let temp_dir = TempDir::new().unwrap();
+    create_tree(temp_dir.path());
+    let mut nav = expanded_navigation(temp_dir.path());
+
+    c.bench_function("rebuild flat list (2000 nodes)", |b| {
+        b.iter(|| nav.rebuild_flat_list())
+    });
+}
+
+fn bench_wrapping(c: &mut Criterion) {
+    let ascii = "fn main() { println!(\"hello world\"); } // ".repeat(20);
+    let multibyte = "日本語のテキストと emoji 🎉 mixed with ASCII ".repeat(20);
+
+    c.bench_function("wrap long ASCII line", |b| {
+        b.iter(|| wrap_ranges(black_box(&ascii), 80))
+    });
+    c.bench_function("wrap long multibyte line", |b| {
+        b.iter(|| wrap_ranges(black_box(&multibyte), 80))
+    });
+}
+
+criterion_group!(benches, bench_tree_loading, bench_flat_list, bench_wrapping);
+criterion_main!(benches);
@@ -8,6 +8,7 @@ REM   dt              - Open interactive TUI
 REM   dt path         - Navigate to path or bookmark
 REM   dt -            - Return to previous directory
 REM   dt --version    - Show version
+REM   dt --keys       - Print the effective keybindings
 REM   dt -bm ...      - Bookmark management
 REM   dt -v file.txt  - View file
 
@@ -60,6 +61,7 @@ REM Handle flags that should run dtree directly without cd
 if "%~1"=="-h" goto :passthrough
 if "%~1"=="--help" goto :passthrough
 if "%~1"=="--version" goto :passthrough
+if "%~1"=="--keys" goto :passthrough
 if "%~1"=="-bm" goto :passthrough
 
 REM Handle -v or --view flags
@@ -107,7 +107,7 @@ dt() {
 
   # If flags or bookmark commands are passed, just run dtree directly without cd
   case "$1" in
-    -h|--help|--version)
+    -h|--help|--version|--keys)
       command dtree "$@"
       return
       ;;
@@ -70,7 +70,7 @@ function dt {
     # Handle flags that should run dtree directly without cd
     if (`$Arguments.Count -gt 0) {
         switch (`$Arguments[0]) {
-            {`$_ -in "-h", "--help", "--version"} {
+            {`$_ -in "-h", "--help", "--version", "--keys"} {
                 & dtree.exe `$Arguments
                 return
             }
@@ -0,0 +1,263 @@
+use crate::config::{ActionSteps, KeybindingsConfig};
+use anyhow::{anyhow, bail, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::{BTreeMap, HashMap};
+
+/// One step of a custom action from the `[actions]` config section
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Built-in command (named like its `[keybindings]` entry), run by pressing its key
+    Keys(Vec<KeyEvent>),
+    /// `copy_path` - copy the selected entry's absolute path (without the copy menu)
+    CopyPath,
+    /// `cd_bookmark:NAME` - go to a bookmarked directory
+    CdBookmark(String),
+    /// `goto:PATH` - go to a path like the go-to prompt (`~` and relative paths work)
+    Goto(String),
+    /// `layout:NAME` - restore a named layout
+    Layout(String),
+    /// `plugin:NAME` - run a plugin command on the selected entry
+    Plugin(String),
+}
+
+impl Action {
+    pub fn parse(name: &str, keybindings: &KeybindingsConfig) -> Result<Self> {
+        if let Some((action, arg)) = name.split_once(':') {
+            if arg.is_empty() {
+                bail!("action '{}' needs an argument after ':'", name);
+            }
+            return match action {
+                "cd_bookmark" => Ok(Action::CdBookmark(arg.to_string())),
+                "goto" => Ok(Action::Goto(arg.to_string())),
+                "layout" => Ok(Action::Layout(arg.to_string())),
+                "plugin" => Ok(Action::Plugin(arg.to_string())),
+                _ => bail!("unknown action '{}'", name),
+            };
+        }
+
+        if name == "copy_path" {
+            return Ok(Action::CopyPath);
+        }
+        let keys = keybindings
+            .keys_for(name)
+            .ok_or_else(|| anyhow!("unknown action '{}'", name))?;
+        let key = keys
+            .iter()
+            .find_map(|key| parse_key(key))
+            .ok_or_else(|| anyhow!("action '{}' has no key bound", name))?;
+        Ok(Action::Keys(vec![KeyEvent::new(key, KeyModifiers::NONE)]))
+    }
+}
+
+/// A key with modifiers, e.g. `ctrl+g`, `alt+F5` or plain `x`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    fn new(code: KeyCode, mut modifiers: KeyModifiers) -> Self {
+        modifiers &= KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT;
+        let code = match code {
+            KeyCode::Char(c) => {
+                // Shift is part of the character ('G'), not a separate modifier
+                let c = if modifiers.contains(KeyModifiers::SHIFT) {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                };
+                modifiers.remove(KeyModifiers::SHIFT);
+                // Terminals differ in the case they report for Ctrl/Alt letters
+                if modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) {
+                    KeyCode::Char(c.to_ascii_lowercase())
+                } else {
+                    KeyCode::Char(c)
+                }
+            }
+            code => code,
+        };
+        Self { code, modifiers }
+    }
+
+    pub fn from_event(key: &KeyEvent) -> Self {
+        Self::new(key.code, key.modifiers)
+    }
+
+    /// Parse `ctrl+`, `alt+` and `shift+` prefixes followed by a key name
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text.trim();
+        while let Some((modifier, key)) = rest.split_once('+') {
+            // "+" and "ctrl++" bind the plus key itself
+            if key.is_empty() {
+                break;
+            }
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => bail!("unknown modifier '{}' in key '{}'", modifier, text),
+            };
+            rest = key;
+        }
+        let code = parse_key(rest).ok_or_else(|| anyhow!("unknown key '{}'", text))?;
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+/// Key code for a key name as written in `[keybindings]` ("x", "Space", "PageUp", "F5")
+pub fn parse_key(text: &str) -> Option<KeyCode> {
+    let mut chars = text.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+
+    let code = match text.to_ascii_lowercase().as_str() {
+        "space" => KeyCode::Char(' '),
+        "esc" => KeyCode::Esc,
+        "enter" => KeyCode::Enter,
+        "backspace" => KeyCode::Backspace,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "tab" => KeyCode::Tab,
+        "delete" => KeyCode::Delete,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        name => {
+            let number = name.strip_prefix('f')?.parse().ok()?;
+            if !(1..=12).contains(&number) {
+                return None;
+            }
+            KeyCode::F(number)
+        }
+    };
+    Some(code)
+}
+
+/// Custom actions by key: each key runs its steps in order
+///
+/// ```toml
+/// [actions]
+/// "ctrl+g" = ["copy_path", "open_file_manager"]
+/// "ctrl+w" = "cd_bookmark:work"
+/// ```
+#[derive(Debug, Default)]
+pub struct ActionRegistry {
+    bindings: HashMap<KeyChord, Vec<Action>>,
+}
+
+impl ActionRegistry {
+    /// Resolve the `[actions]` section
+    /// A binding with an unknown key or action is skipped; the errors are returned for display
+    pub fn new(
+        actions: &BTreeMap<String, ActionSteps>,
+        keybindings: &KeybindingsConfig,
+    ) -> (Self, Vec<String>) {
+        let mut registry = Self::default();
+        let mut errors = Vec::new();
+
+        for (key, steps) in actions {
+            let binding = KeyChord::parse(key).and_then(|chord| {
+                if steps.names().is_empty() {
+                    bail!("no actions listed");
+                }
+                let steps = steps
+                    .names()
+                    .iter()
+                    .map(|name| Action::parse(name, keybindings))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok((chord, steps))
+            });
+            match binding {
+                Ok((chord, steps)) => {
+                    registry.bindings.insert(chord, steps);
+                }
+                Err(e) => errors.push(format!("Action '{}': {}", key, e)),
+            }
+        }
+
+        (registry, errors)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+
+    /// Steps bound to a pressed key
+    pub fn get(&self, key: &KeyEvent) -> Option<&[Action]> {
+        self.bindings
+            .get(&KeyChord::from_event(key))
+            .map(Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bindings_and_actions() {
+        let actions: BTreeMap<String, ActionSteps> = toml::from_str(
+            r#"
+            "ctrl+g" = ["copy_path", "open_file_manager"]
+            "alt+W" = "cd_bookmark:work"
+            "F5" = ["goto:~/src", "plugin:lines", "toggle_files"]
+            "ctrl+x" = ["copy_path", "no_such_action"]
+            "hyper+x" = "quit"
+            "ctrl+e" = []
+            "#,
+        )
+        .unwrap();
+        let (registry, errors) = ActionRegistry::new(&actions, &KeybindingsConfig::default());
+
+        assert_eq!(
+            registry.get(&KeyEvent::new(KeyCode::Char('g'), KeyModifiers::CONTROL)),
+            Some(
+                &[
+                    Action::CopyPath,
+                    Action::Keys(vec![KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE)]),
+                ][..]
+            )
+        );
+        // Case of Ctrl/Alt letters doesn't matter, Shift alone is part of the character
+        assert_eq!(
+            registry.get(&KeyEvent::new(
+                KeyCode::Char('w'),
+                KeyModifiers::ALT | KeyModifiers::SHIFT
+            )),
+            Some(&[Action::CdBookmark("work".to_string())][..])
+        );
+        assert_eq!(
+            registry
+                .get(&KeyEvent::new(KeyCode::F(5), KeyModifiers::NONE))
+                .map(<[Action]>::len),
+            Some(3)
+        );
+        assert!(registry
+            .get(&KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE))
+            .is_none());
+
+        // Bad bindings are skipped and reported
+        assert!(registry
+            .get(&KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL))
+            .is_none());
+        assert_eq!(errors.len(), 3);
+        assert!(errors[0].contains("ctrl+e"));
+        assert!(errors[1].contains("no_such_action"));
+        assert!(errors[2].contains("hyper"));
+
+        assert_eq!(
+            KeyChord::parse("ctrl++").unwrap(),
+            KeyChord::new(KeyCode::Char('+'), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            KeyChord::parse("shift+g").unwrap(),
+            KeyChord::parse("G").unwrap()
+        );
+    }
+}
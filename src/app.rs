@@ -1,16 +1,48 @@
 use anyhow::Result;
-use crossterm::event::{KeyEvent, MouseEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent};
 use ratatui::Frame;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use crate::actions::{Action, ActionRegistry};
 use crate::bookmarks::Bookmarks;
-use crate::config::Config;
+use crate::config::{Config, Overrides};
+use crate::copy_path::CopyPathMenu;
 use crate::dir_size::DirSizeCache;
+use crate::drives::Drives;
 use crate::event_handler::EventHandler;
-use crate::file_viewer::FileViewer;
+use crate::file_ops::{FileOps, TransferMode};
+use crate::file_viewer::{FileViewer, PreviewLimits};
+use crate::filter_set::Excludes;
+use crate::git::GitignoreMode;
+use crate::goto::GotoPrompt;
+use crate::i18n::tr_with;
+use crate::idle::{IdleAction, IdleTimer};
+use crate::key_sequences::KeySequences;
+use crate::layout::{Layout, LayoutPicker};
 use crate::navigation::Navigation;
+use crate::picker::PickMode;
+use crate::plugins::{CommandPrompt, Plugins};
+use crate::preview_cache;
+use crate::profile;
+use crate::quick_filter::{self, FilterPicker};
 use crate::search::Search;
+use crate::tasks::{TaskKind, TaskManager};
+use crate::theme::color_mode::{self, ColorMode};
 use crate::ui::UI;
+use crate::vfs::Vfs;
+use crate::viewer_state::ViewStates;
+use crate::wakeup;
+
+/// How often auto_reload checks the shown file for changes
+const CHANGE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Shortest time between two frames; also how often running background work is looked at
+pub const FRAME_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Longest wait for input when nothing is running or due
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Main application state
 pub struct App {
@@ -20,38 +52,133 @@ pub struct App {
     ui: UI,
     event_handler: EventHandler,
     config: Config,
+    actions: ActionRegistry,
+    sequences: KeySequences,
+    pending_sequence: Option<(KeyEvent, Instant)>, // First key of a sequence, waiting
+    idle: IdleTimer,
     pub bookmarks: Bookmarks,
+    drives: Drives,
+    copy_menu: CopyPathMenu,
+    goto: GotoPrompt,
+    plugins: Plugins,              // Scripted hooks, custom previewers and commands
+    command_prompt: CommandPrompt, // Runs plugin commands (`@`)
     show_files: bool,
     show_files_before_help: bool,
     show_help: bool,
     fullscreen_viewer: bool,
     show_sizes: bool,
     dir_size_cache: DirSizeCache,
+    tasks: TaskManager,
+    file_ops: FileOps,
+    pick_mode: Option<PickMode>,
+    layout_file: Option<PathBuf>, // Where split positions are saved (remember_layout)
+    saved_layout: Option<Layout>,
+    color_mode: ColorMode,
     need_terminal_clear: bool,
-    needs_redraw: bool, // Dirty flag for selective rendering optimization
+    needs_redraw: bool,         // Dirty flag for selective rendering optimization
+    last_change_check: Instant, // When auto_reload last looked at the shown file
 }
 
 impl App {
+    #[cfg_attr(not(test), allow(dead_code))]
     pub fn new(start_path: PathBuf) -> Result<Self> {
-        // Load configuration from global config file
-        let config = Config::load()?;
-
-        let nav = Navigation::new(
-            start_path,
-            false,
-            config.behavior.show_hidden,
-            config.behavior.follow_symlinks,
-        )?;
+        Self::new_with(start_path, Config::load()?, &Overrides::default())
+    }
+
+    /// Create the app with the config loaded with the command line settings over it
+    /// (`Config::load_with`) and the user's bookmarks, plugins and layouts
+    pub fn new_with(start_path: PathBuf, config: Config, overrides: &Overrides) -> Result<Self> {
+        let mut app = Self::with_config(start_path, config, Bookmarks::new()?)?;
+        app.set_plugins(profile::phase("plugin load", Plugins::load));
+        // A split given for this run isn't replaced by (or saved as) the remembered one
+        if app.config.appearance.remember_layout && overrides.split_position.is_none() {
+            if let Some(path) = Layout::file() {
+                app.restore_layout(path);
+            }
+        }
+        if let Some(path) = LayoutPicker::file() {
+            app.load_named_layouts(path);
+        }
+        if app.config.behavior.remember_viewer_state {
+            if let Some(path) = ViewStates::file() {
+                app.file_viewer.view_states.load(path);
+            }
+        }
+        Ok(app)
+    }
+
+    /// Create an app with explicit config and bookmarks instead of the user's files
+    /// (headless driver, tests)
+    pub fn with_config(
+        start_path: PathBuf,
+        mut config: Config,
+        bookmarks: Bookmarks,
+    ) -> Result<Self> {
+        config.resolve_colors();
+
+        let mut startup_errors = Vec::new();
+        let exclude = Arc::new(Excludes::new(&config.search.exclude).unwrap_or_else(|e| {
+            startup_errors.push(e.to_string());
+            Excludes::default()
+        }));
+        let nav = profile::phase("tree build", || {
+            Navigation::builder(start_path)
+                .show_hidden(config.behavior.show_hidden)
+                .follow_symlinks(config.behavior.follow_symlinks)
+                .one_file_system(config.behavior.one_file_system)
+                .exclude(Arc::clone(&exclude))
+                .gitignore(GitignoreMode::from_config(&config.behavior.gitignore))
+                .build()
+        })?;
         let mut file_viewer = FileViewer::new();
-        let search = Search::new();
+        let mut search = Search::new();
         let mut ui = UI::new();
         let event_handler = EventHandler::new();
-        let bookmarks = Bookmarks::new()?;
+        let mut dir_size_cache = DirSizeCache::new();
 
         // Apply config to UI and file viewer
+        dir_size_cache.one_file_system = config.behavior.one_file_system;
+        dir_size_cache.exclude = exclude;
+        // Whatever the tree is read from, previews and sizes are read from too
+        file_viewer.vfs = Arc::clone(&nav.vfs);
+        dir_size_cache.vfs = Arc::clone(&nav.vfs);
         ui.split_position = config.appearance.split_position;
         file_viewer.show_line_numbers = config.appearance.show_line_numbers;
         file_viewer.wrap_lines = config.behavior.wrap_lines;
+        file_viewer.view_states.defaults = file_viewer.view_state();
+        file_viewer.limits = PreviewLimits::from_config(&config.behavior);
+        file_viewer.loader.debounce =
+            std::time::Duration::from_millis(config.behavior.preview_debounce_ms);
+        preview_cache::set_size(config.behavior.preview_cache_size);
+        search.max_results = config.behavior.max_search_results;
+
+        for note in &config.upgrade_notes {
+            ui.notifications.info(note.clone());
+        }
+        for error in startup_errors {
+            ui.notifications.warn(error);
+        }
+        let (actions, errors) = ActionRegistry::new(&config.actions, &config.keybindings);
+        for error in errors {
+            ui.notifications.warn(error);
+        }
+        let idle = IdleTimer::from_config(&config.behavior);
+        let (sequences, errors) = KeySequences::new(&config.keybindings);
+        for error in errors {
+            ui.notifications.warn(error);
+        }
+        let (filters, errors) = quick_filter::load_saved(&config.filters);
+        ui.filter_picker = FilterPicker::new(filters);
+        for error in errors {
+            ui.notifications.warn(error);
+        }
+
+        // The monochrome theme has no colors to send whatever the terminal supports
+        let color_mode = if config.appearance.theme.eq_ignore_ascii_case("monochrome") {
+            ColorMode::Monochrome
+        } else {
+            ColorMode::from_config(&config.appearance.color_mode)
+        };
 
         Ok(App {
             nav,
@@ -60,43 +187,274 @@ impl App {
             ui,
             event_handler,
             config,
+            actions,
+            sequences,
+            pending_sequence: None,
+            idle,
             bookmarks,
+            drives: Drives::new(),
+            copy_menu: CopyPathMenu::new(),
+            goto: GotoPrompt::new(),
+            plugins: Plugins::new(),
+            command_prompt: CommandPrompt::default(),
             show_files: false,
             show_files_before_help: false,
             show_help: false,
             fullscreen_viewer: false,
             show_sizes: false,
-            dir_size_cache: DirSizeCache::new(),
+            dir_size_cache,
+            tasks: TaskManager::new(),
+            file_ops: FileOps::new(),
+            pick_mode: None,
+            layout_file: None,
+            saved_layout: None,
+            color_mode,
             need_terminal_clear: false,
             needs_redraw: true, // Start with redraw needed to render initial frame
+            last_change_check: Instant::now(),
         })
     }
 
     pub fn handle_key(&mut self, key: KeyEvent) -> Result<Option<PathBuf>> {
-        let result = self.event_handler.handle_key(
+        if self.idle.input() {
+            self.mark_dirty();
+            return Ok(Some(PathBuf::new()));
+        }
+        let selected_before = self.selected_path();
+        let result = self.handle_sequence_key(key);
+        self.notify_selection_change(selected_before);
+
+        // Mark for redraw after handling input
+        self.mark_dirty();
+
+        result
+    }
+
+    /// Complete or start a two-key sequence, or run the key
+    fn handle_sequence_key(&mut self, key: KeyEvent) -> Result<Option<PathBuf>> {
+        if let Some((first, _)) = self.pending_sequence.take() {
+            self.ui.sequence_hints = None;
+            if key.code == KeyCode::Esc {
+                return Ok(Some(PathBuf::new()));
+            }
+            if let Some(command) = self.sequences.get(&first, &key) {
+                return self.dispatch_command(command);
+            }
+            // No sequence: the first key does what it does alone, then this one
+            let result = self.run_key(first)?;
+            if result != Some(PathBuf::new()) {
+                return Ok(result);
+            }
+        }
+
+        if !self.sequences.is_empty()
+            && !self.takes_keys_itself()
+            && self.sequences.starts_with(&key)
+        {
+            self.ui.sequence_hints = Some(self.sequences.hints(&key));
+            self.pending_sequence = Some((key, Instant::now()));
+            return Ok(Some(PathBuf::new()));
+        }
+        self.run_key(key)
+    }
+
+    /// Run the custom action bound to `key`, or pass the key on
+    fn run_key(&mut self, key: KeyEvent) -> Result<Option<PathBuf>> {
+        match self.custom_action(&key) {
+            Some(steps) => self.run_action(&steps),
+            None => self.dispatch_key(key),
+        }
+    }
+
+    /// The first key of a sequence that nothing followed in time does what it does alone
+    /// Returns what handling a key returns
+    pub fn poll_key_sequence(&mut self) -> Result<Option<PathBuf>> {
+        let timeout = Duration::from_millis(self.config.behavior.key_sequence_timeout_ms);
+        let Some((first, _)) = self
+            .pending_sequence
+            .take_if(|(_, pressed)| pressed.elapsed() >= timeout)
+        else {
+            return Ok(Some(PathBuf::new()));
+        };
+        self.ui.sequence_hints = None;
+        let selected_before = self.selected_path();
+        let result = self.run_key(first);
+        self.notify_selection_change(selected_before);
+        self.mark_dirty();
+        result
+    }
+
+    /// Pass a key to the event handler
+    fn dispatch_key(&mut self, key: KeyEvent) -> Result<Option<PathBuf>> {
+        self.sync_focus();
+        self.event_handler.handle_key(
             key,
             &mut self.nav,
             &mut self.file_viewer,
             &mut self.search,
             &mut self.bookmarks,
+            &mut self.drives,
+            &mut self.copy_menu,
+            &mut self.goto,
+            &mut self.command_prompt,
             &mut self.show_files,
             &mut self.show_files_before_help,
             &mut self.show_help,
             &mut self.fullscreen_viewer,
             &mut self.show_sizes,
             &mut self.dir_size_cache,
+            &mut self.tasks,
+            &mut self.file_ops,
             &mut self.need_terminal_clear,
-            &self.ui,
+            &mut self.ui,
+            &self.plugins,
             &self.config,
-        );
+            self.pick_mode,
+        )
+    }
 
-        // Mark for redraw after handling input
-        self.mark_dirty();
+    /// Run the command a completed key sequence is bound to
+    fn dispatch_command(&mut self, command: &'static str) -> Result<Option<PathBuf>> {
+        self.sync_focus();
+        self.event_handler.run_command(
+            command,
+            &mut self.nav,
+            &mut self.file_viewer,
+            &mut self.search,
+            &mut self.bookmarks,
+            &mut self.drives,
+            &mut self.copy_menu,
+            &mut self.goto,
+            &mut self.command_prompt,
+            &mut self.show_files,
+            &mut self.show_files_before_help,
+            &mut self.show_help,
+            &mut self.fullscreen_viewer,
+            &mut self.tasks,
+            &mut self.file_ops,
+            &mut self.need_terminal_clear,
+            &mut self.ui,
+            &self.plugins,
+            &self.config,
+            self.pick_mode,
+        )
+    }
 
-        result
+    /// Steps of the custom action bound to `key`
+    /// Actions apply in the tree; prompts, menus and the fullscreen viewer keep their keys
+    fn custom_action(&self, key: &KeyEvent) -> Option<Vec<Action>> {
+        if self.actions.is_empty() || self.fullscreen_viewer || self.takes_keys_itself() {
+            return None;
+        }
+        self.actions.get(key).map(<[Action]>::to_vec)
+    }
+
+    /// A prompt, menu or pending key takes keys as they are (no actions or sequences)
+    fn takes_keys_itself(&self) -> bool {
+        self.search.mode
+            || self.file_viewer.search_mode
+            || self.file_viewer.mark_key.is_some()
+            || self.file_ops.is_resolving_conflict()
+            || self.copy_menu.is_open
+            || self.goto.is_open
+            || self.ui.live_filter_open
+            || self.drives.is_selecting
+            || self.ui.projects.is_selecting
+            || self.ui.clipboard_history.is_open
+            || self.ui.marks_panel.is_open
+            || self.ui.filter_picker.is_open
+            || self.ui.layout_picker.is_open
+            || self.ui.export_menu.is_open
+            || self.bookmarks.is_selecting
+            || self.bookmarks.is_creating
+            || self.ui.notifications.show_log
+            || self.command_prompt.is_open
+            || self.event_handler.pending_z.is_some()
+    }
+
+    /// Run the steps of a custom action in order
+    /// A step that fails or exits dtree (editor, file manager, quit) ends the chain
+    fn run_action(&mut self, steps: &[Action]) -> Result<Option<PathBuf>> {
+        let stay = || Some(PathBuf::new());
+        for step in steps {
+            match step {
+                Action::Keys(keys) => {
+                    for key in keys {
+                        let result = self.dispatch_key(*key)?;
+                        if result != stay() {
+                            return Ok(result);
+                        }
+                    }
+                }
+                Action::CopyPath => {
+                    if let Some(path) = self.selected_path() {
+                        EventHandler::copy_path_text(&path.display().to_string(), &mut self.ui);
+                    }
+                }
+                Action::CdBookmark(name) => {
+                    let Some(bookmark) = self.bookmarks.get(name) else {
+                        self.ui
+                            .notifications
+                            .error(format!("No bookmark '{}'", name));
+                        break;
+                    };
+                    let path = match bookmark.resolve(&self.root_path(), &self.config.workspaces) {
+                        Ok(path) => path.to_string_lossy().to_string(),
+                        Err(e) => {
+                            self.ui.notifications.error(format!("{:#}", e));
+                            break;
+                        }
+                    };
+                    if !self.go_to(&path)? {
+                        break;
+                    }
+                }
+                Action::Goto(path) => {
+                    if !self.go_to(path)? {
+                        break;
+                    }
+                }
+                Action::Layout(name) => {
+                    if !self.ui.layout_picker.select_name(name) {
+                        self.ui
+                            .notifications
+                            .error(tr_with("No layout '{name}'", &[("name", name)]));
+                        break;
+                    }
+                    self.ui.layout_picker.open();
+                    self.dispatch_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))?;
+                }
+                Action::Plugin(name) => {
+                    let path = self.selected_path().unwrap_or_else(|| self.root_path());
+                    if !self.plugins.run_command(name, &path) {
+                        self.ui
+                            .notifications
+                            .error(format!("No plugin command '{}'", name));
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(stay())
+    }
+
+    /// Go to a path through the go-to prompt (reveals files like typing the path would)
+    /// Returns false if the path can't be opened; the prompt then stays open with the error
+    fn go_to(&mut self, path: &str) -> Result<bool> {
+        let root = self.root_path();
+        self.goto.open(&root);
+        self.goto.input = path.to_string();
+        self.dispatch_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))?;
+        Ok(!self.goto.is_open)
     }
 
     pub fn handle_mouse(&mut self, mouse: MouseEvent) -> Result<()> {
+        if self.idle.input() {
+            self.mark_dirty();
+            return Ok(());
+        }
+        let selected_before = self.selected_path();
+        self.sync_focus();
         let result = self.event_handler.handle_mouse(
             mouse,
             &mut self.nav,
@@ -104,11 +462,13 @@ impl App {
             &mut self.search,
             &mut self.bookmarks,
             &mut self.ui,
+            &self.plugins,
             &mut self.show_files,
             &mut self.show_help,
             self.fullscreen_viewer,
             &self.config,
         );
+        self.notify_selection_change(selected_before);
 
         // Mark for redraw after handling mouse input
         self.mark_dirty();
@@ -116,19 +476,60 @@ impl App {
         result
     }
 
+    /// The terminal got the focus back: files may have changed in the meantime
+    pub fn focus_gained(&mut self) {
+        self.ui.repo.invalidate();
+    }
+
+    /// Move the focus off panes that were closed since the last event
+    fn sync_focus(&mut self) {
+        self.ui.sync_focus(
+            self.show_files,
+            self.search.show_results,
+            self.bookmarks.is_selecting,
+        );
+    }
+
     pub fn render(&mut self, frame: &mut Frame) {
+        let _span = tracing::trace_span!("render").entered();
+        if self.idle.blanked {
+            self.ui.render_idle_screen(frame, &self.config);
+        } else {
+            self.render_ui(frame);
+        }
+        // Terminals without truecolor get RGB colors as palette indices
+        match self.color_mode {
+            ColorMode::TrueColor => {}
+            ColorMode::Indexed => color_mode::downsample(frame.buffer_mut()),
+            ColorMode::Monochrome => {
+                let background = Config::parse_color(Config::get_color(
+                    &self.config.appearance.colors.background_color,
+                ));
+                color_mode::strip(frame.buffer_mut(), background);
+            }
+        }
+    }
+
+    fn render_ui(&mut self, frame: &mut Frame) {
         self.ui.render(
             frame,
             &self.nav,
             &self.file_viewer,
             &self.search,
             &self.bookmarks,
+            &self.drives,
+            &self.copy_menu,
+            &self.goto,
+            &self.command_prompt,
             &self.config,
             self.show_files,
             self.show_help,
             self.fullscreen_viewer,
             self.show_sizes,
             &self.dir_size_cache,
+            &self.tasks,
+            &self.file_ops,
+            self.pick_mode,
         );
     }
 
@@ -142,6 +543,51 @@ impl App {
         updated
     }
 
+    /// Show the preview loaded in the background once it's ready
+    /// Returns true if it arrived and UI needs to be redrawn
+    pub fn poll_preview(&mut self) -> bool {
+        let updated = self.file_viewer.poll_loaded();
+        if updated {
+            self.mark_dirty();
+        }
+        updated
+    }
+
+    /// Wait for the preview loading in the background (headless driver)
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn finish_preview(&mut self) -> bool {
+        let updated = self.file_viewer.finish_loading();
+        if updated {
+            self.mark_dirty();
+        }
+        updated
+    }
+
+    /// Read the shown file again when it changed on disk (auto_reload)
+    /// Returns true if it was reloaded and UI needs to be redrawn
+    pub fn poll_file_change(&mut self) -> bool {
+        if !self.config.behavior.auto_reload
+            || !(self.show_files || self.fullscreen_viewer)
+            || self.last_change_check.elapsed() < CHANGE_CHECK_INTERVAL
+        {
+            return false;
+        }
+        self.last_change_check = Instant::now();
+        if !self.file_viewer.changed_on_disk() {
+            return false;
+        }
+        self.ui.repo.invalidate();
+        let _ = self.ui.reload_file_for_viewer(
+            &mut self.file_viewer,
+            self.config.behavior.max_file_lines,
+            self.fullscreen_viewer,
+            &self.config,
+            &self.plugins,
+        );
+        self.mark_dirty();
+        true
+    }
+
     /// Poll directory size calculation results from background thread
     /// Returns true if there were updates and UI needs to be redrawn
     pub fn poll_sizes(&mut self) -> bool {
@@ -152,6 +598,502 @@ impl App {
         updated
     }
 
+    /// Follow the git repository of the root (branch, uncommitted changes)
+    /// Returns true if the status changed and UI needs to be redrawn
+    pub fn poll_repo(&mut self) -> bool {
+        let root = self.nav.root().path.clone();
+        let updated = self.ui.repo.refresh(&root);
+        if updated {
+            self.mark_dirty();
+        }
+        updated
+    }
+
+    /// Pick up the projects detected for the projects panel
+    /// Returns true if they arrived and UI needs to be redrawn
+    pub fn poll_projects(&mut self) -> bool {
+        let updated = self.ui.projects.poll();
+        if updated {
+            self.mark_dirty();
+        }
+        updated
+    }
+
+    /// Show the comparison scanned in the background once it is done
+    /// Returns true if it was shown and UI needs to be redrawn
+    pub fn poll_comparison(&mut self) -> bool {
+        let entering = self.nav.comparison.is_none();
+        if !self.nav.poll_comparison() {
+            return false;
+        }
+        if entering {
+            EventHandler::comparison_shown(
+                &self.nav,
+                &mut self.file_viewer,
+                &mut self.ui,
+                &self.plugins,
+                &self.config,
+            );
+        }
+        self.mark_dirty();
+        true
+    }
+
+    /// Show the flat listing scanned in the background once it is done
+    /// Returns true if it was shown and UI needs to be redrawn
+    pub fn poll_flat_listing(&mut self) -> bool {
+        let entering = self.nav.flat_listing.is_none();
+        if !self.nav.poll_flat_listing() {
+            return false;
+        }
+        if entering && self.show_files {
+            EventHandler::flat_listing_shown(
+                &self.nav,
+                &mut self.file_viewer,
+                &mut self.ui,
+                &self.plugins,
+                &self.config,
+            );
+        }
+        self.mark_dirty();
+        true
+    }
+
+    /// Expand the directory listed in the background once its listing arrived
+    /// Returns true if it was expanded and UI needs to be redrawn
+    pub fn poll_listing(&mut self) -> bool {
+        let Some(error) = self.nav.poll_listing(self.show_files) else {
+            return false;
+        };
+        if let Some(message) = error {
+            self.ui.notifications.error(message);
+        }
+        self.mark_dirty();
+        true
+    }
+
+    /// Hide or dim the entries git ignores once they were listed in the background
+    /// Returns true if they arrived and UI needs to be redrawn
+    pub fn poll_ignored(&mut self) -> bool {
+        let updated = self.nav.poll_ignored();
+        if updated {
+            self.mark_dirty();
+        }
+        updated
+    }
+
+    /// Sync the task manager with running background jobs
+    /// Returns true if the task list changed and UI needs to be redrawn
+    pub fn poll_tasks(&mut self) -> bool {
+        let mut updated = false;
+
+        // Deep search
+        let search_task = self.tasks.find_kind(TaskKind::Search);
+        if self.search.is_searching {
+            let id = match search_task {
+                Some(id) => id,
+                None => {
+                    updated = true;
+                    self.tasks.start(TaskKind::Search, self.search.title())
+                }
+            };
+            let detail = format!("{} dirs scanned", self.search.scanned_count);
+            updated |= self.tasks.update(id, detail, None);
+        } else if let Some(id) = search_task {
+            self.tasks.finish(id);
+            updated = true;
+        }
+
+        // Directory size calculation
+        let sizes_task = self.tasks.find_kind(TaskKind::DirSizes);
+        let pending = self.dir_size_cache.pending_count();
+        if pending > 0 {
+            let id = match sizes_task {
+                Some(id) => id,
+                None => {
+                    updated = true;
+                    self.tasks.start(TaskKind::DirSizes, "Directory sizes")
+                }
+            };
+            updated |= self.tasks.update(id, format!("{} pending", pending), None);
+        } else if let Some(id) = sizes_task {
+            self.tasks.finish(id);
+            updated = true;
+        }
+
+        // Comparison
+        let compare_task = self.tasks.find_kind(TaskKind::Compare);
+        if let Some(pending) = &self.nav.pending_comparison {
+            if compare_task.is_none() {
+                updated = true;
+                self.tasks.start(
+                    TaskKind::Compare,
+                    format!("Compare with {}", pending.right.display()),
+                );
+            }
+        } else if let Some(id) = compare_task {
+            self.tasks.finish(id);
+            updated = true;
+        }
+
+        // Directory listing
+        let listing_task = self.tasks.find_kind(TaskKind::Listing);
+        if let Some(pending) = &self.nav.pending_listing {
+            if listing_task.is_none() {
+                updated = true;
+                self.tasks.start(
+                    TaskKind::Listing,
+                    format!("List {}", pending.path.display()),
+                );
+            }
+        } else if let Some(id) = listing_task {
+            self.tasks.finish(id);
+            updated = true;
+        }
+
+        // Flat listing
+        let flat_task = self.tasks.find_kind(TaskKind::FlatListing);
+        if let Some(pending) = &self.nav.pending_flat_listing {
+            if flat_task.is_none() {
+                updated = true;
+                self.tasks.start(
+                    TaskKind::FlatListing,
+                    format!("List files under {}", pending.root.display()),
+                );
+            }
+        } else if let Some(id) = flat_task {
+            self.tasks.finish(id);
+            updated = true;
+        }
+
+        // File copy/move
+        let transfer_task = self.tasks.find_kind(TaskKind::FileTransfer);
+        if let Some(job) = &self.file_ops.job {
+            let id = match transfer_task {
+                Some(id) => id,
+                None => {
+                    updated = true;
+                    let verb = match job.mode {
+                        TransferMode::Copy => "Copy",
+                        TransferMode::Move => "Move",
+                    };
+                    self.tasks.start(
+                        TaskKind::FileTransfer,
+                        format!("{} {} item(s)", verb, job.total),
+                    )
+                }
+            };
+            let detail = format!("{}/{} done", job.done, job.total);
+            updated |= self
+                .tasks
+                .update(id, detail, Some((job.done as u64, job.total as u64)));
+        } else if let Some(id) = transfer_task {
+            self.tasks.finish(id);
+            updated = true;
+        }
+
+        if updated {
+            self.mark_dirty();
+        }
+        updated
+    }
+
+    /// Toggle the sizes for a `z` that no z, t or b followed in time
+    /// Returns true if the UI needs to be redrawn
+    pub fn poll_pending_z(&mut self) -> bool {
+        let updated = self.event_handler.poll_pending_z(
+            self.fullscreen_viewer,
+            &self.nav,
+            &mut self.dir_size_cache,
+            &mut self.show_sizes,
+        );
+        if updated {
+            self.mark_dirty();
+        }
+        updated
+    }
+
+    /// Blank the screen or exit after `idle_timeout_minutes` without input
+    /// Returns what handling a key returns (None: exit)
+    pub fn poll_idle(&mut self) -> Option<PathBuf> {
+        match self.idle.poll() {
+            Some(IdleAction::Exit) => {
+                tracing::info!(
+                    "exiting after {} idle minutes",
+                    self.config.behavior.idle_timeout_minutes
+                );
+                None
+            }
+            Some(IdleAction::Blank) => {
+                self.mark_dirty();
+                Some(PathBuf::new())
+            }
+            None => Some(PathBuf::new()),
+        }
+    }
+
+    /// Expire toasts whose time is up and show messages from plugins
+    /// Returns true if the UI needs to be redrawn
+    pub fn poll_notifications(&mut self) -> bool {
+        for (level, message) in self.plugins.take_messages() {
+            self.ui.notifications.push(level, message);
+        }
+        let updated = self.ui.notifications.poll();
+        if updated {
+            self.mark_dirty();
+        }
+        updated
+    }
+
+    /// Poll the running file copy/move
+    /// Returns true if there were updates and UI needs to be redrawn
+    pub fn poll_file_ops(&mut self) -> bool {
+        let (updated, report) = self.file_ops.poll();
+
+        if let Some(report) = report {
+            // Show the result in the tree, revealing the first pasted entry
+            let _ = self.nav.reload_tree(self.show_files);
+            self.ui.repo.invalidate();
+            if !self.nav.flat_list.is_empty() && self.nav.selected >= self.nav.flat_list.len() {
+                self.nav.selected = self.nav.flat_list.len() - 1;
+            }
+            if let Some(target) = &report.first_target {
+                let _ = self.nav.expand_path_to_node(target, self.show_files);
+            }
+
+            // Report the result as a toast; each failure is kept in the message log too
+            let verb = match report.mode {
+                TransferMode::Copy => "Copy",
+                TransferMode::Move => "Move",
+            };
+            let summary = format!(
+                "{} finished: {}/{} item(s){}",
+                verb,
+                report.done,
+                report.total,
+                if report.cancelled { " (cancelled)" } else { "" }
+            );
+            if report.errors.is_empty() {
+                if report.cancelled {
+                    self.ui.notifications.warn(summary);
+                } else {
+                    self.ui.notifications.info(summary);
+                }
+            } else {
+                for error in report.errors {
+                    self.ui.notifications.error(error);
+                }
+                self.ui.notifications.error(summary);
+            }
+        }
+
+        if updated {
+            self.mark_dirty();
+        }
+        updated
+    }
+
+    /// Replace the tree with a virtual one built from a path list (`--stdin`)
+    pub fn set_virtual_tree(&mut self, paths: &[PathBuf]) -> Result<()> {
+        let mut nav = Navigation::from_paths(
+            paths,
+            self.config.behavior.show_hidden,
+            self.config.behavior.follow_symlinks,
+        )?;
+        nav.one_file_system = self.config.behavior.one_file_system;
+        nav.exclude = Arc::clone(&self.nav.exclude);
+        nav.set_gitignore(GitignoreMode::from_config(&self.config.behavior.gitignore));
+        self.nav = nav;
+
+        // Listed files are the point of the list - show them with the preview
+        self.show_files = true;
+        Ok(())
+    }
+
+    /// Replace the tree with one read from `vfs`, starting at `root` (`--s3`)
+    #[cfg_attr(not(feature = "s3"), allow(dead_code))]
+    pub fn set_vfs_tree(&mut self, vfs: Vfs, root: PathBuf) -> Result<()> {
+        self.nav = Navigation::builder(root)
+            .vfs(Arc::clone(&vfs))
+            .show_files(true)
+            .show_hidden(self.config.behavior.show_hidden)
+            .follow_symlinks(self.config.behavior.follow_symlinks)
+            .one_file_system(self.config.behavior.one_file_system)
+            .exclude(Arc::clone(&self.nav.exclude))
+            .build()?;
+        self.file_viewer.vfs = Arc::clone(&vfs);
+        // The next size calculation starts a worker reading from `vfs`
+        self.dir_size_cache.clear();
+        self.dir_size_cache.vfs = vfs;
+
+        // Objects are what a bucket is about - show them with the preview
+        self.show_files = true;
+        Ok(())
+    }
+
+    /// Show the root compared with `right` instead of the tree (`--compare`)
+    pub fn set_comparison(&mut self, right: &std::path::Path) -> Result<()> {
+        self.show_files = true;
+        self.nav.reload_tree(true)?;
+        self.nav.enter_comparison(right)
+    }
+
+    /// Directory currently shown as the tree root
+    pub fn root_path(&self) -> PathBuf {
+        self.nav.root().path.clone()
+    }
+
+    /// Terminal window title for the current root (`terminal_title`), None when disabled
+    pub fn terminal_title(&self) -> Option<String> {
+        self.config
+            .appearance
+            .terminal_title
+            .then(|| format!("dtree \u{2013} {}", self.root_path().display()))
+    }
+
+    /// Print the marked paths instead of the cd target when exiting with `q` (`--print-marked`)
+    pub fn set_print_marked(&mut self) {
+        self.config.behavior.print_marked_on_exit = true;
+    }
+
+    /// Keep navigation inside the current root directory (`--restrict`)
+    pub fn set_restricted(&mut self) -> Result<()> {
+        self.nav.restrict_to_root(self.show_files)
+    }
+
+    /// Turn the TUI into a picker (`--pick`): Enter returns the selection
+    pub fn set_pick_mode(&mut self, mode: PickMode) -> Result<()> {
+        self.pick_mode = Some(mode);
+        if mode.shows_files() {
+            self.show_files = true;
+            self.nav.reload_tree(true)?;
+        }
+        Ok(())
+    }
+
+    /// Start with files shown in the tree and the file viewer (`--show-files`)
+    pub fn set_show_files(&mut self) -> Result<()> {
+        self.show_files = true;
+        self.nav.reload_tree(true)
+    }
+
+    /// Start with directory sizes shown (`--sizes`)
+    pub fn set_show_sizes(&mut self) {
+        EventHandler::set_show_sizes(
+            true,
+            &self.nav,
+            &mut self.dir_size_cache,
+            &mut self.show_sizes,
+        );
+    }
+
+    /// Start with `path` selected, its parent directories expanded; files are shown
+    /// when it is a file (`--select`)
+    pub fn set_selection(&mut self, path: &std::path::Path) -> Result<()> {
+        if !path.is_dir() && !self.show_files {
+            self.set_show_files()?;
+        }
+        self.nav.expand_path_to_node(path, self.show_files)?;
+        if self.selected_path().as_deref() != Some(path) {
+            self.ui.notifications.warn(format!(
+                "--select: {} is not in the tree (hidden or ignored?)",
+                path.display()
+            ));
+        } else if self.show_files {
+            let _ = self.ui.load_file_for_viewer(
+                &mut self.file_viewer,
+                path,
+                self.config.behavior.max_file_lines,
+                false,
+                &self.config,
+                &self.plugins,
+            );
+        }
+        Ok(())
+    }
+
+    /// Start in the fullscreen viewer on the selected file, or the first file in the
+    /// tree when a directory is selected (`--fullscreen`)
+    pub fn set_fullscreen(&mut self) -> Result<()> {
+        self.set_show_files()?;
+        let selected = self.nav.get_selected_node().filter(|node| !node.is_dir);
+        let file = selected.or_else(|| self.nav.visible_nodes().find(|node| !node.is_dir));
+        match file {
+            Some(node) => {
+                let path = node.path.clone();
+                self.set_fullscreen_viewer(&path)
+            }
+            None => {
+                self.ui.notifications.warn("--fullscreen: no file to show");
+                Ok(())
+            }
+        }
+    }
+
+    /// Replace the loaded plugins (App::new loads them from the plugin directory)
+    pub fn set_plugins(&mut self, plugins: Plugins) {
+        self.plugins = plugins;
+    }
+
+    /// Apply the split positions saved in `path` (if any) and save changes there on exit
+    /// (App::new uses the state directory with `remember_layout`)
+    pub fn restore_layout(&mut self, path: PathBuf) {
+        if let Some(layout) = Layout::load(&path) {
+            self.ui.adjust_split(layout.split_position);
+            self.ui
+                .adjust_bottom_split(layout.bottom_panel_split_position);
+        }
+        self.saved_layout = Some(self.layout());
+        self.layout_file = Some(path);
+    }
+
+    /// Save the split positions if they changed since they were restored
+    pub fn save_layout(&mut self) -> Result<()> {
+        let Some(path) = &self.layout_file else {
+            return Ok(());
+        };
+        let layout = self.layout();
+        if self.saved_layout != Some(layout) {
+            layout.save(path)?;
+            self.saved_layout = Some(layout);
+        }
+        Ok(())
+    }
+
+    /// Read the named layouts saved in `path` (App::new uses the state directory)
+    pub fn load_named_layouts(&mut self, path: PathBuf) {
+        self.ui.layout_picker.load(path);
+    }
+
+    /// Save how the viewed files were left (with `remember_viewer_state`)
+    pub fn save_view_states(&mut self) -> Result<()> {
+        self.file_viewer.remember_view_state();
+        self.file_viewer.view_states.save()
+    }
+
+    fn layout(&self) -> Layout {
+        Layout {
+            split_position: self.ui.split_position,
+            bottom_panel_split_position: self.ui.bottom_panel_split_position,
+        }
+    }
+
+    fn selected_path(&self) -> Option<PathBuf> {
+        self.nav.get_selected_node().map(|node| node.path.clone())
+    }
+
+    /// Run the plugins' on_select hook if the selection moved
+    fn notify_selection_change(&self, selected_before: Option<PathBuf>) {
+        if self.plugins.is_empty() {
+            return;
+        }
+        if let Some(path) = self.selected_path() {
+            if selected_before.as_ref() != Some(&path) {
+                self.plugins.on_select(&path);
+            }
+        }
+    }
+
     /// Set fullscreen viewer mode and load the specified file
     pub fn set_fullscreen_viewer(&mut self, file_path: &std::path::Path) -> Result<()> {
         self.fullscreen_viewer = true;
@@ -165,7 +1107,7 @@ impl App {
             .nav
             .flat_list
             .iter()
-            .position(|node| node.borrow().path == file_path)
+            .position(|&id| self.nav.tree[id].path == file_path)
         {
             self.nav.selected = index;
         }
@@ -197,7 +1139,7 @@ impl App {
     /// Reload file in fullscreen mode with correct terminal width
     pub fn reload_fullscreen_file(&mut self, terminal_width: u16) -> Result<()> {
         if let Some(node) = self.nav.get_selected_node() {
-            let path = node.borrow().path.clone();
+            let path = node.path.clone();
 
             // Update UI terminal width so load_file_for_viewer can use it
             self.ui.terminal_width = terminal_width;
@@ -209,6 +1151,7 @@ impl App {
                 self.config.behavior.max_file_lines,
                 true, // fullscreen
                 &self.config,
+                &self.plugins,
             )?;
 
             // Mark for redraw after reloading file
@@ -217,6 +1160,39 @@ impl App {
         Ok(())
     }
 
+    /// Pick up what an external program (editor, file manager) changed after dtree
+    /// resumed from it: new files in the tree, new content in the viewer
+    pub fn resume_after_program(&mut self, error: Option<String>) {
+        let _ = self.nav.reload_tree(self.show_files);
+        self.ui.repo.invalidate();
+        if self.show_files {
+            if let Some(path) = self.selected_path() {
+                let scroll = self.file_viewer.scroll;
+                let _ = self.ui.load_file_for_viewer(
+                    &mut self.file_viewer,
+                    &path,
+                    self.config.behavior.max_file_lines,
+                    self.fullscreen_viewer,
+                    &self.config,
+                    &self.plugins,
+                );
+                self.file_viewer.scroll =
+                    scroll.min(self.file_viewer.content.len().saturating_sub(1));
+            }
+        }
+        if let Some(error) = error {
+            self.ui.notifications.error(error);
+        }
+        self.need_terminal_clear = true;
+        self.mark_dirty();
+    }
+
+    /// Unmark files an external program was opened on
+    pub fn unmark(&mut self, paths: &[PathBuf]) {
+        EventHandler::unmark(paths, &mut self.nav, &mut self.search);
+        self.mark_dirty();
+    }
+
     /// Check if terminal needs to be cleared, and reset the flag
     pub fn should_clear_terminal(&mut self) -> bool {
         let result = self.need_terminal_clear;
@@ -242,6 +1218,47 @@ impl App {
     pub fn needs_redraw(&self) -> bool {
         self.needs_redraw
     }
+
+    /// How long the event loop may wait for input before the poll_* functions have work:
+    /// a frame while something runs in the background, else until the next timer is due
+    pub fn poll_timeout(&self) -> Duration {
+        // Search, size, comparison, listing, flat listing and git workers wake the loop themselves where they can
+        let workers_busy = self.search.is_searching
+            || self.dir_size_cache.pending_count() > 0
+            || self.nav.pending_comparison.is_some()
+            || self.nav.pending_listing.is_some()
+            || self.nav.pending_flat_listing.is_some()
+            || self.nav.pending_ignored.is_some();
+        let busy = (workers_busy && !wakeup::SUPPORTED)
+            || self.search.is_queued()
+            || self.file_viewer.is_loading()
+            || self.file_ops.job.is_some()
+            || self.ui.projects.is_loading()
+            || self.ui.repo.is_checking();
+        if busy {
+            return FRAME_INTERVAL;
+        }
+
+        let key_sequence_timeout =
+            Duration::from_millis(self.config.behavior.key_sequence_timeout_ms);
+        let file_change = (self.config.behavior.auto_reload
+            && (self.show_files || self.fullscreen_viewer))
+            .then(|| self.last_change_check + CHANGE_CHECK_INTERVAL);
+        let deadlines = [
+            self.pending_sequence
+                .map(|(_, pressed)| pressed + key_sequence_timeout),
+            self.event_handler.pending_z_deadline(),
+            self.idle.deadline(),
+            self.ui.notifications.next_expiry(),
+            file_change,
+        ];
+        let now = Instant::now();
+        deadlines
+            .into_iter()
+            .flatten()
+            .map(|deadline| deadline.saturating_duration_since(now))
+            .fold(IDLE_POLL_INTERVAL, Duration::min)
+    }
 }
 
 #[cfg(test)]
@@ -347,4 +1364,135 @@ mod tests {
 
         std::fs::remove_dir_all(&temp_dir).ok();
     }
+
+    #[test]
+    fn test_selection_preview_loads_in_background() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(temp_dir.path().join("a.txt"), "alpha\n").unwrap();
+        std::fs::write(temp_dir.path().join("b.txt"), "beta\n").unwrap();
+        let mut app = App::new(temp_dir.path().to_path_buf()).unwrap();
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+
+        let _ = app.handle_key(key('s'));
+        let _ = app.handle_key(key('j'));
+        // The old preview stays until the new one is loaded
+        assert_ne!(app.file_viewer.current_path, temp_dir.path().join("a.txt"));
+        assert!(app.finish_preview());
+        assert_eq!(app.file_viewer.content, ["alpha"]);
+
+        // Moving on before the load starts replaces it
+        let _ = app.handle_key(key('j'));
+        let _ = app.handle_key(key('k'));
+        assert!(app.finish_preview());
+        assert_eq!(app.file_viewer.current_path, temp_dir.path().join("a.txt"));
+        assert!(!app.finish_preview());
+    }
+
+    #[test]
+    fn test_startup_flags() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("root");
+        std::fs::create_dir_all(root.join("docs")).unwrap();
+        std::fs::write(root.join("notes.txt"), "first\n").unwrap();
+        let bookmarks = Bookmarks::open(temp_dir.path().join("bookmarks.json")).unwrap();
+        let mut app = App::with_config(root, Config::default(), bookmarks).unwrap();
+
+        app.set_show_sizes();
+        assert!(app.show_sizes);
+        // The root is selected: the first file is shown
+        app.set_fullscreen().unwrap();
+        assert!(app.show_files && app.fullscreen_viewer);
+        assert_eq!(app.file_viewer.content, ["first"]);
+    }
+
+    #[test]
+    fn test_config_upgrade_is_notified() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        std::fs::write(&path, "[appearance]\nwrap_lines = false\n").unwrap();
+        let config = Config::load_from(&path, &Overrides::default()).unwrap();
+        let bookmarks = Bookmarks::open(temp_dir.path().join("bookmarks.json")).unwrap();
+        let app = App::with_config(temp_dir.path().to_path_buf(), config, bookmarks).unwrap();
+
+        let toasts: Vec<&str> = app
+            .ui
+            .notifications
+            .toasts()
+            .into_iter()
+            .map(|toast| toast.message.as_str())
+            .collect();
+        assert_eq!(
+            toasts[0],
+            "appearance.wrap_lines is now behavior.wrap_lines"
+        );
+        assert!(toasts[1].starts_with("Config upgraded to version 2"));
+    }
+
+    #[test]
+    fn test_start_with_selection() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("root");
+        let file = root.join("src/ui/tree.rs");
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        std::fs::write(&file, "fn tree() {}\n").unwrap();
+        let bookmarks = Bookmarks::open(temp_dir.path().join("bookmarks.json")).unwrap();
+        let mut app = App::with_config(root, Config::default(), bookmarks).unwrap();
+
+        app.set_selection(&file).unwrap();
+        assert!(app.show_files);
+        assert_eq!(app.selected_path(), Some(file));
+        assert_eq!(app.file_viewer.content, ["fn tree() {}"]);
+    }
+
+    #[test]
+    fn test_auto_reload_picks_up_changes() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("app.log");
+        std::fs::write(&path, "one\n").unwrap();
+        let mut config = Config::default();
+        config.behavior.auto_reload = true;
+        let bookmarks = Bookmarks::open(temp_dir.path().join("bookmarks.json")).unwrap();
+        let mut app = App::with_config(temp_dir.path().to_path_buf(), config, bookmarks).unwrap();
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+
+        let _ = app.handle_key(key('s'));
+        let _ = app.handle_key(key('j'));
+        app.finish_preview();
+        assert_eq!(app.file_viewer.content, ["one"]);
+
+        std::fs::write(&path, "one\ntwo\n").unwrap();
+        // The file is looked at once a second
+        assert!(!app.poll_file_change());
+        app.last_change_check -= CHANGE_CHECK_INTERVAL;
+        assert!(app.poll_file_change());
+        assert_eq!(app.file_viewer.content, ["one", "two"]);
+        assert!(!app.poll_file_change());
+    }
+
+    #[test]
+    fn test_poll_timeout_follows_timers_and_work() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let bookmarks = Bookmarks::open(temp_dir.path().join("bookmarks.json")).unwrap();
+        let mut app =
+            App::with_config(temp_dir.path().to_path_buf(), Config::default(), bookmarks).unwrap();
+        let _ = app.poll_repo();
+        assert_eq!(app.poll_timeout(), IDLE_POLL_INTERVAL);
+
+        // A pending z is toggled in time
+        let _ = app.handle_key(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::NONE));
+        let timeout = app.poll_timeout();
+        assert!(timeout > FRAME_INTERVAL && timeout <= Duration::from_millis(500));
+
+        // Running work is looked at every frame, unless it wakes the loop itself
+        app.event_handler.pending_z = None;
+        app.search.is_searching = true;
+        let expected = if wakeup::SUPPORTED {
+            IDLE_POLL_INTERVAL
+        } else {
+            FRAME_INTERVAL
+        };
+        assert_eq!(app.poll_timeout(), expected);
+        app.ui.projects.enter_selection_mode(temp_dir.path());
+        assert_eq!(app.poll_timeout(), FRAME_INTERVAL);
+    }
 }
@@ -1,8 +1,11 @@
+use crate::completion::{self, Completion};
 use anyhow::{Context, Result};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// A single bookmark entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,6 +49,49 @@ impl Bookmark {
 
         Ok(())
     }
+
+    /// Directory the bookmark points to; a workspace-relative path is resolved from `from`
+    pub fn resolve(&self, from: &Path, workspaces: &BTreeMap<String, String>) -> Result<PathBuf> {
+        resolve_path(&self.path, from, workspaces)
+    }
+}
+
+/// Resolve a workspace-relative path: `@repo/src` is below the git repository around
+/// `from`, `@NAME/src` below the `[workspaces]` entry NAME; other paths stay as they are
+pub fn resolve_path(
+    path: &Path,
+    from: &Path,
+    workspaces: &BTreeMap<String, String>,
+) -> Result<PathBuf> {
+    let Some(rest) = path.to_str().and_then(|text| text.strip_prefix('@')) else {
+        return Ok(path.to_path_buf());
+    };
+    let (name, below) = rest.split_once(['/', '\\']).unwrap_or((rest, ""));
+
+    let base = if name == "repo" {
+        crate::git::find_top_level(from)
+            .with_context(|| format!("@repo: {} is not in a git repository", from.display()))?
+    } else {
+        let base = workspaces.get(name).with_context(|| {
+            format!(
+                "Unknown workspace '@{}' (add it to [workspaces] in config.toml)",
+                name
+            )
+        })?;
+        crate::goto::expand_path(base, from)
+    };
+    Ok(if below.is_empty() {
+        base
+    } else {
+        base.join(below)
+    })
+}
+
+/// Characters of a bookmark's key and name matched by the filter (for highlighting)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FilterMatch {
+    pub key: Vec<usize>,
+    pub name: Vec<usize>,
 }
 
 /// Manages persistent bookmarks
@@ -58,9 +104,11 @@ pub struct Bookmarks {
     pub input_buffer: String,
     pub selected_index: usize,                 // Current selection in list
     pub filter_mode: bool,                     // True = filter/search mode, False = navigation mode
-    filtered_keys: Vec<String>,                // Cached filtered bookmark keys
+    filtered_keys: Vec<(String, FilterMatch)>, // Cached filtered bookmark keys, best match first
     pub scroll_offset: usize,                  // Scroll offset for bookmark list in creation mode
     pub pending_deletion_index: Option<usize>, // Index of bookmark marked for deletion
+    pub completion: Completion,                // Tab completion of names in creation mode
+    pub compare_mode: bool,                    // Selecting the directory to compare the root with
 }
 
 impl Bookmarks {
@@ -73,8 +121,11 @@ impl Bookmarks {
         // Ensure config directory exists
         fs::create_dir_all(&config_dir)?;
 
-        let file_path = config_dir.join("bookmarks.json");
+        Self::open(config_dir.join("bookmarks.json"))
+    }
 
+    /// Load bookmarks from a specific file (created if missing)
+    pub fn open(file_path: PathBuf) -> Result<Self> {
         let mut bookmarks = Self {
             bookmarks: HashMap::new(),
             file_path,
@@ -86,6 +137,8 @@ impl Bookmarks {
             filtered_keys: Vec::new(),
             scroll_offset: 0,
             pending_deletion_index: None,
+            completion: Completion::new(),
+            compare_mode: false,
         };
 
         // Try to load, but don't fail if JSON is corrupted
@@ -211,9 +264,16 @@ impl Bookmarks {
         self.selected_index = 0;
         self.filter_mode = false;
         self.pending_deletion_index = None;
+        self.compare_mode = false;
         self.update_filtered_list();
     }
 
+    /// Enter bookmark selection mode to pick the directory to compare with
+    pub fn enter_compare_mode(&mut self) {
+        self.enter_selection_mode();
+        self.compare_mode = true;
+    }
+
     /// Exit bookmark selection mode
     pub fn exit_selection_mode(&mut self) {
         self.is_selecting = false;
@@ -222,6 +282,7 @@ impl Bookmarks {
         self.filter_mode = false;
         self.filtered_keys.clear();
         self.pending_deletion_index = None;
+        self.compare_mode = false;
     }
 
     /// Enter bookmark creation mode (after pressing 'm')
@@ -232,6 +293,7 @@ impl Bookmarks {
         self.selected_index = 0;
         self.filter_mode = false;
         self.scroll_offset = 0;
+        self.completion.reset();
     }
 
     /// Exit bookmark creation mode
@@ -239,6 +301,22 @@ impl Bookmarks {
         self.is_creating = false;
         self.input_buffer.clear();
         self.scroll_offset = 0;
+        self.completion.reset();
+    }
+
+    /// Tab/Shift+Tab in creation mode: complete an existing bookmark name (to update it)
+    pub fn complete_name(&mut self, forward: bool) {
+        let keys = &self.bookmarks;
+        let source =
+            |input: &str| completion::name_candidates(input, keys.keys().map(|k| k.as_str()));
+        let completed = if forward {
+            self.completion.next(&self.input_buffer, source)
+        } else {
+            self.completion.prev(&self.input_buffer, source)
+        };
+        if let Some(completed) = completed {
+            self.input_buffer = completed;
+        }
     }
 
     /// Scroll bookmark list up in creation mode
@@ -258,6 +336,7 @@ impl Bookmarks {
     /// Add character to input buffer
     pub fn add_char(&mut self, c: char) {
         self.input_buffer.push(c);
+        self.completion.reset();
         // Update filtered list if in filter mode
         if self.filter_mode {
             self.update_filtered_list();
@@ -274,6 +353,7 @@ impl Bookmarks {
     /// Remove last character from input buffer
     pub fn backspace(&mut self) {
         self.input_buffer.pop();
+        self.completion.reset();
         // Update filtered list if in filter mode
         if self.filter_mode {
             self.update_filtered_list();
@@ -306,41 +386,57 @@ impl Bookmarks {
     }
 
     /// Update filtered list based on input buffer
+    /// Keys and names are matched fuzzily, like the fuzzy tree search, and the best
+    /// matches come first (bookmarks matching equally well stay sorted by key)
     fn update_filtered_list(&mut self) {
         let query = self.input_buffer.to_lowercase();
-
-        if query.is_empty() {
-            // No filter - show all bookmarks
-            self.filtered_keys = self.list().iter().map(|b| b.key.clone()).collect();
-        } else {
-            // Filter bookmarks by key or name
-            self.filtered_keys = self
-                .list()
-                .iter()
-                .filter(|b| {
-                    let key_match = b.key.to_lowercase().contains(&query);
-                    let name_match = b
-                        .name
-                        .as_ref()
-                        .map(|n| n.to_lowercase().contains(&query))
-                        .unwrap_or(false);
-                    key_match || name_match
-                })
-                .map(|b| b.key.clone())
-                .collect();
-        }
+        let matcher = SkimMatcherV2::default();
+
+        let mut matched: Vec<(i64, String, FilterMatch)> = self
+            .list()
+            .iter()
+            .filter_map(|b| {
+                if query.is_empty() {
+                    // No filter - show all bookmarks
+                    return Some((0, b.key.clone(), FilterMatch::default()));
+                }
+                let key = matcher.fuzzy_indices(&b.key.to_lowercase(), &query);
+                let name = b
+                    .name
+                    .as_ref()
+                    .and_then(|n| matcher.fuzzy_indices(&n.to_lowercase(), &query));
+                let score = key.iter().chain(&name).map(|(score, _)| *score).max()?;
+                let indices = |matched: Option<(i64, Vec<usize>)>| {
+                    matched.map(|(_, indices)| indices).unwrap_or_default()
+                };
+                let filter_match = FilterMatch {
+                    key: indices(key),
+                    name: indices(name),
+                };
+                Some((score, b.key.clone(), filter_match))
+            })
+            .collect();
+        matched.sort_by_key(|(score, _, _)| std::cmp::Reverse(*score));
+
+        self.filtered_keys = matched
+            .into_iter()
+            .map(|(_, key, filter_match)| (key, filter_match))
+            .collect();
     }
 
     /// Get filtered bookmarks for display
     pub fn get_filtered_bookmarks(&self) -> Vec<&Bookmark> {
-        if self.filtered_keys.is_empty() {
-            Vec::new()
-        } else {
-            self.filtered_keys
-                .iter()
-                .filter_map(|key| self.bookmarks.get(key))
-                .collect()
-        }
+        self.filtered_keys
+            .iter()
+            .filter_map(|(key, _)| self.bookmarks.get(key))
+            .collect()
+    }
+
+    /// Characters the filter matched in the `index`th filtered bookmark
+    pub fn filter_match(&self, index: usize) -> Option<&FilterMatch> {
+        self.filtered_keys
+            .get(index)
+            .map(|(_, filter_match)| filter_match)
     }
 
     /// Move selection up in bookmark list
@@ -426,6 +522,8 @@ mod tests {
             filtered_keys: Vec::new(),
             scroll_offset: 0,
             pending_deletion_index: None,
+            completion: Completion::new(),
+            compare_mode: false,
         }
     }
 
@@ -493,6 +591,8 @@ mod tests {
             filtered_keys: Vec::new(),
             scroll_offset: 0,
             pending_deletion_index: None,
+            completion: Completion::new(),
+            compare_mode: false,
         };
 
         let result = bookmarks.load();
@@ -530,6 +630,8 @@ mod tests {
             filtered_keys: Vec::new(),
             scroll_offset: 0,
             pending_deletion_index: None,
+            completion: Completion::new(),
+            compare_mode: false,
         };
 
         // Should load without error
@@ -744,4 +846,87 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("not found"));
     }
+
+    #[test]
+    fn test_filter_is_fuzzy_and_ranked() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut bookmarks = create_test_bookmarks(&temp_dir);
+        for (key, name) in [
+            ("docs", "Documents"),
+            ("dl", "Downloads"),
+            ("proj", "Projects"),
+        ] {
+            bookmarks
+                .add(
+                    key.to_string(),
+                    PathBuf::from("/tmp"),
+                    Some(name.to_string()),
+                )
+                .unwrap();
+        }
+
+        bookmarks.enter_selection_mode();
+        bookmarks.toggle_filter_mode();
+        for c in "dwn".chars() {
+            bookmarks.add_char(c);
+        }
+        let keys: Vec<&str> = bookmarks
+            .get_filtered_bookmarks()
+            .iter()
+            .map(|b| b.key.as_str())
+            .collect();
+        assert_eq!(keys, ["dl"]);
+        // "Downloads": D, w, n
+        assert_eq!(bookmarks.filter_match(0).unwrap().name, [0, 2, 3]);
+        assert!(bookmarks.filter_match(0).unwrap().key.is_empty());
+
+        bookmarks.backspace();
+        bookmarks.backspace();
+        let keys: Vec<&str> = bookmarks
+            .get_filtered_bookmarks()
+            .iter()
+            .map(|b| b.key.as_str())
+            .collect();
+        assert_eq!(keys.len(), 2);
+        assert!(keys.contains(&"dl") && keys.contains(&"docs"));
+    }
+
+    #[test]
+    fn test_workspace_relative_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let checkout = temp_dir.path().join("checkout");
+        fs::create_dir_all(checkout.join(".git")).unwrap();
+        fs::create_dir_all(checkout.join("src/ui")).unwrap();
+        let workspaces =
+            BTreeMap::from([("src".to_string(), temp_dir.path().display().to_string())]);
+
+        let bookmark = Bookmark {
+            key: "ui".to_string(),
+            path: PathBuf::from("@repo/src/ui"),
+            name: None,
+        };
+        // Anywhere in the checkout
+        assert_eq!(
+            bookmark
+                .resolve(&checkout.join("src"), &workspaces)
+                .unwrap(),
+            checkout.join("src/ui")
+        );
+        assert!(bookmark.resolve(temp_dir.path(), &workspaces).is_err());
+
+        let from = Path::new("/");
+        assert_eq!(
+            resolve_path(Path::new("@src/checkout"), from, &workspaces).unwrap(),
+            checkout
+        );
+        assert_eq!(
+            resolve_path(Path::new("@src"), from, &workspaces).unwrap(),
+            temp_dir.path()
+        );
+        assert!(resolve_path(Path::new("@work/x"), from, &workspaces).is_err());
+        assert_eq!(
+            resolve_path(Path::new("/abs/path"), from, &workspaces).unwrap(),
+            Path::new("/abs/path")
+        );
+    }
 }
@@ -0,0 +1,112 @@
+use crate::list_selection::ListSelection;
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Texts kept in the history unless `clipboard_history_size` says otherwise
+pub const DEFAULT_HISTORY_SIZE: usize = 20;
+
+static LIMIT: AtomicUsize = AtomicUsize::new(DEFAULT_HISTORY_SIZE);
+
+/// Everything copied during this run, newest first (process-wide like the operation
+/// log, so every place that copies records into the same list)
+static HISTORY: Lazy<Mutex<VecDeque<String>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+
+/// Keep the last `limit` copied texts (0 turns the history off)
+pub fn set_limit(limit: usize) {
+    LIMIT.store(limit, Ordering::Relaxed);
+    if let Ok(mut history) = HISTORY.lock() {
+        history.truncate(limit);
+    }
+}
+
+/// Put `text` on the system clipboard and at the top of the history
+/// Copying a text again moves it to the top instead of listing it twice
+pub fn copy(text: &str) -> Result<()> {
+    remember(text);
+    arboard::Clipboard::new()?.set_text(text)?;
+    Ok(())
+}
+
+fn remember(text: &str) {
+    let limit = LIMIT.load(Ordering::Relaxed);
+    let Ok(mut history) = HISTORY.lock() else {
+        return;
+    };
+    history.retain(|entry| entry != text);
+    history.push_front(text.to_string());
+    history.truncate(limit);
+}
+
+/// The copied texts, newest first
+pub fn history() -> Vec<String> {
+    HISTORY
+        .lock()
+        .map(|history| history.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Clipboard history panel shown in the bottom panel
+#[derive(Debug, Default)]
+pub struct HistoryPanel {
+    pub is_open: bool,
+    pub selected_index: usize,
+    pub entries: Vec<String>,
+}
+
+impl HistoryPanel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the panel with the current history
+    pub fn open(&mut self) {
+        self.entries = history();
+        self.selected_index = 0;
+        self.is_open = true;
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+        self.entries.clear();
+    }
+
+    pub fn get_selected(&self) -> Option<&str> {
+        self.entries.get(self.selected_index).map(String::as_str)
+    }
+}
+
+impl ListSelection for HistoryPanel {
+    fn selected_index_mut(&mut self) -> &mut usize {
+        &mut self.selected_index
+    }
+
+    fn entry_count(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_keeps_newest_first_without_duplicates() {
+        set_limit(3);
+        for text in ["/a", "/b", "/c", "/a", "/d"] {
+            remember(text);
+        }
+        assert_eq!(history(), ["/d", "/a", "/c"]);
+
+        let mut panel = HistoryPanel::new();
+        panel.open();
+        panel.move_down();
+        assert_eq!(panel.get_selected(), Some("/a"));
+        assert!(panel.select_number(3));
+        assert!(!panel.select_number(4));
+        assert_eq!(panel.get_selected(), Some("/c"));
+        set_limit(DEFAULT_HISTORY_SIZE);
+    }
+}
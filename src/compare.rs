@@ -0,0 +1,366 @@
+use crate::platform;
+use crate::tree_node::{NodeId, Tree, TreeNode};
+use crate::wakeup;
+use crossbeam_channel::Receiver;
+use std::collections::{BTreeSet, HashMap};
+use std::ffi::OsString;
+use std::fs::{self, File, Metadata};
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::thread;
+
+/// Entries compared at most; the rest of two huge trees is left out
+pub const MAX_ENTRIES: usize = 20_000;
+
+/// How an entry of the left tree differs from the right one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difference {
+    OnlyLeft,
+    OnlyRight,
+    /// A file on one side, a directory on the other
+    Kind,
+    Size,
+    /// Same size, different bytes
+    Content,
+    /// Same content, different modification time
+    Modified,
+    /// Directory with differences below it
+    Contains,
+}
+
+impl Difference {
+    pub fn label(self) -> &'static str {
+        match self {
+            Difference::OnlyLeft => "left only",
+            Difference::OnlyRight => "right only",
+            Difference::Kind => "file/dir",
+            Difference::Size => "size",
+            Difference::Content => "content",
+            Difference::Modified => "mtime",
+            Difference::Contains => "differs",
+        }
+    }
+}
+
+/// Two directories merged into one tree (like `diff -r` as a tree), shown in place
+/// of the tree; entries only on the right keep their right-hand path
+pub struct Comparison {
+    pub left: PathBuf,
+    pub right: PathBuf,
+    /// Tree to go back to when the comparison is closed
+    pub saved_tree: Tree,
+    pub only_left: usize,
+    pub only_right: usize,
+    /// Files that differ in kind, size, content or modification time
+    pub changed: usize,
+    /// More than MAX_ENTRIES entries - the rest are left out
+    pub truncated: bool,
+    show_hidden: bool,
+    entries: usize,
+    differences: HashMap<PathBuf, Difference>,
+}
+
+impl Comparison {
+    /// Compare `left` with `right` and build the merged tree
+    /// Directories with differences are expanded, identical ones collapsed; symlinked
+    /// directories are compared as entries but not descended into
+    pub fn scan(left: &Path, right: &Path, saved_tree: Tree, show_hidden: bool) -> (Self, Tree) {
+        let _span = tracing::debug_span!(
+            "compare",
+            left = %left.display(),
+            right = %right.display()
+        )
+        .entered();
+        let mut comparison = Self {
+            left: left.to_path_buf(),
+            right: right.to_path_buf(),
+            saved_tree,
+            only_left: 0,
+            only_right: 0,
+            changed: 0,
+            truncated: false,
+            show_hidden,
+            entries: 0,
+            differences: HashMap::new(),
+        };
+
+        let mut tree = Tree::new(TreeNode::new_virtual(left.to_path_buf(), 0, true));
+        let root = tree.root();
+        tree[root].children = comparison.compare_dirs(&mut tree, Some(left), Some(right), 1);
+        tree.sort_virtual(root);
+        (comparison, tree)
+    }
+
+    /// Difference of the entry at `path` (a path of the merged tree); None when identical
+    pub fn difference(&self, path: &Path) -> Option<Difference> {
+        self.differences.get(path).copied()
+    }
+
+    /// Whether anything differs at all
+    pub fn is_identical(&self) -> bool {
+        self.only_left == 0 && self.only_right == 0 && self.changed == 0
+    }
+
+    /// Children of the merged directory; a missing side makes them all one-sided
+    fn compare_dirs(
+        &mut self,
+        tree: &mut Tree,
+        left: Option<&Path>,
+        right: Option<&Path>,
+        depth: usize,
+    ) -> Vec<NodeId> {
+        let mut names = BTreeSet::new();
+        for dir in [left, right].into_iter().flatten() {
+            names.extend(self.list(dir));
+        }
+        let both_sides = left.is_some() && right.is_some();
+
+        let mut children = Vec::new();
+        for name in names {
+            if self.entries == MAX_ENTRIES {
+                self.truncated = true;
+                break;
+            }
+            self.entries += 1;
+
+            let left_path = left.map(|dir| dir.join(&name));
+            let right_path = right.map(|dir| dir.join(&name));
+            let left_meta = left_path
+                .as_deref()
+                .and_then(|path| fs::metadata(path).ok());
+            let right_meta = right_path
+                .as_deref()
+                .and_then(|path| fs::metadata(path).ok());
+
+            let (path, is_dir, difference, grandchildren) = match (left_meta, right_meta) {
+                (Some(meta), None) => {
+                    let path = left_path.unwrap_or_default();
+                    let children = self.descend(tree, &path, &meta, |dir| (Some(dir), None), depth);
+                    if both_sides {
+                        self.only_left += 1;
+                    }
+                    (path, meta.is_dir(), Some(Difference::OnlyLeft), children)
+                }
+                (None, Some(meta)) => {
+                    let path = right_path.unwrap_or_default();
+                    let children = self.descend(tree, &path, &meta, |dir| (None, Some(dir)), depth);
+                    if both_sides {
+                        self.only_right += 1;
+                    }
+                    (path, meta.is_dir(), Some(Difference::OnlyRight), children)
+                }
+                (Some(left_meta), Some(right_meta)) => {
+                    let left_path = left_path.unwrap_or_default();
+                    let right_path = right_path.unwrap_or_default();
+                    if left_meta.is_dir() && right_meta.is_dir() {
+                        let children = if is_symlink(&left_path) || is_symlink(&right_path) {
+                            Vec::new()
+                        } else {
+                            self.compare_dirs(tree, Some(&left_path), Some(&right_path), depth + 1)
+                        };
+                        let differs = children
+                            .iter()
+                            .any(|&child| self.differences.contains_key(&tree[child].path));
+                        (
+                            left_path,
+                            true,
+                            differs.then_some(Difference::Contains),
+                            children,
+                        )
+                    } else {
+                        let difference = if left_meta.is_dir() != right_meta.is_dir() {
+                            Some(Difference::Kind)
+                        } else {
+                            file_difference(&left_path, &right_path, &left_meta, &right_meta)
+                        };
+                        if difference.is_some() {
+                            self.changed += 1;
+                        }
+                        (left_path, left_meta.is_dir(), difference, Vec::new())
+                    }
+                }
+                // Broken symlinks on both sides
+                (None, None) => continue,
+            };
+
+            let mut node = TreeNode::new_virtual(path.clone(), depth, is_dir);
+            node.children = grandchildren;
+            // Only directories with differences inside start expanded
+            node.is_expanded = difference == Some(Difference::Contains);
+            if let Some(difference) = difference {
+                self.differences.insert(path, difference);
+            }
+            children.push(tree.insert(node));
+        }
+        children
+    }
+
+    /// Children of an entry that exists on one side only, all marked one-sided
+    fn descend<'a>(
+        &mut self,
+        tree: &mut Tree,
+        path: &'a Path,
+        meta: &Metadata,
+        sides: impl Fn(&'a Path) -> (Option<&'a Path>, Option<&'a Path>),
+        depth: usize,
+    ) -> Vec<NodeId> {
+        if !meta.is_dir() || is_symlink(path) {
+            return Vec::new();
+        }
+        let (left, right) = sides(path);
+        self.compare_dirs(tree, left, right, depth + 1)
+    }
+
+    /// Names in `dir`, without hidden ones unless they are shown
+    fn list(&self, dir: &Path) -> Vec<OsString> {
+        let Ok(read_dir) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        read_dir
+            .flatten()
+            .filter(|entry| self.show_hidden || !platform::is_hidden(&entry.path()))
+            .map(|entry| entry.file_name())
+            .collect()
+    }
+}
+
+/// A comparison scanned on a worker thread, shown once it is done
+pub struct PendingComparison {
+    pub right: PathBuf,
+    receiver: Receiver<(Comparison, Tree)>,
+}
+
+impl PendingComparison {
+    /// Start comparing `left` with `right` in the background
+    /// The tree to go back to is filled in when the result is shown
+    pub fn start(left: &Path, right: &Path, show_hidden: bool) -> Self {
+        let (tx, rx) = wakeup::unbounded();
+        let (l, r) = (left.to_path_buf(), right.to_path_buf());
+        thread::spawn(move || {
+            let saved = Tree::new(TreeNode::new_virtual(l.clone(), 0, true));
+            let _ = tx.send(Comparison::scan(&l, &r, saved, show_hidden));
+        });
+        Self {
+            right: right.to_path_buf(),
+            receiver: rx,
+        }
+    }
+
+    /// The comparison and its merged tree, once the worker is done
+    pub fn try_finish(&self) -> Option<(Comparison, Tree)> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+fn is_symlink(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_symlink())
+}
+
+/// How two files differ: sizes first, then (when the times differ) their bytes
+/// Like rsync's quick check, files of the same size and time count as identical
+fn file_difference(
+    left: &Path,
+    right: &Path,
+    left_meta: &Metadata,
+    right_meta: &Metadata,
+) -> Option<Difference> {
+    if left_meta.len() != right_meta.len() {
+        return Some(Difference::Size);
+    }
+    let (left_time, right_time) = (left_meta.modified().ok(), right_meta.modified().ok());
+    if left_time.is_some() && left_time == right_time {
+        return None;
+    }
+    if same_content(left, right).unwrap_or(false) {
+        (left_time != right_time).then_some(Difference::Modified)
+    } else {
+        Some(Difference::Content)
+    }
+}
+
+/// Compare two files of the same size chunk by chunk, stopping at the first difference
+fn same_content(left: &Path, right: &Path) -> std::io::Result<bool> {
+    let mut left = BufReader::new(File::open(left)?);
+    let mut right = BufReader::new(File::open(right)?);
+    let mut left_chunk = [0u8; 8192];
+    let mut right_chunk = [0u8; 8192];
+    loop {
+        let read = left.read(&mut left_chunk)?;
+        if read == 0 {
+            return Ok(true);
+        }
+        right.read_exact(&mut right_chunk[..read])?;
+        if left_chunk[..read] != right_chunk[..read] {
+            return Ok(false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn test_compare_trees() {
+        let left = tempfile::tempdir().unwrap();
+        let right = tempfile::tempdir().unwrap();
+        let (l, r) = (left.path(), right.path());
+        for dir in [l, r] {
+            fs::create_dir_all(dir.join("same/deep")).unwrap();
+            fs::write(dir.join("same/deep/a.txt"), "same").unwrap();
+        }
+        fs::write(l.join("size.txt"), "short").unwrap();
+        fs::write(r.join("size.txt"), "longer").unwrap();
+        fs::write(l.join("content.txt"), "aaaa").unwrap();
+        fs::write(r.join("content.txt"), "bbbb").unwrap();
+        fs::write(l.join("touched.txt"), "x").unwrap();
+        fs::write(r.join("touched.txt"), "x").unwrap();
+        // Same size and time count as identical, so the right-hand copies are older
+        let old = SystemTime::now() - Duration::from_secs(3600);
+        for name in ["content.txt", "touched.txt"] {
+            File::options()
+                .write(true)
+                .open(r.join(name))
+                .unwrap()
+                .set_modified(old)
+                .unwrap();
+        }
+        fs::create_dir(l.join("gone")).unwrap();
+        fs::write(l.join("gone/file"), "").unwrap();
+        fs::write(r.join("new.txt"), "").unwrap();
+
+        let saved = Tree::new(TreeNode::new_virtual(l.to_path_buf(), 0, true));
+        let (comparison, tree) = Comparison::scan(l, r, saved, false);
+        let difference = |path: PathBuf| comparison.difference(&path);
+        assert_eq!(difference(l.join("size.txt")), Some(Difference::Size));
+        assert_eq!(difference(l.join("content.txt")), Some(Difference::Content));
+        assert_eq!(
+            difference(l.join("touched.txt")),
+            Some(Difference::Modified)
+        );
+        assert_eq!(difference(l.join("gone")), Some(Difference::OnlyLeft));
+        assert_eq!(difference(l.join("gone/file")), Some(Difference::OnlyLeft));
+        assert_eq!(difference(r.join("new.txt")), Some(Difference::OnlyRight));
+        assert_eq!(difference(l.join("same")), None);
+        assert_eq!(
+            (
+                comparison.only_left,
+                comparison.only_right,
+                comparison.changed
+            ),
+            (1, 1, 3)
+        );
+
+        // Identical directories start collapsed, directories come first
+        let children = &tree.root_node().children;
+        assert_eq!(tree[children[0]].name, "gone");
+        let same = children
+            .iter()
+            .map(|&c| &tree[c])
+            .find(|c| c.name == "same");
+        let same = same.unwrap();
+        assert!(!same.is_expanded);
+        assert_eq!(same.children.len(), 1);
+    }
+}
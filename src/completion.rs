@@ -0,0 +1,255 @@
+use std::fs;
+use std::path::{Path, MAIN_SEPARATOR};
+
+/// Maximum number of candidates shown at once in the completion popup
+pub const POPUP_HEIGHT: usize = 8;
+
+/// A single completion: what the input becomes and how it is shown in the popup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub label: String,
+    pub value: String,
+}
+
+/// Tab completion state shared by text prompts (go to path, bookmark name)
+///
+/// The first Tab completes the longest common prefix of all candidates; when that
+/// is not enough, the candidates are shown in a popup and further Tabs cycle them.
+#[derive(Debug, Default)]
+pub struct Completion {
+    candidates: Vec<Candidate>,
+    selected: Option<usize>,
+}
+
+impl Completion {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check if the popup is shown (more than one candidate)
+    pub fn is_active(&self) -> bool {
+        !self.candidates.is_empty()
+    }
+
+    pub fn candidates(&self) -> &[Candidate] {
+        &self.candidates
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// Forget the candidates (any key other than Tab edits the input)
+    pub fn reset(&mut self) {
+        self.candidates.clear();
+        self.selected = None;
+    }
+
+    /// Tab: complete `input` or cycle to the next candidate
+    /// Returns the new input, or None when nothing matches
+    pub fn next(
+        &mut self,
+        input: &str,
+        source: impl FnOnce(&str) -> Vec<Candidate>,
+    ) -> Option<String> {
+        self.step(input, source, true)
+    }
+
+    /// Shift+Tab: cycle to the previous candidate
+    pub fn prev(
+        &mut self,
+        input: &str,
+        source: impl FnOnce(&str) -> Vec<Candidate>,
+    ) -> Option<String> {
+        self.step(input, source, false)
+    }
+
+    fn step(
+        &mut self,
+        input: &str,
+        source: impl FnOnce(&str) -> Vec<Candidate>,
+        forward: bool,
+    ) -> Option<String> {
+        if self.is_active() {
+            let len = self.candidates.len();
+            let index = match (self.selected, forward) {
+                (None, true) => 0,
+                (None, false) => len - 1,
+                (Some(i), true) => (i + 1) % len,
+                (Some(i), false) => (i + len - 1) % len,
+            };
+            self.selected = Some(index);
+            return Some(self.candidates[index].value.clone());
+        }
+
+        let candidates = source(input);
+        match candidates.as_slice() {
+            [] => None,
+            [only] => Some(only.value.clone()),
+            [first, rest @ ..] => {
+                let common = rest.iter().fold(first.value.as_str(), |prefix, candidate| {
+                    &prefix[..common_prefix_len(prefix, &candidate.value)]
+                });
+                let completed = if common.len() > input.len() {
+                    common.to_string()
+                } else {
+                    input.to_string()
+                };
+                self.candidates = candidates;
+                self.selected = None;
+                Some(completed)
+            }
+        }
+    }
+}
+
+/// Length in bytes of the common prefix of two strings (on a char boundary)
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    a.char_indices()
+        .zip(b.chars())
+        .take_while(|((_, x), y)| x == y)
+        .last()
+        .map(|((i, c), _)| i + c.len_utf8())
+        .unwrap_or(0)
+}
+
+/// Directories matching the last component of a typed path
+/// Hidden directories are only offered when the component starts with a dot
+pub fn path_candidates(input: &str, base: &Path) -> Vec<Candidate> {
+    // Split into the directory being listed and the partial name typed so far
+    let split_at = input
+        .rfind(['/', MAIN_SEPARATOR])
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let (dir_part, partial) = input.split_at(split_at);
+    let dir = if dir_part.is_empty() {
+        base.to_path_buf()
+    } else {
+        crate::goto::expand_path(dir_part, base)
+    };
+
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().to_str().map(|s| s.to_string()))
+        .filter(|name| name.starts_with(partial))
+        .filter(|name| partial.starts_with('.') || !name.starts_with('.'))
+        .collect();
+    names.sort();
+
+    names
+        .into_iter()
+        .map(|name| Candidate {
+            value: format!("{}{}{}", dir_part, name, MAIN_SEPARATOR),
+            label: format!("{}{}", name, MAIN_SEPARATOR),
+        })
+        .collect()
+}
+
+/// Bookmark names starting with `input`
+pub fn name_candidates<'a>(input: &str, names: impl Iterator<Item = &'a str>) -> Vec<Candidate> {
+    let mut names: Vec<&str> = names.filter(|name| name.starts_with(input)).collect();
+    names.sort();
+    names
+        .into_iter()
+        .map(|name| Candidate {
+            label: name.to_string(),
+            value: name.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn names(values: &[&str]) -> Vec<Candidate> {
+        name_candidates("", values.iter().copied())
+    }
+
+    #[test]
+    fn test_tab_completes_common_prefix_then_cycles() {
+        let mut completion = Completion::new();
+        let source = |_: &str| names(&["project-b", "project-a"]);
+
+        // Common prefix first, popup shown with the candidates
+        assert_eq!(completion.next("pr", source), Some("project-".to_string()));
+        assert!(completion.is_active());
+        assert_eq!(completion.selected(), None);
+
+        // Then Tab cycles (and wraps), Shift+Tab goes back
+        assert_eq!(
+            completion.next("project-", source),
+            Some("project-a".to_string())
+        );
+        assert_eq!(
+            completion.next("project-a", source),
+            Some("project-b".to_string())
+        );
+        assert_eq!(
+            completion.next("project-b", source),
+            Some("project-a".to_string())
+        );
+        assert_eq!(
+            completion.prev("project-a", source),
+            Some("project-b".to_string())
+        );
+
+        // Editing the input starts over
+        completion.reset();
+        assert!(!completion.is_active());
+        assert_eq!(completion.next("x", |_: &str| Vec::new()), None);
+        assert_eq!(
+            completion.next("m", |input: &str| name_candidates(
+                input,
+                ["music"].into_iter()
+            )),
+            Some("music".to_string())
+        );
+        assert!(!completion.is_active());
+    }
+
+    #[test]
+    fn test_path_candidates() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path();
+        fs::create_dir_all(base.join("projects/dtree")).unwrap();
+        fs::create_dir_all(base.join("projects/dotfiles")).unwrap();
+        fs::create_dir_all(base.join("pictures")).unwrap();
+        fs::create_dir_all(base.join(".private")).unwrap();
+        fs::write(base.join("projects/notes.txt"), "").unwrap();
+
+        let sep = MAIN_SEPARATOR;
+        let values = |input: &str| -> Vec<String> {
+            path_candidates(input, base)
+                .into_iter()
+                .map(|c| c.value)
+                .collect()
+        };
+        assert_eq!(values("pr"), [format!("projects{}", sep)]);
+        assert_eq!(
+            values(&format!("projects{}d", sep)),
+            [
+                format!("projects{}dotfiles{}", sep, sep),
+                format!("projects{}dtree{}", sep, sep)
+            ]
+        );
+        // Files are not offered, hidden directories only with a leading dot
+        assert!(values(&format!("projects{}n", sep)).is_empty());
+        assert_eq!(values("p").len(), 2);
+        assert_eq!(values(".p"), [format!(".private{}", sep)]);
+
+        let labels: Vec<String> = path_candidates(&format!("projects{}d", sep), base)
+            .into_iter()
+            .map(|c| c.label)
+            .collect();
+        assert_eq!(
+            labels,
+            [format!("dotfiles{}", sep), format!("dtree{}", sep)]
+        );
+    }
+}
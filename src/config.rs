@@ -2,15 +2,22 @@ use anyhow::{Context, Result};
 use crossterm::event::KeyCode;
 use ratatui::style::Color;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::clipboard;
+use crate::config_migration;
+use crate::mime;
+use crate::preview_cache;
+use crate::search;
+use crate::theme::background::{self, Background};
 use crate::theme::ThemeConfig;
 
 /// Appearance configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppearanceConfig {
-    /// Theme name (can be expanded later for preset themes)
+    /// Theme name: a preset, or "auto" for "light"/"default" by the terminal background
     #[serde(default = "default_theme")]
     pub theme: String,
 
@@ -22,10 +29,23 @@ pub struct AppearanceConfig {
     #[serde(default = "default_split_position")]
     pub split_position: u16,
 
+    /// Restore split positions changed with Ctrl+arrows or the mouse on the next run
+    #[serde(default = "default_remember_layout")]
+    pub remember_layout: bool,
+
+    /// Set the terminal window title to "dtree – <root>" (restored on exit)
+    #[serde(default)]
+    pub terminal_title: bool,
+
     /// Show line numbers in fullscreen viewer by default
     #[serde(default = "default_show_line_numbers")]
     pub show_line_numbers: bool,
 
+    /// Column on the right of the fullscreen viewer marking the search matches
+    /// and the visible part of the file
+    #[serde(default = "default_show_minimap")]
+    pub show_minimap: bool,
+
     /// Enable syntax highlighting for code files
     #[serde(default = "default_enable_syntax_highlighting")]
     pub enable_syntax_highlighting: bool,
@@ -34,6 +54,31 @@ pub struct AppearanceConfig {
     #[serde(default = "default_syntax_theme")]
     pub syntax_theme: String,
 
+    /// RGB colors as-is ("truecolor"), mapped to the 256-color palette ("256"),
+    /// or "auto" to map them only on terminals known to lack truecolor
+    #[serde(default = "default_color_mode")]
+    pub color_mode: String,
+
+    /// Size units: "binary" (powers of 1024) or "si" (powers of 1000)
+    #[serde(default = "default_size_units")]
+    pub size_units: String,
+
+    /// Decimals of sizes (unset: 1 in the size column, 2 in the file info line)
+    #[serde(default)]
+    pub size_decimals: Option<usize>,
+
+    /// Modification times: "iso", "relative" or "locale"
+    #[serde(default = "default_date_format")]
+    pub date_format: String,
+
+    /// UI language ("de", "de_DE.UTF-8"), or "auto" to follow LC_ALL/LC_MESSAGES/LANG
+    #[serde(default = "default_locale")]
+    pub locale: String,
+
+    /// Flag setuid/setgid and world-writable entries in the tree (Unix)
+    #[serde(default)]
+    pub permission_warnings: bool,
+
     /// Custom theme colors
     #[serde(default)]
     pub colors: ThemeConfig,
@@ -45,32 +90,60 @@ impl Default for AppearanceConfig {
             theme: default_theme(),
             show_icons: default_show_icons(),
             split_position: default_split_position(),
+            remember_layout: default_remember_layout(),
+            terminal_title: false,
             show_line_numbers: default_show_line_numbers(),
+            show_minimap: default_show_minimap(),
             enable_syntax_highlighting: default_enable_syntax_highlighting(),
             syntax_theme: default_syntax_theme(),
+            color_mode: default_color_mode(),
+            size_units: default_size_units(),
+            size_decimals: None,
+            date_format: default_date_format(),
+            locale: default_locale(),
+            permission_warnings: false,
             colors: ThemeConfig::default(),
         }
     }
 }
 
 fn default_theme() -> String {
-    "default".to_string()
+    "auto".to_string()
 }
 fn default_show_icons() -> bool {
     false
 }
+fn default_remember_layout() -> bool {
+    true
+}
+
 fn default_split_position() -> u16 {
     20
 }
 fn default_show_line_numbers() -> bool {
     false
 }
+fn default_show_minimap() -> bool {
+    true
+}
 fn default_enable_syntax_highlighting() -> bool {
     true
 }
 fn default_syntax_theme() -> String {
     "base16-ocean.dark".to_string()
 }
+fn default_color_mode() -> String {
+    "auto".to_string()
+}
+fn default_size_units() -> String {
+    "binary".to_string()
+}
+fn default_date_format() -> String {
+    "iso".to_string()
+}
+fn default_locale() -> String {
+    "auto".to_string()
+}
 
 /// Behavior configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -79,6 +152,30 @@ pub struct BehaviorConfig {
     #[serde(default = "default_max_file_lines")]
     pub max_file_lines: usize,
 
+    /// Lines shown from the start and the end of longer files (0 = the start only)
+    #[serde(default)]
+    pub head_tail_lines: usize,
+
+    /// Files larger than this (in MB) aren't previewed; 0 = no limit
+    #[serde(default = "default_max_preview_size_mb")]
+    pub max_preview_size_mb: u64,
+
+    /// Bytes read from the start of a file to tell binary files from text
+    #[serde(default = "default_binary_sniff_bytes")]
+    pub binary_sniff_bytes: usize,
+
+    /// Paths never previewed (entries below them aren't even stat'ed)
+    #[serde(default = "default_preview_skip")]
+    pub preview_skip: Vec<String>,
+
+    /// Milliseconds the selection has to rest before its preview is loaded
+    #[serde(default = "default_preview_debounce_ms")]
+    pub preview_debounce_ms: u64,
+
+    /// Recently read files kept in memory (0 = always read from disk)
+    #[serde(default = "default_preview_cache_size")]
+    pub preview_cache_size: usize,
+
     /// Show hidden files (dotfiles)
     #[serde(default = "default_show_hidden")]
     pub show_hidden: bool,
@@ -87,10 +184,34 @@ pub struct BehaviorConfig {
     #[serde(default = "default_follow_symlinks")]
     pub follow_symlinks: bool,
 
+    /// Stay on one filesystem: don't descend into mountpoints (tree, search, sizes)
+    #[serde(default = "default_one_file_system")]
+    pub one_file_system: bool,
+
+    /// Entries git ignores: "show", "hide" or "dim"
+    #[serde(default = "default_gitignore")]
+    pub gitignore: String,
+
+    /// Search results kept at most; further matches are only counted (0 = no limit)
+    #[serde(default = "default_max_search_results")]
+    pub max_search_results: usize,
+
     /// Double-click timeout in milliseconds
     #[serde(default = "default_double_click_timeout")]
     pub double_click_timeout_ms: u64,
 
+    /// How long the first key of a two-key sequence ("space f") waits for the second
+    #[serde(default = "default_key_sequence_timeout")]
+    pub key_sequence_timeout_ms: u64,
+
+    /// Minutes without input before `idle_action` happens (0 = never)
+    #[serde(default)]
+    pub idle_timeout_minutes: u64,
+
+    /// What happens when idle: "blank" (hide everything until a key) or "exit"
+    #[serde(default = "default_idle_action")]
+    pub idle_action: String,
+
     /// External editor command for opening files
     #[serde(default = "default_editor")]
     pub editor: String,
@@ -103,43 +224,154 @@ pub struct BehaviorConfig {
     #[serde(default = "default_hex_editor")]
     pub hex_editor: String,
 
+    /// Programs with their own window besides the known ones (started without leaving dtree)
+    #[serde(default)]
+    pub gui_programs: Vec<String>,
+
+    /// Come back to dtree when the editor (or file manager) exits instead of quitting
+    #[serde(default = "default_return_after_editor")]
+    pub return_after_editor: bool,
+
+    /// Open the editor in a tmux/wezterm split instead of leaving the TUI
+    #[serde(default = "default_open_in_split")]
+    pub open_in_split: String,
+
+    /// Placement of the split pane ("right" or "below")
+    #[serde(default = "default_split_direction")]
+    pub split_direction: String,
+
+    /// Open files in the parent Neovim when running inside its :terminal ($NVIM)
+    #[serde(default = "default_nvim_remote")]
+    pub nvim_remote: bool,
+
+    /// What Enter does on a file: "none", "preview", "editor", "open" or "select"
+    #[serde(default = "default_enter_file_action")]
+    pub enter_file_action: String,
+
     /// Wrap long lines in file viewer (true = wrap, false = truncate)
     #[serde(default = "default_wrap_lines")]
     pub wrap_lines: bool,
 
+    /// Read the shown file again when its modification time changes (logs)
+    #[serde(default)]
+    pub auto_reload: bool,
+
+    /// Keep each file's scroll position, head/tail mode, wrapping and line numbers
+    /// between runs (they are always kept while dtree runs)
+    #[serde(default)]
+    pub remember_viewer_state: bool,
+
     /// Number of lines to scroll with mouse wheel in file viewer mode
     #[serde(default = "default_mouse_scroll_lines")]
     pub mouse_scroll_lines: usize,
+
+    /// File the search results export writes to; empty prints them to stdout on exit
+    #[serde(default)]
+    pub quickfix_file: String,
+
+    /// File the tree export writes to; empty prints the tree to stdout on exit
+    #[serde(default)]
+    pub tree_export_file: String,
+
+    /// `q` prints the marked paths (one per line) instead of the directory to cd into
+    #[serde(default)]
+    pub print_marked_on_exit: bool,
+
+    /// Copied paths and snippets kept for the clipboard history panel
+    #[serde(default = "default_clipboard_history_size")]
+    pub clipboard_history_size: usize,
 }
 
 impl Default for BehaviorConfig {
     fn default() -> Self {
         Self {
             max_file_lines: default_max_file_lines(),
+            head_tail_lines: 0,
+            max_preview_size_mb: default_max_preview_size_mb(),
+            binary_sniff_bytes: default_binary_sniff_bytes(),
+            preview_skip: default_preview_skip(),
+            preview_debounce_ms: default_preview_debounce_ms(),
+            preview_cache_size: default_preview_cache_size(),
             show_hidden: default_show_hidden(),
             follow_symlinks: default_follow_symlinks(),
+            one_file_system: default_one_file_system(),
+            gitignore: default_gitignore(),
+            max_search_results: default_max_search_results(),
             double_click_timeout_ms: default_double_click_timeout(),
+            key_sequence_timeout_ms: default_key_sequence_timeout(),
+            idle_timeout_minutes: 0,
+            idle_action: default_idle_action(),
             editor: default_editor(),
             file_manager: default_file_manager(),
             hex_editor: default_hex_editor(),
+            gui_programs: Vec::new(),
+            return_after_editor: default_return_after_editor(),
+            open_in_split: default_open_in_split(),
+            split_direction: default_split_direction(),
+            nvim_remote: default_nvim_remote(),
+            enter_file_action: default_enter_file_action(),
             wrap_lines: default_wrap_lines(),
+            auto_reload: false,
+            remember_viewer_state: false,
             mouse_scroll_lines: default_mouse_scroll_lines(),
+            quickfix_file: String::new(),
+            tree_export_file: String::new(),
+            print_marked_on_exit: false,
+            clipboard_history_size: default_clipboard_history_size(),
         }
     }
 }
 
+fn default_clipboard_history_size() -> usize {
+    clipboard::DEFAULT_HISTORY_SIZE
+}
+
 fn default_max_file_lines() -> usize {
     10000
 }
+fn default_max_preview_size_mb() -> u64 {
+    100
+}
+fn default_preview_debounce_ms() -> u64 {
+    30
+}
+fn default_max_search_results() -> usize {
+    search::DEFAULT_MAX_RESULTS
+}
+fn default_preview_cache_size() -> usize {
+    preview_cache::DEFAULT_SIZE
+}
+fn default_binary_sniff_bytes() -> usize {
+    mime::SAMPLE_SIZE
+}
+fn default_preview_skip() -> Vec<String> {
+    if cfg!(unix) {
+        vec!["/proc".to_string(), "/sys".to_string(), "/dev".to_string()]
+    } else {
+        Vec::new()
+    }
+}
 fn default_show_hidden() -> bool {
     true
 }
 fn default_follow_symlinks() -> bool {
     true
 }
+fn default_one_file_system() -> bool {
+    false
+}
+fn default_gitignore() -> String {
+    "show".to_string()
+}
 fn default_double_click_timeout() -> u64 {
     500
 }
+fn default_key_sequence_timeout() -> u64 {
+    1000
+}
+fn default_idle_action() -> String {
+    "blank".to_string()
+}
 #[cfg(unix)]
 fn default_editor() -> String {
     std::env::var("EDITOR").unwrap_or_else(|_| "nano".to_string())
@@ -179,6 +411,21 @@ fn default_hex_editor() -> String {
         "notepad.exe".to_string()
     }
 }
+fn default_return_after_editor() -> bool {
+    false
+}
+fn default_open_in_split() -> String {
+    "off".to_string()
+}
+fn default_split_direction() -> String {
+    "right".to_string()
+}
+fn default_nvim_remote() -> bool {
+    true
+}
+fn default_enter_file_action() -> String {
+    "none".to_string()
+}
 fn default_wrap_lines() -> bool {
     true
 }
@@ -186,6 +433,29 @@ fn default_mouse_scroll_lines() -> usize {
     5
 }
 
+/// Search configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchConfig {
+    /// Names searches, scans and directory sizes don't go into (globs)
+    #[serde(default = "default_search_exclude")]
+    pub exclude: Vec<String>,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            exclude: default_search_exclude(),
+        }
+    }
+}
+
+fn default_search_exclude() -> Vec<String> {
+    ["node_modules", ".git", "target"]
+        .iter()
+        .map(|name| name.to_string())
+        .collect()
+}
+
 /// Keybindings configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeybindingsConfig {
@@ -233,6 +503,10 @@ pub struct KeybindingsConfig {
     #[serde(default = "default_toggle_wrap_keys")]
     pub toggle_wrap: Vec<String>,
 
+    /// Keys to read the file in the viewer again
+    #[serde(default = "default_reload_file_keys")]
+    pub reload_file: Vec<String>,
+
     /// Keys to enter visual selection mode (fullscreen viewer)
     #[serde(default = "default_visual_mode_keys")]
     pub visual_mode: Vec<String>,
@@ -240,6 +514,138 @@ pub struct KeybindingsConfig {
     /// Keys to copy selection in visual mode
     #[serde(default = "default_visual_copy_keys")]
     pub visual_copy: Vec<String>,
+
+    /// Keys to show/hide the background task overlay
+    #[serde(default = "default_toggle_tasks_keys")]
+    pub toggle_tasks: Vec<String>,
+
+    /// Keys to mark/unmark the entry under the cursor
+    #[serde(default = "default_toggle_mark_keys")]
+    pub toggle_mark: Vec<String>,
+
+    /// Keys to yank (copy) marked or selected entries
+    #[serde(default = "default_yank_keys")]
+    pub yank: Vec<String>,
+
+    /// Keys to cut marked or selected entries
+    #[serde(default = "default_cut_keys")]
+    pub cut: Vec<String>,
+
+    /// Keys to paste yanked/cut entries into the current directory
+    #[serde(default = "default_paste_keys")]
+    pub paste: Vec<String>,
+
+    /// Keys to open the go-to-path prompt
+    #[serde(default = "default_goto_path_keys")]
+    pub goto_path: Vec<String>,
+
+    /// Show/hide the log of actions and messages
+    #[serde(default = "default_message_log_keys")]
+    pub message_log: Vec<String>,
+
+    /// Keys to open the plugin command prompt
+    #[serde(default = "default_plugin_command_keys")]
+    pub plugin_command: Vec<String>,
+
+    /// Keys to cycle the quick filters (directories, code, images, documents)
+    #[serde(default = "default_quick_filter_keys")]
+    pub quick_filter: Vec<String>,
+
+    /// Keys to list all files under the root as a flat list
+    #[serde(default = "default_flat_listing_keys")]
+    pub flat_listing: Vec<String>,
+
+    /// Keys to change the sort order of the flat listing
+    #[serde(default = "default_flat_sort_keys")]
+    pub flat_sort: Vec<String>,
+
+    /// Keys to list empty directories (and zero-byte files when files are shown)
+    #[serde(default = "default_find_empty_keys")]
+    pub find_empty: Vec<String>,
+
+    /// Keys to list broken symlinks under the root
+    #[serde(default = "default_find_broken_links_keys")]
+    pub find_broken_links: Vec<String>,
+
+    /// Keys to list the largest files under the selected directory
+    #[serde(default = "default_largest_files_keys")]
+    pub largest_files: Vec<String>,
+
+    /// Keys to show git log, then git blame of the selected entry in the viewer
+    #[serde(default = "default_git_view_keys")]
+    pub git_view: Vec<String>,
+
+    /// Keys to change the root to the top level of the git repository
+    #[serde(default = "default_repo_root_keys")]
+    pub repo_root: Vec<String>,
+
+    /// Keys to open the panel of projects around the root
+    #[serde(default = "default_projects_keys")]
+    pub projects: Vec<String>,
+
+    /// Keys to open all marked files in the editor at once
+    #[serde(default = "default_open_marked_keys")]
+    pub open_marked: Vec<String>,
+
+    /// Keys to export the search results as a quickfix list
+    #[serde(default = "default_export_results_keys")]
+    pub export_results: Vec<String>,
+
+    /// Open the clipboard history (copy an earlier path or snippet again)
+    #[serde(default = "default_clipboard_history_keys")]
+    pub clipboard_history: Vec<String>,
+
+    /// Open the saved filters picker ([filters] section)
+    #[serde(default = "default_saved_filters_keys")]
+    pub saved_filters: Vec<String>,
+
+    /// Compare the root with a bookmarked directory, or leave the comparison
+    #[serde(default = "default_compare_keys")]
+    pub compare: Vec<String>,
+
+    /// Export the visible tree as text or Markdown
+    #[serde(default = "default_export_tree_keys")]
+    pub export_tree: Vec<String>,
+
+    /// List all hard links to the selected file under the root
+    #[serde(default = "default_find_hardlinks_keys")]
+    pub find_hardlinks: Vec<String>,
+
+    /// Open the named layouts picker (restore or save a window arrangement)
+    #[serde(default = "default_layouts_keys")]
+    pub layouts: Vec<String>,
+
+    /// Collapse the tree down to the selected entry (or viewed file, result) and center it
+    #[serde(default = "default_reveal_keys")]
+    pub reveal: Vec<String>,
+
+    /// Open the fuzzy jump overlay over every node loaded in the tree
+    #[serde(default = "default_fuzzy_jump_keys")]
+    pub fuzzy_jump: Vec<String>,
+
+    /// Narrow the tree to the names containing what is typed, keeping their directories
+    #[serde(default = "default_live_filter_keys")]
+    pub live_filter: Vec<String>,
+
+    /// List the marked entries, to reveal or unmark them wherever they are
+    #[serde(default = "default_marks_keys")]
+    pub marks: Vec<String>,
+
+    /// Results panel: add the marked results (all when none are) to the tree's marks
+    #[serde(default = "default_mark_results_keys")]
+    pub mark_results: Vec<String>,
+
+    /// Select the next search result in the tree and viewer, from any pane
+    #[serde(default = "default_next_result_keys")]
+    pub next_result: Vec<String>,
+
+    /// Select the previous search result in the tree and viewer, from any pane
+    #[serde(default = "default_previous_result_keys")]
+    pub previous_result: Vec<String>,
+
+    /// Results panel: make the selected result's directory the root
+    #[serde(default = "default_root_at_result_keys")]
+    pub root_at_result: Vec<String>,
 }
 
 impl Default for KeybindingsConfig {
@@ -256,8 +662,42 @@ impl Default for KeybindingsConfig {
             select_bookmark: default_select_bookmark_keys(),
             show_line_numbers: default_show_line_numbers_keys(),
             toggle_wrap: default_toggle_wrap_keys(),
+            reload_file: default_reload_file_keys(),
             visual_mode: default_visual_mode_keys(),
             visual_copy: default_visual_copy_keys(),
+            toggle_tasks: default_toggle_tasks_keys(),
+            toggle_mark: default_toggle_mark_keys(),
+            yank: default_yank_keys(),
+            cut: default_cut_keys(),
+            paste: default_paste_keys(),
+            goto_path: default_goto_path_keys(),
+            message_log: default_message_log_keys(),
+            plugin_command: default_plugin_command_keys(),
+            quick_filter: default_quick_filter_keys(),
+            flat_listing: default_flat_listing_keys(),
+            flat_sort: default_flat_sort_keys(),
+            find_empty: default_find_empty_keys(),
+            find_broken_links: default_find_broken_links_keys(),
+            largest_files: default_largest_files_keys(),
+            git_view: default_git_view_keys(),
+            repo_root: default_repo_root_keys(),
+            projects: default_projects_keys(),
+            open_marked: default_open_marked_keys(),
+            export_results: default_export_results_keys(),
+            clipboard_history: default_clipboard_history_keys(),
+            saved_filters: default_saved_filters_keys(),
+            compare: default_compare_keys(),
+            export_tree: default_export_tree_keys(),
+            find_hardlinks: default_find_hardlinks_keys(),
+            layouts: default_layouts_keys(),
+            reveal: default_reveal_keys(),
+            fuzzy_jump: default_fuzzy_jump_keys(),
+            live_filter: default_live_filter_keys(),
+            marks: default_marks_keys(),
+            mark_results: default_mark_results_keys(),
+            next_result: default_next_result_keys(),
+            previous_result: default_previous_result_keys(),
+            root_at_result: default_root_at_result_keys(),
         }
     }
 }
@@ -295,17 +735,139 @@ fn default_show_line_numbers_keys() -> Vec<String> {
 fn default_toggle_wrap_keys() -> Vec<String> {
     vec!["w".to_string()]
 }
+fn default_reload_file_keys() -> Vec<String> {
+    vec!["r".to_string()]
+}
 fn default_visual_mode_keys() -> Vec<String> {
-    vec!["V".to_string()]
+    vec!["V".to_string(), "v".to_string()]
 }
 fn default_visual_copy_keys() -> Vec<String> {
     vec!["y".to_string(), "Y".to_string()]
 }
+fn default_toggle_tasks_keys() -> Vec<String> {
+    vec!["t".to_string()]
+}
+fn default_toggle_mark_keys() -> Vec<String> {
+    vec!["Space".to_string()]
+}
+fn default_yank_keys() -> Vec<String> {
+    vec!["y".to_string()]
+}
+fn default_cut_keys() -> Vec<String> {
+    vec!["x".to_string()]
+}
+fn default_paste_keys() -> Vec<String> {
+    vec!["p".to_string()]
+}
+fn default_goto_path_keys() -> Vec<String> {
+    vec!["g".to_string(), ":".to_string()]
+}
+fn default_message_log_keys() -> Vec<String> {
+    vec!["!".to_string()]
+}
+fn default_plugin_command_keys() -> Vec<String> {
+    vec!["@".to_string()]
+}
+fn default_quick_filter_keys() -> Vec<String> {
+    vec!["f".to_string()]
+}
+fn default_flat_listing_keys() -> Vec<String> {
+    vec!["a".to_string()]
+}
+fn default_flat_sort_keys() -> Vec<String> {
+    vec!["#".to_string()]
+}
+fn default_find_empty_keys() -> Vec<String> {
+    vec!["0".to_string()]
+}
+fn default_find_broken_links_keys() -> Vec<String> {
+    vec!["b".to_string()]
+}
+fn default_largest_files_keys() -> Vec<String> {
+    vec!["%".to_string()]
+}
+fn default_git_view_keys() -> Vec<String> {
+    vec![";".to_string()]
+}
+fn default_repo_root_keys() -> Vec<String> {
+    vec!["^".to_string()]
+}
+fn default_projects_keys() -> Vec<String> {
+    vec!["&".to_string()]
+}
+fn default_open_marked_keys() -> Vec<String> {
+    vec!["*".to_string()]
+}
+fn default_export_results_keys() -> Vec<String> {
+    vec![">".to_string()]
+}
+fn default_clipboard_history_keys() -> Vec<String> {
+    vec!["\"".to_string()]
+}
+fn default_saved_filters_keys() -> Vec<String> {
+    vec!["|".to_string()]
+}
+fn default_compare_keys() -> Vec<String> {
+    vec!["=".to_string()]
+}
+fn default_export_tree_keys() -> Vec<String> {
+    vec!["$".to_string()]
+}
+fn default_find_hardlinks_keys() -> Vec<String> {
+    vec!["~".to_string()]
+}
+fn default_layouts_keys() -> Vec<String> {
+    vec!["L".to_string()]
+}
+fn default_reveal_keys() -> Vec<String> {
+    vec!["T".to_string()]
+}
+fn default_fuzzy_jump_keys() -> Vec<String> {
+    vec!["F".to_string()]
+}
+fn default_live_filter_keys() -> Vec<String> {
+    vec!["\\".to_string()]
+}
+
+fn default_marks_keys() -> Vec<String> {
+    vec!["M".to_string()]
+}
+fn default_mark_results_keys() -> Vec<String> {
+    vec!["a".to_string()]
+}
+fn default_next_result_keys() -> Vec<String> {
+    vec!["n".to_string()]
+}
+fn default_previous_result_keys() -> Vec<String> {
+    vec!["N".to_string()]
+}
+fn default_root_at_result_keys() -> Vec<String> {
+    vec!["R".to_string()]
+}
+
+/// What a command's handler runs for: a pressed key, or the command named by a completed
+/// two-key sequence (which has no single key of its own)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    Key(KeyCode),
+    Command(&'static str),
+}
+
+impl From<KeyCode> for Trigger {
+    fn from(key: KeyCode) -> Self {
+        Trigger::Key(key)
+    }
+}
 
 impl KeybindingsConfig {
-    /// Check if a key matches any of the configured keys in the list
-    fn matches_key(&self, key: KeyCode, configured_keys: &[String]) -> bool {
+    /// Check if the trigger is the command `binding` or one of its configured keys
+    fn matches(&self, binding: &str, trigger: Trigger, configured_keys: &[String]) -> bool {
+        let key = match trigger {
+            Trigger::Key(key) => key,
+            Trigger::Command(command) => return command == binding,
+        };
         let key_str = match key {
+            KeyCode::Char(' ') => "Space".to_string(),
             KeyCode::Char(c) => c.to_string(),
             KeyCode::Esc => "Esc".to_string(),
             KeyCode::Enter => "Enter".to_string(),
@@ -323,57 +885,306 @@ impl KeybindingsConfig {
             _ => return false,
         };
 
-        configured_keys
-            .iter()
-            .any(|k| k.eq_ignore_ascii_case(&key_str))
+        // Letters are case-sensitive ("t" and "T" are different keys), key names aren't
+        configured_keys.iter().any(|k| match key {
+            KeyCode::Char(c) if c != ' ' => k.chars().eq(std::iter::once(c)),
+            _ => k.eq_ignore_ascii_case(&key_str),
+        })
+    }
+
+    pub fn is_search(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("search", trigger.into(), &self.search)
     }
 
-    pub fn is_search(&self, key: KeyCode) -> bool {
-        self.matches_key(key, &self.search)
+    pub fn is_toggle_files(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("toggle_files", trigger.into(), &self.toggle_files)
     }
 
-    pub fn is_toggle_files(&self, key: KeyCode) -> bool {
-        self.matches_key(key, &self.toggle_files)
+    pub fn is_toggle_help(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("toggle_help", trigger.into(), &self.toggle_help)
     }
 
-    pub fn is_toggle_help(&self, key: KeyCode) -> bool {
-        self.matches_key(key, &self.toggle_help)
+    pub fn is_copy_path(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("copy_path", trigger.into(), &self.copy_path)
     }
 
-    pub fn is_copy_path(&self, key: KeyCode) -> bool {
-        self.matches_key(key, &self.copy_path)
+    pub fn is_open_editor(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("open_editor", trigger.into(), &self.open_editor)
     }
 
-    pub fn is_open_editor(&self, key: KeyCode) -> bool {
-        self.matches_key(key, &self.open_editor)
+    pub fn is_open_file_manager(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("open_file_manager", trigger.into(), &self.open_file_manager)
     }
 
-    pub fn is_open_file_manager(&self, key: KeyCode) -> bool {
-        self.matches_key(key, &self.open_file_manager)
+    pub fn is_create_bookmark(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("create_bookmark", trigger.into(), &self.create_bookmark)
     }
 
-    pub fn is_create_bookmark(&self, key: KeyCode) -> bool {
-        self.matches_key(key, &self.create_bookmark)
+    pub fn is_select_bookmark(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("select_bookmark", trigger.into(), &self.select_bookmark)
     }
 
-    pub fn is_select_bookmark(&self, key: KeyCode) -> bool {
-        self.matches_key(key, &self.select_bookmark)
+    pub fn is_show_line_numbers(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("show_line_numbers", trigger.into(), &self.show_line_numbers)
     }
 
-    pub fn is_show_line_numbers(&self, key: KeyCode) -> bool {
-        self.matches_key(key, &self.show_line_numbers)
+    pub fn is_toggle_wrap(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("toggle_wrap", trigger.into(), &self.toggle_wrap)
     }
 
-    pub fn is_toggle_wrap(&self, key: KeyCode) -> bool {
-        self.matches_key(key, &self.toggle_wrap)
+    pub fn is_reload_file(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("reload_file", trigger.into(), &self.reload_file)
     }
 
-    pub fn is_visual_mode(&self, key: KeyCode) -> bool {
-        self.matches_key(key, &self.visual_mode)
+    pub fn is_visual_mode(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("visual_mode", trigger.into(), &self.visual_mode)
     }
 
-    pub fn is_visual_copy(&self, key: KeyCode) -> bool {
-        self.matches_key(key, &self.visual_copy)
+    pub fn is_visual_copy(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("visual_copy", trigger.into(), &self.visual_copy)
+    }
+
+    pub fn is_toggle_tasks(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("toggle_tasks", trigger.into(), &self.toggle_tasks)
+    }
+
+    pub fn is_toggle_mark(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("toggle_mark", trigger.into(), &self.toggle_mark)
+    }
+
+    pub fn is_yank(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("yank", trigger.into(), &self.yank)
+    }
+
+    pub fn is_cut(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("cut", trigger.into(), &self.cut)
+    }
+
+    pub fn is_paste(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("paste", trigger.into(), &self.paste)
+    }
+
+    pub fn is_goto_path(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("goto_path", trigger.into(), &self.goto_path)
+    }
+
+    pub fn is_message_log(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("message_log", trigger.into(), &self.message_log)
+    }
+
+    pub fn is_plugin_command(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("plugin_command", trigger.into(), &self.plugin_command)
+    }
+
+    pub fn is_quick_filter(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("quick_filter", trigger.into(), &self.quick_filter)
+    }
+
+    pub fn is_flat_listing(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("flat_listing", trigger.into(), &self.flat_listing)
+    }
+
+    pub fn is_flat_sort(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("flat_sort", trigger.into(), &self.flat_sort)
+    }
+
+    pub fn is_find_empty(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("find_empty", trigger.into(), &self.find_empty)
+    }
+
+    pub fn is_find_broken_links(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("find_broken_links", trigger.into(), &self.find_broken_links)
+    }
+
+    pub fn is_largest_files(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("largest_files", trigger.into(), &self.largest_files)
+    }
+
+    pub fn is_git_view(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("git_view", trigger.into(), &self.git_view)
+    }
+
+    pub fn is_repo_root(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("repo_root", trigger.into(), &self.repo_root)
+    }
+
+    pub fn is_projects(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("projects", trigger.into(), &self.projects)
+    }
+
+    pub fn is_open_marked(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("open_marked", trigger.into(), &self.open_marked)
+    }
+
+    pub fn is_export_results(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("export_results", trigger.into(), &self.export_results)
+    }
+
+    pub fn is_clipboard_history(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("clipboard_history", trigger.into(), &self.clipboard_history)
+    }
+
+    pub fn is_saved_filters(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("saved_filters", trigger.into(), &self.saved_filters)
+    }
+
+    pub fn is_compare(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("compare", trigger.into(), &self.compare)
+    }
+
+    pub fn is_export_tree(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("export_tree", trigger.into(), &self.export_tree)
+    }
+
+    pub fn is_find_hardlinks(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("find_hardlinks", trigger.into(), &self.find_hardlinks)
+    }
+
+    pub fn is_layouts(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("layouts", trigger.into(), &self.layouts)
+    }
+
+    pub fn is_reveal(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("reveal", trigger.into(), &self.reveal)
+    }
+
+    pub fn is_fuzzy_jump(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("fuzzy_jump", trigger.into(), &self.fuzzy_jump)
+    }
+
+    pub fn is_live_filter(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("live_filter", trigger.into(), &self.live_filter)
+    }
+
+    pub fn is_marks(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("marks", trigger.into(), &self.marks)
+    }
+
+    pub fn is_mark_results(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("mark_results", trigger.into(), &self.mark_results)
+    }
+
+    pub fn is_next_result(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("next_result", trigger.into(), &self.next_result)
+    }
+
+    pub fn is_previous_result(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("previous_result", trigger.into(), &self.previous_result)
+    }
+
+    pub fn is_root_at_result(&self, trigger: impl Into<Trigger>) -> bool {
+        self.matches("root_at_result", trigger.into(), &self.root_at_result)
+    }
+
+    /// Every command in `[keybindings]` by name, with its keys
+    pub fn bindings(&self) -> Vec<(&'static str, &[String])> {
+        vec![
+            ("quit", &self.quit),
+            ("search", &self.search),
+            ("toggle_files", &self.toggle_files),
+            ("toggle_help", &self.toggle_help),
+            ("copy_path", &self.copy_path),
+            ("open_editor", &self.open_editor),
+            ("open_file_manager", &self.open_file_manager),
+            ("create_bookmark", &self.create_bookmark),
+            ("select_bookmark", &self.select_bookmark),
+            ("show_line_numbers", &self.show_line_numbers),
+            ("toggle_wrap", &self.toggle_wrap),
+            ("reload_file", &self.reload_file),
+            ("visual_mode", &self.visual_mode),
+            ("visual_copy", &self.visual_copy),
+            ("toggle_tasks", &self.toggle_tasks),
+            ("toggle_mark", &self.toggle_mark),
+            ("yank", &self.yank),
+            ("cut", &self.cut),
+            ("paste", &self.paste),
+            ("goto_path", &self.goto_path),
+            ("message_log", &self.message_log),
+            ("plugin_command", &self.plugin_command),
+            ("quick_filter", &self.quick_filter),
+            ("flat_listing", &self.flat_listing),
+            ("flat_sort", &self.flat_sort),
+            ("find_empty", &self.find_empty),
+            ("find_broken_links", &self.find_broken_links),
+            ("largest_files", &self.largest_files),
+            ("git_view", &self.git_view),
+            ("repo_root", &self.repo_root),
+            ("projects", &self.projects),
+            ("open_marked", &self.open_marked),
+            ("export_results", &self.export_results),
+            ("clipboard_history", &self.clipboard_history),
+            ("saved_filters", &self.saved_filters),
+            ("compare", &self.compare),
+            ("export_tree", &self.export_tree),
+            ("find_hardlinks", &self.find_hardlinks),
+            ("layouts", &self.layouts),
+            ("reveal", &self.reveal),
+            ("fuzzy_jump", &self.fuzzy_jump),
+            ("live_filter", &self.live_filter),
+            ("marks", &self.marks),
+            ("mark_results", &self.mark_results),
+            ("next_result", &self.next_result),
+            ("previous_result", &self.previous_result),
+            ("root_at_result", &self.root_at_result),
+        ]
+    }
+
+    /// Keys bound to a command by its name in `[keybindings]` (for custom actions)
+    pub fn keys_for(&self, name: &str) -> Option<&[String]> {
+        self.bindings()
+            .into_iter()
+            .find(|&(binding, _)| binding == name)
+            .map(|(_, keys)| keys)
+    }
+}
+
+/// Steps of a custom action: one action name or a list run in order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ActionSteps {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl ActionSteps {
+    pub fn names(&self) -> &[String] {
+        match self {
+            ActionSteps::One(name) => std::slice::from_ref(name),
+            ActionSteps::Many(names) => names,
+        }
+    }
+}
+
+/// Settings given on the command line for one run (`--theme`, `--split`, ...),
+/// taking precedence over the config file
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    pub theme: Option<String>,
+    pub show_hidden: Option<bool>,
+    pub split_position: Option<u16>,
+    /// `--exclude` (given at all: these instead of `[search] exclude`) or `--no-exclude`
+    pub exclude: Option<Vec<String>>,
+    /// `--no-color` or `NO_COLOR`: the monochrome theme, whatever theme is chosen
+    pub no_color: bool,
+}
+
+impl Overrides {
+    fn apply(&self, config: &mut Config) {
+        if let Some(theme) = &self.theme {
+            config.appearance.theme = theme.clone();
+        }
+        if self.no_color {
+            config.appearance.theme = "monochrome".to_string();
+        }
+        if let Some(show_hidden) = self.show_hidden {
+            config.behavior.show_hidden = show_hidden;
+        }
+        if let Some(split_position) = self.split_position {
+            config.appearance.split_position = split_position;
+        }
+        if let Some(exclude) = &self.exclude {
+            config.search.exclude = exclude.clone();
+        }
     }
 }
 
@@ -386,8 +1197,27 @@ pub struct Config {
     #[serde(default)]
     pub behavior: BehaviorConfig,
 
+    #[serde(default)]
+    pub search: SearchConfig,
+
     #[serde(default)]
     pub keybindings: KeybindingsConfig,
+
+    /// Custom actions: key (e.g. "ctrl+g") -> built-in commands to run in order
+    #[serde(default)]
+    pub actions: BTreeMap<String, ActionSteps>,
+
+    /// Saved filters: name -> globs and `type:` entries, toggled with `|`
+    #[serde(default)]
+    pub filters: BTreeMap<String, Vec<String>>,
+
+    /// Bases of workspace-relative bookmarks: `@NAME/...` -> directory
+    #[serde(default)]
+    pub workspaces: BTreeMap<String, String>,
+
+    /// What upgrading an older config file changed, shown at startup
+    #[serde(skip)]
+    pub upgrade_notes: Vec<String>,
 }
 
 impl Config {
@@ -427,61 +1257,113 @@ impl Config {
     /// If config file doesn't exist, it will be created automatically with default values.
     /// If config file has parse errors, returns an error with details.
     pub fn load() -> anyhow::Result<Self> {
+        Self::load_with(&Overrides::default())
+    }
+
+    /// Load the configuration (see `load`) with command line settings applied
+    pub fn load_with(overrides: &Overrides) -> anyhow::Result<Self> {
+        match Self::global_config_path() {
+            Some(path) => Self::load_from(&path, overrides),
+            None => Ok(Config::default().with_overrides(overrides)),
+        }
+    }
+
+    /// Load the config file at `path` like `load_with`, upgrading an older one
+    pub fn load_from(path: &Path, overrides: &Overrides) -> anyhow::Result<Self> {
         let mut config = Config::default();
 
-        // Get global config path
-        if let Some(global_path) = Self::global_config_path() {
-            // Create config file if it doesn't exist
-            if !global_path.exists() {
-                // Silently create default config file
-                let _ = Self::create_default_file(&global_path);
-            }
+        // Create config file if it doesn't exist
+        if !path.exists() {
+            // Silently create default config file
+            let _ = Self::create_default_file(path);
+        }
 
-            // Load config from file
-            if global_path.exists() {
-                match Self::from_file(&global_path) {
-                    Ok(global_config) => {
-                        config = global_config;
-                    }
-                    Err(e) => {
-                        // Return error with detailed message
-                        anyhow::bail!(
-                            "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\
-                            ⚠  Configuration file error!\n\
-                            ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\
-                            \n\
-                            Config file: {}\n\
-                            \n\
-                            Error details:\n\
-                            {:#}\n\
-                            \n\
-                            To fix:\n\
-                              1. Edit the config file and fix the syntax error\n\
-                              2. Or delete the file - it will be recreated with defaults\n\
-                            \n\
-                            ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━",
-                            global_path.display(),
-                            e
-                        );
-                    }
+        // Rename keys of an older version first (a failed upgrade only warns)
+        let upgrade_notes = if path.exists() {
+            config_migration::upgrade_file(path)
+                .unwrap_or_else(|e| vec![format!("Config upgrade failed: {:#}", e)])
+        } else {
+            Vec::new()
+        };
+
+        // Load config from file
+        if path.exists() {
+            match Self::from_file(path) {
+                Ok(global_config) => {
+                    config = global_config;
+                    config.upgrade_notes = upgrade_notes;
+                }
+                Err(e) => {
+                    // Return error with detailed message
+                    anyhow::bail!(
+                        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\
+                        ⚠  Configuration file error!\n\
+                        ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\
+                        \n\
+                        Config file: {}\n\
+                        \n\
+                        Error details:\n\
+                        {:#}\n\
+                        \n\
+                        To fix:\n\
+                          1. Edit the config file and fix the syntax error\n\
+                          2. Or delete the file - it will be recreated with defaults\n\
+                        \n\
+                        ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━",
+                        path.display(),
+                        e
+                    );
                 }
             }
         }
 
-        // Apply color resolution:
-        // 1. Use explicitly set color from config file (if Some)
-        // 2. Otherwise, use preset theme color (if theme is set and preset has color)
-        // 3. Otherwise, use fallback default color
-        let preset = ThemeConfig::get_preset_theme(&config.appearance.theme);
+        Ok(config.with_overrides(overrides))
+    }
+
+    /// Apply the command line settings, then resolve the theme into colors
+    fn with_overrides(mut self, overrides: &Overrides) -> Self {
+        // Before the theme is resolved into colors
+        overrides.apply(&mut self);
+        if self.appearance.theme.eq_ignore_ascii_case("auto") {
+            self.resolve_auto_theme(background::detect());
+        }
+        self.resolve_colors();
+        self
+    }
+
+    /// Replace `theme = "auto"` with the preset for the terminal background: "light"
+    /// on light terminals (with the light syntax theme unless one was chosen),
+    /// "default" otherwise
+    pub fn resolve_auto_theme(&mut self, background: Option<Background>) {
+        if !self.appearance.theme.eq_ignore_ascii_case("auto") {
+            return;
+        }
+        if background == Some(Background::Light) {
+            self.appearance.theme = "light".to_string();
+            if self.appearance.syntax_theme == default_syntax_theme() {
+                self.appearance.syntax_theme = "base16-ocean.light".to_string();
+            }
+        } else {
+            self.appearance.theme = "default".to_string();
+        }
+    }
+
+    /// Fill in colors not set explicitly (done by load(); idempotent):
+    /// 1. Use explicitly set color from config file (if Some)
+    /// 2. Otherwise, use preset theme color (if theme is set and preset has color)
+    /// 3. Otherwise, use fallback default color
+    pub fn resolve_colors(&mut self) {
+        let preset = ThemeConfig::get_preset_theme(&self.appearance.theme);
         let fallback = ThemeConfig::fallback_colors();
 
         // Helper macro to apply color resolution
         macro_rules! resolve_color {
             ($field:ident) => {
-                config.appearance.colors.$field = config
+                self.appearance.colors.$field = self
                     .appearance
                     .colors
                     .$field
+                    .take()
                     .or_else(|| preset.as_ref().and_then(|p| p.$field.clone()))
                     .or_else(|| fallback.$field.clone());
             };
@@ -490,6 +1372,8 @@ impl Config {
         resolve_color!(selected_color);
         resolve_color!(directory_color);
         resolve_color!(file_color);
+        resolve_color!(executable_color);
+        resolve_color!(permission_warning_color);
         resolve_color!(border_color);
         resolve_color!(error_color);
         resolve_color!(highlight_color);
@@ -499,9 +1383,8 @@ impl Config {
         resolve_color!(tree_cursor_bg_color);
         resolve_color!(main_border_color);
         resolve_color!(panel_border_color);
+        resolve_color!(focus_border_color);
         resolve_color!(background_color);
-
-        Ok(config)
     }
 
     /// Create a default config file with comments
@@ -510,24 +1393,44 @@ impl Config {
         let editor = default_editor();
         let file_manager = default_file_manager();
         let hex_editor = default_hex_editor();
+        let preview_skip = default_preview_skip()
+            .iter()
+            .map(|path| format!("{:?}", path))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let search_exclude = default_search_exclude()
+            .iter()
+            .map(|name| format!("{:?}", name))
+            .collect::<Vec<_>>()
+            .join(", ");
 
         let default_config = format!(
-            r#"# dtree configuration file
+            r##"# dtree configuration file
 # This file uses TOML format: https://toml.io
 
+# Format version: older files are upgraded at startup (the old file is kept as
+# config.toml.v<N>.bak)
+config_version = {config_version}
+
 [appearance]
 # Theme name - preset color schemes
 # Available themes:
+#   "auto"       - "light" on light terminal backgrounds, "default" otherwise
+#                  (asks the terminal for its background, or uses $COLORFGBG)
 #   "default"    - Classic terminal colors (blue dirs, cyan selection)
 #   "gruvbox"    - Warm, high contrast theme inspired by Gruvbox
 #   "nord"       - Cold, muted colors inspired by Nord theme
 #   "tokyonight" - Modern dark theme with vibrant colors
 #   "dracula"    - Popular dark theme with high contrast
 #   "obsidian"   - Dark theme inspired by Obsidian app with subtle cursor
+#   "light"      - Dark text for light terminal backgrounds
+#   "high-contrast" - Bright colors on black with a yellow cursor bar
+#   "deuteranopia"  - Blue/orange palette without red/green distinctions
+#   "monochrome" - No colors at all: bold, reverse video, underlines and markers
 #
 # You can override individual colors in [appearance.colors] section below
 # Preset themes provide a good starting point with harmonious color palettes
-theme = "default"
+theme = "auto"
 
 # Show file type icons (requires nerd fonts)
 show_icons = false
@@ -535,17 +1438,64 @@ show_icons = false
 # Split position for file viewer (20-80, percentage)
 split_position = 20
 
+# Remember split positions changed with Ctrl+arrows or by dragging the dividers
+# and restore them on the next run (saved in the state directory, layout.toml)
+# split_position above is used until the layout has been changed once
+remember_layout = true
+
+# Show "dtree – <current root>" in the terminal window title (restored on exit)
+terminal_title = false
+
 # Show line numbers in fullscreen viewer by default (toggle with 'l' key)
 show_line_numbers = false
 
+# Mark search matches and the visible part of the file in a column on the
+# right of the fullscreen viewer
+show_minimap = true
+
 # Enable syntax highlighting for code files
 enable_syntax_highlighting = true
 
-# Syntax highlighting theme
+# Syntax highlighting theme ("auto" switches it to "base16-ocean.light" on
+# light terminals while it's left at the default)
 # Available themes: "base16-ocean.dark", "base16-ocean.light", "InspiredGitHub",
 #                   "Solarized (dark)", "Solarized (light)", "Monokai Extended"
 syntax_theme = "base16-ocean.dark"
 
+# How hex colors (themes, syntax highlighting) are sent to the terminal:
+#   "auto"      - the nearest of the 256 palette colors on terminals known to
+#                 lack truecolor (Apple Terminal, Linux console, GNU screen),
+#                 truecolor everywhere else
+#   "truecolor" - always 24-bit colors
+#   "256"       - always the 256-color palette (for terminals that garble RGB)
+#   "monochrome" - no colors (the "monochrome" theme sets this)
+color_mode = "auto"
+
+# Sizes in the size column (z) and the file info line:
+#   "binary" - powers of 1024 (1.5K, 1.50 KB)
+#   "si"     - powers of 1000 (1.5K, 1.50 kB)
+size_units = "binary"
+
+# Decimals of sizes (default: 1 in the size column, 2 in the file info line)
+# size_decimals = 1
+
+# Modification times in the file info line and the binary file details:
+#   "iso"      - 2026-10-16 14:03 (local time)
+#   "relative" - 5 min ago, 3 d ago
+#   "locale"   - the date and time format of your locale (LC_TIME)
+date_format = "iso"
+
+# Language of titles, hints and messages: "auto" follows LC_ALL/LC_MESSAGES/LANG
+# Built in: "en", "de"; add or override translations in locales/<language>.toml
+# next to this file (English text = "translation", see the docs)
+locale = "auto"
+
+# Flag setuid/setgid and world-writable entries in the tree (Unix)
+# They get permission_warning_color and a [setuid], [setgid] or [o+w] marker;
+# world-writable directories with the sticky bit (like /tmp) are not flagged
+# Executable files always use executable_color
+permission_warnings = false
+
 # Custom theme colors
 # These colors override the preset theme colors above
 # By default, all colors are commented out to use the preset theme
@@ -560,6 +1510,8 @@ syntax_theme = "base16-ocean.dark"
 # selected_color = "cyan"           # Color for selected item text
 # directory_color = "gray"          # Color for directory names
 # file_color = "white"              # Color for file names
+# executable_color = "green"        # Color for executable files
+# permission_warning_color = "lightred"  # Setuid/setgid and world-writable entries
 # border_color = "gray"             # Color for UI borders
 # error_color = "red"               # Color for error messages
 # highlight_color = "yellow"        # Color for fuzzy search character highlighting (directory search)
@@ -569,26 +1521,84 @@ syntax_theme = "base16-ocean.dark"
 # tree_cursor_bg_color = "dim"      # Cursor background for tree ("dim" = no background color)
 # main_border_color = "gray"        # Main window border color
 # panel_border_color = "cyan"       # Panel borders (search, bookmarks)
+# focus_border_color = "cyan"       # Border of the focused pane (Tab/Shift+Tab)
 # background_color = "reset"        # Background color ("reset" = terminal default)
 
 [behavior]
 # Maximum number of lines to read from files
 max_file_lines = 10000
 
+# Files longer than max_file_lines show this many lines from their start and as
+# many from their end, with "[... N lines omitted ...]" in between
+# 0 = show the start only (End switches to the end)
+head_tail_lines = 0
+
+# Files larger than this (in MB) aren't previewed, only their size is shown,
+# so selecting a core dump or a huge log never stalls the UI (0 = no limit)
+max_preview_size_mb = 100
+
+# Bytes read from the start of a file to tell binary files from text
+binary_sniff_bytes = 8192
+
+# Paths never previewed: selecting an entry at or below one of them doesn't
+# read or even stat it (pseudo filesystems, slow network mounts, ...)
+# FIFOs, sockets and devices are never opened wherever they are
+# preview_skip = ["/proc", "/sys", "/dev", "~/mnt/nas"]
+preview_skip = [{preview_skip}]
+
+# Previews load in the background: moving the selection only starts loading once
+# it rests for this many milliseconds, and a newer selection cancels the load
+preview_debounce_ms = 30
+
+# Recently read files stay in memory, so going back to one doesn't read it again
+# (a changed file is always read again; 0 = no cache)
+preview_cache_size = 16
+
 # Show hidden files (dotfiles)
 show_hidden = true
 
 # Follow symbolic links
 follow_symlinks = true
 
+# Stay on one filesystem (like `find -xdev` / `du -x`)
+# true  = Don't descend into mountpoints (/proc, /sys, network mounts) when
+#         expanding the tree, deep searching or calculating directory sizes
+# false = Cross filesystem boundaries freely (default)
+# Mountpoints are marked with [mnt] in the tree either way
+one_file_system = false
+
+# Entries ignored by git (.gitignore, .git/info/exclude, global excludes)
+# "show" = Like any other entry (default)
+# "hide" = Leave them out of the tree
+# "dim"  = Keep them visible but dimmed, so build outputs don't compete with sources
+gitignore = "show"
+
+# Search results kept at most: a broad query matching more shows
+# "+N more (refine your query)" under them instead of holding every match
+# (fuzzy searches keep the best ones; 0 = no limit)
+max_search_results = 10000
+
 # Double-click timeout in milliseconds
 double_click_timeout_ms = 500
 
+# How long the first key of a two-key sequence like "space f" waits for the
+# second one before it does what it does alone (milliseconds)
+key_sequence_timeout_ms = 1000
+
+# After this many minutes without a key or mouse input (0 = never), e.g. when
+# dtree is left open on a shared machine:
+# "blank" = Hide the tree, preview and panels until a key is pressed
+# "exit"  = Quit dtree
+idle_timeout_minutes = 0
+idle_action = "blank"
+
 # External editor for opening files (press 'e' to open)
 # Platform-specific defaults: Unix: $EDITOR or nano, Windows: VS Code or Notepad
 # Popular options:
 #   - Terminal editors: "nvim", "vim", "nano", "emacs", "micro", "helix"
-#   - GUI editors (if terminal wrapper available): "code", "subl", "gedit"
+#   - GUI editors: "code", "subl", "gedit" (opened without leaving dtree)
+# {{path}} and {{line}} place the file and the viewer's line, e.g. "code --wait -g {{path}}:{{line}}";
+# known editors ("nvim", "hx", "code", ...) get the line without a template
 editor = "{}"
 
 # External file manager (press 'o' to open)
@@ -613,36 +1623,167 @@ file_manager = "{}"
 #   - "hd"      - Alias for hexdump -C
 hex_editor = "{}"
 
+# GUI programs not recognized as such (code, subl, gedit, nautilus, xdg-open, ... are)
+# They are started in the background while dtree keeps running
+gui_programs = []
+
+# What happens when the editor, hex editor or file manager exits
+# true  = Come back to dtree with the same tree, selection and viewer
+# false = dtree has exited to run it (default)
+return_after_editor = false
+
+# Open the editor in a terminal multiplexer split, next to the tree
+# "off"     = Suspend dtree and run the editor in this terminal (default)
+# "auto"    = Use tmux or wezterm when dtree runs inside one of them
+# "tmux"    = Always use `tmux split-window` (only inside tmux)
+# "wezterm" = Always use `wezterm cli split-pane` (only inside wezterm)
+# Outside a multiplexer the editor opens normally
+open_in_split = "off"
+
+# Where the split pane opens: "right" or "below"
+split_direction = "right"
+
+# Neovim remote: when dtree runs inside Neovim's :terminal ($NVIM is set),
+# 'e' opens text files in that Neovim (`nvim --server $NVIM --remote`)
+# instead of starting a nested editor
+nvim_remote = true
+
+# What Enter does on a file (Enter on a directory always goes inside)
+# "none"    = Nothing (default)
+# "preview" = Open the fullscreen viewer (same as 'v')
+# "editor"  = Open in the external editor (same as 'e')
+# "open"    = Open with the system handler (xdg-open / open / start)
+# "select"  = Print the file path and exit
+enter_file_action = "none"
+
 # Wrap long lines in file viewer (press 'w' to toggle in fullscreen mode)
 # true  = Wrap long lines at word boundaries (default, better for reading text)
 # false = Truncate long lines with "..." indicator (better for code with long lines)
 wrap_lines = true
 
+# Read the shown file again when it changes on disk, like `tail -f` when the
+# viewer is at the end (press 'r' in the viewer to reload by hand)
+auto_reload = false
+
+# Going back to a file shows it the way it was left (scroll position, head/tail,
+# wrapping, line numbers); true keeps this between runs as well
+# (saved in the state directory, viewer_state.toml)
+remember_viewer_state = false
+
 # Number of lines to scroll with mouse wheel in file viewer mode
 # Default: 5 (balanced speed for comfortable scrolling)
 # Decrease to 1-2 for precise control, increase to 10+ for faster scrolling through large files
 mouse_scroll_lines = 5
 
+# Where export_results (> with search results shown) writes `path:line:col: text` lines
+# for vim's :cfile or nvim -q; empty = print them to stdout when dtree exits
+# quickfix_file = "~/.cache/dtree/quickfix.txt"
+quickfix_file = ""
+
+# Where the tree export ($) writes the visible tree when not copying it;
+# empty = print it to stdout when dtree exits
+# tree_export_file = "~/tree.md"
+tree_export_file = ""
+
+# Exiting with q while entries are marked prints the marked paths, one per line,
+# instead of the directory to cd into (e.g. dtree | xargs -d '\n' wc -l)
+# Same as starting with --print-marked
+print_marked_on_exit = false
+
+# Copied paths and snippets the clipboard history (") keeps for copying again
+# (this run only; 0 = no history)
+clipboard_history_size = 20
+
+[search]
+# Directories deep searches, scans and directory sizes don't go into: globs
+# matched against each directory's name ("*.egg-info"). An excluded directory is
+# still found, only what's in it is left out, and a size leaving one out is
+# partial. What the tree shows is always searched, and searching from inside one
+# still works. --exclude NAME (repeatable) replaces the list for one run,
+# --no-exclude empties it
+exclude = [{search_exclude}]
+
 [keybindings]
 # Key bindings (each can have multiple keys)
+# Two-key sequences are written with a space, e.g. search = ["/", "space f"]:
+# the first key then shows what can follow and waits for the second
 quit = ["q", "Esc"]
 search = ["/"]
 toggle_files = ["s"]
 toggle_help = ["i"]
-copy_path = ["c"]
+copy_path = ["c"]            # Copy path menu (press twice to copy absolute path)
 open_editor = ["e"]
 open_file_manager = ["o"]
 create_bookmark = ["m"]
 select_bookmark = ["'"]
 show_line_numbers = ["l"]
 toggle_wrap = ["w"]
+reload_file = ["r"]          # Viewer: read the file again (fullscreen or focused)
+toggle_tasks = ["t"]         # Show/hide background task overlay (1-9 cancels a task)
+toggle_mark = ["Space"]      # Mark/unmark entry for bulk yank/cut
+yank = ["y"]                 # Yank marked/selected entries (copy on paste)
+cut = ["x"]                  # Cut marked/selected entries (move on paste)
+paste = ["p"]                # Paste into directory under cursor
+goto_path = ["g", ":"]       # Go to path (Tab completes)
+message_log = ["!"]          # Log of actions, errors and notifications
+plugin_command = ["@"]       # Run a plugin command (Tab completes)
+quick_filter = ["f"]         # Cycle quick filters: dirs/code/images/docs
+flat_listing = ["a"]         # Flat list of all files under the root
+flat_sort = ["#"]            # Sort flat list: name/size/modified
+find_empty = ["0"]           # List empty directories (and empty files with files shown)
+find_broken_links = ["b"]    # List broken symlinks under the root
+largest_files = ["%"]        # Top 20 largest files under the selected directory
+git_view = [";"]             # Viewer: git log -> git blame -> preview
+repo_root = ["^"]            # Go to the git repository root
+projects = ["&"]             # Jump to a project around the root
+open_marked = ["*"]          # Open marked files
+export_results = [">"]       # Export search results (path:line:col: text)
+clipboard_history = ["\""]   # Clipboard history: copy an earlier path again
+saved_filters = ["|"]        # Toggle a saved filter ([filters] below)
+compare = ["="]              # Compare the root with a bookmark (again: back to the tree)
+export_tree = ["$"]          # Export the visible tree (text/Markdown)
+find_hardlinks = ["~"]       # List hard links to the selected file
+layouts = ["L"]              # Named layouts: restore one, or save the current one
+reveal = ["T"]               # Collapse the tree to the selection (viewer, results too)
+fuzzy_jump = ["F"]           # Fuzzy find among the loaded entries, with a preview
+live_filter = ["\\"]         # Narrow the tree as you type (Enter keeps it, Esc clears)
+marks = ["M"]                # Review the marks, those outside the root too
+mark_results = ["a"]         # Results panel: add the marked results to the tree's marks
+next_result = ["n"]          # Next search result, shown in the tree (from any pane)
+previous_result = ["N"]      # Previous search result
+root_at_result = ["R"]       # Results panel: root the tree at the result's directory
 
 # Visual selection mode (fullscreen viewer only)
 # Enter visual mode to select multiple lines with keyboard
-visual_mode = ["V"]          # Enter/exit visual selection mode (Shift+V)
+visual_mode = ["V", "v"]     # Enter/exit visual selection mode (Shift+V or v)
 visual_copy = ["y", "Y"]     # Copy selected lines to clipboard and exit
-"#,
-            editor, file_manager, hex_editor
+
+[actions]
+# Custom actions: bind a key (with optional ctrl+/alt+/shift+) to built-in
+# commands run in order. Commands are named like the keybindings above, plus
+# "cd_bookmark:NAME", "goto:PATH", "layout:NAME" and "plugin:NAME". They
+# override built-in keys.
+# "ctrl+g" = ["copy_path", "open_file_manager"]
+# "ctrl+w" = "cd_bookmark:work"
+
+[filters]
+# Saved filters, toggled from the picker ("|"): a file stays in the tree when it
+# matches any entry. Globs with a "/" match the path below the root ("**" spans
+# directories, a leading "/" is the root), others the file name anywhere;
+# "type:code", "type:images" and "type:documents" add a quick filter's files
+# rust-src = ["src/**/*.rs", "/Cargo.*"]
+# media = ["type:images", "*.mp4"]
+
+[workspaces]
+# Bases of workspace-relative bookmarks ("dt -bm add NAME @src/dtree/docs"), so
+# one bookmarks file works on machines with the code in different places;
+# "@repo" is always the git repository around the current directory
+# src = "~/src"
+"##,
+            editor,
+            file_manager,
+            hex_editor,
+            config_version = config_migration::CONFIG_VERSION
         );
 
         // Create parent directory if it doesn't exist
@@ -667,8 +1808,90 @@ mod tests {
     fn test_default_config() {
         let config = Config::default();
         assert_eq!(config.appearance.split_position, 20);
+        assert!(!config.appearance.permission_warnings);
+        assert!(config.appearance.remember_layout);
         assert_eq!(config.behavior.max_file_lines, 10000);
         assert!(config.behavior.show_hidden);
+        assert!(!config.behavior.one_file_system);
+        assert_eq!(config.behavior.gitignore, "show");
+        assert!(!config.behavior.return_after_editor);
+        assert_eq!(config.behavior.open_in_split, "off");
+        assert!(config.behavior.nvim_remote);
+        assert_eq!(config.behavior.enter_file_action, "none");
+        assert_eq!(config.search.exclude, ["node_modules", ".git", "target"]);
+    }
+
+    #[test]
+    fn test_default_file_parses() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        Config::create_default_file(&path).unwrap();
+        let config = Config::from_file(&path).unwrap();
+        assert_eq!(config.behavior.preview_skip, default_preview_skip());
+        assert_eq!(config.behavior.editor, default_editor());
+        assert_eq!(config.search.exclude, default_search_exclude());
+    }
+
+    #[test]
+    fn test_letter_keys_are_case_sensitive() {
+        let keybindings = KeybindingsConfig::default();
+        assert!(keybindings.is_toggle_tasks(KeyCode::Char('t')));
+        assert!(!keybindings.is_toggle_tasks(KeyCode::Char('T')));
+        assert!(keybindings.is_reveal(KeyCode::Char('T')));
+        assert!(!keybindings.is_reveal(KeyCode::Char('t')));
+        assert!(keybindings.is_marks(KeyCode::Char('M')));
+        assert!(!keybindings.is_create_bookmark(KeyCode::Char('M')));
+        assert!(keybindings.is_next_result(KeyCode::Char('n')));
+        assert!(keybindings.is_previous_result(KeyCode::Char('N')));
+        // Both keys still enter visual mode, as before letters were case-sensitive
+        assert!(keybindings.is_visual_mode(KeyCode::Char('V')));
+        assert!(keybindings.is_visual_mode(KeyCode::Char('v')));
+        // Key names match in any case
+        assert!(keybindings.is_toggle_mark(KeyCode::Char(' ')));
+        let keybindings = KeybindingsConfig {
+            toggle_files: vec!["pagedown".to_string()],
+            ..KeybindingsConfig::default()
+        };
+        assert!(keybindings.is_toggle_files(KeyCode::PageDown));
+    }
+
+    #[test]
+    fn test_commands_match_their_binding_only() {
+        let keybindings = KeybindingsConfig::default();
+        assert!(keybindings.is_search(Trigger::Command("search")));
+        assert!(!keybindings.is_search(Trigger::Command("marks")));
+        assert!(keybindings.is_marks(Trigger::Command("marks")));
+    }
+
+    #[test]
+    fn test_auto_theme_follows_background() {
+        let mut config = Config::default();
+        assert_eq!(config.appearance.theme, "auto");
+        config.resolve_auto_theme(Some(Background::Light));
+        assert_eq!(config.appearance.theme, "light");
+        assert_eq!(config.appearance.syntax_theme, "base16-ocean.light");
+
+        // Unknown backgrounds keep the dark defaults; a chosen syntax theme stays
+        let mut config = Config::default();
+        config.resolve_auto_theme(None);
+        assert_eq!(config.appearance.theme, "default");
+        assert_eq!(config.appearance.syntax_theme, "base16-ocean.dark");
+        let mut config = Config::default();
+        config.appearance.syntax_theme = "Monokai Extended".to_string();
+        config.resolve_auto_theme(Some(Background::Light));
+        assert_eq!(config.appearance.syntax_theme, "Monokai Extended");
+    }
+
+    #[test]
+    fn test_no_color_wins_over_theme() {
+        let mut config = Config::default();
+        let overrides = Overrides {
+            theme: Some("nord".to_string()),
+            no_color: true,
+            ..Overrides::default()
+        };
+        overrides.apply(&mut config);
+        assert_eq!(config.appearance.theme, "monochrome");
     }
 
     #[test]
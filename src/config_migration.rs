@@ -0,0 +1,237 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{value, DocumentMut, Item, Key, Table};
+
+/// Version written by this dtree; config files without `config_version` are version 1
+pub const CONFIG_VERSION: u32 = 2;
+
+/// A key that was renamed or moved to another section ("section.key")
+struct Moved {
+    from: &'static str,
+    to: &'static str,
+}
+
+/// Changes made by each version, oldest first
+const MIGRATIONS: &[(u32, &[Moved])] = &[(
+    // Each view setting lives in one section; the same name in the other one was ignored
+    2,
+    &[
+        Moved {
+            from: "appearance.wrap_lines",
+            to: "behavior.wrap_lines",
+        },
+        Moved {
+            from: "appearance.show_hidden",
+            to: "behavior.show_hidden",
+        },
+        Moved {
+            from: "behavior.show_icons",
+            to: "appearance.show_icons",
+        },
+        Moved {
+            from: "behavior.show_line_numbers",
+            to: "appearance.show_line_numbers",
+        },
+    ],
+)];
+
+/// `config_version` of a config file
+pub fn version(content: &str) -> u32 {
+    content
+        .parse::<toml::Table>()
+        .ok()
+        .and_then(|table| table.get("config_version")?.as_integer())
+        .and_then(|version| u32::try_from(version).ok())
+        .unwrap_or(1)
+}
+
+/// Upgrade an older config to the current version, keeping its comments and layout
+/// None when the config is current (or doesn't parse, left for the usual error message);
+/// otherwise the new text and a note per changed key
+pub fn migrate(content: &str) -> Option<(String, Vec<String>)> {
+    let from_version = version(content);
+    if from_version >= CONFIG_VERSION {
+        return None;
+    }
+    let mut document = content.parse::<DocumentMut>().ok()?;
+    let mut notes = Vec::new();
+
+    for (version, moves) in MIGRATIONS {
+        if *version <= from_version {
+            continue;
+        }
+        for moved in *moves {
+            if let Some(note) = apply(&mut document, moved) {
+                notes.push(note);
+            }
+        }
+    }
+
+    set_version(&mut document);
+    Some((document.to_string(), notes))
+}
+
+/// Upgrade the config file at `path` in place, saving the old one next to it
+/// Returns what changed (nothing when the file is up to date)
+pub fn upgrade_file(path: &Path) -> Result<Vec<String>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+    let Some((text, mut notes)) = migrate(&content) else {
+        return Ok(Vec::new());
+    };
+
+    let backup = backup_path(path, version(&content));
+    fs::copy(path, &backup)
+        .with_context(|| format!("Failed to back up config file to {}", backup.display()))?;
+    fs::write(path, text)
+        .with_context(|| format!("Failed to write config file: {}", path.display()))?;
+    notes.push(format!(
+        "Config upgraded to version {}, the old one is in {}",
+        CONFIG_VERSION,
+        backup.display()
+    ));
+    Ok(notes)
+}
+
+/// `config.toml.v1.bak` next to `config.toml`
+fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".v{}.bak", version));
+    path.with_file_name(name)
+}
+
+/// The `[section]` table of the config
+fn section<'a>(document: &'a mut DocumentMut, name: &str) -> Option<&'a mut Table> {
+    document.get_mut(name)?.as_table_mut()
+}
+
+fn apply(document: &mut DocumentMut, moved: &Moved) -> Option<String> {
+    let (from_section, from_key) = moved.from.rsplit_once('.')?;
+    let (to_section, to_key) = moved.to.rsplit_once('.')?;
+    if !section(document, from_section)?.contains_key(from_key) {
+        return None;
+    }
+    if section(document, to_section).is_some_and(|table| table.contains_key(to_key)) {
+        return Some(format!(
+            "{} is ignored, {} is set as well",
+            moved.from, moved.to
+        ));
+    }
+
+    let (key, item) = section(document, from_section)?.remove_entry(from_key)?;
+    // The comment lines above the key go along with it
+    let key = Key::new(to_key).with_leaf_decor(key.leaf_decor().clone());
+    let target = document
+        .entry(to_section)
+        .or_insert_with(|| Item::Table(Table::new()))
+        .as_table_mut()?;
+    target.insert_formatted(&key, item);
+    Some(format!("{} is now {}", moved.from, moved.to))
+}
+
+/// Set `config_version` to the current version, above the first section
+fn set_version(document: &mut DocumentMut) {
+    let has_top_level_keys = document.iter().any(|(_, item)| item.is_value());
+    document["config_version"] = value(i64::from(CONFIG_VERSION));
+    if has_top_level_keys {
+        return;
+    }
+
+    // The comments heading the file stay above the version
+    let first_section = document
+        .iter_mut()
+        .filter_map(|(_, item)| item.as_table_mut())
+        .min_by_key(|table| table.position().unwrap_or(usize::MAX));
+    let Some(first_section) = first_section else {
+        return;
+    };
+    let heading = first_section
+        .decor()
+        .prefix()
+        .and_then(|prefix| prefix.as_str())
+        .unwrap_or_default()
+        .to_string();
+    first_section.decor_mut().set_prefix("\n");
+    if let Some(mut key) = document.key_mut("config_version") {
+        key.leaf_decor_mut().set_prefix(heading);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    const OLD: &str = "# My config\n\
+        [appearance]\n\
+        wrap_lines = false  # no wrapping\n\
+        show_icons = true\n\
+        \n\
+        [behavior]\n\
+        max_file_lines = 50\n\
+        show_icons = false\n";
+
+    #[test]
+    fn test_migrate_moves_keys() {
+        let (text, notes) = migrate(OLD).unwrap();
+        assert_eq!(
+            notes,
+            [
+                "appearance.wrap_lines is now behavior.wrap_lines",
+                "behavior.show_icons is ignored, appearance.show_icons is set as well",
+            ]
+        );
+        assert_eq!(version(&text), CONFIG_VERSION);
+        assert!(text.starts_with("# My config\nconfig_version = 2\n\n[appearance]\n"));
+        assert!(text.ends_with("show_icons = false\nwrap_lines = false  # no wrapping\n"));
+
+        let config: Config = toml::from_str(&text).unwrap();
+        assert!(!config.behavior.wrap_lines);
+        assert_eq!(config.behavior.max_file_lines, 50);
+
+        // Nothing to do the second time
+        assert!(migrate(&text).is_none());
+    }
+
+    #[test]
+    fn test_migrate_stamps_every_old_config() {
+        // A line of a multi-line array is no section header
+        let old = "[search]\nexclude = [\n    \"[appearance]\",\n]\n\n\
+            [appearance]\nwrap_lines = true\n";
+        let (text, notes) = migrate(old).unwrap();
+        assert_eq!(notes, ["appearance.wrap_lines is now behavior.wrap_lines"]);
+        let config: Config = toml::from_str(&text).unwrap();
+        assert!(config.behavior.wrap_lines);
+        assert_eq!(config.search.exclude, ["[appearance]"]);
+
+        // An old config with nothing to move is still brought to the current version
+        let (text, notes) = migrate("[behavior]\nmax_file_lines = 50\n").unwrap();
+        assert!(notes.is_empty());
+        assert_eq!(
+            text,
+            "config_version = 2\n\n[behavior]\nmax_file_lines = 50\n"
+        );
+        assert!(migrate(&text).is_none());
+    }
+
+    #[test]
+    fn test_upgrade_file_keeps_a_backup() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        fs::write(&path, OLD).unwrap();
+
+        let notes = upgrade_file(&path).unwrap();
+        assert_eq!(notes.len(), 3);
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("config.toml.v1.bak")).unwrap(),
+            OLD
+        );
+        assert_eq!(version(&fs::read_to_string(&path).unwrap()), 2);
+        assert!(upgrade_file(&path).unwrap().is_empty());
+
+        // A new config file is current
+        Config::create_default_file(&path).unwrap();
+        assert_eq!(version(&fs::read_to_string(&path).unwrap()), CONFIG_VERSION);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::file_viewer::FileViewer;
+use ratatui::text::Span;
+use std::ops::Range;
+use std::path::Path;
+
+/// Bytes of a long line kept around the match
+const SNIPPET_LEN: usize = 200;
+
+/// Bytes of a long line kept before the match
+const SNIPPET_CONTEXT: usize = 40;
+
+/// Line of a file matching a content search
+#[derive(Debug, Clone)]
+pub struct ContentMatch {
+    /// Line number (1-based)
+    pub line: usize,
+    /// Byte column of the match in the line (1-based, like vim's quickfix)
+    pub column: usize,
+    /// Byte range of the match in the snippet
+    pub range: Range<usize>,
+    /// The line, trimmed and cut down to SNIPPET_LEN around the match, with syntax
+    /// highlighting (a single plain span when it's off)
+    pub spans: Vec<Span<'static>>,
+}
+
+impl ContentMatch {
+    /// Match at byte `range` of `text`, line `line` (1-based) of the file at `path`
+    /// The snippet is highlighted with `syntax_theme` by the file's syntax, if given
+    #[allow(dead_code)] // Library API; dtree itself has no content search yet
+    pub fn new(
+        path: &Path,
+        line: usize,
+        text: &str,
+        range: Range<usize>,
+        syntax_theme: Option<&str>,
+    ) -> Self {
+        let column = range.start + 1;
+        let (text, range) = snippet(text, range);
+        let spans = match syntax_theme {
+            Some(theme) => FileViewer::highlight_line(path, &text, theme),
+            None => vec![Span::raw(text)],
+        };
+        Self {
+            line,
+            column,
+            range,
+            spans,
+        }
+    }
+
+    /// The snippet without highlighting
+    pub fn text(&self) -> String {
+        self.spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect()
+    }
+}
+
+/// Cut `line` down to the snippet shown for a match at `range`
+fn snippet(line: &str, range: Range<usize>) -> (String, Range<usize>) {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+    let text = trimmed.trim_end();
+    // Whitespace around the line may be part of the match
+    let range = range.start.saturating_sub(indent).min(text.len())
+        ..range.end.saturating_sub(indent).min(text.len());
+    if text.len() <= SNIPPET_LEN {
+        return (text.to_string(), range);
+    }
+
+    let mut start = range.start.saturating_sub(SNIPPET_CONTEXT);
+    while !text.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = (start + SNIPPET_LEN).max(range.end).min(text.len());
+    while !text.is_char_boundary(end) {
+        end += 1;
+    }
+    (
+        text[start..end].to_string(),
+        range.start - start..range.end - start,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippet_keeps_the_match() {
+        let (text, range) = snippet("    return value;  ", 11..16);
+        assert_eq!(text, "return value;");
+        assert_eq!(&text[range], "value");
+
+        let line = format!("{}needle{}", "a".repeat(500), "b".repeat(500));
+        let (text, range) = snippet(&line, 500..506);
+        assert!(text.len() <= SNIPPET_LEN);
+        assert_eq!(&text[range], "needle");
+    }
+}
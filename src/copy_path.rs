@@ -0,0 +1,264 @@
+use crate::list_selection::ListSelection;
+use std::path::{Component, Path, PathBuf};
+
+/// Ways a path can be copied to the clipboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathFormat {
+    Absolute,
+    Relative,
+    ShellQuoted,
+    FileUri,
+    Converted, // WSL <-> Windows
+}
+
+impl PathFormat {
+    pub const ALL: [PathFormat; 5] = [
+        PathFormat::Absolute,
+        PathFormat::Relative,
+        PathFormat::ShellQuoted,
+        PathFormat::FileUri,
+        PathFormat::Converted,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PathFormat::Absolute => "Absolute",
+            PathFormat::Relative => "Relative to cwd",
+            PathFormat::ShellQuoted => "Shell-quoted",
+            PathFormat::FileUri => "file:// URI",
+            PathFormat::Converted => "WSL/Windows",
+        }
+    }
+}
+
+/// Render a path in the requested format
+/// Returns None if the format doesn't apply (e.g. no WSL/Windows counterpart)
+pub fn format_path(path: &Path, format: PathFormat, cwd: &Path) -> Option<String> {
+    match format {
+        PathFormat::Absolute => Some(path.display().to_string()),
+        PathFormat::Relative => Some(relative_path(path, cwd).display().to_string()),
+        PathFormat::ShellQuoted => Some(shell_quote(&path.display().to_string())),
+        PathFormat::FileUri => Some(file_uri(path)),
+        PathFormat::Converted => convert_wsl_windows(
+            &path.display().to_string(),
+            std::env::var("WSL_DISTRO_NAME").ok().as_deref(),
+        ),
+    }
+}
+
+/// Express `path` relative to `base`, using ".." where needed
+pub fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let path_components: Vec<Component> = path.components().collect();
+    let base_components: Vec<Component> = base.components().collect();
+
+    let common = path_components
+        .iter()
+        .zip(base_components.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    // Different roots (e.g. other drive) - relative form is impossible
+    if common == 0 && path.has_root() {
+        return path.to_path_buf();
+    }
+
+    let mut result = PathBuf::new();
+    for _ in common..base_components.len() {
+        result.push("..");
+    }
+    for component in &path_components[common..] {
+        result.push(component.as_os_str());
+    }
+
+    if result.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        result
+    }
+}
+
+/// Quote a path for pasting into a shell
+#[cfg(unix)]
+pub fn shell_quote(s: &str) -> String {
+    // Single quotes preserve everything except single quotes themselves
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+#[cfg(windows)]
+pub fn shell_quote(s: &str) -> String {
+    // cmd.exe and PowerShell both accept double-quoted paths
+    format!("\"{}\"", s.replace('"', "\"\""))
+}
+
+/// Build a file:// URI with percent-encoding (RFC 8089)
+pub fn file_uri(path: &Path) -> String {
+    let path_str = path.display().to_string().replace('\\', "/");
+
+    let mut encoded = String::with_capacity(path_str.len());
+    for byte in path_str.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    // Windows drive paths need an extra slash: file:///C:/Users
+    if encoded.starts_with('/') {
+        format!("file://{}", encoded)
+    } else {
+        format!("file:///{}", encoded)
+    }
+}
+
+/// Convert between WSL (/mnt/c/...) and Windows (C:\...) forms
+/// Plain Linux paths map to \\wsl$\<distro>\... when the distro name is known
+pub fn convert_wsl_windows(path: &str, distro: Option<&str>) -> Option<String> {
+    // Windows -> WSL: C:\Users\me -> /mnt/c/Users/me
+    let bytes = path.as_bytes();
+    if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+        let drive = (bytes[0] as char).to_ascii_lowercase();
+        let rest = path[2..].replace('\\', "/");
+        let rest = rest.trim_start_matches('/');
+        return Some(if rest.is_empty() {
+            format!("/mnt/{}", drive)
+        } else {
+            format!("/mnt/{}/{}", drive, rest)
+        });
+    }
+
+    // WSL -> Windows: /mnt/c/Users/me -> C:\Users\me
+    if let Some(rest) = path.strip_prefix("/mnt/") {
+        let mut parts = rest.splitn(2, '/');
+        let drive = parts.next().unwrap_or("");
+        if drive.len() == 1 && drive.as_bytes()[0].is_ascii_alphabetic() {
+            let tail = parts.next().unwrap_or("").replace('/', "\\");
+            return Some(format!("{}:\\{}", drive.to_ascii_uppercase(), tail));
+        }
+    }
+
+    // Linux path inside WSL -> \\wsl$\Distro\home\me
+    if path.starts_with('/') {
+        return distro.map(|d| format!("\\\\wsl$\\{}{}", d, path.replace('/', "\\")));
+    }
+
+    None
+}
+
+/// Copy-path menu shown in the bottom panel
+#[derive(Debug, Default)]
+pub struct CopyPathMenu {
+    pub is_open: bool,
+    pub selected_index: usize,
+    pub options: Vec<(PathFormat, String)>,
+}
+
+impl CopyPathMenu {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the menu with all applicable variants of `path`
+    pub fn open(&mut self, path: &Path, cwd: &Path) {
+        self.options = PathFormat::ALL
+            .iter()
+            .filter_map(|&format| format_path(path, format, cwd).map(|s| (format, s)))
+            .collect();
+        self.selected_index = 0;
+        self.is_open = true;
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+        self.options.clear();
+    }
+
+    pub fn get_selected(&self) -> Option<&str> {
+        self.options
+            .get(self.selected_index)
+            .map(|(_, text)| text.as_str())
+    }
+}
+
+impl ListSelection for CopyPathMenu {
+    fn selected_index_mut(&mut self) -> &mut usize {
+        &mut self.selected_index
+    }
+
+    fn entry_count(&self) -> usize {
+        self.options.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_path() {
+        assert_eq!(
+            relative_path(Path::new("/home/me/src/main.rs"), Path::new("/home/me")),
+            PathBuf::from("src/main.rs")
+        );
+        assert_eq!(
+            relative_path(Path::new("/home/other/a"), Path::new("/home/me/proj")),
+            PathBuf::from("../../other/a")
+        );
+        assert_eq!(
+            relative_path(Path::new("/home/me"), Path::new("/home/me")),
+            PathBuf::from(".")
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("/tmp/my file"), "'/tmp/my file'");
+        assert_eq!(shell_quote("/tmp/it's"), "'/tmp/it'\\''s'");
+    }
+
+    #[test]
+    fn test_file_uri() {
+        assert_eq!(
+            file_uri(Path::new("/tmp/my file#1.txt")),
+            "file:///tmp/my%20file%231.txt"
+        );
+        assert_eq!(file_uri(Path::new("C:\\Users\\me")), "file:///C:/Users/me");
+    }
+
+    #[test]
+    fn test_convert_wsl_windows() {
+        assert_eq!(
+            convert_wsl_windows("/mnt/c/Users/me", None).as_deref(),
+            Some("C:\\Users\\me")
+        );
+        assert_eq!(
+            convert_wsl_windows("D:\\Projects\\dtree", None).as_deref(),
+            Some("/mnt/d/Projects/dtree")
+        );
+        assert_eq!(
+            convert_wsl_windows("/home/me", Some("Ubuntu")).as_deref(),
+            Some("\\\\wsl$\\Ubuntu\\home\\me")
+        );
+        assert_eq!(convert_wsl_windows("/home/me", None), None);
+    }
+
+    #[test]
+    fn test_menu_navigation() {
+        let mut menu = CopyPathMenu::new();
+        menu.open(Path::new("/tmp/file.txt"), Path::new("/tmp"));
+        assert!(menu.is_open);
+        assert_eq!(menu.get_selected(), Some("/tmp/file.txt"));
+
+        menu.move_down();
+        assert_eq!(menu.get_selected(), Some("file.txt"));
+
+        assert!(menu.select_number(1));
+        assert!(!menu.select_number(99));
+        assert_eq!(menu.selected_index, 0);
+
+        menu.close();
+        assert!(!menu.is_open);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::logging;
+use crate::oplog;
+use anyhow::{Context, Result};
+use std::backtrace::Backtrace;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Operation log entries included in a crash report
+const RECENT_ACTIONS: usize = 50;
+
+/// Build the crash report text: version, platform, panic message, recent actions, backtrace
+pub fn report_text(panic_message: &str, actions: &[oplog::Entry], backtrace: &str) -> String {
+    let mut report = format!(
+        "dtree {} crash report\n\nPlatform: {} {}\nCommand: {}\n\nPanic: {}\n\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        std::env::args().collect::<Vec<_>>().join(" "),
+        panic_message
+    );
+
+    report.push_str(&format!("Last actions ({}):\n", actions.len()));
+    for entry in actions {
+        report.push_str(&entry.format());
+        report.push('\n');
+    }
+
+    report.push_str("\nBacktrace:\n");
+    report.push_str(backtrace);
+    report.push('\n');
+    report
+}
+
+/// Write a crash report into `dir` and return its path
+pub fn write_report(dir: &Path, report: &str) -> Result<PathBuf> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let path = dir.join(format!("crash-{}.txt", timestamp));
+    fs::write(&path, report).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Write a crash report for a panic (called from the panic hook, after the terminal
+/// has been restored) and tell the user where it is
+pub fn report_panic(panic_message: &str) {
+    let backtrace = Backtrace::force_capture();
+    let actions = oplog::try_recent(RECENT_ACTIONS);
+    let report = report_text(panic_message, &actions, &backtrace.to_string());
+
+    let written = logging::state_dir()
+        .context("Could not find state directory")
+        .and_then(|dir| write_report(&dir, &report));
+
+    match written {
+        Ok(path) => {
+            eprintln!("\ndtree crashed. A crash report was written to:");
+            eprintln!("  {}", path.display());
+            eprintln!(
+                "Please attach it to an issue at {}/issues",
+                env!("CARGO_PKG_REPOSITORY")
+            );
+        }
+        Err(e) => {
+            // No state directory - the report is all we have, so print it
+            eprintln!("\ndtree crashed ({:#}). Crash report:\n\n{}", e, report);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_report() {
+        let actions = vec![oplog::Entry {
+            elapsed: Duration::from_millis(1500),
+            message: "Changed root to /tmp".to_string(),
+        }];
+        let report = report_text("index out of bounds", &actions, "0: main");
+        assert!(report.starts_with(&format!("dtree {} crash report", env!("CARGO_PKG_VERSION"))));
+        assert!(report.contains("Panic: index out of bounds"));
+        assert!(report.contains("Last actions (1):\n[    1.500s] Changed root to /tmp\n"));
+        assert!(report.contains("Backtrace:\n0: main"));
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = write_report(&temp_dir.path().join("state"), &report).unwrap();
+        assert!(path
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("crash-"));
+        assert_eq!(fs::read_to_string(path).unwrap(), report);
+    }
+}
@@ -1,3 +1,7 @@
+use crate::filter_set::Excludes;
+use crate::format;
+use crate::vfs::{self, Vfs, VfsProvider};
+use crate::wakeup::{self, WakingSender};
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -45,6 +49,12 @@ pub struct DirSizeCache {
     task_sender: Option<Sender<TaskMessage>>,
     /// Handle to background worker thread
     worker_handle: Option<thread::JoinHandle<()>>,
+    /// Don't count directories on other filesystems (like `du -x`)
+    pub one_file_system: bool,
+    /// Directories not gone into (`[search] exclude`)
+    pub exclude: Arc<Excludes>,
+    /// Where the directories are read from
+    pub vfs: Vfs,
 }
 
 impl Default for DirSizeCache {
@@ -61,6 +71,9 @@ impl DirSizeCache {
             result_receiver: None,
             task_sender: None,
             worker_handle: None,
+            one_file_system: false,
+            exclude: Arc::default(),
+            vfs: vfs::local(),
         }
     }
 
@@ -71,13 +84,23 @@ impl DirSizeCache {
         }
 
         let (task_tx, task_rx) = unbounded();
-        let (result_tx, result_rx) = unbounded();
+        let (result_tx, result_rx) = wakeup::unbounded();
 
         let calculating = Arc::clone(&self.calculating);
+        let one_file_system = self.one_file_system;
+        let exclude = Arc::clone(&self.exclude);
+        let vfs = Arc::clone(&self.vfs);
 
         // Spawn worker thread
         let handle = thread::spawn(move || {
-            worker_loop(task_rx, result_tx, calculating);
+            worker_loop(
+                task_rx,
+                result_tx,
+                calculating,
+                one_file_system,
+                &exclude,
+                vfs,
+            );
         });
 
         self.task_sender = Some(task_tx);
@@ -99,6 +122,11 @@ impl DirSizeCache {
         }
     }
 
+    /// Number of directories queued or being calculated
+    pub fn pending_count(&self) -> usize {
+        self.calculating.lock().map(|c| c.len()).unwrap_or(0)
+    }
+
     /// Start async calculation for a directory
     pub fn calculate_async(&mut self, path: PathBuf) {
         // Don't calculate if already in cache or being calculated
@@ -170,26 +198,9 @@ impl DirSizeCache {
         self.cache.clear();
     }
 
-    /// Format size in human-readable format
+    /// Format size in human-readable format (`size_units`, `size_decimals`)
     pub fn format_size(size: u64, is_partial: bool) -> String {
-        const KB: u64 = 1024;
-        const MB: u64 = KB * 1024;
-        const GB: u64 = MB * 1024;
-        const TB: u64 = GB * 1024;
-
-        let prefix = if is_partial { ">" } else { "" };
-
-        if size >= TB {
-            format!("{}{:.1}T", prefix, size as f64 / TB as f64)
-        } else if size >= GB {
-            format!("{}{:.1}G", prefix, size as f64 / GB as f64)
-        } else if size >= MB {
-            format!("{}{:.1}M", prefix, size as f64 / MB as f64)
-        } else if size >= KB {
-            format!("{}{:.1}K", prefix, size as f64 / KB as f64)
-        } else {
-            format!("{}{}B", prefix, size)
-        }
+        format::current().short_size(size, is_partial)
     }
 }
 
@@ -202,24 +213,21 @@ impl Drop for DirSizeCache {
 /// Worker thread loop that processes calculation tasks
 fn worker_loop(
     task_rx: Receiver<TaskMessage>,
-    result_tx: Sender<SizeMessage>,
+    result_tx: WakingSender<SizeMessage>,
     calculating: Arc<Mutex<Vec<PathBuf>>>,
+    one_file_system: bool,
+    exclude: &Excludes,
+    vfs: Vfs,
 ) {
     loop {
         match task_rx.recv() {
             Ok(TaskMessage::Calculate(path)) => {
-                // Calculate size with timeout and file limit
-                let start_time = Instant::now();
-                let mut file_count = 0;
-
-                let result = calculate_dir_size_limited(&path, start_time, &mut file_count);
+                let _span = tracing::debug_span!("dir_size", path = %path.display()).entered();
+                let (size, is_partial) =
+                    dir_size_excluding(vfs.as_ref(), &path, one_file_system, exclude);
 
                 // Send results
-                let _ = result_tx.send(SizeMessage::Result(
-                    path.clone(),
-                    result.size,
-                    result.is_partial,
-                ));
+                let _ = result_tx.send(SizeMessage::Result(path.clone(), size, is_partial));
                 let _ = result_tx.send(SizeMessage::Done(path));
             }
             Ok(TaskMessage::Shutdown) | Err(_) => {
@@ -233,11 +241,61 @@ fn worker_loop(
     }
 }
 
+/// Total size of the files under `path` and whether it is partial: counting stops
+/// after 10000 files or 5 seconds, like the sizes shown in the tree
+/// `one_file_system` leaves out directories on other filesystems (like `du -x`)
+#[allow(dead_code)] // Library API; the app's sizes honor `[search] exclude`
+pub fn dir_size(vfs: &dyn VfsProvider, path: &Path, one_file_system: bool) -> (u64, bool) {
+    dir_size_excluding(vfs, path, one_file_system, &Excludes::default())
+}
+
+/// Like `dir_size()`, not going into the directories `exclude` names; a size
+/// leaving one out is partial
+pub fn dir_size_excluding(
+    vfs: &dyn VfsProvider,
+    path: &Path,
+    one_file_system: bool,
+    exclude: &Excludes,
+) -> (u64, bool) {
+    let mut file_count = 0;
+    let mut skipped = false;
+    let root_device = if one_file_system {
+        vfs.metadata(path)
+            .ok()
+            .and_then(|metadata| metadata.device())
+    } else {
+        None
+    };
+
+    let result = calculate_dir_size_limited(
+        vfs,
+        path,
+        Instant::now(),
+        &mut file_count,
+        root_device,
+        exclude,
+        &mut skipped,
+    );
+    let is_partial = result.is_partial || skipped;
+    tracing::debug!(
+        size = result.size,
+        files = file_count,
+        partial = is_partial,
+        "size calculated"
+    );
+    (result.size, is_partial)
+}
+
 /// Calculate total size of a directory recursively with limits
+/// `skipped` is set when an excluded directory is left out
 fn calculate_dir_size_limited(
+    vfs: &dyn VfsProvider,
     path: &Path,
     start_time: Instant,
     file_count: &mut usize,
+    root_device: Option<u64>,
+    exclude: &Excludes,
+    skipped: &mut bool,
 ) -> CalculationResult {
     let mut total_size = 0u64;
     let mut is_partial = false;
@@ -258,8 +316,8 @@ fn calculate_dir_size_limited(
         };
     }
 
-    if let Ok(entries) = std::fs::read_dir(path) {
-        for entry in entries.flatten() {
+    if let Ok(entries) = vfs.read_dir(path) {
+        for entry in entries.into_iter().flatten() {
             // Periodic checks
             if (*file_count).is_multiple_of(100) {
                 // Check timeout every 100 files
@@ -271,9 +329,9 @@ fn calculate_dir_size_limited(
                 }
             }
 
-            if let Ok(metadata) = entry.metadata() {
-                if metadata.is_file() {
-                    total_size += metadata.len();
+            if let Ok(metadata) = vfs.symlink_metadata(&entry) {
+                if metadata.is_file {
+                    total_size += metadata.len;
                     *file_count += 1;
 
                     // Check file limit
@@ -283,10 +341,26 @@ fn calculate_dir_size_limited(
                             is_partial: true,
                         };
                     }
-                } else if metadata.is_dir() {
+                } else if metadata.is_dir {
+                    // Skip mountpoints when staying on one filesystem
+                    if root_device.is_some() && metadata.device() != root_device {
+                        continue;
+                    }
+                    if exclude.skips_contents(&entry, path) {
+                        *skipped = true;
+                        continue;
+                    }
+
                     // Recursively calculate subdirectory size
-                    let subdir_result =
-                        calculate_dir_size_limited(&entry.path(), start_time, file_count);
+                    let subdir_result = calculate_dir_size_limited(
+                        vfs,
+                        &entry,
+                        start_time,
+                        file_count,
+                        root_device,
+                        exclude,
+                        skipped,
+                    );
 
                     total_size += subdir_result.size;
 
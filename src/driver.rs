@@ -0,0 +1,175 @@
+use crate::app::App;
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::backend::TestBackend;
+use ratatui::style::Style;
+use ratatui::Terminal;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use unicode_width::UnicodeWidthStr;
+
+/// Headless driver: feeds synthetic key and mouse events to an `App` and renders it
+/// into an in-memory buffer, so flows can be scripted without a real terminal
+///
+/// ```no_run
+/// # use dtree_tui::{app::App, driver::Driver};
+/// # use crossterm::event::KeyCode;
+/// # use std::time::Duration;
+/// let app = App::new("/tmp".into())?;
+/// let mut driver = Driver::new(app, 100, 30)?;
+/// driver.press(KeyCode::Char('/'))?.type_text("notes")?.press(KeyCode::Enter)?;
+/// driver.wait_for("notes.txt", Duration::from_secs(5))?;
+/// println!("{}", driver.screen()?);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct Driver {
+    app: App,
+    terminal: Terminal<TestBackend>,
+    /// Set when the app exits: None = quit, Some(path) = exit with a path or marker
+    exit: Option<Option<PathBuf>>,
+}
+
+impl Driver {
+    pub fn new(mut app: App, width: u16, height: u16) -> Result<Self> {
+        // Like run_app: a fullscreen file is laid out for the real terminal width
+        if app.is_fullscreen_viewer() {
+            app.reload_fullscreen_file(width)?;
+        }
+        Ok(Self {
+            app,
+            terminal: Terminal::new(TestBackend::new(width, height))?,
+            exit: None,
+        })
+    }
+
+    pub fn app(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    /// How the app exited, if it did (same value `run_app` returns)
+    pub fn exit(&self) -> Option<&Option<PathBuf>> {
+        self.exit.as_ref()
+    }
+
+    pub fn press(&mut self, code: KeyCode) -> Result<&mut Self> {
+        self.press_with(code, KeyModifiers::NONE)
+    }
+
+    pub fn press_with(&mut self, code: KeyCode, modifiers: KeyModifiers) -> Result<&mut Self> {
+        if self.exit.is_some() {
+            anyhow::bail!("dtree already exited, key {:?} not sent", code);
+        }
+        match self.app.handle_key(KeyEvent::new(code, modifiers))? {
+            Some(path) if path.as_os_str().is_empty() => {}
+            result => self.exit = Some(result),
+        }
+        self.poll();
+        Ok(self)
+    }
+
+    /// Press a key for every character (e.g. for prompts)
+    pub fn type_text(&mut self, text: &str) -> Result<&mut Self> {
+        for c in text.chars() {
+            self.press(KeyCode::Char(c))?;
+        }
+        Ok(self)
+    }
+
+    pub fn mouse(&mut self, kind: MouseEventKind, column: u16, row: u16) -> Result<&mut Self> {
+        self.app.handle_mouse(MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        })?;
+        self.poll();
+        Ok(self)
+    }
+
+    /// Left click at a screen position (render first so the layout is known)
+    pub fn click(&mut self, column: u16, row: u16) -> Result<&mut Self> {
+        self.render()?;
+        self.mouse(MouseEventKind::Down(MouseButton::Left), column, row)
+    }
+
+    /// Collect results from background work (search, sizes, file operations, git status)
+    /// A preview loading in the background is waited for, so screens are predictable
+    pub fn poll(&mut self) {
+        self.app.finish_preview();
+        self.app.poll_search();
+        self.app.poll_sizes();
+        self.app.poll_comparison();
+        self.app.poll_listing();
+        self.app.poll_flat_listing();
+        self.app.poll_ignored();
+        self.app.poll_file_ops();
+        self.app.poll_tasks();
+        self.app.poll_repo();
+        self.app.poll_projects();
+        self.app.poll_notifications();
+        self.app.poll_pending_z();
+        if self.exit.is_none() && self.app.poll_idle().is_none() {
+            self.exit = Some(None);
+        }
+        if self.exit.is_none() {
+            match self.app.poll_key_sequence() {
+                Ok(Some(path)) if path.as_os_str().is_empty() => {}
+                Ok(result) => self.exit = Some(result),
+                Err(e) => tracing::warn!("key sequence: {}", e),
+            }
+        }
+    }
+
+    pub fn render(&mut self) -> Result<()> {
+        let app = &mut self.app;
+        self.terminal.draw(|frame| app.render(frame))?;
+        app.clear_dirty();
+        Ok(())
+    }
+
+    /// Render and return the screen as text, one line per row (trailing spaces trimmed)
+    pub fn screen(&mut self) -> Result<String> {
+        self.render()?;
+        let buffer = self.terminal.backend().buffer();
+        let width = buffer.area.width as usize;
+
+        let mut lines = Vec::new();
+        for row in buffer.content.chunks(width) {
+            let mut line = String::new();
+            let mut skip = 0;
+            for cell in row {
+                // A wide character covers the next cell(s)
+                if skip > 0 {
+                    skip -= 1;
+                    continue;
+                }
+                line.push_str(cell.symbol());
+                skip = cell.symbol().width().saturating_sub(1);
+            }
+            lines.push(line.trim_end().to_string());
+        }
+        Ok(lines.join("\n"))
+    }
+
+    /// Render and return the style of the cell at a screen position
+    pub fn style_at(&mut self, column: u16, row: u16) -> Result<Style> {
+        self.render()?;
+        Ok(self.terminal.backend().buffer()[(column, row)].style())
+    }
+
+    /// Poll background work until the screen contains `text`
+    pub fn wait_for(&mut self, text: &str, timeout: Duration) -> Result<&mut Self> {
+        let started = Instant::now();
+        loop {
+            self.poll();
+            let screen = self.screen()?;
+            if screen.contains(text) {
+                return Ok(self);
+            }
+            if started.elapsed() > timeout {
+                anyhow::bail!("Timed out waiting for {:?}, screen:\n{}", text, screen);
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use crate::platform;
+use std::path::{Path, PathBuf};
+
+/// Drive selection panel, shown when going up from a drive root (e.g. C:\)
+#[derive(Debug, Default)]
+pub struct Drives {
+    drives: Vec<PathBuf>,
+    pub is_selecting: bool,
+    pub selected_index: usize,
+}
+
+impl Drives {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the panel with the current list of drives
+    /// Returns false (and stays closed) if the platform has no drives to choose from
+    pub fn enter_selection_mode(&mut self, current_root: &Path) -> bool {
+        self.open_with(platform::list_drives(), current_root)
+    }
+
+    fn open_with(&mut self, drives: Vec<PathBuf>, current_root: &Path) -> bool {
+        if drives.is_empty() {
+            return false;
+        }
+
+        // Pre-select the drive we came from
+        self.selected_index = drives
+            .iter()
+            .position(|drive| current_root.starts_with(drive))
+            .unwrap_or(0);
+        self.drives = drives;
+        self.is_selecting = true;
+        true
+    }
+
+    pub fn exit_selection_mode(&mut self) {
+        self.is_selecting = false;
+        self.selected_index = 0;
+    }
+
+    pub fn list(&self) -> &[PathBuf] {
+        &self.drives
+    }
+
+    pub fn move_up(&mut self) {
+        if self.selected_index > 0 {
+            self.selected_index -= 1;
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        if self.selected_index + 1 < self.drives.len() {
+            self.selected_index += 1;
+        }
+    }
+
+    pub fn get_selected_drive(&self) -> Option<&PathBuf> {
+        self.drives.get(self.selected_index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_drives() -> Vec<PathBuf> {
+        vec![
+            PathBuf::from("C:/"),
+            PathBuf::from("D:/"),
+            PathBuf::from("Z:/"),
+        ]
+    }
+
+    #[test]
+    fn test_open_preselects_current_drive() {
+        let mut drives = Drives::new();
+        assert!(drives.open_with(sample_drives(), Path::new("D:/")));
+        assert!(drives.is_selecting);
+        assert_eq!(drives.get_selected_drive(), Some(&PathBuf::from("D:/")));
+    }
+
+    #[test]
+    fn test_open_without_drives_stays_closed() {
+        let mut drives = Drives::new();
+        assert!(!drives.open_with(Vec::new(), Path::new("/")));
+        assert!(!drives.is_selecting);
+        assert!(drives.get_selected_drive().is_none());
+    }
+
+    #[test]
+    fn test_move_stays_in_bounds() {
+        let mut drives = Drives::new();
+        drives.open_with(sample_drives(), Path::new("C:/"));
+
+        drives.move_up();
+        assert_eq!(drives.selected_index, 0);
+
+        drives.move_down();
+        drives.move_down();
+        drives.move_down();
+        assert_eq!(drives.selected_index, 2);
+
+        drives.exit_selection_mode();
+        assert!(!drives.is_selecting);
+        assert_eq!(drives.selected_index, 0);
+    }
+}
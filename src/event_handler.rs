@@ -2,18 +2,44 @@
 #![allow(clippy::too_many_arguments)]
 
 use anyhow::Result;
-use arboard::Clipboard;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use crate::bookmarks::Bookmarks;
-use crate::config::Config;
+use crate::clipboard;
+use crate::compare;
+use crate::config::{Config, Trigger};
+use crate::copy_path::CopyPathMenu;
 use crate::dir_size::DirSizeCache;
-use crate::file_viewer::FileViewer;
+use crate::drives::Drives;
+use crate::file_ops::{ConflictChoice, FileOps, TransferMode};
+use crate::file_viewer::{FileViewer, MarkKey, Placement};
+use crate::flat_listing;
+use crate::git::{self, GitView};
+use crate::goto::{self, GotoPrompt};
+use crate::i18n::{tr, tr_with};
+use crate::launcher;
+use crate::layout::{Layout, NamedLayout};
+use crate::list_selection::ListSelection;
+use crate::multiplexer::{self, SplitDirection};
 use crate::navigation::Navigation;
+use crate::nvim;
+use crate::oplog;
+use crate::picker::{self, PickMode};
+use crate::platform;
+use crate::plugins::{CommandPrompt, Plugins};
+use crate::quick_filter::QuickFilter;
+use crate::quickfix;
+use crate::scan::ScanKind;
 use crate::search::Search;
-use crate::ui::UI;
+use crate::tasks::{TaskKind, TaskManager};
+use crate::tree_export::{self, Destination};
+use crate::ui::{Focus, UI};
+
+/// How long `z` waits for z, t or b before it toggles the sizes on its own
+const Z_TIMEOUT: Duration = Duration::from_millis(500);
 
 /// Event handler for keyboard and mouse input
 pub struct EventHandler {
@@ -22,6 +48,7 @@ pub struct EventHandler {
     pub last_click_time: Option<(Instant, usize)>,
     pub last_bookmark_click_time: Option<(Instant, usize)>, // For bookmark double-click
     pub last_search_click_time: Option<(Instant, usize)>,   // For search results double-click
+    pub pending_z: Option<Instant>, // z pressed, waiting for z, t or b (zz, zt, zb)
 }
 
 impl Default for EventHandler {
@@ -38,6 +65,7 @@ impl EventHandler {
             last_click_time: None,
             last_bookmark_click_time: None,
             last_search_click_time: None,
+            pending_z: None,
         }
     }
 
@@ -49,19 +77,522 @@ impl EventHandler {
         file_viewer: &mut FileViewer,
         search: &mut Search,
         bookmarks: &mut Bookmarks,
+        drives: &mut Drives,
+        copy_menu: &mut CopyPathMenu,
+        goto: &mut GotoPrompt,
+        command_prompt: &mut CommandPrompt,
         show_files: &mut bool,
         show_files_before_help: &mut bool,
         show_help: &mut bool,
         fullscreen_viewer: &mut bool,
         show_sizes: &mut bool,
         dir_size_cache: &mut DirSizeCache,
+        tasks: &mut TaskManager,
+        file_ops: &mut FileOps,
         need_terminal_clear: &mut bool,
-        ui: &UI,
+        ui: &mut UI,
+        plugins: &Plugins,
         config: &Config,
+        pick_mode: Option<PickMode>,
     ) -> Result<Option<PathBuf>> {
         // Search mode - separate handling
         if search.mode {
-            return self.handle_search_input(key, search, nav, *show_files);
+            return self.handle_search_input(key, search, nav, ui, *show_files);
+        }
+
+        // Paste conflict prompt (target already exists)
+        if file_ops.is_resolving_conflict() {
+            match key.code {
+                KeyCode::Char('o') => file_ops.resolve_conflict(ConflictChoice::Overwrite, false),
+                KeyCode::Char('O') => file_ops.resolve_conflict(ConflictChoice::Overwrite, true),
+                KeyCode::Char('s') => file_ops.resolve_conflict(ConflictChoice::Skip, false),
+                KeyCode::Char('S') => file_ops.resolve_conflict(ConflictChoice::Skip, true),
+                KeyCode::Char('r') => file_ops.resolve_conflict(ConflictChoice::Rename, false),
+                KeyCode::Char('R') => file_ops.resolve_conflict(ConflictChoice::Rename, true),
+                KeyCode::Esc => file_ops.abort_paste(),
+                _ => {}
+            }
+            return Ok(Some(PathBuf::new()));
+        }
+
+        // Fuzzy jump overlay (rank the loaded entries, Enter reveals the selected one)
+        if ui.fuzzy_jump.is_open {
+            match key.code {
+                KeyCode::Esc => ui.fuzzy_jump.close(),
+                KeyCode::Down | KeyCode::Tab => ui.fuzzy_jump.move_down(),
+                KeyCode::Up | KeyCode::BackTab => ui.fuzzy_jump.move_up(),
+                KeyCode::Backspace => ui.fuzzy_jump.backspace(),
+                KeyCode::Enter => {
+                    let path = ui.fuzzy_jump.get_selected().map(Path::to_path_buf);
+                    ui.fuzzy_jump.close();
+                    if let Some(path) = path {
+                        Self::reveal_in_tree(
+                            &path,
+                            nav,
+                            file_viewer,
+                            ui,
+                            plugins,
+                            config,
+                            show_files,
+                            show_help,
+                        )?;
+                    }
+                    return Ok(Some(PathBuf::new()));
+                }
+                KeyCode::Char(c) => ui.fuzzy_jump.add_char(c),
+                _ => {}
+            }
+            ui.fuzzy_jump.update_preview(&nav.tree, nav.vfs.as_ref());
+            return Ok(Some(PathBuf::new()));
+        }
+
+        // Copy path menu (absolute, relative, quoted, URI, WSL/Windows)
+        if copy_menu.is_open {
+            let mut copy_selected = false;
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => copy_menu.close(),
+                KeyCode::Char('j') | KeyCode::Down => copy_menu.move_down(),
+                KeyCode::Char('k') | KeyCode::Up => copy_menu.move_up(),
+                KeyCode::Char(c @ '1'..='9') => {
+                    copy_selected = copy_menu.select_number(c as usize - '0' as usize);
+                }
+                KeyCode::Enter => copy_selected = true,
+                // Pressing the copy key twice copies the absolute path (the first option)
+                _ if config.keybindings.is_copy_path(key.code) => {
+                    copy_selected = copy_menu.select_number(1);
+                }
+                _ => {}
+            }
+
+            if copy_selected {
+                if let Some(text) = copy_menu.get_selected() {
+                    Self::copy_path_text(text, ui);
+                }
+                copy_menu.close();
+            }
+            return Ok(Some(PathBuf::new()));
+        }
+
+        // Export of the visible tree (text or Markdown, to the clipboard or a file)
+        if ui.export_menu.is_open {
+            let mut export_selected = false;
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => ui.export_menu.close(),
+                KeyCode::Char('j') | KeyCode::Down => ui.export_menu.move_down(),
+                KeyCode::Char('k') | KeyCode::Up => ui.export_menu.move_up(),
+                KeyCode::Char(c @ '1'..='9') => {
+                    export_selected = ui.export_menu.select_number(c as usize - '0' as usize);
+                }
+                KeyCode::Char('i') => ui.export_menu.icons = !ui.export_menu.icons,
+                KeyCode::Enter => export_selected = true,
+                _ if config.keybindings.is_export_tree(key.code) => ui.export_menu.close(),
+                _ => {}
+            }
+
+            if export_selected {
+                if let Some((format, destination)) = ui.export_menu.get_selected().cloned() {
+                    let text = tree_export::format(
+                        nav.visible_nodes(),
+                        format,
+                        ui.export_menu.icons,
+                        config.appearance.show_icons,
+                    );
+                    ui.export_menu.close();
+                    match destination {
+                        Destination::Clipboard => match clipboard::copy(&text) {
+                            Ok(()) => ui.notifications.info(format!(
+                                "Copied the tree ({} entries) as {}",
+                                nav.flat_list.len(),
+                                format.label()
+                            )),
+                            Err(e) => ui
+                                .notifications
+                                .error(tr_with("Clipboard: {error}", &[("error", &e)])),
+                        },
+                        Destination::File(file) => match tree_export::write(&file, &text) {
+                            Ok(()) => {
+                                oplog::record(format!("Exported the tree to {}", file.display()));
+                                ui.notifications.info(format!(
+                                    "Exported the tree ({} entries) to {}",
+                                    nav.flat_list.len(),
+                                    file.display()
+                                ));
+                            }
+                            Err(e) => ui.notifications.error(format!("{:#}", e)),
+                        },
+                        Destination::Stdout => return Ok(Some(tree_export::encode(&text))),
+                    }
+                }
+            }
+            return Ok(Some(PathBuf::new()));
+        }
+
+        // Clipboard history (copy an earlier path or snippet again)
+        if ui.clipboard_history.is_open {
+            let mut copy_selected = false;
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => ui.clipboard_history.close(),
+                KeyCode::Char('j') | KeyCode::Down => ui.clipboard_history.move_down(),
+                KeyCode::Char('k') | KeyCode::Up => ui.clipboard_history.move_up(),
+                KeyCode::Char(c @ '1'..='9') => {
+                    copy_selected = ui
+                        .clipboard_history
+                        .select_number(c as usize - '0' as usize);
+                }
+                KeyCode::Enter => copy_selected = true,
+                _ if config.keybindings.is_clipboard_history(key.code) => {
+                    ui.clipboard_history.close()
+                }
+                _ => {}
+            }
+
+            if copy_selected {
+                if let Some(text) = ui.clipboard_history.get_selected() {
+                    let text = text.to_string();
+                    match clipboard::copy(&text) {
+                        Ok(()) => {
+                            ui.notifications.info("Copied to clipboard");
+                            oplog::record(format!("Copied again: {}", text));
+                        }
+                        Err(e) => ui
+                            .notifications
+                            .error(tr_with("Clipboard: {error}", &[("error", &e)])),
+                    }
+                }
+                ui.clipboard_history.close();
+            }
+            return Ok(Some(PathBuf::new()));
+        }
+
+        // Marks panel (review the marks, those outside the root too)
+        if ui.marks_panel.is_open {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => ui.marks_panel.close(),
+                KeyCode::Char('j') | KeyCode::Down => ui.marks_panel.move_down(),
+                KeyCode::Char('k') | KeyCode::Up => ui.marks_panel.move_up(),
+                KeyCode::Char('d') | KeyCode::Char(' ') | KeyCode::Delete => {
+                    if let Some(path) = ui.marks_panel.remove_selected() {
+                        nav.marked.remove(&path);
+                    }
+                    if ui.marks_panel.entries.is_empty() {
+                        ui.marks_panel.close();
+                    }
+                }
+                KeyCode::Char('D') => {
+                    oplog::record(format!("Unmarked {} item(s)", nav.marked.len()));
+                    nav.marked.clear();
+                    ui.marks_panel.close();
+                    ui.notifications.info("Marks cleared");
+                }
+                KeyCode::Enter => {
+                    if let Some(path) = ui.marks_panel.get_selected().map(Path::to_path_buf) {
+                        ui.marks_panel.close();
+                        // A mark outside the tree: its directory becomes the root
+                        if !path.starts_with(&nav.root().path) {
+                            let dir = path.parent().unwrap_or(&path).to_path_buf();
+                            if let Some(error_msg) = nav.go_to_directory(dir, *show_files)? {
+                                ui.notifications.error(error_msg);
+                                return Ok(Some(PathBuf::new()));
+                            }
+                        }
+                        if let Err(e) = nav.expand_path_to_node(&path, *show_files) {
+                            ui.notifications.error(tr_with(
+                                "Cannot reveal {path}: {error}",
+                                &[("path", &path.display()), ("error", &format!("{:#}", e))],
+                            ));
+                        }
+                    }
+                }
+                _ if config.keybindings.is_marks(key.code) => ui.marks_panel.close(),
+                _ => {}
+            }
+            return Ok(Some(PathBuf::new()));
+        }
+
+        // Log panel (actions, errors and notifications)
+        if ui.notifications.show_log {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => ui.notifications.toggle_log(),
+                KeyCode::Char('j') | KeyCode::Down => ui.notifications.scroll_down(),
+                KeyCode::Char('k') | KeyCode::Up => ui.notifications.scroll_up(),
+                KeyCode::Char('y') => {
+                    // Whole log for bug reports
+                    match clipboard::copy(&oplog::to_text()) {
+                        Ok(()) => ui.notifications.info("Log copied to clipboard"),
+                        Err(e) => ui
+                            .notifications
+                            .error(tr_with("Clipboard: {error}", &[("error", &e)])),
+                    }
+                }
+                _ if config.keybindings.is_message_log(key.code) => ui.notifications.toggle_log(),
+                _ => {}
+            }
+            return Ok(Some(PathBuf::new()));
+        }
+
+        // Go-to-path prompt (text input with Tab completion)
+        if goto.is_open {
+            let base = nav.root().path.clone();
+            match key.code {
+                KeyCode::Esc => goto.close(),
+                KeyCode::Tab => goto.complete(&base, true),
+                KeyCode::BackTab => goto.complete(&base, false),
+                KeyCode::Backspace => goto.backspace(),
+                KeyCode::Enter => {
+                    let Ok(target) = goto.target(&base).canonicalize() else {
+                        goto.error = Some("no such file or directory".to_string());
+                        return Ok(Some(PathBuf::new()));
+                    };
+                    // A file jumps to its directory with the file selected
+                    let (dir, file) = if target.is_dir() {
+                        (target, None)
+                    } else {
+                        let dir = target.parent().unwrap_or(&target).to_path_buf();
+                        (dir, Some(target))
+                    };
+                    if file.is_some() && !*show_files {
+                        *show_files = true;
+                        *show_help = false;
+                    }
+                    if let Some(error_msg) = nav.go_to_directory(dir, *show_files)? {
+                        goto.error = Some(error_msg);
+                        return Ok(Some(PathBuf::new()));
+                    }
+                    if let Some(file) = file {
+                        nav.expand_path_to_node(&file, *show_files)?;
+                        let _ = ui.load_file_for_viewer(
+                            file_viewer,
+                            &file,
+                            config.behavior.max_file_lines,
+                            false,
+                            config,
+                            plugins,
+                        );
+                    }
+                    goto.close();
+                }
+                KeyCode::Char(c) => goto.add_char(c),
+                _ => {}
+            }
+            return Ok(Some(PathBuf::new()));
+        }
+
+        // Plugin command prompt (runs a command on the selected entry)
+        if command_prompt.is_open {
+            let names = plugins.command_names();
+            match key.code {
+                KeyCode::Esc => command_prompt.close(),
+                KeyCode::Tab => command_prompt.complete(&names, true),
+                KeyCode::BackTab => command_prompt.complete(&names, false),
+                KeyCode::Backspace => command_prompt.backspace(),
+                KeyCode::Enter => {
+                    let name = command_prompt.input.trim().to_string();
+                    let path = nav
+                        .get_selected_node()
+                        .map(|node| node.path.clone())
+                        .unwrap_or_else(|| nav.root().path.clone());
+                    if plugins.run_command(&name, &path) {
+                        command_prompt.close();
+                    } else {
+                        command_prompt.error = Some("unknown command".to_string());
+                    }
+                }
+                KeyCode::Char(c) => command_prompt.add_char(c),
+                _ => {}
+            }
+            return Ok(Some(PathBuf::new()));
+        }
+
+        // Live filter prompt: the tree narrows as the filter is typed
+        if ui.live_filter_open {
+            let mut filter = nav.live_filter.clone();
+            match key.code {
+                KeyCode::Esc => {
+                    filter.clear();
+                    ui.live_filter_open = false;
+                }
+                KeyCode::Enter => ui.live_filter_open = false,
+                KeyCode::Up => nav.move_up(),
+                KeyCode::Down => nav.move_down(),
+                KeyCode::Backspace => {
+                    filter.pop();
+                }
+                KeyCode::Char(c) => filter.push(c),
+                _ => {}
+            }
+            if filter != nav.live_filter {
+                nav.set_live_filter(&filter);
+            }
+            return Ok(Some(PathBuf::new()));
+        }
+
+        // Drive selection mode (reached by going up from a drive root)
+        if drives.is_selecting {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => {
+                    drives.exit_selection_mode();
+                }
+                KeyCode::Char('j') | KeyCode::Down => {
+                    drives.move_down();
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    drives.move_up();
+                }
+                KeyCode::Enter | KeyCode::Char('l') | KeyCode::Right => {
+                    if let Some(drive) = drives.get_selected_drive().cloned() {
+                        drives.exit_selection_mode();
+
+                        // Try to navigate and check for errors (e.g. empty card reader)
+                        if let Ok(Some(error_msg)) = nav.go_to_directory(drive.clone(), *show_files)
+                        {
+                            ui.notifications.error(tr_with(
+                                "Cannot access drive {drive}: {error}",
+                                &[("drive", &drive.display()), ("error", &error_msg)],
+                            ));
+                        }
+                    } else {
+                        drives.exit_selection_mode();
+                    }
+                }
+                _ => {}
+            }
+            return Ok(Some(PathBuf::new()));
+        }
+
+        // Projects panel (detected projects around the root)
+        if ui.projects.is_selecting {
+            let mut go = false;
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => ui.projects.exit_selection_mode(),
+                KeyCode::Char('j') | KeyCode::Down => ui.projects.move_down(),
+                KeyCode::Char('k') | KeyCode::Up => ui.projects.move_up(),
+                KeyCode::Char(c @ '1'..='9') => {
+                    go = ui.projects.select_number(c as usize - '0' as usize);
+                }
+                KeyCode::Enter | KeyCode::Char('l') | KeyCode::Right => go = true,
+                // Pressing the projects key again closes the panel
+                _ if config.keybindings.is_projects(key.code) => ui.projects.exit_selection_mode(),
+                _ => {}
+            }
+
+            if go {
+                if let Some(project) = ui.projects.get_selected() {
+                    let dir = project.path.clone();
+                    ui.projects.exit_selection_mode();
+                    Self::change_root(dir, nav, file_viewer, ui, plugins, config, *show_files)?;
+                }
+            }
+            return Ok(Some(PathBuf::new()));
+        }
+
+        // Named layouts picker: type a name to save the current layout
+        if let Some(name) = &mut ui.layout_picker.naming {
+            match key.code {
+                KeyCode::Esc => ui.layout_picker.naming = None,
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                KeyCode::Char(c) => name.push(c),
+                KeyCode::Enter => {
+                    let name = name.trim().to_string();
+                    if !name.is_empty() {
+                        let named = NamedLayout {
+                            name: name.clone(),
+                            layout: Layout {
+                                split_position: ui.split_position,
+                                bottom_panel_split_position: ui.bottom_panel_split_position,
+                            },
+                            show_files: *show_files,
+                            show_sizes: *show_sizes,
+                        };
+                        match ui.layout_picker.save_as(named) {
+                            Ok(()) => ui.notifications.info(format!("Saved layout '{}'", name)),
+                            Err(e) => ui
+                                .notifications
+                                .error(format!("Cannot save layout: {:#}", e)),
+                        }
+                        ui.layout_picker.naming = None;
+                    }
+                }
+                _ => {}
+            }
+            return Ok(Some(PathBuf::new()));
+        }
+
+        // Named layouts picker (restore a layout saved with `s` here)
+        if ui.layout_picker.is_open {
+            let mut restore = false;
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => ui.layout_picker.close(),
+                KeyCode::Char('j') | KeyCode::Down => ui.layout_picker.move_down(),
+                KeyCode::Char('k') | KeyCode::Up => ui.layout_picker.move_up(),
+                KeyCode::Char(c @ '1'..='9') => {
+                    restore = ui.layout_picker.select_number(c as usize - '0' as usize);
+                }
+                KeyCode::Enter | KeyCode::Char(' ') => restore = true,
+                KeyCode::Char('s') => ui.layout_picker.naming = Some(String::new()),
+                KeyCode::Char('d') => match ui.layout_picker.delete_selected() {
+                    Ok(Some(name)) => ui.notifications.info(format!("Deleted layout '{}'", name)),
+                    Ok(None) => {}
+                    Err(e) => ui
+                        .notifications
+                        .error(format!("Cannot save layouts: {:#}", e)),
+                },
+                _ if config.keybindings.is_layouts(key.code) => ui.layout_picker.close(),
+                _ => {}
+            }
+
+            if restore {
+                if let Some(named) = ui.layout_picker.selected().cloned() {
+                    ui.layout_picker.close();
+                    Self::apply_layout(
+                        &named,
+                        nav,
+                        file_viewer,
+                        ui,
+                        plugins,
+                        dir_size_cache,
+                        config,
+                        show_files,
+                        show_help,
+                        show_sizes,
+                    )?;
+                }
+            }
+            return Ok(Some(PathBuf::new()));
+        }
+
+        // Saved filters picker (toggle a filter from the [filters] config section)
+        if ui.filter_picker.is_open {
+            let mut toggle = false;
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => ui.filter_picker.close(),
+                KeyCode::Char('j') | KeyCode::Down => ui.filter_picker.move_down(),
+                KeyCode::Char('k') | KeyCode::Up => ui.filter_picker.move_up(),
+                KeyCode::Char(c @ '1'..='9') => {
+                    toggle = ui.filter_picker.select_number(c as usize - '0' as usize);
+                }
+                KeyCode::Enter | KeyCode::Char(' ') => toggle = true,
+                _ if config.keybindings.is_saved_filters(key.code) => ui.filter_picker.close(),
+                _ => {}
+            }
+
+            if toggle {
+                if let Some(filter) = ui.filter_picker.toggle(&nav.filters.quick_filter) {
+                    ui.filter_picker.close();
+                    Self::apply_quick_filter(
+                        filter,
+                        nav,
+                        file_viewer,
+                        ui,
+                        plugins,
+                        config,
+                        show_files,
+                        show_help,
+                    )?;
+                }
+            }
+            return Ok(Some(PathBuf::new()));
         }
 
         // Bookmark selection mode (navigation + filter)
@@ -79,45 +610,44 @@ impl EventHandler {
                 KeyCode::Enter => {
                     // Select currently highlighted bookmark (not by name)
                     if let Some(bookmark) = bookmarks.get_selected_bookmark() {
-                        let path = bookmark.path.clone();
+                        let path = match bookmark.resolve(&nav.root().path, &config.workspaces) {
+                            Ok(path) => path,
+                            Err(e) => {
+                                ui.notifications.error(format!("{:#}", e));
+                                return Ok(Some(PathBuf::new()));
+                            }
+                        };
                         let bookmark_key = bookmark.key.clone();
                         let dir_name = bookmark
                             .name
                             .clone()
                             .unwrap_or_else(|| bookmark_key.clone());
+                        let compare_mode = bookmarks.compare_mode;
                         bookmarks.exit_selection_mode();
 
-                        // Try to navigate and check for errors
-                        if let Ok(Some(error_msg)) = nav.go_to_directory(path, *show_files) {
-                            // Error occurred - show details in file viewer if show_files is enabled
-                            if *show_files {
-                                let error_content = vec![
-                                    format!(
-                                        "Error accessing bookmark '{}' ({})",
-                                        bookmark_key, dir_name
-                                    ),
-                                    String::new(),
-                                    error_msg,
-                                    String::new(),
-                                    "This directory cannot be accessed. Possible reasons:"
-                                        .to_string(),
-                                    "- Insufficient permissions".to_string(),
-                                    "- Directory was removed or renamed".to_string(),
-                                    "- Filesystem error".to_string(),
-                                ];
-                                file_viewer.load_content(error_content);
-                                *show_help = false;
-                            }
+                        if compare_mode {
+                            Self::compare_with(&path, nav, ui, show_files, show_help)?;
+                        } else if let Ok(Some(error_msg)) = nav.go_to_directory(path, *show_files) {
+                            // Error occurred - show it as a toast (kept in the message log)
+                            ui.notifications.error(tr_with(
+                                "Cannot access bookmark '{bookmark}' ({dir}): {error}",
+                                &[
+                                    ("bookmark", &bookmark_key),
+                                    ("dir", &dir_name),
+                                    ("error", &error_msg),
+                                ],
+                            ));
                         } else {
                             // Success - load file preview if needed
                             if *show_files {
                                 if let Some(node) = nav.get_selected_node() {
                                     let _ = ui.load_file_for_viewer(
                                         file_viewer,
-                                        &node.borrow().path,
+                                        &node.path,
                                         config.behavior.max_file_lines,
                                         false,
                                         config,
+                                        plugins,
                                     );
                                 }
                             }
@@ -188,18 +718,16 @@ impl EventHandler {
                     let bookmark_name = bookmarks.get_input().to_string();
                     if !bookmark_name.is_empty() {
                         if let Some(node) = nav.get_selected_node() {
-                            let node_borrowed = node.borrow();
                             // Bookmarks must be directories only
-                            let path = if node_borrowed.is_dir {
+                            let path = if node.is_dir {
                                 // Directory - use it directly
-                                node_borrowed.path.clone()
+                                node.path.clone()
                             } else {
                                 // File - use parent directory
-                                node_borrowed
-                                    .path
+                                node.path
                                     .parent()
                                     .map(|p| p.to_path_buf())
-                                    .unwrap_or_else(|| node_borrowed.path.clone())
+                                    .unwrap_or_else(|| node.path.clone())
                             };
 
                             let dir_name = path
@@ -207,7 +735,11 @@ impl EventHandler {
                                 .and_then(|n| n.to_str())
                                 .map(|s| s.to_string());
 
-                            drop(node_borrowed);
+                            oplog::record(format!(
+                                "Created bookmark '{}' -> {}",
+                                bookmark_name,
+                                path.display()
+                            ));
                             let _ = bookmarks.add(bookmark_name, path, dir_name);
                         }
                     }
@@ -222,6 +754,14 @@ impl EventHandler {
                     bookmarks.backspace();
                     return Ok(Some(PathBuf::new()));
                 }
+                KeyCode::Tab => {
+                    bookmarks.complete_name(true);
+                    return Ok(Some(PathBuf::new()));
+                }
+                KeyCode::BackTab => {
+                    bookmarks.complete_name(false);
+                    return Ok(Some(PathBuf::new()));
+                }
                 _ => {
                     return Ok(Some(PathBuf::new()));
                 }
@@ -240,6 +780,30 @@ impl EventHandler {
                 return self.handle_visual_mode_input(key, file_viewer, ui, config);
             }
 
+            // Name of a mark after m (set) or ' (jump); any other key cancels
+            if let Some(mark_key) = file_viewer.mark_key.take() {
+                if let KeyCode::Char(mark) = key.code {
+                    match mark_key {
+                        MarkKey::Set => file_viewer.set_mark(mark),
+                        MarkKey::Jump => {
+                            if !file_viewer.jump_to_mark(mark) {
+                                ui.notifications.info(format!("Mark '{}' is not set", mark));
+                            }
+                        }
+                    }
+                }
+                return Ok(Some(PathBuf::new()));
+            }
+
+            // zt, zz, zb: put the current line at the top, middle or bottom
+            if self.pending_z.take().is_some() {
+                if let Some(placement) = Self::placement(key.code) {
+                    let visible_height = ui.viewer_area_height.saturating_sub(4) as usize;
+                    file_viewer.place(placement, visible_height);
+                }
+                return Ok(Some(PathBuf::new()));
+            }
+
             // Handle Esc key - clear search if active, otherwise exit
             if matches!(key.code, KeyCode::Esc) {
                 if !file_viewer.search_results.is_empty() {
@@ -265,10 +829,11 @@ impl EventHandler {
                         if let Some(node) = nav.get_selected_node() {
                             let _ = ui.load_file_for_viewer(
                                 file_viewer,
-                                &node.borrow().path,
+                                &node.path,
                                 config.behavior.max_file_lines,
                                 true,
                                 config,
+                                plugins,
                             );
                         }
                         return Ok(Some(PathBuf::new()));
@@ -279,10 +844,11 @@ impl EventHandler {
                         if let Some(node) = nav.get_selected_node() {
                             let _ = ui.load_file_for_viewer(
                                 file_viewer,
-                                &node.borrow().path,
+                                &node.path,
                                 config.behavior.max_file_lines,
                                 true,
                                 config,
+                                plugins,
                             );
                         }
                         return Ok(Some(PathBuf::new()));
@@ -295,224 +861,120 @@ impl EventHandler {
             }
 
             // Handle fullscreen-specific keys
+            return self.handle_fullscreen_trigger(
+                Trigger::Key(key.code),
+                nav,
+                file_viewer,
+                show_files,
+                show_help,
+                fullscreen_viewer,
+                need_terminal_clear,
+                ui,
+                plugins,
+                config,
+                pick_mode,
+            );
+        }
+
+        // Handle Ctrl+j/k for scrolling in file viewer or help
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
             match key.code {
-                _ if config.keybindings.is_visual_mode(key.code) => {
-                    // Enter visual selection mode (default: Shift+V)
-                    file_viewer.enter_visual_mode();
-                    return Ok(Some(PathBuf::new()));
-                }
-                KeyCode::Char('/') => {
-                    // Enter file search mode
-                    file_viewer.enter_search_mode();
-                    return Ok(Some(PathBuf::new()));
-                }
-                KeyCode::Char('n') if !file_viewer.search_results.is_empty() => {
-                    // Next search match (only if there are results)
-                    file_viewer.next_match();
-                    return Ok(Some(PathBuf::new()));
-                }
-                KeyCode::Char('N') if !file_viewer.search_results.is_empty() => {
-                    // Previous search match (only if there are results)
-                    file_viewer.prev_match();
-                    return Ok(Some(PathBuf::new()));
-                }
-                KeyCode::Char('j') | KeyCode::Down => {
-                    // Scroll down (j or Down arrow)
-                    let content_height = ui.viewer_area_height.saturating_sub(2) as usize;
-                    let lines_to_show = content_height.saturating_sub(2);
-                    file_viewer.scroll_down(lines_to_show);
-                    return Ok(Some(PathBuf::new()));
-                }
-                KeyCode::Char('k') | KeyCode::Up => {
-                    // Scroll up (k or Up arrow)
-                    file_viewer.scroll_up();
-                    return Ok(Some(PathBuf::new()));
-                }
-                _ if config.keybindings.is_show_line_numbers(key.code) => {
-                    // Toggle line numbers (only in fullscreen mode)
-                    file_viewer.toggle_line_numbers();
-                    return Ok(Some(PathBuf::new()));
-                }
-                _ if config.keybindings.is_toggle_wrap(key.code) => {
-                    // Toggle line wrapping (only in fullscreen mode)
-                    file_viewer.toggle_wrap();
-                    // Save current scroll position
-                    let saved_scroll = file_viewer.scroll;
-                    // Reload the current file to apply wrapping changes
-                    if let Some(node) = nav.get_selected_node() {
-                        let _ = ui.load_file_for_viewer(
-                            file_viewer,
-                            &node.borrow().path,
-                            config.behavior.max_file_lines,
-                            true,
-                            config,
-                        );
-                        // Restore scroll position (clamped to content length)
-                        file_viewer.scroll =
-                            saved_scroll.min(file_viewer.content.len().saturating_sub(1));
+                KeyCode::Char('j') => {
+                    if *show_files || *show_help {
+                        file_viewer.scroll_down_simple();
                     }
                     return Ok(Some(PathBuf::new()));
                 }
-                KeyCode::Char('e') | KeyCode::Char('E') => {
-                    // Open file in editor (or hex editor for binary files)
-                    if let Some(node) = nav.get_selected_node() {
-                        let node_borrowed = node.borrow();
-                        if !node_borrowed.is_dir {
-                            let path = node_borrowed.path.clone();
-                            drop(node_borrowed);
-
-                            // Check if binary file
-                            if file_viewer.is_binary {
-                                // Return special marker for hex editor
-                                let marker_path =
-                                    PathBuf::from(format!("HEXEDITOR:{}", path.display()));
-                                return Ok(Some(marker_path));
-                            } else {
-                                // Return special marker for text editor
-                                let marker_path =
-                                    PathBuf::from(format!("EDITOR:{}", path.display()));
-                                return Ok(Some(marker_path));
-                            }
-                        }
+                KeyCode::Char('k') => {
+                    if *show_files || *show_help {
+                        file_viewer.scroll_up();
                     }
                     return Ok(Some(PathBuf::new()));
                 }
-                KeyCode::Char('c') | KeyCode::Char('C') => {
-                    // Copy path to clipboard
-                    if let Some(node) = nav.get_selected_node() {
-                        if let Ok(mut clipboard) = Clipboard::new() {
-                            let _ = clipboard.set_text(node.borrow().path.display().to_string());
-                        }
+                // Ctrl+arrows move the dividers, like dragging them with the mouse
+                KeyCode::Left | KeyCode::Right => {
+                    if *show_files || *show_help {
+                        ui.resize_split(key.code == KeyCode::Right);
                     }
                     return Ok(Some(PathBuf::new()));
                 }
-                KeyCode::Char('o') | KeyCode::Char('O') => {
-                    // Open in file manager
-                    if let Some(node) = nav.get_selected_node() {
-                        let node_borrowed = node.borrow();
-                        let path_to_open = if node_borrowed.is_dir {
-                            node_borrowed.path.clone()
-                        } else {
-                            node_borrowed
-                                .path
-                                .parent()
-                                .unwrap_or(&node_borrowed.path)
-                                .to_path_buf()
-                        };
-                        drop(node_borrowed);
-                        let marker_path =
-                            PathBuf::from(format!("FILEMGR:{}", path_to_open.display()));
-                        return Ok(Some(marker_path));
+                KeyCode::Up | KeyCode::Down => {
+                    if ui.bottom_panel_height > 0 {
+                        ui.resize_bottom_split(key.code == KeyCode::Up);
                     }
                     return Ok(Some(PathBuf::new()));
                 }
+                _ => {}
+            }
+        }
+
+        // Handle PageUp/PageDown/Home/End in file viewer mode (split view)
+        if *show_files || *show_help {
+            match key.code {
                 KeyCode::PageUp => {
-                    // Scroll up by page
                     let visible_height = ui.viewer_area_height.saturating_sub(4) as usize;
                     file_viewer.scroll_page_up(visible_height);
                     return Ok(Some(PathBuf::new()));
                 }
                 KeyCode::PageDown => {
-                    // Scroll down by page
                     let visible_height = ui.viewer_area_height.saturating_sub(4) as usize;
                     let max_visible_lines = visible_height.saturating_sub(2);
                     file_viewer.scroll_page_down(visible_height, max_visible_lines);
                     return Ok(Some(PathBuf::new()));
                 }
                 KeyCode::Home => {
-                    // Switch to head mode (show first N lines) and reload file
-                    if file_viewer.can_use_tail_mode() && file_viewer.tail_mode {
-                        file_viewer.enable_head_mode();
-                        // Reload file with head mode
-                        if let Some(node) = nav.get_selected_node() {
-                            let _ = ui.load_file_for_viewer(
-                                file_viewer,
-                                &node.borrow().path,
-                                config.behavior.max_file_lines,
-                                true,
-                                config,
-                            );
-                        }
-                    } else {
-                        // Normal Home behavior - jump to top
-                        file_viewer.reset_scroll();
-                    }
+                    file_viewer.reset_scroll();
                     return Ok(Some(PathBuf::new()));
                 }
                 KeyCode::End => {
-                    // Switch to tail mode (show last N lines) and reload file
-                    if file_viewer.can_use_tail_mode() && !file_viewer.tail_mode {
-                        file_viewer.enable_tail_mode();
-                        // Reload file with tail mode
-                        if let Some(node) = nav.get_selected_node() {
-                            let _ = ui.load_file_for_viewer(
-                                file_viewer,
-                                &node.borrow().path,
-                                config.behavior.max_file_lines,
-                                true,
-                                config,
-                            );
-                        }
-                        // Scroll to end after switching to tail mode
-                        let visible_height = ui.viewer_area_height.saturating_sub(4) as usize;
-                        file_viewer.scroll_to_end(visible_height);
-                    } else {
-                        // Normal End behavior - jump to bottom
-                        let visible_height = ui.viewer_area_height.saturating_sub(4) as usize;
-                        file_viewer.scroll_to_end(visible_height);
-                    }
-                    return Ok(Some(PathBuf::new()));
-                }
-                _ => {
-                    // Ignore all other keys in fullscreen mode
+                    let visible_height = ui.viewer_area_height.saturating_sub(4) as usize;
+                    file_viewer.scroll_to_end(visible_height);
                     return Ok(Some(PathBuf::new()));
                 }
+                _ => {}
             }
         }
 
-        // Handle Ctrl+j/k for scrolling in file viewer or help
-        if key.modifiers.contains(KeyModifiers::CONTROL) {
-            match key.code {
-                KeyCode::Char('j') => {
-                    if *show_files || *show_help {
-                        file_viewer.scroll_down_simple();
-                    }
+        // zt, zz, zb: put the selected entry (or the focused viewer's line) at the top,
+        // middle or bottom; z followed by another key toggles the sizes first
+        if self.pending_z.take().is_some() {
+            match Self::placement(key.code) {
+                Some(placement) if ui.focus == Focus::Viewer => {
+                    let visible_height = ui.viewer_area_height.saturating_sub(4) as usize;
+                    file_viewer.place(placement, visible_height);
                     return Ok(Some(PathBuf::new()));
                 }
-                KeyCode::Char('k') => {
-                    if *show_files || *show_help {
-                        file_viewer.scroll_up();
-                    }
+                Some(placement) => {
+                    ui.place_tree_selection(placement);
                     return Ok(Some(PathBuf::new()));
                 }
-                _ => {}
+                None if key.code == KeyCode::Esc => return Ok(Some(PathBuf::new())),
+                None => Self::set_show_sizes(!*show_sizes, nav, dir_size_cache, show_sizes),
             }
         }
 
-        // Handle PageUp/PageDown/Home/End in file viewer mode (split view)
-        if *show_files || *show_help {
-            match key.code {
-                KeyCode::PageUp => {
-                    let visible_height = ui.viewer_area_height.saturating_sub(4) as usize;
-                    file_viewer.scroll_page_up(visible_height);
-                    return Ok(Some(PathBuf::new()));
-                }
-                KeyCode::PageDown => {
-                    let visible_height = ui.viewer_area_height.saturating_sub(4) as usize;
-                    let max_visible_lines = visible_height.saturating_sub(2);
-                    file_viewer.scroll_page_down(visible_height, max_visible_lines);
-                    return Ok(Some(PathBuf::new()));
-                }
-                KeyCode::Home => {
-                    file_viewer.reset_scroll();
-                    return Ok(Some(PathBuf::new()));
-                }
-                KeyCode::End => {
-                    let visible_height = ui.viewer_area_height.saturating_sub(4) as usize;
-                    file_viewer.scroll_to_end(visible_height);
+        // Task overlay: digit keys cancel the listed background task,
+        // other keys (and digits with no task) go on to the usual bindings
+        if tasks.is_overlay_visible() {
+            if let KeyCode::Char(c @ '1'..='9') = key.code {
+                let index = c as usize - '1' as usize;
+                if let Some(task) = tasks.task_at(index) {
+                    let (id, kind) = (task.id, task.kind);
+                    match kind {
+                        TaskKind::Search => search.cancel_search(),
+                        TaskKind::DirSizes => dir_size_cache.cancel(),
+                        TaskKind::Compare => nav.pending_comparison = None,
+                        TaskKind::Listing => nav.pending_listing = None,
+                        TaskKind::FlatListing => nav.pending_flat_listing = None,
+                        TaskKind::FileTransfer => {
+                            // Worker stops after the current file and reports back
+                            file_ops.cancel();
+                            return Ok(Some(PathBuf::new()));
+                        }
+                    }
+                    tasks.finish(id);
                     return Ok(Some(PathBuf::new()));
                 }
-                _ => {}
             }
         }
 
@@ -527,6 +989,10 @@ impl EventHandler {
                 // If showing results, close them
                 search.close_results();
                 return Ok(Some(PathBuf::new()));
+            } else if !nav.live_filter.is_empty() {
+                // A kept live filter is cleared before Esc exits
+                nav.set_live_filter("");
+                return Ok(Some(PathBuf::new()));
             } else {
                 return Ok(None);
             }
@@ -535,14 +1001,17 @@ impl EventHandler {
         // Handle q key - exits with directory change
         // (fullscreen mode already handled above)
         if matches!(key.code, KeyCode::Char('q') | KeyCode::Char('Q')) {
+            // Marked paths for a pipeline instead of the cd target (print_marked_on_exit)
+            if config.behavior.print_marked_on_exit && !nav.marked.is_empty() {
+                return Ok(Some(picker::encode(&nav.marked_or_selected())));
+            }
             // Normal mode: q exits with cd to selected directory (or parent if file)
             if let Some(node) = nav.get_selected_node() {
-                let node_borrowed = node.borrow();
-                if node_borrowed.is_dir {
-                    return Ok(Some(node_borrowed.path.clone()));
+                if node.is_dir {
+                    return Ok(Some(node.path.clone()));
                 } else {
                     // If cursor is on a file, return parent directory
-                    if let Some(parent) = node_borrowed.path.parent() {
+                    if let Some(parent) = node.path.parent() {
                         return Ok(Some(parent.to_path_buf()));
                     }
                 }
@@ -550,315 +1019,1726 @@ impl EventHandler {
             return Ok(None);
         }
 
-        match key.code {
-            _ if config.keybindings.is_search(key.code) => {
+        self.handle_tree_trigger(
+            Trigger::Key(key.code),
+            nav,
+            file_viewer,
+            search,
+            bookmarks,
+            drives,
+            copy_menu,
+            goto,
+            command_prompt,
+            show_files,
+            show_files_before_help,
+            show_help,
+            fullscreen_viewer,
+            tasks,
+            file_ops,
+            ui,
+            plugins,
+            config,
+            pick_mode,
+        )
+    }
+
+    /// Run a command by its name in `[keybindings]` (a completed two-key sequence) the way
+    /// one of its keys would, in the fullscreen viewer or the tree
+    pub fn run_command(
+        &mut self,
+        command: &'static str,
+        nav: &mut Navigation,
+        file_viewer: &mut FileViewer,
+        search: &mut Search,
+        bookmarks: &mut Bookmarks,
+        drives: &mut Drives,
+        copy_menu: &mut CopyPathMenu,
+        goto: &mut GotoPrompt,
+        command_prompt: &mut CommandPrompt,
+        show_files: &mut bool,
+        show_files_before_help: &mut bool,
+        show_help: &mut bool,
+        fullscreen_viewer: &mut bool,
+        tasks: &mut TaskManager,
+        file_ops: &mut FileOps,
+        need_terminal_clear: &mut bool,
+        ui: &mut UI,
+        plugins: &Plugins,
+        config: &Config,
+        pick_mode: Option<PickMode>,
+    ) -> Result<Option<PathBuf>> {
+        let trigger = Trigger::Command(command);
+        if *fullscreen_viewer {
+            return self.handle_fullscreen_trigger(
+                trigger,
+                nav,
+                file_viewer,
+                show_files,
+                show_help,
+                fullscreen_viewer,
+                need_terminal_clear,
+                ui,
+                plugins,
+                config,
+                pick_mode,
+            );
+        }
+        self.handle_tree_trigger(
+            trigger,
+            nav,
+            file_viewer,
+            search,
+            bookmarks,
+            drives,
+            copy_menu,
+            goto,
+            command_prompt,
+            show_files,
+            show_files_before_help,
+            show_help,
+            fullscreen_viewer,
+            tasks,
+            file_ops,
+            ui,
+            plugins,
+            config,
+            pick_mode,
+        )
+    }
+
+    /// Keys and commands of the fullscreen viewer; the others are ignored there
+    fn handle_fullscreen_trigger(
+        &mut self,
+        trigger: Trigger,
+        nav: &mut Navigation,
+        file_viewer: &mut FileViewer,
+        show_files: &mut bool,
+        show_help: &mut bool,
+        fullscreen_viewer: &mut bool,
+        need_terminal_clear: &mut bool,
+        ui: &mut UI,
+        plugins: &Plugins,
+        config: &Config,
+        pick_mode: Option<PickMode>,
+    ) -> Result<Option<PathBuf>> {
+        match trigger {
+            _ if config.keybindings.is_visual_mode(trigger) => {
+                // Enter visual selection mode (default: Shift+V)
+                file_viewer.enter_visual_mode();
+                return Ok(Some(PathBuf::new()));
+            }
+            Trigger::Key(KeyCode::Char('/')) => {
+                // Enter file search mode
+                file_viewer.enter_search_mode();
+                return Ok(Some(PathBuf::new()));
+            }
+            Trigger::Key(KeyCode::Char('n')) if !file_viewer.search_results.is_empty() => {
+                // Next search match (only if there are results)
+                file_viewer.next_match();
+                return Ok(Some(PathBuf::new()));
+            }
+            Trigger::Key(KeyCode::Char('N')) if !file_viewer.search_results.is_empty() => {
+                // Previous search match (only if there are results)
+                file_viewer.prev_match();
+                return Ok(Some(PathBuf::new()));
+            }
+            Trigger::Key(KeyCode::Char('m')) => {
+                file_viewer.mark_key = Some(MarkKey::Set);
+                return Ok(Some(PathBuf::new()));
+            }
+            Trigger::Key(KeyCode::Char('\'')) => {
+                file_viewer.mark_key = Some(MarkKey::Jump);
+                return Ok(Some(PathBuf::new()));
+            }
+            Trigger::Key(KeyCode::Char('z')) => {
+                self.pending_z = Some(Instant::now());
+                return Ok(Some(PathBuf::new()));
+            }
+            Trigger::Key(KeyCode::Char('j') | KeyCode::Down) => {
+                // Scroll down (j or Down arrow)
+                let content_height = ui.viewer_area_height.saturating_sub(2) as usize;
+                let lines_to_show = content_height.saturating_sub(2);
+                file_viewer.scroll_down(lines_to_show);
+                return Ok(Some(PathBuf::new()));
+            }
+            Trigger::Key(KeyCode::Char('k') | KeyCode::Up) => {
+                // Scroll up (k or Up arrow)
+                file_viewer.scroll_up();
+                return Ok(Some(PathBuf::new()));
+            }
+            _ if config.keybindings.is_show_line_numbers(trigger) => {
+                // Toggle line numbers (only in fullscreen mode); they narrow the text
+                file_viewer.toggle_line_numbers();
+                file_viewer.wrap_width = ui.viewer_text_width(file_viewer, true);
+                return Ok(Some(PathBuf::new()));
+            }
+            _ if config.keybindings.is_git_view(trigger) => {
+                Self::cycle_git_view(nav, file_viewer, ui, plugins, config, true);
+                return Ok(Some(PathBuf::new()));
+            }
+            _ if config.keybindings.is_reveal(trigger) => {
+                // Back to the tree, which shows where the viewed file is
+                if let Some(node) = nav.get_selected_node() {
+                    let path = node.path.clone();
+                    *fullscreen_viewer = false;
+                    *need_terminal_clear = true;
+                    Self::reveal_in_tree(
+                        &path,
+                        nav,
+                        file_viewer,
+                        ui,
+                        plugins,
+                        config,
+                        show_files,
+                        show_help,
+                    )?;
+                }
+                return Ok(Some(PathBuf::new()));
+            }
+            _ if config.keybindings.is_reload_file(trigger) => {
+                let _ = ui.reload_file_for_viewer(
+                    file_viewer,
+                    config.behavior.max_file_lines,
+                    true,
+                    config,
+                    plugins,
+                );
+                return Ok(Some(PathBuf::new()));
+            }
+            _ if config.keybindings.is_toggle_wrap(trigger) => {
+                // Toggle line wrapping (only in fullscreen mode); lines are wrapped
+                // when drawn, so the scroll position stays on the same line
+                file_viewer.toggle_wrap();
+                return Ok(Some(PathBuf::new()));
+            }
+            Trigger::Key(KeyCode::Char('e' | 'E' | 'o' | 'O')) if pick_mode.is_some() => {
+                ui.notifications.info("Not available while picking");
+                return Ok(Some(PathBuf::new()));
+            }
+            Trigger::Key(KeyCode::Char('e' | 'E' | 'o' | 'O')) if !nav.vfs.is_local() => {
+                Self::not_local(ui);
+                return Ok(Some(PathBuf::new()));
+            }
+            Trigger::Key(KeyCode::Char('e') | KeyCode::Char('E')) => {
+                // Open file in editor (or hex editor for binary files)
+                if let Some(node) = nav.get_selected_node() {
+                    if !node.is_dir {
+                        let path = node.path.clone();
+
+                        return Ok(Some(Self::open_editor(
+                            path,
+                            file_viewer.is_binary,
+                            file_viewer.current_line(),
+                            config,
+                        )));
+                    }
+                }
+                return Ok(Some(PathBuf::new()));
+            }
+            Trigger::Key(KeyCode::Char('c') | KeyCode::Char('C')) => {
+                // Copy path to clipboard
+                if let Some(node) = nav.get_selected_node() {
+                    let text = node.path.display().to_string();
+                    let _ = clipboard::copy(&text);
+                    oplog::record(format!("Copied path: {}", text));
+                }
+                return Ok(Some(PathBuf::new()));
+            }
+            Trigger::Key(KeyCode::Char('o') | KeyCode::Char('O')) => {
+                // Open in file manager
+                if let Some(node) = nav.get_selected_node() {
+                    let path_to_open = if node.is_dir {
+                        node.path.clone()
+                    } else {
+                        node.path.parent().unwrap_or(&node.path).to_path_buf()
+                    };
+                    return Ok(Some(Self::open_file_manager(path_to_open, config)));
+                }
+                return Ok(Some(PathBuf::new()));
+            }
+            Trigger::Key(KeyCode::PageUp) => {
+                // Scroll up by page
+                let visible_height = ui.viewer_area_height.saturating_sub(4) as usize;
+                file_viewer.scroll_page_up(visible_height);
+                return Ok(Some(PathBuf::new()));
+            }
+            Trigger::Key(KeyCode::PageDown) => {
+                // Scroll down by page
+                let visible_height = ui.viewer_area_height.saturating_sub(4) as usize;
+                let max_visible_lines = visible_height.saturating_sub(2);
+                file_viewer.scroll_page_down(visible_height, max_visible_lines);
+                return Ok(Some(PathBuf::new()));
+            }
+            Trigger::Key(KeyCode::Home) => {
+                // Switch to head mode (show first N lines) and reload file
+                if file_viewer.can_use_tail_mode() && file_viewer.tail_mode {
+                    file_viewer.enable_head_mode();
+                    // Reload file with head mode
+                    if let Some(node) = nav.get_selected_node() {
+                        let _ = ui.load_file_for_viewer(
+                            file_viewer,
+                            &node.path,
+                            config.behavior.max_file_lines,
+                            true,
+                            config,
+                            plugins,
+                        );
+                    }
+                    file_viewer.reset_scroll();
+                } else {
+                    // Normal Home behavior - jump to top
+                    file_viewer.reset_scroll();
+                }
+                return Ok(Some(PathBuf::new()));
+            }
+            Trigger::Key(KeyCode::End) => {
+                // Switch to tail mode (show last N lines) and reload file
+                if file_viewer.can_use_tail_mode() && !file_viewer.tail_mode {
+                    file_viewer.enable_tail_mode();
+                    // Reload file with tail mode
+                    if let Some(node) = nav.get_selected_node() {
+                        let _ = ui.load_file_for_viewer(
+                            file_viewer,
+                            &node.path,
+                            config.behavior.max_file_lines,
+                            true,
+                            config,
+                            plugins,
+                        );
+                    }
+                    // Scroll to end after switching to tail mode
+                    let visible_height = ui.viewer_area_height.saturating_sub(4) as usize;
+                    file_viewer.scroll_to_end(visible_height);
+                } else {
+                    // Normal End behavior - jump to bottom
+                    let visible_height = ui.viewer_area_height.saturating_sub(4) as usize;
+                    file_viewer.scroll_to_end(visible_height);
+                }
+                return Ok(Some(PathBuf::new()));
+            }
+            // Ignore all other keys in fullscreen mode
+            _ => {}
+        }
+
+        Ok(Some(PathBuf::new()))
+    }
+
+    /// Keys and commands of the tree, the results panel and the split viewer
+    fn handle_tree_trigger(
+        &mut self,
+        trigger: Trigger,
+        nav: &mut Navigation,
+        file_viewer: &mut FileViewer,
+        search: &mut Search,
+        bookmarks: &mut Bookmarks,
+        drives: &mut Drives,
+        copy_menu: &mut CopyPathMenu,
+        goto: &mut GotoPrompt,
+        command_prompt: &mut CommandPrompt,
+        show_files: &mut bool,
+        show_files_before_help: &mut bool,
+        show_help: &mut bool,
+        fullscreen_viewer: &mut bool,
+        tasks: &mut TaskManager,
+        file_ops: &mut FileOps,
+        ui: &mut UI,
+        plugins: &Plugins,
+        config: &Config,
+        pick_mode: Option<PickMode>,
+    ) -> Result<Option<PathBuf>> {
+        match trigger {
+            // Results panel: mark the selected result for the panel's bulk actions (copy,
+            // add to the tree's marks, open in the editor)
+            _ if ui.focus == Focus::Results && config.keybindings.is_toggle_mark(trigger) => {
+                search.toggle_mark();
+                search.move_down();
+            }
+            _ if config.keybindings.is_reveal(trigger) => {
+                let path = if ui.focus == Focus::Results {
+                    search.get_selected_result()
+                } else {
+                    nav.get_selected_node().map(|node| node.path.clone())
+                };
+                if let Some(path) = path {
+                    Self::reveal_in_tree(
+                        &path,
+                        nav,
+                        file_viewer,
+                        ui,
+                        plugins,
+                        config,
+                        show_files,
+                        show_help,
+                    )?;
+                }
+            }
+            _ if config.keybindings.is_live_filter(trigger) => {
+                ui.live_filter_open = true;
+            }
+            _ if config.keybindings.is_fuzzy_jump(trigger) => {
+                ui.fuzzy_jump.open(&nav.tree);
+                ui.fuzzy_jump.update_preview(&nav.tree, nav.vfs.as_ref());
+            }
+            // The marked results (all of them when none are) join the tree's marks, for
+            // yank, cut, delete and `*`
+            _ if ui.focus == Focus::Results && config.keybindings.is_mark_results(trigger) => {
+                let count = nav.marked.len();
+                if search.marked.is_empty() {
+                    nav.marked
+                        .extend(search.results.iter().map(|result| result.path.clone()));
+                } else {
+                    nav.marked.extend(search.marked.drain());
+                }
+                let added = nav.marked.len() - count;
+                oplog::record(format!("Marked {} search result(s)", added));
+                ui.notifications.info(format!(
+                    "Marked {} result(s) ({} marked)",
+                    added,
+                    nav.marked.len()
+                ));
+            }
+            // Results panel: make the selected result's directory the root, far as it may
+            // be from the current one, with the result selected
+            _ if ui.focus == Focus::Results && config.keybindings.is_root_at_result(trigger) => {
+                if let Some(path) = search.get_selected_result() {
+                    let dir = path.parent().unwrap_or(&path).to_path_buf();
+                    if let Some(error_msg) = nav.go_to_directory(dir, *show_files)? {
+                        ui.notifications.error(error_msg);
+                    } else {
+                        Self::jump_to_result(
+                            search,
+                            nav,
+                            file_viewer,
+                            ui,
+                            plugins,
+                            *show_files,
+                            show_help,
+                            config,
+                        );
+                        ui.focus = Focus::Tree;
+                    }
+                }
+            }
+            // Step through the results in the tree, wherever the focus is
+            _ if search.show_results
+                && (config.keybindings.is_next_result(trigger)
+                    || config.keybindings.is_previous_result(trigger)) =>
+            {
+                if config.keybindings.is_next_result(trigger) {
+                    search.move_down();
+                } else {
+                    search.move_up();
+                }
+                Self::jump_to_result(
+                    search,
+                    nav,
+                    file_viewer,
+                    ui,
+                    plugins,
+                    *show_files,
+                    show_help,
+                    config,
+                );
+            }
+            _ if config.keybindings.is_search(trigger) => {
                 search.enter_mode();
                 return Ok(Some(PathBuf::new()));
             }
-            KeyCode::Tab => {
-                search.toggle_focus();
+            _ if config.keybindings.is_toggle_tasks(trigger) => {
+                tasks.toggle_overlay();
+            }
+            _ if config.keybindings.is_toggle_mark(trigger) => {
+                if let Some(node) = nav.get_selected_node() {
+                    let path = node.path.clone();
+                    nav.toggle_mark(&path);
+                    nav.move_down();
+                }
+            }
+            // Picker mode only chooses paths: no editor, file manager or copy/move
+            _ if pick_mode.is_some()
+                && (config.keybindings.is_open_editor(trigger)
+                    || config.keybindings.is_open_file_manager(trigger)
+                    || config.keybindings.is_open_marked(trigger)
+                    || config.keybindings.is_yank(trigger)
+                    || config.keybindings.is_cut(trigger)
+                    || config.keybindings.is_paste(trigger)) =>
+            {
+                ui.notifications.info("Not available while picking");
+            }
+            // Remote trees (--s3) have nothing on this machine to open or paste into
+            _ if !nav.vfs.is_local()
+                && (config.keybindings.is_open_editor(trigger)
+                    || config.keybindings.is_open_file_manager(trigger)
+                    || config.keybindings.is_open_marked(trigger)
+                    || config.keybindings.is_paste(trigger)) =>
+            {
+                Self::not_local(ui);
+            }
+            _ if config.keybindings.is_yank(trigger) => {
+                let paths = nav.marked_or_selected();
+                oplog::record(format!("Yanked {} item(s)", paths.len()));
+                file_ops.yank(TransferMode::Copy, paths);
+                nav.marked.clear();
+            }
+            _ if config.keybindings.is_cut(trigger) => {
+                let paths = nav.marked_or_selected();
+                oplog::record(format!("Cut {} item(s)", paths.len()));
+                file_ops.yank(TransferMode::Move, paths);
+                nav.marked.clear();
+            }
+            _ if config.keybindings.is_paste(trigger) => {
+                if let Some(node) = nav.get_selected_node() {
+                    // Paste into the directory under the cursor, or next to the selected file
+                    let dest_dir = if node.is_dir {
+                        node.path.clone()
+                    } else {
+                        node.path
+                            .parent()
+                            .map(|p| p.to_path_buf())
+                            .unwrap_or_else(|| node.path.clone())
+                    };
+
+                    oplog::record(format!("Paste into {}", dest_dir.display()));
+                    if let Some(error_msg) = file_ops.start_paste(dest_dir) {
+                        ui.notifications.warn(error_msg);
+                    }
+                }
+            }
+            Trigger::Key(KeyCode::Tab | KeyCode::BackTab) => {
+                let forward = trigger == Trigger::Key(KeyCode::Tab);
+                ui.cycle_focus(forward, *show_files, search.show_results);
                 return Ok(Some(PathBuf::new()));
             }
-            KeyCode::Char('j') | KeyCode::Down => {
-                if search.focus_on_results {
+            Trigger::Key(KeyCode::Char('j') | KeyCode::Down) if ui.focus == Focus::Viewer => {
+                let content_height = ui.viewer_area_height.saturating_sub(2) as usize;
+                file_viewer.scroll_down(content_height.saturating_sub(2));
+            }
+            Trigger::Key(KeyCode::Char('k') | KeyCode::Up) if ui.focus == Focus::Viewer => {
+                file_viewer.scroll_up();
+            }
+            _ if ui.focus == Focus::Viewer && config.keybindings.is_reload_file(trigger) => {
+                let _ = ui.reload_file_for_viewer(
+                    file_viewer,
+                    config.behavior.max_file_lines,
+                    false,
+                    config,
+                    plugins,
+                );
+            }
+            Trigger::Key(KeyCode::Char('j') | KeyCode::Down) => {
+                if ui.focus == Focus::Results {
                     search.move_down();
                 } else {
                     nav.move_down();
                     if *show_files || *fullscreen_viewer {
                         if let Some(node) = nav.get_selected_node() {
-                            let _ = ui.load_file_for_viewer(
+                            let _ = ui.request_file_for_viewer(
                                 file_viewer,
-                                &node.borrow().path,
+                                &node.path,
                                 config.behavior.max_file_lines,
                                 *fullscreen_viewer,
                                 config,
+                                plugins,
                             );
                             *show_help = false;
                         }
                     }
                 }
             }
-            KeyCode::Char('k') | KeyCode::Up => {
-                if search.focus_on_results {
+            Trigger::Key(KeyCode::Char('k') | KeyCode::Up) => {
+                if ui.focus == Focus::Results {
                     search.move_up();
                 } else {
                     nav.move_up();
                     if *show_files || *fullscreen_viewer {
                         if let Some(node) = nav.get_selected_node() {
-                            let _ = ui.load_file_for_viewer(
+                            let _ = ui.request_file_for_viewer(
                                 file_viewer,
-                                &node.borrow().path,
+                                &node.path,
                                 config.behavior.max_file_lines,
                                 *fullscreen_viewer,
                                 config,
+                                plugins,
                             );
                             *show_help = false;
                         }
                     }
                 }
             }
-            KeyCode::Enter => {
-                if search.focus_on_results && search.show_results {
+            Trigger::Key(KeyCode::Enter) => {
+                if ui.focus == Focus::Results {
                     // In search mode: jump to search result
-                    if let Some(path) = search.get_selected_result() {
-                        let _ = nav.expand_path_to_node(&path, *show_files);
-                        search.focus_on_results = false;
-                        if *show_files {
-                            let _ = ui.load_file_for_viewer(
-                                file_viewer,
-                                &path,
-                                config.behavior.max_file_lines,
-                                false,
-                                config,
-                            );
-                            *show_help = false;
-                        }
-                    }
+                    Self::jump_to_result(
+                        search,
+                        nav,
+                        file_viewer,
+                        ui,
+                        plugins,
+                        *show_files,
+                        show_help,
+                        config,
+                    );
+                    ui.focus = Focus::Tree;
                     return Ok(Some(PathBuf::new()));
                 } else {
+                    // Picker mode: Enter confirms the selection
+                    if let Some(mode) = pick_mode {
+                        let accepted = nav
+                            .get_selected_node()
+                            .filter(|node| mode.accepts(node.is_dir));
+                        let paths: Vec<PathBuf> = match (mode, accepted) {
+                            (PickMode::Multi | PickMode::Files, Some(_)) => {
+                                nav.marked_or_selected()
+                            }
+                            (_, Some(node)) => vec![node.path.clone()],
+                            (_, None) => Vec::new(),
+                        };
+                        if !paths.is_empty() {
+                            return Ok(Some(picker::encode(&paths)));
+                        }
+                    }
+
                     // Normal mode: Enter on directory -> go inside (change root)
                     if let Some(node) = nav.get_selected_node() {
-                        let node_borrowed = node.borrow();
-                        if node_borrowed.is_dir {
-                            let path = node_borrowed.path.clone();
-                            let dir_name = node_borrowed.name.clone();
-                            drop(node_borrowed);
+                        if node.is_dir {
+                            let path = node.path.clone();
+                            let dir_name = node.name.clone();
+
+                            // Virtual tree (--stdin): the listed paths are all there is,
+                            // so expand/collapse in place instead of leaving it
+                            if nav.is_virtual() {
+                                let _ = nav.toggle_node(&path, *show_files)?;
+                                return Ok(Some(PathBuf::new()));
+                            }
 
                             // Try to navigate and check for errors
                             if let Ok(Some(error_msg)) = nav.go_to_directory(path, *show_files) {
-                                // Error occurred - show details in file viewer if show_files is enabled
-                                if *show_files {
-                                    let error_content = vec![
-                                        format!("Error accessing directory: {}", dir_name),
-                                        String::new(),
-                                        error_msg,
-                                        String::new(),
-                                        "This directory cannot be accessed. Possible reasons:"
-                                            .to_string(),
-                                        "- Insufficient permissions".to_string(),
-                                        "- Directory was removed or renamed".to_string(),
-                                        "- Filesystem error".to_string(),
-                                    ];
-                                    file_viewer.load_content(error_content);
-                                    *show_help = false;
-                                }
+                                // Error occurred - show it as a toast (kept in the message log)
+                                ui.notifications.error(tr_with(
+                                    "Cannot access directory '{dir}': {error}",
+                                    &[("dir", &dir_name), ("error", &error_msg)],
+                                ));
                             } else {
                                 // Success - load file preview if needed
                                 if *show_files {
                                     if let Some(node) = nav.get_selected_node() {
                                         let _ = ui.load_file_for_viewer(
                                             file_viewer,
-                                            &node.borrow().path,
+                                            &node.path,
                                             config.behavior.max_file_lines,
                                             false,
                                             config,
+                                            plugins,
                                         );
                                     }
                                 }
                             }
+                        } else {
+                            let path = node.path.clone();
+                            return self.handle_enter_on_file(
+                                path,
+                                file_viewer,
+                                show_help,
+                                fullscreen_viewer,
+                                ui,
+                                plugins,
+                                config,
+                            );
+                        }
+                    }
+                }
+            }
+            // Results: fold the content matches of a file into one row and back
+            Trigger::Key(KeyCode::Char('h') | KeyCode::Left) if ui.focus == Focus::Results => {
+                search.collapse_group();
+            }
+            Trigger::Key(KeyCode::Char('l') | KeyCode::Right) if ui.focus == Focus::Results => {
+                search.expand_group();
+            }
+            Trigger::Key(KeyCode::Char('l') | KeyCode::Right) if ui.focus == Focus::Tree => {
+                if let Some(node) = nav.get_selected_node() {
+                    if node.is_dir {
+                        let path = node.path.clone();
+                        let dir_name = node.name.clone();
+
+                        // Toggle node and check for errors
+                        if let Ok(Some(error_msg)) = nav.toggle_node(&path, *show_files) {
+                            // Error occurred - show it as a toast (kept in the message log)
+                            ui.notifications.error(tr_with(
+                                "Cannot read directory '{dir}': {error}",
+                                &[("dir", &dir_name), ("error", &error_msg)],
+                            ));
+                        }
+                    }
+                }
+            }
+            Trigger::Key(KeyCode::Char('l') | KeyCode::Right) => {}
+            Trigger::Key(KeyCode::Char('h') | KeyCode::Left) => {
+                if let Some(node) = nav.get_selected_node() {
+                    if node.is_dir {
+                        let path = node.path.clone();
+                        let _ = nav.toggle_node(&path, *show_files)?;
+                    }
+                }
+            }
+            Trigger::Key(KeyCode::Char('u') | KeyCode::Backspace) => {
+                if nav.is_at_filesystem_root() {
+                    // Already at a drive root - offer the other drives (Windows)
+                    let root_path = nav.root().path.clone();
+                    drives.enter_selection_mode(&root_path);
+                } else {
+                    nav.go_to_parent(*show_files)?;
+                }
+            }
+            _ if config.keybindings.is_toggle_files(trigger) => {
+                Self::set_show_files(
+                    !*show_files,
+                    nav,
+                    file_viewer,
+                    ui,
+                    plugins,
+                    config,
+                    show_files,
+                    show_help,
+                )?;
+            }
+            _ if config.keybindings.is_toggle_help(trigger) => {
+                *show_help = !*show_help;
+
+                if *show_help {
+                    // Save current show_files state before opening help
+                    *show_files_before_help = *show_files;
+
+                    // Load help content into file viewer for scrolling, with the keys
+                    // as configured at the end
+                    let mut help = crate::ui::get_help_content();
+                    help.push(String::new());
+                    help.extend(crate::keymap::report(config));
+                    file_viewer.load_content(help);
+                    if !*show_files {
+                        *show_files = true;
+                        nav.reload_tree(*show_files)?;
+                    }
+                } else {
+                    // Restore previous show_files state
+                    if *show_files != *show_files_before_help {
+                        *show_files = *show_files_before_help;
+                        nav.reload_tree(*show_files)?;
+                    }
+                    file_viewer.reset_scroll();
+                }
+            }
+            Trigger::Key(KeyCode::Char('v')) => {
+                // Toggle fullscreen viewer mode
+                if let Some(node) = nav.get_selected_node() {
+                    if !node.is_dir {
+                        *fullscreen_viewer = !*fullscreen_viewer;
+                        *show_help = false;
+
+                        if *fullscreen_viewer {
+                            // Load file for fullscreen viewing with full terminal width
+                            let _ = ui.load_file_for_viewer(
+                                file_viewer,
+                                &node.path,
+                                config.behavior.max_file_lines,
+                                true,
+                                config,
+                                plugins,
+                            );
+                        }
+                    }
+                }
+            }
+            // Results panel: the marked results' paths (or the selected one's), one per line
+            _ if ui.focus == Focus::Results && config.keybindings.is_copy_path(trigger) => {
+                let paths = search.marked_or_selected();
+                if !paths.is_empty() {
+                    let text = paths
+                        .iter()
+                        .map(|path| path.display().to_string())
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    match clipboard::copy(&text) {
+                        Ok(()) => {
+                            oplog::record(format!("Copied {} result path(s)", paths.len()));
+                            ui.notifications
+                                .info(format!("Copied {} path(s)", paths.len()));
                         }
+                        Err(e) => ui
+                            .notifications
+                            .error(tr_with("Clipboard: {error}", &[("error", &e)])),
+                    }
+                }
+            }
+            _ if config.keybindings.is_copy_path(trigger) => {
+                // Open menu with path variants (relative paths are based on the shell's cwd)
+                if let Some(node) = nav.get_selected_node() {
+                    let cwd = std::env::current_dir().unwrap_or_default();
+                    copy_menu.open(&node.path, &cwd);
+                }
+            }
+            // Results panel: open the marked file results together (directories skipped)
+            _ if ui.focus == Focus::Results
+                && !search.marked.is_empty()
+                && config.keybindings.is_open_editor(trigger) =>
+            {
+                let files: Vec<PathBuf> = search
+                    .marked_paths()
+                    .into_iter()
+                    .filter(|path| path.is_file())
+                    .collect();
+                if files.is_empty() {
+                    ui.notifications.info("No marked files to open");
+                } else {
+                    // Run after exit: main.rs unmarks them once the editor started
+                    let marker = Self::open_editor_all(&files, config);
+                    if !marker.as_os_str().is_empty() {
+                        return Ok(Some(marker));
                     }
+                    Self::unmark(&files, nav, search);
                 }
             }
-            KeyCode::Char('l') | KeyCode::Right => {
-                if !search.focus_on_results {
+            _ if config.keybindings.is_open_editor(trigger) => {
+                // Open file in external editor (or hex editor for binary files)
+                // With focus on the results panel, the selected result is opened
+                let selected = if ui.focus == Focus::Results {
+                    search
+                        .results
+                        .get(search.selected)
+                        .map(|result| (result.path.clone(), result.is_dir))
+                } else {
+                    nav.get_selected_node()
+                        .map(|node| (node.path.clone(), node.is_dir))
+                };
+                if let Some((path, is_dir)) = selected {
+                    if !is_dir {
+                        // Check if file is binary
+                        use crate::file_viewer::FileViewer;
+                        let is_binary = FileViewer::is_binary_file(Path::new(&path));
+
+                        plugins.on_open(&path);
+                        let line = match search.selected_line() {
+                            Some(line) if ui.focus == Focus::Results => Some(line),
+                            _ => Self::viewer_line(file_viewer, &path),
+                        };
+                        return Ok(Some(Self::open_editor(path, is_binary, line, config)));
+                    }
+                }
+            }
+            _ if config.keybindings.is_open_file_manager(trigger) => {
+                // Open in file manager
+                if let Some(node) = nav.get_selected_node() {
+                    let path_to_open = if node.is_dir {
+                        // For directories, open the directory itself
+                        node.path.clone()
+                    } else {
+                        // For files, open the parent directory
+                        node.path.parent().unwrap_or(&node.path).to_path_buf()
+                    };
+                    return Ok(Some(Self::open_file_manager(path_to_open, config)));
+                }
+            }
+            _ if config.keybindings.is_message_log(trigger) => {
+                ui.notifications.toggle_log();
+            }
+            _ if config.keybindings.is_goto_path(trigger) => {
+                goto.open(&nav.root().path);
+            }
+            _ if config.keybindings.is_plugin_command(trigger) => {
+                if plugins.command_names().is_empty() {
+                    ui.notifications
+                        .info("No plugin commands (define command_<name>(path) in a plugin)");
+                } else {
+                    command_prompt.open();
+                }
+            }
+            _ if config.keybindings.is_flat_listing(trigger) => {
+                if nav.flat_listing.is_some() {
+                    nav.leave_flat_listing(*show_files)?;
+                } else {
+                    // Listed files are the point - show them with the preview
+                    if !*show_files {
+                        *show_files = true;
+                        *show_help = false;
+                    }
+                    // Shown by App::poll_flat_listing once scanned
+                    if let Err(e) = nav.enter_flat_listing() {
+                        ui.notifications.error(e.to_string());
+                    }
+                }
+                if *show_files {
                     if let Some(node) = nav.get_selected_node() {
-                        let node_borrowed = node.borrow();
-                        if node_borrowed.is_dir {
-                            let path = node_borrowed.path.clone();
-                            let dir_name = node_borrowed.name.clone();
-                            drop(node_borrowed);
-
-                            // Toggle node and check for errors
-                            if let Ok(Some(error_msg)) = nav.toggle_node(&path, *show_files) {
-                                // Error occurred - show details in file viewer if show_files is enabled
-                                if *show_files {
-                                    let error_content = vec![
-                                        format!("Error accessing directory: {}", dir_name),
-                                        String::new(),
-                                        error_msg,
-                                        String::new(),
-                                        "This directory cannot be read. Possible reasons:"
-                                            .to_string(),
-                                        "- Insufficient permissions".to_string(),
-                                        "- Directory was removed or renamed".to_string(),
-                                        "- Filesystem error".to_string(),
-                                    ];
-                                    file_viewer.load_content(error_content);
-                                    *show_help = false;
-                                }
-                            }
+                        let _ = ui.load_file_for_viewer(
+                            file_viewer,
+                            &node.path,
+                            config.behavior.max_file_lines,
+                            false,
+                            config,
+                            plugins,
+                        );
+                    }
+                }
+            }
+            _ if config.keybindings.is_compare(trigger) => {
+                if nav.comparison.is_some() {
+                    nav.leave_comparison(*show_files)?;
+                    if *show_files {
+                        if let Some(node) = nav.get_selected_node() {
+                            let _ = ui.load_file_for_viewer(
+                                file_viewer,
+                                &node.path,
+                                config.behavior.max_file_lines,
+                                false,
+                                config,
+                                plugins,
+                            );
                         }
                     }
+                } else if nav.is_virtual() {
+                    ui.notifications
+                        .error("Comparing needs a directory tree, not a path list");
+                } else if bookmarks.list().is_empty() {
+                    ui.notifications
+                        .info("No bookmarks to compare with (or start with --compare DIR)");
+                } else {
+                    bookmarks.enter_compare_mode();
+                }
+            }
+            _ if config.keybindings.is_flat_sort(trigger) => {
+                if let Some(listing) = &nav.flat_listing {
+                    let sort = listing.sort.next();
+                    nav.cycle_flat_sort();
+                    ui.notifications.info(format!("Sorted by {}", sort.label()));
+                }
+            }
+            _ if config.keybindings.is_git_view(trigger) => {
+                // The git output is shown in the viewer pane
+                if !*show_files {
+                    *show_files = true;
+                    *show_help = false;
+                    nav.reload_tree(true)?;
+                }
+                Self::cycle_git_view(nav, file_viewer, ui, plugins, config, false);
+            }
+            _ if config.keybindings.is_repo_root(trigger) => {
+                // Repository of the selected entry - it may be a repo below a plain root
+                let path = nav
+                    .get_selected_node()
+                    .map(|node| node.path.clone())
+                    .unwrap_or_else(|| nav.root().path.clone());
+                match git::find_top_level(&path) {
+                    None => ui.notifications.info("Not in a git repository"),
+                    Some(top_level) if top_level == nav.root().path => {
+                        ui.notifications.info("Already at the repository root")
+                    }
+                    Some(top_level) => Self::change_root(
+                        top_level,
+                        nav,
+                        file_viewer,
+                        ui,
+                        plugins,
+                        config,
+                        *show_files,
+                    )?,
+                }
+            }
+            _ if config.keybindings.is_projects(trigger) => {
+                let root = nav.root().path.clone();
+                ui.projects.enter_selection_mode(&root);
+            }
+            _ if config.keybindings.is_marks(trigger) => {
+                if nav.marked.is_empty() {
+                    ui.notifications.info("Nothing marked");
+                } else {
+                    ui.marks_panel.open(&nav.marked);
+                }
+            }
+            _ if config.keybindings.is_clipboard_history(trigger) => {
+                ui.clipboard_history.open();
+                if ui.clipboard_history.entries.is_empty() {
+                    ui.clipboard_history.close();
+                    ui.notifications.info("Nothing copied yet");
+                }
+            }
+            _ if config.keybindings.is_export_tree(trigger) => {
+                // A file when tree_export_file is set, otherwise stdout on exit
+                let file = (!config.behavior.tree_export_file.is_empty()).then(|| {
+                    let cwd = std::env::current_dir().unwrap_or_default();
+                    goto::expand_path(&config.behavior.tree_export_file, &cwd)
+                });
+                ui.export_menu.open(file);
+            }
+            _ if config.keybindings.is_export_results(trigger) => {
+                // Quickfix list: to quickfix_file right away, or to stdout on exit
+                if !search.show_results || search.results.is_empty() {
+                    ui.notifications.info("No search results to export");
+                } else if config.behavior.quickfix_file.is_empty() {
+                    return Ok(Some(quickfix::encode(&search.results)));
+                } else {
+                    let cwd = std::env::current_dir().unwrap_or_default();
+                    let file = goto::expand_path(&config.behavior.quickfix_file, &cwd);
+                    match quickfix::write(&file, &search.results) {
+                        Ok(()) => ui.notifications.info(format!(
+                            "Exported {} result(s) to {}",
+                            search.results.len(),
+                            file.display()
+                        )),
+                        Err(e) => ui.notifications.error(format!("{:#}", e)),
+                    }
+                }
+            }
+            _ if config.keybindings.is_open_marked(trigger) => {
+                // Directories among the marks are skipped - the editor gets files only
+                let files: Vec<PathBuf> = nav
+                    .marked_or_selected()
+                    .into_iter()
+                    .filter(|path| path.is_file())
+                    .collect();
+                if files.is_empty() {
+                    ui.notifications.info("No marked files to open");
+                } else {
+                    // Run after exit: main.rs unmarks them once the editor started
+                    let marker = Self::open_editor_all(&files, config);
+                    if !marker.as_os_str().is_empty() {
+                        return Ok(Some(marker));
+                    }
+                    Self::unmark(&files, nav, search);
                 }
             }
-            KeyCode::Char('h') | KeyCode::Left => {
+            _ if config.keybindings.is_find_empty(trigger) => {
+                let kind = ScanKind::Empty {
+                    include_files: *show_files,
+                };
+                Self::start_scan(kind, nav, search, ui);
+            }
+            _ if config.keybindings.is_find_broken_links(trigger) => {
+                // Links are files in the tree - show them so results can be jumped to
+                if !*show_files {
+                    *show_files = true;
+                    *show_help = false;
+                    nav.reload_tree(true)?;
+                }
+                Self::start_scan(ScanKind::BrokenSymlinks, nav, search, ui);
+            }
+            _ if config.keybindings.is_find_hardlinks(trigger) => {
+                let metadata = nav
+                    .get_selected_node()
+                    .and_then(|node| nav.vfs.symlink_metadata(&node.path).ok());
+                match metadata {
+                    Some(metadata) if !metadata.is_file => {
+                        ui.notifications
+                            .info("Hard links are listed for files only");
+                    }
+                    Some(metadata) => match metadata.file_id {
+                        None => ui
+                            .notifications
+                            .info("Hard links aren't shown on this platform"),
+                        Some(id) if id.links < 2 => {
+                            ui.notifications.info("No other hard links (link count 1)");
+                        }
+                        Some(id) => {
+                            // Linked files are jumped to in the tree - show them
+                            if !*show_files {
+                                *show_files = true;
+                                *show_help = false;
+                                nav.reload_tree(true)?;
+                            }
+                            Self::start_scan(ScanKind::Hardlinks(id), nav, search, ui);
+                        }
+                    },
+                    None => {}
+                }
+            }
+            _ if config.keybindings.is_largest_files(trigger) => {
+                // Listed files are jumped to in the tree - show them
+                if !*show_files {
+                    *show_files = true;
+                    *show_help = false;
+                    nav.reload_tree(true)?;
+                }
+                Self::start_scan(ScanKind::LargestFiles, nav, search, ui);
+            }
+            _ if config.keybindings.is_quick_filter(trigger) => {
+                let filter = nav.filters.quick_filter.next();
+                Self::apply_quick_filter(
+                    filter,
+                    nav,
+                    file_viewer,
+                    ui,
+                    plugins,
+                    config,
+                    show_files,
+                    show_help,
+                )?;
+            }
+            _ if config.keybindings.is_saved_filters(trigger) => {
+                if ui.filter_picker.filters.is_empty() {
+                    ui.notifications
+                        .info("No saved filters (add them to [filters] in config.toml)");
+                } else {
+                    ui.filter_picker.open(&nav.filters.quick_filter);
+                }
+            }
+            _ if config.keybindings.is_layouts(trigger) => ui.layout_picker.open(),
+            _ if config.keybindings.is_create_bookmark(trigger) => {
+                // Enter bookmark creation mode
+                bookmarks.enter_creation_mode();
+            }
+            _ if config.keybindings.is_select_bookmark(trigger) => {
+                // Enter bookmark selection mode
+                bookmarks.enter_selection_mode();
+            }
+            Trigger::Key(KeyCode::Char('z')) => {
+                // Toggle directory size display, unless zz, zt or zb follows
+                self.pending_z = Some(Instant::now());
+            }
+            _ => {}
+        }
+
+        Ok(Some(PathBuf::new()))
+    }
+
+    /// Cycle the viewer between the preview, git log and git blame of the selected entry
+    fn cycle_git_view(
+        nav: &Navigation,
+        file_viewer: &mut FileViewer,
+        ui: &mut UI,
+        plugins: &Plugins,
+        config: &Config,
+        fullscreen: bool,
+    ) {
+        let Some(node) = nav.get_selected_node() else {
+            return;
+        };
+        let (path, is_dir) = { (node.path.clone(), node.is_dir) };
+        // A git view of another entry doesn't count - start over with the log
+        let current = file_viewer
+            .git_view
+            .filter(|_| file_viewer.current_path == path);
+
+        match GitView::next(current, is_dir) {
+            Some(view) => {
+                if let Err(e) = ui.load_git_view(file_viewer, &path, view, fullscreen) {
+                    ui.notifications.warn(e.to_string());
+                }
+            }
+            None => {
+                file_viewer.git_view = None;
+                let _ = ui.load_file_for_viewer(
+                    file_viewer,
+                    &path,
+                    config.behavior.max_file_lines,
+                    fullscreen,
+                    config,
+                    plugins,
+                );
+            }
+        }
+        // Log, blame and content don't share line positions
+        file_viewer.reset_scroll();
+    }
+
+    /// Expand the tree to the selected search result and show it in the viewer,
+    /// scrolled to the matching line of a content search
+    fn jump_to_result(
+        search: &Search,
+        nav: &mut Navigation,
+        file_viewer: &mut FileViewer,
+        ui: &mut UI,
+        plugins: &Plugins,
+        show_files: bool,
+        show_help: &mut bool,
+        config: &Config,
+    ) {
+        let Some(path) = search.get_selected_result() else {
+            return;
+        };
+        let _ = nav.expand_path_to_node(&path, show_files);
+        if show_files {
+            let _ = ui.load_file_for_viewer(
+                file_viewer,
+                &path,
+                config.behavior.max_file_lines,
+                false,
+                config,
+                plugins,
+            );
+            if let Some(line) = search.selected_line() {
+                file_viewer.scroll_to_line(line);
+            }
+            *show_help = false;
+        }
+    }
+
+    /// Run a scan; its findings are listed in the search results panel
+    /// The largest files report covers the selected directory, the other scans the whole tree
+    fn start_scan(kind: ScanKind, nav: &Navigation, search: &mut Search, ui: &mut UI) {
+        if nav.is_virtual() {
+            ui.notifications
+                .error("Scans need a directory tree, not a path list or flat listing");
+            return;
+        }
+        let root = match (kind, nav.get_selected_node()) {
+            (ScanKind::LargestFiles, Some(node)) => {
+                if node.is_dir {
+                    node.path.clone()
+                } else {
+                    node.path
+                        .parent()
+                        .map(|parent| parent.to_path_buf())
+                        .unwrap_or_else(|| node.path.clone())
+                }
+            }
+            _ => nav.root().path.clone(),
+        };
+        search.start_scan(Arc::clone(&nav.vfs), kind, &root, nav.scan_options());
+        ui.focus = Focus::Results;
+    }
+
+    /// Collapse the tree down to the ancestors of `path`, select it and center it;
+    /// a file turns the file viewer on
+    fn reveal_in_tree(
+        path: &Path,
+        nav: &mut Navigation,
+        file_viewer: &mut FileViewer,
+        ui: &mut UI,
+        plugins: &Plugins,
+        config: &Config,
+        show_files: &mut bool,
+        show_help: &mut bool,
+    ) -> Result<()> {
+        if !path.is_dir() && !*show_files {
+            Self::set_show_files(
+                true,
+                nav,
+                file_viewer,
+                ui,
+                plugins,
+                config,
+                show_files,
+                show_help,
+            )?;
+        }
+        nav.reveal(path, *show_files)?;
+        if *show_files {
+            if let Some(node) = nav.get_selected_node() {
+                let _ = ui.load_file_for_viewer(
+                    file_viewer,
+                    &node.path,
+                    config.behavior.max_file_lines,
+                    false,
+                    config,
+                    plugins,
+                );
+            }
+        }
+        ui.focus = Focus::Tree;
+        ui.place_tree_selection(Placement::Center);
+        Ok(())
+    }
+
+    /// Placement for the key after `z`
+    fn placement(code: KeyCode) -> Option<Placement> {
+        match code {
+            KeyCode::Char('t') => Some(Placement::Top),
+            KeyCode::Char('z') => Some(Placement::Center),
+            KeyCode::Char('b') => Some(Placement::Bottom),
+            _ => None,
+        }
+    }
+
+    /// When a pending `z` stops waiting for zz, zt or zb
+    pub fn pending_z_deadline(&self) -> Option<Instant> {
+        self.pending_z.map(|pressed| pressed + Z_TIMEOUT)
+    }
+
+    /// A `z` nothing followed in time toggles the sizes (in the fullscreen viewer,
+    /// where z only starts zt/zz/zb, it's dropped)
+    /// Returns true if the sizes were toggled
+    pub fn poll_pending_z(
+        &mut self,
+        fullscreen: bool,
+        nav: &Navigation,
+        dir_size_cache: &mut DirSizeCache,
+        show_sizes: &mut bool,
+    ) -> bool {
+        if self
+            .pending_z
+            .is_none_or(|pressed| pressed.elapsed() < Z_TIMEOUT)
+        {
+            return false;
+        }
+        self.pending_z = None;
+        if fullscreen {
+            return false;
+        }
+        Self::set_show_sizes(!*show_sizes, nav, dir_size_cache, show_sizes);
+        true
+    }
+
+    /// Show or hide files in the tree (and the file viewer next to it)
+    fn set_show_files(
+        show: bool,
+        nav: &mut Navigation,
+        file_viewer: &mut FileViewer,
+        ui: &mut UI,
+        plugins: &Plugins,
+        config: &Config,
+        show_files: &mut bool,
+        show_help: &mut bool,
+    ) -> Result<()> {
+        *show_files = show;
+        *show_help = false;
+        nav.reload_tree(*show_files)?;
+
+        // Fix selection if it's out of bounds after reload
+        if !nav.flat_list.is_empty() {
+            if nav.selected >= nav.flat_list.len() {
+                nav.selected = nav.flat_list.len() - 1;
+            }
+        } else {
+            // Empty list - set to 0
+            nav.selected = 0;
+        }
+
+        if *show_files {
+            // Only load file if we have a valid selection
+            if !nav.flat_list.is_empty() {
                 if let Some(node) = nav.get_selected_node() {
-                    let node_borrowed = node.borrow();
-                    if node_borrowed.is_dir {
-                        let path = node_borrowed.path.clone();
-                        drop(node_borrowed);
-                        let _ = nav.toggle_node(&path, *show_files)?;
-                    }
+                    let _ = ui.load_file_for_viewer(
+                        file_viewer,
+                        &node.path,
+                        config.behavior.max_file_lines,
+                        false,
+                        config,
+                        plugins,
+                    );
                 }
             }
-            KeyCode::Char('u') | KeyCode::Backspace => {
-                nav.go_to_parent(*show_files)?;
-            }
-            _ if config.keybindings.is_toggle_files(key.code) => {
-                *show_files = !*show_files;
-                *show_help = false;
-                nav.reload_tree(*show_files)?;
-
-                // Fix selection if it's out of bounds after reload
-                if !nav.flat_list.is_empty() {
-                    if nav.selected >= nav.flat_list.len() {
-                        nav.selected = nav.flat_list.len() - 1;
-                    }
-                } else {
-                    // Empty list - set to 0
-                    nav.selected = 0;
-                }
+        }
+        Ok(())
+    }
 
-                if *show_files {
-                    // Only load file if we have a valid selection
-                    if !nav.flat_list.is_empty() {
-                        if let Some(node) = nav.get_selected_node() {
-                            let _ = ui.load_file_for_viewer(
-                                file_viewer,
-                                &node.borrow().path,
-                                config.behavior.max_file_lines,
-                                false,
-                                config,
-                            );
-                        }
-                    }
+    /// Show or hide directory sizes in the tree
+    pub(crate) fn set_show_sizes(
+        show: bool,
+        nav: &Navigation,
+        dir_size_cache: &mut DirSizeCache,
+        show_sizes: &mut bool,
+    ) {
+        *show_sizes = show;
+        if *show_sizes {
+            // Start calculating sizes for visible directories
+            for &id in &nav.flat_list {
+                let node = &nav.tree[id];
+                if node.is_dir {
+                    dir_size_cache.calculate_async(node.path.clone());
                 }
             }
-            _ if config.keybindings.is_toggle_help(key.code) => {
-                *show_help = !*show_help;
+        } else {
+            // Clear cache when disabling
+            dir_size_cache.clear();
+        }
+    }
 
-                if *show_help {
-                    // Save current show_files state before opening help
-                    *show_files_before_help = *show_files;
+    /// Restore a named layout: the dividers, then files and sizes as they were shown
+    fn apply_layout(
+        named: &NamedLayout,
+        nav: &mut Navigation,
+        file_viewer: &mut FileViewer,
+        ui: &mut UI,
+        plugins: &Plugins,
+        dir_size_cache: &mut DirSizeCache,
+        config: &Config,
+        show_files: &mut bool,
+        show_help: &mut bool,
+        show_sizes: &mut bool,
+    ) -> Result<()> {
+        ui.adjust_split(named.layout.split_position);
+        ui.adjust_bottom_split(named.layout.bottom_panel_split_position);
+        if named.show_files != *show_files {
+            Self::set_show_files(
+                named.show_files,
+                nav,
+                file_viewer,
+                ui,
+                plugins,
+                config,
+                show_files,
+                show_help,
+            )?;
+        }
+        if named.show_sizes != *show_sizes {
+            Self::set_show_sizes(named.show_sizes, nav, dir_size_cache, show_sizes);
+        }
+        ui.notifications.info(format!("Layout: {}", named.name));
+        Ok(())
+    }
 
-                    // Load help content into file viewer for scrolling
-                    file_viewer.load_content(crate::ui::get_help_content());
-                    if !*show_files {
-                        *show_files = true;
-                        nav.reload_tree(*show_files)?;
-                    }
-                } else {
-                    // Restore previous show_files state
-                    if *show_files != *show_files_before_help {
-                        *show_files = *show_files_before_help;
-                        nav.reload_tree(*show_files)?;
-                    }
-                    file_viewer.reset_scroll();
-                }
+    /// Apply a quick or saved filter and preview the entry selected with it
+    fn apply_quick_filter(
+        filter: QuickFilter,
+        nav: &mut Navigation,
+        file_viewer: &mut FileViewer,
+        ui: &mut UI,
+        plugins: &Plugins,
+        config: &Config,
+        show_files: &mut bool,
+        show_help: &mut bool,
+    ) -> Result<()> {
+        // File type filters are about files - show them in the tree
+        if filter.shows_files() && !*show_files {
+            *show_files = true;
+            *show_help = false;
+            nav.reload_tree(true)?;
+        }
+        if filter == QuickFilter::All {
+            ui.notifications.info("Filter off");
+        } else {
+            ui.notifications.info(format!("Filter: {}", filter.label()));
+        }
+        nav.set_quick_filter(filter);
+
+        if *show_files {
+            if let Some(node) = nav.get_selected_node() {
+                let _ = ui.load_file_for_viewer(
+                    file_viewer,
+                    &node.path,
+                    config.behavior.max_file_lines,
+                    false,
+                    config,
+                    plugins,
+                );
             }
-            KeyCode::Char('v') => {
-                // Toggle fullscreen viewer mode
-                if let Some(node) = nav.get_selected_node() {
-                    let node_borrowed = node.borrow();
-                    if !node_borrowed.is_dir {
-                        *fullscreen_viewer = !*fullscreen_viewer;
-                        *show_help = false;
+        }
+        Ok(())
+    }
 
-                        if *fullscreen_viewer {
-                            // Load file for fullscreen viewing with full terminal width
-                            let _ = ui.load_file_for_viewer(
-                                file_viewer,
-                                &node_borrowed.path,
-                                config.behavior.max_file_lines,
-                                true,
-                                config,
-                            );
-                        }
-                    }
-                }
+    /// Start comparing the root with `right` (shown by `comparison_shown` once scanned)
+    pub(crate) fn compare_with(
+        right: &Path,
+        nav: &mut Navigation,
+        ui: &mut UI,
+        show_files: &mut bool,
+        show_help: &mut bool,
+    ) -> Result<()> {
+        // Differing files are the point - show them with the preview
+        if !*show_files {
+            *show_files = true;
+            *show_help = false;
+            nav.reload_tree(true)?;
+        }
+        if let Err(e) = nav.enter_comparison(right) {
+            ui.notifications.error(e.to_string());
+        }
+        Ok(())
+    }
+
+    /// Report a comparison that was just shown when there is nothing to see, and
+    /// preview the entry selected in it
+    pub(crate) fn comparison_shown(
+        nav: &Navigation,
+        file_viewer: &mut FileViewer,
+        ui: &mut UI,
+        plugins: &Plugins,
+        config: &Config,
+    ) {
+        if let Some(comparison) = &nav.comparison {
+            if comparison.truncated {
+                ui.notifications.warn(tr_with(
+                    "Comparison stopped at {count} entries",
+                    &[("count", &compare::MAX_ENTRIES)],
+                ));
+            } else if comparison.is_identical() {
+                ui.notifications.info(format!(
+                    "No differences with {}",
+                    comparison.right.display()
+                ));
             }
-            _ if config.keybindings.is_copy_path(key.code) => {
-                if let Some(node) = nav.get_selected_node() {
-                    if let Ok(mut clipboard) = Clipboard::new() {
-                        let _ = clipboard.set_text(node.borrow().path.display().to_string());
-                    }
-                }
+        }
+        if let Some(node) = nav.get_selected_node() {
+            let _ = ui.load_file_for_viewer(
+                file_viewer,
+                &node.path,
+                config.behavior.max_file_lines,
+                false,
+                config,
+                plugins,
+            );
+        }
+    }
+
+    /// Copy a path (in one of the copy menu's formats) to the clipboard
+    pub(crate) fn copy_path_text(text: &str, ui: &mut UI) {
+        match clipboard::copy(text) {
+            Ok(()) => {
+                ui.notifications.info("Copied to clipboard");
+                oplog::record(format!("Copied path: {}", text));
+            }
+            Err(e) => ui
+                .notifications
+                .error(tr_with("Clipboard: {error}", &[("error", &e)])),
+        }
+    }
+
+    /// Report a flat listing that was just shown when it is cut off or empty, and
+    /// preview the file selected in it
+    pub(crate) fn flat_listing_shown(
+        nav: &Navigation,
+        file_viewer: &mut FileViewer,
+        ui: &mut UI,
+        plugins: &Plugins,
+        config: &Config,
+    ) {
+        if let Some(listing) = &nav.flat_listing {
+            if listing.truncated {
+                ui.notifications.warn(tr_with(
+                    "Flat listing stopped at {count} files",
+                    &[("count", &flat_listing::MAX_FILES)],
+                ));
+            } else if listing.is_empty() {
+                ui.notifications.info("No files under this directory");
             }
-            _ if config.keybindings.is_open_editor(key.code) => {
-                // Open file in external editor (or hex editor for binary files)
-                if let Some(node) = nav.get_selected_node() {
-                    let node_borrowed = node.borrow();
-                    if !node_borrowed.is_dir {
-                        let path = node_borrowed.path.clone();
-                        drop(node_borrowed);
+        }
+        if let Some(node) = nav.get_selected_node() {
+            let _ = ui.load_file_for_viewer(
+                file_viewer,
+                &node.path,
+                config.behavior.max_file_lines,
+                false,
+                config,
+                plugins,
+            );
+        }
+    }
 
-                        // Check if file is binary
-                        use crate::file_viewer::FileViewer;
-                        use std::path::Path;
-                        let is_binary = FileViewer::is_binary_file(Path::new(&path));
+    /// Make `dir` the root and preview the entry selected there
+    fn change_root(
+        dir: PathBuf,
+        nav: &mut Navigation,
+        file_viewer: &mut FileViewer,
+        ui: &mut UI,
+        plugins: &Plugins,
+        config: &Config,
+        show_files: bool,
+    ) -> Result<()> {
+        if let Some(error_msg) = nav.go_to_directory(dir, show_files)? {
+            ui.notifications.error(error_msg);
+        } else if show_files {
+            if let Some(node) = nav.get_selected_node() {
+                let _ = ui.load_file_for_viewer(
+                    file_viewer,
+                    &node.path,
+                    config.behavior.max_file_lines,
+                    false,
+                    config,
+                    plugins,
+                );
+            }
+        }
+        Ok(())
+    }
 
-                        if is_binary {
-                            // Return special marker for hex editor
-                            let marker_path =
-                                PathBuf::from(format!("HEXEDITOR:{}", path.display()));
-                            return Ok(Some(marker_path));
-                        } else {
-                            // Return special marker for text editor
-                            let marker_path = PathBuf::from(format!("EDITOR:{}", path.display()));
-                            return Ok(Some(marker_path));
-                        }
-                    }
-                }
+    /// Enter on a file: run the configured `enter_file_action`
+    fn handle_enter_on_file(
+        &mut self,
+        path: PathBuf,
+        file_viewer: &mut FileViewer,
+        show_help: &mut bool,
+        fullscreen_viewer: &mut bool,
+        ui: &mut UI,
+        plugins: &Plugins,
+        config: &Config,
+    ) -> Result<Option<PathBuf>> {
+        plugins.on_open(&path);
+        match config.behavior.enter_file_action.to_lowercase().as_str() {
+            "preview" => {
+                *fullscreen_viewer = true;
+                *show_help = false;
+                let _ = ui.load_file_for_viewer(
+                    file_viewer,
+                    &path,
+                    config.behavior.max_file_lines,
+                    true,
+                    config,
+                    plugins,
+                );
+            }
+            "editor" => {
+                let is_binary = FileViewer::is_binary_file(&path);
+                let line = Self::viewer_line(file_viewer, &path);
+                return Ok(Some(Self::open_editor(path, is_binary, line, config)));
+            }
+            "open" => match platform::open_with_system_handler(&path) {
+                Ok(()) => {
+                    oplog::record(format!("Opened {} with the system handler", path.display()))
+                }
+                Err(e) => ui
+                    .notifications
+                    .error(format!("Cannot open {}: {}", path.display(), e)),
+            },
+            "select" => {
+                // Return special marker for printing the file path on exit
+                return Ok(Some(PathBuf::from(format!("SELECT:{}", path.display()))));
             }
-            _ if config.keybindings.is_open_file_manager(key.code) => {
-                // Open in file manager
-                if let Some(node) = nav.get_selected_node() {
-                    let node_borrowed = node.borrow();
-                    let path_to_open = if node_borrowed.is_dir {
-                        // For directories, open the directory itself
-                        node_borrowed.path.clone()
-                    } else {
-                        // For files, open the parent directory
-                        node_borrowed
-                            .path
-                            .parent()
-                            .unwrap_or(&node_borrowed.path)
-                            .to_path_buf()
-                    };
-                    // Return special marker path to signal file manager opening
-                    let marker_path = PathBuf::from(format!("FILEMGR:{}", path_to_open.display()));
-                    return Ok(Some(marker_path));
+            _ => {}
+        }
+        Ok(Some(PathBuf::new()))
+    }
+
+    /// Open a file in the editor (or hex editor for binary files)
+    /// Inside Neovim's :terminal text files go to that Neovim; inside tmux/wezterm with
+    /// `open_in_split` enabled the editor gets its own pane. In both cases dtree keeps
+    /// running; otherwise a marker is returned so main.rs runs the editor after exit
+    /// GUI editors start in the background instead, keeping dtree running too
+    /// `line` (from the viewer) is passed to editors that take one
+    fn open_editor(
+        path: PathBuf,
+        is_binary: bool,
+        line: Option<usize>,
+        config: &Config,
+    ) -> PathBuf {
+        if !is_binary && config.behavior.nvim_remote {
+            if let Some(server) = nvim::server_address() {
+                // On failure fall through to the regular editor
+                if nvim::open_remote(&server, &path).is_ok() {
+                    oplog::record(format!("Opened {} in Neovim ({})", path.display(), server));
+                    return PathBuf::new();
                 }
             }
-            _ if config.keybindings.is_create_bookmark(key.code) => {
-                // Enter bookmark creation mode
-                bookmarks.enter_creation_mode();
+        }
+
+        let program = if is_binary {
+            &config.behavior.hex_editor
+        } else {
+            &config.behavior.editor
+        };
+        let line = line.filter(|_| !is_binary);
+
+        if launcher::is_gui(program, &config.behavior.gui_programs) {
+            let command = launcher::command_line(program, &path.display().to_string(), line);
+            // On failure fall through to running it after exit
+            if launcher::spawn_detached(&command).is_ok() {
+                oplog::record(format!("Opened {} with {}", path.display(), program));
+                return PathBuf::new();
             }
-            _ if config.keybindings.is_select_bookmark(key.code) => {
-                // Enter bookmark selection mode
-                bookmarks.enter_selection_mode();
+        }
+
+        if let Some(mux) = multiplexer::resolve(&config.behavior.open_in_split) {
+            let direction = SplitDirection::from_config(&config.behavior.split_direction);
+            // On failure fall back to opening the editor in place
+            let command = launcher::command_line(program, &path.display().to_string(), line);
+            let cwd = path.parent().unwrap_or(&path);
+            if multiplexer::open_in_split(mux, direction, &command, cwd).is_ok() {
+                oplog::record(format!(
+                    "Opened {} with {} in a {:?} split",
+                    path.display(),
+                    program,
+                    mux
+                ));
+                return PathBuf::new();
             }
-            KeyCode::Char('z') => {
-                // Toggle directory size display
-                *show_sizes = !*show_sizes;
-                if *show_sizes {
-                    // Start calculating sizes for visible directories
-                    for node_ref in &nav.flat_list {
-                        let node = node_ref.borrow();
-                        if node.is_dir {
-                            dir_size_cache.calculate_async(node.path.clone());
-                        }
-                    }
-                } else {
-                    // Clear cache when disabling
-                    dir_size_cache.clear();
+        }
+
+        oplog::record(format!("Open editor {} on {}", program, path.display()));
+
+        if is_binary {
+            // Return special marker for hex editor
+            PathBuf::from(format!("HEXEDITOR:{}", path.display()))
+        } else {
+            // Return special marker for text editor
+            launcher::encode_editor(&path, line)
+        }
+    }
+
+    /// Editors, file managers and file operations need entries on the local disk
+    fn not_local(ui: &mut UI) {
+        ui.notifications.error(tr(
+            "Not available here: the entries aren't files on this machine",
+        ));
+    }
+
+    /// Unmark `paths` in the tree and in the results, once the editor has them
+    pub(crate) fn unmark(paths: &[PathBuf], nav: &mut Navigation, search: &mut Search) {
+        for path in paths {
+            nav.marked.remove(path);
+            search.marked.remove(path);
+        }
+    }
+
+    /// Open several files with a single editor invocation, the same ways as `open_editor`
+    /// Returns an empty path when the editor runs alongside dtree, otherwise a marker
+    fn open_editor_all(paths: &[PathBuf], config: &Config) -> PathBuf {
+        if config.behavior.nvim_remote {
+            if let Some(server) = nvim::server_address() {
+                if paths
+                    .iter()
+                    .all(|path| nvim::open_remote(&server, path).is_ok())
+                {
+                    oplog::record(format!(
+                        "Opened {} file(s) in Neovim ({})",
+                        paths.len(),
+                        server
+                    ));
+                    return PathBuf::new();
                 }
             }
-            _ => {}
         }
 
-        Ok(Some(PathBuf::new()))
+        let program = &config.behavior.editor;
+        let command = launcher::command_line_all(program, paths);
+
+        if launcher::is_gui(program, &config.behavior.gui_programs)
+            && launcher::spawn_detached(&command).is_ok()
+        {
+            oplog::record(format!("Opened {} file(s) with {}", paths.len(), program));
+            return PathBuf::new();
+        }
+
+        if let Some(mux) = multiplexer::resolve(&config.behavior.open_in_split) {
+            let direction = SplitDirection::from_config(&config.behavior.split_direction);
+            let cwd = paths[0].parent().unwrap_or(&paths[0]);
+            if multiplexer::open_in_split(mux, direction, &command, cwd).is_ok() {
+                oplog::record(format!(
+                    "Opened {} file(s) with {} in a {:?} split",
+                    paths.len(),
+                    program,
+                    mux
+                ));
+                return PathBuf::new();
+            }
+        }
+
+        oplog::record(format!(
+            "Open editor {} on {} file(s)",
+            program,
+            paths.len()
+        ));
+        launcher::encode_editor_all(paths)
+    }
+
+    /// Line of `path` the viewer is at, if the viewer shows that file
+    fn viewer_line(file_viewer: &FileViewer, path: &Path) -> Option<usize> {
+        if file_viewer.current_path == path {
+            file_viewer.current_line()
+        } else {
+            None
+        }
+    }
+
+    /// Open a directory in the file manager
+    /// GUI file managers start in the background; others run after dtree exits (marker)
+    fn open_file_manager(dir: PathBuf, config: &Config) -> PathBuf {
+        let program = &config.behavior.file_manager;
+        if launcher::is_gui(program, &config.behavior.gui_programs) {
+            let command = launcher::command_line(program, &dir.display().to_string(), None);
+            if launcher::spawn_detached(&command).is_ok() {
+                oplog::record(format!("Opened {} with {}", dir.display(), program));
+                return PathBuf::new();
+            }
+        }
+        oplog::record(format!("Open file manager on {}", dir.display()));
+        PathBuf::from(format!("FILEMGR:{}", dir.display()))
     }
 
     fn handle_search_input(
@@ -866,6 +2746,7 @@ impl EventHandler {
         key: KeyEvent,
         search: &mut Search,
         nav: &Navigation,
+        ui: &mut UI,
         show_files: bool,
     ) -> Result<Option<PathBuf>> {
         match key.code {
@@ -874,7 +2755,8 @@ impl EventHandler {
                 Ok(Some(PathBuf::new()))
             }
             KeyCode::Enter => {
-                search.perform_search(&nav.root, show_files, nav.show_hidden, nav.follow_symlinks);
+                search.perform_search(&nav.tree, show_files, nav.scan_options());
+                ui.focus = Focus::Results;
                 Ok(Some(PathBuf::new()))
             }
             KeyCode::Char(c) => {
@@ -925,7 +2807,20 @@ impl EventHandler {
     ) -> Result<Option<PathBuf>> {
         let visible_height = ui.viewer_area_height.saturating_sub(2) as usize;
 
+        // zt, zz, zb: put the cursor line at the top, middle or bottom
+        if self.pending_z.take().is_some() {
+            if let Some(placement) = Self::placement(key.code) {
+                let shown_rows = ui.viewer_area_height.saturating_sub(4) as usize;
+                file_viewer.place(placement, shown_rows);
+            }
+            return Ok(Some(PathBuf::new()));
+        }
+
         match key.code {
+            KeyCode::Char('z') => {
+                self.pending_z = Some(Instant::now());
+                Ok(Some(PathBuf::new()))
+            }
             KeyCode::Esc => {
                 // Exit visual mode without copying (Esc always exits)
                 file_viewer.exit_visual_mode();
@@ -994,12 +2889,43 @@ impl EventHandler {
         search: &mut Search,
         bookmarks: &mut Bookmarks,
         ui: &mut UI,
+        plugins: &Plugins,
         show_files: &mut bool,
         show_help: &mut bool,
         fullscreen_viewer: bool,
         config: &Config,
     ) -> Result<()> {
+        // The fuzzy jump overlay covers the panes: the wheel moves its selection
+        if ui.fuzzy_jump.is_open {
+            match mouse.kind {
+                MouseEventKind::ScrollDown => ui.fuzzy_jump.move_down(),
+                MouseEventKind::ScrollUp => ui.fuzzy_jump.move_up(),
+                _ => return Ok(()),
+            }
+            ui.fuzzy_jump.update_preview(&nav.tree, nav.vfs.as_ref());
+            return Ok(());
+        }
+
         match mouse.kind {
+            MouseEventKind::Moved => {
+                ui.mouse_position = (!fullscreen_viewer).then_some((mouse.column, mouse.row));
+            }
+            // Middle click copies the path of the row under the pointer, selection unchanged
+            MouseEventKind::Down(MouseButton::Middle) if !fullscreen_viewer => {
+                if let Some(&id) = ui
+                    .tree_row_at(mouse.column, mouse.row)
+                    .and_then(|row| nav.flat_list.get(row))
+                {
+                    let text = nav.tree[id].path.display().to_string();
+                    match clipboard::copy(&text) {
+                        Ok(()) => ui.notifications.info("Copied to clipboard"),
+                        Err(e) => ui
+                            .notifications
+                            .error(tr_with("Clipboard: {error}", &[("error", &e)])),
+                    }
+                    oplog::record(format!("Copied path: {}", text));
+                }
+            }
             MouseEventKind::Down(MouseButton::Left) => {
                 self.handle_mouse_click(
                     mouse,
@@ -1008,32 +2934,30 @@ impl EventHandler {
                     search,
                     bookmarks,
                     ui,
+                    plugins,
                     show_files,
                     show_help,
                     fullscreen_viewer,
                     config,
                 )?;
             }
-            MouseEventKind::Drag(MouseButton::Left) => {
-                // Ignore dragging in fullscreen mode
-                if !fullscreen_viewer {
-                    if self.dragging && ui.terminal_width > 0 {
-                        // Horizontal drag - adjust split position
-                        let new_pos = (mouse.column * 100) / ui.terminal_width;
-                        ui.adjust_split(new_pos);
-                    } else if self.dragging_vertical && ui.terminal_height > 0 {
-                        // Vertical drag - adjust bottom panel split position
-                        let new_pos = (mouse.row * 100) / ui.terminal_height;
-                        ui.adjust_bottom_split(new_pos);
-                    }
+            // Ignore dragging in fullscreen mode
+            MouseEventKind::Drag(MouseButton::Left) if !fullscreen_viewer => {
+                if self.dragging && ui.terminal_width > 0 {
+                    // Horizontal drag - adjust split position
+                    let new_pos = (mouse.column * 100) / ui.terminal_width;
+                    ui.adjust_split(new_pos);
+                } else if self.dragging_vertical && ui.terminal_height > 0 {
+                    // Vertical drag - adjust bottom panel split position
+                    let new_pos = (mouse.row * 100) / ui.terminal_height;
+                    ui.adjust_bottom_split(new_pos);
                 }
             }
-            MouseEventKind::Up(MouseButton::Left) => {
-                if !fullscreen_viewer {
-                    self.dragging = false;
-                    self.dragging_vertical = false;
-                }
+            MouseEventKind::Up(MouseButton::Left) if !fullscreen_viewer => {
+                self.dragging = false;
+                self.dragging_vertical = false;
             }
+            MouseEventKind::Drag(MouseButton::Left) | MouseEventKind::Up(MouseButton::Left) => {}
             MouseEventKind::ScrollUp => {
                 self.handle_scroll_up(
                     mouse,
@@ -1042,6 +2966,7 @@ impl EventHandler {
                     search,
                     bookmarks,
                     ui,
+                    plugins,
                     show_files,
                     show_help,
                     fullscreen_viewer,
@@ -1056,6 +2981,7 @@ impl EventHandler {
                     search,
                     bookmarks,
                     ui,
+                    plugins,
                     show_files,
                     show_help,
                     fullscreen_viewer,
@@ -1075,6 +3001,7 @@ impl EventHandler {
         search: &mut Search,
         bookmarks: &mut Bookmarks,
         ui: &mut UI,
+        plugins: &Plugins,
         show_files: &mut bool,
         show_help: &mut bool,
         fullscreen_viewer: bool,
@@ -1108,24 +3035,22 @@ impl EventHandler {
                     if is_double_click {
                         // Double-click: jump to search result
                         search.set_selected(clicked_row);
-                        if let Some(path) = search.get_selected_result() {
-                            let _ = nav.expand_path_to_node(&path, *show_files);
-                            search.focus_on_results = false;
-                            if *show_files {
-                                let _ = ui.load_file_for_viewer(
-                                    file_viewer,
-                                    &path,
-                                    config.behavior.max_file_lines,
-                                    false,
-                                    config,
-                                );
-                                *show_help = false;
-                            }
-                        }
+                        Self::jump_to_result(
+                            search,
+                            nav,
+                            file_viewer,
+                            ui,
+                            plugins,
+                            *show_files,
+                            show_help,
+                            config,
+                        );
+                        ui.focus = Focus::Tree;
                         self.last_search_click_time = None;
                     } else {
                         // Single click: just select the result
                         search.set_selected(clicked_row);
+                        ui.focus = Focus::Results;
                         self.last_search_click_time = Some((now, clicked_row));
                     }
                 }
@@ -1157,45 +3082,47 @@ impl EventHandler {
                         // Double-click: navigate to bookmark
                         bookmarks.selected_index = clicked_row;
                         if let Some(bookmark) = bookmarks.get_selected_bookmark() {
-                            let path = bookmark.path.clone();
+                            let path = match bookmark.resolve(&nav.root().path, &config.workspaces)
+                            {
+                                Ok(path) => path,
+                                Err(e) => {
+                                    ui.notifications.error(format!("{:#}", e));
+                                    return Ok(());
+                                }
+                            };
                             let bookmark_key = bookmark.key.clone();
                             let dir_name = bookmark
                                 .name
                                 .clone()
                                 .unwrap_or_else(|| bookmark_key.clone());
+                            let compare_mode = bookmarks.compare_mode;
                             bookmarks.exit_selection_mode();
 
-                            // Try to navigate and check for errors
-                            if let Ok(Some(error_msg)) = nav.go_to_directory(path, *show_files) {
-                                // Error occurred - show details in file viewer if show_files is enabled
-                                if *show_files {
-                                    let error_content = vec![
-                                        format!(
-                                            "Error accessing bookmark '{}' ({})",
-                                            bookmark_key, dir_name
-                                        ),
-                                        String::new(),
-                                        error_msg,
-                                        String::new(),
-                                        "This directory cannot be accessed. Possible reasons:"
-                                            .to_string(),
-                                        "- Insufficient permissions".to_string(),
-                                        "- Directory was removed or renamed".to_string(),
-                                        "- Filesystem error".to_string(),
-                                    ];
-                                    file_viewer.load_content(error_content);
-                                    *show_help = false;
-                                }
+                            if compare_mode {
+                                Self::compare_with(&path, nav, ui, show_files, show_help)?;
+                            } else if let Ok(Some(error_msg)) =
+                                nav.go_to_directory(path, *show_files)
+                            {
+                                // Error occurred - show it as a toast (kept in the message log)
+                                ui.notifications.error(tr_with(
+                                    "Cannot access bookmark '{bookmark}' ({dir}): {error}",
+                                    &[
+                                        ("bookmark", &bookmark_key),
+                                        ("dir", &dir_name),
+                                        ("error", &error_msg),
+                                    ],
+                                ));
                             } else {
                                 // Success - load file preview if needed
                                 if *show_files {
                                     if let Some(node) = nav.get_selected_node() {
                                         let _ = ui.load_file_for_viewer(
                                             file_viewer,
-                                            &node.borrow().path,
+                                            &node.path,
                                             config.behavior.max_file_lines,
                                             false,
                                             config,
+                                            plugins,
                                         );
                                     }
                                 }
@@ -1218,11 +3145,12 @@ impl EventHandler {
             && mouse.row >= ui.tree_area_top
             && mouse.row < ui.tree_area_top + ui.tree_area_height
         {
-            // Calculate clicked row accounting for scroll offset
-            let clicked_row_visible = mouse.row.saturating_sub(ui.tree_area_top + 1) as usize;
-            let clicked_row = clicked_row_visible + ui.tree_scroll_offset;
-
-            if clicked_row < nav.flat_list.len() {
+            ui.focus = Focus::Tree;
+            // Row under the pointer, accounting for the scroll offset (not the borders)
+            if let Some(clicked_row) = ui
+                .tree_row_at(mouse.column, mouse.row)
+                .filter(|&row| row < nav.flat_list.len())
+            {
                 let now = Instant::now();
                 let is_double_click = if let Some((last_time, last_idx)) = self.last_click_time {
                     clicked_row == last_idx
@@ -1233,30 +3161,18 @@ impl EventHandler {
                 };
 
                 if is_double_click {
-                    let node = &nav.flat_list[clicked_row];
-                    let node_borrowed = node.borrow();
-                    if node_borrowed.is_dir {
-                        let path = node_borrowed.path.clone();
-                        let dir_name = node_borrowed.name.clone();
-                        drop(node_borrowed);
+                    let node = &nav.tree[nav.flat_list[clicked_row]];
+                    if node.is_dir {
+                        let path = node.path.clone();
+                        let dir_name = node.name.clone();
 
                         // Toggle node and check for errors
                         if let Ok(Some(error_msg)) = nav.toggle_node(&path, *show_files) {
-                            // Error occurred - show details in file viewer if show_files is enabled
-                            if *show_files {
-                                let error_content = vec![
-                                    format!("Error accessing directory: {}", dir_name),
-                                    String::new(),
-                                    error_msg,
-                                    String::new(),
-                                    "This directory cannot be read. Possible reasons:".to_string(),
-                                    "- Insufficient permissions".to_string(),
-                                    "- Directory was removed or renamed".to_string(),
-                                    "- Filesystem error".to_string(),
-                                ];
-                                file_viewer.load_content(error_content);
-                                *show_help = false;
-                            }
+                            // Error occurred - show it as a toast (kept in the message log)
+                            ui.notifications.error(tr_with(
+                                "Cannot read directory '{dir}': {error}",
+                                &[("dir", &dir_name), ("error", &error_msg)],
+                            ));
                         }
                     }
                     self.last_click_time = None;
@@ -1265,13 +3181,14 @@ impl EventHandler {
                     self.last_click_time = Some((now, clicked_row));
 
                     if *show_files || fullscreen_viewer {
-                        let path = nav.flat_list[clicked_row].borrow().path.clone();
-                        let _ = ui.load_file_for_viewer(
+                        let path = nav.tree[nav.flat_list[clicked_row]].path.clone();
+                        let _ = ui.request_file_for_viewer(
                             file_viewer,
                             &path,
                             config.behavior.max_file_lines,
                             fullscreen_viewer,
                             config,
+                            plugins,
                         );
                         *show_help = false;
                     }
@@ -1282,6 +3199,8 @@ impl EventHandler {
             let divider_col = (ui.terminal_width * ui.split_position) / 100;
             if mouse.column.abs_diff(divider_col) <= 2 {
                 self.dragging = true;
+            } else if mouse.column >= ui.viewer_area_start {
+                ui.focus = Focus::Viewer;
             }
         }
 
@@ -1304,6 +3223,7 @@ impl EventHandler {
         search: &mut Search,
         bookmarks: &mut Bookmarks,
         ui: &mut UI,
+        plugins: &Plugins,
         show_files: &mut bool,
         show_help: &mut bool,
         fullscreen_viewer: bool,
@@ -1348,12 +3268,13 @@ impl EventHandler {
             nav.move_up();
             if (*show_files || fullscreen_viewer) && !*show_help {
                 if let Some(node) = nav.get_selected_node() {
-                    let _ = ui.load_file_for_viewer(
+                    let _ = ui.request_file_for_viewer(
                         file_viewer,
-                        &node.borrow().path,
+                        &node.path,
                         config.behavior.max_file_lines,
                         fullscreen_viewer,
                         config,
+                        plugins,
                     );
                 }
             }
@@ -1369,6 +3290,7 @@ impl EventHandler {
         search: &mut Search,
         bookmarks: &mut Bookmarks,
         ui: &mut UI,
+        plugins: &Plugins,
         show_files: &mut bool,
         show_help: &mut bool,
         fullscreen_viewer: bool,
@@ -1416,12 +3338,13 @@ impl EventHandler {
             nav.move_down();
             if (*show_files || fullscreen_viewer) && !*show_help {
                 if let Some(node) = nav.get_selected_node() {
-                    let _ = ui.load_file_for_viewer(
+                    let _ = ui.request_file_for_viewer(
                         file_viewer,
-                        &node.borrow().path,
+                        &node.path,
                         config.behavior.max_file_lines,
                         fullscreen_viewer,
                         config,
+                        plugins,
                     );
                 }
             }
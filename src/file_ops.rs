@@ -0,0 +1,648 @@
+use anyhow::{bail, Context, Result};
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+/// What happens to the yanked entries on paste
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferMode {
+    Copy,
+    Move,
+}
+
+/// How to handle a paste target that already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictChoice {
+    Overwrite,
+    Skip,
+    Rename,
+}
+
+/// Internal file clipboard (yank = copy, cut = move)
+#[derive(Debug, Clone)]
+pub struct FileClipboard {
+    pub mode: TransferMode,
+    pub paths: Vec<PathBuf>,
+}
+
+/// A single planned copy/move
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferItem {
+    pub source: PathBuf,
+    pub target: PathBuf,
+    pub replace: bool, // Remove existing target first (overwrite)
+}
+
+/// Paste being prepared: name conflicts are resolved one by one before anything is written
+#[derive(Debug)]
+pub struct PastePlan {
+    pub mode: TransferMode,
+    pub dest_dir: PathBuf,
+    queue: VecDeque<PathBuf>,
+    items: Vec<TransferItem>,
+    pub conflict: Option<TransferItem>, // Waiting for the user to pick a ConflictChoice
+    apply_to_all: Option<ConflictChoice>,
+    pub errors: Vec<String>,
+}
+
+impl PastePlan {
+    pub fn new(mode: TransferMode, sources: Vec<PathBuf>, dest_dir: PathBuf) -> Self {
+        let mut plan = Self {
+            mode,
+            dest_dir,
+            queue: sources.into(),
+            items: Vec::new(),
+            conflict: None,
+            apply_to_all: None,
+            errors: Vec::new(),
+        };
+        plan.advance();
+        plan
+    }
+
+    /// Plan queued sources until the next unresolved conflict
+    fn advance(&mut self) {
+        while self.conflict.is_none() {
+            let Some(source) = self.queue.pop_front() else {
+                break;
+            };
+
+            let Some(name) = source.file_name() else {
+                self.errors
+                    .push(format!("{}: cannot paste a root", source.display()));
+                continue;
+            };
+            let target = self.dest_dir.join(name);
+
+            if target == source {
+                // Moving onto itself is a no-op, copying onto itself makes a duplicate
+                if self.mode == TransferMode::Copy {
+                    self.items.push(TransferItem {
+                        source,
+                        target: unique_target(&target),
+                        replace: false,
+                    });
+                }
+                continue;
+            }
+
+            if source.is_dir() && self.dest_dir.starts_with(&source) {
+                self.errors.push(format!(
+                    "{}: cannot paste a directory into itself",
+                    source.display()
+                ));
+                continue;
+            }
+
+            // The target holds the source: replacing it would delete the source too
+            if source.starts_with(&target) {
+                self.errors.push(format!(
+                    "{}: cannot replace {}, which contains it",
+                    source.display(),
+                    target.display()
+                ));
+                continue;
+            }
+
+            let item = TransferItem {
+                source,
+                target,
+                replace: false,
+            };
+
+            // symlink_metadata also catches broken symlinks at the target
+            if fs::symlink_metadata(&item.target).is_ok() {
+                match self.apply_to_all {
+                    Some(choice) => self.apply(item, choice),
+                    None => self.conflict = Some(item),
+                }
+            } else {
+                self.items.push(item);
+            }
+        }
+    }
+
+    fn apply(&mut self, item: TransferItem, choice: ConflictChoice) {
+        match choice {
+            ConflictChoice::Overwrite => self.items.push(TransferItem {
+                replace: true,
+                ..item
+            }),
+            ConflictChoice::Skip => {}
+            ConflictChoice::Rename => {
+                let target = unique_target(&item.target);
+                self.items.push(TransferItem { target, ..item });
+            }
+        }
+    }
+
+    /// Resolve the pending conflict (optionally for all remaining conflicts)
+    pub fn resolve(&mut self, choice: ConflictChoice, apply_to_all: bool) {
+        if let Some(item) = self.conflict.take() {
+            if apply_to_all {
+                self.apply_to_all = Some(choice);
+            }
+            self.apply(item, choice);
+            self.advance();
+        }
+    }
+
+    /// All sources are planned and no conflict is pending
+    pub fn is_ready(&self) -> bool {
+        self.conflict.is_none() && self.queue.is_empty()
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn items(&self) -> &[TransferItem] {
+        &self.items
+    }
+}
+
+/// Find a free name next to `target`: "name (1).ext", "name (2).ext", ...
+pub fn unique_target(target: &Path) -> PathBuf {
+    let parent = target.parent().unwrap_or(Path::new(""));
+    let name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    // Keep the extension for files ("report (1).txt"), use the whole name for directories
+    let (stem, ext) = match name.rfind('.') {
+        Some(pos) if pos > 0 && !target.is_dir() => (&name[..pos], &name[pos..]),
+        _ => (name.as_str(), ""),
+    };
+
+    (1..)
+        .map(|n| parent.join(format!("{} ({}){}", stem, n, ext)))
+        .find(|candidate| fs::symlink_metadata(candidate).is_err())
+        .expect("unbounded range always yields a free name")
+}
+
+/// Messages from transfer thread to main thread
+#[derive(Debug)]
+enum TransferMessage {
+    /// One item finished (successfully or not)
+    Progress,
+    /// An item failed
+    Error(String),
+    /// All items processed (or cancelled)
+    Done,
+}
+
+/// Summary of a finished transfer
+#[derive(Debug)]
+pub struct TransferReport {
+    pub mode: TransferMode,
+    pub done: usize,
+    pub total: usize,
+    pub errors: Vec<String>,
+    pub cancelled: bool,
+    pub first_target: Option<PathBuf>,
+}
+
+/// Copy/move running in a background thread
+pub struct TransferJob {
+    pub mode: TransferMode,
+    pub total: usize,
+    pub done: usize,
+    pub errors: Vec<String>,
+    first_target: Option<PathBuf>,
+    cancel: Arc<AtomicBool>,
+    receiver: Receiver<TransferMessage>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl TransferJob {
+    pub fn spawn(mode: TransferMode, items: Vec<TransferItem>) -> Self {
+        let (tx, rx) = unbounded();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let total = items.len();
+        let first_target = items.first().map(|item| item.target.clone());
+
+        let thread_cancel = Arc::clone(&cancel);
+        let handle = thread::spawn(move || {
+            run_transfer(mode, &items, &thread_cancel, &tx);
+        });
+
+        Self {
+            mode,
+            total,
+            done: 0,
+            errors: Vec::new(),
+            first_target,
+            cancel,
+            receiver: rx,
+            handle: Some(handle),
+        }
+    }
+
+    /// Ask the worker to stop after the current file
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    /// Poll progress from the worker
+    /// Returns (updated, finished)
+    fn poll(&mut self) -> (bool, bool) {
+        let mut updated = false;
+        let mut finished = false;
+
+        while let Ok(msg) = self.receiver.try_recv() {
+            updated = true;
+            match msg {
+                TransferMessage::Progress => self.done += 1,
+                TransferMessage::Error(e) => self.errors.push(e),
+                TransferMessage::Done => finished = true,
+            }
+        }
+
+        if finished {
+            if let Some(handle) = self.handle.take() {
+                let _ = handle.join();
+            }
+        }
+
+        (updated, finished)
+    }
+}
+
+/// Worker: execute planned items in order, reporting progress
+fn run_transfer(
+    mode: TransferMode,
+    items: &[TransferItem],
+    cancel: &AtomicBool,
+    tx: &Sender<TransferMessage>,
+) {
+    for item in items {
+        if cancel.load(Ordering::Relaxed) {
+            break;
+        }
+        if let Err(e) = execute_item(mode, item, cancel) {
+            let _ = tx.send(TransferMessage::Error(format!(
+                "{}: {:#}",
+                item.source.display(),
+                e
+            )));
+        }
+        let _ = tx.send(TransferMessage::Progress);
+    }
+    let _ = tx.send(TransferMessage::Done);
+}
+
+/// Copy or move a single planned item
+pub fn execute_item(mode: TransferMode, item: &TransferItem, cancel: &AtomicBool) -> Result<()> {
+    if item.replace {
+        remove_path(&item.target)
+            .with_context(|| format!("Failed to replace {}", item.target.display()))?;
+    }
+
+    match mode {
+        TransferMode::Copy => copy_recursive(&item.source, &item.target, cancel),
+        TransferMode::Move => {
+            // Rename is instant on the same filesystem; fall back to copy + delete across devices
+            match fs::rename(&item.source, &item.target) {
+                Ok(()) => Ok(()),
+                Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+                    copy_recursive(&item.source, &item.target, cancel)?;
+                    remove_path(&item.source)
+                }
+                Err(e) => {
+                    Err(e).with_context(|| format!("Cannot move to {}", item.target.display()))
+                }
+            }
+        }
+    }
+}
+
+/// Recursively copy a file, directory or symlink
+fn copy_recursive(source: &Path, target: &Path, cancel: &AtomicBool) -> Result<()> {
+    if cancel.load(Ordering::Relaxed) {
+        bail!("Cancelled");
+    }
+
+    let metadata = fs::symlink_metadata(source)
+        .with_context(|| format!("Cannot read {}", source.display()))?;
+
+    #[cfg(unix)]
+    {
+        if metadata.is_symlink() {
+            // Recreate the link itself instead of copying what it points to
+            let link = fs::read_link(source)?;
+            std::os::unix::fs::symlink(link, target)
+                .with_context(|| format!("Cannot create {}", target.display()))?;
+            return Ok(());
+        }
+    }
+
+    if metadata.is_dir() || (metadata.is_symlink() && source.is_dir()) {
+        fs::create_dir(target).with_context(|| format!("Cannot create {}", target.display()))?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &target.join(entry.file_name()), cancel)?;
+        }
+    } else {
+        fs::copy(source, target).with_context(|| format!("Cannot copy to {}", target.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Remove a file, symlink or whole directory
+fn remove_path(path: &Path) -> Result<()> {
+    let metadata = fs::symlink_metadata(path)?;
+    if metadata.is_dir() {
+        fs::remove_dir_all(path)?;
+    } else {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// File clipboard, paste preparation and the running transfer
+#[derive(Default)]
+pub struct FileOps {
+    pub clipboard: Option<FileClipboard>,
+    pub paste: Option<PastePlan>,
+    pub job: Option<TransferJob>,
+}
+
+impl FileOps {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Put paths into the clipboard for a later paste
+    pub fn yank(&mut self, mode: TransferMode, paths: Vec<PathBuf>) {
+        if !paths.is_empty() {
+            self.clipboard = Some(FileClipboard { mode, paths });
+        }
+    }
+
+    /// Check if a path was cut (shown dimmed in the tree)
+    pub fn is_cut(&self, path: &Path) -> bool {
+        self.clipboard
+            .as_ref()
+            .is_some_and(|c| c.mode == TransferMode::Move && c.paths.iter().any(|p| p == path))
+    }
+
+    /// Start pasting the clipboard into dest_dir
+    /// Returns an error message if nothing can be pasted right now
+    pub fn start_paste(&mut self, dest_dir: PathBuf) -> Option<String> {
+        if self.job.is_some() {
+            return Some("Another copy/move is still running".to_string());
+        }
+        let Some(clipboard) = &self.clipboard else {
+            return Some("Clipboard is empty - yank (y) or cut (x) entries first".to_string());
+        };
+
+        self.paste = Some(PastePlan::new(
+            clipboard.mode,
+            clipboard.paths.clone(),
+            dest_dir,
+        ));
+        self.start_job_if_ready();
+        None
+    }
+
+    /// Answer the conflict prompt
+    pub fn resolve_conflict(&mut self, choice: ConflictChoice, apply_to_all: bool) {
+        if let Some(plan) = &mut self.paste {
+            plan.resolve(choice, apply_to_all);
+        }
+        self.start_job_if_ready();
+    }
+
+    /// Abort paste preparation (nothing has been written yet)
+    pub fn abort_paste(&mut self) {
+        self.paste = None;
+    }
+
+    pub fn is_resolving_conflict(&self) -> bool {
+        self.paste.as_ref().is_some_and(|p| p.conflict.is_some())
+    }
+
+    fn start_job_if_ready(&mut self) {
+        if !self.paste.as_ref().is_some_and(|p| p.is_ready()) {
+            return;
+        }
+        let Some(plan) = self.paste.take() else {
+            return;
+        };
+
+        let mut job = TransferJob::spawn(plan.mode, plan.items);
+        job.errors = plan.errors;
+        self.job = Some(job);
+
+        // Cut entries move exactly once
+        if plan.mode == TransferMode::Move {
+            self.clipboard = None;
+        }
+    }
+
+    /// Cancel the running transfer
+    pub fn cancel(&self) {
+        if let Some(job) = &self.job {
+            job.cancel();
+        }
+    }
+
+    /// Poll the running transfer
+    /// Returns (updated, report) - report is Some when the transfer just finished
+    pub fn poll(&mut self) -> (bool, Option<TransferReport>) {
+        let Some(job) = &mut self.job else {
+            return (false, None);
+        };
+
+        let (updated, finished) = job.poll();
+        if !finished {
+            return (updated, None);
+        }
+
+        let job = self.job.take().expect("job checked above");
+        let cancelled = job.cancel.load(Ordering::Relaxed);
+        (
+            true,
+            Some(TransferReport {
+                mode: job.mode,
+                done: job.done,
+                total: job.total,
+                errors: job.errors,
+                cancelled,
+                first_target: job.first_target,
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, PathBuf, PathBuf) {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        let dst = temp.path().join("dst");
+        fs::create_dir(&src).unwrap();
+        fs::create_dir(&dst).unwrap();
+        fs::write(src.join("a.txt"), "a").unwrap();
+        fs::create_dir(src.join("dir")).unwrap();
+        fs::write(src.join("dir").join("b.txt"), "b").unwrap();
+        (temp, src, dst)
+    }
+
+    #[test]
+    fn test_unique_target() {
+        let (_temp, src, _dst) = setup();
+        assert_eq!(unique_target(&src.join("a.txt")), src.join("a (1).txt"));
+        assert_eq!(unique_target(&src.join("dir")), src.join("dir (1)"));
+
+        fs::write(src.join("a (1).txt"), "").unwrap();
+        assert_eq!(unique_target(&src.join("a.txt")), src.join("a (2).txt"));
+    }
+
+    #[test]
+    fn test_plan_without_conflicts_is_ready() {
+        let (_temp, src, dst) = setup();
+        let plan = PastePlan::new(
+            TransferMode::Copy,
+            vec![src.join("a.txt"), src.join("dir")],
+            dst.clone(),
+        );
+        assert!(plan.is_ready());
+        assert_eq!(plan.items().len(), 2);
+        assert_eq!(plan.items()[0].target, dst.join("a.txt"));
+    }
+
+    #[test]
+    fn test_plan_conflict_resolution() {
+        let (_temp, src, dst) = setup();
+        fs::write(dst.join("a.txt"), "old").unwrap();
+        fs::create_dir(dst.join("dir")).unwrap();
+
+        let mut plan = PastePlan::new(
+            TransferMode::Copy,
+            vec![src.join("a.txt"), src.join("dir")],
+            dst.clone(),
+        );
+        assert!(!plan.is_ready());
+        assert_eq!(
+            plan.conflict.as_ref().map(|c| c.target.clone()),
+            Some(dst.join("a.txt"))
+        );
+
+        plan.resolve(ConflictChoice::Overwrite, false);
+        assert_eq!(
+            plan.conflict.as_ref().map(|c| c.target.clone()),
+            Some(dst.join("dir"))
+        );
+
+        plan.resolve(ConflictChoice::Rename, false);
+        assert!(plan.is_ready());
+        assert!(plan.items()[0].replace);
+        assert_eq!(plan.items()[1].target, dst.join("dir (1)"));
+    }
+
+    #[test]
+    fn test_plan_apply_to_all_and_skip() {
+        let (_temp, src, dst) = setup();
+        fs::write(dst.join("a.txt"), "old").unwrap();
+        fs::create_dir(dst.join("dir")).unwrap();
+
+        let mut plan = PastePlan::new(
+            TransferMode::Move,
+            vec![src.join("a.txt"), src.join("dir")],
+            dst,
+        );
+        plan.resolve(ConflictChoice::Skip, true);
+        assert!(plan.is_ready());
+        assert!(plan.items().is_empty());
+    }
+
+    #[test]
+    fn test_plan_rejects_paste_into_itself() {
+        let (_temp, src, _dst) = setup();
+        let plan = PastePlan::new(TransferMode::Copy, vec![src.clone()], src.join("dir"));
+        assert!(plan.is_ready());
+        assert!(plan.items().is_empty());
+        assert_eq!(plan.errors.len(), 1);
+    }
+
+    #[test]
+    fn test_plan_rejects_replacing_an_ancestor_of_the_source() {
+        let temp = TempDir::new().unwrap();
+        let base = temp.path().to_path_buf();
+        fs::create_dir_all(base.join("a/a")).unwrap();
+        fs::write(base.join("a/a/keep.txt"), "keep").unwrap();
+
+        // Pasting base/a/a into base collides with base/a, which holds the source
+        let plan = PastePlan::new(TransferMode::Move, vec![base.join("a/a")], base.clone());
+        assert!(plan.is_ready());
+        assert!(plan.items().is_empty());
+        assert_eq!(plan.errors.len(), 1);
+        assert!(base.join("a/a/keep.txt").exists());
+    }
+
+    #[test]
+    fn test_copy_onto_itself_duplicates() {
+        let (_temp, src, _dst) = setup();
+        let plan = PastePlan::new(TransferMode::Copy, vec![src.join("a.txt")], src.clone());
+        assert_eq!(plan.items()[0].target, src.join("a (1).txt"));
+
+        let plan = PastePlan::new(TransferMode::Move, vec![src.join("a.txt")], src.clone());
+        assert!(plan.items().is_empty());
+    }
+
+    #[test]
+    fn test_execute_copy_and_move() {
+        let (_temp, src, dst) = setup();
+        let cancel = AtomicBool::new(false);
+
+        let copy = TransferItem {
+            source: src.join("dir"),
+            target: dst.join("dir"),
+            replace: false,
+        };
+        execute_item(TransferMode::Copy, &copy, &cancel).unwrap();
+        assert_eq!(
+            fs::read_to_string(dst.join("dir").join("b.txt")).unwrap(),
+            "b"
+        );
+        assert!(src.join("dir").exists());
+
+        let mv = TransferItem {
+            source: src.join("a.txt"),
+            target: dst.join("a.txt"),
+            replace: false,
+        };
+        execute_item(TransferMode::Move, &mv, &cancel).unwrap();
+        assert!(!src.join("a.txt").exists());
+        assert_eq!(fs::read_to_string(dst.join("a.txt")).unwrap(), "a");
+    }
+
+    #[test]
+    fn test_file_ops_paste_runs_job() {
+        let (_temp, src, dst) = setup();
+        let mut ops = FileOps::new();
+        ops.yank(TransferMode::Move, vec![src.join("a.txt")]);
+        assert!(ops.is_cut(&src.join("a.txt")));
+
+        assert!(ops.start_paste(dst.clone()).is_none());
+        assert!(ops.clipboard.is_none()); // cut clipboard is consumed
+
+        let report = loop {
+            if let (_, Some(report)) = ops.poll() {
+                break report;
+            }
+            thread::sleep(std::time::Duration::from_millis(5));
+        };
+        assert_eq!(report.done, 1);
+        assert!(report.errors.is_empty());
+        assert!(dst.join("a.txt").exists());
+    }
+}
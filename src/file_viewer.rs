@@ -1,17 +1,32 @@
+use crate::clipboard;
+use crate::config::BehaviorConfig;
+use crate::format;
+use crate::git::{self, GitView};
+use crate::goto;
+use crate::highlight_cache::{self, HighlightJob, HighlightKey};
+use crate::i18n::{tr, tr_with};
+use crate::mime::{self, FileType};
+use crate::patch::{self, LineKind};
+use crate::platform::FileId;
+use crate::preview_cache::{self, CachedPreview, PreviewKey};
+use crate::preview_loader::{PreviewLoader, PreviewRequest};
+use crate::vfs::{self, Vfs, VfsMetadata, VfsProvider};
+use crate::viewer_state::{ViewState, ViewStates};
 use anyhow::Result;
 use once_cell::sync::Lazy;
 use ratatui::style::{Color, Style};
 use ratatui::text::{Line, Span};
-use std::fs::File;
+use std::collections::HashMap;
 use std::io::{BufRead, BufReader};
-#[cfg(unix)]
-use std::os::unix::fs::PermissionsExt;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::SystemTime;
 use syntect::easy::HighlightLines;
 use syntect::highlighting::ThemeSet;
 use syntect::parsing::SyntaxSet;
-use unicode_truncate::UnicodeTruncateStr;
-use unicode_width::UnicodeWidthStr;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 /// Lazy-loaded syntax set (loaded once on first use)
 static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
@@ -19,6 +34,79 @@ static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines
 /// Lazy-loaded theme set (loaded once on first use)
 static THEME_SET: Lazy<ThemeSet> = Lazy::new(ThemeSet::load_defaults);
 
+/// Wrap width without a known viewer width (very large, so nothing wraps)
+const DEFAULT_MAX_WIDTH: usize = 10000;
+
+/// What the preview reads, so huge or special files never block the UI
+#[derive(Debug, Clone)]
+pub struct PreviewLimits {
+    /// Larger files aren't read (0 = no limit)
+    pub max_size: u64,
+    /// Bytes read to tell binary files from text
+    pub sniff_bytes: usize,
+    /// Paths under these directories aren't previewed (not even stat'ed)
+    pub skip: Vec<PathBuf>,
+    /// Lines shown from both ends of files longer than max_file_lines (0 = head only)
+    pub head_tail_lines: usize,
+}
+
+impl Default for PreviewLimits {
+    fn default() -> Self {
+        Self {
+            max_size: 100 * 1024 * 1024,
+            sniff_bytes: mime::SAMPLE_SIZE,
+            skip: Vec::new(),
+            head_tail_lines: 0,
+        }
+    }
+}
+
+impl PreviewLimits {
+    /// Limits from the behavior config; skipped paths may start with `~`
+    pub fn from_config(behavior: &BehaviorConfig) -> Self {
+        let cwd = std::env::current_dir().unwrap_or_default();
+        Self {
+            max_size: behavior.max_preview_size_mb.saturating_mul(1024 * 1024),
+            sniff_bytes: behavior.binary_sniff_bytes.max(1),
+            skip: behavior
+                .preview_skip
+                .iter()
+                .filter(|path| !path.is_empty())
+                .map(|path| goto::expand_path(path, &cwd))
+                .collect(),
+            head_tail_lines: behavior.head_tail_lines,
+        }
+    }
+
+    /// Whether `path` is at or below one of the skipped paths
+    pub fn skips(&self, path: &Path) -> bool {
+        self.skip.iter().any(|dir| path.starts_with(dir))
+    }
+}
+
+/// Mark command waiting for the name of the mark (`m` or `'` pressed)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkKey {
+    Set,
+    Jump,
+}
+
+/// Where `zt`, `zz` and `zb` put the selected line (tree entry, viewer line) on the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    Top,
+    Center,
+    Bottom,
+}
+
+/// One row of the minimap: a share of the file scaled to the viewer height
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MinimapRow {
+    pub in_view: bool,
+    pub has_match: bool,
+    pub current_match: bool,
+}
+
 /// File viewer state and logic for displaying file contents
 pub struct FileViewer {
     pub content: Vec<String>,
@@ -27,12 +115,23 @@ pub struct FileViewer {
     pub current_path: PathBuf,
     pub current_size: u64,
     pub current_permissions: u32,
+    pub current_modified: Option<SystemTime>,
+    pub current_file_id: Option<FileId>, // Device, inode and link count (Unix)
     pub show_line_numbers: bool,
     pub wrap_lines: bool, // true = wrap long lines, false = truncate
     pub syntax_name: Option<String>,
     pub is_binary: bool,
     pub tail_mode: bool, // true = showing last N lines, false = showing first N lines
     pub total_lines: Option<usize>, // total lines in file (if known)
+    pub git_view: Option<GitView>, // git log/blame shown instead of the content
+    pub wrap_width: usize, // Columns lines are wrapped at when shown (wrap_lines)
+    line_numbers: Vec<usize>, // Line in the file of each content line (0 = notice)
+    pub line_kinds: Vec<Option<LineKind>>, // Patch and conflict lines (empty = none)
+    pub limits: PreviewLimits,
+    pub loader: PreviewLoader, // Loads previews of the selection in the background
+    pub vfs: Vfs,              // Where previewed files are read from
+    cancel: Option<Arc<AtomicBool>>, // Set when this viewer loads for the background loader
+    highlight_job: Option<HighlightJob>, // Highlights the rows not highlighted yet
 
     // Search functionality
     pub search_mode: bool,
@@ -44,6 +143,14 @@ pub struct FileViewer {
     pub visual_mode: bool,
     pub visual_start: Option<usize>, // Start line of selection (0-indexed)
     pub visual_cursor: usize,        // Current cursor position in visual mode (0-indexed)
+
+    // Marks: m{name} remembers the top line, '{name} goes back to it ('' undoes a jump)
+    pub marks: HashMap<char, usize>, // Line in the file (1-based) of each mark
+    pub mark_key: Option<MarkKey>,   // Waiting for the mark name
+
+    // Position, head/tail, wrap and line numbers of the files viewed before
+    pub view_states: ViewStates,
+    restore_line: Option<usize>, // Line to show at the top once the content is loaded
 }
 
 impl Default for FileViewer {
@@ -61,12 +168,23 @@ impl FileViewer {
             current_path: PathBuf::new(),
             current_size: 0,
             current_permissions: 0,
+            current_modified: None,
+            current_file_id: None,
             show_line_numbers: false,
             wrap_lines: true, // Default to wrapping enabled
             syntax_name: None,
             is_binary: false,
             tail_mode: false,
             total_lines: None,
+            git_view: None,
+            wrap_width: DEFAULT_MAX_WIDTH,
+            line_numbers: Vec::new(),
+            line_kinds: Vec::new(),
+            limits: PreviewLimits::default(),
+            loader: PreviewLoader::new(),
+            vfs: vfs::local(),
+            cancel: None,
+            highlight_job: None,
             search_mode: false,
             search_query: String::new(),
             search_results: Vec::new(),
@@ -74,6 +192,58 @@ impl FileViewer {
             visual_mode: false,
             visual_start: None,
             visual_cursor: 0,
+            marks: HashMap::new(),
+            mark_key: None,
+            restore_line: None,
+            view_states: ViewStates::new(ViewState {
+                line: 1,
+                tail_mode: false,
+                wrap_lines: true,
+                show_line_numbers: false,
+            }),
+        }
+    }
+
+    /// How the shown file is viewed
+    pub fn view_state(&self) -> ViewState {
+        ViewState {
+            line: self.top_line(),
+            tail_mode: self.tail_mode,
+            wrap_lines: self.wrap_lines,
+            show_line_numbers: self.show_line_numbers,
+        }
+    }
+
+    /// Remember how the shown file is viewed (not help or a git view)
+    pub fn remember_view_state(&mut self) {
+        if !self.current_path.as_os_str().is_empty() && self.git_view.is_none() {
+            self.view_states
+                .remember(&self.current_path, self.view_state());
+        }
+    }
+
+    /// How `path` is viewed when it replaces the shown file; None keeps the current
+    /// settings (the same file, or a new file after none or help)
+    fn view_state_for(&self, path: &Path) -> Option<ViewState> {
+        if path == self.current_path {
+            return None;
+        }
+        self.view_states.get(path).or_else(|| {
+            (!self.current_path.as_os_str().is_empty()).then_some(self.view_states.defaults)
+        })
+    }
+
+    /// Remember how the shown file is viewed and view `path` the way it was last
+    fn switch_view_state(&mut self, path: &Path) {
+        let state = self.view_state_for(path);
+        self.remember_view_state();
+        self.scroll = 0;
+        self.restore_line = None;
+        if let Some(state) = state {
+            self.restore_line = Some(state.line);
+            self.tail_mode = state.tail_mode;
+            self.wrap_lines = state.wrap_lines;
+            self.show_line_numbers = state.show_line_numbers;
         }
     }
 
@@ -88,11 +258,15 @@ impl FileViewer {
     }
 
     /// Read last N lines from a file (for tail mode)
-    fn read_tail_lines(path: &Path, max_lines: usize) -> Result<(Vec<String>, usize)> {
-        use std::io::{Seek, SeekFrom};
+    fn read_tail_lines(
+        vfs: &dyn VfsProvider,
+        path: &Path,
+        max_lines: usize,
+    ) -> Result<(Vec<String>, usize)> {
+        use std::io::SeekFrom;
 
-        let mut file = File::open(path)?;
-        let file_size = file.metadata()?.len();
+        let mut file = vfs.open(path)?;
+        let file_size = vfs.metadata(path)?.len;
 
         // If file is small, just read all lines
         if file_size < 1024 * 1024 {
@@ -156,6 +330,187 @@ impl FileViewer {
         }
     }
 
+    /// Clear the previous file's content before loading `path`
+    fn reset_for(&mut self, path: &Path) {
+        // Whatever is loaded now replaces a preview still loading in the background
+        self.loader.cancel();
+        self.highlight_job = None;
+        // Marks and the scroll position stay while the same file is reloaded
+        // (resize, head/tail); another file is shown the way it was left
+        if self.current_path != path {
+            self.marks.clear();
+            self.switch_view_state(path);
+        }
+        self.content.clear();
+        self.highlighted_content.clear();
+        self.current_path = path.to_path_buf();
+        self.current_size = 0;
+        self.current_permissions = 0;
+        self.current_modified = None;
+        self.current_file_id = None;
+        self.syntax_name = None;
+        self.is_binary = false;
+        self.total_lines = None;
+        self.git_view = None;
+        self.line_numbers.clear();
+        self.line_kinds.clear();
+    }
+
+    /// Size, permissions and modification time shown in the file info line
+    fn set_metadata(&mut self, metadata: &VfsMetadata) {
+        self.current_size = metadata.len;
+        self.current_modified = metadata.modified;
+        self.current_file_id = metadata.file_id;
+        #[cfg(unix)]
+        {
+            self.current_permissions = metadata.mode;
+        }
+        #[cfg(windows)]
+        {
+            // On Windows, permissions are simpler - just check if file is readonly
+            self.current_permissions = if metadata.readonly {
+                0o444 // read-only
+            } else {
+                0o644 // read-write
+            };
+        }
+    }
+
+    /// Show text produced elsewhere (plugin previewer) for `path`, without highlighting
+    pub fn load_text(&mut self, path: &Path, text: &str, max_width: Option<usize>) {
+        self.reset_for(path);
+        if let Ok(metadata) = self.vfs.metadata(path) {
+            self.set_metadata(&metadata);
+        }
+
+        self.wrap_width = max_width.unwrap_or(DEFAULT_MAX_WIDTH);
+        self.content = text
+            .lines()
+            .map(|line| line.replace('\t', "    "))
+            .collect();
+        self.total_lines = Some(self.content.len());
+        self.restore_scroll();
+    }
+
+    /// Like `load_file_with_width`, but on the loader's worker thread once the
+    /// selection rests; the current content stays until `poll_loaded` takes the new one
+    pub fn load_file_in_background(
+        &mut self,
+        path: &Path,
+        max_width: Option<usize>,
+        max_lines: usize,
+        enable_syntax_highlighting: bool,
+        syntax_theme: &str,
+    ) {
+        let state = self
+            .view_state_for(path)
+            .unwrap_or_else(|| self.view_state());
+        self.loader.request(PreviewRequest {
+            path: path.to_path_buf(),
+            max_width,
+            max_lines,
+            syntax_theme: enable_syntax_highlighting.then(|| syntax_theme.to_string()),
+            tail_mode: state.tail_mode,
+            wrap_lines: state.wrap_lines,
+            limits: self.limits.clone(),
+            vfs: Arc::clone(&self.vfs),
+        });
+    }
+
+    /// Take the preview loaded or highlighted in the background, if it's ready
+    /// Returns true if the content changed
+    pub fn poll_loaded(&mut self) -> bool {
+        if let Some(loaded) = self.loader.poll() {
+            self.take_loaded(loaded);
+            return true;
+        }
+        let highlighted = self.highlight_job.as_ref().and_then(HighlightJob::poll);
+        self.take_highlighted(highlighted)
+    }
+
+    /// Whether the preview is still loading or highlighting in the background
+    pub fn is_loading(&self) -> bool {
+        self.loader.is_busy() || self.highlight_job.is_some()
+    }
+
+    /// Wait for the preview loading or highlighting in the background (headless driver)
+    pub fn finish_loading(&mut self) -> bool {
+        let loaded = self.loader.finish();
+        let updated = loaded.is_some();
+        if let Some(loaded) = loaded {
+            self.take_loaded(loaded);
+        }
+        let highlighted = self.highlight_job.as_ref().and_then(HighlightJob::wait);
+        self.take_highlighted(highlighted) || updated
+    }
+
+    fn take_highlighted(&mut self, highlighted: Option<Vec<Line<'static>>>) -> bool {
+        match highlighted {
+            Some(lines) => {
+                self.highlighted_content = lines;
+                self.highlight_job = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Show what the background loader read (same state `load_file_with_width` sets)
+    fn take_loaded(&mut self, loaded: FileViewer) {
+        self.content = loaded.content;
+        self.highlighted_content = loaded.highlighted_content;
+        if self.current_path != loaded.current_path {
+            self.marks.clear();
+            self.switch_view_state(&loaded.current_path);
+        }
+        self.current_path = loaded.current_path;
+        self.current_size = loaded.current_size;
+        self.current_permissions = loaded.current_permissions;
+        self.current_modified = loaded.current_modified;
+        self.current_file_id = loaded.current_file_id;
+        self.syntax_name = loaded.syntax_name;
+        self.is_binary = loaded.is_binary;
+        self.total_lines = loaded.total_lines;
+        self.git_view = None;
+        self.wrap_width = loaded.wrap_width;
+        self.line_numbers = loaded.line_numbers;
+        self.line_kinds = loaded.line_kinds;
+        self.highlight_job = None;
+        self.restore_scroll();
+    }
+
+    /// Scroll to the line a file was left at, and keep the scroll position within
+    /// a file that got shorter
+    fn restore_scroll(&mut self) {
+        if let Some(line) = self.restore_line.take() {
+            self.go_to_file_line(line);
+        }
+        self.scroll = self.scroll.min(self.content.len().saturating_sub(1));
+    }
+
+    /// Whether the shown file's size or modification time differ from when it
+    /// was loaded (auto_reload)
+    pub fn changed_on_disk(&self) -> bool {
+        if self.current_modified.is_none() || self.git_view.is_some() {
+            return false;
+        }
+        self.vfs.metadata(&self.current_path).is_ok_and(|metadata| {
+            metadata.modified != self.current_modified || metadata.len != self.current_size
+        })
+    }
+
+    /// Let the background loader stop this viewer's load early
+    pub(crate) fn set_cancel(&mut self, cancel: Arc<AtomicBool>) {
+        self.cancel = Some(cancel);
+    }
+
+    /// Whether a background load was replaced by a newer one
+    fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+
     /// Load file content with specified max width and max lines
     pub fn load_file_with_width(
         &mut self,
@@ -165,82 +520,139 @@ impl FileViewer {
         enable_syntax_highlighting: bool,
         syntax_theme: &str,
     ) -> Result<()> {
-        const DEFAULT_MAX_WIDTH: usize = 10000; // Very large default to avoid truncation
+        let result = self.read_file(
+            path,
+            max_width,
+            max_lines,
+            enable_syntax_highlighting,
+            syntax_theme,
+        );
+        self.restore_scroll();
+        result
+    }
 
-        let max_width = max_width.unwrap_or(DEFAULT_MAX_WIDTH);
+    /// Read `path` into content (the scroll position is clamped by the caller)
+    fn read_file(
+        &mut self,
+        path: &Path,
+        max_width: Option<usize>,
+        max_lines: usize,
+        enable_syntax_highlighting: bool,
+        syntax_theme: &str,
+    ) -> Result<()> {
+        self.reset_for(path);
+        self.wrap_width = max_width.unwrap_or(DEFAULT_MAX_WIDTH);
 
-        self.content.clear();
-        self.highlighted_content.clear();
-        self.scroll = 0;
-        self.current_path = path.to_path_buf();
-        self.current_size = 0;
-        self.current_permissions = 0;
-        self.syntax_name = None;
-        self.is_binary = false;
-        // Note: tail_mode is NOT reset here - it persists across reloads
-        self.total_lines = None;
+        // Skipped paths (/proc, network mounts, ...) aren't touched at all
+        if self.limits.skips(path) {
+            self.content
+                .push(tr("[Preview skipped (preview_skip)]").to_string());
+            return Ok(());
+        }
 
-        // Check if this is a file
-        if !path.is_file() {
-            if path.is_dir() {
+        // Check if this is a file (FIFOs, sockets and devices are never opened)
+        let metadata = self.vfs.metadata(path);
+        match &metadata {
+            Ok(metadata) if metadata.is_file => self.set_metadata(metadata),
+            Ok(metadata) if metadata.is_dir => {
                 self.content
-                    .push("[Directory - use arrow keys to navigate]".to_string());
-            } else if path.is_symlink() {
-                self.content.push("[Symbolic link]".to_string());
-            } else {
-                self.content.push("[Not a regular file]".to_string());
+                    .push(tr("[Directory - use arrow keys to navigate]").to_string());
+                return Ok(());
+            }
+            _ if self
+                .vfs
+                .symlink_metadata(path)
+                .is_ok_and(|metadata| metadata.is_symlink) =>
+            {
+                self.content.push(tr("[Symbolic link]").to_string());
+                return Ok(());
             }
+            _ => {
+                self.content.push(tr("[Not a regular file]").to_string());
+                return Ok(());
+            }
+        }
+
+        if self.limits.max_size > 0 && self.current_size > self.limits.max_size {
+            self.content.push(tr_with(
+                "[Too large to preview: {size} (max_preview_size_mb)]",
+                &[("size", &format_file_size(self.current_size))],
+            ));
             return Ok(());
         }
 
-        // Get file metadata
-        match std::fs::metadata(path) {
-            Ok(metadata) => {
-                self.current_size = metadata.len();
-                #[cfg(unix)]
-                {
-                    self.current_permissions = metadata.permissions().mode();
-                }
-                #[cfg(windows)]
-                {
-                    // On Windows, permissions are simpler - just check if file is readonly
-                    self.current_permissions = if metadata.permissions().readonly() {
-                        0o444 // read-only
-                    } else {
-                        0o644 // read-write
-                    };
-                }
+        // Recently read files come from the cache (known to be text)
+        let key = PreviewKey {
+            path: path.to_path_buf(),
+            modified: self.current_modified,
+            size: self.current_size,
+            tail_mode: self.tail_mode,
+            max_lines,
+            head_tail_lines: self.limits.head_tail_lines,
+        };
+        if let Some(cached) = preview_cache::get(&key) {
+            self.content = cached.content;
+            self.line_numbers = cached.line_numbers;
+            self.total_lines = cached.total_lines;
+        } else {
+            // Check if file is binary before trying to read it as text
+            if let Some(file_type) = self
+                .vfs
+                .read_head(path, self.limits.sniff_bytes)
+                .ok()
+                .map(|sample| mime::detect(&sample))
+                .filter(|file_type| !file_type.is_text)
+            {
+                self.is_binary = true;
+                self.load_binary_info(path, file_type);
+                return Ok(());
             }
-            Err(e) => {
-                self.content.push(format!("[Cannot read metadata: {}]", e));
+
+            if !self.read_text(path, max_lines) {
                 return Ok(());
             }
+            preview_cache::insert(
+                key,
+                CachedPreview {
+                    content: self.content.clone(),
+                    line_numbers: self.line_numbers.clone(),
+                    total_lines: self.total_lines,
+                },
+            );
         }
 
-        // Check if file is binary before trying to read it as text
-        if Self::is_binary_file(path) {
-            self.is_binary = true;
-            self.load_binary_info(path);
-            return Ok(());
+        self.line_kinds = patch::classify(&self.content, patch::is_patch_file(path));
+
+        // Apply syntax highlighting if enabled
+        if enable_syntax_highlighting && !self.content.is_empty() {
+            self.apply_syntax_highlighting(syntax_theme);
         }
 
+        Ok(())
+    }
+
+    /// Read the head or tail of a text file into content
+    /// Returns false when it stopped early (read error, cancelled) with nothing to cache
+    fn read_text(&mut self, path: &Path, max_lines: usize) -> bool {
         // Read file content based on mode (head or tail)
-        let (raw_lines, total_lines) = if self.tail_mode {
+        let (mut raw_lines, total_lines) = if self.tail_mode {
             // Tail mode: read last N lines
-            match Self::read_tail_lines(path, max_lines) {
+            match Self::read_tail_lines(self.vfs.as_ref(), path, max_lines) {
                 Ok(result) => result,
                 Err(e) => {
-                    self.content.push(format!("[Error reading file: {}]", e));
-                    return Ok(());
+                    self.content
+                        .push(tr_with("[Error reading file: {error}]", &[("error", &e)]));
+                    return false;
                 }
             }
         } else {
             // Head mode: read first N lines
-            let file = match File::open(path) {
+            let file = match self.vfs.open(path) {
                 Ok(f) => f,
                 Err(e) => {
-                    self.content.push(format!("[Error: {}]", e));
-                    return Ok(());
+                    self.content
+                        .push(tr_with("[Error: {error}]", &[("error", &e)]));
+                    return false;
                 }
             };
 
@@ -251,6 +663,9 @@ impl FileViewer {
 
             for line in reader.lines() {
                 total += 1;
+                if total % 4096 == 0 && self.is_cancelled() {
+                    return false;
+                }
 
                 if line_count >= max_lines {
                     // Continue counting total lines even after truncation
@@ -265,9 +680,11 @@ impl FileViewer {
                     Err(e) => {
                         // Possibly binary file or encoding error
                         self.content.clear();
-                        self.content
-                            .push(format!("[Binary file or encoding error: {}]", e));
-                        return Ok(());
+                        self.content.push(tr_with(
+                            "[Binary file or encoding error: {error}]",
+                            &[("error", &e)],
+                        ));
+                        return false;
                     }
                 }
             }
@@ -275,55 +692,71 @@ impl FileViewer {
             (lines, total)
         };
 
+        // Both ends of a long file in one view (head_tail_lines)
+        let keep = self.limits.head_tail_lines.min(max_lines / 2);
+        let tail_lines = if !self.tail_mode && keep > 0 && total_lines > max_lines {
+            Self::read_tail_lines(self.vfs.as_ref(), path, keep)
+                .ok()
+                .map(|(lines, _)| lines)
+        } else {
+            None
+        };
+        if tail_lines.is_some() {
+            raw_lines.truncate(keep);
+        }
+
         // Store total lines for UI display
         self.total_lines = Some(total_lines);
+        let first_line = if self.tail_mode {
+            total_lines.saturating_sub(raw_lines.len()) + 1
+        } else {
+            1
+        };
 
-        // Process lines: replace tabs and wrap/truncate based on settings
-        for content in raw_lines {
-            // Replace tabs with spaces (4 spaces per tab)
-            let content_no_tabs = content.replace('\t', "    ");
-
-            if self.wrap_lines {
-                // Wrap long lines
-                let wrapped_lines = Self::wrap_line(&content_no_tabs, max_width);
-                for wrapped in wrapped_lines {
-                    self.content.push(wrapped);
-                }
-            } else {
-                // Don't truncate - keep full line content for copying
-                self.content.push(content_no_tabs);
-            }
+        // Lines stay whole (wrapping happens when they're shown); tabs become 4 spaces
+        for (index, content) in raw_lines.into_iter().enumerate() {
+            self.content.push(content.replace('\t', "    "));
+            self.line_numbers.push(first_line + index);
         }
 
         // Add truncation indicator if needed
-        if !self.tail_mode && total_lines > max_lines {
-            self.content.push(format!(
-                "\n[... truncated, showing first {} of {} lines. Press End to see tail ...]",
-                max_lines, total_lines
+        if let Some(tail_lines) = tail_lines {
+            let first_tail_line = total_lines.saturating_sub(tail_lines.len()) + 1;
+            self.content.push(tr_with(
+                "[... {count} lines omitted ...]",
+                &[("count", &(first_tail_line - keep - 1))],
+            ));
+            self.line_numbers.push(0);
+            for (index, content) in tail_lines.into_iter().enumerate() {
+                self.content.push(content.replace('\t', "    "));
+                self.line_numbers.push(first_tail_line + index);
+            }
+        } else if !self.tail_mode && total_lines > max_lines {
+            self.content.push(tr_with(
+                "\n[... truncated, showing first {shown} of {total} lines. Press End to see tail ...]",
+                &[("shown", &max_lines), ("total", &total_lines)],
             ));
+            self.line_numbers.push(0);
         } else if self.tail_mode && total_lines > max_lines {
             self.content.insert(
                 0,
-                format!(
-                    "[... showing last {} of {} lines. Press Home to see head ...]",
-                    max_lines, total_lines
+                tr_with(
+                    "[... showing last {shown} of {total} lines. Press Home to see head ...]",
+                    &[("shown", &max_lines), ("total", &total_lines)],
                 ),
             );
+            self.line_numbers.insert(0, 0);
         }
 
         if self.content.is_empty() {
-            self.content.push("[Empty file]".to_string());
-        }
-
-        // Apply syntax highlighting if enabled
-        if enable_syntax_highlighting && !self.content.is_empty() {
-            self.apply_syntax_highlighting(syntax_theme);
+            self.content.push(tr("[Empty file]").to_string());
         }
-
-        Ok(())
+        true
     }
 
     /// Apply syntax highlighting to content
+    /// The first lines are highlighted right away and the rest in the background,
+    /// unless the file is still cached or this already runs in the background
     fn apply_syntax_highlighting(&mut self, theme_name: &str) {
         // Detect syntax based on file extension
         let syntax = SYNTAX_SET
@@ -334,163 +767,223 @@ impl FileViewer {
 
         self.syntax_name = Some(syntax.name.clone());
 
+        let key = HighlightKey::new(
+            &self.current_path,
+            self.current_modified,
+            theme_name,
+            &self.content,
+        );
+        if let Some(lines) = highlight_cache::get(&key) {
+            self.highlighted_content = lines;
+            return;
+        }
+
         // Get theme
         let theme = THEME_SET
             .themes
             .get(theme_name)
             .unwrap_or_else(|| THEME_SET.themes.get("base16-ocean.dark").unwrap());
 
-        // Highlight lines
-        let mut highlighter = HighlightLines::new(syntax, theme);
-
-        for line_text in &self.content {
-            let highlighted = highlighter.highlight_line(line_text, &SYNTAX_SET);
-
-            match highlighted {
-                Ok(ranges) => {
-                    let spans: Vec<Span> = ranges
-                        .iter()
-                        .map(|(style, text)| {
-                            let fg_color = Self::syntect_color_to_ratatui(style.foreground);
-                            Span::styled(text.to_string(), Style::default().fg(fg_color))
-                        })
-                        .collect();
-
-                    self.highlighted_content.push(Line::from(spans));
-                }
-                Err(_) => {
-                    // Fallback to plain text
-                    self.highlighted_content.push(Line::from(line_text.clone()));
-                }
-            }
+        // The rows the view opens at come first (parsed from there, so a construct
+        // spanning lines may be off until the whole file is highlighted)
+        let len = self.content.len();
+        let (start, end) = if self.cancel.is_some() {
+            (0, len)
+        } else {
+            let start = self.opening_row().min(len.saturating_sub(1));
+            (start, (start + highlight_cache::FIRST_ROWS).min(len))
+        };
+        let Some(lines) = highlight_cache::highlight(
+            &self.content[start..end],
+            syntax,
+            &SYNTAX_SET,
+            theme,
+            self.cancel.as_deref(),
+        ) else {
+            return;
+        };
+        if start == 0 && end == len {
+            self.highlighted_content = lines;
+            highlight_cache::insert(key, self.highlighted_content.clone());
+            return;
         }
-    }
 
-    /// Convert syntect color to ratatui color
-    fn syntect_color_to_ratatui(color: syntect::highlighting::Color) -> Color {
-        Color::Rgb(color.r, color.g, color.b)
+        // The other lines stay plain until the whole file is highlighted
+        let plain = |rows: &[String]| -> Vec<Line<'static>> {
+            rows.iter().map(|row| Line::from(row.clone())).collect()
+        };
+        self.highlighted_content = plain(&self.content[..start]);
+        self.highlighted_content.extend(lines);
+        self.highlighted_content.extend(plain(&self.content[end..]));
+        self.highlight_job = Some(HighlightJob::start(
+            key,
+            self.content.clone(),
+            syntax,
+            &SYNTAX_SET,
+            theme,
+        ));
     }
 
-    /// Wrap a line to max_width, returning a vector of wrapped lines
-    fn wrap_line(line: &str, max_width: usize) -> Vec<String> {
-        if max_width == 0 {
-            return vec![line.to_string()];
+    /// Row the view opens at: the line to restore, else the current scroll position
+    fn opening_row(&self) -> usize {
+        match self.restore_line {
+            Some(line) => self
+                .line_numbers
+                .iter()
+                .position(|&number| number >= line)
+                .unwrap_or(0),
+            None => self.scroll,
         }
+    }
 
-        // If line fits, return it as-is
-        let line_width = line.width();
-        if line_width <= max_width {
-            return vec![line.to_string()];
+    /// Highlight a single line of the file at `path` (content search snippets)
+    /// Starts from a fresh parser state, so constructs spanning lines may be off
+    pub fn highlight_line(path: &Path, text: &str, theme_name: &str) -> Vec<Span<'static>> {
+        let syntax = SYNTAX_SET
+            .find_syntax_for_file(path)
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
+        let theme = THEME_SET
+            .themes
+            .get(theme_name)
+            .unwrap_or_else(|| THEME_SET.themes.get("base16-ocean.dark").unwrap());
+
+        match HighlightLines::new(syntax, theme).highlight_line(text, &SYNTAX_SET) {
+            Ok(ranges) => ranges
+                .iter()
+                .map(|(style, text)| {
+                    let fg_color = Self::syntect_color_to_ratatui(style.foreground);
+                    Span::styled(text.to_string(), Style::default().fg(fg_color))
+                })
+                .collect(),
+            Err(_) => vec![Span::raw(text.to_string())],
         }
+    }
 
-        let mut result = Vec::new();
-        let mut current_line = String::new();
-        let mut current_width = 0;
+    /// Scroll so that `line` of the file (1-based) is at the top
+    pub fn scroll_to_line(&mut self, line: usize) {
+        self.scroll = self
+            .line_numbers
+            .iter()
+            .position(|&number| number >= line)
+            .unwrap_or(0);
+    }
 
-        for word in line.split_whitespace() {
-            let word_width = word.width();
+    /// Line in the file (1-based) at the top of the screen
+    fn top_line(&self) -> usize {
+        self.line_numbers
+            .iter()
+            .skip(self.scroll)
+            .find(|&&number| number > 0)
+            .copied()
+            .unwrap_or(self.scroll + 1)
+    }
 
-            // If this is the first word in the line
-            if current_line.is_empty() {
-                // If word is longer than max_width, we must break it
-                if word_width > max_width {
-                    let mut remaining = word;
-                    while !remaining.is_empty() {
-                        let (chunk, byte_offset) = remaining.unicode_truncate(max_width);
-                        result.push(chunk.to_string());
+    /// Remember the line at the top of the screen as `mark`
+    pub fn set_mark(&mut self, mark: char) {
+        self.marks.insert(mark, self.top_line());
+    }
 
-                        // Safely advance to next char boundary
-                        if byte_offset >= remaining.len() {
-                            break;
-                        }
+    /// Scroll to the line of `mark`, remembering where the jump started as `'`
+    /// Returns false for a mark that isn't set
+    pub fn jump_to_mark(&mut self, mark: char) -> bool {
+        let Some(&line) = self.marks.get(&mark) else {
+            return false;
+        };
+        self.marks.insert('\'', self.top_line());
+        self.go_to_file_line(line);
+        true
+    }
 
-                        // Find the next valid char boundary if byte_offset is not on one
-                        let next_offset = if remaining.is_char_boundary(byte_offset) {
-                            byte_offset
-                        } else {
-                            // Find the next char boundary after byte_offset
-                            (byte_offset..remaining.len())
-                                .find(|&i| remaining.is_char_boundary(i))
-                                .unwrap_or(remaining.len())
-                        };
+    /// Scroll so that `line` of the file (1-based) is at the top
+    fn go_to_file_line(&mut self, line: usize) {
+        if self.line_numbers.is_empty() {
+            // Text without file lines (git output, plugin previews)
+            self.scroll = line
+                .saturating_sub(1)
+                .min(self.content.len().saturating_sub(1));
+        } else {
+            self.scroll_to_line(line);
+        }
+    }
 
-                        remaining = &remaining[next_offset..];
-                    }
-                } else {
-                    current_line.push_str(word);
-                    current_width = word_width;
-                }
-            } else {
-                // Check if adding space + word would exceed max_width
-                let space_width = 1;
-                let new_width = current_width + space_width + word_width;
-
-                if new_width <= max_width {
-                    // Add space and word to current line
-                    current_line.push(' ');
-                    current_line.push_str(word);
-                    current_width = new_width;
-                } else {
-                    // Save current line and start new one
-                    result.push(current_line.clone());
-                    current_line.clear();
-
-                    // If word is longer than max_width, break it
-                    if word_width > max_width {
-                        let mut remaining = word;
-                        while !remaining.is_empty() {
-                            let remaining_width = remaining.width();
-                            if remaining_width <= max_width {
-                                current_line = remaining.to_string();
-                                current_width = remaining_width;
-                                break;
-                            } else {
-                                let (chunk, byte_offset) = remaining.unicode_truncate(max_width);
-                                result.push(chunk.to_string());
-
-                                // Safely advance to next char boundary
-                                if byte_offset >= remaining.len() {
-                                    break;
-                                }
-
-                                // Find the next valid char boundary if byte_offset is not on one
-                                let next_offset = if remaining.is_char_boundary(byte_offset) {
-                                    byte_offset
-                                } else {
-                                    // Find the next char boundary after byte_offset
-                                    (byte_offset..remaining.len())
-                                        .find(|&i| remaining.is_char_boundary(i))
-                                        .unwrap_or(remaining.len())
-                                };
-
-                                remaining = &remaining[next_offset..];
-                            }
-                        }
-                    } else {
-                        current_line.push_str(word);
-                        current_width = word_width;
-                    }
-                }
-            }
+    /// Line number shown next to content line `idx`; None for notices
+    /// (text without file lines, like git output, is numbered from 1)
+    pub fn line_number(&self, idx: usize) -> Option<usize> {
+        match self.line_numbers.get(idx) {
+            Some(0) => None,
+            Some(&number) => Some(number),
+            None => Some(idx + 1),
         }
+    }
 
-        // Don't forget the last line
-        if !current_line.is_empty() {
-            result.push(current_line);
+    /// Convert syntect color to ratatui color
+    fn syntect_color_to_ratatui(color: syntect::highlighting::Color) -> Color {
+        Color::Rgb(color.r, color.g, color.b)
+    }
+
+    /// Byte ranges of content line `idx` shown on one screen row each
+    pub fn rows(&self, idx: usize) -> Vec<Range<usize>> {
+        let line = self.content.get(idx).map_or("", String::as_str);
+        if self.wrap_lines {
+            wrap_ranges(line, self.wrap_width)
+        } else {
+            std::iter::once(0..line.len()).collect()
         }
+    }
 
-        // If nothing was added (e.g., empty line), return empty string
-        if result.is_empty() {
-            result.push(String::new());
+    /// How many of `lines` (content indices) fit into `height` rows, at least one
+    fn lines_in_rows(&self, lines: impl Iterator<Item = usize>, height: usize) -> usize {
+        let mut rows = 0;
+        let mut count = 0;
+        for idx in lines {
+            rows += self.rows(idx).len();
+            if rows > height {
+                break;
+            }
+            count += 1;
         }
+        count.max(1)
+    }
 
-        result
+    /// Scroll so the line the viewer is at (visual cursor, current search match or the
+    /// top of the screen) is at the top, in the middle or at the bottom of `visible_height` rows
+    pub fn place(&mut self, placement: Placement, visible_height: usize) {
+        let idx = if self.visual_mode {
+            self.visual_cursor
+        } else if let Some(&idx) = self.search_results.get(self.current_match) {
+            idx
+        } else {
+            self.scroll
+        };
+        let free_rows = visible_height.saturating_sub(self.rows(idx).len());
+        let rows_above = match placement {
+            Placement::Top => 0,
+            Placement::Center => free_rows / 2,
+            Placement::Bottom => free_rows,
+        };
+        // Lines before `idx` that fit into the rows above it
+        let mut rows = 0;
+        let lines_above = (0..idx)
+            .rev()
+            .take_while(|&line| {
+                rows += self.rows(line).len();
+                rows <= rows_above
+            })
+            .count();
+        self.scroll = (idx - lines_above).min(self.max_scroll(visible_height));
+    }
+
+    /// Largest scroll that still fills `visible_height` rows
+    pub fn max_scroll(&self, visible_height: usize) -> usize {
+        let len = self.content.len();
+        len.saturating_sub(self.lines_in_rows((0..len).rev(), visible_height))
     }
 
     /// Scroll down in file content
     pub fn scroll_down(&mut self, max_visible_lines: usize) {
-        let max_scroll = self.content.len().saturating_sub(max_visible_lines);
-        if self.scroll < max_scroll {
+        if self.scroll < self.max_scroll(max_visible_lines) {
             self.scroll += 1;
         }
     }
@@ -514,28 +1007,35 @@ impl FileViewer {
 
     /// Scroll up by page (visible height)
     pub fn scroll_page_up(&mut self, visible_height: usize) {
-        self.scroll = self.scroll.saturating_sub(visible_height);
+        let lines = self.lines_in_rows((0..self.scroll).rev(), visible_height);
+        self.scroll = self.scroll.saturating_sub(lines);
     }
 
     /// Scroll down by page (visible height)
     pub fn scroll_page_down(&mut self, visible_height: usize, max_visible_lines: usize) {
-        let max_scroll = self.content.len().saturating_sub(max_visible_lines);
-        self.scroll = (self.scroll + visible_height).min(max_scroll);
+        let lines = self.lines_in_rows(self.scroll..self.content.len(), visible_height);
+        self.scroll = (self.scroll + lines).min(self.max_scroll(max_visible_lines));
     }
 
     /// Jump to end of file
     pub fn scroll_to_end(&mut self, visible_height: usize) {
-        self.scroll = self.content.len().saturating_sub(visible_height);
+        self.scroll = self.max_scroll(visible_height);
     }
 
     /// Load custom content (e.g., help text)
     pub fn load_content(&mut self, content: Vec<String>) {
+        self.remember_view_state();
         self.content = content;
+        self.line_kinds.clear();
         self.highlighted_content.clear();
+        self.highlight_job = None;
         self.scroll = 0;
+        self.marks.clear();
         self.current_path = PathBuf::new();
         self.current_size = 0;
         self.current_permissions = 0;
+        self.current_modified = None;
+        self.current_file_id = None;
         self.syntax_name = None;
         self.is_binary = false;
         self.tail_mode = false;
@@ -557,29 +1057,13 @@ impl FileViewer {
         !self.is_binary && !self.current_path.as_os_str().is_empty()
     }
 
-    /// Check if a file is binary by looking for NULL bytes in the first 8KB
+    /// Check if a file is binary from its content (magic numbers, NULL bytes)
     pub fn is_binary_file(path: &Path) -> bool {
-        use std::io::Read;
-
-        let file = match File::open(path) {
-            Ok(f) => f,
-            Err(_) => return false,
-        };
-
-        let mut reader = BufReader::new(file);
-        let mut buffer = [0u8; 8192]; // Check first 8KB
-
-        match reader.read(&mut buffer) {
-            Ok(n) if n > 0 => {
-                // Check for NULL bytes (indicator of binary data)
-                buffer[..n].contains(&0)
-            }
-            _ => false,
-        }
+        mime::detect_file(path).is_some_and(|file_type| !file_type.is_text)
     }
 
     /// Load informational message for binary files
-    fn load_binary_info(&mut self, path: &Path) {
+    fn load_binary_info(&mut self, path: &Path, file_type: FileType) {
         let file_name = path
             .file_name()
             .and_then(|n| n.to_str())
@@ -588,68 +1072,52 @@ impl FileViewer {
         let size_str = format_file_size(self.current_size);
         let perms_str = format_permissions(self.current_permissions);
 
-        // Try to detect file type using file extension
-        let file_type = Self::guess_binary_type(path);
-
+        // Center the heading in the 66 columns of the box
+        let heading = tr("BINARY FILE");
+        let padding = 66usize.saturating_sub(heading.width());
         self.content = vec![
             "".to_string(),
             "╔══════════════════════════════════════════════════════════════════╗".to_string(),
-            "║                         BINARY FILE                              ║".to_string(),
+            format!(
+                "║{}{}{}║",
+                " ".repeat(padding / 2),
+                heading,
+                " ".repeat(padding - padding / 2)
+            ),
             "╚══════════════════════════════════════════════════════════════════╝".to_string(),
             "".to_string(),
-            format!("  File: {}", file_name),
-            format!("  Size: {} ({} bytes)", size_str, self.current_size),
-            format!("  Type: {}", file_type),
-            format!("  Permissions: {}", perms_str),
+            tr_with("  File: {name}", &[("name", &file_name)]),
+            tr_with(
+                "  Size: {size} ({bytes} bytes)",
+                &[("size", &size_str), ("bytes", &self.current_size)],
+            ),
+            tr_with(
+                "  Type: {description} ({mime})",
+                &[
+                    ("description", &file_type.description),
+                    ("mime", &file_type.mime),
+                ],
+            ),
+            tr_with(
+                "  Permissions: {permissions}",
+                &[("permissions", &perms_str)],
+            ),
+            tr_with("  Modified: {date}", &[("date", &self.format_modified())]),
+            tr_with("  Inode: {inode}", &[("inode", &self.format_file_id())]),
             "".to_string(),
-            "  This is a binary file and cannot be displayed as text.".to_string(),
+            tr("  This is a binary file and cannot be displayed as text.").to_string(),
             "".to_string(),
-            "  Available Actions:".to_string(),
-            "    e  -  Open in hex editor".to_string(),
-            "    o  -  Open in file manager".to_string(),
-            "    c  -  Copy path to clipboard".to_string(),
+            tr("  Available Actions:").to_string(),
+            tr("    e  -  Open in hex editor").to_string(),
+            tr("    o  -  Open in file manager").to_string(),
+            tr("    c  -  Copy path to clipboard").to_string(),
             "".to_string(),
-            "  Tip: Configure your preferred hex editor in ~/.config/dtree/config.toml".to_string(),
+            tr("  Tip: Configure your preferred hex editor in ~/.config/dtree/config.toml")
+                .to_string(),
             "".to_string(),
         ];
     }
 
-    /// Guess binary file type based on extension
-    fn guess_binary_type(path: &Path) -> String {
-        let extension = path
-            .extension()
-            .and_then(|e| e.to_str())
-            .unwrap_or("")
-            .to_lowercase();
-
-        match extension.as_str() {
-            // Executables
-            "exe" | "dll" | "so" | "dylib" | "bin" => "Executable / Library".to_string(),
-            // Archives
-            "zip" | "tar" | "gz" | "bz2" | "xz" | "7z" | "rar" => "Archive".to_string(),
-            // Images
-            "png" | "jpg" | "jpeg" | "gif" | "bmp" | "ico" | "webp" => "Image".to_string(),
-            // Video
-            "mp4" | "mkv" | "avi" | "mov" | "wmv" | "flv" | "webm" => "Video".to_string(),
-            // Audio
-            "mp3" | "wav" | "flac" | "ogg" | "m4a" | "aac" => "Audio".to_string(),
-            // Documents
-            "pdf" => "PDF Document".to_string(),
-            "doc" | "docx" => "Word Document".to_string(),
-            "xls" | "xlsx" => "Excel Spreadsheet".to_string(),
-            "ppt" | "pptx" => "PowerPoint Presentation".to_string(),
-            // Database
-            "db" | "sqlite" | "sqlite3" => "Database".to_string(),
-            // Object files
-            "o" | "a" | "lib" => "Object / Library File".to_string(),
-            // ISO images
-            "iso" | "img" => "Disk Image".to_string(),
-            // Font files
-            "ttf" | "otf" | "woff" | "woff2" => "Font File".to_string(),
-            _ => "Binary Data".to_string(),
-        }
-    }
-
     // ===== Search functionality =====
 
     /// Enter search mode
@@ -692,11 +1160,9 @@ impl FileViewer {
             return;
         }
 
-        let query_lower = self.search_query.to_lowercase();
-
         // Search through content lines
         for (line_idx, line) in self.content.iter().enumerate() {
-            if line.to_lowercase().contains(&query_lower) {
+            if !match_ranges(line, &self.search_query).is_empty() {
                 self.search_results.push(line_idx);
             }
         }
@@ -707,6 +1173,14 @@ impl FileViewer {
         }
     }
 
+    /// Search reloaded content again, staying at the current position
+    pub fn refresh_search(&mut self) {
+        let (scroll, current_match) = (self.scroll, self.current_match);
+        self.perform_search();
+        self.scroll = scroll;
+        self.current_match = current_match.min(self.search_results.len().saturating_sub(1));
+    }
+
     /// Go to next search match
     pub fn next_match(&mut self) {
         if self.search_results.is_empty() {
@@ -745,19 +1219,41 @@ impl FileViewer {
     /// Get match info string for display
     pub fn get_match_info(&self) -> String {
         if self.search_results.is_empty() {
-            "No matches".to_string()
+            tr("No matches").to_string()
         } else {
-            format!(
-                "Match {}/{}",
-                self.current_match + 1,
-                self.search_results.len()
+            tr_with(
+                "Match {current}/{count}",
+                &[
+                    ("current", &(self.current_match + 1)),
+                    ("count", &self.search_results.len()),
+                ],
             )
         }
     }
 
+    /// Line in the file the viewer is at (1-based): the visual cursor, the current search
+    /// match or the top of the screen; None at the first line or without a text file
+    pub fn current_line(&self) -> Option<usize> {
+        let idx = if self.visual_mode {
+            self.visual_cursor
+        } else if let Some(&idx) = self.search_results.get(self.current_match) {
+            idx
+        } else {
+            self.scroll
+        };
+        // A notice (tail mode header) stands for the line after it
+        self.line_numbers
+            .iter()
+            .skip(idx)
+            .find(|&&number| number > 0)
+            .copied()
+            .filter(|&line| line > 1)
+    }
+
     /// Check if a line has a match
     pub fn line_has_match(&self, line_idx: usize) -> bool {
-        self.search_results.contains(&line_idx)
+        // Matches are found in line order
+        self.search_results.binary_search(&line_idx).is_ok()
     }
 
     /// Check if a line is the current match
@@ -768,6 +1264,27 @@ impl FileViewer {
         self.search_results.get(self.current_match) == Some(&line_idx)
     }
 
+    /// Minimap of `height` rows with `shown_lines` lines on screen from `scroll`
+    pub fn minimap(&self, height: usize, shown_lines: usize) -> Vec<MinimapRow> {
+        let mut rows = vec![MinimapRow::default(); height];
+        let len = self.content.len();
+        if height == 0 || len == 0 {
+            return rows;
+        }
+        let row_of = |idx: usize| (idx.min(len - 1) * height / len).min(height - 1);
+
+        let last_shown = (self.scroll + shown_lines.max(1) - 1).min(len - 1);
+        for row in &mut rows[row_of(self.scroll)..=row_of(last_shown)] {
+            row.in_view = true;
+        }
+        for (i, &idx) in self.search_results.iter().enumerate() {
+            let row = &mut rows[row_of(idx)];
+            row.has_match = true;
+            row.current_match |= i == self.current_match;
+        }
+        rows
+    }
+
     /// Format file information string
     pub fn format_file_info(&self) -> String {
         if self.current_path.as_os_str().is_empty() {
@@ -815,12 +1332,45 @@ impl FileViewer {
             String::new()
         };
 
+        let modified_info = match self.current_modified {
+            Some(_) => format!(" | {}", self.format_modified()),
+            None => String::new(),
+        };
+        let modified_info = match self.current_file_id {
+            Some(_) => format!("{} | inode {}", modified_info, self.format_file_id()),
+            None => modified_info,
+        };
+
         format!(
-            " {} | {} | {} | {}{}{}",
-            file_name, size_str, lines_info, permissions_str, search_info, visual_info
+            " {} | {} | {} | {}{}{}{}",
+            file_name,
+            size_str,
+            lines_info,
+            permissions_str,
+            modified_info,
+            search_info,
+            visual_info
         )
     }
 
+    /// Inode, device and (for hard-linked files) the link count
+    fn format_file_id(&self) -> String {
+        match self.current_file_id {
+            Some(id) if id.links > 1 => {
+                format!("{} on dev {}, {} links", id.inode, id.device, id.links)
+            }
+            Some(id) => format!("{} on dev {}", id.inode, id.device),
+            None => "-".to_string(),
+        }
+    }
+
+    /// Modification time of the file in the `date_format`
+    fn format_modified(&self) -> String {
+        self.current_modified
+            .map(|time| format::current().date(time, SystemTime::now()))
+            .unwrap_or_default()
+    }
+
     // ===== Visual selection functionality =====
 
     /// Enter visual selection mode
@@ -894,10 +1444,23 @@ impl FileViewer {
             return Ok(());
         }
 
-        let text = self.get_selected_text();
+        // In a git view the commit hashes of the selected lines are copied
+        let text = if self.git_view.is_some() {
+            let mut hashes: Vec<&str> = Vec::new();
+            let (start, end) = self.get_selection_range();
+            for line in self.content.iter().take(end + 1).skip(start) {
+                if let Some(hash) = git::commit_hash(line) {
+                    if !hashes.contains(&hash) {
+                        hashes.push(hash);
+                    }
+                }
+            }
+            hashes.join("\n")
+        } else {
+            self.get_selected_text()
+        };
         if !text.is_empty() {
-            let mut clipboard = arboard::Clipboard::new()?;
-            clipboard.set_text(text)?;
+            clipboard::copy(&text)?;
         }
 
         self.exit_visual_mode();
@@ -910,37 +1473,90 @@ impl FileViewer {
             return;
         }
 
-        let max_scroll = self.content.len().saturating_sub(visible_height);
-
         // If cursor is above visible area, scroll up
         if self.visual_cursor < self.scroll {
             self.scroll = self.visual_cursor;
         }
-        // If cursor is below visible area, scroll down
-        else if self.visual_cursor >= self.scroll + visible_height {
-            self.scroll = self
-                .visual_cursor
-                .saturating_sub(visible_height - 1)
-                .min(max_scroll);
+        // If cursor is below visible area, scroll down (lines may take several rows)
+        else if self.visual_cursor
+            >= self.scroll + self.lines_in_rows(self.scroll..self.content.len(), visible_height)
+        {
+            let lines = self.lines_in_rows((0..=self.visual_cursor).rev(), visible_height);
+            self.scroll = (self.visual_cursor + 1 - lines).min(self.max_scroll(visible_height));
         }
     }
 }
 
-/// Format file size in human-readable format
-pub fn format_file_size(size: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-
-    if size >= GB {
-        format!("{:.2} GB", size as f64 / GB as f64)
-    } else if size >= MB {
-        format!("{:.2} MB", size as f64 / MB as f64)
-    } else if size >= KB {
-        format!("{:.2} KB", size as f64 / KB as f64)
-    } else {
-        format!("{} B", size)
+/// Byte ranges of `line` wrapped at `max_width` columns: after a space where
+/// possible, inside words longer than a row otherwise; the text itself is kept
+pub fn wrap_ranges(line: &str, max_width: usize) -> Vec<Range<usize>> {
+    if max_width == 0 || line.width() <= max_width {
+        return std::iter::once(0..line.len()).collect();
     }
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut width = 0;
+    let mut last_space = None; // Offset after the last space of the row
+    for (offset, c) in line.char_indices() {
+        let char_width = c.width().unwrap_or(0);
+        while width + char_width > max_width && offset > start {
+            let end = match last_space {
+                Some(end) if end > start && end < offset => end,
+                _ => offset,
+            };
+            ranges.push(start..end);
+            start = end;
+            width = line[start..offset].width();
+            last_space = None;
+        }
+        width += char_width;
+        if c.is_whitespace() {
+            last_space = Some(offset + c.len_utf8());
+        }
+    }
+    ranges.push(start..line.len());
+    ranges
+}
+
+/// Byte ranges of every occurrence of `query` in `line`, ignoring case
+/// (the offsets are in `line` itself, also where lowercasing changes lengths)
+pub fn match_ranges(line: &str, query: &str) -> Vec<Range<usize>> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut ranges = Vec::new();
+    let mut from = 0;
+    for (start, _) in line.char_indices() {
+        if start < from {
+            continue;
+        }
+        // Lowercased chars from `start`, each with the offset after its source char
+        let mut lowered = line[start..].char_indices().flat_map(|(offset, c)| {
+            let end = start + offset + c.len_utf8();
+            c.to_lowercase().map(move |lower| (lower, end))
+        });
+        let mut end = start;
+        let matched = query.iter().all(|&wanted| match lowered.next() {
+            Some((lower, after)) if lower == wanted => {
+                end = after;
+                true
+            }
+            _ => false,
+        });
+        if matched {
+            ranges.push(start..end);
+            from = end;
+        }
+    }
+    ranges
+}
+
+/// Format file size in human-readable format (`size_units`, `size_decimals`)
+pub fn format_file_size(size: u64) -> String {
+    format::current().size(size)
 }
 
 /// Format permissions as string (cross-platform)
@@ -990,53 +1606,350 @@ fn format_permission_triplet(triplet: u32) -> String {
 mod tests {
     use super::*;
 
+    fn wrap(line: &str, max_width: usize) -> Vec<&str> {
+        wrap_ranges(line, max_width)
+            .into_iter()
+            .map(|range| &line[range])
+            .collect()
+    }
+
     #[test]
     fn test_wrap_line_with_multibyte_chars() {
         // Test string with multibyte Unicode characters (▶ is 3 bytes)
         let line = "▶ folder ▶ another";
-        let result = FileViewer::wrap_line(line, 10);
+        let result = wrap(line, 10);
 
-        // Should not panic and should produce some output
-        assert!(!result.is_empty());
-        assert!(result[0].len() <= 30); // Rough check - line shouldn't be empty
+        // Should not panic, and the rows put together are the line again
+        assert_eq!(result, ["▶ folder ", "▶ another"]);
     }
 
     #[test]
     fn test_wrap_line_with_long_multibyte_word() {
         // Test with a very long word containing multibyte characters
         let line = "▶▶▶▶▶▶▶▶▶▶▶▶▶▶▶▶▶▶▶▶";
-        let result = FileViewer::wrap_line(line, 5);
+        let result = wrap(line, 5);
 
-        // Should not panic
-        assert!(!result.is_empty());
+        assert_eq!(result.len(), 4);
+        assert!(result.iter().all(|row| row.width() <= 5));
     }
 
     #[test]
     fn test_wrap_line_with_ansi_like_sequences() {
         // Simulate ANSI escape sequences mixed with Unicode
         let line = "[9;7H▶[9;9H.dotnet[9;125H│[10;1H│[10;7H▶[10;9H.fltk";
-        let result = FileViewer::wrap_line(line, 20);
+        let result = wrap(line, 20);
 
         // Should not panic
-        assert!(!result.is_empty());
+        assert_eq!(result.concat(), line);
     }
 
     #[test]
     fn test_wrap_line_normal_text() {
         // Test normal ASCII text to ensure we didn't break existing functionality
         let line = "This is a normal line of text";
-        let result = FileViewer::wrap_line(line, 10);
+        let result = wrap(line, 10);
 
-        assert!(result.len() >= 3); // Should wrap into multiple lines
+        assert_eq!(result, ["This is a ", "normal ", "line of ", "text"]);
     }
 
     #[test]
     fn test_wrap_line_fits_in_width() {
         // Test line that fits within max_width
         let line = "Short";
-        let result = FileViewer::wrap_line(line, 100);
+        let result = wrap(line, 100);
 
         assert_eq!(result.len(), 1);
         assert_eq!(result[0], "Short");
     }
+
+    #[test]
+    fn test_match_ranges_finds_every_occurrence() {
+        let line = "Foo foo FOO";
+        assert_eq!(match_ranges(line, "foo"), [0..3, 4..7, 8..11]);
+        assert_eq!(match_ranges("aaaa", "aa"), [0..2, 2..4]);
+        assert!(match_ranges(line, "").is_empty());
+        // Offsets stay in the line when lowercasing changes the length (İ → i̇)
+        let line = "İx İx";
+        assert_eq!(match_ranges(line, "x"), [2..3, 6..7]);
+    }
+
+    #[test]
+    fn test_current_line_follows_file_lines() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("notes.txt");
+        std::fs::write(&path, "one\ntwo words that wrap\nthree\nfour\n").unwrap();
+
+        let mut viewer = FileViewer::new();
+        viewer
+            .load_file_with_width(&path, Some(10), 100, false, "base16-ocean.dark")
+            .unwrap();
+        // The second line wraps when shown, but stays one line of content
+        assert_eq!(viewer.content.len(), 4);
+        assert_eq!(viewer.rows(1).len(), 2);
+        assert_eq!(viewer.current_line(), None);
+        viewer.scroll = 1;
+        assert_eq!(viewer.current_line(), Some(2));
+        viewer.scroll = 2;
+        assert_eq!(viewer.current_line(), Some(3));
+        viewer.visual_mode = true;
+        viewer.visual_cursor = 3;
+        assert_eq!(viewer.current_line(), Some(4));
+
+        // Search matches refer to lines of the file
+        viewer.visual_mode = false;
+        viewer.search_query = "wrap".to_string();
+        viewer.perform_search();
+        assert_eq!(viewer.search_results, [1]);
+        assert_eq!(viewer.current_line(), Some(2));
+        viewer.clear_search();
+
+        // Tail mode starts further down, after the "showing last" notice
+        viewer.enable_tail_mode();
+        viewer
+            .load_file_with_width(&path, Some(10), 2, false, "base16-ocean.dark")
+            .unwrap();
+        assert_eq!(viewer.line_number(0), None);
+        assert_eq!(viewer.line_number(1), Some(3));
+        viewer.scroll = 1;
+        assert_eq!(viewer.current_line(), Some(3));
+    }
+
+    #[test]
+    fn test_minimap_scales_matches_and_view() {
+        let mut viewer = FileViewer::new();
+        viewer.load_content((0..100).map(|i| format!("line {}", i)).collect());
+        viewer.search_query = "line 9".to_string();
+        viewer.perform_search();
+        viewer.scroll = 50;
+        viewer.current_match = 1;
+
+        let rows = viewer.minimap(10, 20);
+        let in_view: Vec<usize> = (0..10).filter(|&row| rows[row].in_view).collect();
+        assert_eq!(in_view, [5, 6]);
+        // "line 9" and "line 90".."line 99"
+        assert!(rows[0].has_match && !rows[0].current_match);
+        assert!(rows[9].has_match && rows[9].current_match);
+        assert!(!rows[4].has_match);
+    }
+
+    #[test]
+    fn test_place_current_line() {
+        let mut viewer = FileViewer::new();
+        viewer.load_content((0..100).map(|i| format!("line {}", i)).collect());
+        viewer.search_query = "line 50".to_string();
+        viewer.perform_search();
+
+        viewer.place(Placement::Top, 10);
+        assert_eq!(viewer.scroll, 50);
+        viewer.place(Placement::Center, 10);
+        assert_eq!(viewer.scroll, 46);
+        viewer.place(Placement::Bottom, 10);
+        assert_eq!(viewer.scroll, 41);
+
+        // Without a match the top line is placed, as far as the end allows
+        viewer.clear_search();
+        viewer.scroll = 95;
+        viewer.place(Placement::Top, 10);
+        assert_eq!(viewer.scroll, 90);
+    }
+
+    #[test]
+    fn test_marks_survive_reloads_of_the_same_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("long.log");
+        let text: String = (1..=50).map(|i| format!("line {}\n", i)).collect();
+        std::fs::write(&path, text).unwrap();
+        let load = |viewer: &mut FileViewer, path: &Path| {
+            viewer
+                .load_file_with_width(path, Some(80), 100, false, "base16-ocean.dark")
+                .unwrap();
+        };
+
+        let mut viewer = FileViewer::new();
+        load(&mut viewer, &path);
+        viewer.scroll = 30;
+        viewer.set_mark('a');
+        viewer.scroll = 5;
+        assert!(viewer.jump_to_mark('a'));
+        assert_eq!(viewer.scroll, 30);
+        // '' goes back to where the jump started
+        assert!(viewer.jump_to_mark('\''));
+        assert_eq!(viewer.scroll, 5);
+        assert!(!viewer.jump_to_mark('b'));
+
+        // Tail mode shows lines 41-50 after the "showing last" notice
+        viewer.scroll = 45;
+        viewer.set_mark('t');
+        viewer.enable_tail_mode();
+        viewer
+            .load_file_with_width(&path, Some(80), 10, false, "base16-ocean.dark")
+            .unwrap();
+        assert!(viewer.jump_to_mark('t'));
+        assert_eq!(viewer.current_line(), Some(46));
+
+        let other = temp_dir.path().join("other.txt");
+        std::fs::write(&other, "x\n").unwrap();
+        load(&mut viewer, &other);
+        assert!(viewer.marks.is_empty());
+    }
+
+    #[test]
+    fn test_unchanged_files_come_from_the_preview_cache() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("cached.txt");
+        std::fs::write(&path, "old\n").unwrap();
+        let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+        let load = |viewer: &mut FileViewer| {
+            viewer
+                .load_file_with_width(&path, Some(80), 100, false, "base16-ocean.dark")
+                .unwrap();
+        };
+
+        let mut viewer = FileViewer::new();
+        load(&mut viewer);
+        assert_eq!(viewer.content, vec!["old"]);
+
+        // Same size and mtime: the cached lines are shown without reading the file
+        std::fs::write(&path, "new\n").unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(modified).unwrap();
+        load(&mut viewer);
+        assert_eq!(viewer.content, vec!["old"]);
+
+        preview_cache::forget(&path);
+        load(&mut viewer);
+        assert_eq!(viewer.content, vec!["new"]);
+
+        // A changed size is read again
+        std::fs::write(&path, "newer\n").unwrap();
+        file.set_modified(modified).unwrap();
+        load(&mut viewer);
+        assert_eq!(viewer.content, vec!["newer"]);
+    }
+
+    #[test]
+    fn test_files_are_shown_the_way_they_were_left() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let log = temp_dir.path().join("app.log");
+        let notes = temp_dir.path().join("notes.txt");
+        let text: String = (1..=50).map(|i| format!("line {}\n", i)).collect();
+        std::fs::write(&log, &text).unwrap();
+        std::fs::write(&notes, &text).unwrap();
+        let load = |viewer: &mut FileViewer, path: &Path| {
+            viewer
+                .load_file_with_width(path, Some(80), 20, false, "base16-ocean.dark")
+                .unwrap();
+        };
+
+        let mut viewer = FileViewer::new();
+        load(&mut viewer, &log);
+        viewer.enable_tail_mode();
+        load(&mut viewer, &log);
+        viewer.scroll = 7;
+        viewer.toggle_line_numbers();
+
+        // Another file starts from the defaults
+        load(&mut viewer, &notes);
+        assert_eq!(viewer.scroll, 0);
+        assert!(!viewer.tail_mode && !viewer.show_line_numbers);
+        viewer.scroll = 3;
+
+        // The same line is shown even when the file got longer meanwhile
+        std::fs::write(&log, format!("{}line 51\n", text)).unwrap();
+        load(&mut viewer, &log);
+        assert!(viewer.tail_mode && viewer.show_line_numbers);
+        assert_eq!(viewer.current_line(), Some(37));
+        load(&mut viewer, &notes);
+        assert_eq!(viewer.scroll, 3);
+    }
+
+    #[test]
+    fn test_scrolling_counts_wrapped_rows() {
+        let mut viewer = FileViewer::new();
+        viewer.wrap_width = 10;
+        viewer.load_content(vec![
+            "short".to_string(),
+            "a long line that wraps".to_string(),
+            "end".to_string(),
+        ]);
+        assert_eq!(viewer.max_scroll(4), 1);
+        viewer.scroll_to_end(4);
+        assert_eq!(viewer.scroll, 1);
+        viewer.toggle_wrap();
+        assert_eq!(viewer.max_scroll(4), 0);
+    }
+
+    #[test]
+    fn test_highlighting_in_background_and_cached() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("long.rs");
+        let rows = highlight_cache::FIRST_ROWS + 50;
+        std::fs::write(&path, "fn main() {}\n".repeat(rows)).unwrap();
+        let load = |viewer: &mut FileViewer| {
+            viewer
+                .load_file_with_width(&path, Some(80), 1000, true, "base16-ocean.dark")
+                .unwrap();
+        };
+        let is_highlighted = |line: &Line| line.spans.iter().any(|span| span.style.fg.is_some());
+
+        let mut viewer = FileViewer::new();
+        load(&mut viewer);
+        assert_eq!(viewer.highlighted_content.len(), rows);
+        assert!(is_highlighted(&viewer.highlighted_content[0]));
+        assert!(!is_highlighted(&viewer.highlighted_content[rows - 1]));
+        assert!(viewer.finish_loading());
+        assert!(is_highlighted(&viewer.highlighted_content[rows - 1]));
+
+        // Loading it again takes every row from the cache
+        load(&mut viewer);
+        assert!(viewer.highlight_job.is_none());
+        assert!(is_highlighted(&viewer.highlighted_content[rows - 1]));
+
+        // Scrolled down, the rows on screen are highlighted first
+        viewer.scroll = rows - 10;
+        viewer
+            .load_file_with_width(&path, Some(80), 1000, true, "InspiredGitHub")
+            .unwrap();
+        assert_eq!(viewer.highlighted_content.len(), rows);
+        assert!(is_highlighted(&viewer.highlighted_content[rows - 10]));
+        assert!(!is_highlighted(&viewer.highlighted_content[0]));
+        assert!(viewer.finish_loading());
+        assert!(is_highlighted(&viewer.highlighted_content[0]));
+    }
+
+    #[test]
+    fn test_preview_limits() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("big.log");
+        std::fs::write(&path, "x".repeat(2048)).unwrap();
+        let load = |viewer: &mut FileViewer, path: &Path| {
+            viewer
+                .load_file_with_width(path, Some(80), 100, false, "base16-ocean.dark")
+                .unwrap();
+        };
+
+        let mut viewer = FileViewer::new();
+        viewer.limits.max_size = 1024;
+        load(&mut viewer, &path);
+        assert!(viewer.content[0].starts_with("[Too large to preview"));
+        assert_eq!(viewer.current_size, 2048);
+
+        viewer.limits.max_size = 0;
+        viewer.limits.skip = vec![temp_dir.path().to_path_buf()];
+        load(&mut viewer, &path);
+        assert_eq!(viewer.content, ["[Preview skipped (preview_skip)]"]);
+
+        // A FIFO without a writer would block a read forever
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            let fifo = temp_dir.path().join("pipe");
+            let c_path = std::ffi::CString::new(fifo.as_os_str().as_bytes()).unwrap();
+            assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+            viewer.limits.skip.clear();
+            load(&mut viewer, &fifo);
+            assert_eq!(viewer.content, ["[Not a regular file]"]);
+            assert!(!FileViewer::is_binary_file(&fifo));
+        }
+    }
 }
@@ -0,0 +1,121 @@
+use crate::git::IgnoredPaths;
+use crate::quick_filter::QuickFilter;
+use crate::vfs::VfsProvider;
+use anyhow::Result;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Everything that keeps an entry out of the tree: hidden entries, entries git
+/// ignores (with `gitignore = "hide"`) and the quick or saved filter
+/// Tree loading, the flat list and every search go through the same set, so the
+/// results panel never lists an entry the tree would hide
+#[derive(Debug, Clone, Default)]
+pub struct FilterSet {
+    pub show_hidden: bool,
+    pub quick_filter: QuickFilter,
+    pub ignored: Option<Arc<IgnoredPaths>>, // Only while ignored entries are hidden
+}
+
+impl FilterSet {
+    /// No filter, hidden entries left out unless `show_hidden`
+    pub fn new(show_hidden: bool) -> Self {
+        Self {
+            show_hidden,
+            ..Self::default()
+        }
+    }
+
+    /// Hidden entry left out (the tree doesn't even load these)
+    pub fn hides(&self, vfs: &dyn VfsProvider, path: &Path) -> bool {
+        !self.show_hidden && vfs.is_hidden(path)
+    }
+
+    /// Whether a loaded entry passes the quick filter and the ignore rules in the tree
+    /// shown from `root`
+    pub fn shows(&self, path: &Path, is_dir: bool, root: &Path) -> bool {
+        self.quick_filter.matches(path, is_dir, root)
+            && !self
+                .ignored
+                .as_ref()
+                .is_some_and(|ignored| ignored.is_ignored(path))
+    }
+
+    /// Whether an entry found on disk under `root` would be in the tree; a directory
+    /// that isn't hides everything in it. The root itself always is
+    pub fn allows(&self, vfs: &dyn VfsProvider, path: &Path, is_dir: bool, root: &Path) -> bool {
+        path == root || (!self.hides(vfs, path) && self.shows(path, is_dir, root))
+    }
+}
+
+/// Directories searches, scans and directory sizes don't go into (`[search] exclude`):
+/// globs like `node_modules` or `*.egg-info` matched against a directory's name.
+/// The directory itself is still found, only what's in it is left out
+#[derive(Debug, Clone, Default)]
+pub struct Excludes {
+    globs: GlobSet,
+}
+
+impl Excludes {
+    pub fn new(patterns: &[String]) -> Result<Self> {
+        let mut globs = GlobSetBuilder::new();
+        for pattern in patterns {
+            globs.add(
+                Glob::new(pattern)
+                    .map_err(|e| anyhow::anyhow!("search exclude '{}': {}", pattern, e))?,
+            );
+        }
+        Ok(Self {
+            globs: globs.build()?,
+        })
+    }
+
+    /// Whether a walk stops at the directory `path` instead of going into it
+    /// (the root it starts from is always gone into)
+    pub fn skips_contents(&self, path: &Path, root: &Path) -> bool {
+        path != root
+            && path
+                .file_name()
+                .is_some_and(|name| self.globs.is_match(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs;
+
+    #[test]
+    fn test_allows() {
+        let vfs = vfs::local();
+        let root = Path::new("/work/.project");
+        let filters = FilterSet {
+            quick_filter: QuickFilter::Code,
+            ..FilterSet::new(false)
+        };
+        // A hidden root is still searched
+        assert!(filters.allows(vfs.as_ref(), root, true, root));
+        assert!(filters.allows(vfs.as_ref(), &root.join("src"), true, root));
+        assert!(filters.allows(vfs.as_ref(), &root.join("src/main.rs"), false, root));
+        assert!(!filters.allows(vfs.as_ref(), &root.join("logo.png"), false, root));
+        assert!(!filters.allows(vfs.as_ref(), &root.join(".git"), true, root));
+
+        let filters = FilterSet::new(true);
+        assert!(filters.allows(vfs.as_ref(), &root.join(".git"), true, root));
+        assert!(filters.allows(vfs.as_ref(), &root.join("logo.png"), false, root));
+    }
+
+    #[test]
+    fn test_excludes() {
+        let root = Path::new("/work/node_modules");
+        let patterns = ["node_modules".to_string(), "*.egg-info".to_string()];
+        let excludes = Excludes::new(&patterns).unwrap();
+        // Searching an excluded directory itself still works
+        assert!(!excludes.skips_contents(root, root));
+        assert!(excludes.skips_contents(&root.join("left-pad/node_modules"), root));
+        assert!(excludes.skips_contents(&root.join("dtree.egg-info"), root));
+        assert!(!excludes.skips_contents(&root.join("left-pad/lib"), root));
+        assert!(!Excludes::default().skips_contents(&root.join("node_modules"), root));
+        assert!(Excludes::new(&["[".to_string()]).is_err());
+    }
+}
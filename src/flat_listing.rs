@@ -0,0 +1,213 @@
+use crate::tree_node::{Tree, TreeNode};
+use crate::vfs::Vfs;
+use crate::wakeup;
+use crossbeam_channel::Receiver;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::SystemTime;
+
+/// Files listed at most; a larger tree is cut off (the listing is meant for modest file counts)
+pub const MAX_FILES: usize = 20_000;
+
+/// Order of the flat listing (`#` cycles)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FlatSort {
+    #[default]
+    Name,
+    /// Largest first
+    Size,
+    /// Newest first
+    Modified,
+}
+
+impl FlatSort {
+    pub fn next(self) -> Self {
+        match self {
+            FlatSort::Name => FlatSort::Size,
+            FlatSort::Size => FlatSort::Modified,
+            FlatSort::Modified => FlatSort::Name,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FlatSort::Name => "name",
+            FlatSort::Size => "size",
+            FlatSort::Modified => "modified",
+        }
+    }
+}
+
+/// A flat listing being scanned in the background, shown once done
+pub struct PendingFlatListing {
+    pub root: PathBuf,
+    receiver: Receiver<FlatListing>,
+}
+
+impl PendingFlatListing {
+    /// Start `FlatListing::scan` of `root` on a worker
+    /// The tree to go back to is filled in when the listing is shown
+    pub fn start(
+        vfs: Vfs,
+        root: &Path,
+        show_hidden: bool,
+        follow_symlinks: bool,
+        one_file_system: bool,
+    ) -> Self {
+        let (tx, rx) = wakeup::unbounded();
+        let worker_root = root.to_path_buf();
+        thread::spawn(move || {
+            let saved = Tree::new(TreeNode::new_virtual(worker_root.clone(), 0, true));
+            let _ = tx.send(FlatListing::scan(
+                vfs,
+                &worker_root,
+                saved,
+                show_hidden,
+                follow_symlinks,
+                one_file_system,
+            ));
+        });
+        Self {
+            root: root.to_path_buf(),
+            receiver: rx,
+        }
+    }
+
+    /// The listing, once the worker is done
+    pub fn try_finish(&self) -> Option<FlatListing> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FlatEntry {
+    path: PathBuf,
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+/// All files under a root as one list (like `fd --type f`), shown in place of the tree
+pub struct FlatListing {
+    /// Tree to go back to when the listing is closed
+    pub saved_tree: Tree,
+    pub sort: FlatSort,
+    /// The root holds more than MAX_FILES files - the rest are left out
+    pub truncated: bool,
+    entries: Vec<FlatEntry>,
+    vfs: Vfs, // Where the files are read from
+}
+
+impl FlatListing {
+    /// Collect the files under `root`, read from `vfs`
+    /// Hidden files and symlinks follow the tree settings; symlinked directories are
+    /// not descended into, so links can't loop
+    pub fn scan(
+        vfs: Vfs,
+        root: &Path,
+        saved_tree: Tree,
+        show_hidden: bool,
+        follow_symlinks: bool,
+        one_file_system: bool,
+    ) -> Self {
+        let _span = tracing::debug_span!("flat_listing", root = %root.display()).entered();
+        let root_device = vfs
+            .metadata(root)
+            .ok()
+            .and_then(|metadata| metadata.device());
+        let mut entries = Vec::new();
+        let mut truncated = false;
+        let mut dirs = vec![root.to_path_buf()];
+
+        'scan: while let Some(dir) = dirs.pop() {
+            let Ok(read_dir) = vfs.read_dir(&dir) else {
+                continue;
+            };
+            for path in read_dir.into_iter().flatten() {
+                if !show_hidden && vfs.is_hidden(&path) {
+                    continue;
+                }
+                let Ok(link_metadata) = vfs.symlink_metadata(&path) else {
+                    continue;
+                };
+                if link_metadata.is_symlink && !follow_symlinks {
+                    continue;
+                }
+                if link_metadata.is_dir {
+                    if !one_file_system || link_metadata.device() == root_device {
+                        dirs.push(path);
+                    }
+                    continue;
+                }
+
+                // Size and time of the target for symlinks (broken links are skipped)
+                let Ok(metadata) = vfs.metadata(&path) else {
+                    continue;
+                };
+                if metadata.is_dir {
+                    continue;
+                }
+                if entries.len() == MAX_FILES {
+                    truncated = true;
+                    break 'scan;
+                }
+                entries.push(FlatEntry {
+                    path,
+                    size: metadata.len,
+                    modified: metadata.modified,
+                });
+            }
+        }
+
+        let mut listing = Self {
+            saved_tree,
+            sort: FlatSort::Name,
+            truncated,
+            entries,
+            vfs,
+        };
+        listing.sort_entries();
+        listing
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn set_sort(&mut self, sort: FlatSort) {
+        self.sort = sort;
+        self.sort_entries();
+    }
+
+    fn sort_entries(&mut self) {
+        match self.sort {
+            FlatSort::Name => self.entries.sort_by(|a, b| a.path.cmp(&b.path)),
+            FlatSort::Size => self
+                .entries
+                .sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path))),
+            FlatSort::Modified => self.entries.sort_by(|a, b| {
+                b.modified
+                    .cmp(&a.modified)
+                    .then_with(|| a.path.cmp(&b.path))
+            }),
+        }
+    }
+
+    /// Virtual tree whose root's children are the files, named by their path relative to the root
+    pub fn build_tree(&self, root_path: &Path) -> Tree {
+        let mut tree = Tree::new(TreeNode::new_virtual(root_path.to_path_buf(), 0, true));
+        for entry in &self.entries {
+            let mut node = TreeNode::new_virtual(entry.path.clone(), 1, false);
+            node.vfs = Arc::clone(&self.vfs);
+            if let Ok(relative) = entry.path.strip_prefix(root_path) {
+                node.name = relative.display().to_string();
+            }
+            tree.add_child(tree.root(), node);
+        }
+        tree
+    }
+}
@@ -0,0 +1,269 @@
+use once_cell::sync::OnceCell;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::AppearanceConfig;
+use crate::i18n::{tr, tr_with};
+
+/// How sizes and dates are shown, set once at startup from the config
+static FORMATTING: OnceCell<Formatting> = OnceCell::new();
+
+/// How modification times are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DateFormat {
+    /// `2026-10-16 14:03` (local time)
+    #[default]
+    Iso,
+    /// `5 min ago`, `3 d ago`
+    Relative,
+    /// The locale's date and time (LC_TIME; ISO on Windows)
+    Locale,
+}
+
+impl DateFormat {
+    /// Parse the `date_format` config value (unknown values fall back to ISO)
+    pub fn from_config(value: &str) -> Self {
+        match value.to_ascii_lowercase().as_str() {
+            "relative" => DateFormat::Relative,
+            "locale" => DateFormat::Locale,
+            _ => DateFormat::Iso,
+        }
+    }
+}
+
+/// Size units, decimals and date format for the size column, the file info line
+/// and the binary file details
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Formatting {
+    /// Powers of 1000 (kB, MB) instead of 1024
+    pub si_units: bool,
+    /// Decimals of sizes; None keeps 1 in the size column and 2 elsewhere
+    pub decimals: Option<usize>,
+    pub dates: DateFormat,
+}
+
+impl Formatting {
+    pub fn from_config(appearance: &AppearanceConfig) -> Self {
+        Self {
+            si_units: appearance.size_units.eq_ignore_ascii_case("si"),
+            decimals: appearance.size_decimals,
+            dates: DateFormat::from_config(&appearance.date_format),
+        }
+    }
+
+    /// Unit `size` is shown in: its exponent and the size in that unit
+    fn scale(&self, size: u64) -> (usize, f64) {
+        let base = if self.si_units { 1000.0 } else { 1024.0 };
+        let mut value = size as f64;
+        let mut exponent = 0;
+        while value >= base && exponent < 4 {
+            value /= base;
+            exponent += 1;
+        }
+        (exponent, value)
+    }
+
+    /// Compact size for the size column: `512B`, `1.5K`, `>2.0G` (partial)
+    pub fn short_size(&self, size: u64, is_partial: bool) -> String {
+        const UNITS: [&str; 5] = ["B", "K", "M", "G", "T"];
+        let prefix = if is_partial { ">" } else { "" };
+        match self.scale(size) {
+            (0, _) => format!("{}{}B", prefix, size),
+            (exponent, value) => format!(
+                "{}{:.*}{}",
+                prefix,
+                self.decimals.unwrap_or(1),
+                value,
+                UNITS[exponent]
+            ),
+        }
+    }
+
+    /// Size for the file info line: `512 B`, `1.50 KB` (`1.50 kB` with SI units)
+    pub fn size(&self, size: u64) -> String {
+        let units = if self.si_units {
+            ["B", "kB", "MB", "GB", "TB"]
+        } else {
+            ["B", "KB", "MB", "GB", "TB"]
+        };
+        match self.scale(size) {
+            (0, _) => format!("{} B", size),
+            (exponent, value) => format!(
+                "{:.*} {}",
+                self.decimals.unwrap_or(2),
+                value,
+                units[exponent]
+            ),
+        }
+    }
+
+    /// Modification time `time` as of `now`
+    pub fn date(&self, time: SystemTime, now: SystemTime) -> String {
+        let secs = match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs() as i64,
+            Err(before) => -(before.duration().as_secs() as i64),
+        };
+        match self.dates {
+            DateFormat::Relative => match now.duration_since(time) {
+                Ok(age) => relative(age.as_secs()),
+                // Clock skew or a file from the future
+                Err(_) => iso_date(secs),
+            },
+            DateFormat::Locale => locale_date(secs).unwrap_or_else(|| iso_date(secs)),
+            DateFormat::Iso => iso_date(secs),
+        }
+    }
+}
+
+/// Use `formatting` from now on (once, at startup)
+pub fn init(formatting: Formatting) {
+    #[cfg(unix)]
+    // SAFETY: called before any other thread formats dates; the string is NUL-terminated
+    unsafe {
+        libc::setlocale(libc::LC_TIME, c"".as_ptr());
+    }
+    let _ = FORMATTING.set(formatting);
+}
+
+/// The formatting set by `init` (defaults without it)
+pub fn current() -> Formatting {
+    FORMATTING.get().copied().unwrap_or_default()
+}
+
+/// Age in the largest whole unit: `just now`, `5 min ago`, `3 d ago`
+fn relative(age: u64) -> String {
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+    const MONTH: u64 = 30 * DAY;
+    const YEAR: u64 = 365 * DAY;
+
+    let (text, unit) = match age {
+        age if age < MINUTE => return tr("just now").to_string(),
+        age if age < HOUR => ("{count} min ago", MINUTE),
+        age if age < DAY => ("{count} h ago", HOUR),
+        age if age < MONTH => ("{count} d ago", DAY),
+        age if age < YEAR => ("{count} mo ago", MONTH),
+        _ => ("{count} y ago", YEAR),
+    };
+    tr_with(text, &[("count", &(age / unit))])
+}
+
+/// `YYYY-MM-DD HH:MM` in local time (UTC on Windows)
+fn iso_date(secs: i64) -> String {
+    let local = secs + utc_offset(secs);
+    let (year, month, day) = civil_from_days(local.div_euclid(86400));
+    let minutes = local.rem_euclid(86400) / 60;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        minutes / 60,
+        minutes % 60
+    )
+}
+
+/// Year, month and day of a day count since 1970-01-01 (proleptic Gregorian)
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(unix)]
+fn local_tm(secs: i64) -> Option<libc::tm> {
+    let time = secs as libc::time_t;
+    // SAFETY: tm is plain data that localtime_r fills in
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    // SAFETY: both pointers are valid for the call
+    let result = unsafe { libc::localtime_r(&time, &mut tm) };
+    (!result.is_null()).then_some(tm)
+}
+
+/// Seconds the local time zone is ahead of UTC at `secs`
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)] // c_long is 32 bits on 32-bit targets
+fn utc_offset(secs: i64) -> i64 {
+    local_tm(secs).map_or(0, |tm| tm.tm_gmtoff as i64)
+}
+
+#[cfg(windows)]
+fn utc_offset(_secs: i64) -> i64 {
+    0
+}
+
+/// The locale's date and time (`%x %X`)
+#[cfg(unix)]
+fn locale_date(secs: i64) -> Option<String> {
+    let tm = local_tm(secs)?;
+    let mut buffer = [0u8; 64];
+    // SAFETY: strftime writes at most buffer.len() bytes; the format is NUL-terminated
+    let len = unsafe {
+        libc::strftime(
+            buffer.as_mut_ptr() as *mut libc::c_char,
+            buffer.len(),
+            c"%x %X".as_ptr(),
+            &tm,
+        )
+    };
+    (len > 0).then(|| String::from_utf8_lossy(&buffer[..len]).into_owned())
+}
+
+#[cfg(windows)]
+fn locale_date(_secs: i64) -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_sizes_in_binary_and_si_units() {
+        let binary = Formatting::default();
+        assert_eq!(binary.short_size(512, false), "512B");
+        assert_eq!(binary.short_size(1536, true), ">1.5K");
+        assert_eq!(binary.size(1536), "1.50 KB");
+        assert_eq!(binary.size(3 * 1024 * 1024 * 1024), "3.00 GB");
+
+        let si = Formatting {
+            si_units: true,
+            decimals: Some(0),
+            ..Formatting::default()
+        };
+        assert_eq!(si.short_size(1536, false), "2K");
+        assert_eq!(si.size(1_500_000), "2 MB");
+        assert_eq!(si.size(999), "999 B");
+    }
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(20_742), (2026, 10, 16));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+
+        let relative = Formatting {
+            dates: DateFormat::Relative,
+            ..Formatting::default()
+        };
+        let now = UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        let ago = |secs| relative.date(now - Duration::from_secs(secs), now);
+        assert_eq!(ago(10), "just now");
+        assert_eq!(ago(300), "5 min ago");
+        assert_eq!(ago(3 * 86400 + 5), "3 d ago");
+        assert_eq!(ago(800 * 86400), "2 y ago");
+    }
+}
@@ -0,0 +1,310 @@
+use crate::tree_node::{NodeId, Tree};
+use crate::vfs::VfsProvider;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use std::path::{Path, PathBuf};
+
+/// Matches shown in the list (all of them are kept for narrowing the next query)
+pub const MAX_SHOWN: usize = 200;
+
+/// Bytes of a file read for its preview
+const PREVIEW_BYTES: usize = 16 * 1024;
+
+/// Lines of a preview
+const PREVIEW_LINES: usize = 100;
+
+/// A loaded node the overlay can jump to
+#[derive(Debug, Clone)]
+struct Candidate {
+    id: NodeId,
+    path: PathBuf,
+    relative: String, // Path below the root, what the query is matched against
+    is_dir: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Match {
+    candidate: usize,
+    score: i64,
+    indices: Vec<usize>, // Matched characters of the relative path (shown matches only)
+}
+
+/// A row of the match list
+pub struct JumpItem<'a> {
+    pub relative: &'a str,
+    pub is_dir: bool,
+    pub indices: &'a [usize],
+}
+
+/// Fuzzy jump overlay (`F`): ranks every node loaded in the tree by name and path as
+/// the query is typed, with a preview of the selected one; nothing is read from disk
+/// but that preview
+#[derive(Debug, Default)]
+pub struct FuzzyJump {
+    pub is_open: bool,
+    pub input: String,
+    candidates: Vec<Candidate>,
+    matches: Vec<Match>,
+    matched_query: String, // Query `matches` were ranked for
+    pub selected: usize,
+    preview: Option<(PathBuf, Vec<String>)>,
+}
+
+impl FuzzyJump {
+    /// Open the overlay over the nodes loaded below the root, collapsed ones included
+    pub fn open(&mut self, tree: &Tree) {
+        let root = tree.root_node();
+        self.candidates.clear();
+        let mut stack: Vec<NodeId> = root.children.iter().rev().copied().collect();
+        while let Some(id) = stack.pop() {
+            let node = &tree[id];
+            let relative = node
+                .path
+                .strip_prefix(&root.path)
+                .unwrap_or(&node.path)
+                .display()
+                .to_string();
+            self.candidates.push(Candidate {
+                id,
+                path: node.path.clone(),
+                relative,
+                is_dir: node.is_dir,
+            });
+            stack.extend(node.children.iter().rev());
+        }
+        self.input.clear();
+        self.is_open = true;
+        self.rank();
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+        self.input.clear();
+        self.candidates = Vec::new();
+        self.matches = Vec::new();
+        self.matched_query.clear();
+        self.selected = 0;
+        self.preview = None;
+    }
+
+    pub fn add_char(&mut self, c: char) {
+        self.input.push(c);
+        self.rank();
+    }
+
+    pub fn backspace(&mut self) {
+        self.input.pop();
+        self.rank();
+    }
+
+    pub fn move_down(&mut self) {
+        if self.selected + 1 < self.shown() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Number of matches for the query, shown or not
+    pub fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    pub fn candidate_count(&self) -> usize {
+        self.candidates.len()
+    }
+
+    /// Best matches first
+    pub fn items(&self) -> impl Iterator<Item = JumpItem<'_>> {
+        self.matches.iter().take(MAX_SHOWN).map(|m| {
+            let candidate = &self.candidates[m.candidate];
+            JumpItem {
+                relative: &candidate.relative,
+                is_dir: candidate.is_dir,
+                indices: &m.indices,
+            }
+        })
+    }
+
+    pub fn get_selected(&self) -> Option<&Path> {
+        let m = self.matches.get(self.selected)?;
+        Some(&self.candidates[m.candidate].path)
+    }
+
+    /// Lines previewing the selected node, once `update_preview` has read them
+    pub fn preview(&self) -> &[String] {
+        match (&self.preview, self.get_selected()) {
+            (Some((path, lines)), Some(selected)) if path == selected => lines,
+            _ => &[],
+        }
+    }
+
+    /// Read the preview of the selected node if it changed: a file's first lines,
+    /// or the loaded entries of a directory
+    pub fn update_preview(&mut self, tree: &Tree, vfs: &dyn VfsProvider) {
+        let Some(m) = self.matches.get(self.selected) else {
+            self.preview = None;
+            return;
+        };
+        let candidate = &self.candidates[m.candidate];
+        if self
+            .preview
+            .as_ref()
+            .is_some_and(|(path, _)| *path == candidate.path)
+        {
+            return;
+        }
+
+        let lines = if candidate.is_dir {
+            match tree
+                .get(candidate.id)
+                .filter(|node| node.path == candidate.path)
+            {
+                Some(node) if node.children.is_empty() => vec!["(nothing loaded)".to_string()],
+                Some(node) => node
+                    .children
+                    .iter()
+                    .take(PREVIEW_LINES)
+                    .map(|&child| {
+                        let child = &tree[child];
+                        if child.is_dir {
+                            format!("{}/", child.name)
+                        } else {
+                            child.name.clone()
+                        }
+                    })
+                    .collect(),
+                None => Vec::new(),
+            }
+        } else {
+            match vfs.read_head(&candidate.path, PREVIEW_BYTES) {
+                Ok(bytes) if bytes.contains(&0) => vec!["(binary file)".to_string()],
+                Ok(bytes) => String::from_utf8_lossy(&bytes)
+                    .lines()
+                    .take(PREVIEW_LINES)
+                    .map(|line| line.replace('\t', "    "))
+                    .collect(),
+                Err(e) => vec![format!("(cannot read: {})", e)],
+            }
+        };
+        self.preview = Some((candidate.path.clone(), lines));
+    }
+
+    /// Rank the candidates for the input; a query that only grew narrows the last matches
+    fn rank(&mut self) {
+        self.selected = 0;
+        let query = self.input.trim();
+        if query.is_empty() {
+            // Everything in tree order
+            self.matches = (0..self.candidates.len())
+                .map(|candidate| Match {
+                    candidate,
+                    score: 0,
+                    indices: Vec::new(),
+                })
+                .collect();
+            self.matched_query.clear();
+            return;
+        }
+
+        let matcher = SkimMatcherV2::default();
+        let narrowing = !self.matched_query.is_empty() && query.starts_with(&self.matched_query);
+        let pool: Vec<usize> = if narrowing {
+            self.matches.iter().map(|m| m.candidate).collect()
+        } else {
+            (0..self.candidates.len()).collect()
+        };
+        let candidates = &self.candidates;
+        let mut matches: Vec<Match> = pool
+            .into_iter()
+            .filter_map(|candidate| {
+                let score = matcher.fuzzy_match(&candidates[candidate].relative, query)?;
+                Some(Match {
+                    candidate,
+                    score,
+                    indices: Vec::new(),
+                })
+            })
+            .collect();
+        // Best score first, then the shorter path
+        matches.sort_by(|a, b| {
+            b.score.cmp(&a.score).then_with(|| {
+                candidates[a.candidate]
+                    .relative
+                    .len()
+                    .cmp(&candidates[b.candidate].relative.len())
+            })
+        });
+        for m in matches.iter_mut().take(MAX_SHOWN) {
+            if let Some((_, indices)) =
+                matcher.fuzzy_indices(&candidates[m.candidate].relative, query)
+            {
+                m.indices = indices;
+            }
+        }
+        self.matches = matches;
+        self.matched_query = query.to_string();
+    }
+
+    fn shown(&self) -> usize {
+        self.matches.len().min(MAX_SHOWN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::navigation::Navigation;
+    use crate::vfs;
+    use std::fs;
+
+    #[test]
+    fn test_ranks_loaded_nodes_and_previews() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("project");
+        fs::create_dir_all(root.join("src/ui")).unwrap();
+        fs::write(root.join("src/ui/render.rs"), "fn render() {}\n").unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(root.join("README.md"), "").unwrap();
+
+        let mut nav = Navigation::builder(&root).show_files(true).build().unwrap();
+        // Loaded, then collapsed again: still a candidate
+        nav.toggle_node(&root.join("src"), true).unwrap();
+        nav.toggle_node(&root.join("src"), true).unwrap();
+
+        let mut jump = FuzzyJump::default();
+        jump.open(&nav.tree);
+        assert_eq!(jump.candidate_count(), 4); // src/ui isn't loaded yet
+        assert_eq!(jump.match_count(), 4);
+
+        for c in "main".chars() {
+            jump.add_char(c);
+        }
+        assert_eq!(
+            jump.get_selected(),
+            Some(root.join("src/main.rs").as_path())
+        );
+        let item = jump.items().next().unwrap();
+        assert_eq!(
+            item.relative,
+            format!("src{}main.rs", std::path::MAIN_SEPARATOR)
+        );
+        assert_eq!(item.indices, [4, 5, 6, 7]);
+
+        jump.update_preview(&nav.tree, vfs::local().as_ref());
+        assert_eq!(jump.preview(), ["fn main() {}"]);
+
+        // Narrowed to nothing, then widened back
+        jump.add_char('x');
+        assert_eq!(jump.match_count(), 0);
+        assert_eq!(jump.get_selected(), None);
+        jump.backspace();
+        assert_eq!(jump.match_count(), 1);
+
+        jump.close();
+        assert!(!jump.is_open);
+        assert_eq!(jump.candidate_count(), 0);
+    }
+}
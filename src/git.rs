@@ -0,0 +1,427 @@
+use crate::wakeup;
+use anyhow::{bail, Context, Result};
+use crossbeam_channel::{bounded, Receiver, TryRecvError};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+
+/// Git output shown in the viewer in place of the preview (cycled with `;`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitView {
+    /// `git log --oneline` of the entry
+    Log,
+    /// `git blame` of the file
+    Blame,
+}
+
+impl GitView {
+    /// Next view when cycling; None goes back to the preview
+    /// Directories have no blame, so they only cycle through the log
+    pub fn next(view: Option<GitView>, is_dir: bool) -> Option<GitView> {
+        match view {
+            None => Some(GitView::Log),
+            Some(GitView::Log) if !is_dir => Some(GitView::Blame),
+            Some(_) => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GitView::Log => "git log",
+            GitView::Blame => "git blame",
+        }
+    }
+
+    /// Run git for `path` and return its output
+    /// Fails outside a repository, for untracked entries and when git isn't installed
+    pub fn output(self, path: &Path) -> Result<String> {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        // Run from the entry's directory so the right repository is found
+        let (dir, target) = match path.parent() {
+            Some(parent) if !path.is_dir() => (parent, name.as_str()),
+            _ => (path, "."),
+        };
+
+        let mut cmd = Command::new("git");
+        cmd.current_dir(dir);
+        match self {
+            GitView::Log if path.is_dir() => cmd.args(["log", "--oneline", "--", target]),
+            GitView::Log => cmd.args(["log", "--oneline", "--follow", "--", target]),
+            GitView::Blame if path.is_dir() => bail!("git blame needs a file"),
+            GitView::Blame => cmd.args(["blame", "--date=short", "--", target]),
+        };
+
+        let output = cmd
+            .stdin(Stdio::null())
+            .output()
+            .context("Cannot run git")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let message = stderr.lines().next().unwrap_or("git failed");
+            bail!("{}", message.trim_start_matches("fatal: "));
+        }
+
+        let text = String::from_utf8_lossy(&output.stdout).to_string();
+        if text.trim().is_empty() {
+            bail!("No git history for {}", name);
+        }
+        Ok(text)
+    }
+}
+
+/// Top level of the repository containing `path` (the directory holding `.git`)
+pub fn find_top_level(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Checked-out branch, or the short commit hash when detached
+/// Reads `.git/HEAD` directly (worktrees point to their git dir with a `.git` file)
+fn read_branch(top_level: &Path) -> Option<String> {
+    let dot_git = top_level.join(".git");
+    let git_dir = if dot_git.is_file() {
+        let text = fs::read_to_string(&dot_git).ok()?;
+        let dir = PathBuf::from(text.strip_prefix("gitdir:")?.trim());
+        top_level.join(dir)
+    } else {
+        dot_git
+    };
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+    match head.strip_prefix("ref: ") {
+        Some(reference) => Some(
+            reference
+                .strip_prefix("refs/heads/")
+                .unwrap_or(reference)
+                .to_string(),
+        ),
+        None => Some(head.chars().take(7).collect()),
+    }
+}
+
+/// Repository the tree root is in: branch and uncommitted changes for the status line
+/// Changes are checked with `git status` in the background for a new root, and again
+/// once `invalidate` says files may have changed
+#[derive(Default)]
+pub struct RepoStatus {
+    /// Root the status is for
+    root: PathBuf,
+    pub top_level: Option<PathBuf>,
+    pub branch: Option<String>,
+    /// None until the first check finishes
+    pub dirty: Option<bool>,
+    /// Whether the next refresh starts a check
+    stale: bool,
+    receiver: Option<Receiver<bool>>,
+}
+
+impl RepoStatus {
+    /// Follow the tree root and collect finished checks
+    /// Returns true if the status changed and the UI needs to be redrawn
+    pub fn refresh(&mut self, root: &Path) -> bool {
+        let mut changed = false;
+        if self.root != root {
+            let top_level = find_top_level(root);
+            *self = Self {
+                root: root.to_path_buf(),
+                branch: top_level.as_deref().and_then(read_branch),
+                top_level,
+                stale: true,
+                ..Self::default()
+            };
+            changed = true;
+        }
+
+        if let Some(receiver) = &self.receiver {
+            match receiver.try_recv() {
+                Ok(dirty) => {
+                    changed |= self.dirty != Some(dirty);
+                    self.dirty = Some(dirty);
+                    self.receiver = None;
+                }
+                Err(TryRecvError::Disconnected) => self.receiver = None,
+                Err(TryRecvError::Empty) => {}
+            }
+        }
+
+        if self.stale && self.receiver.is_none() {
+            if let Some(top_level) = &self.top_level {
+                // The branch may have been switched outside
+                let branch = read_branch(top_level);
+                changed |= branch != self.branch;
+                self.branch = branch;
+                self.receiver = Some(spawn_status_check(top_level.clone()));
+            }
+            self.stale = false;
+        }
+        changed
+    }
+
+    /// Check again on the next refresh (files changed, dtree got the focus back)
+    /// A check that is running now is followed by another one
+    pub fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    /// Whether a check runs in the background
+    pub fn is_checking(&self) -> bool {
+        self.receiver.is_some()
+    }
+
+    /// Status text like `main*` (`*`: uncommitted changes); None outside a repository
+    pub fn label(&self) -> Option<String> {
+        self.top_level.as_ref()?;
+        let branch = self.branch.as_deref().unwrap_or("?");
+        let dirty = if self.dirty == Some(true) { "*" } else { "" };
+        Some(format!("{}{}", branch, dirty))
+    }
+}
+
+/// Run `git status --porcelain` in a thread; sends true if there are changes
+fn spawn_status_check(top_level: PathBuf) -> Receiver<bool> {
+    let (tx, rx) = bounded(1);
+    thread::spawn(move || {
+        let output = Command::new("git")
+            .current_dir(&top_level)
+            .args(["status", "--porcelain"])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output();
+        // Without git the state stays unknown
+        if let Some(output) = output.ok().filter(|output| output.status.success()) {
+            let _ = tx.send(!output.stdout.is_empty());
+        }
+    });
+    rx
+}
+
+/// What the tree does with entries git ignores (`gitignore` config option)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GitignoreMode {
+    /// Like any other entry
+    #[default]
+    Show,
+    /// Left out of the tree
+    Hide,
+    /// Shown dimmed, so build outputs don't compete with sources
+    Dim,
+}
+
+impl GitignoreMode {
+    /// Parse the `gitignore` config value (unknown values fall back to show)
+    pub fn from_config(value: &str) -> Self {
+        match value.to_ascii_lowercase().as_str() {
+            "hide" => GitignoreMode::Hide,
+            "dim" => GitignoreMode::Dim,
+            _ => GitignoreMode::Show,
+        }
+    }
+}
+
+/// Untracked entries git ignores in a repository (`.gitignore`, `.git/info/exclude`,
+/// global excludes); an ignored directory is listed once, not its contents
+#[derive(Debug, Clone)]
+pub struct IgnoredPaths {
+    top_level: PathBuf,
+    paths: HashSet<PathBuf>,
+}
+
+impl IgnoredPaths {
+    /// Ask git for the ignored entries of the repository at `top_level`
+    pub fn load(top_level: &Path) -> Option<Self> {
+        let output = Command::new("git")
+            .current_dir(top_level)
+            .args([
+                "ls-files",
+                "--others",
+                "--ignored",
+                "--exclude-standard",
+                "--directory",
+                "-z",
+            ])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+
+        let paths = output
+            .stdout
+            .split(|&byte| byte == 0)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let entry = String::from_utf8_lossy(entry);
+                top_level.join(entry.trim_end_matches('/'))
+            })
+            .collect();
+        Some(Self {
+            top_level: top_level.to_path_buf(),
+            paths,
+        })
+    }
+
+    /// Repository the entries are for
+    pub fn top_level(&self) -> &Path {
+        &self.top_level
+    }
+
+    /// True for ignored entries and everything inside ignored directories
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        path.starts_with(&self.top_level)
+            && path
+                .ancestors()
+                .take_while(|ancestor| *ancestor != self.top_level)
+                .any(|ancestor| self.paths.contains(ancestor))
+    }
+}
+
+/// Ignored entries being listed on a worker, so a large repository doesn't hold up the tree
+pub struct PendingIgnored {
+    receiver: Receiver<Option<IgnoredPaths>>,
+}
+
+impl PendingIgnored {
+    /// Start `IgnoredPaths::load` for the repository at `top_level`
+    pub fn start(top_level: &Path) -> Self {
+        let (tx, rx) = wakeup::unbounded();
+        let top_level = top_level.to_path_buf();
+        thread::spawn(move || {
+            let _ = tx.send(IgnoredPaths::load(&top_level));
+        });
+        Self { receiver: rx }
+    }
+
+    /// The ignored entries once the worker is done (None inside: git failed)
+    pub fn try_finish(&self) -> Option<Option<IgnoredPaths>> {
+        match self.receiver.try_recv() {
+            Ok(ignored) => Some(ignored),
+            Err(TryRecvError::Disconnected) => Some(None),
+            Err(TryRecvError::Empty) => None,
+        }
+    }
+}
+
+/// Commit hash at the start of a `git log --oneline` or `git blame` line
+/// (`^` marks a boundary commit in blame; uncommitted lines have no hash)
+pub fn commit_hash(line: &str) -> Option<&str> {
+    let hash = line.split_whitespace().next()?.trim_start_matches('^');
+    let is_hash = hash.len() >= 7
+        && hash.chars().all(|c| c.is_ascii_hexdigit())
+        && hash.chars().any(|c| c != '0');
+    is_hash.then_some(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_commit_hash() {
+        assert_eq!(
+            commit_hash("f7ed57e Add flat listing of all files"),
+            Some("f7ed57e")
+        );
+        assert_eq!(
+            commit_hash("^12d2df2 (alice 2024-05-01  1) use std::fs;"),
+            Some("12d2df2")
+        );
+        assert_eq!(
+            commit_hash("00000000 (Not Committed Yet 2024-05-01 3) x"),
+            None
+        );
+        // Continuation of a wrapped line
+        assert_eq!(commit_hash("    of all files"), None);
+        assert_eq!(commit_hash(""), None);
+    }
+
+    #[test]
+    fn test_log_and_blame_output() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .current_dir(dir)
+                .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+                .args(args)
+                .output()
+                .is_ok_and(|output| output.status.success())
+        };
+        // Nothing to test without git
+        if !git(&["init", "-q"]) {
+            return;
+        }
+        std::fs::create_dir(dir.join("src")).unwrap();
+        std::fs::write(dir.join("src/lib.rs"), "fn a() {}\n").unwrap();
+        std::fs::write(dir.join("new.txt"), "x").unwrap();
+        assert!(git(&["add", "src"]));
+        assert!(git(&["commit", "-q", "-m", "Add lib"]));
+
+        let file = dir.join("src/lib.rs");
+        let log = GitView::Log.output(&file).unwrap();
+        assert!(log.trim_end().ends_with(" Add lib"));
+        assert!(commit_hash(&log).is_some());
+        let blame = GitView::Blame.output(&file).unwrap();
+        assert!(blame.contains("fn a() {}"));
+        assert_eq!(commit_hash(&blame), commit_hash(&log));
+
+        assert!(GitView::Log.output(&dir.join("src")).is_ok());
+        assert!(GitView::Blame.output(&dir.join("src")).is_err());
+        assert!(GitView::Log.output(&dir.join("new.txt")).is_err());
+        assert_eq!(GitView::next(Some(GitView::Log), true), None);
+    }
+
+    #[test]
+    fn test_repo_status_follows_root() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let mut status = RepoStatus::default();
+        assert!(status.refresh(dir));
+        assert_eq!(status.label(), None);
+
+        let initialized = Command::new("git")
+            .current_dir(dir)
+            .args(["init", "-q", "-b", "trunk"])
+            .status()
+            .is_ok_and(|status| status.success());
+        // Nothing more to test without git
+        if !initialized {
+            return;
+        }
+        std::fs::create_dir(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("sub/new.txt"), "x").unwrap();
+
+        let sub = dir.join("sub");
+        assert!(status.refresh(&sub));
+        assert_eq!(status.top_level.as_deref(), Some(dir));
+        assert_eq!(status.label().as_deref(), Some("trunk"));
+
+        // The untracked file makes the repository dirty once the check finishes
+        let started = Instant::now();
+        while status.dirty.is_none() {
+            assert!(
+                started.elapsed() < Duration::from_secs(5),
+                "git status hung"
+            );
+            status.refresh(&sub);
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(status.label().as_deref(), Some("trunk*"));
+
+        // Nothing runs again until the status is invalidated
+        status.refresh(&sub);
+        assert!(!status.is_checking());
+        status.invalidate();
+        status.refresh(&sub);
+        assert!(status.is_checking());
+        assert_eq!(find_top_level(&sub.join("new.txt")).as_deref(), Some(dir));
+    }
+}
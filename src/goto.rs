@@ -0,0 +1,113 @@
+use crate::completion::{self, Completion};
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+
+/// "Go to path" prompt (`g` or `:`) for jumping to any directory from the TUI
+#[derive(Debug, Default)]
+pub struct GotoPrompt {
+    pub is_open: bool,
+    pub input: String,
+    pub error: Option<String>,
+    pub completion: Completion,
+}
+
+impl GotoPrompt {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the prompt pre-filled with the current root, ready for a child name
+    pub fn open(&mut self, root: &Path) {
+        let mut input = root.display().to_string();
+        if !input.ends_with(MAIN_SEPARATOR) {
+            input.push(MAIN_SEPARATOR);
+        }
+        self.input = input;
+        self.error = None;
+        self.completion.reset();
+        self.is_open = true;
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+        self.input.clear();
+        self.error = None;
+        self.completion.reset();
+    }
+
+    pub fn add_char(&mut self, c: char) {
+        self.input.push(c);
+        self.error = None;
+        self.completion.reset();
+    }
+
+    pub fn backspace(&mut self) {
+        self.input.pop();
+        self.error = None;
+        self.completion.reset();
+    }
+
+    /// Tab/Shift+Tab: complete the last path component against existing directories
+    pub fn complete(&mut self, base: &Path, forward: bool) {
+        let source = |input: &str| completion::path_candidates(input, base);
+        let completed = if forward {
+            self.completion.next(&self.input, source)
+        } else {
+            self.completion.prev(&self.input, source)
+        };
+        match completed {
+            Some(completed) => {
+                self.input = completed;
+                self.error = None;
+            }
+            None => self.error = Some("no match".to_string()),
+        }
+    }
+
+    /// Path the input refers to (accepts a vim-like "cd " prefix, "~" and relative paths)
+    pub fn target(&self, base: &Path) -> PathBuf {
+        let input = self.input.trim();
+        let input = input.strip_prefix("cd ").unwrap_or(input).trim_start();
+        expand_path(input, base)
+    }
+}
+
+/// Expand "~" and resolve relative paths against `base`
+pub fn expand_path(input: &str, base: &Path) -> PathBuf {
+    if input == "~" {
+        if let Some(home) = dirs::home_dir() {
+            return home;
+        }
+    }
+    if let Some(rest) = input
+        .strip_prefix("~/")
+        .or_else(|| input.strip_prefix("~\\"))
+    {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    }
+    base.join(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_target_accepts_cd_prefix() {
+        let mut prompt = GotoPrompt::new();
+        prompt.open(Path::new("/tmp"));
+        assert!(prompt.is_open);
+
+        prompt.input = "cd sub".to_string();
+        assert_eq!(prompt.target(Path::new("/tmp")), PathBuf::from("/tmp/sub"));
+
+        prompt.input = "/etc".to_string();
+        assert_eq!(prompt.target(Path::new("/tmp")), PathBuf::from("/etc"));
+
+        prompt.close();
+        assert!(!prompt.is_open);
+        assert!(prompt.input.is_empty());
+    }
+}
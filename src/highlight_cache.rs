@@ -0,0 +1,151 @@
+use crate::lru_cache::LruCache;
+use crossbeam_channel::{bounded, Receiver};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::Theme;
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+
+/// Rows highlighted before a file is shown; the rest follow in the background
+pub const FIRST_ROWS: usize = 300;
+
+/// Highlighted files kept, so reloads (resizing, fullscreen toggles, going back
+/// to a file) don't run syntect again
+const CACHE_SIZE: usize = 8;
+
+/// Longest `wait` waits for a background highlighting
+const WAIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Highlighted rows of one file
+type Highlighted = (HighlightKey, Vec<Line<'static>>);
+
+/// Rows of recently highlighted files
+static CACHE: Mutex<LruCache<HighlightKey, Vec<Line<'static>>>> =
+    Mutex::new(LruCache::new(CACHE_SIZE));
+
+/// What highlighted rows depend on: the file (path, mtime), the theme and the
+/// rows themselves (head or tail)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighlightKey {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    theme: String,
+    rows: u64,
+}
+
+impl HighlightKey {
+    pub fn new(path: &Path, modified: Option<SystemTime>, theme: &str, rows: &[String]) -> Self {
+        let mut hasher = DefaultHasher::new();
+        rows.hash(&mut hasher);
+        Self {
+            path: path.to_path_buf(),
+            modified,
+            theme: theme.to_string(),
+            rows: hasher.finish(),
+        }
+    }
+}
+
+/// Highlighted rows cached for `key`
+pub fn get(key: &HighlightKey) -> Option<Vec<Line<'static>>> {
+    CACHE.lock().ok()?.get(key)
+}
+
+/// Cache the highlighted rows for `key`, dropping the least recently used file
+pub fn insert(key: HighlightKey, lines: Vec<Line<'static>>) {
+    if let Ok(mut cache) = CACHE.lock() {
+        cache.insert(key, lines);
+    }
+}
+
+/// Highlight `rows` from the first one on; None when `cancel` is set meanwhile
+pub fn highlight(
+    rows: &[String],
+    syntax: &SyntaxReference,
+    syntax_set: &SyntaxSet,
+    theme: &Theme,
+    cancel: Option<&AtomicBool>,
+) -> Option<Vec<Line<'static>>> {
+    let mut highlighter = HighlightLines::new(syntax, theme);
+    let mut lines = Vec::with_capacity(rows.len());
+    for (index, row) in rows.iter().enumerate() {
+        if index % 256 == 0 && cancel.is_some_and(|cancel| cancel.load(Ordering::Relaxed)) {
+            return None;
+        }
+        match highlighter.highlight_line(row, syntax_set) {
+            Ok(ranges) => {
+                let spans: Vec<Span> = ranges
+                    .iter()
+                    .map(|(style, text)| {
+                        let fg = style.foreground;
+                        Span::styled(
+                            text.to_string(),
+                            Style::default().fg(Color::Rgb(fg.r, fg.g, fg.b)),
+                        )
+                    })
+                    .collect();
+                lines.push(Line::from(spans));
+            }
+            // Fallback to plain text
+            Err(_) => lines.push(Line::from(row.clone())),
+        }
+    }
+    Some(lines)
+}
+
+/// Highlighting of a whole file on its own thread; dropping it cancels it
+pub struct HighlightJob {
+    receiver: Receiver<Highlighted>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl HighlightJob {
+    pub fn start(
+        key: HighlightKey,
+        rows: Vec<String>,
+        syntax: &'static SyntaxReference,
+        syntax_set: &'static SyntaxSet,
+        theme: &'static Theme,
+    ) -> Self {
+        let (tx, rx) = bounded(1);
+        let cancel = Arc::new(AtomicBool::new(false));
+        let thread_cancel = Arc::clone(&cancel);
+        thread::spawn(move || {
+            let _span = tracing::debug_span!("highlight", rows = rows.len()).entered();
+            if let Some(lines) = highlight(&rows, syntax, syntax_set, theme, Some(&thread_cancel)) {
+                let _ = tx.send((key, lines));
+            }
+        });
+        Self {
+            receiver: rx,
+            cancel,
+        }
+    }
+
+    /// The highlighted rows once they're done (and cached)
+    pub fn poll(&self) -> Option<Vec<Line<'static>>> {
+        let (key, lines) = self.receiver.try_recv().ok()?;
+        insert(key, lines.clone());
+        Some(lines)
+    }
+
+    /// Wait for the highlighted rows (headless driver)
+    pub fn wait(&self) -> Option<Vec<Line<'static>>> {
+        let (key, lines) = self.receiver.recv_timeout(WAIT_TIMEOUT).ok()?;
+        insert(key, lines.clone());
+        Some(lines)
+    }
+}
+
+impl Drop for HighlightJob {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,151 @@
+use anyhow::{Context, Result};
+use once_cell::sync::OnceCell;
+use std::collections::HashMap;
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+
+/// Catalogs shipped with dtree, by language code
+const BUILTIN: &[(&str, &str)] = &[("de", include_str!("../locales/de.toml"))];
+
+/// Translations of the selected language, keyed by the English text
+static CATALOG: OnceCell<HashMap<String, String>> = OnceCell::new();
+
+/// Language code for the `locale` config value; "auto" takes it from
+/// LC_ALL, LC_MESSAGES or LANG (`de_DE.UTF-8` -> `de`), English for C/POSIX
+pub fn language(locale: &str) -> String {
+    let locale = if locale.eq_ignore_ascii_case("auto") {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default()
+    } else {
+        locale.to_string()
+    };
+    let language = locale
+        .split(['_', '.', '@', '-'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match language.as_str() {
+        "" | "c" | "posix" => "en".to_string(),
+        _ => language,
+    }
+}
+
+/// Parse a catalog: a TOML table of English text = translated text
+pub fn parse(text: &str) -> Result<HashMap<String, String>> {
+    Ok(toml::from_str(text)?)
+}
+
+/// Select the UI language (once, at startup): the built-in catalog for it, with
+/// `<locales_dir>/<language>.toml` on top; without either the UI stays in English
+pub fn init(locale: &str, locales_dir: Option<&Path>) -> Result<()> {
+    let language = language(locale);
+    let mut catalog = HashMap::new();
+    if let Some((_, text)) = BUILTIN.iter().find(|(code, _)| *code == language) {
+        catalog.extend(parse(text)?);
+    }
+    if let Some(file) = locales_dir.map(|dir| dir.join(format!("{}.toml", language))) {
+        if file.exists() {
+            let text = fs::read_to_string(&file)?;
+            catalog.extend(parse(&text).with_context(|| format!("{}", file.display()))?);
+        }
+    }
+    let _ = CATALOG.set(catalog);
+    Ok(())
+}
+
+/// `text` in the selected language (as is when it has no translation)
+pub fn tr(text: &'static str) -> &'static str {
+    CATALOG
+        .get()
+        .and_then(|catalog| catalog.get(text))
+        .map_or(text, |translated| translated.as_str())
+}
+
+/// `tr` with `{name}` placeholders filled in
+pub fn tr_with(text: &'static str, args: &[(&str, &dyn Display)]) -> String {
+    fill(tr(text), args)
+}
+
+/// Replace the `{name}` placeholders of `template` in one pass, so braces in the
+/// values (file names, error messages) are left as they are
+pub fn fill(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut text = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        let placeholder = &rest[start..];
+        let value = placeholder.find('}').and_then(|end| {
+            let name = &placeholder[1..end];
+            let (_, value) = args.iter().find(|(arg, _)| *arg == name)?;
+            Some((value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                text.push_str(&value.to_string());
+                rest = &placeholder[end + 1..];
+            }
+            None => {
+                text.push('{');
+                rest = &placeholder[1..];
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_from_locale() {
+        assert_eq!(language("de_DE.UTF-8"), "de");
+        assert_eq!(language("pt-BR"), "pt");
+        assert_eq!(language("C"), "en");
+        assert_eq!(language("FR"), "fr");
+    }
+
+    #[test]
+    fn test_builtin_catalogs_keep_placeholders() {
+        // A translation must use the same placeholders as its English text
+        let placeholders = |text: &str| {
+            let mut names: Vec<String> = text
+                .split('{')
+                .skip(1)
+                .filter_map(|rest| rest.split_once('}').map(|(name, _)| name.to_string()))
+                .collect();
+            names.sort();
+            names
+        };
+        for (code, text) in BUILTIN {
+            for (english, translated) in parse(text).unwrap() {
+                assert_eq!(
+                    placeholders(&english),
+                    placeholders(&translated),
+                    "{}: {}",
+                    code,
+                    english
+                );
+            }
+        }
+        assert_eq!(
+            fill("{count} of {total}", &[("count", &3), ("total", &7)]),
+            "3 of 7"
+        );
+        // A value that looks like a placeholder stays as it is
+        assert_eq!(
+            fill(
+                "{path}: {error}",
+                &[("path", &"{error}"), ("error", &"denied")]
+            ),
+            "{error}: denied"
+        );
+        assert_eq!(fill("{unknown} {", &[]), "{unknown} {");
+    }
+}
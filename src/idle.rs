@@ -0,0 +1,94 @@
+use std::time::{Duration, Instant};
+
+use crate::config::BehaviorConfig;
+
+/// What happens after `idle_timeout_minutes` without input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleAction {
+    /// Hide the tree, viewer and panels until a key is pressed
+    Blank,
+    /// Quit dtree (like Esc)
+    Exit,
+}
+
+impl IdleAction {
+    /// Parse the `idle_action` config value (unknown values fall back to blank)
+    pub fn from_config(value: &str) -> Self {
+        match value.to_ascii_lowercase().as_str() {
+            "exit" => IdleAction::Exit,
+            _ => IdleAction::Blank,
+        }
+    }
+}
+
+/// Time since the last key or mouse input, for blanking or exiting when left alone
+#[derive(Debug)]
+pub struct IdleTimer {
+    timeout: Option<Duration>, // None: never idle
+    action: IdleAction,
+    last_input: Instant,
+    pub blanked: bool,
+}
+
+impl IdleTimer {
+    pub fn new(timeout: Option<Duration>, action: IdleAction) -> Self {
+        Self {
+            timeout,
+            action,
+            last_input: Instant::now(),
+            blanked: false,
+        }
+    }
+
+    pub fn from_config(behavior: &BehaviorConfig) -> Self {
+        let timeout = (behavior.idle_timeout_minutes > 0)
+            .then(|| Duration::from_secs(behavior.idle_timeout_minutes * 60));
+        Self::new(timeout, IdleAction::from_config(&behavior.idle_action))
+    }
+
+    /// Record input; returns true if it ends the blank screen (the input does nothing else)
+    pub fn input(&mut self) -> bool {
+        self.last_input = Instant::now();
+        std::mem::take(&mut self.blanked)
+    }
+
+    /// When the idle action is due; None when it never is or already happened
+    pub fn deadline(&self) -> Option<Instant> {
+        let timeout = self.timeout.filter(|_| !self.blanked)?;
+        Some(self.last_input + timeout)
+    }
+
+    /// The action due now: once per idle period, none while blanked
+    pub fn poll(&mut self) -> Option<IdleAction> {
+        let timeout = self.timeout?;
+        if self.blanked || self.last_input.elapsed() < timeout {
+            return None;
+        }
+        if self.action == IdleAction::Blank {
+            self.blanked = true;
+        }
+        Some(self.action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blank_until_input() {
+        let mut timer = IdleTimer::new(Some(Duration::ZERO), IdleAction::Blank);
+        assert_eq!(timer.poll(), Some(IdleAction::Blank));
+        assert!(timer.blanked);
+        assert_eq!(timer.poll(), None);
+
+        // The waking key is swallowed, later ones aren't
+        assert!(timer.input());
+        assert!(!timer.input());
+
+        let mut never = IdleTimer::new(None, IdleAction::Exit);
+        assert_eq!(never.poll(), None);
+        let mut waiting = IdleTimer::new(Some(Duration::from_secs(60)), IdleAction::Exit);
+        assert_eq!(waiting.poll(), None);
+    }
+}
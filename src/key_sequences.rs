@@ -0,0 +1,151 @@
+use crate::actions::KeyChord;
+use crate::config::KeybindingsConfig;
+use anyhow::{bail, Result};
+use crossterm::event::KeyEvent;
+
+/// A two-key sequence from `[keybindings]`, e.g. `search = ["/", "space f"]`
+#[derive(Debug, Clone)]
+struct Sequence {
+    first: KeyChord,
+    second: KeyChord,
+    text: String,         // As written in the config, what the binding lists
+    second_label: String, // Second key as written, for the hints
+    binding: &'static str,
+}
+
+/// Keys that can follow a pressed first key, shown while the second one is awaited
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceHints {
+    /// First key as written in the config ("space")
+    pub prefix: String,
+    /// Second key and the command it runs, in config order
+    pub entries: Vec<(String, &'static str)>,
+}
+
+/// Two-key sequences ("leader" keys): their first key waits for the second one
+#[derive(Debug, Default)]
+pub struct KeySequences {
+    sequences: Vec<Sequence>,
+}
+
+impl KeySequences {
+    /// Collect the sequences of all keybindings
+    /// A sequence with an unknown key is skipped; the errors are returned for display
+    pub fn new(keybindings: &KeybindingsConfig) -> (Self, Vec<String>) {
+        let mut sequences = Vec::new();
+        let mut errors = Vec::new();
+
+        for (binding, keys) in keybindings.bindings() {
+            for text in keys {
+                match parse(text) {
+                    Ok(Some((first, second))) => sequences.push(Sequence {
+                        first,
+                        second,
+                        text: text.clone(),
+                        second_label: text.split_whitespace().nth(1).unwrap_or("").to_string(),
+                        binding,
+                    }),
+                    Ok(None) => {}
+                    Err(e) => errors.push(format!("Keybinding {} '{}': {}", binding, text, e)),
+                }
+            }
+        }
+
+        (Self { sequences }, errors)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sequences.is_empty()
+    }
+
+    /// Whether `key` is the first key of a sequence
+    pub fn starts_with(&self, key: &KeyEvent) -> bool {
+        let first = KeyChord::from_event(key);
+        self.sequences
+            .iter()
+            .any(|sequence| sequence.first == first)
+    }
+
+    /// The command the sequence `first`, `second` is bound to ("search")
+    pub fn get(&self, first: &KeyEvent, second: &KeyEvent) -> Option<&'static str> {
+        let (first, second) = (KeyChord::from_event(first), KeyChord::from_event(second));
+        self.sequences
+            .iter()
+            .find(|sequence| sequence.first == first && sequence.second == second)
+            .map(|sequence| sequence.binding)
+    }
+
+    /// What can follow `first`
+    pub fn hints(&self, first: &KeyEvent) -> SequenceHints {
+        let first = KeyChord::from_event(first);
+        let matching: Vec<&Sequence> = self
+            .sequences
+            .iter()
+            .filter(|sequence| sequence.first == first)
+            .collect();
+        SequenceHints {
+            prefix: matching
+                .first()
+                .and_then(|sequence| sequence.text.split_whitespace().next())
+                .unwrap_or("")
+                .to_string(),
+            entries: matching
+                .iter()
+                .map(|sequence| (sequence.second_label.clone(), sequence.binding))
+                .collect(),
+        }
+    }
+}
+
+/// Keys of a two-key sequence ("space f", "ctrl+x s"); None for a single key
+fn parse(text: &str) -> Result<Option<(KeyChord, KeyChord)>> {
+    let keys: Vec<&str> = text.split_whitespace().collect();
+    match keys[..] {
+        [first, second] => Ok(Some((KeyChord::parse(first)?, KeyChord::parse(second)?))),
+        [] | [_] => Ok(None),
+        _ => bail!("only sequences of two keys are supported"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_sequences_from_keybindings() {
+        let keybindings = KeybindingsConfig {
+            search: vec!["/".to_string(), "space f".to_string()],
+            select_bookmark: vec!["'".to_string(), "space b".to_string()],
+            yank: vec!["space y x".to_string()],
+            cut: vec!["space hyper+x".to_string()],
+            ..KeybindingsConfig::default()
+        };
+        let (sequences, errors) = KeySequences::new(&keybindings);
+
+        let space = key(' ');
+        assert!(sequences.starts_with(&space));
+        assert!(!sequences.starts_with(&key('/')));
+        assert_eq!(sequences.get(&space, &key('f')), Some("search"));
+        assert_eq!(sequences.get(&space, &key('x')), None);
+        assert_eq!(
+            sequences.hints(&space),
+            SequenceHints {
+                prefix: "space".to_string(),
+                entries: vec![
+                    ("f".to_string(), "search"),
+                    ("b".to_string(), "select_bookmark")
+                ],
+            }
+        );
+
+        // Longer sequences and unknown keys are reported
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].contains("yank"));
+        assert!(errors[1].contains("hyper"));
+    }
+}
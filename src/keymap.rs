@@ -0,0 +1,224 @@
+use crate::actions::KeyChord;
+use crate::config::Config;
+
+/// Part of dtree where a command's keys work; a key can do different things in each
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    Tree,
+    Viewer,
+    Visual,
+    Results,
+}
+
+impl Scope {
+    const ALL: [Scope; 4] = [Scope::Tree, Scope::Viewer, Scope::Visual, Scope::Results];
+
+    fn name(self) -> &'static str {
+        match self {
+            Scope::Tree => "the tree",
+            Scope::Viewer => "the fullscreen viewer",
+            Scope::Visual => "visual mode",
+            Scope::Results => "the results panel",
+        }
+    }
+
+    /// Keys with a fixed meaning here (not in `[keybindings]`)
+    fn built_in_keys(self) -> &'static [&'static str] {
+        match self {
+            Scope::Tree => &[
+                "Up",
+                "Down",
+                "Left",
+                "Right",
+                "j",
+                "k",
+                "h",
+                "l",
+                "Enter",
+                "Backspace",
+                "u",
+                "Tab",
+                "v",
+                "z",
+                "Home",
+                "End",
+                "PageUp",
+                "PageDown",
+            ],
+            Scope::Viewer => &[
+                "Up", "Down", "j", "k", "/", "n", "N", "m", "'", "z", "e", "E", "c", "C", "o", "O",
+                "q", "Q", "Esc", "Home", "End", "PageUp", "PageDown",
+            ],
+            Scope::Visual => &["Up", "Down", "j", "k", "z", "Esc"],
+            Scope::Results => &[
+                "Up", "Down", "j", "k", "h", "l", "Left", "Right", "Enter", "Tab", "Esc",
+            ],
+        }
+    }
+
+    /// Where the keys of a `[keybindings]` command work
+    fn of(command: &str) -> &'static [Scope] {
+        match command {
+            "show_line_numbers" | "toggle_wrap" => &[Scope::Viewer],
+            "reload_file" | "git_view" | "reveal" => &[Scope::Tree, Scope::Viewer],
+            "visual_mode" => &[Scope::Viewer, Scope::Visual],
+            "visual_copy" => &[Scope::Visual],
+            "mark_results" | "root_at_result" => &[Scope::Results],
+            _ => &[Scope::Tree],
+        }
+    }
+}
+
+/// A key or two-key sequence and what it does in one scope
+struct Assignment {
+    keys: Vec<KeyChord>,
+    text: String, // As first written, for the report
+    scope: Scope,
+    owners: Vec<String>,
+}
+
+/// Parse a key as written in the config; None for keys dtree can't match
+fn parse(text: &str) -> Option<Vec<KeyChord>> {
+    let keys: Option<Vec<KeyChord>> = text
+        .split_whitespace()
+        .map(|key| KeyChord::parse(key).ok())
+        .collect();
+    keys.filter(|keys| (1..=2).contains(&keys.len()))
+}
+
+/// Everything bound to each key, per scope
+fn assignments(config: &Config) -> Vec<Assignment> {
+    let mut assignments: Vec<Assignment> = Vec::new();
+    let mut assign = |text: &str, scope: Scope, owner: String| {
+        let Some(keys) = parse(text) else {
+            return;
+        };
+        match assignments
+            .iter_mut()
+            .find(|assignment| assignment.keys == keys && assignment.scope == scope)
+        {
+            Some(assignment) if !assignment.owners.contains(&owner) => {
+                assignment.owners.push(owner)
+            }
+            Some(_) => {}
+            None => assignments.push(Assignment {
+                keys,
+                text: text.to_string(),
+                scope,
+                owners: vec![owner],
+            }),
+        }
+    };
+
+    for scope in Scope::ALL {
+        for key in scope.built_in_keys() {
+            assign(key, scope, "built-in".to_string());
+        }
+    }
+    for (command, keys) in config.keybindings.bindings() {
+        for key in keys {
+            for &scope in Scope::of(command) {
+                assign(key, scope, command.to_string());
+            }
+        }
+    }
+    // Custom actions run before the built-in commands, outside the fullscreen viewer
+    for key in config.actions.keys() {
+        assign(key, Scope::Tree, format!("custom action \"{}\"", key));
+    }
+
+    assignments
+}
+
+/// Keys that do more than one thing, and single keys delayed by a sequence starting with them
+pub fn conflicts(config: &Config) -> Vec<String> {
+    let assignments = assignments(config);
+    let mut conflicts = Vec::new();
+
+    for assignment in &assignments {
+        if assignment.owners.len() > 1 {
+            conflicts.push(format!(
+                "\"{}\" in {}: {}",
+                assignment.text,
+                assignment.scope.name(),
+                assignment.owners.join(", ")
+            ));
+        }
+    }
+    for sequence in assignments.iter().filter(|a| a.keys.len() == 2) {
+        let shadowed = assignments
+            .iter()
+            .find(|single| single.scope == sequence.scope && single.keys[..] == sequence.keys[..1]);
+        if let Some(single) = shadowed {
+            conflicts.push(format!(
+                "\"{}\" in {} starts the sequence \"{}\": {} waits for the next key",
+                single.text,
+                single.scope.name(),
+                sequence.text,
+                single.owners.join(", ")
+            ));
+        }
+    }
+
+    conflicts
+}
+
+/// The effective keymap: keybindings after the config, custom actions and conflicts
+/// (printed by `dtree --keys` and shown at the end of the help)
+pub fn report(config: &Config) -> Vec<String> {
+    let mut lines = vec!["KEYBINDINGS".to_string()];
+    for (command, keys) in config.keybindings.bindings() {
+        lines.push(format!("  {:<20} {}", command, keys.join(", ")));
+    }
+
+    if !config.actions.is_empty() {
+        lines.push(String::new());
+        lines.push("CUSTOM ACTIONS".to_string());
+        for (key, steps) in &config.actions {
+            lines.push(format!("  {:<20} {}", key, steps.names().join(", ")));
+        }
+    }
+
+    lines.push(String::new());
+    lines.push("CONFLICTS".to_string());
+    let conflicts = conflicts(config);
+    if conflicts.is_empty() {
+        lines.push("  None".to_string());
+    }
+    lines.extend(conflicts.iter().map(|conflict| format!("  {}", conflict)));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ActionSteps;
+
+    #[test]
+    fn test_conflicts() {
+        assert!(conflicts(&Config::default()).is_empty());
+
+        let mut config = Config::default();
+        config.keybindings.cut = vec!["x".to_string(), "/".to_string()];
+        config.keybindings.search = vec!["/".to_string(), "space f".to_string()];
+        config.keybindings.toggle_wrap = vec!["j".to_string()];
+        config.keybindings.mark_results = vec!["Tab".to_string()];
+        config
+            .actions
+            .insert("x".to_string(), ActionSteps::One("quit".to_string()));
+        assert_eq!(
+            conflicts(&config),
+            [
+                "\"j\" in the fullscreen viewer: built-in, toggle_wrap",
+                "\"Tab\" in the results panel: built-in, mark_results",
+                "\"/\" in the tree: search, cut",
+                "\"x\" in the tree: cut, custom action \"x\"",
+                "\"Space\" in the tree starts the sequence \"space f\": toggle_mark waits for the next key",
+            ]
+        );
+
+        let report = report(&config);
+        assert!(report.contains(&format!("  {:<20} /, space f", "search")));
+        assert!(report.contains(&format!("  {:<20} quit", "x")));
+    }
+}
@@ -0,0 +1,292 @@
+use crate::copy_path::shell_quote;
+use crate::platform;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Marker prefix for the editor run after dtree exits: `EDITOR:[<line>:]<path>`
+const EDITOR_MARKER: &str = "EDITOR:";
+
+/// Marker prefix for the editor run on several files: the paths after it, NUL-separated
+/// (NUL is the one character paths can't contain)
+const EDITOR_ALL_MARKER: &str = "EDITALL:";
+
+/// Programs with their own window: started in the background while dtree keeps running
+const GUI_PROGRAMS: &[&str] = &[
+    "code",
+    "code-insiders",
+    "codium",
+    "subl",
+    "sublime_text",
+    "zed",
+    "gedit",
+    "gnome-text-editor",
+    "kate",
+    "kwrite",
+    "mousepad",
+    "xed",
+    "pluma",
+    "gvim",
+    "mvim",
+    "notepad",
+    "notepad++",
+    "idea",
+    "nautilus",
+    "dolphin",
+    "thunar",
+    "nemo",
+    "caja",
+    "pcmanfm",
+    "explorer",
+    "open",
+    "xdg-open",
+];
+
+/// Name of the program a command line runs (`/usr/bin/code --wait` -> `code`)
+fn program_name(program: &str) -> String {
+    let first = program.split_whitespace().next().unwrap_or_default();
+    let name = Path::new(first)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    for extension in [".exe", ".cmd", ".bat"] {
+        if let Some(stem) = name.strip_suffix(extension) {
+            return stem.to_string();
+        }
+    }
+    name
+}
+
+/// Check if `program` opens its own window (known GUI apps plus the `gui_programs` option)
+pub fn is_gui(program: &str, gui_programs: &[String]) -> bool {
+    let name = program_name(program);
+    GUI_PROGRAMS.contains(&name.as_str())
+        || gui_programs.iter().any(|gui| program_name(gui) == name)
+}
+
+/// How known editors are told the line to open at, for a plain program name
+fn line_template(program: &str) -> Option<&'static str> {
+    if program.split_whitespace().count() != 1 {
+        return None;
+    }
+    match program_name(program).as_str() {
+        "vi" | "vim" | "nvim" | "gvim" | "nano" | "emacs" | "micro" | "kak" | "ne" => {
+            Some("+{line} {path}")
+        }
+        "hx" | "helix" | "subl" | "zed" => Some("{path}:{line}"),
+        "code" | "code-insiders" | "codium" => Some("--goto {path}:{line}"),
+        _ => None,
+    }
+}
+
+/// Command line that opens `path` (at `line`, when known) with `program`
+/// `{path}` and `{line}` in the program are replaced (line 1 when not known); without
+/// `{path}` the path is appended, and known editors get the line in their own syntax
+pub fn command_line(program: &str, path: &str, line: Option<usize>) -> String {
+    let program = program.trim();
+    let quoted = shell_quote(path);
+    if program.contains("{path}") {
+        return program
+            .replace("{path}", &quoted)
+            .replace("{line}", &line.unwrap_or(1).to_string());
+    }
+    match (line, line_template(program)) {
+        (Some(line), Some(template)) => format!(
+            "{} {}",
+            program,
+            template
+                .replace("{path}", &quoted)
+                .replace("{line}", &line.to_string())
+        ),
+        _ => format!("{} {}", program, quoted),
+    }
+}
+
+/// Command line that opens all `paths` with a single `program` invocation
+/// `{path}` stands for all of them and `{line}` for 1; without `{path}` they are appended
+pub fn command_line_all(program: &str, paths: &[PathBuf]) -> String {
+    let program = program.trim();
+    let quoted: Vec<String> = paths
+        .iter()
+        .map(|path| shell_quote(&path.display().to_string()))
+        .collect();
+    let quoted = quoted.join(" ");
+    if program.contains("{path}") {
+        program.replace("{path}", &quoted).replace("{line}", "1")
+    } else {
+        format!("{} {}", program, quoted)
+    }
+}
+
+/// Command line that opens all files passed to the shell as arguments (`"$@"`) with a
+/// single `program` invocation, so no path is ever quoted into it
+/// `{path}` stands for all of them and `{line}` for 1; without `{path}` they are appended
+#[cfg(unix)]
+pub fn command_line_args(program: &str) -> String {
+    let program = program.trim();
+    if program.contains("{path}") {
+        program.replace("{path}", "\"$@\"").replace("{line}", "1")
+    } else {
+        format!("{} \"$@\"", program)
+    }
+}
+
+/// Start a command line in the background, detached from the terminal
+/// (waited for on a thread, so it doesn't stay a zombie once it exits)
+pub fn spawn_detached(command_line: &str) -> Result<()> {
+    platform::spawn_reaped(
+        shell(command_line)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null()),
+    )
+    .with_context(|| format!("Failed to run {}", command_line))?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn shell(command_line: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(command_line);
+    command
+}
+
+#[cfg(windows)]
+fn shell(command_line: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+
+    // cmd /C runs scripts (code.cmd) too; the line is passed on untouched
+    let mut command = Command::new("cmd");
+    command.arg("/C").raw_arg(command_line);
+    command
+}
+
+/// Encode the editor to run after exit as a return marker for main.rs
+pub fn encode_editor(path: &Path, line: Option<usize>) -> PathBuf {
+    match line {
+        Some(line) => PathBuf::from(format!("{}{}:{}", EDITOR_MARKER, line, path.display())),
+        None => PathBuf::from(format!("{}{}", EDITOR_MARKER, path.display())),
+    }
+}
+
+/// Decode a return marker produced by `encode_editor` into the path and line
+pub fn decode_editor(marker: &str) -> Option<(&str, Option<usize>)> {
+    let rest = marker.strip_prefix(EDITOR_MARKER)?;
+    // Absolute paths never start with digits and a colon, so the line can't be mistaken
+    match rest.split_once(':') {
+        Some((line, path)) if !line.is_empty() && line.bytes().all(|b| b.is_ascii_digit()) => {
+            Some((path, line.parse().ok()))
+        }
+        _ => Some((rest, None)),
+    }
+}
+
+/// Encode the editor to run on several files after exit as a return marker for main.rs
+pub fn encode_editor_all(paths: &[PathBuf]) -> PathBuf {
+    let paths: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+    PathBuf::from(format!("{}{}", EDITOR_ALL_MARKER, paths.join("\0")))
+}
+
+/// Decode a return marker produced by `encode_editor_all`
+pub fn decode_editor_all(marker: &str) -> Option<Vec<PathBuf>> {
+    let rest = marker.strip_prefix(EDITOR_ALL_MARKER)?;
+    Some(rest.split('\0').map(PathBuf::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_command_line() {
+        // Plain program: the quoted path is appended
+        assert_eq!(command_line("nano", "/tmp/a b", None), "nano '/tmp/a b'");
+        assert_eq!(
+            command_line("less -R", "/tmp/a", Some(7)),
+            "less -R '/tmp/a'"
+        );
+
+        // Known editors get the line in their own syntax
+        assert_eq!(
+            command_line("nvim", "/tmp/a", Some(42)),
+            "nvim +42 '/tmp/a'"
+        );
+        assert_eq!(
+            command_line("/usr/bin/hx", "/tmp/a", Some(3)),
+            "/usr/bin/hx '/tmp/a':3"
+        );
+        assert_eq!(
+            command_line("code", "/tmp/it's", Some(9)),
+            "code --goto '/tmp/it'\\''s':9"
+        );
+
+        // Templates place the path and line themselves
+        assert_eq!(
+            command_line("code --wait -g {path}:{line}", "/tmp/a", Some(12)),
+            "code --wait -g '/tmp/a':12"
+        );
+        assert_eq!(
+            command_line("vim +{line} {path}", "/tmp/a", None),
+            "vim +1 '/tmp/a'"
+        );
+    }
+
+    #[test]
+    fn test_is_gui() {
+        assert!(is_gui("code --wait {path}", &[]));
+        assert!(is_gui("/usr/bin/xdg-open", &[]));
+        assert!(is_gui("notepad.exe", &[]));
+        assert!(!is_gui("nvim", &[]));
+        assert!(!is_gui("$EDITOR", &[]));
+        assert!(is_gui("neovide --frame none", &["neovide".to_string()]));
+    }
+
+    #[test]
+    fn test_editor_marker_round_trip() {
+        let path = Path::new("/tmp/12:notes.txt");
+        let marker = encode_editor(path, Some(30));
+        assert_eq!(
+            decode_editor(&marker.to_string_lossy()),
+            Some(("/tmp/12:notes.txt", Some(30)))
+        );
+        let marker = encode_editor(path, None);
+        assert_eq!(
+            decode_editor(&marker.to_string_lossy()),
+            Some(("/tmp/12:notes.txt", None))
+        );
+        assert_eq!(
+            decode_editor("EDITOR:C:\\notes.txt"),
+            Some(("C:\\notes.txt", None))
+        );
+        assert_eq!(decode_editor("HEXEDITOR:/tmp/a"), None);
+
+        // Newlines are fine in names
+        let paths = [PathBuf::from("/tmp/a\nb.rs"), PathBuf::from("/tmp/c.rs")];
+        let marker = encode_editor_all(&paths);
+        assert_eq!(decode_editor(&marker.to_string_lossy()), None);
+        assert_eq!(
+            decode_editor_all(&marker.to_string_lossy()).as_deref(),
+            Some(&paths[..])
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_command_line_all() {
+        let paths = [PathBuf::from("/tmp/a b.rs"), PathBuf::from("/tmp/c.rs")];
+        assert_eq!(
+            command_line_all("nvim -p", &paths),
+            "nvim -p '/tmp/a b.rs' '/tmp/c.rs'"
+        );
+        assert_eq!(
+            command_line_all("code --wait {path}", &paths),
+            "code --wait '/tmp/a b.rs' '/tmp/c.rs'"
+        );
+        assert_eq!(command_line_args("nvim -p"), "nvim -p \"$@\"");
+        assert_eq!(
+            command_line_args("code --wait {path} -g {line}"),
+            "code --wait \"$@\" -g 1"
+        );
+    }
+}
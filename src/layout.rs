@@ -0,0 +1,217 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::list_selection::ListSelection;
+use crate::logging;
+
+/// Split positions kept between runs (with `remember_layout`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Layout {
+    /// Tree width in percent of the screen
+    pub split_position: u16,
+    /// Top of the bottom panel (results, bookmarks) in percent of the tree height
+    pub bottom_panel_split_position: u16,
+}
+
+impl Layout {
+    /// File the layout is saved to (~/.local/state/dtree/layout.toml on Linux)
+    pub fn file() -> Option<PathBuf> {
+        logging::state_dir().map(|dir| dir.join("layout.toml"))
+    }
+
+    /// Read a saved layout; None when there is none or it can't be parsed
+    pub fn load(path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(path).ok()?;
+        match toml::from_str(&content) {
+            Ok(layout) => Some(layout),
+            Err(e) => {
+                tracing::warn!("ignoring {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = toml::to_string(self).context("Failed to serialize layout")?;
+        fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(())
+    }
+}
+
+/// A window arrangement saved under a name: the splits and which panels show
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamedLayout {
+    pub name: String,
+    #[serde(flatten)]
+    pub layout: Layout,
+    pub show_files: bool,
+    pub show_sizes: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SavedLayouts {
+    #[serde(default)]
+    layouts: Vec<NamedLayout>,
+}
+
+/// Picker of the named layouts (`L`): restore one, save the current one under a name
+#[derive(Debug, Default)]
+pub struct LayoutPicker {
+    pub layouts: Vec<NamedLayout>,
+    pub is_open: bool,
+    pub selected_index: usize,
+    /// Name typed for the current layout (Some while naming it)
+    pub naming: Option<String>,
+    file_path: Option<PathBuf>, // Where the layouts are saved
+}
+
+impl LayoutPicker {
+    /// File the named layouts are saved to (~/.local/state/dtree/layouts.toml on Linux)
+    pub fn file() -> Option<PathBuf> {
+        logging::state_dir().map(|dir| dir.join("layouts.toml"))
+    }
+
+    /// Read the layouts saved in `path` (if any) and save changes there
+    pub fn load(&mut self, path: PathBuf) {
+        if let Ok(content) = fs::read_to_string(&path) {
+            match toml::from_str::<SavedLayouts>(&content) {
+                Ok(saved) => self.layouts = saved.layouts,
+                Err(e) => tracing::warn!("ignoring {}: {}", path.display(), e),
+            }
+        }
+        self.file_path = Some(path);
+    }
+
+    pub fn open(&mut self) {
+        self.selected_index = self
+            .selected_index
+            .min(self.layouts.len().saturating_sub(1));
+        self.naming = None;
+        self.is_open = true;
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+        self.naming = None;
+    }
+
+    /// Select the layout called `name`
+    pub fn select_name(&mut self, name: &str) -> bool {
+        match self.layouts.iter().position(|layout| layout.name == name) {
+            Some(index) => {
+                self.selected_index = index;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn selected(&self) -> Option<&NamedLayout> {
+        self.layouts.get(self.selected_index)
+    }
+
+    /// Save `layout`, replacing the one with the same name
+    pub fn save_as(&mut self, layout: NamedLayout) -> Result<()> {
+        match self
+            .layouts
+            .iter()
+            .position(|saved| saved.name == layout.name)
+        {
+            Some(index) => {
+                self.layouts[index] = layout;
+                self.selected_index = index;
+            }
+            None => {
+                self.layouts.push(layout);
+                self.selected_index = self.layouts.len() - 1;
+            }
+        }
+        self.save()
+    }
+
+    /// Delete the selected layout; returns its name
+    pub fn delete_selected(&mut self) -> Result<Option<String>> {
+        if self.selected_index >= self.layouts.len() {
+            return Ok(None);
+        }
+        let removed = self.layouts.remove(self.selected_index);
+        self.selected_index = self
+            .selected_index
+            .min(self.layouts.len().saturating_sub(1));
+        self.save()?;
+        Ok(Some(removed.name))
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.file_path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let saved = SavedLayouts {
+            layouts: self.layouts.clone(),
+        };
+        let content = toml::to_string(&saved).context("Failed to serialize layouts")?;
+        fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(())
+    }
+}
+
+impl ListSelection for LayoutPicker {
+    fn selected_index_mut(&mut self) -> &mut usize {
+        &mut self.selected_index
+    }
+
+    fn entry_count(&self) -> usize {
+        self.layouts.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(name: &str, split_position: u16, show_files: bool) -> NamedLayout {
+        NamedLayout {
+            name: name.to_string(),
+            layout: Layout {
+                split_position,
+                bottom_panel_split_position: 70,
+            },
+            show_files,
+            show_sizes: false,
+        }
+    }
+
+    #[test]
+    fn test_named_layouts_are_saved_and_replaced() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let file = temp_dir.path().join("layouts.toml");
+
+        let mut picker = LayoutPicker::default();
+        picker.load(file.clone());
+        picker.save_as(named("browse", 50, false)).unwrap();
+        picker.save_as(named("log-review", 20, true)).unwrap();
+        // Saving under a taken name replaces that layout
+        picker.save_as(named("browse", 40, false)).unwrap();
+        assert_eq!(picker.selected_index, 0);
+
+        let mut loaded = LayoutPicker::default();
+        loaded.load(file.clone());
+        assert_eq!(
+            loaded.layouts,
+            [named("browse", 40, false), named("log-review", 20, true)]
+        );
+
+        assert_eq!(loaded.delete_selected().unwrap().as_deref(), Some("browse"));
+        let mut reloaded = LayoutPicker::default();
+        reloaded.load(file);
+        assert_eq!(reloaded.layouts, [named("log-review", 20, true)]);
+    }
+}
@@ -1,15 +1,112 @@
-// Export modules for testing
+//! dtree's engine, for tools that want its tree without the TUI
+//!
+//! The stable API is what the crate root re-exports:
+//! - [`Navigation`] (opened with [`Navigation::builder`]): a lazily loaded tree with
+//!   expand/collapse, filters and selection; [`Navigation::visible_nodes`] iterates the
+//!   rows in display order
+//! - [`Tree`], [`TreeNode`] and [`NodeId`]: the nodes of a tree
+//! - [`SearchStream`]: a name search running in the background, as an iterator of matches;
+//!   [`ScanOptions`] and its [`FilterSet`] say what it skips, like the tree, and its
+//!   [`Excludes`] what it doesn't go into
+//! - [`dir_size()`] (or [`dir_size_excluding()`]) and [`DirSizeCache`]: directory sizes,
+//!   computed directly or on a worker
+//! - [`VfsProvider`] and [`Vfs`]: where trees, searches and sizes read from
+//! - [`DtreeWidget`] and [`DtreeState`]: a ratatui `StatefulWidget` showing a tree pane,
+//!   with [`DtreeState::handle_event`] for the key and mouse events it gets
+//!
+//! ```no_run
+//! use dtree_tui::{dir_size, vfs, Navigation, ScanOptions, SearchStream};
+//!
+//! let mut nav = Navigation::builder(".").show_files(true).build()?;
+//! nav.toggle_node(std::path::Path::new("./src"), true)?;
+//! for node in nav.visible_nodes() {
+//!     println!("{}{}", "  ".repeat(node.depth), node.name);
+//! }
+//!
+//! let options = ScanOptions::default();
+//! for result in SearchStream::start(vfs::local(), ".".as_ref(), "main", false, true, options) {
+//!     println!("{}", result.path.display());
+//! }
+//! let (bytes, _partial) = dir_size(vfs::local().as_ref(), ".".as_ref(), false);
+//! # let _ = bytes;
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+//! The other modules make up the dtree application; they are public for its tests and
+//! may change in any release.
+
+pub use dir_size::{dir_size, dir_size_excluding, DirSizeCache};
+pub use filter_set::{Excludes, FilterSet};
+pub use navigation::{Navigation, NavigationBuilder};
+pub use scan::ScanOptions;
+pub use search::{SearchResult, SearchStream};
+pub use tree_node::{NodeId, Tree, TreeNode};
+pub use vfs::{Vfs, VfsMetadata, VfsProvider};
+pub use widget::{DtreeOutcome, DtreeState, DtreeWidget};
+
+pub mod actions;
 pub mod bookmarks;
+pub mod clipboard;
+pub mod compare;
+pub mod completion;
 pub mod config;
+pub mod config_migration;
+pub mod content_search;
+pub mod copy_path;
+pub mod crash;
 pub mod dir_size;
+pub mod driver;
+pub mod drives;
 pub mod event_handler;
 pub mod file_icons;
+pub mod file_ops;
 pub mod file_viewer;
+pub mod filter_set;
+pub mod flat_listing;
+pub mod format;
+pub mod fuzzy_jump;
+pub mod git;
+pub mod goto;
+pub mod highlight_cache;
+pub mod i18n;
+pub mod idle;
+pub mod key_sequences;
+pub mod keymap;
+pub mod launcher;
+pub mod layout;
+pub mod list_selection;
+pub mod logging;
+pub mod lru_cache;
+pub mod marks;
+pub mod mime;
+pub mod multiplexer;
 pub mod navigation;
+pub mod notifications;
+pub mod nvim;
+pub mod oplog;
+pub mod patch;
+pub mod picker;
+pub mod platform;
+pub mod plugins;
+pub mod preview_cache;
+pub mod preview_loader;
+pub mod profile;
+pub mod projects;
+pub mod quick_filter;
+pub mod quickfix;
+#[cfg(feature = "s3")]
+pub mod s3;
+pub mod scan;
 pub mod search;
+pub mod tasks;
 pub mod theme;
+pub mod tree_export;
 pub mod tree_node;
 pub mod ui;
+pub mod vfs;
+pub mod viewer_state;
+pub mod wakeup;
+pub mod widget;
 
 // Re-export app module (not public but tests need access)
 pub mod app;
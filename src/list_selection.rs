@@ -0,0 +1,32 @@
+/// A list with a selected entry, shown in a picker or panel: ↑↓ move the selection,
+/// a digit picks an entry by its number
+pub trait ListSelection {
+    /// Index of the selected entry
+    fn selected_index_mut(&mut self) -> &mut usize;
+
+    /// Number of entries in the list
+    fn entry_count(&self) -> usize;
+
+    fn move_up(&mut self) {
+        let index = self.selected_index_mut();
+        *index = index.saturating_sub(1);
+    }
+
+    fn move_down(&mut self) {
+        let count = self.entry_count();
+        let index = self.selected_index_mut();
+        if *index + 1 < count {
+            *index += 1;
+        }
+    }
+
+    /// Select an entry by its 1-based number (as shown in the list)
+    fn select_number(&mut self, number: usize) -> bool {
+        if number >= 1 && number <= self.entry_count() {
+            *self.selected_index_mut() = number - 1;
+            true
+        } else {
+            false
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use anyhow::{Context, Result};
+use std::fs::{self, OpenOptions};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::EnvFilter;
+
+/// Level for dtree's own messages when neither --log-level nor RUST_LOG is set
+const DEFAULT_LEVEL: &str = "warn";
+
+/// A log bigger than this is moved to dtree.log.old at startup
+const MAX_LOG_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Directory for the debug log and crash reports
+/// (~/.local/state/dtree on Linux; the local data dir elsewhere)
+pub fn state_dir() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|dir| dir.join("dtree"))
+}
+
+pub fn log_file() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("dtree.log"))
+}
+
+/// Filter directives: --log-level wins over RUST_LOG, which wins over the default
+/// --log-level only raises dtree's own verbosity; dependencies stay at warn
+fn filter_directives(level: Option<&str>, rust_log: Option<&str>) -> String {
+    match (level, rust_log) {
+        (Some("off"), _) => "off".to_string(),
+        (Some(level), _) => format!("warn,dtree={0},dtree_tui={0}", level),
+        (None, Some(rust_log)) if !rust_log.trim().is_empty() => rust_log.to_string(),
+        _ => format!("warn,dtree={0},dtree_tui={0}", DEFAULT_LEVEL),
+    }
+}
+
+/// Start writing tracing events and span timings to the log file
+/// Returns the log file path
+pub fn init(level: Option<&str>) -> Result<PathBuf> {
+    let path = log_file().context("Could not find state directory")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+
+    // Keep one previous log instead of growing forever
+    if fs::metadata(&path).is_ok_and(|meta| meta.len() > MAX_LOG_SIZE) {
+        let _ = fs::rename(&path, path.with_extension("log.old"));
+    }
+
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+
+    let rust_log = std::env::var("RUST_LOG").ok();
+    let filter = EnvFilter::try_new(filter_directives(level, rust_log.as_deref()))
+        .or_else(|_| EnvFilter::try_new(filter_directives(None, None)))?;
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(Mutex::new(file))
+        .with_ansi(false)
+        .with_span_events(FmtSpan::CLOSE)
+        .try_init()
+        .map_err(|e| anyhow::anyhow!(e))?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_directives() {
+        assert_eq!(
+            filter_directives(Some("debug"), Some("trace")),
+            "warn,dtree=debug,dtree_tui=debug"
+        );
+        assert_eq!(filter_directives(Some("off"), None), "off");
+        assert_eq!(
+            filter_directives(None, Some("dtree::search=trace")),
+            "dtree::search=trace"
+        );
+        assert_eq!(
+            filter_directives(None, Some(" ")),
+            "warn,dtree=warn,dtree_tui=warn"
+        );
+        assert_eq!(
+            filter_directives(None, None),
+            "warn,dtree=warn,dtree_tui=warn"
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::VecDeque;
+
+/// A small cache keeping the `capacity` most recently used entries
+/// Entries are looked up by a linear scan, which beats hashing at a few dozen of them
+#[derive(Debug)]
+pub struct LruCache<K, V> {
+    entries: VecDeque<(K, V)>, // Most recently used first
+    capacity: usize,
+}
+
+impl<K: PartialEq, V: Clone> LruCache<K, V> {
+    pub const fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Number of entries kept (0 turns the cache off), dropping the oldest ones beyond it
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.entries.truncate(capacity);
+    }
+
+    /// The value cached for `key`, which becomes the most recently used one
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        let index = self.entries.iter().position(|(cached, _)| cached == key)?;
+        let entry = self.entries.remove(index)?;
+        let value = entry.1.clone();
+        self.entries.push_front(entry);
+        Some(value)
+    }
+
+    /// Cache `value` for `key`, dropping the least recently used entry when full
+    pub fn insert(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.entries.retain(|(cached, _)| cached != &key);
+        self.entries.push_front((key, value));
+        self.entries.truncate(self.capacity);
+    }
+
+    /// Keep only the entries whose key passes `keep`
+    pub fn retain(&mut self, mut keep: impl FnMut(&K) -> bool) {
+        self.entries.retain(|(cached, _)| keep(cached));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_least_recently_used_entry_goes_first() {
+        let mut cache = LruCache::new(2);
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        // Looking "a" up makes "b" the oldest
+        assert_eq!(cache.get(&"a"), Some(1));
+        cache.insert("c", 3);
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.get(&"a"), Some(1));
+        assert_eq!(cache.get(&"c"), Some(3));
+
+        // Inserting a key again replaces its value
+        cache.insert("a", 10);
+        assert_eq!(cache.get(&"a"), Some(10));
+        cache.retain(|key| *key != "a");
+        assert_eq!(cache.get(&"a"), None);
+
+        cache.set_capacity(0);
+        cache.insert("d", 4);
+        assert_eq!(cache.get(&"c"), None);
+        assert_eq!(cache.get(&"d"), None);
+    }
+}
@@ -1,26 +1,83 @@
+mod actions;
 mod app;
 mod bookmarks;
+mod clipboard;
+mod compare;
+mod completion;
 mod config;
+mod config_migration;
+mod content_search;
+mod copy_path;
+mod crash;
 mod dir_size;
+mod drives;
 mod event_handler;
 mod file_icons;
+mod file_ops;
 mod file_viewer;
+mod filter_set;
+mod flat_listing;
+mod format;
+mod fuzzy_jump;
+mod git;
+mod goto;
+mod highlight_cache;
+mod i18n;
+mod idle;
+mod key_sequences;
+mod keymap;
+mod launcher;
+mod layout;
+mod list_selection;
+mod logging;
+mod lru_cache;
+mod marks;
+mod mime;
+mod multiplexer;
 mod navigation;
+mod notifications;
+mod nvim;
+mod oplog;
+mod patch;
+mod picker;
 mod platform;
+mod plugins;
+mod preview_cache;
+mod preview_loader;
+mod profile;
+mod projects;
+mod quick_filter;
+mod quickfix;
+#[cfg(feature = "s3")]
+mod s3;
+mod scan;
 mod search;
+mod tasks;
 mod terminal;
 mod theme;
+mod tree_export;
 mod tree_node;
 mod ui;
+mod vfs;
+mod viewer_state;
+mod wakeup;
+#[allow(dead_code)] // The pane for other applications; dtree draws its own tree
+mod widget;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use app::App;
 use bookmarks::Bookmarks;
 use clap::Parser;
-use config::Config;
-use platform::{canonicalize_and_normalize, open_external_program};
-use std::path::PathBuf;
-use terminal::{cleanup_terminal, run_app, setup_terminal};
+use config::{Config, Overrides};
+use format::Formatting;
+use picker::PickMode;
+use platform::{canonicalize_and_normalize, open_external_program, open_external_program_all};
+use ratatui::{backend::CrosstermBackend, Terminal};
+use std::collections::BTreeMap;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use terminal::{cleanup_terminal, resume_terminal, run_app, setup_terminal};
+use theme::ThemeConfig;
 
 #[derive(Parser)]
 #[command(name = "dtree")]
@@ -40,6 +97,132 @@ struct Args {
     #[arg(long = "version")]
     version: bool,
 
+    /// Print the effective keybindings and their conflicts
+    #[arg(long = "keys")]
+    keys: bool,
+
+    /// Picker mode for scripts: print the chosen path(s) and nothing else (file, dir, multi)
+    #[arg(
+        long = "pick",
+        value_name = "MODE",
+        value_parser = ["file", "dir", "multi"],
+        conflicts_with_all = ["view", "bookmark_mode"]
+    )]
+    pick: Option<String>,
+
+    /// Write the directory to cd into to FILE instead of stdout (ranger/lf compatible)
+    #[arg(long = "choosedir", value_name = "FILE")]
+    choosedir: Option<PathBuf>,
+
+    /// Choose files: Enter on a file writes the marked (or selected) files to FILE
+    #[arg(
+        long = "choosefiles",
+        value_name = "FILE",
+        conflicts_with_all = ["pick", "view", "bookmark_mode"]
+    )]
+    choosefiles: Option<PathBuf>,
+
+    /// Lock navigation, search and bookmarks to the starting directory
+    #[arg(long = "restrict", conflicts_with = "bookmark_mode")]
+    restrict: bool,
+
+    /// Read newline-separated paths from stdin and browse them as a tree
+    #[arg(long = "stdin", conflicts_with_all = ["view", "bookmark_mode", "args"])]
+    stdin: bool,
+
+    /// Compare the current directory with DIR (a path or bookmark) as one merged tree
+    #[arg(
+        long = "compare",
+        value_name = "DIR",
+        conflicts_with_all = ["view", "bookmark_mode", "stdin", "pick", "choosefiles", "args"]
+    )]
+    compare: Option<String>,
+
+    /// Browse an S3 bucket (s3://BUCKET/PREFIX) as a tree, using the AWS_* environment variables
+    #[cfg(feature = "s3")]
+    #[arg(
+        long = "s3",
+        value_name = "LOCATION",
+        conflicts_with_all = ["view", "bookmark_mode", "stdin", "compare", "pick", "choosefiles", "args"]
+    )]
+    s3: Option<String>,
+
+    /// Exiting with q prints the marked paths, one per line, instead of the cd target
+    #[arg(long = "print-marked", conflicts_with_all = ["view", "bookmark_mode", "pick", "args"])]
+    print_marked: bool,
+
+    /// End each path printed by --pick or --print-marked with NUL instead of a newline
+    #[arg(long = "print0")]
+    print0: bool,
+
+    /// Start with files shown in the tree and the file viewer
+    #[arg(long = "show-files", conflicts_with = "bookmark_mode")]
+    show_files: bool,
+
+    /// Show hidden files this run (--show-hidden=false hides them)
+    #[arg(
+        long = "show-hidden",
+        value_name = "BOOL",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    show_hidden: Option<bool>,
+
+    /// Start in the fullscreen viewer on the --select file, or the first file of the tree
+    #[arg(
+        long = "fullscreen",
+        conflicts_with_all = ["bookmark_mode", "pick", "choosefiles"]
+    )]
+    fullscreen: bool,
+
+    /// Start with directory sizes shown
+    #[arg(long = "sizes", conflicts_with = "bookmark_mode")]
+    sizes: bool,
+
+    /// Color theme this run (a preset name or auto)
+    #[arg(long = "theme", value_name = "NAME", value_parser = parse_theme)]
+    theme: Option<String>,
+
+    /// No colors, only bold, reverse video and underlines (also when NO_COLOR is set)
+    #[arg(long = "no-color")]
+    no_color: bool,
+
+    /// Tree width in percent of the screen this run (20-80)
+    #[arg(long = "split", value_name = "PCT", value_parser = clap::value_parser!(u16).range(20..=80))]
+    split: Option<u16>,
+
+    /// Search, scan and size without going into NAME (repeatable; replaces [search] exclude)
+    #[arg(long = "exclude", value_name = "NAME", conflicts_with = "no_exclude")]
+    exclude: Vec<String>,
+
+    /// Search, scan and size everything, whatever [search] exclude says
+    #[arg(long = "no-exclude")]
+    no_exclude: bool,
+
+    /// Start with PATH selected and its parent directories expanded
+    #[arg(
+        long = "select",
+        value_name = "PATH",
+        conflicts_with_all = ["view", "bookmark_mode", "stdin", "compare"]
+    )]
+    select: Option<PathBuf>,
+
+    /// Debug log verbosity (off, error, warn, info, debug, trace); overrides RUST_LOG
+    #[arg(
+        long = "log-level",
+        value_name = "LEVEL",
+        value_parser = ["off", "error", "warn", "info", "debug", "trace"]
+    )]
+    log_level: Option<String>,
+
+    /// Print how long config load, tree build and the first frame took, then exit
+    #[arg(
+        long = "profile-startup",
+        conflicts_with_all = ["view", "bookmark_mode", "pick", "choosefiles"]
+    )]
+    profile_startup: bool,
+
     /// Bookmark management mode (use: -bm, -bm add <name> [path], -bm remove <name>, -bm list)
     #[arg(long = "bm")]
     bookmark_mode: bool,
@@ -49,23 +232,217 @@ struct Args {
     args: Vec<String>,
 }
 
-/// Open a file in the external editor specified in config
-fn open_in_editor(file_path: &str, config: &Config) -> Result<()> {
-    open_external_program(&config.behavior.editor, file_path)
+/// `--theme` value: "auto" or the name of a preset theme
+fn parse_theme(value: &str) -> Result<String, String> {
+    if value.eq_ignore_ascii_case("auto") || ThemeConfig::get_preset_theme(value).is_some() {
+        Ok(value.to_string())
+    } else {
+        Err(format!("unknown theme '{}'", value))
+    }
+}
+
+/// Config settings given on the command line
+fn overrides(args: &Args) -> Overrides {
+    Overrides {
+        theme: args.theme.clone(),
+        show_hidden: args.show_hidden,
+        split_position: args.split,
+        exclude: if args.no_exclude {
+            Some(Vec::new())
+        } else {
+            (!args.exclude.is_empty()).then(|| args.exclude.clone())
+        },
+        no_color: args.no_color || no_color_env(),
+    }
+}
+
+/// NO_COLOR (https://no-color.org) is set to something
+fn no_color_env() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+}
+
+/// Path to start with selected (`--select`), resolved against the current directory
+fn selected_path(args: &Args) -> Result<Option<PathBuf>> {
+    let Some(path) = &args.select else {
+        return Ok(None);
+    };
+    let resolved = canonicalize_and_normalize(path)
+        .with_context(|| format!("--select: cannot open {}", path.display()))?;
+    Ok(Some(resolved))
+}
+
+/// Directory to start in: `dir`, or the parent of the `--select` path when that is
+/// outside of it
+fn start_dir(dir: PathBuf, selected: Option<&Path>) -> Result<PathBuf> {
+    let Some(selected) = selected else {
+        return Ok(dir);
+    };
+    let dir = canonicalize_and_normalize(&dir)?;
+    if selected.starts_with(&dir) {
+        Ok(dir)
+    } else {
+        Ok(selected.parent().unwrap_or(selected).to_path_buf())
+    }
+}
+
+/// Apply the initial state given on the command line (`--select`, `--show-files`,
+/// `--sizes`, `--fullscreen`)
+fn apply_startup_flags(app: &mut App, args: &Args, selected: Option<&Path>) -> Result<()> {
+    if let Some(path) = selected {
+        app.set_selection(path)?;
+    }
+    if args.show_files {
+        app.set_show_files()?;
+    }
+    if args.sizes {
+        app.set_show_sizes();
+    }
+    if args.fullscreen {
+        app.set_fullscreen()?;
+    }
+    Ok(())
+}
+
+/// Open a file in the external editor specified in config (at `line`, when known)
+fn open_in_editor(file_path: &str, line: Option<usize>, config: &Config) -> Result<()> {
+    open_external_program(&config.behavior.editor, file_path, line)
 }
 
 /// Open a binary file in the external hex editor specified in config
 fn open_in_hex_editor(file_path: &str, config: &Config) -> Result<()> {
-    open_external_program(&config.behavior.hex_editor, file_path)
+    open_external_program(&config.behavior.hex_editor, file_path, None)
 }
 
 /// Open a directory in the external file manager specified in config
 fn open_in_file_manager(dir_path: &str, config: &Config) -> Result<()> {
-    open_external_program(&config.behavior.file_manager, dir_path)
+    open_external_program(&config.behavior.file_manager, dir_path, None)
+}
+
+/// Open several files with a single editor invocation
+fn open_all_in_editor(paths: &[PathBuf], config: &Config) -> Result<()> {
+    open_external_program_all(&config.behavior.editor, paths)
+}
+
+/// Run the external program a return marker asks for (editor, hex editor, file manager)
+/// Returns None for other markers
+fn open_program(marker: &str, config: &Config) -> Option<Result<()>> {
+    if let Some((file_path, line)) = launcher::decode_editor(marker) {
+        Some(open_in_editor(file_path, line, config))
+    } else if let Some(paths) = launcher::decode_editor_all(marker) {
+        Some(open_all_in_editor(&paths, config))
+    } else if let Some(file_path) = marker.strip_prefix("HEXEDITOR:") {
+        Some(open_in_hex_editor(file_path, config))
+    } else {
+        marker
+            .strip_prefix("FILEMGR:")
+            .map(|dir_path| open_in_file_manager(dir_path, config))
+    }
+}
+
+/// Run the TUI until it exits
+/// With `return_after_editor`, external programs run in between and dtree resumes with
+/// the same tree, selection and viewer afterwards
+fn run_tui(
+    terminal: &mut Terminal<CrosstermBackend<std::io::Stderr>>,
+    app: &mut App,
+    config: &Config,
+) -> Result<Option<PathBuf>> {
+    let result = loop {
+        let result = run_app(terminal, app)?;
+        let marker = match &result {
+            Some(path) if config.behavior.return_after_editor => path.to_string_lossy().to_string(),
+            _ => break result,
+        };
+        let is_program = launcher::decode_editor(&marker).is_some()
+            || launcher::decode_editor_all(&marker).is_some()
+            || marker.starts_with("HEXEDITOR:")
+            || marker.starts_with("FILEMGR:");
+        if !is_program {
+            break result;
+        }
+
+        cleanup_terminal()?;
+        let error = open_program(&marker, config).and_then(|opened| opened.err());
+        resume_terminal(terminal)?;
+        if let (None, Some(paths)) = (&error, launcher::decode_editor_all(&marker)) {
+            app.unmark(&paths);
+        }
+        app.resume_after_program(error.map(|e| e.to_string()));
+    };
+
+    // Not worth failing the exit (and losing the cd) over
+    if let Err(e) = app.save_layout() {
+        tracing::warn!("cannot save layout: {:#}", e);
+    }
+    if let Err(e) = app.save_view_states() {
+        tracing::warn!("cannot save viewer state: {:#}", e);
+    }
+    Ok(result)
+}
+
+/// Output the directory to cd into: to the --choosedir file if given, otherwise stdout
+fn output_dir(path: &Path, choosedir: Option<&Path>) -> Result<()> {
+    match choosedir {
+        Some(file) => write_chooser_file(file, std::slice::from_ref(&path.to_path_buf())),
+        None => {
+            println!("{}", path.display());
+            Ok(())
+        }
+    }
+}
+
+/// Write the directory being browsed to the --choosedir file, for exits without
+/// a cd target (Esc, editor, printed paths): the file is written on every exit
+fn choose_browsed_dir(choosedir: Option<&Path>, app: &App) -> Result<()> {
+    match choosedir {
+        Some(file) => write_chooser_file(file, &[app.root_path()]),
+        None => Ok(()),
+    }
+}
+
+/// Print picked or marked paths, one per line or NUL-terminated (`--print0`)
+fn print_paths(paths: &[PathBuf], print0: bool) {
+    let end = if print0 { '\0' } else { '\n' };
+    for path in paths {
+        print!("{}{}", path.display(), end);
+    }
+}
+
+/// Write paths to a chooser file, one per line
+fn write_chooser_file(file: &Path, paths: &[PathBuf]) -> Result<()> {
+    let content: String = paths
+        .iter()
+        .map(|path| format!("{}\n", path.display()))
+        .collect();
+    std::fs::write(file, content).with_context(|| format!("Failed to write {}", file.display()))?;
+    Ok(())
+}
+
+/// Read a newline-separated path list from stdin (e.g. output of `fd` or `find`)
+/// Relative paths are resolved against the current directory
+fn read_stdin_paths() -> Result<Vec<PathBuf>> {
+    let cwd = std::env::current_dir()?;
+    let mut paths = Vec::new();
+    for line in std::io::stdin().lock().lines() {
+        let line = line?;
+        let line = line.trim_end_matches('\r');
+        if !line.is_empty() {
+            paths.push(cwd.join(line));
+        }
+    }
+
+    if paths.is_empty() {
+        anyhow::bail!("--stdin: no paths received on standard input");
+    }
+    Ok(paths)
 }
 
 /// Resolve path or bookmark name to a PathBuf
-fn resolve_path_or_bookmark(input: &str, bookmarks: &Bookmarks) -> Result<PathBuf> {
+fn resolve_path_or_bookmark(
+    input: &str,
+    bookmarks: &Bookmarks,
+    workspaces: &BTreeMap<String, String>,
+) -> Result<PathBuf> {
     // Windows-specific: Handle bare drive letters (e.g., "C:", "E:")
     // Convert "C:" to "C:\" to navigate to the root of the drive
     #[cfg(windows)]
@@ -85,6 +462,16 @@ fn resolve_path_or_bookmark(input: &str, bookmarks: &Bookmarks) -> Result<PathBu
         }
     }
 
+    // 0. Workspace-relative path (@repo/src, @NAME/src)
+    if input.starts_with('@') && bookmarks.get(input).is_none() {
+        let path =
+            bookmarks::resolve_path(Path::new(input), &std::env::current_dir()?, workspaces)?;
+        if !path.exists() {
+            anyhow::bail!("Directory not found: {} ({})", input, path.display());
+        }
+        return Ok(canonicalize_and_normalize(&path)?);
+    }
+
     // 1. If looks like absolute path or contains path separator → treat as path
     if platform::is_absolute_path(input) || input.contains(std::path::MAIN_SEPARATOR) {
         let path = PathBuf::from(input);
@@ -96,14 +483,15 @@ fn resolve_path_or_bookmark(input: &str, bookmarks: &Bookmarks) -> Result<PathBu
 
     // 2. Check if it's a bookmark
     if let Some(bookmark) = bookmarks.get(input) {
-        if bookmark.path.exists() {
-            return Ok(bookmark.path.clone());
+        let path = bookmark.resolve(&std::env::current_dir()?, workspaces)?;
+        if path.exists() {
+            return Ok(path);
         } else {
             anyhow::bail!(
                 "Bookmark '{}' points to non-existent directory: {}\n\
                 Use 'dt -bm list' to see all bookmarks",
                 input,
-                bookmark.path.display()
+                path.display()
             );
         }
     }
@@ -124,6 +512,8 @@ fn resolve_path_or_bookmark(input: &str, bookmarks: &Bookmarks) -> Result<PathBu
 }
 
 fn main() -> Result<()> {
+    profile::start();
+
     // Preprocess arguments: convert -bm to --bm for clap compatibility
     let args: Vec<String> = std::env::args()
         .map(|arg| {
@@ -135,11 +525,21 @@ fn main() -> Result<()> {
         })
         .collect();
 
-    // Ensure config file exists (create if missing)
-    let config = Config::load()?;
-
     let args = Args::parse_from(args);
 
+    // Ensure config file exists (create if missing); loaded once, the app gets it
+    // with any upgrade notes
+    let config = profile::phase("config load", || Config::load_with(&overrides(&args)))?;
+
+    // UI language (a broken catalog leaves the UI in English)
+    let locales_dir =
+        Config::global_config_path().and_then(|path| path.parent().map(|dir| dir.join("locales")));
+    if let Err(e) = i18n::init(&config.appearance.locale, locales_dir.as_deref()) {
+        eprintln!("Warning: translations disabled: {:#}", e);
+    }
+    format::init(Formatting::from_config(&config.appearance));
+    clipboard::set_limit(config.behavior.clipboard_history_size);
+
     // Print version
     if args.version {
         println!("dtree {}", env!("CARGO_PKG_VERSION"));
@@ -155,6 +555,30 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
+    // Print the effective keymap
+    if args.keys {
+        for line in keymap::report(&config) {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
+
+    // Debug log file (best effort - dtree works without it)
+    let log_file = logging::init(args.log_level.as_deref());
+    if let (Err(e), Some(_)) = (&log_file, &args.log_level) {
+        eprintln!("Warning: debug log disabled: {:#}", e);
+    }
+
+    // Start the operation log clock (also records the invocation for bug reports)
+    oplog::record(format!(
+        "dtree {} started: {}",
+        env!("CARGO_PKG_VERSION"),
+        std::env::args().collect::<Vec<_>>().join(" ")
+    ));
+    if let Ok(path) = &log_file {
+        oplog::record(format!("Debug log: {}", path.display()));
+    }
+
     // Handle bookmark management mode
     if args.bookmark_mode {
         let mut bookmarks = Bookmarks::new()?;
@@ -189,6 +613,31 @@ fn main() -> Result<()> {
                     anyhow::bail!("Missing bookmark name\nUsage: dt -bm add <name> [path]");
                 }
                 let name = &args.args[1];
+
+                // Workspace-relative bookmarks are saved as written and resolved when used
+                if let Some(relative) = args.args.get(2).filter(|path| path.starts_with('@')) {
+                    let path = bookmarks::resolve_path(
+                        Path::new(relative),
+                        &std::env::current_dir()?,
+                        &config.workspaces,
+                    )?;
+                    if !path.is_dir() {
+                        anyhow::bail!("Not a directory: {} ({})", relative, path.display());
+                    }
+                    let dir_name = path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .map(|s| s.to_string());
+                    bookmarks.add(name.clone(), PathBuf::from(relative), dir_name)?;
+                    println!(
+                        "✓ Bookmark '{}' added: {} (here: {})",
+                        name,
+                        relative,
+                        path.display()
+                    );
+                    return Ok(());
+                }
+
                 let path = if args.args.len() >= 3 {
                     PathBuf::from(&args.args[2])
                 } else {
@@ -258,14 +707,93 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
+    // Path list for the virtual tree - must be read before the TUI takes over the terminal
+    let stdin_paths = if args.stdin {
+        Some(read_stdin_paths()?)
+    } else {
+        None
+    };
+
+    // Picker mode: the TUI only selects paths, stdout gets the picks and nothing else
+    // --choosefiles is the ranger-style variant that writes the picks to a file instead
+    let pick_mode = if args.choosefiles.is_some() {
+        Some(PickMode::Files)
+    } else {
+        args.pick.as_deref().and_then(PickMode::parse)
+    };
+    if let Some(mode) = pick_mode {
+        let start_path = match args.args.first() {
+            Some(input) => resolve_path_or_bookmark(input, &Bookmarks::new()?, &config.workspaces)?,
+            None => std::env::current_dir()?,
+        };
+        if !start_path.is_dir() {
+            anyhow::bail!("--pick requires a directory, got: {}", start_path.display());
+        }
+        let selected = selected_path(&args)?;
+        let start_path = start_dir(start_path, selected.as_deref())?;
+
+        let mut app = App::new_with(start_path, config.clone(), &overrides(&args))?;
+        if let Some(paths) = &stdin_paths {
+            app.set_virtual_tree(paths)?;
+        }
+        app.set_pick_mode(mode)?;
+        apply_startup_flags(&mut app, &args, selected.as_deref())?;
+        if args.restrict {
+            app.set_restricted()?;
+        }
+        let mut terminal = setup_terminal()?;
+        let result = run_tui(&mut terminal, &mut app, &config);
+        cleanup_terminal()?;
+
+        // Anything but a confirmed pick (Esc, q) counts as cancel
+        let picked = result?.as_deref().and_then(picker::decode);
+
+        // The directory being browsed, whether something was chosen or not
+        choose_browsed_dir(args.choosedir.as_deref(), &app)?;
+        if let Some(file) = &args.choosefiles {
+            // Like ranger: the file is only written when something was chosen
+            if let Some(paths) = picked {
+                write_chooser_file(file, &paths)?;
+            }
+            return Ok(());
+        }
+
+        match picked {
+            Some(paths) => {
+                print_paths(&paths, args.print0);
+                return Ok(());
+            }
+            None => std::process::exit(1),
+        }
+    }
+
+    // A bucket browsed as a tree: its objects aren't local files, so nothing is
+    // printed for the shell to cd into on exit
+    #[cfg(feature = "s3")]
+    if let Some(location) = &args.s3 {
+        let (bucket, prefix) = s3::parse_location(location)?;
+        let s3 = s3::S3Fs::from_env(&bucket)?;
+        let root = s3.path(&prefix);
+        let mut app = App::new_with(std::env::current_dir()?, config.clone(), &overrides(&args))?;
+        app.set_vfs_tree(std::sync::Arc::new(s3), root)
+            .with_context(|| format!("Cannot list {}", location))?;
+        apply_startup_flags(&mut app, &args, None)?;
+        let mut terminal = setup_terminal()?;
+        let result = run_tui(&mut terminal, &mut app, &config);
+        cleanup_terminal()?;
+        result?;
+        return Ok(());
+    }
+
     // If path or bookmark argument provided, resolve and output without entering TUI
-    if !args.args.is_empty() {
+    // (with --select or --profile-startup it is the directory to start in)
+    if !args.args.is_empty() && args.select.is_none() && !args.profile_startup {
         let input = &args.args[0];
 
         // Special case: -v flag with path/bookmark
         if args.view {
             let bookmarks = Bookmarks::new()?;
-            let start_path = resolve_path_or_bookmark(input, &bookmarks)?;
+            let start_path = resolve_path_or_bookmark(input, &bookmarks, &config.workspaces)?;
 
             if !start_path.is_file() {
                 anyhow::bail!("--view requires a file path, got: {}", start_path.display());
@@ -274,58 +802,103 @@ fn main() -> Result<()> {
             // Start app in fullscreen viewer mode
             let mut terminal = setup_terminal()?;
             let parent_dir = start_path.parent().unwrap_or(&start_path).to_path_buf();
-            let mut app = App::new(parent_dir)?;
+            let mut app = App::new_with(parent_dir, config.clone(), &overrides(&args))?;
 
             // Set fullscreen mode and load the file
             app.set_fullscreen_viewer(&start_path)?;
+            if args.restrict {
+                app.set_restricted()?;
+            }
 
-            let result = run_app(&mut terminal, &mut app);
+            let result = run_tui(&mut terminal, &mut app, &config);
             cleanup_terminal()?;
 
+            let mut chose_dir = false;
             if let Some(path) = result? {
                 let path_str = path.to_string_lossy();
-                if let Some(file_path) = path_str.strip_prefix("EDITOR:") {
-                    open_in_editor(file_path, &config)?;
-                } else if let Some(file_path) = path_str.strip_prefix("HEXEDITOR:") {
-                    open_in_hex_editor(file_path, &config)?;
-                } else if let Some(dir_path) = path_str.strip_prefix("FILEMGR:") {
-                    open_in_file_manager(dir_path, &config)?;
+                if let Some(opened) = open_program(&path_str, &config) {
+                    opened?;
+                } else if let Some(file_path) = path_str.strip_prefix("SELECT:") {
+                    println!("{}", file_path);
                 } else {
-                    println!("{}", path.display());
+                    output_dir(&path, args.choosedir.as_deref())?;
+                    chose_dir = true;
                 }
             }
+            if !chose_dir {
+                choose_browsed_dir(args.choosedir.as_deref(), &app)?;
+            }
             return Ok(());
         }
 
         // Normal case: resolve path/bookmark and output directly (no TUI)
         let bookmarks = Bookmarks::new()?;
-        let resolved_path = resolve_path_or_bookmark(input, &bookmarks)?;
+        let resolved_path = resolve_path_or_bookmark(input, &bookmarks, &config.workspaces)?;
 
         // Output path for bash wrapper to cd into
-        println!("{}", resolved_path.display());
+        output_dir(&resolved_path, args.choosedir.as_deref())?;
         return Ok(());
     }
 
-    // No arguments: launch interactive TUI from current directory
-    let start_path = std::env::current_dir()?;
-    let mut terminal = setup_terminal()?;
-    let mut app = App::new(start_path)?;
-    let result = run_app(&mut terminal, &mut app);
+    // Launch interactive TUI from the current or given directory (or the --stdin list)
+    if args.profile_startup {
+        profile::enable();
+    }
+    let selected = selected_path(&args)?;
+    let start_path = match args.args.first() {
+        Some(input) => resolve_path_or_bookmark(input, &Bookmarks::new()?, &config.workspaces)?,
+        None => std::env::current_dir()?,
+    };
+    if !start_path.is_dir() {
+        anyhow::bail!("{} is not a directory", start_path.display());
+    }
+    let start_path = start_dir(start_path, selected.as_deref())?;
+    let mut app = App::new_with(start_path, config.clone(), &overrides(&args))?;
+    if let Some(paths) = &stdin_paths {
+        app.set_virtual_tree(paths)?;
+    }
+    apply_startup_flags(&mut app, &args, selected.as_deref())?;
+    if args.restrict {
+        app.set_restricted()?;
+    }
+    if args.print_marked {
+        app.set_print_marked();
+    }
+    if let Some(input) = &args.compare {
+        let right = resolve_path_or_bookmark(input, &Bookmarks::new()?, &config.workspaces)?;
+        app.set_comparison(&right)?;
+    }
+    let mut terminal = profile::phase("terminal setup", setup_terminal)?;
+    let result = run_tui(&mut terminal, &mut app, &config);
 
     cleanup_terminal()?;
 
+    if args.profile_startup {
+        eprint!("{}", profile::report());
+    }
+
+    let mut chose_dir = false;
     if let Some(path) = result? {
         let path_str = path.to_string_lossy();
-        if let Some(file_path) = path_str.strip_prefix("EDITOR:") {
-            open_in_editor(file_path, &config)?;
-        } else if let Some(file_path) = path_str.strip_prefix("HEXEDITOR:") {
-            open_in_hex_editor(file_path, &config)?;
-        } else if let Some(dir_path) = path_str.strip_prefix("FILEMGR:") {
-            open_in_file_manager(dir_path, &config)?;
+        if let Some(opened) = open_program(&path_str, &config) {
+            opened?;
+        } else if let Some(file_path) = path_str.strip_prefix("SELECT:") {
+            println!("{}", file_path);
+        } else if let Some(lines) = quickfix::decode(&path_str) {
+            print!("{}", lines);
+        } else if let Some(tree) = tree_export::decode(&path_str) {
+            print!("{}", tree);
+        } else if let Some(paths) = picker::decode(&path) {
+            // Marked paths on exit (print_marked_on_exit)
+            print_paths(&paths, args.print0);
         } else {
-            println!("{}", path.display());
+            output_dir(&path, args.choosedir.as_deref())?;
+            chose_dir = true;
         }
     }
+    if !chose_dir {
+        choose_browsed_dir(args.choosedir.as_deref(), &app)?;
+    }
 
     Ok(())
 }
@@ -0,0 +1,85 @@
+use crate::list_selection::ListSelection;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Panel listing the marked entries, those outside the current root included
+#[derive(Debug, Default)]
+pub struct MarksPanel {
+    pub is_open: bool,
+    pub selected_index: usize,
+    pub entries: Vec<PathBuf>, // Sorted by path
+}
+
+impl MarksPanel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the panel with the current marks
+    pub fn open(&mut self, marked: &HashSet<PathBuf>) {
+        self.entries = marked.iter().cloned().collect();
+        self.entries.sort();
+        self.selected_index = 0;
+        self.is_open = true;
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+        self.entries.clear();
+    }
+
+    pub fn get_selected(&self) -> Option<&Path> {
+        self.entries.get(self.selected_index).map(PathBuf::as_path)
+    }
+
+    /// Take the selected entry out of the list, keeping the selection in range
+    pub fn remove_selected(&mut self) -> Option<PathBuf> {
+        if self.selected_index >= self.entries.len() {
+            return None;
+        }
+        let path = self.entries.remove(self.selected_index);
+        self.selected_index = self
+            .selected_index
+            .min(self.entries.len().saturating_sub(1));
+        Some(path)
+    }
+}
+
+impl ListSelection for MarksPanel {
+    fn selected_index_mut(&mut self) -> &mut usize {
+        &mut self.selected_index
+    }
+
+    fn entry_count(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panel_lists_marks_sorted_and_removes_them() {
+        let marked: HashSet<PathBuf> = ["/b/two", "/a/one", "/c/three"]
+            .into_iter()
+            .map(PathBuf::from)
+            .collect();
+        let mut panel = MarksPanel::new();
+        panel.open(&marked);
+        assert_eq!(panel.get_selected(), Some(Path::new("/a/one")));
+
+        panel.move_down();
+        panel.move_down();
+        panel.move_down();
+        assert_eq!(
+            panel.remove_selected().as_deref(),
+            Some(Path::new("/c/three"))
+        );
+        // The selection moves up to the new last entry
+        assert_eq!(panel.get_selected(), Some(Path::new("/b/two")));
+        panel.remove_selected();
+        panel.remove_selected();
+        assert_eq!(panel.remove_selected(), None);
+    }
+}
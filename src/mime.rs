@@ -0,0 +1,316 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Bytes read from the start of a file to detect its type
+pub const SAMPLE_SIZE: usize = 8192;
+
+/// Type of a file detected from its content
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileType {
+    pub mime: &'static str,
+    pub description: &'static str,
+    /// Shown as text in the viewer and opened in the text editor
+    pub is_text: bool,
+}
+
+impl FileType {
+    const fn binary(mime: &'static str, description: &'static str) -> Self {
+        Self {
+            mime,
+            description,
+            is_text: false,
+        }
+    }
+
+    const fn text(mime: &'static str, description: &'static str) -> Self {
+        Self {
+            mime,
+            description,
+            is_text: true,
+        }
+    }
+}
+
+/// Bytes expected at an offset from the start of the file
+type Magic = (usize, &'static [u8]);
+
+/// Magic numbers: all parts have to match
+const SIGNATURES: &[(&[Magic], FileType)] = &[
+    // Images
+    (
+        &[(0, b"\x89PNG\r\n\x1a\n")],
+        FileType::binary("image/png", "PNG image"),
+    ),
+    (
+        &[(0, b"\xff\xd8\xff")],
+        FileType::binary("image/jpeg", "JPEG image"),
+    ),
+    (
+        &[(0, b"GIF87a")],
+        FileType::binary("image/gif", "GIF image"),
+    ),
+    (
+        &[(0, b"GIF89a")],
+        FileType::binary("image/gif", "GIF image"),
+    ),
+    (
+        &[(0, b"RIFF"), (8, b"WEBP")],
+        FileType::binary("image/webp", "WebP image"),
+    ),
+    (
+        &[(0, b"\x00\x00\x01\x00")],
+        FileType::binary("image/vnd.microsoft.icon", "Icon"),
+    ),
+    // Documents and databases
+    (
+        &[(0, b"%PDF-")],
+        FileType::binary("application/pdf", "PDF document"),
+    ),
+    (
+        &[(0, b"SQLite format 3\x00")],
+        FileType::binary("application/vnd.sqlite3", "SQLite database"),
+    ),
+    // Archives and compressed data
+    (
+        &[(0, b"PK\x03\x04")],
+        FileType::binary("application/zip", "Zip archive"),
+    ),
+    (
+        &[(0, b"\x1f\x8b")],
+        FileType::binary("application/gzip", "Gzip compressed data"),
+    ),
+    (
+        &[(0, b"BZh")],
+        FileType::binary("application/x-bzip2", "Bzip2 compressed data"),
+    ),
+    (
+        &[(0, b"\xfd7zXZ\x00")],
+        FileType::binary("application/x-xz", "XZ compressed data"),
+    ),
+    (
+        &[(0, b"\x28\xb5\x2f\xfd")],
+        FileType::binary("application/zstd", "Zstandard compressed data"),
+    ),
+    (
+        &[(0, b"7z\xbc\xaf\x27\x1c")],
+        FileType::binary("application/x-7z-compressed", "7-Zip archive"),
+    ),
+    (
+        &[(0, b"Rar!\x1a\x07")],
+        FileType::binary("application/vnd.rar", "RAR archive"),
+    ),
+    (
+        &[(257, b"ustar")],
+        FileType::binary("application/x-tar", "Tar archive"),
+    ),
+    // Executables and libraries
+    (
+        &[(0, b"\x7fELF")],
+        FileType::binary("application/x-executable", "ELF executable / library"),
+    ),
+    (
+        &[(0, b"MZ")],
+        FileType::binary(
+            "application/vnd.microsoft.portable-executable",
+            "Windows executable / library",
+        ),
+    ),
+    (
+        &[(0, b"\xcf\xfa\xed\xfe")],
+        FileType::binary("application/x-mach-binary", "Mach-O executable"),
+    ),
+    (
+        &[(0, b"\xce\xfa\xed\xfe")],
+        FileType::binary("application/x-mach-binary", "Mach-O executable"),
+    ),
+    (
+        &[(0, b"\x00asm")],
+        FileType::binary("application/wasm", "WebAssembly module"),
+    ),
+    // Audio and video
+    (&[(0, b"ID3")], FileType::binary("audio/mpeg", "MP3 audio")),
+    (
+        &[(0, b"fLaC")],
+        FileType::binary("audio/flac", "FLAC audio"),
+    ),
+    (&[(0, b"OggS")], FileType::binary("audio/ogg", "Ogg audio")),
+    (
+        &[(0, b"RIFF"), (8, b"WAVE")],
+        FileType::binary("audio/wav", "WAV audio"),
+    ),
+    (&[(4, b"ftyp")], FileType::binary("video/mp4", "MP4 video")),
+    (
+        &[(0, b"\x1a\x45\xdf\xa3")],
+        FileType::binary("video/x-matroska", "Matroska / WebM video"),
+    ),
+    // Fonts
+    (
+        &[(0, b"\x00\x01\x00\x00\x00")],
+        FileType::binary("font/ttf", "TrueType font"),
+    ),
+    (
+        &[(0, b"OTTO")],
+        FileType::binary("font/otf", "OpenType font"),
+    ),
+    (&[(0, b"wOFF")], FileType::binary("font/woff", "WOFF font")),
+    (
+        &[(0, b"wOF2")],
+        FileType::binary("font/woff2", "WOFF2 font"),
+    ),
+    // Text the viewer can't decode
+    (
+        &[(0, b"\xff\xfe")],
+        FileType::binary("text/plain; charset=utf-16le", "UTF-16 text"),
+    ),
+    (
+        &[(0, b"\xfe\xff")],
+        FileType::binary("text/plain; charset=utf-16be", "UTF-16 text"),
+    ),
+];
+
+/// Interpreters named in a `#!` line, with the type of the script
+const INTERPRETERS: &[(&str, FileType)] = &[
+    ("sh", FileType::text("text/x-shellscript", "Shell script")),
+    ("bash", FileType::text("text/x-shellscript", "Shell script")),
+    ("zsh", FileType::text("text/x-shellscript", "Shell script")),
+    ("dash", FileType::text("text/x-shellscript", "Shell script")),
+    ("fish", FileType::text("text/x-shellscript", "Shell script")),
+    ("python", FileType::text("text/x-python", "Python script")),
+    ("python3", FileType::text("text/x-python", "Python script")),
+    ("perl", FileType::text("text/x-perl", "Perl script")),
+    ("ruby", FileType::text("text/x-ruby", "Ruby script")),
+    ("node", FileType::text("text/javascript", "JavaScript")),
+    ("lua", FileType::text("text/x-lua", "Lua script")),
+];
+
+const PLAIN_TEXT: FileType = FileType::text("text/plain", "Text");
+const SCRIPT: FileType = FileType::text("text/x-script", "Script");
+const XML: FileType = FileType::text("text/xml", "XML document");
+const UNKNOWN: FileType = FileType::binary("application/octet-stream", "Binary data");
+
+fn matches(sample: &[u8], parts: &[Magic]) -> bool {
+    parts
+        .iter()
+        .all(|(offset, magic)| sample.get(*offset..offset + magic.len()) == Some(*magic))
+}
+
+fn is_printable(bytes: &[u8]) -> bool {
+    bytes.iter().all(|&b| b.is_ascii_graphic() || b == b' ')
+}
+
+/// Check if the sample reads as text: no NULL bytes and hardly any control characters
+/// Non-UTF-8 text (Latin-1 etc.) counts too
+fn looks_like_text(sample: &[u8]) -> bool {
+    if sample.contains(&0) {
+        return false;
+    }
+    let control = sample
+        .iter()
+        .filter(|&&b| b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0c | 0x1b))
+        .count();
+    control * 100 <= sample.len()
+}
+
+/// Type of a script from its `#!` line (`#!/usr/bin/env python3` -> Python)
+fn script_type(first_line: &str) -> FileType {
+    let mut words = first_line.split_whitespace();
+    let mut program = words.next().unwrap_or_default();
+    if program.rsplit('/').next() == Some("env") {
+        program = words
+            .find(|word| !word.starts_with('-'))
+            .unwrap_or_default();
+    }
+    let name = program.rsplit('/').next().unwrap_or_default();
+    INTERPRETERS
+        .iter()
+        .find(|(interpreter, _)| *interpreter == name)
+        .map(|&(_, file_type)| file_type)
+        .unwrap_or(SCRIPT)
+}
+
+/// Detect the type from the first bytes of a file
+/// Signatures made of printable characters could start a text file too, so they only
+/// decide the type of content that isn't text
+pub fn detect(sample: &[u8]) -> FileType {
+    let is_text = looks_like_text(sample);
+    let signature = SIGNATURES.iter().find(|(parts, _)| {
+        matches(sample, parts) && (!is_text || parts.iter().any(|(_, magic)| !is_printable(magic)))
+    });
+    if let Some(&(_, file_type)) = signature {
+        return file_type;
+    }
+    if !is_text {
+        return UNKNOWN;
+    }
+
+    let text = String::from_utf8_lossy(sample);
+    let text = text.trim_start_matches('\u{feff}');
+    if let Some(first_line) = text.strip_prefix("#!") {
+        script_type(first_line.lines().next().unwrap_or_default())
+    } else if text.starts_with("<?xml") {
+        XML
+    } else {
+        PLAIN_TEXT
+    }
+}
+
+/// Detect the type of the file at `path` from its content
+/// Returns None when the file can't be read
+pub fn detect_file(path: &Path) -> Option<FileType> {
+    detect_file_sample(path, SAMPLE_SIZE)
+}
+
+/// Like `detect_file`, sniffing the first `sample_size` bytes
+/// FIFOs, sockets and devices are never opened (reading them could block)
+pub fn detect_file_sample(path: &Path, sample_size: usize) -> Option<FileType> {
+    if !std::fs::metadata(path).ok()?.is_file() {
+        return None;
+    }
+    let file = File::open(path).ok()?;
+    let mut sample = Vec::with_capacity(sample_size);
+    file.take(sample_size as u64)
+        .read_to_end(&mut sample)
+        .ok()?;
+    Some(detect(&sample))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_binary_formats() {
+        let png = detect(b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR");
+        assert_eq!(png.mime, "image/png");
+        assert!(!png.is_text);
+
+        let mut tar = vec![b'a'; 300];
+        tar[257..262].copy_from_slice(b"ustar");
+        tar[100] = 0;
+        assert_eq!(detect(&tar).mime, "application/x-tar");
+        assert_eq!(detect(b"RIFF\x24\x00\x00\x00WAVEfmt ").mime, "audio/wav");
+
+        // Without a signature, NULL bytes still mean binary data
+        assert_eq!(detect(b"\x01\x02\x00\x03"), UNKNOWN);
+    }
+
+    #[test]
+    fn test_detect_text() {
+        assert_eq!(detect(b""), PLAIN_TEXT);
+        assert_eq!(detect("caf\u{e9}\n".as_bytes()), PLAIN_TEXT);
+        // Latin-1 is still text
+        assert_eq!(detect(b"caf\xe9\n"), PLAIN_TEXT);
+
+        // Printable signatures don't turn text into binary
+        assert_eq!(detect(b"MZ is a prefix\n"), PLAIN_TEXT);
+        assert_eq!(detect(b"OTTO was here\n"), PLAIN_TEXT);
+
+        let script = detect(b"#!/usr/bin/env -S python3 -u\nprint()\n");
+        assert_eq!(script.mime, "text/x-python");
+        assert!(script.is_text);
+        assert_eq!(detect(b"#!/bin/bash\necho\n").mime, "text/x-shellscript");
+        assert_eq!(detect(b"#!/opt/tool/run\n").mime, "text/x-script");
+        assert_eq!(detect(b"<?xml version=\"1.0\"?>\n<a/>").mime, "text/xml");
+    }
+}
@@ -0,0 +1,196 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Terminal multiplexer able to open a new pane next to dtree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Multiplexer {
+    Tmux,
+    Wezterm,
+}
+
+/// Where the new pane is placed relative to dtree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitDirection {
+    Right,
+    Below,
+}
+
+impl SplitDirection {
+    /// Parse the `split_direction` config value (unknown values fall back to right)
+    pub fn from_config(value: &str) -> Self {
+        if value.eq_ignore_ascii_case("below") {
+            SplitDirection::Below
+        } else {
+            SplitDirection::Right
+        }
+    }
+}
+
+/// Resolve the `open_in_split` config value to a multiplexer
+/// "auto" picks whichever multiplexer dtree is running inside; "off" disables splits
+pub fn resolve(mode: &str) -> Option<Multiplexer> {
+    resolve_with(
+        mode,
+        std::env::var_os("TMUX").is_some(),
+        std::env::var_os("WEZTERM_PANE").is_some(),
+    )
+}
+
+fn resolve_with(mode: &str, in_tmux: bool, in_wezterm: bool) -> Option<Multiplexer> {
+    match mode.to_ascii_lowercase().as_str() {
+        "tmux" if in_tmux => Some(Multiplexer::Tmux),
+        "wezterm" if in_wezterm => Some(Multiplexer::Wezterm),
+        // tmux inside wezterm is the common nesting, so prefer the innermost one
+        "auto" if in_tmux => Some(Multiplexer::Tmux),
+        "auto" if in_wezterm => Some(Multiplexer::Wezterm),
+        _ => None,
+    }
+}
+
+/// Shell invocation that runs `command` (from `launcher::command_line`) inside the new pane
+#[cfg(unix)]
+fn shell_command(command: &str) -> Vec<String> {
+    vec!["sh".to_string(), "-c".to_string(), command.to_string()]
+}
+
+#[cfg(windows)]
+fn shell_command(command: &str) -> Vec<String> {
+    vec!["cmd".to_string(), "/C".to_string(), command.to_string()]
+}
+
+/// Build the multiplexer invocation (program and arguments) for opening a pane
+pub fn split_command(
+    multiplexer: Multiplexer,
+    direction: SplitDirection,
+    command: &str,
+    cwd: &Path,
+) -> (String, Vec<String>) {
+    let cwd = cwd.display().to_string();
+    let mut args: Vec<String> = match multiplexer {
+        Multiplexer::Tmux => vec![
+            "split-window".to_string(),
+            match direction {
+                SplitDirection::Right => "-h".to_string(),
+                SplitDirection::Below => "-v".to_string(),
+            },
+            "-c".to_string(),
+            cwd,
+        ],
+        Multiplexer::Wezterm => vec![
+            "cli".to_string(),
+            "split-pane".to_string(),
+            match direction {
+                SplitDirection::Right => "--right".to_string(),
+                SplitDirection::Below => "--bottom".to_string(),
+            },
+            "--cwd".to_string(),
+            cwd,
+            "--".to_string(),
+        ],
+    };
+
+    // tmux takes a single shell command, wezterm an argv after "--"
+    let command = shell_command(command);
+    match multiplexer {
+        Multiplexer::Tmux => args.push(command[2..].join(" ")),
+        Multiplexer::Wezterm => args.extend(command),
+    }
+
+    let binary = match multiplexer {
+        Multiplexer::Tmux => "tmux",
+        Multiplexer::Wezterm => "wezterm",
+    };
+    (binary.to_string(), args)
+}
+
+/// Run `command` (from `launcher::command_line`) in a new pane starting in `cwd`,
+/// leaving dtree running
+pub fn open_in_split(
+    multiplexer: Multiplexer,
+    direction: SplitDirection,
+    command: &str,
+    cwd: &Path,
+) -> Result<()> {
+    let (binary, args) = split_command(multiplexer, direction, command, cwd);
+
+    let status = Command::new(&binary)
+        .args(&args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .with_context(|| format!("Failed to run {}", binary))?;
+
+    if !status.success() {
+        anyhow::bail!("{} exited with {}", binary, status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launcher;
+
+    #[test]
+    fn test_resolve_modes() {
+        assert_eq!(resolve_with("off", true, true), None);
+        assert_eq!(resolve_with("tmux", false, true), None);
+        assert_eq!(resolve_with("tmux", true, false), Some(Multiplexer::Tmux));
+        assert_eq!(
+            resolve_with("WezTerm", false, true),
+            Some(Multiplexer::Wezterm)
+        );
+        assert_eq!(resolve_with("auto", true, true), Some(Multiplexer::Tmux));
+        assert_eq!(
+            resolve_with("auto", false, true),
+            Some(Multiplexer::Wezterm)
+        );
+        assert_eq!(resolve_with("auto", false, false), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_split_command() {
+        let (binary, args) = split_command(
+            Multiplexer::Tmux,
+            SplitDirection::Right,
+            &launcher::command_line("nvim", "/tmp/it's.txt", None),
+            Path::new("/tmp"),
+        );
+        assert_eq!(binary, "tmux");
+        assert_eq!(
+            args,
+            [
+                "split-window",
+                "-h",
+                "-c",
+                "/tmp",
+                "nvim '/tmp/it'\\''s.txt'"
+            ]
+        );
+
+        let (binary, args) = split_command(
+            Multiplexer::Wezterm,
+            SplitDirection::Below,
+            &launcher::command_line("hx", "/tmp/a b", None),
+            Path::new("/tmp"),
+        );
+        assert_eq!(binary, "wezterm");
+        assert_eq!(
+            args,
+            [
+                "cli",
+                "split-pane",
+                "--bottom",
+                "--cwd",
+                "/tmp",
+                "--",
+                "sh",
+                "-c",
+                "hx '/tmp/a b'"
+            ]
+        );
+    }
+}
@@ -1,80 +1,727 @@
-use crate::tree_node::{TreeNode, TreeNodeRef};
+use crate::compare::{Comparison, PendingComparison};
+use crate::filter_set::{Excludes, FilterSet};
+use crate::flat_listing::{FlatListing, PendingFlatListing};
+use crate::git::{GitignoreMode, IgnoredPaths, PendingIgnored};
+use crate::i18n::tr_with;
+use crate::oplog;
+use crate::quick_filter::QuickFilter;
+use crate::scan::ScanOptions;
+use crate::tree_node::{NodeId, Tree, TreeNode};
+use crate::vfs::{self, PendingListing, Vfs};
 use anyhow::Result;
-use std::cell::RefCell;
-use std::collections::HashMap;
-use std::path::{Path, PathBuf};
-use std::rc::Rc;
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
 
 /// Navigation logic for tree traversal and manipulation
 pub struct Navigation {
-    pub root: TreeNodeRef,
-    pub flat_list: Vec<TreeNodeRef>,
+    pub tree: Tree,
+    pub flat_list: Vec<NodeId>, // Visible nodes of the tree, in display order
     pub selected: usize,
-    pub show_hidden: bool,
+    pub filters: FilterSet, // Hidden, ignored and filtered entries are left out of flat_list
     pub follow_symlinks: bool,
-    // Performance optimization: HashMap for O(1) path lookup
-    path_to_index: HashMap<PathBuf, usize>,
+    pub one_file_system: bool,             // Refuse to expand mountpoints
+    pub exclude: Arc<Excludes>,            // Not gone into by searches and scans
+    pub marked: HashSet<PathBuf>,          // Marked entries for bulk actions (yank/cut)
+    pub live_filter: String,               // Shown names contain it (or lead to one that does)
+    pub flat_listing: Option<FlatListing>, // All files under the root in place of the tree
+    pub pending_flat_listing: Option<PendingFlatListing>, // Flat listing being scanned, shown once done
+    pub comparison: Option<Comparison>, // Root merged with another directory, in place of the tree
+    pub pending_comparison: Option<PendingComparison>, // Comparison being scanned, shown once done
+    pub pending_listing: Option<PendingListing>, // Directory of a slow provider expanded once listed
+    pub gitignore: GitignoreMode,                // Entries git ignores are shown, hidden or dimmed
+    ignored: Option<Arc<IgnoredPaths>>,          // Ignored entries of the root's repository
+    ignored_for: Option<PathBuf>,                // Root the ignored entries were loaded for
+    pub pending_ignored: Option<PendingIgnored>, // git listing the ignored entries
+    restrict_root: Option<PathBuf>, // --restrict: navigation can't leave this directory
+    pub vfs: Vfs,                   // Where the tree is read from (the local disk by default)
+    // Rows by path: the node of each path, the node's row from `node_rows`, so expanding
+    // or collapsing touches only the entries that came or went
+    path_to_node: HashMap<PathBuf, NodeId>,
+    node_rows: Vec<usize>, // Row of the node in each arena slot
+    rows_valid: usize,     // node_rows is up to date for the rows before this one
+}
+
+/// Options of a `Navigation` to open; `Navigation::builder(path)` starts with the
+/// defaults of dtree (directories only, no hidden entries, symlinks not followed)
+#[derive(Debug, Clone)]
+pub struct NavigationBuilder {
+    root: PathBuf,
+    vfs: Vfs,
+    show_files: bool,
+    show_hidden: bool,
+    follow_symlinks: bool,
+    one_file_system: bool,
+    exclude: Arc<Excludes>,
+    gitignore: GitignoreMode,
+}
+
+impl NavigationBuilder {
+    /// Read the tree from `vfs` instead of the local disk
+    pub fn vfs(mut self, vfs: Vfs) -> Self {
+        self.vfs = vfs;
+        self
+    }
+
+    pub fn show_files(mut self, show_files: bool) -> Self {
+        self.show_files = show_files;
+        self
+    }
+
+    pub fn show_hidden(mut self, show_hidden: bool) -> Self {
+        self.show_hidden = show_hidden;
+        self
+    }
+
+    pub fn follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    /// Refuse to expand mountpoints
+    pub fn one_file_system(mut self, one_file_system: bool) -> Self {
+        self.one_file_system = one_file_system;
+        self
+    }
+
+    /// Entries searches and scans don't go into (the tree still shows them)
+    pub fn exclude(mut self, exclude: Arc<Excludes>) -> Self {
+        self.exclude = exclude;
+        self
+    }
+
+    /// Show, hide or dim the entries git ignores
+    pub fn gitignore(mut self, gitignore: GitignoreMode) -> Self {
+        self.gitignore = gitignore;
+        self
+    }
+
+    /// Read the root directory and open the tree on it
+    pub fn build(self) -> Result<Navigation> {
+        let mut nav = Navigation::new_in(
+            self.vfs,
+            self.root,
+            self.show_files,
+            self.show_hidden,
+            self.follow_symlinks,
+        )?;
+        nav.one_file_system = self.one_file_system;
+        nav.exclude = self.exclude;
+        if self.gitignore != GitignoreMode::Show {
+            nav.set_gitignore(self.gitignore);
+        }
+        Ok(nav)
+    }
 }
 
 impl Navigation {
+    /// Options for opening a tree on `root`
+    pub fn builder(root: impl Into<PathBuf>) -> NavigationBuilder {
+        NavigationBuilder {
+            root: root.into(),
+            vfs: vfs::local(),
+            show_files: false,
+            show_hidden: false,
+            follow_symlinks: false,
+            one_file_system: false,
+            exclude: Arc::default(),
+            gitignore: GitignoreMode::Show,
+        }
+    }
+
+    #[allow(dead_code)] // Library API; the app opens trees with the builder
     pub fn new(
         start_path: PathBuf,
         show_files: bool,
         show_hidden: bool,
         follow_symlinks: bool,
     ) -> Result<Self> {
-        let mut root = TreeNode::new(start_path, 0)?;
-        root.load_children(show_files, show_hidden, follow_symlinks)?;
-        root.is_expanded = true;
-        let root = Rc::new(RefCell::new(root));
+        Self::new_in(
+            vfs::local(),
+            start_path,
+            show_files,
+            show_hidden,
+            follow_symlinks,
+        )
+    }
+
+    /// Like `new`, reading the tree from `vfs`
+    pub fn new_in(
+        vfs: Vfs,
+        start_path: PathBuf,
+        show_files: bool,
+        show_hidden: bool,
+        follow_symlinks: bool,
+    ) -> Result<Self> {
+        let filters = FilterSet::new(show_hidden);
+        let mut tree = Tree::new(TreeNode::new_in(Arc::clone(&vfs), start_path, 0)?);
+        tree.load_children(tree.root(), show_files, &filters, follow_symlinks)?;
+        tree.root_node_mut().is_expanded = true;
 
         let mut nav = Self {
-            root,
+            tree,
             flat_list: Vec::new(),
             selected: 0,
-            show_hidden,
+            filters,
             follow_symlinks,
-            path_to_index: HashMap::new(),
+            one_file_system: false,
+            exclude: Arc::default(),
+            marked: HashSet::new(),
+            live_filter: String::new(),
+            flat_listing: None,
+            pending_flat_listing: None,
+            comparison: None,
+            pending_comparison: None,
+            pending_listing: None,
+            gitignore: GitignoreMode::Show,
+            ignored: None,
+            ignored_for: None,
+            pending_ignored: None,
+            restrict_root: None,
+            vfs,
+            path_to_node: HashMap::new(),
+            node_rows: Vec::new(),
+            rows_valid: 0,
         };
 
         nav.rebuild_flat_list();
         Ok(nav)
     }
 
+    /// Build a virtual tree from an arbitrary list of paths (e.g. `fd ... | dtree --stdin`)
+    /// The root is the deepest directory containing all paths; only listed paths and their
+    /// ancestors appear in the tree, fully expanded
+    pub fn from_paths(paths: &[PathBuf], show_hidden: bool, follow_symlinks: bool) -> Result<Self> {
+        // Normalize ("./a" -> "a", "a/../b" -> "b") and drop paths that don't exist
+        let paths: Vec<PathBuf> = paths
+            .iter()
+            .map(|path| normalize_lexically(path))
+            .filter(|path| path.symlink_metadata().is_ok())
+            .collect();
+
+        let Some(first) = paths.first() else {
+            anyhow::bail!("No existing paths in the list");
+        };
+
+        let mut root_path = first.clone();
+        for path in &paths[1..] {
+            while !path.starts_with(&root_path) {
+                if !root_path.pop() {
+                    break;
+                }
+            }
+        }
+        // A single listed file is shown inside its directory
+        if !root_path.is_dir() {
+            if let Some(parent) = root_path.parent() {
+                root_path = parent.to_path_buf();
+            }
+        }
+
+        let mut tree = Tree::new(TreeNode::new_virtual(root_path, 0, true));
+        for path in &paths {
+            tree.add_virtual_path(tree.root(), path);
+        }
+        tree.sort_virtual(tree.root());
+
+        let mut nav = Self {
+            tree,
+            flat_list: Vec::new(),
+            selected: 0,
+            filters: FilterSet::new(show_hidden),
+            follow_symlinks,
+            one_file_system: false,
+            exclude: Arc::default(),
+            marked: HashSet::new(),
+            live_filter: String::new(),
+            flat_listing: None,
+            pending_flat_listing: None,
+            comparison: None,
+            pending_comparison: None,
+            pending_listing: None,
+            gitignore: GitignoreMode::Show,
+            ignored: None,
+            ignored_for: None,
+            pending_ignored: None,
+            restrict_root: None,
+            vfs: vfs::local(),
+            path_to_node: HashMap::new(),
+            node_rows: Vec::new(),
+            rows_valid: 0,
+        };
+
+        nav.rebuild_flat_list();
+        Ok(nav)
+    }
+
+    /// The root node of the tree
+    pub fn root(&self) -> &TreeNode {
+        self.tree.root_node()
+    }
+
+    /// Check if the tree was built from a path list rather than the filesystem
+    pub fn is_virtual(&self) -> bool {
+        self.root().is_virtual
+    }
+
+    /// Take the tree out to keep it aside (flat listing, comparison), leaving a bare root
+    fn take_tree(&mut self) -> Tree {
+        let root = TreeNode::new_virtual(self.root().path.clone(), 0, true);
+        std::mem::replace(&mut self.tree, Tree::new(root))
+    }
+
+    /// List all files under the root as one flat list in place of the tree
+    /// The files are scanned in the background; `poll_flat_listing` shows them
+    pub fn enter_flat_listing(&mut self) -> Result<()> {
+        if self.is_virtual() {
+            anyhow::bail!("The flat listing needs a directory tree, not a path list");
+        }
+        self.start_flat_listing();
+        Ok(())
+    }
+
+    fn start_flat_listing(&mut self) {
+        self.pending_flat_listing = Some(PendingFlatListing::start(
+            Arc::clone(&self.vfs),
+            &self.root().path,
+            self.filters.show_hidden,
+            self.follow_symlinks,
+            self.one_file_system,
+        ));
+    }
+
+    /// Show the flat listing scanned in the background, once it is done
+    /// A listing scanned again keeps its sort order; the selection stays either way
+    pub fn poll_flat_listing(&mut self) -> bool {
+        let Some(pending) = &self.pending_flat_listing else {
+            return false;
+        };
+        let Some(mut listing) = pending.try_finish() else {
+            return false;
+        };
+        self.pending_flat_listing = None;
+        let selected_path = self.get_selected_node().map(|node| node.path.clone());
+        let root_path = self.root().path.clone();
+
+        match self.flat_listing.take() {
+            Some(previous) => {
+                listing.saved_tree = previous.saved_tree;
+                listing.set_sort(previous.sort);
+            }
+            None => {
+                oplog::record(format!(
+                    "Flat listing of {}: {} files",
+                    root_path.display(),
+                    listing.len()
+                ));
+                listing.saved_tree = self.take_tree();
+            }
+        }
+        self.tree = listing.build_tree(&root_path);
+        self.flat_listing = Some(listing);
+        self.rebuild_flat_list();
+
+        self.selected = 0;
+        if let Some(path) = selected_path {
+            self.select_path_or_ancestor(&path);
+        }
+        true
+    }
+
+    /// Go back to the tree, revealing the file selected in the listing
+    pub fn leave_flat_listing(&mut self, show_files: bool) -> Result<()> {
+        self.pending_flat_listing = None;
+        let Some(listing) = self.flat_listing.take() else {
+            return Ok(());
+        };
+        let selected_path = self.get_selected_node().map(|node| node.path.clone());
+
+        self.tree = listing.saved_tree;
+        self.reload_tree(show_files)?;
+        if let Some(path) = selected_path {
+            self.expand_path_to_node(&path, show_files)?;
+        }
+        Ok(())
+    }
+
+    /// Sort the flat listing by the next order (name, size, modified)
+    pub fn cycle_flat_sort(&mut self) {
+        let Some(listing) = &mut self.flat_listing else {
+            return;
+        };
+        let selected_path = self
+            .flat_list
+            .get(self.selected)
+            .map(|&id| self.tree[id].path.clone());
+
+        listing.set_sort(listing.sort.next());
+        let root_path = self.tree.root_node().path.clone();
+        self.tree = listing.build_tree(&root_path);
+        self.rebuild_flat_list();
+        if let Some(path) = selected_path {
+            self.select_path_or_ancestor(&path);
+        }
+    }
+
+    /// Scan the files again (after changes on disk); the listing shown stays until
+    /// the new one arrives
+    fn refresh_flat_listing(&mut self) {
+        if self.flat_listing.is_some() {
+            self.start_flat_listing();
+        }
+    }
+
+    /// Compare the root with `right`, showing both merged into one tree in place of the tree
+    /// The comparison is scanned in the background; `poll_comparison` shows it
+    pub fn enter_comparison(&mut self, right: &Path) -> Result<()> {
+        if self.is_virtual() {
+            anyhow::bail!("Comparing needs a directory tree, not a path list");
+        }
+        // The other side is a local directory, walked with the local one
+        if !self.vfs.is_local() {
+            anyhow::bail!("Comparing needs a tree on the local disk");
+        }
+        if !right.is_dir() {
+            anyhow::bail!("{} is not a directory", right.display());
+        }
+        if !self.is_within_restriction(right) {
+            anyhow::bail!(
+                "{} is outside the restricted directory (--restrict)",
+                right.display()
+            );
+        }
+        let root_path = self.root().path.clone();
+        if right == root_path {
+            anyhow::bail!("Can't compare a directory with itself");
+        }
+
+        self.pending_comparison = Some(PendingComparison::start(
+            &root_path,
+            right,
+            self.filters.show_hidden,
+        ));
+        Ok(())
+    }
+
+    /// Show the comparison scanned in the background, once it is done
+    /// Returns true if it was shown (a comparison started just now, or refreshed)
+    pub fn poll_comparison(&mut self) -> bool {
+        let Some(pending) = &self.pending_comparison else {
+            return false;
+        };
+        let Some((mut comparison, tree)) = pending.try_finish() else {
+            return false;
+        };
+        self.pending_comparison = None;
+        oplog::record(format!(
+            "Compared {} with {}: {} left only, {} right only, {} changed",
+            comparison.left.display(),
+            comparison.right.display(),
+            comparison.only_left,
+            comparison.only_right,
+            comparison.changed
+        ));
+
+        match self.comparison.take() {
+            // Refreshed: the selection stays
+            Some(previous) => {
+                let selected_path = self.get_selected_node().map(|node| node.path.clone());
+                comparison.saved_tree = previous.saved_tree;
+                self.tree = tree;
+                self.comparison = Some(comparison);
+                self.rebuild_flat_list();
+                if let Some(path) = selected_path {
+                    self.select_path_or_ancestor(&path);
+                }
+            }
+            None => {
+                comparison.saved_tree = std::mem::replace(&mut self.tree, tree);
+                self.comparison = Some(comparison);
+                self.rebuild_flat_list();
+                self.selected = 0;
+            }
+        }
+        true
+    }
+
+    /// Go back to the tree the comparison was started from
+    pub fn leave_comparison(&mut self, show_files: bool) -> Result<()> {
+        self.pending_comparison = None;
+        let Some(comparison) = self.comparison.take() else {
+            return Ok(());
+        };
+        self.tree = comparison.saved_tree;
+        self.reload_tree(show_files)
+    }
+
+    /// Compare again in the background (after changes on disk), keeping the selection
+    fn refresh_comparison(&mut self) {
+        let Some(comparison) = &self.comparison else {
+            return;
+        };
+        self.pending_comparison = Some(PendingComparison::start(
+            &comparison.left,
+            &comparison.right,
+            self.filters.show_hidden,
+        ));
+    }
+
+    /// Lock navigation to the current root and its descendants (`--restrict`)
+    /// Symlinks are no longer followed, since they could point outside the root
+    pub fn restrict_to_root(&mut self, show_files: bool) -> Result<()> {
+        let root_path = self.root().path.clone();
+        self.restrict_root = Some(root_path.canonicalize().unwrap_or(root_path));
+        self.follow_symlinks = false;
+        self.reload_tree(show_files)
+    }
+
+    /// Check if a path may be visited under `--restrict`
+    pub fn is_within_restriction(&self, path: &Path) -> bool {
+        match &self.restrict_root {
+            Some(restrict_root) => path
+                .canonicalize()
+                .map(|path| path.starts_with(restrict_root))
+                .unwrap_or(false),
+            None => true,
+        }
+    }
+
     /// Rebuild flat list of visible nodes and update path index
     pub fn rebuild_flat_list(&mut self) {
         self.flat_list.clear();
-        self.path_to_index.clear();
-        Self::collect_visible_nodes(&self.root, &mut self.flat_list);
+        self.path_to_node.clear();
+        self.rows_valid = 0;
+        self.sync_ignored();
+        let mut flat_list = std::mem::take(&mut self.flat_list);
+        let root_path = self.root().path.clone();
+        Self::collect_visible_nodes(
+            &self.tree,
+            self.tree.root(),
+            &self.filters,
+            &root_path,
+            &mut flat_list,
+        );
+        if !self.live_filter.is_empty() {
+            Self::narrow(&self.tree, &mut flat_list, &self.live_filter.to_lowercase());
+        }
+        self.flat_list = flat_list;
 
-        // Build path → index mapping for O(1) lookups
-        for (idx, node) in self.flat_list.iter().enumerate() {
-            let path = node.borrow().path.clone();
-            self.path_to_index.insert(path, idx);
+        // Build path → node mapping for O(1) lookups
+        for &id in &self.flat_list {
+            self.path_to_node.insert(self.tree[id].path.clone(), id);
         }
     }
 
-    fn collect_visible_nodes(node: &TreeNodeRef, result: &mut Vec<TreeNodeRef>) {
-        result.push(Rc::clone(node));
-
-        // Check if node is expanded and get children count
-        let (is_expanded, children_count) = {
-            let node_borrowed = node.borrow();
-            (node_borrowed.is_expanded, node_borrowed.children.len())
+    /// Row of `path` in the flat list, if it's shown
+    /// Rows after an expanded or collapsed directory are brought up to date here, once
+    /// for any number of changes since the last lookup
+    fn row_of(&mut self, path: &Path) -> Option<usize> {
+        let id = *self.path_to_node.get(path)?;
+        let cached = |nav: &Self| {
+            nav.node_rows
+                .get(id.slot())
+                .copied()
+                .filter(|&row| row < nav.rows_valid && nav.flat_list.get(row) == Some(&id))
         };
+        if let Some(row) = cached(self) {
+            return Some(row);
+        }
+        for row in self.rows_valid..self.flat_list.len() {
+            let slot = self.flat_list[row].slot();
+            if slot >= self.node_rows.len() {
+                self.node_rows.resize(slot + 1, 0);
+            }
+            self.node_rows[slot] = row;
+        }
+        self.rows_valid = self.flat_list.len();
+        cached(self)
+    }
+
+    fn collect_visible_nodes(
+        tree: &Tree,
+        id: NodeId,
+        filters: &FilterSet,
+        root: &Path,
+        result: &mut Vec<NodeId>,
+    ) {
+        result.push(id);
+
+        let node = &tree[id];
+        if node.is_expanded {
+            for &child in &node.children {
+                let child_node = &tree[child];
+                if filters.shows(&child_node.path, child_node.is_dir, root) {
+                    Self::collect_visible_nodes(tree, child, filters, root, result);
+                }
+            }
+        }
+    }
+
+    /// Keep the rows whose name contains `query` (lowercase) and the directories
+    /// leading to them; the root always stays
+    fn narrow(tree: &Tree, flat_list: &mut Vec<NodeId>, query: &str) {
+        // Walking back up the display order, the first row less deep than a kept
+        // one is its directory
+        let mut keep = vec![false; flat_list.len()];
+        let mut ancestor_depth = usize::MAX;
+        for (index, &id) in flat_list.iter().enumerate().rev() {
+            let node = &tree[id];
+            if node.depth < ancestor_depth {
+                keep[index] = ancestor_depth != usize::MAX;
+            }
+            if Self::has_match(tree, id, query) {
+                keep[index] = true;
+            }
+            if keep[index] {
+                ancestor_depth = node.depth;
+            }
+        }
+        if let Some(root) = keep.first_mut() {
+            *root = true;
+        }
+        let mut keep = keep.into_iter();
+        flat_list.retain(|_| keep.next().unwrap_or(false));
+    }
+
+    /// Whether the entry matches the live filter, or a collapsed directory holds a
+    /// loaded entry that does (so it can be expanded again)
+    fn has_match(tree: &Tree, id: NodeId, query: &str) -> bool {
+        let node = &tree[id];
+        node.name.to_lowercase().contains(query)
+            || (!node.is_expanded
+                && node
+                    .children
+                    .iter()
+                    .any(|&child| Self::has_match_below(tree, child, query)))
+    }
+
+    fn has_match_below(tree: &Tree, id: NodeId, query: &str) -> bool {
+        let node = &tree[id];
+        node.name.to_lowercase().contains(query)
+            || node
+                .children
+                .iter()
+                .any(|&child| Self::has_match_below(tree, child, query))
+    }
 
-        if is_expanded {
-            // Recursively collect children without cloning the entire vector
-            for i in 0..children_count {
-                let child = Rc::clone(&node.borrow().children[i]);
-                Self::collect_visible_nodes(&child, result);
+    /// Ask git for the ignored entries again (in the background) when the root changed;
+    /// the filters leave them out while they are hidden
+    fn sync_ignored(&mut self) {
+        if self.gitignore == GitignoreMode::Show {
+            self.ignored = None;
+            self.ignored_for = None;
+            self.pending_ignored = None;
+        } else {
+            let root_path = self.root().path.clone();
+            if self.ignored_for.as_ref() != Some(&root_path) {
+                let top_level = crate::git::find_top_level(&root_path);
+                // The entries loaded before stay until the new ones arrive, unless
+                // they are another repository's
+                if self.ignored.as_ref().map(|ignored| ignored.top_level()) != top_level.as_deref()
+                {
+                    self.ignored = None;
+                }
+                self.pending_ignored = top_level.as_deref().map(PendingIgnored::start);
+                self.ignored_for = Some(root_path);
             }
         }
+        self.filters.ignored = match self.gitignore {
+            GitignoreMode::Hide => self.ignored.clone(),
+            _ => None,
+        };
+    }
+
+    /// Apply the ignored entries git listed in the background, keeping the selection
+    /// Returns true if they arrived
+    pub fn poll_ignored(&mut self) -> bool {
+        let Some(ignored) = self
+            .pending_ignored
+            .as_ref()
+            .and_then(PendingIgnored::try_finish)
+        else {
+            return false;
+        };
+        self.pending_ignored = None;
+        self.ignored = ignored.map(Arc::new);
+        let selected_path = self.get_selected_node().map(|node| node.path.clone());
+        self.rebuild_flat_list();
+        if let Some(path) = selected_path {
+            self.select_path_or_ancestor(&path);
+        }
+        true
+    }
+
+    /// Show, hide or dim the entries git ignores, keeping the selection like a quick filter
+    pub fn set_gitignore(&mut self, mode: GitignoreMode) {
+        let selected_path = self.get_selected_node().map(|node| node.path.clone());
+        self.gitignore = mode;
+        self.rebuild_flat_list();
+        if let Some(path) = selected_path {
+            self.select_path_or_ancestor(&path);
+        }
+    }
+
+    /// Ignored entry drawn dimmed in the tree
+    pub fn is_dimmed(&self, path: &Path) -> bool {
+        self.gitignore == GitignoreMode::Dim
+            && self
+                .ignored
+                .as_ref()
+                .is_some_and(|ignored| ignored.is_ignored(path))
+    }
+
+    /// Narrow the tree as the live filter is typed: the selection stays on its entry
+    /// while that still matches, or moves to the first match
+    pub fn set_live_filter(&mut self, filter: &str) {
+        let selected_path = self.get_selected_node().map(|node| node.path.clone());
+        self.live_filter = filter.to_string();
+        self.rebuild_flat_list();
+        let query = self.live_filter.to_lowercase();
+        let matches = |nav: &Self, index: usize| {
+            nav.tree[nav.flat_list[index]]
+                .name
+                .to_lowercase()
+                .contains(&query)
+        };
+        self.selected = match selected_path.and_then(|path| self.row_of(&path)) {
+            Some(index) if matches(self, index) => index,
+            _ => (0..self.flat_list.len())
+                .find(|&index| matches(self, index))
+                .unwrap_or(0),
+        };
+    }
+
+    /// Apply a quick filter, keeping the selection on the same entry (or its directory)
+    pub fn set_quick_filter(&mut self, filter: QuickFilter) {
+        let selected_path = self.get_selected_node().map(|node| node.path.clone());
+        self.filters.quick_filter = filter;
+        self.rebuild_flat_list();
+        if let Some(path) = selected_path {
+            self.select_path_or_ancestor(&path);
+        }
+    }
+
+    /// How searches and scans walk the disk: the tree's filters and symlink settings,
+    /// and the directories they don't go into
+    pub fn scan_options(&self) -> ScanOptions {
+        ScanOptions {
+            filters: self.filters.clone(),
+            follow_symlinks: self.follow_symlinks,
+            one_file_system: self.one_file_system,
+            exclude: Arc::clone(&self.exclude),
+        }
     }
 
     /// Get currently selected node
-    pub fn get_selected_node(&self) -> Option<TreeNodeRef> {
-        self.flat_list.get(self.selected).map(Rc::clone)
+    pub fn get_selected_node(&self) -> Option<&TreeNode> {
+        self.flat_list.get(self.selected).map(|&id| &self.tree[id])
+    }
+
+    /// The visible nodes (the rows of the tree), in display order
+    pub fn visible_nodes(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &TreeNode> + ExactSizeIterator + '_ {
+        self.flat_list.iter().map(|&id| &self.tree[id])
     }
 
     /// Move selection down
@@ -89,35 +736,94 @@ impl Navigation {
         self.selected = self.selected.saturating_sub(1);
     }
 
+    /// Toggle mark on a path
+    pub fn toggle_mark(&mut self, path: &Path) {
+        if !self.marked.remove(path) {
+            self.marked.insert(path.to_path_buf());
+        }
+    }
+
+    /// Check if a path is marked
+    pub fn is_marked(&self, path: &Path) -> bool {
+        self.marked.contains(path)
+    }
+
+    /// Marks on entries outside the current root (kept for when it changes back)
+    pub fn marks_outside_root(&self) -> usize {
+        let root = &self.root().path;
+        self.marked
+            .iter()
+            .filter(|path| !path.starts_with(root))
+            .count()
+    }
+
+    /// Drop the marks on entries that no longer exist (deleted or renamed elsewhere)
+    fn prune_marks(&mut self) {
+        if !self.is_virtual() {
+            let vfs = Arc::clone(&self.vfs);
+            self.marked
+                .retain(|path| vfs.symlink_metadata(path).is_ok());
+        }
+    }
+
+    /// Marked paths if any, otherwise the selected entry
+    pub fn marked_or_selected(&self) -> Vec<PathBuf> {
+        if self.marked.is_empty() {
+            self.get_selected_node()
+                .map(|node| vec![node.path.clone()])
+                .unwrap_or_default()
+        } else {
+            let mut paths: Vec<PathBuf> = self.marked.iter().cloned().collect();
+            paths.sort();
+            paths
+        }
+    }
+
     /// Toggle node expansion at path
     /// Returns Some(error_message) if node has error after toggle, None otherwise
+    /// A directory of a slow provider (`--s3`) is listed in the background first;
+    /// `poll_listing` expands it once the listing arrived
     pub fn toggle_node(&mut self, path: &Path, show_files: bool) -> Result<Option<String>> {
         // Try incremental update first
-        if let Some(index) = self.path_to_index.get(path).copied() {
+        if let Some(index) = self.row_of(path) {
             if index < self.flat_list.len() {
-                let node = &self.flat_list[index];
-                let was_expanded = node.borrow().is_expanded;
+                let id = self.flat_list[index];
+                let was_expanded = self.tree[id].is_expanded;
 
-                // Toggle the node
-                let error_msg = {
-                    let mut node_borrowed = node.borrow_mut();
-                    node_borrowed.toggle_expand(
-                        show_files,
-                        self.show_hidden,
-                        self.follow_symlinks,
-                    )?;
-                    if node_borrowed.has_error {
-                        node_borrowed.error_message.clone()
-                    } else {
-                        None
+                let node = &self.tree[id];
+                let unloaded = node.is_dir && !node.is_virtual && node.children.is_empty();
+                if !was_expanded && unloaded && node.vfs.is_slow(path) {
+                    if self.pending_listing.as_ref().map(|p| p.path.as_path()) != Some(path) {
+                        self.pending_listing = Some(PendingListing::start(&node.vfs, path));
                     }
+                    return Ok(None);
+                }
+
+                // Stay on one filesystem - don't descend into mountpoints
+                if self.one_file_system && !was_expanded && self.tree[id].is_mountpoint {
+                    return Ok(Some(tr_with(
+                        "{path} is on another filesystem (one_file_system = true)",
+                        &[("path", &path.display())],
+                    )));
+                }
+
+                // Toggle the node
+                self.tree
+                    .toggle_expand(id, show_files, &self.filters, self.follow_symlinks)?;
+                let node = &self.tree[id];
+                let error_msg = if node.has_error {
+                    node.error_message.clone()
+                } else {
+                    None
                 };
 
                 // Check actual state after toggle (may not change if error occurred)
-                let is_expanded = node.borrow().is_expanded;
+                let is_expanded = node.is_expanded;
 
-                // Incremental update of flat_list
-                if was_expanded && !is_expanded {
+                // Incremental update of flat_list (the live filter looks at whole subtrees)
+                if !self.live_filter.is_empty() && was_expanded != is_expanded {
+                    self.rebuild_flat_list();
+                } else if was_expanded && !is_expanded {
                     // Node was expanded, now collapsed - remove children from flat_list
                     self.remove_descendants_from_flat_list(index);
                 } else if !was_expanded && is_expanded {
@@ -130,119 +836,221 @@ impl Navigation {
         }
 
         // Fallback to full rebuild if node not found in flat_list
-        let error_msg = Self::toggle_node_recursive(
-            &self.root,
-            path,
-            show_files,
-            self.show_hidden,
-            self.follow_symlinks,
-        )?;
+        let error_msg = match self.find_node(path) {
+            Some(id) => {
+                self.tree
+                    .toggle_expand(id, show_files, &self.filters, self.follow_symlinks)?;
+                let node = &self.tree[id];
+                node.error_message.clone().filter(|_| node.has_error)
+            }
+            None => None,
+        };
         self.rebuild_flat_list();
         Ok(error_msg)
     }
 
-    fn toggle_node_recursive(
-        node: &TreeNodeRef,
-        target_path: &Path,
-        show_files: bool,
-        show_hidden: bool,
-        follow_symlinks: bool,
-    ) -> Result<Option<String>> {
-        // Check if this is the target node
-        {
-            let mut node_borrowed = node.borrow_mut();
-            if node_borrowed.path == target_path {
-                node_borrowed.toggle_expand(show_files, show_hidden, follow_symlinks)?;
-                // Check if node has error after toggle
-                let error_msg = if node_borrowed.has_error {
-                    node_borrowed.error_message.clone()
-                } else {
-                    None
-                };
-                return Ok(error_msg);
-            }
+    /// Expand the directory listed in the background once its listing arrived
+    /// Returns None while it's still being listed, else the error toggle_node reports
+    pub fn poll_listing(&mut self, show_files: bool) -> Option<Option<String>> {
+        if !self.pending_listing.as_ref()?.is_done() {
+            return None;
         }
+        let pending = self.pending_listing.take()?;
+        Some(
+            self.toggle_node(&pending.path, show_files)
+                .unwrap_or_else(|e| Some(e.to_string())),
+        )
+    }
 
-        // Recursively search children without cloning
-        // We need to drop the borrow before recursing
-        let children_count = node.borrow().children.len();
-        for i in 0..children_count {
-            let child = Rc::clone(&node.borrow().children[i]);
-            if let Some(error_msg) = Self::toggle_node_recursive(
-                &child,
-                target_path,
-                show_files,
-                show_hidden,
-                follow_symlinks,
-            )? {
-                return Ok(Some(error_msg));
+    /// Node at `path` among the loaded ones
+    fn find_node(&self, path: &Path) -> Option<NodeId> {
+        let mut stack = vec![self.tree.root()];
+        while let Some(id) = stack.pop() {
+            let node = &self.tree[id];
+            if node.path == path {
+                return Some(id);
             }
+            stack.extend(node.children.iter().rev());
         }
-
-        Ok(None)
+        None
     }
 
     /// Reload tree with new show_files setting
+    /// Expanded directories and the selected entry survive the reload
+    /// (a flat listing or comparison is scanned again)
     pub fn reload_tree(&mut self, show_files: bool) -> Result<()> {
+        let _span = tracing::debug_span!("reload_tree").entered();
+        // New build outputs may have appeared
+        self.ignored_for = None;
+        self.prune_marks();
+        if self.flat_listing.is_some() {
+            self.refresh_flat_listing();
+            return Ok(());
+        }
+        if self.comparison.is_some() {
+            self.refresh_comparison();
+            return Ok(());
+        }
+        let expanded = self.expanded_paths();
+        let selected_path = self.get_selected_node().map(|node| node.path.clone());
+
+        let root = self.tree.root();
         Self::reload_node_recursive(
-            &self.root,
+            &mut self.tree,
+            root,
+            &expanded,
             show_files,
-            self.show_hidden,
+            &self.filters,
             self.follow_symlinks,
         )?;
         self.rebuild_flat_list();
+
+        if let Some(path) = selected_path {
+            self.select_path_or_ancestor(&path);
+        }
         Ok(())
     }
 
+    /// Paths of all expanded directories in the tree
+    pub fn expanded_paths(&self) -> HashSet<PathBuf> {
+        let mut expanded = HashSet::new();
+        let mut stack = vec![self.tree.root()];
+        while let Some(id) = stack.pop() {
+            let node = &self.tree[id];
+            if node.is_expanded {
+                expanded.insert(node.path.clone());
+                stack.extend(&node.children);
+            }
+        }
+        expanded
+    }
+
+    /// Select `path`, or its closest ancestor still in the tree (e.g. after files were hidden)
+    pub fn select_path_or_ancestor(&mut self, path: &Path) {
+        if let Some(idx) = path.ancestors().find_map(|ancestor| self.row_of(ancestor)) {
+            self.selected = idx;
+        } else {
+            self.selected = self.selected.min(self.flat_list.len().saturating_sub(1));
+        }
+    }
+
     fn reload_node_recursive(
-        node: &TreeNodeRef,
+        tree: &mut Tree,
+        id: NodeId,
+        expanded: &HashSet<PathBuf>,
         show_files: bool,
-        show_hidden: bool,
+        filters: &FilterSet,
         follow_symlinks: bool,
     ) -> Result<()> {
-        // Check if we need to reload this node
-        let should_reload = {
-            let node_borrowed = node.borrow();
-            node_borrowed.is_expanded && node_borrowed.is_dir
-        };
-
-        if should_reload {
-            // Clear children and reload with new mode
-            {
-                let mut node_borrowed = node.borrow_mut();
-                node_borrowed.children.clear();
-                node_borrowed.load_children(show_files, show_hidden, follow_symlinks)?;
+        let node = &tree[id];
+        if node.is_expanded && node.is_dir {
+            // Clear children and reload with new mode (virtual children are fixed)
+            if !node.is_virtual {
+                tree.clear_children(id);
+                tree.load_children(id, show_files, filters, follow_symlinks)?;
+                let node = &mut tree[id];
+                if node.has_error {
+                    node.is_expanded = false;
+                    return Ok(());
+                }
             }
 
-            // Recursively reload child nodes without cloning
-            let children_count = node.borrow().children.len();
-            for i in 0..children_count {
-                let child = Rc::clone(&node.borrow().children[i]);
-                Self::reload_node_recursive(&child, show_files, show_hidden, follow_symlinks)?;
+            Self::restore_expanded_children(
+                tree,
+                id,
+                expanded,
+                show_files,
+                filters,
+                follow_symlinks,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Re-expand freshly loaded children that were expanded before, recursively
+    fn restore_expanded_children(
+        tree: &mut Tree,
+        id: NodeId,
+        expanded: &HashSet<PathBuf>,
+        show_files: bool,
+        filters: &FilterSet,
+        follow_symlinks: bool,
+    ) -> Result<()> {
+        for child in tree[id].children.clone() {
+            let node = &mut tree[child];
+            if node.is_dir && !node.is_virtual && expanded.contains(&node.path) {
+                node.is_expanded = true;
             }
+            Self::reload_node_recursive(
+                tree,
+                child,
+                expanded,
+                show_files,
+                filters,
+                follow_symlinks,
+            )?;
         }
         Ok(())
     }
 
+    /// Put back the tree a flat listing or comparison is shown in place of, and drop
+    /// the background work started for the current root (before changing it)
+    fn leave_root_views(&mut self) {
+        if let Some(listing) = self.flat_listing.take() {
+            self.tree = listing.saved_tree;
+        }
+        if let Some(comparison) = self.comparison.take() {
+            self.tree = comparison.saved_tree;
+        }
+        self.pending_flat_listing = None;
+        self.pending_comparison = None;
+        self.pending_listing = None;
+    }
+
     /// Navigate to parent directory
     pub fn go_to_parent(&mut self, show_files: bool) -> Result<()> {
-        let parent_path = {
-            let root_borrowed = self.root.borrow();
-            root_borrowed.path.parent().map(|p| p.to_path_buf())
-        };
+        // Going up leaves a flat listing or comparison for the tree it was opened from,
+        // even where the root can't change (a virtual tree, the --restrict root)
+        if self.flat_listing.is_some() || self.comparison.is_some() {
+            self.leave_root_views();
+        }
+        // A virtual tree has nothing above its root
+        if self.is_virtual() {
+            return Ok(());
+        }
+
+        let parent_path = self.root().path.parent().map(|p| p.to_path_buf());
+
+        // --restrict: stay at the restricted root
+        let parent_path = parent_path.filter(|parent| self.is_within_restriction(parent));
 
         if let Some(parent_path) = parent_path {
-            let current_path = self.root.borrow().path.clone();
+            // The root changes: drop the background work started for the old one too
+            // (a view left above was put back already, this only clears the pending work)
+            self.leave_root_views();
+            let current_path = self.root().path.clone();
+            let expanded = self.expanded_paths();
 
-            let mut new_root = TreeNode::new(parent_path, 0)?;
-            new_root.load_children(show_files, self.show_hidden, self.follow_symlinks)?;
-            new_root.is_expanded = true;
+            oplog::record(format!("Changed root to {}", parent_path.display()));
+            let mut new_tree = Tree::new(TreeNode::new_in(Arc::clone(&self.vfs), parent_path, 0)?);
+            let root = new_tree.root();
+            new_tree.load_children(root, show_files, &self.filters, self.follow_symlinks)?;
+            new_tree.root_node_mut().is_expanded = true;
 
-            self.root = Rc::new(RefCell::new(new_root));
+            // Keep the directory we came from expanded as it was
+            self.tree = new_tree;
+            Self::restore_expanded_children(
+                &mut self.tree,
+                root,
+                &expanded,
+                show_files,
+                &self.filters,
+                self.follow_symlinks,
+            )?;
             self.rebuild_flat_list();
 
             // Find and select previous directory using HashMap (O(1) instead of O(n))
-            if let Some(&idx) = self.path_to_index.get(&current_path) {
+            if let Some(idx) = self.row_of(&current_path) {
                 self.selected = idx;
             }
         }
@@ -250,6 +1058,11 @@ impl Navigation {
         Ok(())
     }
 
+    /// Check if the tree root has no parent (e.g. / or C:\)
+    pub fn is_at_filesystem_root(&self) -> bool {
+        self.root().path.parent().is_none()
+    }
+
     /// Navigate to arbitrary directory (for bookmarks)
     /// Returns Some(error_message) if directory cannot be accessed, None otherwise
     pub fn go_to_directory(
@@ -257,47 +1070,90 @@ impl Navigation {
         target_path: PathBuf,
         show_files: bool,
     ) -> Result<Option<String>> {
-        if !target_path.is_dir() {
+        if !self.vfs.is_dir(&target_path) {
             return Ok(None);
         }
 
-        // Save current state in case we need to restore it
-        let old_root = Rc::clone(&self.root);
-        let old_selected = self.selected;
+        if !self.is_within_restriction(&target_path) {
+            return Ok(Some(tr_with(
+                "{path} is outside the restricted directory (--restrict)",
+                &[("path", &target_path.display())],
+            )));
+        }
 
-        let mut new_root = TreeNode::new(target_path, 0)?;
-        new_root.load_children(show_files, self.show_hidden, self.follow_symlinks)?;
-        new_root.is_expanded = true;
+        let mut new_tree = Tree::new(TreeNode::new_in(Arc::clone(&self.vfs), target_path, 0)?);
+        let root = new_tree.root();
+        new_tree.load_children(root, show_files, &self.filters, self.follow_symlinks)?;
+        new_tree.root_node_mut().is_expanded = true;
 
-        // Check if the new root has an error
-        if new_root.has_error {
-            // Restore previous state - don't change directory
-            self.root = old_root;
-            self.selected = old_selected;
-            return Ok(new_root.error_message);
+        // Check if the new root has an error - don't change directory then
+        if new_tree.root_node().has_error {
+            return Ok(new_tree.root_node().error_message.clone());
         }
 
-        // Success - update to new root
-        self.root = Rc::new(RefCell::new(new_root));
+        // Success - update to new root, keeping subdirectories expanded as they were
+        oplog::record(format!(
+            "Changed root to {}",
+            new_tree.root_node().path.display()
+        ));
+        self.leave_root_views();
+        let expanded = self.expanded_paths();
+        self.tree = new_tree;
+        Self::restore_expanded_children(
+            &mut self.tree,
+            root,
+            &expanded,
+            show_files,
+            &self.filters,
+            self.follow_symlinks,
+        )?;
         self.rebuild_flat_list();
         self.selected = 0;
 
         Ok(None)
     }
 
+    /// Collapse every directory but the ancestors of `path`, and select it
+    /// (a flat listing or comparison only selects it)
+    pub fn reveal(&mut self, path: &Path, show_files: bool) -> Result<()> {
+        if self.flat_listing.is_none() && self.comparison.is_none() {
+            let mut stack = vec![self.tree.root()];
+            while let Some(id) = stack.pop() {
+                let node = &mut self.tree[id];
+                node.is_expanded = false;
+                stack.extend(&node.children);
+            }
+            self.tree.root_node_mut().is_expanded = true;
+            let root = self.tree.root();
+            Self::expand_path_recursive(
+                &mut self.tree,
+                root,
+                path,
+                show_files,
+                &self.filters,
+                self.follow_symlinks,
+            )?;
+            self.rebuild_flat_list();
+        }
+        self.select_path_or_ancestor(path);
+        Ok(())
+    }
+
     /// Expand path to node (for search results)
-    pub fn expand_path_to_node(&mut self, target_path: &PathBuf, show_files: bool) -> Result<()> {
+    pub fn expand_path_to_node(&mut self, target_path: &Path, show_files: bool) -> Result<()> {
+        let root = self.tree.root();
         Self::expand_path_recursive(
-            &self.root,
+            &mut self.tree,
+            root,
             target_path,
             show_files,
-            self.show_hidden,
+            &self.filters,
             self.follow_symlinks,
         )?;
         self.rebuild_flat_list();
 
         // Find and select element in tree using HashMap (O(1) instead of O(n))
-        if let Some(&idx) = self.path_to_index.get(target_path) {
+        if let Some(idx) = self.row_of(target_path) {
             self.selected = idx;
         }
 
@@ -305,44 +1161,40 @@ impl Navigation {
     }
 
     fn expand_path_recursive(
-        node: &TreeNodeRef,
-        target_path: &PathBuf,
+        tree: &mut Tree,
+        id: NodeId,
+        target_path: &Path,
         show_files: bool,
-        show_hidden: bool,
+        filters: &FilterSet,
         follow_symlinks: bool,
     ) -> Result<bool> {
-        // Check if this is the target node or if target is a descendant
-        {
-            let mut node_borrowed = node.borrow_mut();
+        let node = &tree[id];
 
-            // If this is the target node, do nothing
-            if &node_borrowed.path == target_path {
-                return Ok(true);
-            }
-
-            // Check if target_path is a descendant of current node
-            if !target_path.starts_with(&node_borrowed.path) {
-                return Ok(false);
-            }
+        // If this is the target node, do nothing
+        if node.path == target_path {
+            return Ok(true);
+        }
 
-            // Load children if needed
-            if node_borrowed.children.is_empty() && node_borrowed.is_dir {
-                node_borrowed.load_children(show_files, show_hidden, follow_symlinks)?;
-            }
+        // Check if target_path is a descendant of current node
+        if !target_path.starts_with(&node.path) {
+            return Ok(false);
+        }
 
-            // Expand current node
-            node_borrowed.is_expanded = true;
+        // Load children if needed
+        if node.children.is_empty() && node.is_dir {
+            tree.load_children(id, show_files, filters, follow_symlinks)?;
         }
 
-        // Recursively search in children without cloning
-        let children_count = node.borrow().children.len();
-        for i in 0..children_count {
-            let child = Rc::clone(&node.borrow().children[i]);
+        // Expand current node
+        tree[id].is_expanded = true;
+
+        for child in tree[id].children.clone() {
             if Self::expand_path_recursive(
-                &child,
+                tree,
+                child,
                 target_path,
                 show_files,
-                show_hidden,
+                filters,
                 follow_symlinks,
             )? {
                 return Ok(true);
@@ -354,63 +1206,82 @@ impl Navigation {
 
     /// Remove all descendants of node at given index from flat_list (when collapsing)
     fn remove_descendants_from_flat_list(&mut self, parent_index: usize) {
-        let parent_depth = self.flat_list[parent_index].borrow().depth;
+        let parent_depth = self.tree[self.flat_list[parent_index]].depth;
 
         // Find the range of descendants to remove
         // All nodes after parent with depth > parent_depth are descendants
         let mut remove_count = 0;
         for i in (parent_index + 1)..self.flat_list.len() {
-            if self.flat_list[i].borrow().depth > parent_depth {
+            if self.tree[self.flat_list[i]].depth > parent_depth {
                 remove_count += 1;
             } else {
                 break; // Found a sibling or ancestor, stop
             }
         }
 
-        // Remove descendants
-        if remove_count > 0 {
-            self.flat_list
-                .drain((parent_index + 1)..(parent_index + 1 + remove_count));
+        if remove_count == 0 {
+            return;
         }
-
-        // Rebuild path_to_index mapping
-        self.rebuild_path_index();
+        let start = parent_index + 1;
+        for id in self.flat_list.drain(start..start + remove_count) {
+            self.path_to_node.remove(&self.tree[id].path);
+        }
+        self.rows_valid = self.rows_valid.min(start);
     }
 
     /// Insert children of node at given index into flat_list (when expanding)
     fn insert_children_into_flat_list(&mut self, parent_index: usize) {
-        let node = &self.flat_list[parent_index];
+        let node = &self.tree[self.flat_list[parent_index]];
 
         // Collect all visible descendants of the newly expanded node
         let mut new_nodes = Vec::new();
-        let (is_expanded, children_count) = {
-            let node_borrowed = node.borrow();
-            (node_borrowed.is_expanded, node_borrowed.children.len())
-        };
-
-        if is_expanded {
-            for i in 0..children_count {
-                let child = Rc::clone(&node.borrow().children[i]);
-                Self::collect_visible_nodes(&child, &mut new_nodes);
+        if node.is_expanded {
+            let root_path = self.root().path.clone();
+            for &child in &node.children {
+                let child_node = &self.tree[child];
+                if self
+                    .filters
+                    .shows(&child_node.path, child_node.is_dir, &root_path)
+                {
+                    Self::collect_visible_nodes(
+                        &self.tree,
+                        child,
+                        &self.filters,
+                        &root_path,
+                        &mut new_nodes,
+                    );
+                }
             }
         }
 
-        // Insert new nodes after parent
-        if !new_nodes.is_empty() {
-            let insert_pos = parent_index + 1;
-            self.flat_list.splice(insert_pos..insert_pos, new_nodes);
+        if new_nodes.is_empty() {
+            return;
         }
-
-        // Rebuild path_to_index mapping
-        self.rebuild_path_index();
+        let start = parent_index + 1;
+        for &id in &new_nodes {
+            self.path_to_node.insert(self.tree[id].path.clone(), id);
+        }
+        self.flat_list.splice(start..start, new_nodes);
+        self.rows_valid = self.rows_valid.min(start);
     }
+}
 
-    /// Rebuild only the path_to_index HashMap (faster than full rebuild)
-    fn rebuild_path_index(&mut self) {
-        self.path_to_index.clear();
-        for (idx, node) in self.flat_list.iter().enumerate() {
-            let path = node.borrow().path.clone();
-            self.path_to_index.insert(path, idx);
+/// Resolve `.` and `..` components without touching the disk
+/// (`..` at the root stays at the root, like the shell does)
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => normalized.push(component),
+            },
+            _ => normalized.push(component),
         }
     }
+    normalized
 }
@@ -0,0 +1,151 @@
+use crate::oplog;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How long a toast stays on screen
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+
+/// Toasts stacked in the corner at once (older ones are only in the log)
+pub const MAX_TOASTS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Level::Info => "info",
+            Level::Warn => "warn",
+            Level::Error => "error",
+        }
+    }
+}
+
+/// A single message shown as a toast
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub level: Level,
+    pub message: String,
+    pub time: Instant,
+}
+
+/// Transient toasts (info/warn/error); every message also goes to the operation log,
+/// which is shown in the log panel
+#[derive(Debug, Default)]
+pub struct Notifications {
+    recent: VecDeque<Notification>,
+    visible_toasts: usize,
+    pub show_log: bool,
+    pub log_scroll: usize,
+}
+
+impl Notifications {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, level: Level, message: impl Into<String>) {
+        let message = message.into();
+        oplog::record(format!("{}: {}", level.label(), message));
+
+        if self.recent.len() == MAX_TOASTS {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(Notification {
+            level,
+            message,
+            time: Instant::now(),
+        });
+    }
+
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.push(Level::Info, message);
+    }
+
+    pub fn warn(&mut self, message: impl Into<String>) {
+        self.push(Level::Warn, message);
+    }
+
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.push(Level::Error, message);
+    }
+
+    /// Toasts that are still on screen, oldest first
+    pub fn toasts(&self) -> Vec<&Notification> {
+        self.recent
+            .iter()
+            .filter(|n| n.time.elapsed() < TOAST_DURATION)
+            .collect()
+    }
+
+    /// When the oldest toast on screen expires
+    pub fn next_expiry(&self) -> Option<Instant> {
+        self.toasts()
+            .first()
+            .map(|toast| toast.time + TOAST_DURATION)
+    }
+
+    /// Check if toasts appeared or expired since the last call
+    /// Returns true if the UI needs to be redrawn
+    pub fn poll(&mut self) -> bool {
+        let visible = self.toasts().len();
+        let changed = visible != self.visible_toasts;
+        self.visible_toasts = visible;
+        changed
+    }
+
+    pub fn toggle_log(&mut self) {
+        self.show_log = !self.show_log;
+        self.log_scroll = 0;
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.log_scroll = self.log_scroll.saturating_sub(1);
+    }
+
+    pub fn scroll_down(&mut self) {
+        if self.log_scroll + 1 < oplog::entries().len() {
+            self.log_scroll += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toasts_and_log() {
+        let mut notifications = Notifications::new();
+        assert!(notifications.toasts().is_empty());
+
+        for i in 0..5 {
+            notifications.info(format!("message {}", i));
+        }
+        notifications.error("failed");
+
+        // Only the newest toasts are stacked, oldest first
+        let toasts = notifications.toasts();
+        assert_eq!(toasts.len(), MAX_TOASTS);
+        assert_eq!(toasts[0].message, "message 3");
+        assert_eq!(toasts[2].level, Level::Error);
+        assert!(notifications.poll());
+        assert!(!notifications.poll());
+
+        // Every message is kept in the operation log
+        assert!(oplog::entries()
+            .iter()
+            .any(|entry| entry.message == "error: failed"));
+
+        // Expired toasts leave the screen
+        for toast in notifications.recent.iter_mut() {
+            toast.time -= TOAST_DURATION;
+        }
+        assert!(notifications.toasts().is_empty());
+        assert!(notifications.poll());
+    }
+}
@@ -0,0 +1,58 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Listen address of the Neovim instance dtree runs inside (`:terminal`)
+/// Neovim sets $NVIM for its terminal jobs; older versions used $NVIM_LISTEN_ADDRESS
+pub fn server_address() -> Option<String> {
+    ["NVIM", "NVIM_LISTEN_ADDRESS"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|address| !address.is_empty())
+}
+
+/// Build the `nvim --server <address> --remote <file>` invocation
+pub fn remote_command(server: &str, path: &Path) -> (String, Vec<String>) {
+    (
+        "nvim".to_string(),
+        vec![
+            "--server".to_string(),
+            server.to_string(),
+            "--remote".to_string(),
+            path.display().to_string(),
+        ],
+    )
+}
+
+/// Open `path` in the existing Neovim instance instead of spawning a new editor
+pub fn open_remote(server: &str, path: &Path) -> Result<()> {
+    let (binary, args) = remote_command(server, path);
+
+    let status = Command::new(&binary)
+        .args(&args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .with_context(|| format!("Failed to run {}", binary))?;
+
+    if !status.success() {
+        anyhow::bail!("{} exited with {}", binary, status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remote_command() {
+        let (binary, args) = remote_command("/run/nvim.sock", Path::new("/tmp/my file.rs"));
+        assert_eq!(binary, "nvim");
+        assert_eq!(
+            args,
+            ["--server", "/run/nvim.sock", "--remote", "/tmp/my file.rs"]
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use once_cell::sync::Lazy;
+use std::collections::VecDeque;
+use std::sync::{Mutex, TryLockError};
+use std::time::{Duration, Instant};
+
+/// Entries kept in memory (oldest are dropped first)
+const MAX_ENTRIES: usize = 500;
+
+/// A single logged action or message
+#[derive(Debug, Clone)]
+pub struct Entry {
+    /// Time since dtree started
+    pub elapsed: Duration,
+    pub message: String,
+}
+
+impl Entry {
+    /// One log line, e.g. "[   12.345s] Copied path: /tmp/a"
+    pub fn format(&self) -> String {
+        format!("[{:>9.3}s] {}", self.elapsed.as_secs_f64(), self.message)
+    }
+}
+
+struct OperationLog {
+    started: Instant,
+    entries: VecDeque<Entry>,
+}
+
+/// Process-wide operation log, so background threads and the panic hook can reach it
+static LOG: Lazy<Mutex<OperationLog>> = Lazy::new(|| {
+    Mutex::new(OperationLog {
+        started: Instant::now(),
+        entries: VecDeque::new(),
+    })
+});
+
+/// Record an action and its result (e.g. "Opened editor on /tmp/a.rs")
+pub fn record(message: impl Into<String>) {
+    let message = message.into();
+    tracing::info!("{}", message);
+
+    let Ok(mut log) = LOG.lock() else {
+        return;
+    };
+    if log.entries.len() == MAX_ENTRIES {
+        log.entries.pop_front();
+    }
+    let elapsed = log.started.elapsed();
+    log.entries.push_back(Entry { elapsed, message });
+}
+
+/// The last `count` entries, oldest first
+pub fn recent(count: usize) -> Vec<Entry> {
+    let Ok(log) = LOG.lock() else {
+        return Vec::new();
+    };
+    let skip = log.entries.len().saturating_sub(count);
+    log.entries.iter().skip(skip).cloned().collect()
+}
+
+/// Like `recent`, but never blocks (for the panic hook - the panicking thread may hold the lock)
+pub fn try_recent(count: usize) -> Vec<Entry> {
+    let log = match LOG.try_lock() {
+        Ok(log) => log,
+        Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+        Err(TryLockError::WouldBlock) => return Vec::new(),
+    };
+    let skip = log.entries.len().saturating_sub(count);
+    log.entries.iter().skip(skip).cloned().collect()
+}
+
+/// All entries, oldest first
+pub fn entries() -> Vec<Entry> {
+    recent(MAX_ENTRIES)
+}
+
+/// Whole log as text (for copying into bug reports)
+pub fn to_text() -> String {
+    entries()
+        .iter()
+        .map(|entry| entry.format())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_recent() {
+        // The log is shared by all tests in this process, so look up unique messages
+        record("oplog test: first");
+        record("oplog test: second");
+
+        let entries = entries();
+        let position = |message: &str| entries.iter().position(|e| e.message == message);
+        let first = position("oplog test: first").unwrap();
+        let second = position("oplog test: second").unwrap();
+        assert!(first < second);
+        assert!(entries[first].elapsed <= entries[second].elapsed);
+        assert!(entries[second].format().ends_with("] oplog test: second"));
+
+        assert_eq!(recent(1).len(), 1);
+        assert!(to_text().contains("] oplog test: first\n"));
+    }
+}
@@ -0,0 +1,124 @@
+use std::path::Path;
+
+/// Line of a patch or merge conflict that the viewer colors on its own (not syntect)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    Added,
+    Removed,
+    /// `@@ -1,4 +1,5 @@`
+    Hunk,
+    /// `diff --git`, `index`, `---`/`+++` file names
+    Header,
+    /// `<<<<<<<`, `|||||||`, `=======`, `>>>>>>>` of a git conflict
+    ConflictMarker,
+}
+
+/// Whether `path` is a patch (.diff, .patch)
+pub fn is_patch_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("diff") || ext.eq_ignore_ascii_case("patch"))
+}
+
+/// Kind of each line; empty when there is nothing to color
+/// Patch lines are only recognized in a patch, conflict markers in any file
+/// (`=======` and `|||||||` only between `<<<<<<<` and `>>>>>>>`)
+pub fn classify(lines: &[String], is_patch: bool) -> Vec<Option<LineKind>> {
+    if !is_patch && !lines.iter().any(|line| is_marker(line, "<<<<<<<")) {
+        return Vec::new();
+    }
+
+    let mut in_conflict = false;
+    lines
+        .iter()
+        .map(|line| {
+            if is_marker(line, "<<<<<<<") {
+                in_conflict = true;
+                return Some(LineKind::ConflictMarker);
+            }
+            if in_conflict && (is_marker(line, "=======") || is_marker(line, "|||||||")) {
+                return Some(LineKind::ConflictMarker);
+            }
+            if in_conflict && is_marker(line, ">>>>>>>") {
+                in_conflict = false;
+                return Some(LineKind::ConflictMarker);
+            }
+            if is_patch {
+                patch_line(line)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// `line` is `marker`, alone or followed by a space and a label
+fn is_marker(line: &str, marker: &str) -> bool {
+    line.strip_prefix(marker)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+}
+
+fn patch_line(line: &str) -> Option<LineKind> {
+    if line.starts_with("+++") || line.starts_with("---") {
+        Some(LineKind::Header)
+    } else if line.starts_with('+') {
+        Some(LineKind::Added)
+    } else if line.starts_with('-') {
+        Some(LineKind::Removed)
+    } else if line.starts_with("@@") {
+        Some(LineKind::Hunk)
+    } else if line.starts_with("diff ") || line.starts_with("index ") {
+        Some(LineKind::Header)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_patch_lines() {
+        let patch = lines(
+            "diff --git a/x b/x\nindex 1..2\n--- a/x\n+++ b/x\n@@ -1,2 +1,2 @@\n same\n-old\n+new",
+        );
+        assert_eq!(
+            classify(&patch, true),
+            [
+                Some(LineKind::Header),
+                Some(LineKind::Header),
+                Some(LineKind::Header),
+                Some(LineKind::Header),
+                Some(LineKind::Hunk),
+                None,
+                Some(LineKind::Removed),
+                Some(LineKind::Added),
+            ]
+        );
+        // The same lines in another file stay as they are
+        assert!(classify(&patch, false).is_empty());
+    }
+
+    #[test]
+    fn test_conflict_markers_in_any_file() {
+        let text =
+            lines("Title\n=======\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> topic\n=======");
+        let marker = Some(LineKind::ConflictMarker);
+        assert_eq!(
+            classify(&text, false),
+            [None, None, marker, None, marker, None, marker, None]
+        );
+    }
+
+    #[test]
+    fn test_is_patch_file() {
+        assert!(is_patch_file(Path::new("fix.patch")));
+        assert!(is_patch_file(Path::new("a/B.DIFF")));
+        assert!(!is_patch_file(Path::new("patch.txt")));
+    }
+}
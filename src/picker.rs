@@ -0,0 +1,105 @@
+use std::path::{Path, PathBuf};
+
+/// Marker prefix for paths confirmed in picker mode: the paths after it, NUL-separated
+/// (newlines are valid in file names, NUL is not)
+const PICK_MARKER: &str = "PICK:";
+
+/// What `dtree --pick <mode>` lets the user choose
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickMode {
+    /// A single file
+    File,
+    /// A single directory
+    Dir,
+    /// Any number of marked entries (files or directories)
+    Multi,
+    /// Marked files, confirmed with Enter on a file (`--choosefiles`)
+    Files,
+}
+
+impl PickMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "file" => Some(PickMode::File),
+            "dir" => Some(PickMode::Dir),
+            "multi" => Some(PickMode::Multi),
+            _ => None,
+        }
+    }
+
+    /// Every picker except `dir` needs files visible in the tree
+    pub fn shows_files(&self) -> bool {
+        !matches!(self, PickMode::Dir)
+    }
+
+    /// Check if an entry can be confirmed with Enter in this mode
+    pub fn accepts(&self, is_dir: bool) -> bool {
+        match self {
+            PickMode::File | PickMode::Files => !is_dir,
+            PickMode::Dir => is_dir,
+            PickMode::Multi => true,
+        }
+    }
+
+    /// Tree title with the picker key hints
+    pub fn title(&self) -> &'static str {
+        match self {
+            PickMode::File => " Pick a file (Enter: pick | l/h: expand/collapse | /: search | Esc/q: cancel) ",
+            PickMode::Dir => " Pick a directory (Enter: pick | l/h: expand/collapse | /: search | Esc/q: cancel) ",
+            PickMode::Multi => " Pick entries (Space: mark | Enter: pick marked | /: search | Esc/q: cancel) ",
+            PickMode::Files => " Choose files (Space: mark | Enter on file: choose marked | /: search | Esc/q: cancel) ",
+        }
+    }
+}
+
+/// Encode picked paths as a return marker for main.rs
+pub fn encode(paths: &[PathBuf]) -> PathBuf {
+    let paths: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+    PathBuf::from(format!("{}{}", PICK_MARKER, paths.join("\0")))
+}
+
+/// Decode a return marker produced by `encode`
+/// Returns None if the marker is not a pick (i.e. the picker was cancelled)
+pub fn decode(marker: &Path) -> Option<Vec<PathBuf>> {
+    let marker = marker.to_string_lossy();
+    let paths = marker.strip_prefix(PICK_MARKER)?;
+    Some(
+        paths
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accepts() {
+        assert!(PickMode::File.accepts(false));
+        assert!(!PickMode::File.accepts(true));
+        assert!(PickMode::Dir.accepts(true));
+        assert!(!PickMode::Dir.accepts(false));
+        assert!(PickMode::Multi.accepts(true));
+        assert!(!PickMode::Files.accepts(true));
+        assert_eq!(PickMode::parse("multi"), Some(PickMode::Multi));
+        assert_eq!(PickMode::parse("files"), None);
+    }
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        let paths = vec![
+            PathBuf::from("/tmp/a b.txt"),
+            PathBuf::from("/tmp/two\nlines"),
+            PathBuf::from("/tmp/dir"),
+        ];
+        assert_eq!(decode(&encode(&paths)), Some(paths));
+        assert_eq!(decode(&encode(&[])), Some(Vec::new()));
+
+        // Anything else (e.g. "cd & exit" path or editor marker) is not a pick
+        assert_eq!(decode(Path::new("/tmp/dir")), None);
+        assert_eq!(decode(Path::new("EDITOR:/tmp/a")), None);
+    }
+}
@@ -1,50 +1,128 @@
+use crate::launcher;
 use anyhow::Result;
-use std::process::Command;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 
-/// Open an external program with the given path
+/// Open an external program with the given path (at `line`, when known)
+/// The program may be a template with `{path}` and `{line}` (see `launcher::command_line`)
 /// This function handles platform differences in launching external programs
 #[cfg(unix)]
-pub fn open_external_program(program: &str, path: &str) -> Result<()> {
+pub fn open_external_program(program: &str, path: &str, line: Option<usize>) -> Result<()> {
+    run_in_terminal(&launcher::command_line(program, path, line))
+}
+
+/// Run a command line (from `launcher`) in this terminal and wait for it
+#[cfg(unix)]
+pub fn run_in_terminal(command_line: &str) -> Result<()> {
     // Unix: use sh shell with proper TTY handling
-    // Properly quote the path to handle spaces and special characters
+    // The braces give a template running several commands the terminal as a whole
+    let shell_cmd = format!("{{ {}\n}} < /dev/tty > /dev/tty 2> /dev/tty", command_line);
+
+    Command::new("sh").arg("-c").arg(&shell_cmd).status()?;
+
+    Ok(())
+}
+
+/// Open all `paths` with a single `program` invocation in this terminal and wait for it
+/// The paths reach the program as arguments of the shell, unquoted and intact
+#[cfg(unix)]
+pub fn open_external_program_all(program: &str, paths: &[PathBuf]) -> Result<()> {
     let shell_cmd = format!(
-        "{} '{}' < /dev/tty > /dev/tty 2> /dev/tty",
-        program,
-        path.replace("'", "'\\''")
+        "{{ {}\n}} < /dev/tty > /dev/tty 2> /dev/tty",
+        launcher::command_line_args(program)
     );
 
-    Command::new("sh").arg("-c").arg(&shell_cmd).status()?;
+    // The first argument after the script is the shell's $0
+    Command::new("sh")
+        .arg("-c")
+        .arg(&shell_cmd)
+        .arg("sh")
+        .args(paths)
+        .status()?;
 
     Ok(())
 }
 
 #[cfg(windows)]
-pub fn open_external_program(program: &str, path: &str) -> Result<()> {
+pub fn open_external_program(program: &str, path: &str, line: Option<usize>) -> Result<()> {
     // On Windows, use cmd /C to handle .exe, .cmd, .bat files
     // This allows VS Code (code.cmd) and other script-based programs to work
 
     if program.contains("explorer") || program.contains("start") {
         // File manager: use cmd /C start to open without waiting
-        Command::new("cmd")
-            .args(["/C", "start", "", path])
-            .spawn()?; // spawn instead of status to avoid waiting
+        spawn_reaped(Command::new("cmd").args(["/C", "start", "", path]))?; // Don't wait
     } else {
-        // Editor or other program: use cmd /C to support scripts (.cmd, .bat)
-        // Quote the path to handle spaces
-        let quoted_path = if path.contains(' ') {
-            format!("\"{}\"", path)
-        } else {
-            path.to_string()
-        };
+        run_in_terminal(&launcher::command_line(program, path, line))?;
+    }
 
+    Ok(())
+}
+
+/// Run a command line (from `launcher`) in this terminal and wait for it
+#[cfg(windows)]
+pub fn run_in_terminal(command_line: &str) -> Result<()> {
+    // Editor or other program: use cmd /C to support scripts (.cmd, .bat)
+    use std::os::windows::process::CommandExt;
+    Command::new("cmd")
+        .arg("/C")
+        .raw_arg(command_line)
+        .status()?;
+    Ok(())
+}
+
+/// Open all `paths` with a single `program` invocation in this terminal and wait for it
+#[cfg(windows)]
+pub fn open_external_program_all(program: &str, paths: &[PathBuf]) -> Result<()> {
+    // cmd has no argument list to hand over: the paths are quoted into the line
+    run_in_terminal(&launcher::command_line_all(program, paths))
+}
+
+/// Open a path with the system's default application, without waiting for it
+#[cfg(unix)]
+pub fn open_with_system_handler(path: &Path) -> Result<()> {
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+
+    // Detach from the TUI: the handler must not draw over the terminal
+    spawn_reaped(
+        Command::new(opener)
+            .arg(path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null()),
+    )?;
+
+    Ok(())
+}
+
+#[cfg(windows)]
+pub fn open_with_system_handler(path: &Path) -> Result<()> {
+    // "start" with an empty title opens the file with its associated program
+    spawn_reaped(
         Command::new("cmd")
-            .args(["/C", program, &quoted_path])
-            .status()?;
-    }
+            .args(["/C", "start", ""])
+            .arg(path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null()),
+    )?;
 
     Ok(())
 }
 
+/// Start `command` without waiting for it; a thread waits for it to exit instead,
+/// so it doesn't stay behind as a zombie process
+pub fn spawn_reaped(command: &mut Command) -> std::io::Result<()> {
+    let mut child = command.spawn()?;
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
 /// Check if a path is absolute according to platform conventions
 #[cfg(unix)]
 pub fn is_absolute_path(path: &str) -> bool {
@@ -99,6 +177,100 @@ pub fn canonicalize_and_normalize(
     Ok(canonical)
 }
 
+/// Check if a file or directory should be treated as hidden
+/// Dotfiles are hidden on every platform; on Windows the FILE_ATTRIBUTE_HIDDEN flag is honored too
+pub fn is_hidden(path: &Path) -> bool {
+    let is_dotfile = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|name| name.starts_with('.'));
+
+    is_dotfile || has_hidden_attribute(path)
+}
+
+#[cfg(unix)]
+fn has_hidden_attribute(_path: &Path) -> bool {
+    // Unix has no hidden attribute - only the dotfile convention applies
+    false
+}
+
+#[cfg(windows)]
+fn has_hidden_attribute(path: &Path) -> bool {
+    use std::os::windows::fs::MetadataExt;
+
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+
+    // Use symlink_metadata so a hidden junction is judged by its own attributes
+    std::fs::symlink_metadata(path)
+        .map(|metadata| metadata.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0)
+        .unwrap_or(false)
+}
+
+/// Identity of a file on disk; hard links to one file share device and inode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileId {
+    pub device: u64,
+    pub inode: u64,
+    /// Number of hard links (directory entries) to the file
+    pub links: u64,
+}
+
+impl FileId {
+    /// Whether `other` is the same file (another hard link to it)
+    pub fn same_file(&self, other: &FileId) -> bool {
+        self.device == other.device && self.inode == other.inode
+    }
+}
+
+/// Device, inode and link count of an entry
+/// Returns None where the platform doesn't expose them through metadata
+#[cfg(unix)]
+pub fn file_id(metadata: &std::fs::Metadata) -> Option<FileId> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some(FileId {
+        device: metadata.dev(),
+        inode: metadata.ino(),
+        links: metadata.nlink(),
+    })
+}
+
+#[cfg(windows)]
+pub fn file_id(_metadata: &std::fs::Metadata) -> Option<FileId> {
+    // The file index needs an open handle (GetFileInformationByHandle) - not shown
+    None
+}
+
+/// Permission bits of an entry, including setuid/setgid/sticky
+#[cfg(unix)]
+pub fn file_mode(metadata: &std::fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+
+    metadata.permissions().mode() & 0o7777
+}
+
+#[cfg(windows)]
+pub fn file_mode(_metadata: &std::fs::Metadata) -> u32 {
+    // No Unix permission bits - nothing is highlighted
+    0
+}
+
+/// List available drive roots (C:\, D:\, mapped network shares, ...)
+/// Returns an empty list on platforms with a single filesystem root
+#[cfg(unix)]
+pub fn list_drives() -> Vec<PathBuf> {
+    Vec::new()
+}
+
+#[cfg(windows)]
+pub fn list_drives() -> Vec<PathBuf> {
+    // Probe every drive letter - mapped network drives are reported like local ones
+    (b'A'..=b'Z')
+        .map(|letter| PathBuf::from(format!("{}:\\", letter as char)))
+        .filter(|root| root.exists())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -138,4 +310,12 @@ mod tests {
             assert_eq!(normalize_path_separator("path\\to\\file"), "path\\to\\file");
         }
     }
+
+    #[test]
+    fn test_is_hidden_dotfile() {
+        assert!(is_hidden(Path::new(".git")));
+        assert!(is_hidden(Path::new("project/.env")));
+        assert!(!is_hidden(Path::new("project/src")));
+        assert!(!is_hidden(Path::new("file.txt")));
+    }
 }
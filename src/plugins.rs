@@ -0,0 +1,482 @@
+use crate::completion::{self, Completion};
+use crate::notifications::Level;
+use crate::oplog;
+use anyhow::{Context, Result};
+use rhai::{CallFnOptions, Dynamic, Engine, Scope, AST};
+use std::cell::{Cell, RefCell};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::rc::Rc;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Called with the path whenever the tree selection changes
+const ON_SELECT: &str = "on_select";
+/// Called with the path when a file is opened (Enter, editor key)
+const ON_OPEN: &str = "on_open";
+/// Returns the text to show in the viewer for a path, or `()` for the built-in preview
+const PREVIEW: &str = "preview";
+/// `fn command_<name>(path)` becomes the command `<name>` in the `@` prompt
+const COMMAND_PREFIX: &str = "command_";
+
+/// Script operations per call before it is aborted (stops runaway loops freezing the UI)
+const MAX_OPERATIONS: u64 = 1_000_000;
+/// Wall-clock time per call before the script is aborted (a slow `shell()` included)
+const SCRIPT_TIMEOUT: Duration = Duration::from_secs(3);
+/// Time a `shell()` command may run before it is killed
+const SHELL_TIMEOUT: Duration = Duration::from_secs(2);
+/// Bytes of a `shell()` command's output kept; the pipe is closed after that
+const MAX_SHELL_OUTPUT: u64 = 1024 * 1024;
+
+type Messages = Rc<RefCell<Vec<(Level, String)>>>;
+
+/// A loaded `.rhai` script
+struct Plugin {
+    name: String,
+    ast: AST,
+}
+
+impl Plugin {
+    fn defines(&self, function: &str) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|f| f.name == function && f.params.len() == 1)
+    }
+}
+
+/// Rhai plugins from `~/.config/dtree/plugins/*.rhai`
+///
+/// Scripts define hook functions (`on_select(path)`, `on_open(path)`, `preview(path)`)
+/// and commands (`command_<name>(path)`, run from the `@` prompt with the selected path).
+/// Messages from `notify`/`warn` and script errors are queued and shown as toasts.
+pub struct Plugins {
+    engine: Engine,
+    plugins: Vec<Plugin>,
+    messages: Messages,
+    deadline: Rc<Cell<Instant>>, // When the running call is aborted
+}
+
+impl Default for Plugins {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Plugins {
+    /// No plugins loaded
+    pub fn new() -> Self {
+        let messages: Messages = Rc::new(RefCell::new(Vec::new()));
+        let deadline = Rc::new(Cell::new(Instant::now()));
+        Self {
+            engine: Self::create_engine(&messages, &deadline),
+            plugins: Vec::new(),
+            messages,
+            deadline,
+        }
+    }
+
+    pub fn plugin_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("dtree").join("plugins"))
+    }
+
+    /// Load all plugins from the plugin directory
+    /// A broken script is skipped; its error is queued as a warning
+    pub fn load() -> Self {
+        let mut plugins = Self::new();
+        if let Some(dir) = Self::plugin_dir() {
+            plugins.load_dir(&dir);
+        }
+        plugins
+    }
+
+    /// Load `*.rhai` files from `dir` in name order
+    pub fn load_dir(&mut self, dir: &Path) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            if let Err(e) = self.load_file(&path) {
+                self.queue(Level::Warn, format!("{:#}", e));
+            }
+        }
+    }
+
+    /// Compile a script and run its top-level statements once
+    pub fn load_file(&mut self, path: &Path) -> Result<()> {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("Plugin {}: cannot read {}", name, path.display()))?;
+        let ast = self
+            .engine
+            .compile(&source)
+            .map_err(|e| anyhow::anyhow!("Plugin {}: {}", name, e))?;
+        self.deadline.set(Instant::now() + SCRIPT_TIMEOUT);
+        self.engine
+            .run_ast_with_scope(&mut Scope::new(), &ast)
+            .map_err(|e| anyhow::anyhow!("Plugin {}: {}", name, e))?;
+
+        oplog::record(format!("Loaded plugin {} from {}", name, path.display()));
+        self.plugins.push(Plugin { name, ast });
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// Messages and errors from scripts since the last call
+    pub fn take_messages(&self) -> Vec<(Level, String)> {
+        self.messages.borrow_mut().drain(..).collect()
+    }
+
+    pub fn on_select(&self, path: &Path) {
+        self.call_all(ON_SELECT, path);
+    }
+
+    pub fn on_open(&self, path: &Path) {
+        self.call_all(ON_OPEN, path);
+    }
+
+    /// Custom preview text from the first plugin that handles `path`
+    pub fn preview(&self, path: &Path) -> Option<String> {
+        self.plugins
+            .iter()
+            .filter(|plugin| plugin.defines(PREVIEW))
+            .find_map(|plugin| {
+                let result = self.call(plugin, PREVIEW, path)?;
+                result.into_string().ok()
+            })
+    }
+
+    /// Command names defined by all plugins, sorted
+    pub fn command_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .plugins
+            .iter()
+            .flat_map(|plugin| plugin.ast.iter_functions())
+            .filter(|f| f.params.len() == 1)
+            .filter_map(|f| f.name.strip_prefix(COMMAND_PREFIX).map(str::to_string))
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Run a command on `path`; a returned string is shown as a toast
+    /// Returns false if no plugin defines the command
+    pub fn run_command(&self, name: &str, path: &Path) -> bool {
+        let function = format!("{}{}", COMMAND_PREFIX, name);
+        let Some(plugin) = self.plugins.iter().find(|plugin| plugin.defines(&function)) else {
+            return false;
+        };
+
+        oplog::record(format!("Plugin command {} on {}", name, path.display()));
+        if let Some(result) = self.call(plugin, &function, path) {
+            if let Ok(text) = result.into_string() {
+                if !text.is_empty() {
+                    self.queue(Level::Info, text);
+                }
+            }
+        }
+        true
+    }
+
+    fn call_all(&self, function: &str, path: &Path) {
+        for plugin in self
+            .plugins
+            .iter()
+            .filter(|plugin| plugin.defines(function))
+        {
+            self.call(plugin, function, path);
+        }
+    }
+
+    /// Call a script function with the path; errors are queued, not returned
+    fn call(&self, plugin: &Plugin, function: &str, path: &Path) -> Option<Dynamic> {
+        // Top-level statements already ran at load time
+        let options = CallFnOptions::new().eval_ast(false);
+        let arg = path.to_string_lossy().to_string();
+        self.deadline.set(Instant::now() + SCRIPT_TIMEOUT);
+        match self.engine.call_fn_with_options::<Dynamic>(
+            options,
+            &mut Scope::new(),
+            &plugin.ast,
+            function,
+            (arg,),
+        ) {
+            Ok(result) => Some(result),
+            Err(e) => {
+                self.queue(
+                    Level::Error,
+                    format!("Plugin {}: {}: {}", plugin.name, function, e),
+                );
+                None
+            }
+        }
+    }
+
+    fn queue(&self, level: Level, message: String) {
+        self.messages.borrow_mut().push((level, message));
+    }
+
+    /// Engine with the functions scripts can call
+    fn create_engine(messages: &Messages, deadline: &Rc<Cell<Instant>>) -> Engine {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let deadline = Rc::clone(deadline);
+        engine.on_progress(move |_| {
+            (Instant::now() > deadline.get()).then(|| Dynamic::from("script timed out"))
+        });
+
+        let queue = Rc::clone(messages);
+        engine.register_fn("notify", move |text: &str| {
+            queue.borrow_mut().push((Level::Info, text.to_string()));
+        });
+        let queue = Rc::clone(messages);
+        engine.register_fn("warn", move |text: &str| {
+            queue.borrow_mut().push((Level::Warn, text.to_string()));
+        });
+
+        engine.register_fn("file_name", |path: &str| -> String {
+            Path::new(path)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+        engine.register_fn("extension", |path: &str| -> String {
+            Path::new(path)
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+        engine.register_fn("is_dir", |path: &str| Path::new(path).is_dir());
+        engine.register_fn("read_file", |path: &str| -> String {
+            fs::read_to_string(path).unwrap_or_default()
+        });
+        engine.register_fn("shell", shell);
+
+        engine
+    }
+}
+
+/// Run a shell command and return its standard output (empty on failure or timeout)
+/// Output past `MAX_SHELL_OUTPUT` is dropped; a command still running after
+/// `SHELL_TIMEOUT` is killed
+fn shell(command: &str) -> String {
+    #[cfg(windows)]
+    let mut cmd = {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    };
+    #[cfg(not(windows))]
+    let mut cmd = {
+        use std::os::unix::process::CommandExt;
+        let mut cmd = Command::new("sh");
+        // Its own process group, so a timeout kills what the command started too
+        cmd.args(["-c", command]).process_group(0);
+        cmd
+    };
+
+    let Ok(mut child) = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+    else {
+        return String::new();
+    };
+    let deadline = Instant::now() + SHELL_TIMEOUT;
+
+    // Read on a thread so a command that never closes its output can't block past the deadline
+    let (output_tx, output_rx) = mpsc::channel();
+    if let Some(stdout) = child.stdout.take() {
+        thread::spawn(move || {
+            let mut output = Vec::new();
+            let _ = stdout.take(MAX_SHELL_OUTPUT).read_to_end(&mut output);
+            let _ = output_tx.send(output);
+        });
+    }
+    let output = output_rx
+        .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        .unwrap_or_default();
+
+    // Reap the command, killing it once the time is up
+    loop {
+        match child.try_wait() {
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(10)),
+            Ok(None) => {
+                #[cfg(unix)]
+                unsafe {
+                    libc::killpg(child.id() as libc::pid_t, libc::SIGKILL);
+                }
+                let _ = child.kill();
+                let _ = child.wait();
+                break;
+            }
+            _ => break,
+        }
+    }
+    String::from_utf8_lossy(&output).to_string()
+}
+
+/// Plugin command prompt (`@`) with Tab completion of command names
+#[derive(Debug, Default)]
+pub struct CommandPrompt {
+    pub is_open: bool,
+    pub input: String,
+    pub error: Option<String>,
+    pub completion: Completion,
+}
+
+impl CommandPrompt {
+    pub fn open(&mut self) {
+        self.input.clear();
+        self.error = None;
+        self.completion.reset();
+        self.is_open = true;
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+        self.input.clear();
+        self.error = None;
+        self.completion.reset();
+    }
+
+    pub fn add_char(&mut self, c: char) {
+        self.input.push(c);
+        self.error = None;
+        self.completion.reset();
+    }
+
+    pub fn backspace(&mut self) {
+        self.input.pop();
+        self.error = None;
+        self.completion.reset();
+    }
+
+    /// Tab/Shift+Tab: complete against the available command names
+    pub fn complete(&mut self, names: &[String], forward: bool) {
+        let source =
+            |input: &str| completion::name_candidates(input, names.iter().map(String::as_str));
+        let completed = if forward {
+            self.completion.next(&self.input, source)
+        } else {
+            self.completion.prev(&self.input, source)
+        };
+        match completed {
+            Some(completed) => {
+                self.input = completed;
+                self.error = None;
+            }
+            None => self.error = Some("no match".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_hooks_commands_and_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("a.rhai"),
+            r#"
+            notify("a loaded");
+            fn on_select(path) { notify("selected " + file_name(path)); }
+            fn preview(path) { if extension(path) == "csv" { "csv: " + path } }
+            fn command_shout(path) { file_name(path).to_upper() }
+            "#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("b.rhai"),
+            "fn command_fail(path) { throw \"boom\"; }",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("broken.rhai"), "fn (").unwrap();
+        fs::write(temp_dir.path().join("notes.txt"), "not a plugin").unwrap();
+
+        let mut plugins = Plugins::new();
+        plugins.load_dir(temp_dir.path());
+        assert!(!plugins.is_empty());
+        let messages = plugins.take_messages();
+        assert_eq!(messages[0], (Level::Info, "a loaded".to_string()));
+        assert!(messages[1].1.starts_with("Plugin broken:"));
+
+        plugins.on_select(Path::new("/tmp/x.txt"));
+        assert_eq!(
+            plugins.take_messages(),
+            [(Level::Info, "selected x.txt".to_string())]
+        );
+
+        // () falls back to the built-in preview
+        assert_eq!(
+            plugins.preview(Path::new("/tmp/a.csv")),
+            Some("csv: /tmp/a.csv".to_string())
+        );
+        assert_eq!(plugins.preview(Path::new("/tmp/a.txt")), None);
+
+        assert_eq!(plugins.command_names(), ["fail", "shout"]);
+        assert!(plugins.run_command("shout", Path::new("/tmp/x.txt")));
+        assert_eq!(
+            plugins.take_messages(),
+            [(Level::Info, "X.TXT".to_string())]
+        );
+        assert!(plugins.run_command("fail", Path::new("/tmp/x.txt")));
+        let errors = plugins.take_messages();
+        assert_eq!(errors[0].0, Level::Error);
+        assert!(errors[0].1.contains("boom"));
+        assert!(!plugins.run_command("missing", Path::new("/tmp")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_runaway_scripts_and_commands_are_cut_off() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("slow.rhai"),
+            r#"
+            fn command_spin(path) { loop { shell("true"); } }
+            fn command_hang(path) { shell("echo started; sleep 30") }
+            fn command_flood(path) { shell("yes").len().to_string() }
+            "#,
+        )
+        .unwrap();
+        let mut plugins = Plugins::new();
+        plugins.load_dir(temp_dir.path());
+
+        let started = Instant::now();
+        assert!(plugins.run_command("spin", Path::new("/tmp")));
+        let errors = plugins.take_messages();
+        assert_eq!(errors[0].0, Level::Error);
+        assert!(errors[0].1.contains("terminated"));
+
+        // A command that outlives the timeout gives nothing
+        assert!(plugins.run_command("hang", Path::new("/tmp")));
+        assert!(plugins.take_messages().is_empty());
+
+        // Endless output is capped
+        assert!(plugins.run_command("flood", Path::new("/tmp")));
+        let messages = plugins.take_messages();
+        assert_eq!(messages, [(Level::Info, MAX_SHELL_OUTPUT.to_string())]);
+        assert!(started.elapsed() < SCRIPT_TIMEOUT + SHELL_TIMEOUT * 2);
+    }
+}
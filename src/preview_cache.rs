@@ -0,0 +1,57 @@
+use crate::lru_cache::LruCache;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Files kept unless `preview_cache_size` says otherwise
+pub const DEFAULT_SIZE: usize = 16;
+
+/// Lines of recently read files
+static CACHE: Mutex<LruCache<PreviewKey, CachedPreview>> = Mutex::new(LruCache::new(DEFAULT_SIZE));
+
+/// What the read lines depend on: the file (path, mtime, size) and which part of
+/// it was read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreviewKey {
+    pub path: PathBuf,
+    pub modified: Option<SystemTime>,
+    pub size: u64,
+    pub tail_mode: bool,
+    pub max_lines: usize,
+    pub head_tail_lines: usize,
+}
+
+/// Lines of a text file as the viewer shows them (before highlighting, which
+/// `highlight_cache` keeps)
+#[derive(Debug, Clone)]
+pub struct CachedPreview {
+    pub content: Vec<String>,
+    pub line_numbers: Vec<usize>,
+    pub total_lines: Option<usize>,
+}
+
+/// Number of files kept (`preview_cache_size`; 0 turns the cache off)
+pub fn set_size(size: usize) {
+    if let Ok(mut cache) = CACHE.lock() {
+        cache.set_capacity(size);
+    }
+}
+
+/// Lines cached for `key`
+pub fn get(key: &PreviewKey) -> Option<CachedPreview> {
+    CACHE.lock().ok()?.get(key)
+}
+
+/// Cache the lines read for `key`, dropping the least recently used file
+pub fn insert(key: PreviewKey, preview: CachedPreview) {
+    if let Ok(mut cache) = CACHE.lock() {
+        cache.insert(key, preview);
+    }
+}
+
+/// Drop everything cached for `path`, so it's read from disk again (reload)
+pub fn forget(path: &Path) {
+    if let Ok(mut cache) = CACHE.lock() {
+        cache.retain(|cached| cached.path != path);
+    }
+}
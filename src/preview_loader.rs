@@ -0,0 +1,212 @@
+use crate::file_viewer::{FileViewer, PreviewLimits};
+use crate::vfs::Vfs;
+use crossbeam_channel::{unbounded, Receiver, RecvTimeoutError, Sender};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Longest `finish` waits for a preview still being loaded
+const FINISH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A preview to load in the background (the arguments of `load_file_with_width`
+/// plus the viewer settings they depend on)
+#[derive(Debug, Clone)]
+pub struct PreviewRequest {
+    pub path: PathBuf,
+    pub max_width: Option<usize>,
+    pub max_lines: usize,
+    /// Theme to highlight with; None = no syntax highlighting
+    pub syntax_theme: Option<String>,
+    pub tail_mode: bool,
+    pub wrap_lines: bool,
+    pub limits: PreviewLimits,
+    pub vfs: Vfs,
+}
+
+struct Job {
+    request: PreviewRequest,
+    cancel: Arc<AtomicBool>,
+}
+
+/// Reads and highlights previews on a worker thread, so moving the selection
+/// never waits for a file
+/// A request only starts once the selection rests for `debounce`; a newer
+/// request (or a synchronous load) cancels the one before it
+pub struct PreviewLoader {
+    pub debounce: Duration,
+    /// Request waiting for the debounce, with the time it was made
+    pending: Option<(Job, Instant)>,
+    /// Cancel flag of the request sent to the worker last
+    current: Option<Arc<AtomicBool>>,
+    job_sender: Option<Sender<Job>>,
+    result_receiver: Option<Receiver<(Arc<AtomicBool>, FileViewer)>>,
+    worker_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl Default for PreviewLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PreviewLoader {
+    pub fn new() -> Self {
+        Self {
+            debounce: Duration::from_millis(30),
+            pending: None,
+            current: None,
+            job_sender: None,
+            result_receiver: None,
+            worker_handle: None,
+        }
+    }
+
+    /// Initialize worker thread if not already running
+    fn ensure_worker_running(&mut self) {
+        if self.worker_handle.is_some() {
+            return;
+        }
+
+        let (job_tx, job_rx) = unbounded();
+        let (result_tx, result_rx) = unbounded();
+        let handle = thread::spawn(move || worker_loop(job_rx, result_tx));
+
+        self.job_sender = Some(job_tx);
+        self.result_receiver = Some(result_rx);
+        self.worker_handle = Some(handle);
+    }
+
+    /// Load `request` once the debounce has passed, replacing any earlier request
+    pub fn request(&mut self, request: PreviewRequest) {
+        self.cancel();
+        let job = Job {
+            request,
+            cancel: Arc::new(AtomicBool::new(false)),
+        };
+        self.pending = Some((job, Instant::now()));
+    }
+
+    /// Drop the waiting request and stop the one being loaded
+    pub fn cancel(&mut self) {
+        self.pending = None;
+        if let Some(cancel) = self.current.take() {
+            cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Send the waiting request once its debounce has passed and collect the
+    /// loaded preview; returns the viewer to take the content from
+    pub fn poll(&mut self) -> Option<FileViewer> {
+        if self
+            .pending
+            .as_ref()
+            .is_some_and(|(_, requested)| requested.elapsed() >= self.debounce)
+        {
+            self.dispatch();
+        }
+
+        let receiver = self.result_receiver.as_ref()?;
+        let mut loaded = None;
+        while let Ok((cancel, viewer)) = receiver.try_recv() {
+            if take_if_current(&mut self.current, &cancel) {
+                loaded = Some(viewer);
+            }
+        }
+        loaded
+    }
+
+    /// Whether a request waits for its debounce or is being loaded
+    pub fn is_busy(&self) -> bool {
+        self.pending.is_some() || self.current.is_some()
+    }
+
+    /// Load the waiting request right away and wait for it (headless driver)
+    pub fn finish(&mut self) -> Option<FileViewer> {
+        if self.pending.is_some() {
+            self.dispatch();
+        }
+        let started = Instant::now();
+        while self.current.is_some() {
+            let receiver = self.result_receiver.as_ref()?;
+            let timeout = FINISH_TIMEOUT.saturating_sub(started.elapsed());
+            match receiver.recv_timeout(timeout) {
+                Ok((cancel, viewer)) => {
+                    if take_if_current(&mut self.current, &cancel) {
+                        return Some(viewer);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => {
+                    self.cancel();
+                    return None;
+                }
+            }
+        }
+        None
+    }
+
+    fn dispatch(&mut self) {
+        let Some((job, _)) = self.pending.take() else {
+            return;
+        };
+        self.ensure_worker_running();
+        self.current = Some(Arc::clone(&job.cancel));
+        if let Some(sender) = &self.job_sender {
+            let _ = sender.send(job);
+        }
+    }
+}
+
+impl Drop for PreviewLoader {
+    fn drop(&mut self) {
+        self.cancel();
+        // Closing the channel ends the worker; a large file is abandoned, not awaited
+        self.job_sender = None;
+        self.worker_handle = None;
+    }
+}
+
+/// Whether a result belongs to the latest request (which is then done)
+fn take_if_current(current: &mut Option<Arc<AtomicBool>>, cancel: &Arc<AtomicBool>) -> bool {
+    if !current
+        .as_ref()
+        .is_some_and(|current| Arc::ptr_eq(current, cancel))
+    {
+        return false;
+    }
+    *current = None;
+    !cancel.load(Ordering::Relaxed)
+}
+
+/// Worker thread loop: loads the latest job, skipping the ones already replaced
+fn worker_loop(job_rx: Receiver<Job>, result_tx: Sender<(Arc<AtomicBool>, FileViewer)>) {
+    while let Ok(mut job) = job_rx.recv() {
+        while let Ok(newer) = job_rx.try_recv() {
+            job = newer;
+        }
+        if job.cancel.load(Ordering::Relaxed) {
+            continue;
+        }
+
+        let request = job.request;
+        let _span = tracing::debug_span!("preview", path = %request.path.display()).entered();
+        let mut viewer = FileViewer::new();
+        viewer.tail_mode = request.tail_mode;
+        viewer.wrap_lines = request.wrap_lines;
+        viewer.limits = request.limits;
+        viewer.vfs = request.vfs;
+        viewer.set_cancel(Arc::clone(&job.cancel));
+        let _ = viewer.load_file_with_width(
+            &request.path,
+            request.max_width,
+            request.max_lines,
+            request.syntax_theme.is_some(),
+            request.syntax_theme.as_deref().unwrap_or_default(),
+        );
+
+        if !job.cancel.load(Ordering::Relaxed) && result_tx.send((job.cancel, viewer)).is_err() {
+            break;
+        }
+    }
+}
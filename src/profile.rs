@@ -0,0 +1,97 @@
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Startup phase timings for --profile-startup
+struct Profile {
+    started: Instant,
+    enabled: bool,
+    /// End of the last recorded phase
+    finished: Option<Instant>,
+    phases: Vec<(&'static str, Duration)>,
+}
+
+static PROFILE: Lazy<Mutex<Profile>> = Lazy::new(|| {
+    Mutex::new(Profile {
+        started: Instant::now(),
+        enabled: false,
+        finished: None,
+        phases: Vec::new(),
+    })
+});
+
+/// Start the startup clock (call first thing in main)
+pub fn start() {
+    Lazy::force(&PROFILE);
+}
+
+/// Turn on --profile-startup: dtree exits after the first frame and prints the timings
+pub fn enable() {
+    if let Ok(mut profile) = PROFILE.lock() {
+        profile.enabled = true;
+    }
+}
+
+pub fn is_enabled() -> bool {
+    PROFILE
+        .lock()
+        .map(|profile| profile.enabled)
+        .unwrap_or(false)
+}
+
+/// Run `f` and record how long it took as startup phase `name`
+pub fn phase<T>(name: &'static str, f: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let result = f();
+    record(name, started.elapsed());
+    result
+}
+
+pub fn record(name: &'static str, duration: Duration) {
+    tracing::debug!("startup: {} took {:?}", name, duration);
+    if let Ok(mut profile) = PROFILE.lock() {
+        profile.phases.push((name, duration));
+        profile.finished = Some(Instant::now());
+    }
+}
+
+/// Timing table: one line per phase, then the total from `start` to the end of the last phase
+pub fn report() -> String {
+    let Ok(profile) = PROFILE.lock() else {
+        return String::new();
+    };
+    let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+
+    let mut report = String::from("Startup profile:\n");
+    for (name, duration) in &profile.phases {
+        report.push_str(&format!("  {:<16} {:>9.3} ms\n", name, ms(*duration)));
+    }
+    report.push_str(&format!(
+        "  {:<16} {:>9.3} ms\n",
+        "total",
+        ms(profile
+            .finished
+            .unwrap_or_else(Instant::now)
+            .duration_since(profile.started))
+    ));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phases_are_reported() {
+        start();
+        let value = phase("profile test", || 42);
+        assert_eq!(value, 42);
+        record("profile manual", Duration::from_millis(2));
+
+        let report = report();
+        assert!(report.starts_with("Startup profile:\n"));
+        assert!(report.contains("  profile test "));
+        assert!(report.contains("  profile manual       2.000 ms\n"));
+        assert!(report.lines().last().unwrap().starts_with("  total "));
+    }
+}
@@ -0,0 +1,270 @@
+use crate::platform;
+use crossbeam_channel::{bounded, Receiver};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+/// Files and directories marking the top of a project, with the kind shown in the panel
+const MARKERS: &[(&str, &str)] = &[
+    (".git", "git"),
+    ("Cargo.toml", "rust"),
+    ("package.json", "node"),
+    ("go.mod", "go"),
+    ("pyproject.toml", "python"),
+];
+
+/// Directories that hold dependencies or build outputs, not projects of their own
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "vendor", "dist", "build"];
+
+/// Levels below the root searched for projects
+const MAX_DEPTH: usize = 4;
+
+/// Projects listed at most; the rest of a huge tree is left out
+pub const MAX_PROJECTS: usize = 500;
+
+/// Directory with at least one project marker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Project {
+    pub path: PathBuf,
+    /// Kinds of the markers found, e.g. ["git", "rust"]
+    pub kinds: Vec<&'static str>,
+}
+
+/// Kinds of the project markers in `dir`
+pub fn markers(dir: &Path) -> Vec<&'static str> {
+    MARKERS
+        .iter()
+        .filter(|(marker, _)| dir.join(marker).exists())
+        .map(|&(_, kind)| kind)
+        .collect()
+}
+
+/// Projects around `root`: those containing it (outermost first), then the root and the
+/// projects below it by path
+/// Hidden directories, symlinks and dependency/build directories are not searched
+pub fn detect(root: &Path) -> Vec<Project> {
+    let _span = tracing::debug_span!("detect_projects", root = %root.display()).entered();
+    let project = |path: &Path| {
+        let kinds = markers(path);
+        (!kinds.is_empty()).then(|| Project {
+            path: path.to_path_buf(),
+            kinds,
+        })
+    };
+
+    let mut projects: Vec<Project> = root.ancestors().skip(1).filter_map(project).collect();
+    projects.reverse();
+
+    let mut below = Vec::new();
+    let mut dirs = vec![(root.to_path_buf(), 0)];
+    while let Some((dir, depth)) = dirs.pop() {
+        if below.len() == MAX_PROJECTS {
+            break;
+        }
+        below.extend(project(&dir));
+        if depth == MAX_DEPTH {
+            continue;
+        }
+        let Ok(read_dir) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in read_dir.flatten() {
+            let path = entry.path();
+            let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+            let skipped = entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| SKIPPED_DIRS.contains(&name));
+            if is_dir && !skipped && !platform::is_hidden(&path) {
+                dirs.push((path, depth + 1));
+            }
+        }
+    }
+    below.sort_by(|a, b| a.path.cmp(&b.path));
+    projects.extend(below);
+    projects
+}
+
+/// Projects panel: detected projects around the root for quick jumps
+#[derive(Debug, Default)]
+pub struct Projects {
+    projects: Vec<Project>,
+    root: PathBuf,
+    pub is_selecting: bool,
+    pub selected_index: usize,
+    receiver: Option<Receiver<Vec<Project>>>,
+}
+
+impl Projects {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the panel and detect the projects around `root` in the background
+    pub fn enter_selection_mode(&mut self, root: &Path) {
+        let (tx, rx) = bounded(1);
+        let root_path = root.to_path_buf();
+        thread::spawn(move || {
+            let _ = tx.send(detect(&root_path));
+        });
+        self.projects.clear();
+        self.root = root.to_path_buf();
+        self.selected_index = 0;
+        self.receiver = Some(rx);
+        self.is_selecting = true;
+    }
+
+    pub fn exit_selection_mode(&mut self) {
+        self.is_selecting = false;
+        self.selected_index = 0;
+        self.receiver = None;
+    }
+
+    /// Pick up the detected projects
+    /// Returns true when they arrived and UI needs to be redrawn
+    pub fn poll(&mut self) -> bool {
+        let Some(receiver) = &self.receiver else {
+            return false;
+        };
+        let Ok(projects) = receiver.try_recv() else {
+            return false;
+        };
+        self.set_projects(projects);
+        true
+    }
+
+    fn set_projects(&mut self, projects: Vec<Project>) {
+        // Start on the root's project or the first one below it, not on an ancestor
+        self.selected_index = projects
+            .iter()
+            .position(|project| project.path.starts_with(&self.root))
+            .unwrap_or(0);
+        self.projects = projects;
+        self.receiver = None;
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.receiver.is_some()
+    }
+
+    pub fn list(&self) -> &[Project] {
+        &self.projects
+    }
+
+    /// Path shown in the panel: relative to the root, `..` steps for projects containing it
+    pub fn display_path(&self, project: &Project) -> String {
+        if let Ok(relative) = project.path.strip_prefix(&self.root) {
+            if relative.as_os_str().is_empty() {
+                return ".".to_string();
+            }
+            return relative.display().to_string();
+        }
+        let levels = self
+            .root
+            .ancestors()
+            .position(|ancestor| ancestor == project.path);
+        match levels {
+            Some(levels) => vec![".."; levels].join("/"),
+            None => project.path.display().to_string(),
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        if self.selected_index > 0 {
+            self.selected_index -= 1;
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        if self.selected_index + 1 < self.projects.len() {
+            self.selected_index += 1;
+        }
+    }
+
+    /// Select the project by its number in the panel (1-9)
+    pub fn select_number(&mut self, number: usize) -> bool {
+        if number >= 1 && number <= self.projects.len() {
+            self.selected_index = number - 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn get_selected(&self) -> Option<&Project> {
+        self.projects.get(self.selected_index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_finds_projects_below_and_around_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("src");
+        fs::create_dir_all(temp_dir.path().join(".git")).unwrap();
+        fs::create_dir_all(root.join("tool/crates/core")).unwrap();
+        fs::write(root.join("tool/Cargo.toml"), "").unwrap();
+        fs::write(root.join("tool/crates/core/Cargo.toml"), "").unwrap();
+        fs::create_dir_all(root.join("tool/.git")).unwrap();
+        fs::create_dir_all(root.join("web/node_modules/left-pad")).unwrap();
+        fs::write(root.join("web/package.json"), "{}").unwrap();
+        fs::write(root.join("web/node_modules/left-pad/package.json"), "{}").unwrap();
+        fs::create_dir_all(root.join("notes")).unwrap();
+
+        let projects = detect(&root);
+        let found: Vec<(PathBuf, Vec<&str>)> = projects
+            .iter()
+            .filter(|project| project.path.starts_with(temp_dir.path()))
+            .map(|project| (project.path.clone(), project.kinds.clone()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (temp_dir.path().to_path_buf(), vec!["git"]),
+                (root.join("tool"), vec!["git", "rust"]),
+                (root.join("tool/crates/core"), vec!["rust"]),
+                (root.join("web"), vec!["node"]),
+            ]
+        );
+
+        let mut panel = Projects::new();
+        panel.root = root.clone();
+        panel.set_projects(projects);
+        assert_eq!(panel.get_selected().unwrap().path, root.join("tool"));
+        let selected = panel.get_selected().unwrap().clone();
+        assert_eq!(panel.display_path(&selected), "tool");
+        let outer = Project {
+            path: temp_dir.path().to_path_buf(),
+            kinds: vec!["git"],
+        };
+        assert_eq!(panel.display_path(&outer), "..");
+    }
+
+    #[test]
+    fn test_panel_loads_in_background() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("go.mod"), "").unwrap();
+
+        let mut panel = Projects::new();
+        panel.enter_selection_mode(temp_dir.path());
+        assert!(panel.is_selecting);
+        let started = std::time::Instant::now();
+        while !panel.poll() {
+            assert!(started.elapsed() < std::time::Duration::from_secs(5));
+            thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert!(!panel.is_loading());
+        let selected = panel.get_selected().unwrap().clone();
+        assert_eq!(selected.kinds, ["go"]);
+        assert_eq!(panel.display_path(&selected), ".");
+        assert!(!panel.select_number(9));
+        assert!(panel.select_number(panel.list().len()));
+
+        panel.exit_selection_mode();
+        assert!(!panel.is_selecting);
+    }
+}
@@ -0,0 +1,306 @@
+use crate::list_selection::ListSelection;
+use anyhow::{bail, Result};
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const CODE_EXTENSIONS: &[&str] = &[
+    "rs", "go", "py", "js", "mjs", "cjs", "ts", "jsx", "tsx", "java", "kt", "scala", "c", "cpp",
+    "cc", "cxx", "h", "hpp", "hh", "hxx", "cs", "php", "rb", "lua", "vim", "sh", "bash", "zsh",
+    "fish", "ps1", "swift", "zig", "hs", "ml", "ex", "exs", "erl", "clj", "dart", "r", "pl", "sql",
+    "html", "htm", "css", "scss", "sass", "less", "vue", "svelte", "json", "toml", "yaml", "yml",
+    "xml",
+];
+
+const IMAGE_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "bmp", "svg", "ico", "webp", "tif", "tiff", "avif", "heic",
+];
+
+const DOCUMENT_EXTENSIONS: &[&str] = &[
+    "md", "markdown", "txt", "rst", "adoc", "org", "pdf", "doc", "docx", "odt", "rtf", "xls",
+    "xlsx", "ods", "csv", "ppt", "pptx", "odp", "epub", "tex",
+];
+
+/// Quick filter over the tree (`f` cycles, `|` picks a saved one): hides entries of
+/// other types
+/// Directories stay visible with the file filters so matching files can be reached
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum QuickFilter {
+    #[default]
+    All,
+    Directories,
+    Code,
+    Images,
+    Documents,
+    /// Named filter from the `[filters]` config section
+    Saved(Arc<SavedFilter>),
+}
+
+impl QuickFilter {
+    /// Order of the filters when cycling
+    const CYCLE: [QuickFilter; 5] = [
+        QuickFilter::All,
+        QuickFilter::Directories,
+        QuickFilter::Code,
+        QuickFilter::Images,
+        QuickFilter::Documents,
+    ];
+
+    /// The next built-in filter; a saved filter is followed by "all"
+    pub fn next(&self) -> Self {
+        match Self::CYCLE.iter().position(|f| f == self) {
+            Some(index) => Self::CYCLE[(index + 1) % Self::CYCLE.len()].clone(),
+            None => QuickFilter::All,
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        match self {
+            QuickFilter::All => "all",
+            QuickFilter::Directories => "directories",
+            QuickFilter::Code => "code",
+            QuickFilter::Images => "images",
+            QuickFilter::Documents => "documents",
+            QuickFilter::Saved(filter) => &filter.name,
+        }
+    }
+
+    /// Whether this is the saved filter `filter`
+    pub fn is_saved(&self, filter: &Arc<SavedFilter>) -> bool {
+        matches!(self, QuickFilter::Saved(current) if Arc::ptr_eq(current, filter))
+    }
+
+    /// Built-in filter by its label (the `type:` entries of saved filters)
+    fn from_label(label: &str) -> Option<Self> {
+        Self::CYCLE
+            .iter()
+            .find(|filter| filter.label() == label)
+            .cloned()
+    }
+
+    /// Filters that select files (they need files shown in the tree)
+    pub fn shows_files(&self) -> bool {
+        matches!(
+            self,
+            QuickFilter::Code
+                | QuickFilter::Images
+                | QuickFilter::Documents
+                | QuickFilter::Saved(_)
+        )
+    }
+
+    /// Whether the entry at `path` stays in the tree shown from `root`
+    pub fn matches(&self, path: &Path, is_dir: bool, root: &Path) -> bool {
+        if let QuickFilter::Saved(filter) = self {
+            return filter.matches(path, is_dir, root);
+        }
+        if is_dir {
+            return true;
+        }
+        let extensions = match self {
+            QuickFilter::Directories => return false,
+            QuickFilter::Code => CODE_EXTENSIONS,
+            QuickFilter::Images => IMAGE_EXTENSIONS,
+            QuickFilter::Documents => DOCUMENT_EXTENSIONS,
+            _ => return true,
+        };
+        path.extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .is_some_and(|ext| extensions.contains(&ext.as_str()))
+    }
+}
+
+/// Named filter from the config: globs and built-in types, any of which keeps a file
+///
+/// Globs with a `/` match the path below the tree root (`src/**/*.rs`), others the
+/// file name anywhere (`Cargo.*`); `type:code`, `type:images` and `type:documents`
+/// add the files of a quick filter
+#[derive(Debug)]
+pub struct SavedFilter {
+    pub name: String,
+    pub patterns: Vec<String>,
+    path_globs: GlobSet,
+    name_globs: GlobSet,
+    types: Vec<QuickFilter>,
+    /// Leading directories of the path globs (`src` of `src/**/*.rs`) and whether
+    /// the glob goes deeper: only directories on the way to matches are kept, unless
+    /// a name glob or type can match anywhere
+    dir_prefixes: Vec<(PathBuf, bool)>,
+}
+
+impl PartialEq for SavedFilter {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.patterns == other.patterns
+    }
+}
+
+impl SavedFilter {
+    pub fn new(name: &str, patterns: &[String]) -> Result<Self> {
+        if patterns.is_empty() {
+            bail!("filter '{}' has no patterns", name);
+        }
+        let mut path_globs = GlobSetBuilder::new();
+        let mut name_globs = GlobSetBuilder::new();
+        let mut types = Vec::new();
+        let mut dir_prefixes = Vec::new();
+        for pattern in patterns {
+            if let Some(kind) = pattern.strip_prefix("type:") {
+                match QuickFilter::from_label(kind) {
+                    Some(filter) if filter.shows_files() => types.push(filter),
+                    _ => bail!("filter '{}': unknown type '{}'", name, kind),
+                }
+            } else if pattern.contains('/') {
+                let pattern = pattern.trim_start_matches('/');
+                path_globs.add(glob(name, pattern)?);
+                // The last part names the files, the parts before it directories: the
+                // literal ones lead there, and a glob among them can reach any below
+                let parts: Vec<&str> = pattern.split('/').collect();
+                let dirs = &parts[..parts.len() - 1];
+                let literal = dirs
+                    .iter()
+                    .take_while(|part| !part.contains(['*', '?', '[', '{']))
+                    .count();
+                dir_prefixes.push((dirs[..literal].iter().collect(), literal < dirs.len()));
+            } else {
+                name_globs.add(glob(name, pattern)?);
+            }
+        }
+        Ok(Self {
+            name: name.to_string(),
+            patterns: patterns.to_vec(),
+            path_globs: path_globs.build()?,
+            name_globs: name_globs.build()?,
+            types,
+            dir_prefixes,
+        })
+    }
+
+    /// Whether the entry at `path` stays in the tree shown from `root`
+    pub fn matches(&self, path: &Path, is_dir: bool, root: &Path) -> bool {
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        if is_dir {
+            return !self.name_globs.is_empty()
+                || !self.types.is_empty()
+                || self.dir_prefixes.iter().any(|(prefix, deeper)| {
+                    prefix.starts_with(relative) || (*deeper && relative.starts_with(prefix))
+                });
+        }
+        self.path_globs.is_match(relative)
+            || path
+                .file_name()
+                .is_some_and(|file_name| self.name_globs.is_match(file_name))
+            || self
+                .types
+                .iter()
+                .any(|filter| filter.matches(path, false, root))
+    }
+}
+
+/// Glob where `*` stays within one directory (`**` crosses them)
+fn glob(filter: &str, pattern: &str) -> Result<Glob> {
+    GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()
+        .map_err(|e| anyhow::anyhow!("filter '{}': {}", filter, e))
+}
+
+/// Compile the `[filters]` config section; broken filters are left out and reported
+pub fn load_saved(filters: &BTreeMap<String, Vec<String>>) -> (Vec<Arc<SavedFilter>>, Vec<String>) {
+    let mut saved = Vec::new();
+    let mut errors = Vec::new();
+    for (name, patterns) in filters {
+        match SavedFilter::new(name, patterns) {
+            Ok(filter) => saved.push(Arc::new(filter)),
+            Err(e) => errors.push(format!("Saved filters: {}", e)),
+        }
+    }
+    (saved, errors)
+}
+
+/// Picker of the saved filters (`|`), shown in the bottom panel
+#[derive(Debug, Default)]
+pub struct FilterPicker {
+    pub filters: Vec<Arc<SavedFilter>>,
+    pub is_open: bool,
+    pub selected_index: usize,
+}
+
+impl FilterPicker {
+    pub fn new(filters: Vec<Arc<SavedFilter>>) -> Self {
+        Self {
+            filters,
+            ..Self::default()
+        }
+    }
+
+    /// Open the picker on the active filter (if it is a saved one)
+    pub fn open(&mut self, active: &QuickFilter) {
+        self.selected_index = self
+            .filters
+            .iter()
+            .position(|filter| active.is_saved(filter))
+            .unwrap_or(0);
+        self.is_open = true;
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+    }
+
+    /// Filter to apply for the selected entry: it, or "all" when it is already active
+    pub fn toggle(&self, active: &QuickFilter) -> Option<QuickFilter> {
+        let filter = self.filters.get(self.selected_index)?;
+        Some(if active.is_saved(filter) {
+            QuickFilter::All
+        } else {
+            QuickFilter::Saved(Arc::clone(filter))
+        })
+    }
+}
+
+impl ListSelection for FilterPicker {
+    fn selected_index_mut(&mut self) -> &mut usize {
+        &mut self.selected_index
+    }
+
+    fn entry_count(&self) -> usize {
+        self.filters.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saved_filter() {
+        let patterns = ["src/**/*.rs", "Cargo.*"].map(String::from);
+        let filter = SavedFilter::new("rust-src", &patterns).unwrap();
+        let root = Path::new("/p");
+        let file = |path: &str| filter.matches(Path::new(path), false, root);
+        assert!(file("/p/src/main.rs"));
+        assert!(file("/p/src/ui/tree.rs"));
+        assert!(file("/p/Cargo.toml"));
+        assert!(file("/p/crates/a/Cargo.lock"));
+        assert!(!file("/p/benches/tree.rs"));
+        assert!(!file("/p/src/notes.md"));
+        // A name glob can match anywhere, so every directory stays
+        assert!(filter.matches(Path::new("/p/benches"), true, root));
+
+        let patterns = ["src/*.rs", "/Cargo.toml"].map(String::from);
+        let filter = SavedFilter::new("sources", &patterns).unwrap();
+        let dir = |path: &str| filter.matches(Path::new(path), true, root);
+        assert!(dir("/p/src"));
+        assert!(!dir("/p/docs"));
+        assert!(!dir("/p/src/ui"));
+        assert!(filter.matches(Path::new("/p/Cargo.toml"), false, root));
+        assert!(!filter.matches(Path::new("/p/src/Cargo.toml"), false, root));
+        assert!(!filter.matches(Path::new("/p/src/ui/tree.rs"), false, root));
+
+        let filter = SavedFilter::new("media", &["type:images".to_string()]).unwrap();
+        assert!(filter.matches(Path::new("/p/a/logo.PNG"), false, root));
+        assert!(SavedFilter::new("bad", &["type:nothing".to_string()]).is_err());
+        assert!(SavedFilter::new("empty", &[]).is_err());
+    }
+}
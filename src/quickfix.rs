@@ -0,0 +1,93 @@
+use crate::search::SearchResult;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// Marker prefix for the export printed to stdout on exit (the lines follow it)
+const QUICKFIX_MARKER: &str = "QUICKFIX:";
+
+/// Results as `path:line:col: text` lines (vim's default errorformat)
+/// Name matches point at the start of the file, with the name (or detail) as text
+pub fn format(results: &[SearchResult]) -> String {
+    results
+        .iter()
+        .map(|result| {
+            let (line, column, text) = match &result.content {
+                Some(content) => (content.line, content.column, content.text()),
+                None => (
+                    1,
+                    1,
+                    result.detail.clone().unwrap_or_else(|| {
+                        result
+                            .path
+                            .file_name()
+                            .map(|name| name.to_string_lossy().to_string())
+                            .unwrap_or_default()
+                    }),
+                ),
+            };
+            format!("{}:{}:{}: {}\n", result.path.display(), line, column, text)
+        })
+        .collect()
+}
+
+/// Write the results to `file` for `:cfile` / `nvim -q`
+pub fn write(file: &Path, results: &[SearchResult]) -> Result<()> {
+    if let Some(dir) = file.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(file, format(results))
+        .with_context(|| format!("Failed to write {}", file.display()))
+}
+
+/// Encode the results as a return marker for main.rs to print on exit
+pub fn encode(results: &[SearchResult]) -> PathBuf {
+    PathBuf::from(format!("{}{}", QUICKFIX_MARKER, format(results)))
+}
+
+/// Decode a return marker produced by `encode` into the lines to print
+pub fn decode(marker: &str) -> Option<&str> {
+    marker.strip_prefix(QUICKFIX_MARKER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content_search::ContentMatch;
+    use ratatui::text::Span;
+
+    fn result(path: &str, content: Option<ContentMatch>) -> SearchResult {
+        SearchResult {
+            path: PathBuf::from(path),
+            is_dir: false,
+            score: None,
+            match_indices: None,
+            detail: None,
+            content,
+        }
+    }
+
+    #[test]
+    fn test_format_quickfix_lines() {
+        let content = ContentMatch {
+            line: 12,
+            column: 9,
+            range: 4..8,
+            spans: vec![Span::raw("let "), Span::raw("todo = 1;")],
+        };
+        let results = [
+            result("/src/main.rs", Some(content)),
+            result("/src/lib.rs", None),
+        ];
+        assert_eq!(
+            format(&results),
+            "/src/main.rs:12:9: let todo = 1;\n/src/lib.rs:1:1: lib.rs\n"
+        );
+
+        let marker = encode(&results);
+        assert_eq!(
+            decode(&marker.to_string_lossy()),
+            Some(format(&results).as_str())
+        );
+        assert_eq!(decode("SELECT:/src/lib.rs"), None);
+    }
+}
@@ -0,0 +1,188 @@
+use crate::dir_size::DirSizeCache;
+use crate::filter_set::{Excludes, FilterSet};
+use crate::platform::FileId;
+use crate::search::{ProgressReporter, SearchMessage, SearchResult};
+use crate::vfs::VfsProvider;
+use crate::wakeup::WakingSender;
+use crossbeam_channel::Receiver;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Files listed by the largest files report
+pub const TOP_FILES: usize = 20;
+
+/// Reports over everything under the root, run in the background and listed in the
+/// search results panel (Enter jumps to an entry like a search result)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanKind {
+    /// Directories with nothing in them, plus zero-byte files when files are shown
+    Empty { include_files: bool },
+    /// Symlinks whose target doesn't exist
+    BrokenSymlinks,
+    /// The TOP_FILES largest files, biggest first
+    LargestFiles,
+    /// Every hard link to one file (the selected one included)
+    Hardlinks(FileId),
+}
+
+impl ScanKind {
+    /// Title of the results panel and the task list
+    pub fn label(self) -> &'static str {
+        match self {
+            ScanKind::Empty {
+                include_files: false,
+            } => "Empty directories",
+            ScanKind::Empty {
+                include_files: true,
+            } => "Empty directories and files",
+            ScanKind::BrokenSymlinks => "Broken symlinks",
+            ScanKind::LargestFiles => "Largest files",
+            ScanKind::Hardlinks(_) => "Hard links",
+        }
+    }
+
+    /// Findings are listed by path (the largest files keep their size order)
+    pub fn sorted_by_path(self) -> bool {
+        self != ScanKind::LargestFiles
+    }
+}
+
+/// Tree settings the walk follows
+#[derive(Debug, Clone, Default)]
+pub struct ScanOptions {
+    pub filters: FilterSet, // Entries the tree leaves out aren't found either
+    pub follow_symlinks: bool,
+    pub one_file_system: bool,
+    pub exclude: Arc<Excludes>, // Directories found but not gone into
+}
+
+/// Walk `root` (read from `vfs`) and send matching entries as results, progress every
+/// 100 directories
+/// Symlinked directories are not descended into, so links can't loop
+/// Returns early when cancelled; the caller sends `Done`
+pub fn run(
+    vfs: &dyn VfsProvider,
+    kind: ScanKind,
+    root: &Path,
+    options: ScanOptions,
+    result_tx: &WakingSender<SearchMessage>,
+    cancel_rx: &Receiver<()>,
+) {
+    let _span = tracing::info_span!("scan", kind = kind.label(), root = %root.display()).entered();
+    // Hard links never cross filesystems, so other devices aren't descended into either
+    let device = match kind {
+        ScanKind::Hardlinks(id) => Some(id.device),
+        _ if options.one_file_system => vfs.metadata(root).ok().and_then(|m| m.device()),
+        _ => None,
+    };
+    let mut links_found: u64 = 0;
+    let mut dirs: Vec<PathBuf> = vec![root.to_path_buf()];
+    let mut progress = ProgressReporter::default();
+    // Smallest of the largest files on top, so it's the one dropped
+    let mut largest: BinaryHeap<Reverse<(u64, PathBuf)>> = BinaryHeap::new();
+
+    while let Some(dir) = dirs.pop() {
+        if !cancel_rx.is_empty() {
+            return;
+        }
+        progress.enter(&dir, result_tx);
+        let Ok(read_dir) = vfs.read_dir(&dir) else {
+            continue;
+        };
+
+        // A directory with only hidden entries isn't empty, even if they aren't shown
+        let mut is_empty = true;
+        for path in read_dir.into_iter().flatten() {
+            // A huge directory shouldn't keep a cancelled scan going
+            if !cancel_rx.is_empty() {
+                return;
+            }
+            is_empty = false;
+            let Ok(metadata) = vfs.symlink_metadata(&path) else {
+                continue;
+            };
+            if !options.filters.allows(vfs, &path, metadata.is_dir, root) {
+                continue;
+            }
+            if metadata.is_symlink {
+                // A link is broken when its target can't be read
+                if kind == ScanKind::BrokenSymlinks && vfs.metadata(&path).is_err() {
+                    let target = vfs
+                        .read_link(&path)
+                        .map(|target| format!("-> {}", target.display()))
+                        .ok();
+                    found(result_tx, path, false, target);
+                    continue;
+                }
+                if !options.follow_symlinks {
+                    continue;
+                }
+            }
+            if metadata.is_dir {
+                if (device.is_none() || metadata.device() == device)
+                    && !options.exclude.skips_contents(&path, root)
+                {
+                    dirs.push(path);
+                }
+                continue;
+            }
+
+            // Linked files are counted where they really are
+            if metadata.is_symlink {
+                continue;
+            }
+            match kind {
+                ScanKind::Empty {
+                    include_files: true,
+                } if metadata.is_file && metadata.len == 0 => {
+                    found(result_tx, path, false, None);
+                }
+                ScanKind::LargestFiles if metadata.is_file => {
+                    largest.push(Reverse((metadata.len, path)));
+                    if largest.len() > TOP_FILES {
+                        largest.pop();
+                    }
+                }
+                ScanKind::Hardlinks(id)
+                    if metadata.file_id.is_some_and(|other| id.same_file(&other)) =>
+                {
+                    found(result_tx, path, false, None);
+                    // Every link is accounted for - the rest of the tree can't hold more
+                    links_found += 1;
+                    if links_found == id.links {
+                        return;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // The root itself is where the scan started, not a finding
+        if is_empty && dir != root && matches!(kind, ScanKind::Empty { .. }) {
+            found(result_tx, dir, true, None);
+        }
+    }
+
+    for Reverse((size, path)) in largest.into_sorted_vec() {
+        let size = DirSizeCache::format_size(size, false);
+        found(result_tx, path, false, Some(size));
+    }
+}
+
+fn found(
+    result_tx: &WakingSender<SearchMessage>,
+    path: PathBuf,
+    is_dir: bool,
+    detail: Option<String>,
+) {
+    let _ = result_tx.send(SearchMessage::Found(SearchResult {
+        path,
+        is_dir,
+        score: None,
+        match_indices: None,
+        detail,
+        content: None,
+    }));
+}
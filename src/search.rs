@@ -1,18 +1,41 @@
 // Allow many arguments for recursive search function - it needs context for deep traversal
 #![allow(clippy::too_many_arguments)]
 
-use crate::tree_node::TreeNodeRef;
-use crossbeam_channel::{bounded, unbounded, Receiver, Sender};
-use std::path::PathBuf;
+use crate::content_search::ContentMatch;
+use crate::oplog;
+use crate::scan::{self, ScanKind, ScanOptions};
+use crate::tree_node::{NodeId, Tree, TreeNode};
+use crate::vfs::{Vfs, VfsProvider};
+use crate::wakeup::{self, WakingSender};
+use crossbeam_channel::{bounded, Receiver, Sender};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Cancelled workers a new search runs alongside while they wind down; more and it
+/// waits, and a search restarted meanwhile only replaces the one waiting
+const MAX_STOPPING: usize = 1;
+
+/// Longest a worker goes without reporting its progress while it scans directories
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Results kept by default; a broad query's other matches are only counted
+pub const DEFAULT_MAX_RESULTS: usize = 10_000;
+
+/// Body of a background worker, given its message sender and cancel receiver
+type Job = Box<dyn FnOnce(&WakingSender<SearchMessage>, &Receiver<()>) + Send>;
 
 /// Messages from search thread to main thread
 #[derive(Debug, Clone)]
 pub enum SearchMessage {
     /// Found a matching path (path, is_dir, score, match_indices)
     Result(PathBuf, bool, Option<i64>, Option<Vec<usize>>),
-    /// Progress update: number of directories scanned
-    Progress(usize),
+    /// Finding of a scan, with its details
+    Found(SearchResult),
+    /// Progress update: number of directories scanned, the one being scanned
+    Progress(usize, PathBuf),
     /// Search completed
     Done,
 }
@@ -24,6 +47,8 @@ pub struct SearchResult {
     pub is_dir: bool,
     pub score: Option<i64>, // Fuzzy match score (None for exact match)
     pub match_indices: Option<Vec<usize>>, // Character positions that matched (for highlighting)
+    pub detail: Option<String>, // Extra info shown after the path (symlink target, ...)
+    pub content: Option<ContentMatch>, // Matching line of a content search
 }
 
 /// Search functionality for finding files and directories
@@ -34,14 +59,32 @@ pub struct Search {
     pub results: Vec<SearchResult>,
     pub selected: usize,
     pub show_results: bool,
-    pub focus_on_results: bool,
+    /// Results come from a scan (empty directories, ...) rather than the query
+    pub scan: Option<ScanKind>,
+    /// Files whose content matches are collapsed into their first one
+    collapsed: HashSet<PathBuf>,
+    /// Results marked in the panel (Space), for its bulk actions
+    pub marked: HashSet<PathBuf>,
+    /// Results kept at most (0 = no limit)
+    pub max_results: usize,
+    /// Matches the worker reported, kept or not
+    matched: usize,
+    /// Paths the quick search found, which the deep search reports again
+    quick_found: HashSet<PathBuf>,
 
     // Async search state
     pub is_searching: bool,
     pub scanned_count: usize,
+    /// Directory the worker last reported scanning
+    pub current_dir: Option<PathBuf>,
+    started_at: Option<Instant>,
     search_thread: Option<JoinHandle<()>>,
     cancel_sender: Option<Sender<()>>,
     result_receiver: Option<Receiver<SearchMessage>>,
+    /// Cancelled workers still running
+    stopping: Vec<JoinHandle<()>>,
+    /// Worker waiting for the stopping ones, with its channel ends
+    queued: Option<(Job, WakingSender<SearchMessage>, Receiver<()>)>,
 }
 
 impl Default for Search {
@@ -59,12 +102,21 @@ impl Search {
             results: Vec::new(),
             selected: 0,
             show_results: false,
-            focus_on_results: false,
+            scan: None,
+            collapsed: HashSet::new(),
+            marked: HashSet::new(),
+            max_results: DEFAULT_MAX_RESULTS,
+            matched: 0,
+            quick_found: HashSet::new(),
             is_searching: false,
             scanned_count: 0,
+            current_dir: None,
+            started_at: None,
             search_thread: None,
             cancel_sender: None,
             result_receiver: None,
+            stopping: Vec::new(),
+            queued: None,
         }
     }
 
@@ -111,19 +163,10 @@ impl Search {
     }
 
     /// Execute two-phase search: quick + deep background scan
-    pub fn perform_search(
-        &mut self,
-        root: &TreeNodeRef,
-        show_files: bool,
-        show_hidden: bool,
-        follow_symlinks: bool,
-    ) {
-        // Cancel any existing search
-        self.cancel_search();
-
-        self.results.clear();
-        self.selected = 0;
-        self.scanned_count = 0;
+    /// Entries the `options` filters leave out of the tree aren't found
+    pub fn perform_search(&mut self, tree: &Tree, show_files: bool, options: ScanOptions) {
+        self.restart();
+        self.scan = None;
 
         let search_query = self.get_search_query();
 
@@ -138,43 +181,158 @@ impl Search {
         let is_fuzzy = self.fuzzy_mode;
 
         // Phase 1: Quick search through already loaded nodes
-        self.search_loaded_nodes(root, &query_lower, show_files, show_hidden, is_fuzzy);
-
-        // Phase 2: Deep search in background thread
-        self.spawn_deep_search(
-            root,
-            query_lower,
+        self.search_loaded_nodes(
+            tree,
+            tree.root(),
+            &query_lower,
             show_files,
-            show_hidden,
-            follow_symlinks,
+            &options,
             is_fuzzy,
         );
 
+        // Phase 2: Deep search in background thread
+        self.spawn_deep_search(tree.root_node(), query_lower, show_files, is_fuzzy, options);
+
         self.show_results = true;
-        self.focus_on_results = true; // Always focus on results after search
         self.mode = false;
         self.is_searching = true;
     }
 
+    /// Run a scan under `root` (read from `vfs`) in the background; its findings replace
+    /// the results
+    pub fn start_scan(&mut self, vfs: Vfs, kind: ScanKind, root: &Path, options: ScanOptions) {
+        self.restart();
+        self.fuzzy_mode = false;
+        self.scan = Some(kind);
+
+        let root = root.to_path_buf();
+        self.start_worker(Box::new(move |result_tx, cancel_rx| {
+            scan::run(vfs.as_ref(), kind, &root, options, result_tx, cancel_rx);
+        }));
+        self.show_results = true;
+        self.mode = false;
+        self.is_searching = true;
+    }
+
+    /// Cancel the running search and clear its results and progress for the next one
+    fn restart(&mut self) {
+        self.cancel_search();
+        self.results.clear();
+        self.selected = 0;
+        self.scanned_count = 0;
+        self.current_dir = None;
+        self.matched = 0;
+        self.quick_found.clear();
+        self.collapsed.clear();
+        self.marked.clear();
+        self.started_at = Some(Instant::now());
+    }
+
+    /// Store a result while there's room; returns whether it was stored
+    /// Fuzzy results may take twice the room before the worse half goes, so the
+    /// best ones are kept
+    fn keep(&mut self, result: SearchResult) -> bool {
+        if self.max_results == 0 || self.results.len() < self.max_results {
+            self.results.push(result);
+            return true;
+        }
+        if !self.fuzzy_mode {
+            return false;
+        }
+        self.results.push(result);
+        if self.results.len() >= 2 * self.max_results {
+            self.sort_by_score();
+            self.results.truncate(self.max_results);
+            self.selected = self.selected.min(self.max_results - 1);
+        }
+        true
+    }
+
+    /// Directories scanned per second, once the search ran for a second
+    pub fn rate(&self) -> Option<u64> {
+        let elapsed = self.started_at?.elapsed().as_secs_f64();
+        (elapsed >= 1.0).then(|| (self.scanned_count as f64 / elapsed).round() as u64)
+    }
+
+    /// Matches found but not kept, to refine the query for
+    pub fn more(&self) -> usize {
+        self.matched.saturating_sub(self.results.len())
+    }
+
+    /// Highest fuzzy score first
+    fn sort_by_score(&mut self) {
+        self.results.sort_by(|a, b| {
+            match (a.score, b.score) {
+                (Some(score_a), Some(score_b)) => score_b.cmp(&score_a), // Descending order
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            }
+        });
+    }
+
+    /// Run `job` on its own thread, once cancelled workers left running allow
+    /// Its messages are the ones polled from now on, even before it starts
+    fn start_worker(&mut self, job: Job) {
+        let (result_tx, result_rx) = wakeup::unbounded();
+        let (cancel_tx, cancel_rx) = bounded(1);
+        self.cancel_sender = Some(cancel_tx);
+        self.result_receiver = Some(result_rx);
+        self.queued = Some((job, result_tx, cancel_rx));
+        self.start_queued();
+    }
+
+    /// Start the queued worker if few enough cancelled ones are still running
+    fn start_queued(&mut self) {
+        self.stopping.retain(|handle| !handle.is_finished());
+        if self.stopping.len() > MAX_STOPPING {
+            return;
+        }
+        if let Some((job, result_tx, cancel_rx)) = self.queued.take() {
+            self.search_thread = Some(thread::spawn(move || {
+                job(&result_tx, &cancel_rx);
+                let _ = result_tx.send(SearchMessage::Done);
+            }));
+        }
+    }
+
+    /// A search is waiting for cancelled workers to stop (they don't wake the event loop)
+    pub fn is_queued(&self) -> bool {
+        self.queued.is_some()
+    }
+
+    /// What the results are for: `Search 'query'` or the scan name
+    pub fn title(&self) -> String {
+        match self.scan {
+            Some(kind) => kind.label().to_string(),
+            None => format!("Search '{}'", self.query),
+        }
+    }
+
     /// Phase 1: Quick search through already loaded (visible) nodes
     fn search_loaded_nodes(
         &mut self,
-        node: &TreeNodeRef,
+        tree: &Tree,
+        id: NodeId,
         query: &str,
         show_files: bool,
-        show_hidden: bool,
+        options: &ScanOptions,
         fuzzy: bool,
     ) {
         use fuzzy_matcher::skim::SkimMatcherV2;
         use fuzzy_matcher::FuzzyMatcher;
 
-        let node_borrowed = node.borrow();
+        let node_borrowed = &tree[id];
         let name_lower = node_borrowed.name.to_lowercase();
 
-        // Check if node is hidden (starts with .)
-        let is_hidden = node_borrowed.name.starts_with('.');
-        if !show_hidden && is_hidden {
-            // Skip hidden files/directories if show_hidden is false
+        // Skip what the tree doesn't show (hidden, ignored or filtered out); what it
+        // shows is found, excluded directories' loaded entries included
+        if !options.filters.allows(
+            node_borrowed.vfs.as_ref(),
+            &node_borrowed.path,
+            node_borrowed.is_dir,
+            &tree.root_node().path,
+        ) {
             return;
         }
 
@@ -184,21 +342,27 @@ impl Search {
                 // Fuzzy matching
                 let matcher = SkimMatcherV2::default();
                 if let Some((score, indices)) = matcher.fuzzy_indices(&name_lower, query) {
-                    self.results.push(SearchResult {
+                    self.quick_found.insert(node_borrowed.path.clone());
+                    self.keep(SearchResult {
                         path: node_borrowed.path.clone(),
                         is_dir: node_borrowed.is_dir,
                         score: Some(score),
                         match_indices: Some(indices),
+                        detail: None,
+                        content: None,
                     });
                 }
             } else {
                 // Exact substring matching
                 if name_lower.contains(query) {
-                    self.results.push(SearchResult {
+                    self.quick_found.insert(node_borrowed.path.clone());
+                    self.keep(SearchResult {
                         path: node_borrowed.path.clone(),
                         is_dir: node_borrowed.is_dir,
                         score: None,
                         match_indices: None,
+                        detail: None,
+                        content: None,
                     });
                 }
             }
@@ -206,11 +370,8 @@ impl Search {
 
         // Recursively search already loaded children
         if node_borrowed.is_expanded {
-            let children = node_borrowed.children.clone();
-            drop(node_borrowed);
-
-            for child in &children {
-                self.search_loaded_nodes(child, query, show_files, show_hidden, fuzzy);
+            for &child in &node_borrowed.children {
+                self.search_loaded_nodes(tree, child, query, show_files, options, fuzzy);
             }
         }
     }
@@ -218,83 +379,63 @@ impl Search {
     /// Phase 2: Spawn background thread for deep search
     fn spawn_deep_search(
         &mut self,
-        root: &TreeNodeRef,
+        root: &TreeNode,
         query: String,
         show_files: bool,
-        show_hidden: bool,
-        follow_symlinks: bool,
         fuzzy: bool,
+        options: ScanOptions,
     ) {
-        let (result_tx, result_rx) = unbounded();
-        let (cancel_tx, cancel_rx) = bounded(1);
-
-        // Clone root node for thread (Rc can't be sent across threads, so we need path)
-        let root_path = root.borrow().path.clone();
-
-        // Spawn search thread
-        let handle = thread::spawn(move || {
-            Self::deep_search_recursive(
-                &root_path,
-                &query,
-                &result_tx,
-                &cancel_rx,
-                show_files,
-                show_hidden,
-                follow_symlinks,
-                fuzzy,
-                &mut 0,
-            );
-            let _ = result_tx.send(SearchMessage::Done);
-        });
-
-        self.search_thread = Some(handle);
-        self.cancel_sender = Some(cancel_tx);
-        self.result_receiver = Some(result_rx);
+        self.start_worker(name_search(
+            Arc::clone(&root.vfs),
+            root.path.clone(),
+            query,
+            show_files,
+            fuzzy,
+            options,
+        ));
     }
 
     /// Recursive deep search in background thread
     fn deep_search_recursive(
-        path: &PathBuf,
+        vfs: &dyn VfsProvider,
+        path: &Path,
+        root: &Path,
         query: &str,
-        result_tx: &Sender<SearchMessage>,
+        result_tx: &WakingSender<SearchMessage>,
         cancel_rx: &Receiver<()>,
         show_files: bool,
-        show_hidden: bool,
-        follow_symlinks: bool,
+        options: &ScanOptions,
+        root_device: Option<u64>,
         fuzzy: bool,
-        scanned: &mut usize,
+        progress: &mut ProgressReporter,
     ) {
         use fuzzy_matcher::skim::SkimMatcherV2;
         use fuzzy_matcher::FuzzyMatcher;
 
-        // Check for cancellation
-        if cancel_rx.try_recv().is_ok() {
+        // Check for cancellation (the signal stays in the channel for every level)
+        if !cancel_rx.is_empty() {
             return;
         }
 
         // Check if entry is a symlink and whether to follow it
-        if !follow_symlinks {
-            if let Ok(metadata) = std::fs::symlink_metadata(path) {
-                if metadata.is_symlink() {
+        if !options.follow_symlinks {
+            if let Ok(metadata) = vfs.symlink_metadata(path) {
+                if metadata.is_symlink {
                     return; // Skip symlinks if follow_symlinks is false
                 }
             }
         }
 
         // Check if this is a directory
-        let is_dir = path.is_dir();
+        let is_dir = vfs.is_dir(path);
 
         if !is_dir && !show_files {
             return; // Skip files if not in file viewing mode
         }
 
-        // Check if file/directory is hidden (starts with .)
-        if !show_hidden {
-            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                if name.starts_with('.') {
-                    return; // Skip hidden files/directories
-                }
-            }
+        // Skip what the tree doesn't show, and everything in such a directory
+        if !options.filters.allows(vfs, path, is_dir, root) {
+            return;
         }
 
         // Check if name matches query
@@ -306,7 +447,7 @@ impl Search {
                 let matcher = SkimMatcherV2::default();
                 if let Some((score, indices)) = matcher.fuzzy_indices(&name_lower, query) {
                     let _ = result_tx.send(SearchMessage::Result(
-                        path.clone(),
+                        path.to_path_buf(),
                         is_dir,
                         Some(score),
                         Some(indices),
@@ -315,39 +456,48 @@ impl Search {
             } else {
                 // Exact substring matching
                 if name_lower.contains(query) {
-                    let _ = result_tx.send(SearchMessage::Result(path.clone(), is_dir, None, None));
+                    let _ = result_tx.send(SearchMessage::Result(
+                        path.to_path_buf(),
+                        is_dir,
+                        None,
+                        None,
+                    ));
                 }
             }
         }
 
-        // If directory, scan children
-        if is_dir {
-            *scanned += 1;
+        // Don't descend into mountpoints when staying on one filesystem
+        let crosses_boundary = root_device.is_some()
+            && vfs
+                .metadata(path)
+                .ok()
+                .and_then(|metadata| metadata.device())
+                != root_device;
 
-            // Send progress update every 100 directories
-            if (*scanned).is_multiple_of(100) {
-                let _ = result_tx.send(SearchMessage::Progress(*scanned));
-            }
+        // If directory, scan children (unless the search excludes it)
+        if is_dir && !crosses_boundary && !options.exclude.skips_contents(path, root) {
+            progress.enter(path, result_tx);
 
             // Read directory entries
-            if let Ok(entries) = std::fs::read_dir(path) {
-                for entry in entries.flatten() {
+            if let Ok(entries) = vfs.read_dir(path) {
+                for child_path in entries.into_iter().flatten() {
                     // Check for cancellation frequently
-                    if cancel_rx.try_recv().is_ok() {
+                    if !cancel_rx.is_empty() {
                         return;
                     }
 
-                    let child_path = entry.path();
                     Self::deep_search_recursive(
+                        vfs,
                         &child_path,
+                        root,
                         query,
                         result_tx,
                         cancel_rx,
                         show_files,
-                        show_hidden,
-                        follow_symlinks,
+                        options,
+                        root_device,
                         fuzzy,
-                        scanned,
+                        progress,
                     );
                 }
             }
@@ -359,24 +509,38 @@ impl Search {
         let mut has_updates = false;
         let mut search_done = false;
 
-        if let Some(ref rx) = self.result_receiver {
+        if self.queued.is_some() {
+            self.start_queued();
+        }
+
+        if let Some(rx) = self.result_receiver.clone() {
             // Process all available messages
             while let Ok(msg) = rx.try_recv() {
                 match msg {
                     SearchMessage::Result(path, is_dir, score, match_indices) => {
-                        // Check if we already have this result (from quick search)
-                        if !self.results.iter().any(|r| r.path == path) {
-                            self.results.push(SearchResult {
+                        self.matched += 1;
+                        // Skip what the quick search already found (a set, since
+                        // broad queries stream in a lot of results)
+                        if !self.quick_found.contains(&path) {
+                            self.keep(SearchResult {
                                 path,
                                 is_dir,
                                 score,
                                 match_indices,
+                                detail: None,
+                                content: None,
                             });
-                            has_updates = true;
                         }
+                        has_updates = true;
+                    }
+                    SearchMessage::Found(result) => {
+                        self.matched += 1;
+                        self.keep(result);
+                        has_updates = true;
                     }
-                    SearchMessage::Progress(count) => {
+                    SearchMessage::Progress(count, dir) => {
                         self.scanned_count = count;
+                        self.current_dir = Some(dir);
                         has_updates = true;
                     }
                     SearchMessage::Done => {
@@ -389,21 +553,33 @@ impl Search {
 
         // Clean up if search is done
         if search_done {
+            if let Some(started_at) = self.started_at.take() {
+                oplog::record(format!(
+                    "{}: {} result(s), {} dirs scanned in {} ms",
+                    self.title(),
+                    self.results.len(),
+                    self.scanned_count,
+                    started_at.elapsed().as_millis()
+                ));
+            }
             self.is_searching = false;
+            self.current_dir = None;
             self.search_thread = None;
             self.cancel_sender = None;
             self.result_receiver = None;
 
             // Sort results by score in fuzzy mode (highest score first)
             if self.fuzzy_mode {
-                self.results.sort_by(|a, b| {
-                    match (a.score, b.score) {
-                        (Some(score_a), Some(score_b)) => score_b.cmp(&score_a), // Descending order
-                        (Some(_), None) => std::cmp::Ordering::Less,
-                        (None, Some(_)) => std::cmp::Ordering::Greater,
-                        (None, None) => std::cmp::Ordering::Equal,
-                    }
-                });
+                self.sort_by_score();
+                if self.max_results > 0 && self.results.len() > self.max_results {
+                    self.results.truncate(self.max_results);
+                    self.selected = self.selected.min(self.max_results - 1);
+                }
+            }
+
+            // Scan findings arrive in walk order
+            if self.scan.is_some_and(ScanKind::sorted_by_path) {
+                self.results.sort_by(|a, b| a.path.cmp(&b.path));
             }
         }
 
@@ -414,66 +590,158 @@ impl Search {
     pub fn cancel_search(&mut self) {
         // Send cancellation signal to background thread
         if let Some(cancel_tx) = self.cancel_sender.take() {
-            let _ = cancel_tx.send(());
+            let _ = cancel_tx.try_send(());
         }
 
         // Don't wait for the thread to finish - it will check cancel_rx and exit on its own
         // Waiting here with join() would block the UI if the thread is in a long operation
         // (e.g., scanning a large directory with thousands of files)
-        if let Some(_handle) = self.search_thread.take() {
-            // Thread will be detached and will terminate when it checks cancel_rx
-            // The thread checks cancel_rx frequently (at line 233 and 303)
-            // No need to join() - the thread will clean up automatically
+        // It's kept until then, so the next search can wait for it rather than pile up
+        if let Some(handle) = self.search_thread.take() {
+            self.stopping.push(handle);
         }
 
+        self.queued = None;
         self.result_receiver = None;
         self.is_searching = false;
+        self.started_at = None;
     }
 
     /// Move selection down in results
     pub fn move_down(&mut self) {
-        if self.selected < self.results.len().saturating_sub(1) {
-            self.selected += 1;
+        if let Some(next) = (self.selected + 1..self.results.len()).find(|&i| !self.is_hidden(i)) {
+            self.selected = next;
         }
     }
 
     /// Move selection up in results
     pub fn move_up(&mut self) {
-        self.selected = self.selected.saturating_sub(1);
+        if let Some(previous) = (0..self.selected).rev().find(|&i| !self.is_hidden(i)) {
+            self.selected = previous;
+        }
     }
 
-    /// Get number of search results
+    /// Get number of results shown in the panel
     pub fn get_results_count(&self) -> usize {
-        self.results.len()
+        self.visible().len()
     }
 
-    /// Set selected index (with bounds checking)
-    pub fn set_selected(&mut self, index: usize) {
-        if index < self.results.len() {
+    /// Select the result shown at `row` of the panel (with bounds checking)
+    pub fn set_selected(&mut self, row: usize) {
+        if let Some(&index) = self.visible().get(row) {
             self.selected = index;
         }
     }
 
+    /// Check if a result is folded into the first match of its file
+    pub fn is_hidden(&self, index: usize) -> bool {
+        let result = &self.results[index];
+        result.content.is_some()
+            && index > 0
+            && self.results[index - 1].path == result.path
+            && self.collapsed.contains(&result.path)
+    }
+
+    /// Indices of the results shown in the panel
+    pub fn visible(&self) -> Vec<usize> {
+        (0..self.results.len())
+            .filter(|&i| !self.is_hidden(i))
+            .collect()
+    }
+
+    /// Check if the results are lines inside files, which fold per file
+    pub fn has_content_matches(&self) -> bool {
+        self.results.iter().any(|result| result.content.is_some())
+    }
+
+    /// Matches folded into a collapsed result (0 when it isn't the head of a group)
+    pub fn folded_count(&self, index: usize) -> usize {
+        let path = &self.results[index].path;
+        if !self.collapsed.contains(path) || self.is_hidden(index) {
+            return 0;
+        }
+        self.results[index + 1..]
+            .iter()
+            .take_while(|result| &result.path == path)
+            .count()
+    }
+
+    /// Collapse the matches of the selected result's file into its first match
+    pub fn collapse_group(&mut self) {
+        let Some(result) = self.results.get(self.selected) else {
+            return;
+        };
+        if result.content.is_none() {
+            return;
+        }
+        let path = result.path.clone();
+        while self.selected > 0 && self.results[self.selected - 1].path == path {
+            self.selected -= 1;
+        }
+        self.collapsed.insert(path);
+    }
+
+    /// Show all matches of the selected result's file again
+    pub fn expand_group(&mut self) {
+        if let Some(result) = self.results.get(self.selected) {
+            self.collapsed.remove(&result.path);
+        }
+    }
+
+    /// Line of the selected content match
+    pub fn selected_line(&self) -> Option<usize> {
+        self.results
+            .get(self.selected)
+            .and_then(|result| result.content.as_ref())
+            .map(|content| content.line)
+    }
+
     /// Get selected result path
     pub fn get_selected_result(&self) -> Option<PathBuf> {
         self.results.get(self.selected).map(|r| r.path.clone())
     }
 
+    /// Mark or unmark the selected result
+    pub fn toggle_mark(&mut self) {
+        if let Some(path) = self.get_selected_result() {
+            if !self.marked.remove(&path) {
+                self.marked.insert(path);
+            }
+        }
+    }
+
+    /// Paths of the marked results in the order listed, each once (a file's content
+    /// matches share its path)
+    pub fn marked_paths(&self) -> Vec<PathBuf> {
+        let mut seen = HashSet::new();
+        self.results
+            .iter()
+            .filter(|result| self.marked.contains(&result.path) && seen.insert(&result.path))
+            .map(|result| result.path.clone())
+            .collect()
+    }
+
+    /// Marked results if any, otherwise the selected one
+    pub fn marked_or_selected(&self) -> Vec<PathBuf> {
+        if self.marked.is_empty() {
+            self.get_selected_result().into_iter().collect()
+        } else {
+            self.marked_paths()
+        }
+    }
+
     /// Close search results panel
     pub fn close_results(&mut self) {
         self.cancel_search();
         self.show_results = false;
         self.results.clear();
+        self.collapsed.clear();
+        self.marked.clear();
         self.selected = 0;
-        self.focus_on_results = false;
         self.scanned_count = 0;
-    }
-
-    /// Toggle focus between tree and search results
-    pub fn toggle_focus(&mut self) {
-        if self.show_results {
-            self.focus_on_results = !self.focus_on_results;
-        }
+        self.current_dir = None;
+        self.matched = 0;
+        self.quick_found.clear();
     }
 
     /// Check if search is active
@@ -482,6 +750,140 @@ impl Search {
     }
 }
 
+/// Counts the directories a worker scans and reports them with the one it's in,
+/// every 100 directories or `PROGRESS_INTERVAL`, whichever comes first: a slow
+/// scan (network mounts) still shows where it is
+#[derive(Debug, Default)]
+pub struct ProgressReporter {
+    scanned: usize,
+    sent_at: Option<Instant>,
+}
+
+impl ProgressReporter {
+    /// Count `dir`, about to be read
+    pub fn enter(&mut self, dir: &Path, result_tx: &WakingSender<SearchMessage>) {
+        self.scanned += 1;
+        let due = self
+            .sent_at
+            .is_none_or(|sent_at| sent_at.elapsed() >= PROGRESS_INTERVAL);
+        if due || self.scanned.is_multiple_of(100) {
+            let _ = result_tx.send(SearchMessage::Progress(self.scanned, dir.to_path_buf()));
+            self.sent_at = Some(Instant::now());
+        }
+    }
+}
+
+/// The deep name search under `root`, for a worker thread; `query` is lowercase
+fn name_search(
+    vfs: Vfs,
+    root: PathBuf,
+    query: String,
+    show_files: bool,
+    fuzzy: bool,
+    options: ScanOptions,
+) -> Job {
+    // Device to stay on if one_file_system is enabled
+    let root_device = if options.one_file_system {
+        vfs.metadata(&root)
+            .ok()
+            .and_then(|metadata| metadata.device())
+    } else {
+        None
+    };
+
+    Box::new(move |result_tx, cancel_rx| {
+        let _span = tracing::info_span!("deep_search", query = %query).entered();
+        Search::deep_search_recursive(
+            vfs.as_ref(),
+            &root,
+            &root,
+            &query,
+            result_tx,
+            cancel_rx,
+            show_files,
+            &options,
+            root_device,
+            fuzzy,
+            &mut ProgressReporter::default(),
+        );
+    })
+}
+
+/// A name search under a directory running in the background, for embedding dtree's
+/// search: iterating yields the matches as they are found, and dropping it stops the search
+pub struct SearchStream {
+    receiver: Receiver<SearchMessage>,
+    cancel_sender: Sender<()>,
+    /// Directories scanned so far (updated every 100)
+    pub scanned_count: usize,
+}
+
+impl SearchStream {
+    /// Search the names of the entries under `root` (read from `vfs`) for `query`,
+    /// ignoring case: as a substring, or as a fuzzy pattern with `fuzzy`
+    /// Directories only unless `show_files`, none that `options.filters` leave out
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn start(
+        vfs: Vfs,
+        root: &Path,
+        query: &str,
+        fuzzy: bool,
+        show_files: bool,
+        options: ScanOptions,
+    ) -> Self {
+        let job = name_search(
+            vfs,
+            root.to_path_buf(),
+            query.to_lowercase(),
+            show_files,
+            fuzzy,
+            options,
+        );
+        let (result_tx, receiver) = wakeup::unbounded();
+        let (cancel_sender, cancel_rx) = bounded(1);
+        thread::spawn(move || {
+            job(&result_tx, &cancel_rx);
+            let _ = result_tx.send(SearchMessage::Done);
+        });
+        Self {
+            receiver,
+            cancel_sender,
+            scanned_count: 0,
+        }
+    }
+}
+
+impl Iterator for SearchStream {
+    type Item = SearchResult;
+
+    /// Waits for the next match; None once the whole directory was searched
+    fn next(&mut self) -> Option<SearchResult> {
+        loop {
+            match self.receiver.recv().ok()? {
+                SearchMessage::Result(path, is_dir, score, match_indices) => {
+                    return Some(SearchResult {
+                        path,
+                        is_dir,
+                        score,
+                        match_indices,
+                        detail: None,
+                        content: None,
+                    })
+                }
+                SearchMessage::Found(result) => return Some(result),
+                SearchMessage::Progress(count, _) => self.scanned_count = count,
+                SearchMessage::Done => return None,
+            }
+        }
+    }
+}
+
+impl Drop for SearchStream {
+    fn drop(&mut self) {
+        let _ = self.cancel_sender.try_send(());
+    }
+}
+
 impl Drop for Search {
     fn drop(&mut self) {
         self.cancel_search();
@@ -491,8 +893,167 @@ impl Drop for Search {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::vfs;
     use std::time::{Duration, Instant};
 
+    /// Poll until the running search or scan is done
+    fn wait_for_search(search: &mut Search) {
+        let started = Instant::now();
+        while search.is_searching {
+            assert!(started.elapsed() < Duration::from_secs(5), "search hung");
+            search.poll_results();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn test_search_stream() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = &temp_dir.path().join("project"); // Not hidden like the temp dir
+        std::fs::create_dir_all(root.join("src/app")).unwrap();
+        std::fs::create_dir_all(root.join("docs")).unwrap();
+        std::fs::write(root.join("src/app.rs"), "").unwrap();
+
+        let stream = SearchStream::start(
+            vfs::local(),
+            root,
+            "APP",
+            false,
+            true,
+            ScanOptions::default(),
+        );
+        let mut found: Vec<(PathBuf, bool)> =
+            stream.map(|result| (result.path, result.is_dir)).collect();
+        found.sort();
+        assert_eq!(
+            found,
+            [
+                (root.join("src/app"), true),
+                (root.join("src/app.rs"), false)
+            ]
+        );
+
+        // Fuzzy, directories only
+        let stream = SearchStream::start(
+            vfs::local(),
+            root,
+            "dcs",
+            true,
+            false,
+            ScanOptions::default(),
+        );
+        let found: Vec<PathBuf> = stream.map(|result| result.path).collect();
+        assert_eq!(found, [root.join("docs")]);
+    }
+
+    #[test]
+    fn test_restarts_wait_for_stopping_workers() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("project");
+        std::fs::create_dir_all(root.join("apps")).unwrap();
+        let tree = Tree::new(TreeNode::new(root.clone(), 0).unwrap());
+        let mut search = Search::new();
+
+        // Cancelled workers that haven't stopped yet
+        let (release_tx, release_rx) = bounded::<()>(0);
+        for _ in 0..=MAX_STOPPING {
+            let release_rx = release_rx.clone();
+            search.stopping.push(thread::spawn(move || {
+                let _ = release_rx.recv();
+            }));
+        }
+
+        // Restarted while they run: no new thread, only the last search waits
+        for query in ["a", "ap", "app"] {
+            search.enter_mode();
+            for c in query.chars() {
+                search.add_char(c);
+            }
+            search.perform_search(&tree, false, ScanOptions::default());
+            assert!(search.is_queued());
+            assert!(search.search_thread.is_none());
+        }
+
+        drop(release_tx);
+        wait_for_search(&mut search);
+        assert!(!search.is_queued());
+        let found: Vec<&Path> = search.results.iter().map(|r| r.path.as_path()).collect();
+        assert_eq!(found, [root.join("apps")]);
+    }
+
+    #[test]
+    fn test_quick_results_are_not_repeated() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("project");
+        std::fs::create_dir_all(root.join("src/app")).unwrap();
+        std::fs::create_dir_all(root.join("apps")).unwrap();
+        let mut nav = crate::navigation::Navigation::builder(&root)
+            .build()
+            .unwrap();
+        nav.toggle_node(&root.join("src"), false).unwrap();
+
+        let mut search = Search::new();
+        search.enter_mode();
+        search.add_char('a');
+        search.add_char('p');
+        search.perform_search(&nav.tree, false, ScanOptions::default());
+        assert_eq!(search.results.len(), 2); // Loaded: src/app and apps
+        wait_for_search(&mut search);
+        let mut found: Vec<&Path> = search.results.iter().map(|r| r.path.as_path()).collect();
+        found.sort();
+        assert_eq!(found, [root.join("apps"), root.join("src/app")]);
+    }
+
+    #[test]
+    fn test_progress_reports() {
+        let (result_tx, result_rx) = wakeup::unbounded();
+        let mut progress = ProgressReporter::default();
+        for i in 0..100 {
+            progress.enter(Path::new(&format!("/dir{}", i)), &result_tx);
+        }
+        // The first one right away, then the 100th (unless the test is slow)
+        let reports: Vec<(usize, PathBuf)> = result_rx
+            .try_iter()
+            .map(|message| match message {
+                SearchMessage::Progress(count, dir) => (count, dir),
+                other => panic!("{:?}", other),
+            })
+            .collect();
+        assert_eq!(reports.first(), Some(&(1, PathBuf::from("/dir0"))));
+        assert_eq!(reports.last(), Some(&(100, PathBuf::from("/dir99"))));
+    }
+
+    #[test]
+    fn test_results_are_capped() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("project");
+        for i in 0..10 {
+            std::fs::create_dir_all(root.join(format!("lib{}", i))).unwrap();
+        }
+        std::fs::create_dir_all(root.join("b")).unwrap();
+        let tree = Tree::new(TreeNode::new(root.clone(), 0).unwrap());
+        let mut search = Search::new();
+        search.max_results = 3;
+
+        let run = |search: &mut Search, query: &str| {
+            search.enter_mode();
+            for c in query.chars() {
+                search.add_char(c);
+            }
+            search.perform_search(&tree, false, ScanOptions::default());
+            wait_for_search(search);
+        };
+        run(&mut search, "lib");
+        assert_eq!(search.results.len(), 3);
+        assert_eq!(search.more(), 7);
+
+        // Fuzzy: the best match is kept
+        run(&mut search, "/b");
+        assert_eq!(search.results.len(), 3);
+        assert_eq!(search.more(), 8);
+        assert_eq!(search.results[0].path, root.join("b"));
+    }
+
     #[test]
     fn test_cancel_search_does_not_block() {
         // This test ensures that cancel_search() returns quickly
@@ -503,9 +1064,7 @@ mod tests {
         let test_dir = std::env::temp_dir().join("dtree_test_cancel");
         std::fs::create_dir_all(&test_dir).unwrap();
 
-        let root = std::rc::Rc::new(std::cell::RefCell::new(
-            crate::tree_node::TreeNode::new(test_dir.clone(), 0).unwrap(),
-        ));
+        let root = Tree::new(TreeNode::new(test_dir.clone(), 0).unwrap());
 
         // Start a search
         search.enter_mode();
@@ -513,7 +1072,7 @@ mod tests {
         search.add_char('e');
         search.add_char('s');
         search.add_char('t');
-        search.perform_search(&root, false, false, false);
+        search.perform_search(&root, false, ScanOptions::default());
 
         // Give the background thread time to start
         std::thread::sleep(Duration::from_millis(10));
@@ -544,14 +1103,12 @@ mod tests {
         let test_dir = std::env::temp_dir().join("dtree_test_repeated");
         std::fs::create_dir_all(&test_dir).unwrap();
 
-        let root = std::rc::Rc::new(std::cell::RefCell::new(
-            crate::tree_node::TreeNode::new(test_dir.clone(), 0).unwrap(),
-        ));
+        let root = Tree::new(TreeNode::new(test_dir.clone(), 0).unwrap());
 
         // Start first search
         search.enter_mode();
         search.add_char('a');
-        search.perform_search(&root, false, false, false);
+        search.perform_search(&root, false, ScanOptions::default());
 
         // Give it a moment to start
         std::thread::sleep(Duration::from_millis(10));
@@ -560,7 +1117,7 @@ mod tests {
         let start = Instant::now();
         search.enter_mode();
         search.add_char('b');
-        search.perform_search(&root, false, false, false);
+        search.perform_search(&root, false, ScanOptions::default());
         let elapsed = start.elapsed();
 
         // The second search should start quickly without blocking
@@ -573,7 +1130,7 @@ mod tests {
         // Start third search (stress test)
         search.enter_mode();
         search.add_char('c');
-        search.perform_search(&root, false, false, false);
+        search.perform_search(&root, false, ScanOptions::default());
 
         // Clean up
         search.cancel_search();
@@ -588,9 +1145,7 @@ mod tests {
         let test_dir = std::env::temp_dir().join("dtree_test_rapid");
         std::fs::create_dir_all(&test_dir).unwrap();
 
-        let root = std::rc::Rc::new(std::cell::RefCell::new(
-            crate::tree_node::TreeNode::new(test_dir.clone(), 0).unwrap(),
-        ));
+        let root = Tree::new(TreeNode::new(test_dir.clone(), 0).unwrap());
 
         let start = Instant::now();
 
@@ -599,7 +1154,7 @@ mod tests {
             search.enter_mode();
             search.add_char('a');
             search.add_char((b'0' + (i % 10) as u8) as char);
-            search.perform_search(&root, false, false, false);
+            search.perform_search(&root, false, ScanOptions::default());
             std::thread::sleep(Duration::from_millis(5));
         }
 
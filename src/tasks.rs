@@ -0,0 +1,182 @@
+use std::time::{Duration, Instant};
+
+/// Maximum number of tasks that get a cancel key (1-9)
+pub const MAX_CANCEL_KEYS: usize = 9;
+
+/// Kind of background job tracked by the task manager
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskKind {
+    /// Deep search in background thread
+    Search,
+    /// Directory size calculation
+    DirSizes,
+    /// Copy/move of pasted files
+    FileTransfer,
+    /// Comparison of the root with another directory
+    Compare,
+    /// Listing of a directory read over the network (`--s3`)
+    Listing,
+    /// Scan of all files under the root for the flat listing
+    FlatListing,
+}
+
+/// Unique identifier of a tracked task
+pub type TaskId = u64;
+
+/// A background job shown in the progress overlay
+#[derive(Debug, Clone)]
+pub struct Task {
+    pub id: TaskId,
+    pub kind: TaskKind,
+    pub description: String,
+    pub detail: String,               // Progress text (e.g. "1200 dirs scanned")
+    pub progress: Option<(u64, u64)>, // (done, total) when the total is known
+    pub started_at: Instant,
+}
+
+impl Task {
+    /// Completion ratio in 0.0..=1.0, if the total is known
+    pub fn ratio(&self) -> Option<f64> {
+        self.progress.map(|(done, total)| {
+            if total == 0 {
+                1.0
+            } else {
+                (done as f64 / total as f64).min(1.0)
+            }
+        })
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+}
+
+/// Tracks long-running background jobs (search, sizes, ...) for the progress overlay
+#[derive(Debug)]
+pub struct TaskManager {
+    tasks: Vec<Task>,
+    next_id: TaskId,
+    pub show_overlay: bool,
+}
+
+impl Default for TaskManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TaskManager {
+    pub fn new() -> Self {
+        Self {
+            tasks: Vec::new(),
+            next_id: 1,
+            show_overlay: true,
+        }
+    }
+
+    /// Register a new task and return its ID
+    pub fn start(&mut self, kind: TaskKind, description: impl Into<String>) -> TaskId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.tasks.push(Task {
+            id,
+            kind,
+            description: description.into(),
+            detail: String::new(),
+            progress: None,
+            started_at: Instant::now(),
+        });
+        id
+    }
+
+    /// Update progress of a running task
+    /// Returns true if anything visible changed
+    pub fn update(&mut self, id: TaskId, detail: String, progress: Option<(u64, u64)>) -> bool {
+        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == id) {
+            if task.detail != detail || task.progress != progress {
+                task.detail = detail;
+                task.progress = progress;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Remove a finished (or cancelled) task
+    pub fn finish(&mut self, id: TaskId) {
+        self.tasks.retain(|t| t.id != id);
+    }
+
+    /// Find the running task of the given kind
+    pub fn find_kind(&self, kind: TaskKind) -> Option<TaskId> {
+        self.tasks.iter().find(|t| t.kind == kind).map(|t| t.id)
+    }
+
+    /// Get task by its position in the overlay (0-based)
+    pub fn task_at(&self, index: usize) -> Option<&Task> {
+        self.tasks.get(index)
+    }
+
+    pub fn tasks(&self) -> &[Task] {
+        &self.tasks
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Check if the overlay should be drawn
+    pub fn is_overlay_visible(&self) -> bool {
+        self.show_overlay && !self.is_empty()
+    }
+
+    pub fn toggle_overlay(&mut self) {
+        self.show_overlay = !self.show_overlay;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_and_finish() {
+        let mut tasks = TaskManager::new();
+        assert!(tasks.is_empty());
+
+        let search = tasks.start(TaskKind::Search, "Search 'foo'");
+        let sizes = tasks.start(TaskKind::DirSizes, "Directory sizes");
+        assert_ne!(search, sizes);
+        assert_eq!(tasks.tasks().len(), 2);
+        assert_eq!(tasks.find_kind(TaskKind::DirSizes), Some(sizes));
+
+        tasks.finish(search);
+        assert_eq!(tasks.find_kind(TaskKind::Search), None);
+        assert_eq!(tasks.task_at(0).map(|t| t.id), Some(sizes));
+    }
+
+    #[test]
+    fn test_update_reports_changes() {
+        let mut tasks = TaskManager::new();
+        let id = tasks.start(TaskKind::DirSizes, "Directory sizes");
+
+        assert!(tasks.update(id, "3 pending".to_string(), Some((1, 4))));
+        assert!(!tasks.update(id, "3 pending".to_string(), Some((1, 4))));
+        assert_eq!(tasks.task_at(0).and_then(|t| t.ratio()), Some(0.25));
+
+        // Unknown task IDs are ignored
+        assert!(!tasks.update(id + 100, "x".to_string(), None));
+    }
+
+    #[test]
+    fn test_overlay_visibility() {
+        let mut tasks = TaskManager::new();
+        assert!(!tasks.is_overlay_visible());
+
+        tasks.start(TaskKind::Search, "Search");
+        assert!(tasks.is_overlay_visible());
+
+        tasks.toggle_overlay();
+        assert!(!tasks.is_overlay_visible());
+    }
+}
@@ -1,21 +1,38 @@
 use anyhow::Result;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyEventKind},
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    event::{
+        self, DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture,
+        Event, KeyEventKind,
+    },
+    terminal::{
+        disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, SetTitle,
+    },
     ExecutableCommand,
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
-use crate::app::App;
+use crate::app::{App, FRAME_INTERVAL};
+use crate::crash;
+use crate::profile;
+use crate::wakeup;
+
+/// Whether the terminal title was changed (and the previous one saved on the
+/// terminal's title stack), so cleanup knows to restore it
+static TITLE_CHANGED: AtomicBool = AtomicBool::new(false);
 
 /// Install panic hook to ensure terminal is always cleaned up
+/// and a crash report (with backtrace and recent actions) is written
 pub fn install_panic_hook() {
     let original_hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |panic_info| {
+        tracing::error!("{}", panic_info);
         // Try to clean up terminal before panicking
         let _ = cleanup_terminal();
         original_hook(panic_info);
+        crash::report_panic(&panic_info.to_string());
     }));
 }
 
@@ -26,6 +43,7 @@ pub fn setup_terminal() -> Result<Terminal<CrosstermBackend<std::io::Stderr>>> {
     enable_raw_mode()?;
     std::io::stderr().execute(EnterAlternateScreen)?;
     std::io::stderr().execute(EnableMouseCapture)?;
+    std::io::stderr().execute(EnableFocusChange)?;
 
     let backend = CrosstermBackend::new(std::io::stderr());
     let terminal = Terminal::new(backend)?;
@@ -33,6 +51,17 @@ pub fn setup_terminal() -> Result<Terminal<CrosstermBackend<std::io::Stderr>>> {
     Ok(terminal)
 }
 
+/// Take the terminal back after an external program ran in it (`return_after_editor`)
+pub fn resume_terminal(terminal: &mut Terminal<CrosstermBackend<std::io::Stderr>>) -> Result<()> {
+    enable_raw_mode()?;
+    std::io::stderr().execute(EnterAlternateScreen)?;
+    std::io::stderr().execute(EnableMouseCapture)?;
+    std::io::stderr().execute(EnableFocusChange)?;
+    // The program drew over the screen ratatui remembers
+    terminal.clear()?;
+    Ok(())
+}
+
 pub fn cleanup_terminal() -> Result<()> {
     use crossterm::terminal::{Clear, ClearType};
     use std::io::Write;
@@ -47,6 +76,7 @@ pub fn cleanup_terminal() -> Result<()> {
     let _ = write!(std::io::stderr(), "\x1b[?1006l"); // Disable SGR mode
     let _ = write!(std::io::stderr(), "\x1b[?1015l"); // Disable urxvt mode
     let _ = std::io::stderr().execute(DisableMouseCapture);
+    let _ = std::io::stderr().execute(DisableFocusChange);
     let _ = std::io::stderr().flush();
 
     // 2. Give terminal MORE time to process mouse disable commands
@@ -88,12 +118,30 @@ pub fn cleanup_terminal() -> Result<()> {
     let _ = write!(std::io::stderr(), "\x1b[?25h");
     let _ = std::io::stderr().flush();
 
-    // 9. Final delay to ensure terminal processes everything
+    // 9. Give the title back (XTWINOPS 23 pops the one saved by set_title)
+    if TITLE_CHANGED.swap(false, Ordering::SeqCst) {
+        let _ = write!(std::io::stderr(), "\x1b[23;0t");
+    }
+    let _ = std::io::stderr().flush();
+
+    // 10. Final delay to ensure terminal processes everything
     std::thread::sleep(std::time::Duration::from_millis(10));
 
     Ok(())
 }
 
+/// Set the window title (OSC 0); the first time, save the previous title with
+/// XTWINOPS 22 so `cleanup_terminal` can restore it
+fn set_title(title: &str) -> Result<()> {
+    use std::io::Write;
+
+    if !TITLE_CHANGED.swap(true, Ordering::SeqCst) {
+        write!(std::io::stderr(), "\x1b[22;0t")?;
+    }
+    std::io::stderr().execute(SetTitle(title))?;
+    Ok(())
+}
+
 pub fn run_app(
     terminal: &mut Terminal<CrosstermBackend<std::io::Stderr>>,
     app: &mut App,
@@ -104,27 +152,62 @@ pub fn run_app(
         app.reload_fullscreen_file(terminal_size.width)?;
     }
 
+    // Search and size workers wake the loop as soon as they report
+    wakeup::enable();
+
+    let mut first_frame = true;
+    let mut title = None;
+    let mut last_frame: Option<Instant> = None;
     loop {
         // Check if terminal needs to be cleared (e.g., after exiting fullscreen mode)
         if app.should_clear_terminal() {
             terminal.clear()?;
         }
 
-        // Only render when needed (dirty flag optimization)
-        if app.needs_redraw() {
-            terminal.draw(|f| app.render(f))?;
+        // Only render when needed (dirty flag optimization), at most once per frame
+        let frame_wait = last_frame.map_or(Duration::ZERO, |at| {
+            FRAME_INTERVAL.saturating_sub(at.elapsed())
+        });
+        if app.needs_redraw() && frame_wait.is_zero() {
+            last_frame = Some(Instant::now());
+            if first_frame {
+                profile::phase("first render", || terminal.draw(|f| app.render(f)))?;
+                first_frame = false;
+                // --profile-startup only measures startup
+                if profile::is_enabled() {
+                    return Ok(None);
+                }
+            } else {
+                terminal.draw(|f| app.render(f))?;
+            }
             app.clear_dirty();
+
+            // Navigation that changes the root always redraws
+            if let Some(new_title) = app.terminal_title() {
+                if title.as_ref() != Some(&new_title) {
+                    set_title(&new_title)?;
+                    title = Some(new_title);
+                }
+            }
         }
 
-        // EVENT BATCHING: Wait briefly for events to accumulate before processing
+        // EVENT BATCHING: Wait for events to accumulate before processing
         // This prevents rendering after each individual event during rapid input (e.g., held key)
 
-        // First, check if any event is available (with 8ms timeout for optimal balance)
-        // 8ms = 125fps, provides responsive UI while still batching rapid inputs
-        if !event::poll(std::time::Duration::from_millis(8))? {
-            // No events after 8ms - poll async updates and continue
-            let _ = app.poll_search();
-            let _ = app.poll_sizes();
+        // Wait a frame while background work runs, else until the next timer is due,
+        // so an idle dtree barely wakes up; a frame held back by the limit is drawn in time
+        let mut timeout = app.poll_timeout();
+        if app.needs_redraw() {
+            timeout = timeout.min(frame_wait);
+        }
+        if !event::poll(timeout)? {
+            // No events in time - poll async updates and continue
+            wakeup::take();
+            match poll_background(app)? {
+                Some(path) if !path.as_os_str().is_empty() => return Ok(Some(path)),
+                None => return Ok(None),
+                _ => {}
+            }
             continue;
         }
 
@@ -155,8 +238,9 @@ pub fn run_app(
                         // Terminal was resized - mark for redraw
                         app.mark_dirty();
                     }
+                    Event::FocusGained => app.focus_gained(),
                     _ => {
-                        // Consume all other events (FocusGained, FocusLost, Paste, etc.)
+                        // Consume all other events (FocusLost, Paste, etc.)
                     }
                 }
             } else {
@@ -164,5 +248,39 @@ pub fn run_app(
                 break;
             }
         }
+        // Messages from plugin scripts run by the keys
+        let _ = app.poll_notifications();
+
+        // A background worker woke the loop (with a resize event): take its messages now
+        if wakeup::take() {
+            match poll_background(app)? {
+                Some(path) if !path.as_os_str().is_empty() => return Ok(Some(path)),
+                None => return Ok(None),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Collect background results and fire the timers that are due
+/// Returns what handling a key returns (None: exit)
+fn poll_background(app: &mut App) -> Result<Option<PathBuf>> {
+    let _ = app.poll_preview();
+    let _ = app.poll_file_change();
+    let _ = app.poll_search();
+    let _ = app.poll_sizes();
+    let _ = app.poll_comparison();
+    let _ = app.poll_listing();
+    let _ = app.poll_flat_listing();
+    let _ = app.poll_ignored();
+    let _ = app.poll_file_ops();
+    let _ = app.poll_tasks();
+    let _ = app.poll_repo();
+    let _ = app.poll_projects();
+    let _ = app.poll_notifications();
+    let _ = app.poll_pending_z();
+    if app.poll_idle().is_none() {
+        return Ok(None);
     }
+    app.poll_key_sequence()
 }
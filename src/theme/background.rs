@@ -0,0 +1,151 @@
+use std::env;
+
+/// Terminal background brightness, for `theme = "auto"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Background {
+    Light,
+    Dark,
+}
+
+/// Ask the terminal for its background color (OSC 11), falling back to the
+/// `COLORFGBG` hint some terminals export; None when neither tells
+pub fn detect() -> Option<Background> {
+    query_terminal()
+        .and_then(|reply| from_osc11(&reply))
+        .or_else(|| {
+            env::var("COLORFGBG")
+                .ok()
+                .and_then(|value| from_colorfgbg(&value))
+        })
+}
+
+/// Background from a `COLORFGBG` value (`fg;bg` or `fg;default;bg`, ANSI color indices)
+pub fn from_colorfgbg(value: &str) -> Option<Background> {
+    match value.rsplit(';').next()?.trim().parse::<u8>().ok()? {
+        7 | 9..=15 => Some(Background::Light),
+        0..=6 | 8 => Some(Background::Dark),
+        _ => None,
+    }
+}
+
+/// Background from an OSC 11 reply (`ESC ] 11 ; rgb:RRRR/GGGG/BBBB` and BEL or ST)
+pub fn from_osc11(reply: &str) -> Option<Background> {
+    let start = reply.find("]11;")? + 4;
+    let color = &reply[start..];
+    let color = color
+        .strip_prefix("rgba:")
+        .or_else(|| color.strip_prefix("rgb:"))?;
+    let end = color.find(['\x07', '\x1b']).unwrap_or(color.len());
+
+    // Components have 1 to 4 hex digits; scale each to 0.0-1.0
+    let mut channels = color[..end].split('/').map(|component| {
+        let digits = component.len().clamp(1, 4) as u32;
+        let value = u32::from_str_radix(component, 16).ok()?;
+        Some(value as f64 / (16u32.pow(digits) - 1) as f64)
+    });
+    let (r, g, b) = (channels.next()??, channels.next()??, channels.next()??);
+
+    let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    Some(if luminance > 0.5 {
+        Background::Light
+    } else {
+        Background::Dark
+    })
+}
+
+/// Send OSC 11 followed by a device attributes request (which every terminal answers)
+/// and read the replies; stops at the attributes reply so unsupported terminals
+/// don't cost the whole timeout
+#[cfg(unix)]
+fn query_terminal() -> Option<String> {
+    use crossterm::terminal::{disable_raw_mode, enable_raw_mode, is_raw_mode_enabled};
+    use std::io::{IsTerminal, Read, Write};
+    use std::os::fd::AsRawFd;
+    use std::time::{Duration, Instant};
+
+    const TIMEOUT: Duration = Duration::from_millis(200);
+
+    if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+        return None;
+    }
+    let mut tty = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+
+    let was_raw = is_raw_mode_enabled().unwrap_or(false);
+    enable_raw_mode().ok()?;
+
+    let mut reply = Vec::new();
+    if tty.write_all(b"\x1b]11;?\x1b\\\x1b[c").is_ok() && tty.flush().is_ok() {
+        let deadline = Instant::now() + TIMEOUT;
+        let mut chunk = [0u8; 64];
+        while !has_attributes_reply(&reply) && reply.len() < 256 {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let mut fds = libc::pollfd {
+                fd: tty.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            // SAFETY: a single pollfd for a descriptor that stays open for the call
+            let ready = unsafe { libc::poll(&mut fds, 1, remaining.as_millis() as libc::c_int) };
+            if ready <= 0 {
+                break;
+            }
+            match tty.read(&mut chunk) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => reply.extend_from_slice(&chunk[..read]),
+            }
+        }
+    }
+
+    if !was_raw {
+        let _ = disable_raw_mode();
+    }
+    Some(String::from_utf8_lossy(&reply).into_owned())
+}
+
+#[cfg(windows)]
+fn query_terminal() -> Option<String> {
+    None
+}
+
+/// Whether `reply` ends with a primary device attributes reply (`ESC [ ? ... c`)
+#[cfg(unix)]
+fn has_attributes_reply(reply: &[u8]) -> bool {
+    reply
+        .windows(3)
+        .position(|window| window == b"\x1b[?")
+        .is_some_and(|start| reply[start..].contains(&b'c'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_background_from_osc11() {
+        assert_eq!(
+            from_osc11("\x1b]11;rgb:ffff/ffff/ffff\x1b\\"),
+            Some(Background::Light)
+        );
+        assert_eq!(
+            from_osc11("\x1b]11;rgb:2828/2828/2828\x07\x1b[?62;c"),
+            Some(Background::Dark)
+        );
+        assert_eq!(
+            from_osc11("\x1b]11;rgb:fd/f6/e3\x07"),
+            Some(Background::Light)
+        );
+        // Terminals without OSC 11 only answer the attributes request
+        assert_eq!(from_osc11("\x1b[?1;2c"), None);
+    }
+
+    #[test]
+    fn test_background_from_colorfgbg() {
+        assert_eq!(from_colorfgbg("0;15"), Some(Background::Light));
+        assert_eq!(from_colorfgbg("15;default;0"), Some(Background::Dark));
+        assert_eq!(from_colorfgbg("15;default"), None);
+    }
+}
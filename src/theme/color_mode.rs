@@ -0,0 +1,176 @@
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier};
+use std::env;
+
+/// Levels of the 6x6x6 color cube in the 256-color palette (indices 16-231)
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// How RGB colors (hex theme colors, syntax highlighting) reach the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    /// 24-bit colors as they are
+    TrueColor,
+    /// Nearest color of the 256-color palette
+    Indexed,
+    /// No colors at all, only modifiers (the "monochrome" theme)
+    Monochrome,
+}
+
+impl ColorMode {
+    /// Parse the `color_mode` config value: "truecolor", "256", "monochrome", or
+    /// "auto" (the default, also for unknown values) to detect it from the environment
+    pub fn from_config(value: &str) -> Self {
+        match value.to_ascii_lowercase().as_str() {
+            "truecolor" | "24bit" => ColorMode::TrueColor,
+            "256" => ColorMode::Indexed,
+            "monochrome" => ColorMode::Monochrome,
+            _ => Self::detect(),
+        }
+    }
+
+    /// 256 colors only on terminals known to garble RGB (Apple Terminal, the Linux
+    /// console, GNU screen); truecolor elsewhere, since many terminals with it
+    /// don't set `COLORTERM`
+    pub fn detect() -> Self {
+        Self::from_env(
+            env::var("COLORTERM").ok().as_deref(),
+            env::var("TERM").ok().as_deref(),
+            env::var("TERM_PROGRAM").ok().as_deref(),
+            env::var_os("TMUX").is_some(),
+        )
+    }
+
+    /// Mode for the given `COLORTERM`, `TERM` and `TERM_PROGRAM` values
+    /// (tmux reports a `screen` TERM too, but passes RGB through)
+    pub fn from_env(
+        colorterm: Option<&str>,
+        term: Option<&str>,
+        term_program: Option<&str>,
+        in_tmux: bool,
+    ) -> Self {
+        let colorterm = colorterm.unwrap_or_default().to_ascii_lowercase();
+        let term = term.unwrap_or_default().to_ascii_lowercase();
+        if colorterm == "truecolor" || colorterm == "24bit" || term.ends_with("-direct") {
+            return ColorMode::TrueColor;
+        }
+        if term_program == Some("Apple_Terminal")
+            || term == "linux"
+            || (term.starts_with("screen") && !in_tmux)
+        {
+            ColorMode::Indexed
+        } else {
+            ColorMode::TrueColor
+        }
+    }
+}
+
+/// Nearest 256-color palette index for an RGB color (color cube or gray ramp)
+pub fn rgb_to_indexed(r: u8, g: u8, b: u8) -> u8 {
+    let nearest_level = |value: u8| {
+        CUBE_LEVELS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, &level)| level.abs_diff(value))
+            .map_or(0, |(index, _)| index)
+    };
+    let (ri, gi, bi) = (nearest_level(r), nearest_level(g), nearest_level(b));
+    let cube = (CUBE_LEVELS[ri], CUBE_LEVELS[gi], CUBE_LEVELS[bi]);
+
+    // Gray ramp 232-255 runs from 8 to 238 in steps of 10
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let gray_index = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray_level = 8 + 10 * gray_index;
+
+    let distance = |(cr, cg, cb): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(cr, r) + d(cg, g) + d(cb, b)
+    };
+    if distance((gray_level, gray_level, gray_level)) < distance(cube) {
+        232 + gray_index
+    } else {
+        16 + 36 * ri as u8 + 6 * gi as u8 + bi as u8
+    }
+}
+
+/// The color with RGB replaced by its nearest palette index
+pub fn to_indexed(color: Color) -> Color {
+    match color {
+        Color::Rgb(r, g, b) => Color::Indexed(rgb_to_indexed(r, g, b)),
+        color => color,
+    }
+}
+
+/// Map every RGB color of a rendered frame to the 256-color palette
+pub fn downsample(buffer: &mut Buffer) {
+    for cell in buffer.content.iter_mut() {
+        cell.fg = to_indexed(cell.fg);
+        cell.bg = to_indexed(cell.bg);
+    }
+}
+
+/// Drop every color of a rendered frame; cells set apart by a background other
+/// than `background` (selections, matches) are shown in reverse video instead
+pub fn strip(buffer: &mut Buffer, background: Color) {
+    for cell in buffer.content.iter_mut() {
+        if cell.bg != Color::Reset && cell.bg != background {
+            cell.modifier.insert(Modifier::REVERSED);
+        }
+        cell.fg = Color::Reset;
+        cell.bg = Color::Reset;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rgb_to_indexed() {
+        assert_eq!(rgb_to_indexed(0, 0, 0), 16);
+        assert_eq!(rgb_to_indexed(255, 255, 255), 231);
+        assert_eq!(rgb_to_indexed(255, 0, 0), 196);
+        // Grays land on the gray ramp rather than the coarse cube
+        assert_eq!(rgb_to_indexed(0x28, 0x28, 0x28), 235);
+        // gruvbox orange
+        assert_eq!(rgb_to_indexed(0xfe, 0x80, 0x19), 208);
+    }
+
+    #[test]
+    fn test_color_mode_from_env() {
+        let mode = |colorterm, term| ColorMode::from_env(colorterm, term, None, false);
+        assert_eq!(mode(Some("truecolor"), None), ColorMode::TrueColor);
+        assert_eq!(mode(None, Some("xterm-direct")), ColorMode::TrueColor);
+        // Without COLORTERM only terminals known to lack truecolor are downsampled
+        assert_eq!(mode(None, Some("xterm-256color")), ColorMode::TrueColor);
+        assert_eq!(mode(None, Some("linux")), ColorMode::Indexed);
+        assert_eq!(mode(None, Some("screen-256color")), ColorMode::Indexed);
+        assert_eq!(
+            mode(Some("truecolor"), Some("screen")),
+            ColorMode::TrueColor
+        );
+        let apple =
+            ColorMode::from_env(None, Some("xterm-256color"), Some("Apple_Terminal"), false);
+        assert_eq!(apple, ColorMode::Indexed);
+        let tmux = ColorMode::from_env(None, Some("screen-256color"), None, true);
+        assert_eq!(tmux, ColorMode::TrueColor);
+        assert_eq!(ColorMode::from_config("256"), ColorMode::Indexed);
+    }
+
+    #[test]
+    fn test_strip_keeps_highlights_as_reverse_video() {
+        let mut buffer = Buffer::empty(ratatui::layout::Rect::new(0, 0, 3, 1));
+        buffer.content[0].set_fg(Color::Red).set_bg(Color::Black);
+        buffer.content[1].set_bg(Color::Blue);
+        strip(&mut buffer, Color::Black);
+        assert!(buffer
+            .content
+            .iter()
+            .all(|cell| cell.fg == Color::Reset && cell.bg == Color::Reset));
+        let reversed: Vec<bool> = buffer
+            .content
+            .iter()
+            .map(|cell| cell.modifier.contains(Modifier::REVERSED))
+            .collect();
+        assert_eq!(reversed, [false, true, false]);
+    }
+}
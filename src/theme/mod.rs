@@ -1,6 +1,8 @@
 use ratatui::style::Color;
 use serde::{Deserialize, Serialize};
 
+pub mod background;
+pub mod color_mode;
 pub mod presets;
 
 /// Theme configuration with customizable colors
@@ -21,6 +23,14 @@ pub struct ThemeConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub file_color: Option<String>,
 
+    /// Color for executable files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executable_color: Option<String>,
+
+    /// Color for setuid/setgid and world-writable entries (with `permission_warnings`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permission_warning_color: Option<String>,
+
     /// Color for borders
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub border_color: Option<String>,
@@ -57,6 +67,10 @@ pub struct ThemeConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub panel_border_color: Option<String>,
 
+    /// Color for the border of the focused pane (tree, viewer, results, bookmarks)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus_border_color: Option<String>,
+
     /// Color for background (optional, uses terminal default if not set)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub background_color: Option<String>,
@@ -117,6 +131,8 @@ impl ThemeConfig {
             selected_color: Some("cyan".to_string()),
             directory_color: Some("gray".to_string()),
             file_color: Some("white".to_string()),
+            executable_color: Some("green".to_string()),
+            permission_warning_color: Some("lightred".to_string()),
             border_color: Some("gray".to_string()),
             error_color: Some("red".to_string()),
             highlight_color: Some("yellow".to_string()),
@@ -126,6 +142,7 @@ impl ThemeConfig {
             tree_cursor_bg_color: Some("dim".to_string()),
             main_border_color: Some("gray".to_string()),
             panel_border_color: Some("cyan".to_string()),
+            focus_border_color: Some("cyan".to_string()),
             background_color: Some("reset".to_string()),
         }
     }
@@ -9,6 +9,10 @@ pub fn get_preset(theme_name: &str) -> Option<ThemeConfig> {
         "tokyonight" => Some(tokyonight_theme()),
         "dracula" => Some(dracula_theme()),
         "obsidian" => Some(obsidian_theme()),
+        "light" => Some(light_theme()),
+        "high-contrast" => Some(high_contrast_theme()),
+        "deuteranopia" => Some(deuteranopia_theme()),
+        "monochrome" => Some(monochrome_theme()),
         _ => None,
     }
 }
@@ -19,6 +23,8 @@ fn default_theme() -> ThemeConfig {
         selected_color: Some("cyan".to_string()),
         directory_color: Some("gray".to_string()),
         file_color: Some("white".to_string()),
+        executable_color: Some("green".to_string()),
+        permission_warning_color: Some("lightred".to_string()),
         border_color: Some("gray".to_string()),
         error_color: Some("red".to_string()),
         highlight_color: Some("yellow".to_string()),
@@ -28,6 +34,7 @@ fn default_theme() -> ThemeConfig {
         tree_cursor_bg_color: Some("dim".to_string()), // "dim" = no background color
         main_border_color: Some("gray".to_string()), // main window border
         panel_border_color: Some("cyan".to_string()), // panel borders (search, bookmarks)
+        focus_border_color: Some("cyan".to_string()), // focused pane border
         background_color: Some("reset".to_string()), // terminal default
     }
 }
@@ -38,6 +45,8 @@ fn gruvbox_theme() -> ThemeConfig {
         selected_color: Some("#fe8019".to_string()), // bright orange
         directory_color: Some("#83a598".to_string()), // bright blue
         file_color: Some("#ebdbb2".to_string()),     // light foreground
+        executable_color: Some("#b8bb26".to_string()), // bright green
+        permission_warning_color: Some("#d65d0e".to_string()), // orange
         border_color: Some("#928374".to_string()),   // gray
         error_color: Some("#fb4934".to_string()),    // bright red
         highlight_color: Some("#fabd2f".to_string()), // bright yellow
@@ -47,6 +56,7 @@ fn gruvbox_theme() -> ThemeConfig {
         tree_cursor_bg_color: Some("#303030".to_string()), // barely visible darker background
         main_border_color: Some("#928374".to_string()), // gray border
         panel_border_color: Some("#fe8019".to_string()), // orange panel borders (search, bookmarks)
+        focus_border_color: Some("#fe8019".to_string()), // focused pane border
         background_color: Some("#282828".to_string()), // gruvbox dark bg
     }
 }
@@ -57,6 +67,8 @@ fn nord_theme() -> ThemeConfig {
         selected_color: Some("#88c0d0".to_string()), // frost cyan
         directory_color: Some("#81a1c1".to_string()), // frost blue
         file_color: Some("#eceff4".to_string()),     // snow white
+        executable_color: Some("#a3be8c".to_string()), // aurora green
+        permission_warning_color: Some("#d08770".to_string()), // aurora orange
         border_color: Some("#4c566a".to_string()),   // polar night gray
         error_color: Some("#bf616a".to_string()),    // aurora red
         highlight_color: Some("#ebcb8b".to_string()), // aurora yellow
@@ -66,6 +78,7 @@ fn nord_theme() -> ThemeConfig {
         tree_cursor_bg_color: Some("#343a48".to_string()), // barely visible lighter background
         main_border_color: Some("#4c566a".to_string()), // polar night gray border
         panel_border_color: Some("#88c0d0".to_string()), // cyan panel borders (search, bookmarks)
+        focus_border_color: Some("#88c0d0".to_string()), // focused pane border
         background_color: Some("#2e3440".to_string()), // nord dark bg
     }
 }
@@ -73,18 +86,21 @@ fn nord_theme() -> ThemeConfig {
 /// Tokyo Night theme - Modern dark theme with vibrant colors
 fn tokyonight_theme() -> ThemeConfig {
     ThemeConfig {
-        selected_color: Some("#7aa2f7".to_string()),  // blue
-        directory_color: Some("#7dcfff".to_string()), // cyan
-        file_color: Some("#a9b1d6".to_string()),      // light gray-blue
-        border_color: Some("#3b4261".to_string()),    // dark gray
-        error_color: Some("#f7768e".to_string()),     // red
-        highlight_color: Some("#e0af68".to_string()), // yellow
+        selected_color: Some("#7aa2f7".to_string()),   // blue
+        directory_color: Some("#7dcfff".to_string()),  // cyan
+        file_color: Some("#a9b1d6".to_string()),       // light gray-blue
+        executable_color: Some("#9ece6a".to_string()), // green
+        permission_warning_color: Some("#ff9e64".to_string()), // orange
+        border_color: Some("#3b4261".to_string()),     // dark gray
+        error_color: Some("#f7768e".to_string()),      // red
+        highlight_color: Some("#e0af68".to_string()),  // yellow
         file_search_highlight_color: Some("#e0af68".to_string()), // yellow for file search
-        cursor_color: Some("#bb9af7".to_string()),    // purple for search & bookmarks
+        cursor_color: Some("#bb9af7".to_string()),     // purple for search & bookmarks
         tree_cursor_color: Some("#a9b1d6".to_string()), // light gray-blue text
         tree_cursor_bg_color: Some("#1f202e".to_string()), // barely visible lighter background
         main_border_color: Some("#3b4261".to_string()), // dark gray border
         panel_border_color: Some("#9d7cd8".to_string()), // purple panel borders (search, bookmarks)
+        focus_border_color: Some("#9d7cd8".to_string()), // focused pane border
         background_color: Some("#1a1b26".to_string()), // tokyo night dark bg
     }
 }
@@ -92,18 +108,21 @@ fn tokyonight_theme() -> ThemeConfig {
 /// Dracula theme - Popular dark theme with high contrast
 fn dracula_theme() -> ThemeConfig {
     ThemeConfig {
-        selected_color: Some("#ff79c6".to_string()),  // pink
-        directory_color: Some("#8be9fd".to_string()), // cyan
-        file_color: Some("#f8f8f2".to_string()),      // white
-        border_color: Some("#6272a4".to_string()),    // comment gray
-        error_color: Some("#ff5555".to_string()),     // red
-        highlight_color: Some("#f1fa8c".to_string()), // yellow
+        selected_color: Some("#ff79c6".to_string()),   // pink
+        directory_color: Some("#8be9fd".to_string()),  // cyan
+        file_color: Some("#f8f8f2".to_string()),       // white
+        executable_color: Some("#50fa7b".to_string()), // green
+        permission_warning_color: Some("#ffb86c".to_string()), // orange
+        border_color: Some("#6272a4".to_string()),     // comment gray
+        error_color: Some("#ff5555".to_string()),      // red
+        highlight_color: Some("#f1fa8c".to_string()),  // yellow
         file_search_highlight_color: Some("#f1fa8c".to_string()), // yellow for file search
-        cursor_color: Some("#bd93f9".to_string()),    // purple for search & bookmarks
+        cursor_color: Some("#bd93f9".to_string()),     // purple for search & bookmarks
         tree_cursor_color: Some("#f8f8f2".to_string()), // white text
         tree_cursor_bg_color: Some("#2d2f3d".to_string()), // barely visible lighter background
         main_border_color: Some("#6272a4".to_string()), // comment gray border
         panel_border_color: Some("#ff79c6".to_string()), // pink panel borders (search, bookmarks)
+        focus_border_color: Some("#ff79c6".to_string()), // focused pane border
         background_color: Some("#282a36".to_string()), // dracula dark bg
     }
 }
@@ -114,6 +133,8 @@ fn obsidian_theme() -> ThemeConfig {
         selected_color: Some("#a88bfa".to_string()), // soft purple
         directory_color: Some("#8b9dff".to_string()), // light blue
         file_color: Some("#dcddde".to_string()),     // light gray
+        executable_color: Some("#7fd88f".to_string()), // soft green
+        permission_warning_color: Some("#f0a45d".to_string()), // soft orange
         border_color: Some("#3e4044".to_string()),   // dark gray
         error_color: Some("#f14c4c".to_string()),    // soft red
         highlight_color: Some("#c792ea".to_string()), // violet
@@ -123,6 +144,96 @@ fn obsidian_theme() -> ThemeConfig {
         tree_cursor_bg_color: Some("#1e1e21".to_string()), // barely visible dark background
         main_border_color: Some("#3e4044".to_string()), // dark gray border
         panel_border_color: Some("#7c6baf".to_string()), // muted purple panel borders
+        focus_border_color: Some("#7c6baf".to_string()), // focused pane border
         background_color: Some("#1a1a1d".to_string()), // obsidian dark bg
     }
 }
+
+/// Light theme - Dark text for light terminal backgrounds (picked by `theme = "auto"`)
+fn light_theme() -> ThemeConfig {
+    ThemeConfig {
+        selected_color: Some("#0550ae".to_string()),  // dark blue
+        directory_color: Some("#0969da".to_string()), // blue
+        file_color: Some("#24292f".to_string()),      // near-black foreground
+        executable_color: Some("#116329".to_string()), // dark green
+        permission_warning_color: Some("#bc4c00".to_string()), // dark orange
+        border_color: Some("#8c959f".to_string()),    // mid gray
+        error_color: Some("#cf222e".to_string()),     // red
+        highlight_color: Some("#8250df".to_string()), // purple
+        file_search_highlight_color: Some("#9a6700".to_string()), // dark yellow for file search
+        cursor_color: Some("#8250df".to_string()),    // purple for search & bookmarks
+        tree_cursor_color: Some("#24292f".to_string()), // near-black text
+        tree_cursor_bg_color: Some("#ddf4ff".to_string()), // pale blue background
+        main_border_color: Some("#8c959f".to_string()), // gray border
+        panel_border_color: Some("#0969da".to_string()), // blue panel borders (search, bookmarks)
+        focus_border_color: Some("#0969da".to_string()), // focused pane border
+        background_color: Some("reset".to_string()),  // terminal default (light)
+    }
+}
+
+/// High contrast theme - Bright colors on black, strong cursor
+fn high_contrast_theme() -> ThemeConfig {
+    ThemeConfig {
+        selected_color: Some("#ffff00".to_string()),   // yellow
+        directory_color: Some("#00ffff".to_string()),  // cyan
+        file_color: Some("#ffffff".to_string()),       // white
+        executable_color: Some("#00ff00".to_string()), // green
+        permission_warning_color: Some("#ff00ff".to_string()), // magenta
+        border_color: Some("#ffffff".to_string()),     // white
+        error_color: Some("#ff5555".to_string()),      // light red
+        highlight_color: Some("#ffff00".to_string()),  // yellow
+        file_search_highlight_color: Some("#ffff00".to_string()), // yellow for file search
+        cursor_color: Some("#ffff00".to_string()),     // yellow for search & bookmarks
+        tree_cursor_color: Some("#000000".to_string()), // black text
+        tree_cursor_bg_color: Some("#ffff00".to_string()), // on a yellow bar
+        main_border_color: Some("#ffffff".to_string()), // white border
+        panel_border_color: Some("#00ffff".to_string()), // cyan panel borders (search, bookmarks)
+        focus_border_color: Some("#ffff00".to_string()), // focused pane border
+        background_color: Some("#000000".to_string()), // black bg
+    }
+}
+
+/// Deuteranopia theme - Blue/orange palette (Okabe-Ito) without red/green pairs
+fn deuteranopia_theme() -> ThemeConfig {
+    ThemeConfig {
+        selected_color: Some("#e69f00".to_string()),   // orange
+        directory_color: Some("#56b4e9".to_string()),  // sky blue
+        file_color: Some("#e0e0e0".to_string()),       // light gray
+        executable_color: Some("#f0e442".to_string()), // yellow
+        permission_warning_color: Some("#cc79a7".to_string()), // reddish purple
+        border_color: Some("#808080".to_string()),     // gray
+        error_color: Some("#d55e00".to_string()),      // vermillion
+        highlight_color: Some("#e69f00".to_string()),  // orange
+        file_search_highlight_color: Some("#e69f00".to_string()), // orange for file search
+        cursor_color: Some("#e69f00".to_string()),     // orange for search & bookmarks
+        tree_cursor_color: Some("#ffffff".to_string()), // white text
+        tree_cursor_bg_color: Some("#0072b2".to_string()), // on a blue bar
+        main_border_color: Some("#808080".to_string()), // gray border
+        panel_border_color: Some("#0072b2".to_string()), // blue panel borders (search, bookmarks)
+        focus_border_color: Some("#e69f00".to_string()), // focused pane border
+        background_color: Some("reset".to_string()),   // terminal default
+    }
+}
+
+/// Monochrome theme - No colors, only bold/reverse/underline and symbols
+/// (dtree also drops the colors that aren't part of the theme)
+fn monochrome_theme() -> ThemeConfig {
+    ThemeConfig {
+        selected_color: Some("reset".to_string()),
+        directory_color: Some("reset".to_string()),
+        file_color: Some("reset".to_string()),
+        executable_color: Some("reset".to_string()),
+        permission_warning_color: Some("reset".to_string()),
+        border_color: Some("reset".to_string()),
+        error_color: Some("reset".to_string()),
+        highlight_color: Some("reset".to_string()),
+        file_search_highlight_color: Some("reset".to_string()),
+        cursor_color: Some("reset".to_string()), // bold with the ">>" marker
+        tree_cursor_color: Some("reset".to_string()),
+        tree_cursor_bg_color: Some("white".to_string()), // any color: shown as reverse video
+        main_border_color: Some("reset".to_string()),
+        panel_border_color: Some("reset".to_string()),
+        focus_border_color: Some("reset".to_string()), // the focused pane has a thick border
+        background_color: Some("reset".to_string()),
+    }
+}
@@ -0,0 +1,181 @@
+use crate::file_icons;
+use crate::list_selection::ListSelection;
+use crate::tree_node::TreeNode;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// Marker prefix for the tree printed to stdout on exit (the text follows it)
+const EXPORT_MARKER: &str = "TREE:";
+
+/// Text layouts the visible tree can be exported in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeFormat {
+    /// Two spaces per level, directories end in `/`
+    Text,
+    /// Nested `- ` list items
+    Markdown,
+}
+
+impl TreeFormat {
+    pub fn label(self) -> &'static str {
+        match self {
+            TreeFormat::Text => "Text",
+            TreeFormat::Markdown => "Markdown",
+        }
+    }
+}
+
+/// Where an export goes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Destination {
+    Clipboard,
+    File(PathBuf),
+    /// Printed when dtree exits (no `tree_export_file` configured)
+    Stdout,
+}
+
+/// The visible entries (expanded directories, current filters) as indented text
+/// `icons` keeps the tree's icons: nerd-font ones with `nerd_fonts`, emoji otherwise
+pub fn format<'a>(
+    nodes: impl IntoIterator<Item = &'a TreeNode>,
+    format: TreeFormat,
+    icons: bool,
+    nerd_fonts: bool,
+) -> String {
+    let mut text = String::new();
+    for node in nodes {
+        let indent = "  ".repeat(node.depth);
+        let icon = if icons {
+            let icon = file_icons::get_icon(&node.path, node.is_dir, nerd_fonts).trim();
+            if icon.is_empty() {
+                String::new()
+            } else {
+                format!("{} ", icon)
+            }
+        } else {
+            String::new()
+        };
+        let slash = if node.is_dir { "/" } else { "" };
+        match format {
+            TreeFormat::Text => {
+                text.push_str(&format!("{}{}{}{}\n", indent, icon, node.name, slash));
+            }
+            TreeFormat::Markdown => text.push_str(&format!(
+                "{}- {}{}{}\n",
+                indent,
+                icon,
+                escape_markdown(&node.name),
+                slash
+            )),
+        }
+    }
+    text
+}
+
+/// Backslash-escape characters Markdown would turn into formatting
+fn escape_markdown(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Write the exported tree to `file`
+pub fn write(file: &Path, text: &str) -> Result<()> {
+    if let Some(dir) = file.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(file, text).with_context(|| format!("Failed to write {}", file.display()))
+}
+
+/// Encode the exported tree as a return marker for main.rs to print on exit
+pub fn encode(text: &str) -> PathBuf {
+    PathBuf::from(format!("{}{}", EXPORT_MARKER, text))
+}
+
+/// Decode a return marker produced by `encode` into the text to print
+pub fn decode(marker: &str) -> Option<&str> {
+    marker.strip_prefix(EXPORT_MARKER)
+}
+
+/// Export menu shown in the bottom panel: format and destination, `i` toggles icons
+#[derive(Debug, Default)]
+pub struct ExportMenu {
+    pub is_open: bool,
+    pub selected_index: usize,
+    pub icons: bool,
+    pub options: Vec<(TreeFormat, Destination)>,
+}
+
+impl ExportMenu {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the menu; `file` is the configured `tree_export_file` (None: stdout on exit)
+    pub fn open(&mut self, file: Option<PathBuf>) {
+        let other = file.map_or(Destination::Stdout, Destination::File);
+        self.options = vec![
+            (TreeFormat::Text, Destination::Clipboard),
+            (TreeFormat::Markdown, Destination::Clipboard),
+            (TreeFormat::Text, other.clone()),
+            (TreeFormat::Markdown, other),
+        ];
+        self.selected_index = 0;
+        self.is_open = true;
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+        self.options.clear();
+    }
+
+    pub fn get_selected(&self) -> Option<&(TreeFormat, Destination)> {
+        self.options.get(self.selected_index)
+    }
+}
+
+impl ListSelection for ExportMenu {
+    fn selected_index_mut(&mut self) -> &mut usize {
+        &mut self.selected_index
+    }
+
+    fn entry_count(&self) -> usize {
+        self.options.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_tree() {
+        let node =
+            |path: &str, depth, is_dir| TreeNode::new_virtual(PathBuf::from(path), depth, is_dir);
+        let nodes = [
+            node("/p/proj", 0, true),
+            node("/p/proj/src", 1, true),
+            node("/p/proj/src/my_lib.rs", 2, false),
+            node("/p/proj/README.md", 1, false),
+        ];
+
+        assert_eq!(
+            format(nodes.iter(), TreeFormat::Text, false, false),
+            "proj/\n  src/\n    my_lib.rs\n  README.md\n"
+        );
+        assert_eq!(
+            format(nodes.iter(), TreeFormat::Markdown, false, false),
+            "- proj/\n  - src/\n    - my\\_lib.rs\n  - README.md\n"
+        );
+        assert!(format(nodes.iter(), TreeFormat::Text, true, false).starts_with("📁 proj/\n"));
+        assert_eq!(decode(&encode("a\n").to_string_lossy()), Some("a\n"));
+    }
+}
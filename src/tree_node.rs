@@ -1,32 +1,63 @@
+use crate::filter_set::FilterSet;
+use crate::i18n::tr_with;
+use crate::platform;
+use crate::vfs::{self, Vfs};
 use anyhow::Result;
-use std::cell::RefCell;
 use std::fs;
-use std::path::PathBuf;
-use std::rc::Rc;
+use std::ops::{Index, IndexMut};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-pub type TreeNodeRef = Rc<RefCell<TreeNode>>;
+/// Handle of a node in its `Tree`
+/// The id of a removed node doesn't reach the node that later takes its slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId {
+    index: u32,
+    generation: u32,
+}
+
+impl NodeId {
+    /// Position of the node's slot in the arena, for tables kept alongside the tree
+    /// (a later node in the same slot gets the same one)
+    pub fn slot(self) -> usize {
+        self.index as usize
+    }
+}
 
+/// An entry of the tree; its children are ids of nodes in the same `Tree`
 pub struct TreeNode {
     pub path: PathBuf,
     pub name: String,
     pub is_dir: bool,
     pub is_expanded: bool,
     pub depth: usize,
-    pub children: Vec<TreeNodeRef>,
+    pub children: Vec<NodeId>,
     pub has_error: bool,               // Indicates read/access errors
     pub error_message: Option<String>, // Optional error description
+    pub is_mountpoint: bool,           // Directory lives on a different filesystem than its parent
+    pub is_virtual: bool,              // Children come from a path list (--stdin), not the disk
+    pub mode: u32,                     // Permission bits (0 when unknown or on Windows)
+    pub vfs: Vfs,                      // Where the node and its children are read from
     is_sorted: bool,                   // Cache flag: true if children are already sorted
 }
 
 impl TreeNode {
+    #[cfg_attr(not(test), allow(dead_code))]
     pub fn new(path: PathBuf, depth: usize) -> Result<Self> {
+        Self::new_in(vfs::local(), path, depth)
+    }
+
+    /// Create a node read from `vfs` (an archive, a remote host, ...)
+    pub fn new_in(vfs: Vfs, path: PathBuf, depth: usize) -> Result<Self> {
         let name = path
             .file_name()
             .and_then(|n| n.to_str())
             .unwrap_or("")
             .to_string();
 
-        let is_dir = path.is_dir();
+        let metadata = vfs.metadata(&path).ok();
+        let is_dir = metadata.as_ref().is_some_and(|metadata| metadata.is_dir);
+        let mode = metadata.map_or(0, |metadata| metadata.mode);
 
         Ok(TreeNode {
             path,
@@ -37,72 +68,137 @@ impl TreeNode {
             children: Vec::new(),
             has_error: false,
             error_message: None,
+            is_mountpoint: false,
+            is_virtual: false,
+            mode,
+            vfs,
             is_sorted: false,
         })
     }
 
-    pub fn load_children(
-        &mut self,
-        show_files: bool,
-        show_hidden: bool,
-        follow_symlinks: bool,
-    ) -> Result<()> {
-        // If children are already loaded and sorted, skip
-        if !self.is_dir || (!self.children.is_empty() && self.is_sorted) {
-            return Ok(());
+    /// Create a node of a virtual tree whose children are added with `add_virtual_path`
+    pub fn new_virtual(path: PathBuf, depth: usize, is_dir: bool) -> Self {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        let mode = fs::metadata(&path)
+            .map(|metadata| platform::file_mode(&metadata))
+            .unwrap_or(0);
+
+        TreeNode {
+            path,
+            name,
+            is_dir,
+            is_expanded: is_dir,
+            depth,
+            children: Vec::new(),
+            has_error: false,
+            error_message: None,
+            is_mountpoint: false,
+            is_virtual: true,
+            mode,
+            vfs: vfs::local(),
+            is_sorted: true,
         }
+    }
+
+    /// Check if this is a file with an execute bit set
+    pub fn is_executable(&self) -> bool {
+        !self.is_dir && self.mode & 0o111 != 0
+    }
 
-        // If we're reloading (children exist but not sorted), clear them first
-        if !self.children.is_empty() {
-            self.children.clear();
-            self.is_sorted = false;
+    /// Permissions worth a look: setuid/setgid programs and entries anyone can write to
+    /// Setgid and world-writable sticky directories (like /tmp) are normal and not flagged
+    pub fn permission_warning(&self) -> Option<&'static str> {
+        if self.mode & 0o4000 != 0 {
+            Some("setuid")
+        } else if self.mode & 0o2000 != 0 && !self.is_dir {
+            Some("setgid")
+        } else if self.mode & 0o002 != 0 && !(self.is_dir && self.mode & 0o1000 != 0) {
+            Some("o+w")
+        } else {
+            None
         }
+    }
+
+    /// Order of siblings: directories first, then files, sorted by name within each group
+    fn sibling_order(&self, other: &TreeNode) -> std::cmp::Ordering {
+        other
+            .is_dir
+            .cmp(&self.is_dir)
+            .then_with(|| self.name.cmp(&other.name))
+    }
+
+    /// Read the entries of this directory as new child nodes, sorted
+    /// A directory that can't be read marks the node and has no children
+    fn read_children(
+        &mut self,
+        show_files: bool,
+        filters: &FilterSet,
+        follow_symlinks: bool,
+    ) -> Vec<TreeNode> {
+        let _span = tracing::debug_span!("load_children", path = %self.path.display()).entered();
 
         // Try to read directory
-        let entries = match fs::read_dir(&self.path) {
+        let entries = match self.vfs.read_dir(&self.path) {
             Ok(entries) => entries,
             Err(e) => {
+                tracing::warn!("cannot read {}: {}", self.path.display(), e);
                 // Mark this node as having an error
                 self.has_error = true;
-                self.error_message = Some(format!("Cannot read: {}", e));
-                return Ok(()); // Don't propagate error, just mark the node
+                self.error_message = Some(tr_with("Cannot read: {error}", &[("error", &e)]));
+                return Vec::new(); // Don't propagate error, just mark the node
             }
         };
 
+        let mut children = Vec::new();
         let mut error_count = 0;
         let mut skipped_entries = Vec::new();
 
+        // Device of this directory - children on another device are mountpoints
+        let parent_device = self
+            .vfs
+            .metadata(&self.path)
+            .ok()
+            .and_then(|metadata| metadata.device());
+
         // Process entries, tracking errors
         for entry in entries {
             match entry {
-                Ok(entry) => {
-                    let path = entry.path();
-
+                Ok(path) => {
                     // Check if entry is a symlink and whether to follow it
                     if !follow_symlinks {
-                        if let Ok(metadata) = fs::symlink_metadata(&path) {
-                            if metadata.is_symlink() {
+                        if let Ok(metadata) = self.vfs.symlink_metadata(&path) {
+                            if metadata.is_symlink {
                                 continue; // Skip symlinks if follow_symlinks is false
                             }
                         }
                     }
 
-                    let is_dir = path.is_dir();
+                    let is_dir = self.vfs.is_dir(&path);
 
-                    // Check if file/directory is hidden (starts with .)
-                    if !show_hidden {
-                        if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                            if name.starts_with('.') {
-                                continue; // Skip hidden files/directories
-                            }
-                        }
+                    // Check if file/directory is hidden (dotfile or hidden attribute)
+                    if filters.hides(self.vfs.as_ref(), &path) {
+                        continue; // Skip hidden files/directories
                     }
 
                     // Show directories always, files only if show_files == true
                     if is_dir || show_files {
-                        match TreeNode::new(path.clone(), self.depth + 1) {
-                            Ok(node) => {
-                                self.children.push(Rc::new(RefCell::new(node)));
+                        match TreeNode::new_in(Arc::clone(&self.vfs), path.clone(), self.depth + 1)
+                        {
+                            Ok(mut node) => {
+                                // The link's own device: a symlink to another
+                                // filesystem isn't a mountpoint
+                                if is_dir && parent_device.is_some() {
+                                    node.is_mountpoint = self
+                                        .vfs
+                                        .symlink_metadata(&path)
+                                        .ok()
+                                        .and_then(|metadata| metadata.device())
+                                        != parent_device;
+                                }
+                                children.push(node);
                             }
                             Err(e) => {
                                 error_count += 1;
@@ -128,47 +224,276 @@ impl TreeNode {
             if error_count <= 3 {
                 self.error_message = Some(skipped_entries.join(", "));
             } else {
-                self.error_message = Some(format!("{} entries inaccessible", error_count));
+                self.error_message = Some(tr_with(
+                    "{count} entries inaccessible",
+                    &[("count", &error_count)],
+                ));
+            }
+        }
+
+        children.sort_by(TreeNode::sibling_order);
+        children
+    }
+}
+
+struct Slot {
+    generation: u32,
+    node: Option<TreeNode>,
+}
+
+/// The nodes of a tree, kept in one arena and linked by `NodeId`s
+/// Slots of removed nodes are reused; nodes can be moved to other threads
+pub struct Tree {
+    slots: Vec<Slot>,
+    free: Vec<u32>,
+    root: NodeId,
+}
+
+impl Tree {
+    pub fn new(root: TreeNode) -> Self {
+        let mut tree = Tree {
+            slots: Vec::new(),
+            free: Vec::new(),
+            root: NodeId {
+                index: 0,
+                generation: 0,
+            },
+        };
+        tree.root = tree.insert(root);
+        tree
+    }
+
+    pub fn root(&self) -> NodeId {
+        self.root
+    }
+
+    pub fn root_node(&self) -> &TreeNode {
+        &self[self.root]
+    }
+
+    pub fn root_node_mut(&mut self) -> &mut TreeNode {
+        let root = self.root;
+        &mut self[root]
+    }
+
+    /// The node of `id`; None once it was removed
+    pub fn get(&self, id: NodeId) -> Option<&TreeNode> {
+        let slot = self.slots.get(id.index as usize)?;
+        slot.node
+            .as_ref()
+            .filter(|_| slot.generation == id.generation)
+    }
+
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut TreeNode> {
+        let slot = self.slots.get_mut(id.index as usize)?;
+        slot.node
+            .as_mut()
+            .filter(|_| slot.generation == id.generation)
+    }
+
+    /// Add a node that isn't linked as a child yet (builders put it into `children`)
+    pub fn insert(&mut self, node: TreeNode) -> NodeId {
+        match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.node = Some(node);
+                NodeId {
+                    index,
+                    generation: slot.generation,
+                }
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    node: Some(node),
+                });
+                NodeId {
+                    index: self.slots.len() as u32 - 1,
+                    generation: 0,
+                }
             }
         }
+    }
 
-        // Sort: directories first, then files, sorted by name within each group
-        self.children.sort_by(|a, b| {
-            let a_borrowed = a.borrow();
-            let b_borrowed = b.borrow();
-            match (a_borrowed.is_dir, b_borrowed.is_dir) {
-                (true, false) => std::cmp::Ordering::Less,
-                (false, true) => std::cmp::Ordering::Greater,
-                _ => a_borrowed.name.cmp(&b_borrowed.name),
+    /// Add `node` as the last child of `parent`
+    pub fn add_child(&mut self, parent: NodeId, node: TreeNode) -> NodeId {
+        let id = self.insert(node);
+        self[parent].children.push(id);
+        id
+    }
+
+    /// Remove the children of `id` and everything below them
+    pub fn clear_children(&mut self, id: NodeId) {
+        let mut removed = std::mem::take(&mut self[id].children);
+        while let Some(child) = removed.pop() {
+            let slot = &mut self.slots[child.index as usize];
+            if let Some(node) = slot.node.take() {
+                slot.generation = slot.generation.wrapping_add(1);
+                self.free.push(child.index);
+                removed.extend(node.children);
             }
-        });
+        }
+    }
 
-        // Mark as sorted so we don't re-sort on next load
-        self.is_sorted = true;
+    /// Read the children of a directory node, unless they are loaded already
+    /// (virtual nodes never read the disk); hidden entries aren't loaded, the other
+    /// filters only leave entries out of the flat list
+    pub fn load_children(
+        &mut self,
+        id: NodeId,
+        show_files: bool,
+        filters: &FilterSet,
+        follow_symlinks: bool,
+    ) -> Result<()> {
+        let node = &self[id];
+        if node.is_virtual || !node.is_dir || (!node.children.is_empty() && node.is_sorted) {
+            return Ok(());
+        }
 
+        self.clear_children(id);
+        let children = self[id].read_children(show_files, filters, follow_symlinks);
+        let ids = children
+            .into_iter()
+            .map(|child| self.insert(child))
+            .collect();
+        let node = &mut self[id];
+        node.children = ids;
+        // Mark as sorted so we don't re-sort on next load
+        node.is_sorted = true;
         Ok(())
     }
 
     pub fn toggle_expand(
         &mut self,
+        id: NodeId,
         show_files: bool,
-        show_hidden: bool,
+        filters: &FilterSet,
         follow_symlinks: bool,
     ) -> Result<()> {
-        if !self.is_dir {
+        if !self[id].is_dir {
             return Ok(());
         }
 
-        if self.is_expanded {
-            self.is_expanded = false;
+        if self[id].is_expanded {
+            self[id].is_expanded = false;
         } else {
-            self.load_children(show_files, show_hidden, follow_symlinks)?;
+            self.load_children(id, show_files, filters, follow_symlinks)?;
             // Only expand if no access error occurred
-            if !self.has_error {
-                self.is_expanded = true;
+            let node = &mut self[id];
+            if !node.has_error {
+                node.is_expanded = true;
             }
         }
 
         Ok(())
     }
+
+    /// Add `path` (a descendant of the virtual node `id`) and any missing intermediate
+    /// directories
+    pub fn add_virtual_path(&mut self, id: NodeId, path: &Path) {
+        let node = &self[id];
+        let Ok(relative) = path.strip_prefix(&node.path) else {
+            return;
+        };
+        let Some(first) = relative.components().next() else {
+            return; // The node itself
+        };
+
+        let child_path = node.path.join(first);
+        let depth = node.depth + 1;
+        let existing = node
+            .children
+            .iter()
+            .copied()
+            .find(|&child| self[child].path == child_path);
+
+        let child = existing.unwrap_or_else(|| {
+            // Intermediate components are directories by definition
+            let is_dir = child_path != path || path.is_dir();
+            self.add_child(id, TreeNode::new_virtual(child_path, depth, is_dir))
+        });
+
+        self.add_virtual_path(child, path);
+    }
+
+    /// Sort the children below `id` recursively (same order as load_children)
+    pub fn sort_virtual(&mut self, id: NodeId) {
+        let mut children = std::mem::take(&mut self[id].children);
+        children.sort_by(|&a, &b| self[a].sibling_order(&self[b]));
+        for &child in &children {
+            self.sort_virtual(child);
+        }
+        self[id].children = children;
+    }
+}
+
+impl Index<NodeId> for Tree {
+    type Output = TreeNode;
+
+    fn index(&self, id: NodeId) -> &TreeNode {
+        self.get(id).expect("node was removed from the tree")
+    }
+}
+
+impl IndexMut<NodeId> for Tree {
+    fn index_mut(&mut self, id: NodeId) -> &mut TreeNode {
+        self.get_mut(id).expect("node was removed from the tree")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_removed_ids_stay_invalid() {
+        let mut tree = Tree::new(TreeNode::new_virtual(PathBuf::from("/r"), 0, true));
+        let root = tree.root();
+        tree.add_virtual_path(root, Path::new("/r/b/file"));
+        tree.add_virtual_path(root, Path::new("/r/a"));
+        tree.sort_virtual(root);
+        let names: Vec<&str> = tree[root]
+            .children
+            .iter()
+            .map(|&child| tree[child].name.as_str())
+            .collect();
+        assert_eq!(names, ["b", "a"]);
+        assert_eq!(tree.slots.len(), 4);
+
+        let old = tree[root].children[0];
+        tree.clear_children(root);
+        assert_eq!(tree.free.len(), 3);
+        assert!(tree.get(old).is_none());
+
+        // A new node takes a freed slot, the old id doesn't reach it
+        let new = tree.add_child(root, TreeNode::new_virtual(PathBuf::from("/r/c"), 1, false));
+        assert_eq!(tree.slots.len(), 4);
+        assert!(tree.get(old).is_none());
+        assert_eq!(tree[new].name, "c");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_symlink_to_another_filesystem_is_no_mountpoint() {
+        use std::os::unix::fs::MetadataExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let (Ok(proc), Ok(own)) = (std::fs::metadata("/proc"), std::fs::metadata(dir.path()))
+        else {
+            return;
+        };
+        if proc.dev() == own.dev() {
+            return; // No other filesystem to link to
+        }
+        std::os::unix::fs::symlink("/proc", dir.path().join("proc")).unwrap();
+
+        let mut tree = Tree::new(TreeNode::new(dir.path().to_path_buf(), 0).unwrap());
+        let root = tree.root();
+        tree.load_children(root, true, &FilterSet::new(true), true)
+            .unwrap();
+        let link = tree[root].children[0];
+        assert_eq!(tree[link].name, "proc");
+        assert!(tree[link].is_dir);
+        assert!(!tree[link].is_mountpoint);
+    }
 }
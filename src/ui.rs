@@ -2,20 +2,60 @@
 #![allow(clippy::too_many_arguments)]
 
 use crate::bookmarks::Bookmarks;
+use crate::clipboard::HistoryPanel;
+use crate::compare::Difference;
+use crate::completion::{Completion, POPUP_HEIGHT};
 use crate::config::Config;
+use crate::copy_path::CopyPathMenu;
 use crate::dir_size::DirSizeCache;
-use crate::file_icons;
-use crate::file_viewer::FileViewer;
+use crate::drives::Drives;
+use crate::file_ops::{FileOps, TransferMode};
+use crate::file_viewer::{match_ranges, FileViewer, MarkKey, Placement};
+use crate::fuzzy_jump::FuzzyJump;
+use crate::git::{GitView, RepoStatus};
+use crate::goto::GotoPrompt;
+use crate::i18n::{tr, tr_with};
+use crate::key_sequences::SequenceHints;
+use crate::layout::LayoutPicker;
+use crate::marks::MarksPanel;
 use crate::navigation::Navigation;
+use crate::notifications::{Level, Notifications};
+use crate::oplog;
+use crate::patch::LineKind;
+use crate::picker::PickMode;
+use crate::plugins::{CommandPrompt, Plugins};
+use crate::preview_cache;
+use crate::projects::Projects;
+use crate::quick_filter::{FilterPicker, QuickFilter};
 use crate::search::Search;
-use crate::tree_node::TreeNodeRef;
+use crate::tasks::{TaskManager, MAX_CANCEL_KEYS};
+use crate::tree_export::{Destination, ExportMenu};
+use crate::widget;
+use once_cell::sync::Lazy;
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    widgets::{
+        Block, BorderType, Borders, Clear, HighlightSpacing, List, ListItem, ListState, Paragraph,
+    },
     Frame,
 };
+use std::borrow::Cow;
+use unicode_width::UnicodeWidthStr;
+
+/// Percentage points a divider moves per Ctrl+arrow press
+const SPLIT_STEP: u16 = 5;
+
+/// Pane that receives the navigation keys (j/k, Enter, ...)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Focus {
+    #[default]
+    Tree,
+    Viewer,
+    Results,
+    Bookmarks,
+}
 
 /// UI rendering module
 pub struct UI {
@@ -30,10 +70,38 @@ pub struct UI {
     pub terminal_height: u16,
     pub split_position: u16,
     pub tree_scroll_offset: usize,
+    pub mouse_position: Option<(u16, u16)>, // Column and row of the pointer, for the hover row
+    tree_placement: Option<Placement>,      // Where to put the selection on the next frame
+    tree_pinned_offset: Option<usize>,      // Scroll offset the placement left the tree at
     // Bottom panel (search/bookmarks) properties
     pub bottom_panel_split_position: u16, // Percentage from top (default 70)
     pub bottom_panel_top: u16,
     pub bottom_panel_height: u16,
+    // Toasts and message log
+    pub notifications: Notifications,
+    // Branch and uncommitted changes of the repository around the root
+    pub repo: RepoStatus,
+    // Projects panel (`&`)
+    pub projects: Projects,
+    // Clipboard history panel (`"`)
+    pub clipboard_history: HistoryPanel,
+    // Marks panel (`M`)
+    pub marks_panel: MarksPanel,
+    // Saved filters picker (`|`)
+    pub filter_picker: FilterPicker,
+    // Named layouts picker (`L`)
+    pub layout_picker: LayoutPicker,
+    // Export of the visible tree (`$`)
+    pub export_menu: ExportMenu,
+    // Fuzzy jump overlay over the loaded tree (`F`)
+    pub fuzzy_jump: FuzzyJump,
+    // The live filter prompt takes the typed keys (`\`); the filter is kept on the tree
+    pub live_filter_open: bool,
+    // What can follow the first key of a two-key sequence
+    pub sequence_hints: Option<SequenceHints>,
+    // Focused pane, highlighted with focus_border_color when more than one is visible
+    pub focus: Focus,
+    show_focus: bool,
 }
 
 impl Default for UI {
@@ -56,17 +124,122 @@ impl UI {
             terminal_height: 0,
             split_position: 50,
             tree_scroll_offset: 0,
+            mouse_position: None,
+            tree_placement: None,
+            tree_pinned_offset: None,
             bottom_panel_split_position: 70,
             bottom_panel_top: 0,
             bottom_panel_height: 0,
+            notifications: Notifications::new(),
+            repo: RepoStatus::default(),
+            projects: Projects::new(),
+            clipboard_history: HistoryPanel::new(),
+            marks_panel: MarksPanel::new(),
+            filter_picker: FilterPicker::default(),
+            layout_picker: LayoutPicker::default(),
+            export_menu: ExportMenu::new(),
+            fuzzy_jump: FuzzyJump::default(),
+            live_filter_open: false,
+            sequence_hints: None,
+            focus: Focus::Tree,
+            show_focus: false,
+        }
+    }
+
+    /// Move the focus to the next (`true`) or previous visible pane: tree, viewer, results
+    pub fn cycle_focus(&mut self, forward: bool, show_files: bool, show_results: bool) {
+        let panes: Vec<Focus> = [
+            (Focus::Tree, true),
+            (Focus::Viewer, show_files),
+            (Focus::Results, show_results),
+        ]
+        .into_iter()
+        .filter(|&(_, visible)| visible)
+        .map(|(pane, _)| pane)
+        .collect();
+        let current = panes
+            .iter()
+            .position(|&pane| pane == self.focus)
+            .unwrap_or(0);
+        let next = if forward {
+            (current + 1) % panes.len()
+        } else {
+            (current + panes.len() - 1) % panes.len()
+        };
+        self.focus = panes[next];
+    }
+
+    /// Keep the focus on a visible pane: the bookmarks panel takes it while open,
+    /// and a pane that was closed hands it back to the tree
+    pub fn sync_focus(&mut self, show_files: bool, show_results: bool, bookmarks_open: bool) {
+        self.focus = match self.focus {
+            _ if bookmarks_open => Focus::Bookmarks,
+            Focus::Viewer if show_files => Focus::Viewer,
+            Focus::Results if show_results => Focus::Results,
+            _ => Focus::Tree,
+        };
+    }
+
+    /// Border color of a pane: focus_border_color while it has the focus
+    fn pane_border_color(&self, pane: Focus, color: Color, config: &Config) -> Color {
+        if self.show_focus && self.focus == pane {
+            Config::parse_color(Config::get_color(
+                &config.appearance.colors.focus_border_color,
+            ))
+        } else {
+            color
+        }
+    }
+
+    /// Border type of a pane: thick while it has the focus, so it shows without colors
+    fn pane_border_type(&self, pane: Focus) -> BorderType {
+        if self.show_focus && self.focus == pane {
+            BorderType::Thick
+        } else {
+            BorderType::Plain
         }
     }
 
+    /// Grow (`true`) or shrink the tree next to the file viewer by SPLIT_STEP
+    pub fn resize_split(&mut self, grow: bool) {
+        let position = if grow {
+            self.split_position + SPLIT_STEP
+        } else {
+            self.split_position.saturating_sub(SPLIT_STEP)
+        };
+        self.adjust_split(position);
+    }
+
+    /// Index in the flat list of the tree row at a screen position (past the end too),
+    /// None outside the tree's rows
+    pub fn tree_row_at(&self, column: u16, row: u16) -> Option<usize> {
+        let inside = (self.tree_area_start..self.tree_area_end).contains(&column)
+            && row > self.tree_area_top
+            && row + 1 < self.tree_area_top + self.tree_area_height;
+        inside.then(|| (row - self.tree_area_top - 1) as usize + self.tree_scroll_offset)
+    }
+
+    /// Grow (`true`) or shrink the bottom panel (results, bookmarks) by SPLIT_STEP
+    pub fn resize_bottom_split(&mut self, grow: bool) {
+        let position = if grow {
+            self.bottom_panel_split_position.saturating_sub(SPLIT_STEP)
+        } else {
+            self.bottom_panel_split_position + SPLIT_STEP
+        };
+        self.adjust_bottom_split(position);
+    }
+
     /// Adjust horizontal split position (20-80% range)
     pub fn adjust_split(&mut self, position: u16) {
         self.split_position = position.clamp(20, 80);
     }
 
+    /// Show the selection at the top, in the middle or at the bottom of the tree from
+    /// the next frame on, until it moves off the screen
+    pub fn place_tree_selection(&mut self, placement: Placement) {
+        self.tree_placement = Some(placement);
+    }
+
     /// Adjust vertical split position for bottom panel (30-90% range)
     pub fn adjust_bottom_split(&mut self, position: u16) {
         self.bottom_panel_split_position = position.clamp(30, 90);
@@ -80,57 +253,80 @@ impl UI {
         file_viewer: &FileViewer,
         search: &Search,
         bookmarks: &Bookmarks,
+        drives: &Drives,
+        copy_menu: &CopyPathMenu,
+        goto: &GotoPrompt,
+        command_prompt: &CommandPrompt,
         config: &Config,
         show_files: bool,
         show_help: bool,
         fullscreen_viewer: bool,
         show_sizes: bool,
         dir_size_cache: &DirSizeCache,
+        tasks: &TaskManager,
+        file_ops: &FileOps,
+        pick_mode: Option<PickMode>,
     ) {
         self.terminal_width = frame.area().width;
         self.terminal_height = frame.area().height;
         let main_area = frame.area();
+        self.sync_focus(show_files, search.show_results, bookmarks.is_selecting);
+        self.show_focus = show_files || search.show_results || bookmarks.is_selecting;
 
         // If in fullscreen viewer mode, render only the file viewer
         if fullscreen_viewer {
             self.render_file_viewer(frame, main_area, file_viewer, false, config);
+            self.render_sequence_hints(frame, main_area, config);
             return;
         }
 
-        // Reserve space for search bar if in search mode
-        let (content_area, search_bar_area) = if search.mode {
-            let chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([Constraint::Min(3), Constraint::Length(3)])
-                .split(main_area);
-            (chunks[0], Some(chunks[1]))
-        } else {
-            (main_area, None)
-        };
-
-        // If showing search results or bookmarks, split vertically with dynamic position
-        let (tree_area, bottom_panel_area) =
-            if search.show_results || bookmarks.is_selecting || bookmarks.is_creating {
+        // Reserve space for search bar (or go-to-path/command prompt) if in input mode
+        let (content_area, search_bar_area) =
+            if search.mode || goto.is_open || command_prompt.is_open || self.live_filter_open {
                 let chunks = Layout::default()
                     .direction(Direction::Vertical)
-                    .constraints([
-                        Constraint::Percentage(self.bottom_panel_split_position),
-                        Constraint::Percentage(100 - self.bottom_panel_split_position),
-                    ])
-                    .split(content_area);
-
-                // Save bottom panel coordinates for mouse handling
-                self.bottom_panel_top = chunks[1].y;
-                self.bottom_panel_height = chunks[1].height;
-
+                    .constraints([Constraint::Min(3), Constraint::Length(3)])
+                    .split(main_area);
                 (chunks[0], Some(chunks[1]))
             } else {
-                // Reset bottom panel coordinates when not visible
-                self.bottom_panel_top = 0;
-                self.bottom_panel_height = 0;
-                (content_area, None)
+                (main_area, None)
             };
 
+        // If showing search results, bookmarks or drives, split vertically with dynamic position
+        let (tree_area, bottom_panel_area) = if search.show_results
+            || bookmarks.is_selecting
+            || bookmarks.is_creating
+            || drives.is_selecting
+            || self.projects.is_selecting
+            || file_ops.is_resolving_conflict()
+            || copy_menu.is_open
+            || self.clipboard_history.is_open
+            || self.marks_panel.is_open
+            || self.filter_picker.is_open
+            || self.layout_picker.is_open
+            || self.export_menu.is_open
+            || self.notifications.show_log
+        {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Percentage(self.bottom_panel_split_position),
+                    Constraint::Percentage(100 - self.bottom_panel_split_position),
+                ])
+                .split(content_area);
+
+            // Save bottom panel coordinates for mouse handling
+            self.bottom_panel_top = chunks[1].y;
+            self.bottom_panel_height = chunks[1].height;
+
+            (chunks[0], Some(chunks[1]))
+        } else {
+            // Reset bottom panel coordinates when not visible
+            self.bottom_panel_top = 0;
+            self.bottom_panel_height = 0;
+            (content_area, None)
+        };
+
         // If file viewer mode enabled, split horizontally
         if show_files {
             let chunks = Layout::default()
@@ -152,6 +348,8 @@ impl UI {
                 show_sizes,
                 show_files,
                 dir_size_cache,
+                file_ops,
+                pick_mode,
             );
             self.render_file_viewer(frame, chunks[1], file_viewer, show_help, config);
         } else {
@@ -165,22 +363,423 @@ impl UI {
                 show_sizes,
                 show_files,
                 dir_size_cache,
+                file_ops,
+                pick_mode,
             );
         }
 
-        // Render bottom panel - bookmarks take priority over search results
+        // Render bottom panel - drives, then bookmarks take priority over search results
         if let Some(area) = bottom_panel_area {
-            if bookmarks.is_selecting || bookmarks.is_creating {
+            if copy_menu.is_open {
+                self.render_copy_menu(frame, area, copy_menu, config);
+            } else if self.clipboard_history.is_open {
+                self.render_clipboard_history(frame, area, config);
+            } else if self.marks_panel.is_open {
+                self.render_marks_panel(frame, area, nav, config);
+            } else if self.filter_picker.is_open {
+                self.render_filter_picker(frame, area, &nav.filters.quick_filter, config);
+            } else if self.layout_picker.is_open {
+                self.render_layout_picker(frame, area, config);
+            } else if self.export_menu.is_open {
+                self.render_export_menu(frame, area, config);
+            } else if file_ops.is_resolving_conflict() {
+                self.render_conflict_prompt(frame, area, file_ops, config);
+            } else if drives.is_selecting {
+                self.render_drives_panel(frame, area, drives, config);
+            } else if self.projects.is_selecting {
+                self.render_projects_panel(frame, area, config);
+            } else if bookmarks.is_selecting || bookmarks.is_creating {
                 self.render_bookmarks_panel(frame, area, bookmarks, config);
+            } else if self.notifications.show_log {
+                self.render_message_log(frame, area, config);
             } else if search.show_results {
-                self.render_search_results(frame, area, search, &nav.root, config);
+                self.render_search_results(frame, area, search, nav, config);
             }
         }
 
         // Render search bar if in input mode
         if let Some(area) = search_bar_area {
-            self.render_search_bar(frame, area, search, config);
+            if goto.is_open {
+                self.render_goto_bar(frame, area, goto, config);
+            } else if command_prompt.is_open {
+                self.render_command_bar(frame, area, command_prompt, config);
+            } else if self.live_filter_open {
+                self.render_live_filter_bar(frame, area, nav, config);
+            } else {
+                self.render_search_bar(frame, area, search, config);
+            }
+        }
+
+        if self.fuzzy_jump.is_open {
+            self.render_fuzzy_jump(frame, content_area, config);
+        }
+
+        // Background task overlay floats above everything else
+        if tasks.is_overlay_visible() {
+            self.render_task_overlay(frame, content_area, tasks, config);
+        }
+
+        self.render_sequence_hints(frame, content_area, config);
+
+        // Toasts stack up from the bottom-right corner
+        self.render_toasts(frame, content_area, config);
+    }
+
+    /// Blank screen after `idle_timeout_minutes` without input: nothing of the tree,
+    /// preview or panels, only how to get back
+    pub fn render_idle_screen(&self, frame: &mut Frame, config: &Config) {
+        let file_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.file_color));
+        let background_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.background_color,
+        ));
+
+        let area = frame.area();
+        let message = "dtree is idle - press any key";
+        let message_area = Rect {
+            x: area.x,
+            y: area.y + area.height / 2,
+            width: area.width,
+            height: 1.min(area.height),
+        };
+        frame.render_widget(
+            Block::default().style(Style::default().bg(background_color)),
+            area,
+        );
+        frame.render_widget(
+            Paragraph::new(message)
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(file_color).bg(background_color)),
+            message_area,
+        );
+    }
+
+    /// Keys that can follow the pressed first key of a sequence, in the bottom-right corner
+    fn render_sequence_hints(&self, frame: &mut Frame, area: Rect, config: &Config) {
+        let Some(hints) = &self.sequence_hints else {
+            return;
+        };
+        let file_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.file_color));
+        let highlight_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.highlight_color));
+        let panel_border_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.panel_border_color,
+        ));
+        let background_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.background_color,
+        ));
+
+        let key_width = hints
+            .entries
+            .iter()
+            .map(|(key, _)| key.width())
+            .max()
+            .unwrap_or(0);
+        let lines: Vec<Line> = hints
+            .entries
+            .iter()
+            .map(|(key, binding)| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:<width$}  ", key, width = key_width),
+                        Style::default().fg(highlight_color),
+                    ),
+                    Span::raw(binding.replace('_', " ")),
+                ])
+            })
+            .collect();
+
+        let title = format!(" {} … (Esc: cancel) ", hints.prefix);
+        let content_width = lines.iter().map(Line::width).max().unwrap_or(0);
+        // Keep clear of the panel borders
+        let width =
+            ((content_width.max(title.width()) + 2) as u16).min(area.width.saturating_sub(2));
+        let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(2));
+        let hints_area = Rect {
+            x: (area.x + area.width).saturating_sub(width + 1),
+            y: (area.y + area.height).saturating_sub(height + 1),
+            width,
+            height,
+        };
+
+        let paragraph = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title)
+                    .border_style(Style::default().fg(panel_border_color)),
+            )
+            .style(Style::default().fg(file_color).bg(background_color));
+
+        frame.render_widget(Clear, hints_area);
+        frame.render_widget(paragraph, hints_area);
+    }
+
+    fn level_color(level: Level, config: &Config) -> Color {
+        let color = match level {
+            Level::Info => &config.appearance.colors.selected_color,
+            Level::Warn => &config.appearance.colors.highlight_color,
+            Level::Error => &config.appearance.colors.error_color,
+        };
+        Config::parse_color(Config::get_color(color))
+    }
+
+    fn render_toasts(&self, frame: &mut Frame, area: Rect, config: &Config) {
+        let background_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.background_color,
+        ));
+
+        if area.width < 4 {
+            return;
+        }
+
+        // Keep clear of the panel borders
+        let mut bottom = (area.y + area.height).saturating_sub(1);
+        for toast in self.notifications.toasts().iter().rev() {
+            if bottom < area.y + 3 {
+                break;
+            }
+            let color = Self::level_color(toast.level, config);
+            let width = (toast.message.width() as u16 + 4)
+                .clamp(20, 60)
+                .min(area.width.saturating_sub(2));
+            let toast_area = Rect {
+                x: area.x + area.width - width - 1,
+                y: bottom - 3,
+                width,
+                height: 3,
+            };
+            bottom -= 3;
+
+            let paragraph = Paragraph::new(toast.message.as_str())
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(format!(" {} ", toast.level.label()))
+                        .border_style(Style::default().fg(color)),
+                )
+                .style(Style::default().fg(color).bg(background_color));
+
+            frame.render_widget(Clear, toast_area);
+            frame.render_widget(paragraph, toast_area);
+        }
+    }
+
+    fn render_message_log(&self, frame: &mut Frame, area: Rect, config: &Config) {
+        let file_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.file_color));
+        let panel_border_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.panel_border_color,
+        ));
+
+        // Newest first; notifications are logged as "<level>: <message>"
+        let entries = oplog::entries();
+        let lines: Vec<Line> = entries
+            .iter()
+            .rev()
+            .skip(self.notifications.log_scroll)
+            .map(|entry| {
+                let level = [Level::Error, Level::Warn, Level::Info]
+                    .into_iter()
+                    .find(|level| entry.message.starts_with(&format!("{}: ", level.label())));
+                let style = match level {
+                    Some(level) => Style::default().fg(Self::level_color(level, config)),
+                    None => Style::default().fg(file_color),
+                };
+                Line::styled(entry.format(), style)
+            })
+            .collect();
+
+        let title = format!(
+            " Log ({}) | ↑↓/jk: scroll | y: copy all | Esc: close ",
+            entries.len()
+        );
+        let paragraph = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(panel_border_color)),
+        );
+
+        frame.render_widget(paragraph, area);
+    }
+
+    fn render_task_overlay(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        tasks: &TaskManager,
+        config: &Config,
+    ) {
+        let file_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.file_color));
+        let panel_border_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.panel_border_color,
+        ));
+        let background_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.background_color,
+        ));
+
+        let lines: Vec<Line> = tasks
+            .tasks()
+            .iter()
+            .take(MAX_CANCEL_KEYS)
+            .enumerate()
+            .map(|(idx, task)| {
+                let progress = match task.ratio() {
+                    Some(ratio) => format!(" {:>3.0}%", ratio * 100.0),
+                    None => String::new(),
+                };
+                Line::from(format!(
+                    "{} {} - {}{} ({}s)",
+                    idx + 1,
+                    task.description,
+                    task.detail,
+                    progress,
+                    task.elapsed().as_secs()
+                ))
+            })
+            .collect();
+
+        // The key hiding the overlay as configured (none when toggle_tasks is unbound)
+        let title = match config.keybindings.toggle_tasks.first() {
+            Some(key) => format!(" Tasks (1-9: cancel | {}: hide) ", key),
+            None => " Tasks (1-9: cancel) ".to_string(),
+        };
+
+        // Top-right corner of the content area
+        let width = 50.min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let overlay_area = Rect {
+            x: area.x + area.width - width,
+            y: area.y,
+            width,
+            height,
+        };
+
+        let paragraph = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title)
+                    .border_style(Style::default().fg(panel_border_color)),
+            )
+            .style(Style::default().fg(file_color).bg(background_color));
+
+        frame.render_widget(Clear, overlay_area);
+        frame.render_widget(paragraph, overlay_area);
+    }
+
+    /// Fuzzy jump overlay: query and ranked matches on the left, preview on the right
+    fn render_fuzzy_jump(&self, frame: &mut Frame, area: Rect, config: &Config) {
+        let dir_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.directory_color));
+        let file_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.file_color));
+        let selected_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.selected_color));
+        let highlight_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.highlight_color));
+        let panel_border_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.panel_border_color,
+        ));
+        let background_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.background_color,
+        ));
+        let jump = &self.fuzzy_jump;
+
+        // Centered, leaving a tenth of the content area around it
+        let width = area.width - area.width / 5;
+        let height = area.height - area.height / 5;
+        let overlay_area = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        frame.render_widget(Clear, overlay_area);
+        frame.render_widget(
+            Block::default().style(Style::default().bg(background_color)),
+            overlay_area,
+        );
+
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(overlay_area);
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(3)])
+            .split(columns[0]);
+
+        let input = Paragraph::new(format!("> {}█", jump.input))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(
+                        " Jump: {}/{} | ↑↓: move | Enter: reveal | Esc: cancel ",
+                        jump.match_count(),
+                        jump.candidate_count()
+                    ))
+                    .border_style(Style::default().fg(panel_border_color)),
+            )
+            .style(
+                Style::default()
+                    .fg(selected_color)
+                    .add_modifier(Modifier::BOLD),
+            );
+        frame.render_widget(input, rows[0]);
+
+        let items: Vec<ListItem> = jump
+            .items()
+            .map(|item| {
+                let color = if item.is_dir { dir_color } else { file_color };
+                ListItem::new(Line::from(highlight_chars(
+                    item.relative,
+                    item.indices,
+                    Style::default().fg(color),
+                    Style::default()
+                        .fg(highlight_color)
+                        .add_modifier(Modifier::BOLD),
+                )))
+            })
+            .collect();
+
+        let mut state = ListState::default();
+        if jump.match_count() > 0 {
+            state.select(Some(jump.selected));
         }
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(panel_border_color)),
+            )
+            .highlight_style(cursor_highlight_style(config))
+            .highlight_symbol(">> ");
+        frame.render_stateful_widget(list, rows[1], &mut state);
+
+        let title = jump
+            .get_selected()
+            .and_then(|path| path.file_name())
+            .map(|name| format!(" {} ", name.to_string_lossy()))
+            .unwrap_or_default();
+        let preview = Paragraph::new(
+            jump.preview()
+                .iter()
+                .map(|line| Line::from(line.as_str()))
+                .collect::<Vec<_>>(),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(panel_border_color)),
+        )
+        .style(Style::default().fg(file_color));
+        frame.render_widget(preview, columns[1]);
     }
 
     fn render_tree(
@@ -192,89 +791,128 @@ impl UI {
         show_sizes: bool,
         show_files: bool,
         dir_size_cache: &DirSizeCache,
+        file_ops: &FileOps,
+        pick_mode: Option<PickMode>,
     ) {
         self.tree_area_top = area.y;
         self.tree_area_height = area.height;
 
+        // The row under the pointer is underlined; the selection stays where it is
+        let hovered = self
+            .mouse_position
+            .and_then(|(column, row)| self.tree_row_at(column, row))
+            .filter(|&index| index != nav.selected);
+
         let items: Vec<ListItem> = nav
             .flat_list
             .iter()
-            .map(|node| {
-                let node_borrowed = node.borrow();
-                let indent = "  ".repeat(node_borrowed.depth);
+            .enumerate()
+            .map(|(index, &id)| {
+                let node = &nav.tree[id];
+                let indent = "  ".repeat(node.depth);
+
+                // Mark indicator for bulk actions (yank/cut)
+                let mark = if nav.is_marked(&node.path) { "* " } else { "" };
 
                 // Icon with error indicator or file type icon
-                let icon = if node_borrowed.has_error {
-                    // Error indicator always shows, regardless of icon settings
-                    "⚠ ".to_string()
-                } else if config.appearance.show_icons {
-                    // Use file type icons from nerd-fonts
-                    let file_icon =
-                        file_icons::get_icon(&node_borrowed.path, node_borrowed.is_dir, true);
-                    // Fallback to arrows if icon is empty or whitespace-only
-                    if file_icon.trim().is_empty() {
-                        if node_borrowed.is_dir {
-                            if node_borrowed.is_expanded {
-                                "▼ ".to_string()
-                            } else {
-                                "▶ ".to_string()
-                            }
-                        } else {
-                            "  ".to_string()
-                        }
-                    } else {
-                        format!("{}  ", file_icon) // Two spaces after icon for better readability
-                    }
+                let icon = widget::node_icon(node, config.appearance.show_icons);
+
+                // Mountpoint indicator (directory on a different filesystem than its parent)
+                let mount_marker = if node.is_mountpoint { " [mnt]" } else { "" };
+
+                // Setuid/setgid and world-writable entries, when asked for
+                let permission_warning = if config.appearance.permission_warnings {
+                    node.permission_warning()
                 } else {
-                    // Default arrows/markers (original behavior)
-                    if node_borrowed.is_dir {
-                        if node_borrowed.is_expanded {
-                            "▼ ".to_string()
-                        } else {
-                            "▶ ".to_string()
-                        }
-                    } else {
-                        "  ".to_string()
-                    }
+                    None
                 };
+                let permission_marker = permission_warning
+                    .map(|warning| format!(" [{}]", warning))
+                    .unwrap_or_default();
+
+                // How the entry differs from the other side of a comparison
+                let difference = nav
+                    .comparison
+                    .as_ref()
+                    .and_then(|comparison| comparison.difference(&node.path));
+                let difference_marker = difference
+                    .map(|difference| format!(" [{}]", difference.label()))
+                    .unwrap_or_default();
 
                 // Build text with optional size column (after directory/file name)
                 let text = if show_sizes {
-                    let size_str = if node_borrowed.is_dir {
+                    let size_str = if node.is_dir {
                         // Directory size (from cache) - always show if show_sizes is enabled
-                        if let Some((size, is_partial)) = dir_size_cache.get(&node_borrowed.path) {
+                        if let Some((size, is_partial)) = dir_size_cache.get(&node.path) {
                             format!(" [{:>7}]", DirSizeCache::format_size(size, is_partial))
-                        } else if dir_size_cache.is_calculating(&node_borrowed.path) {
+                        } else if dir_size_cache.is_calculating(&node.path) {
                             " [ calc.]".to_string()
                         } else {
                             "".to_string()
                         }
                     } else if show_files {
                         // File size (from metadata) - only show if in file viewer mode (s)
-                        if let Ok(metadata) = std::fs::metadata(&node_borrowed.path) {
-                            format!(" [{:>7}]", DirSizeCache::format_size(metadata.len(), false))
+                        if let Ok(metadata) = node.vfs.metadata(&node.path) {
+                            format!(" [{:>7}]", DirSizeCache::format_size(metadata.len, false))
                         } else {
                             "".to_string()
                         }
                     } else {
                         "".to_string()
                     };
-                    format!("{}{}{}{}", indent, icon, node_borrowed.name, size_str)
+                    format!(
+                        "{}{}{}{}{}{}{}{}",
+                        indent,
+                        mark,
+                        icon,
+                        node.name,
+                        mount_marker,
+                        permission_marker,
+                        difference_marker,
+                        size_str
+                    )
                 } else {
-                    format!("{}{}{}", indent, icon, node_borrowed.name)
+                    format!(
+                        "{}{}{}{}{}{}{}",
+                        indent,
+                        mark,
+                        icon,
+                        node.name,
+                        mount_marker,
+                        permission_marker,
+                        difference_marker
+                    )
                 };
 
                 // Color coding: errors in configured color, directories and files use theme colors
-                let style = if node_borrowed.has_error {
+                let style = if node.has_error {
                     let error_color = Config::parse_color(Config::get_color(
                         &config.appearance.colors.error_color,
                     ));
                     Style::default().fg(error_color)
-                } else if node_borrowed.is_dir {
+                } else if let Some(difference) = difference {
+                    // Left-only entries look removed, right-only ones added
+                    let color = match difference {
+                        Difference::OnlyLeft => &config.appearance.colors.error_color,
+                        Difference::OnlyRight => &config.appearance.colors.executable_color,
+                        _ => &config.appearance.colors.highlight_color,
+                    };
+                    Style::default().fg(Config::parse_color(Config::get_color(color)))
+                } else if permission_warning.is_some() {
+                    let warning_color = Config::parse_color(Config::get_color(
+                        &config.appearance.colors.permission_warning_color,
+                    ));
+                    Style::default().fg(warning_color)
+                } else if node.is_dir {
                     let dir_color = Config::parse_color(Config::get_color(
                         &config.appearance.colors.directory_color,
                     ));
                     Style::default().fg(dir_color)
+                } else if node.is_executable() {
+                    let executable_color = Config::parse_color(Config::get_color(
+                        &config.appearance.colors.executable_color,
+                    ));
+                    Style::default().fg(executable_color)
                 } else {
                     let file_color = Config::parse_color(Config::get_color(
                         &config.appearance.colors.file_color,
@@ -282,6 +920,24 @@ impl UI {
                     Style::default().fg(file_color)
                 };
 
+                // Marked entries are bold, cut entries are dimmed until pasted,
+                // ignored entries are dimmed with `gitignore = "dim"`
+                let style = if nav.is_marked(&node.path) {
+                    style.add_modifier(Modifier::BOLD)
+                } else {
+                    style
+                };
+                let style = if file_ops.is_cut(&node.path) || nav.is_dimmed(&node.path) {
+                    style.add_modifier(Modifier::DIM)
+                } else {
+                    style
+                };
+                let style = if hovered == Some(index) {
+                    style.add_modifier(Modifier::UNDERLINED)
+                } else {
+                    style
+                };
+
                 ListItem::new(text).style(style)
             })
             .collect();
@@ -298,7 +954,7 @@ impl UI {
         // Calculate max possible offset (when end of list is visible)
         let max_offset = total_items.saturating_sub(visible_height);
 
-        let final_offset = if max_offset == 0 {
+        let default_offset = if max_offset == 0 {
             // List fits entirely in window - no scrolling needed
             0
         } else if nav.selected < lines_from_top {
@@ -318,15 +974,104 @@ impl UI {
             offset_from_top.max(offset_from_bottom).min(max_offset)
         };
 
+        let final_offset = if let Some(placement) = self.tree_placement.take() {
+            let rows_above = match placement {
+                Placement::Top => 0,
+                Placement::Center => visible_height / 2,
+                Placement::Bottom => visible_height.saturating_sub(1),
+            };
+            let offset = nav.selected.saturating_sub(rows_above).min(max_offset);
+            self.tree_pinned_offset = Some(offset);
+            offset
+        } else {
+            match self.tree_pinned_offset {
+                Some(offset)
+                    if offset <= max_offset
+                        && (offset..offset + visible_height).contains(&nav.selected) =>
+                {
+                    offset
+                }
+                _ => {
+                    self.tree_pinned_offset = None;
+                    default_offset
+                }
+            }
+        };
+
         *state.offset_mut() = final_offset;
         self.tree_scroll_offset = final_offset;
 
-        let title = if show_sizes {
-            " Directory Tree (↑↓/jk: navigate | Enter: go in | q: cd & exit | Esc: exit | z: hide sizes | /: search | i: help) "
+        let title = if let Some(mode) = pick_mode {
+            mode.title()
+        } else if show_sizes {
+            tr(" Directory Tree (↑↓/jk: navigate | Enter: go in | q: cd & exit | Esc: exit | z: hide sizes | /: search | i: help) ")
         } else {
-            " Directory Tree (↑↓/jk: navigate | Enter: go in | q: cd & exit | Esc: exit | z: show sizes | /: search | i: help) "
+            tr(" Directory Tree (↑↓/jk: navigate | Enter: go in | q: cd & exit | Esc: exit | z: show sizes | /: search | i: help) ")
         };
 
+        // Show the repository, filter, marks and clipboard state in front of the key hints
+        let mut status = String::new();
+        if let Some(repo) = self.repo.label() {
+            status.push_str(&format!(" [git: {}]", repo));
+        }
+        if let Some(listing) = &nav.flat_listing {
+            status.push_str(&format!(
+                " [{}{} files by {}]",
+                listing.len(),
+                if listing.truncated { "+" } else { "" },
+                listing.sort.label()
+            ));
+        }
+        if let Some(comparison) = &nav.comparison {
+            let right = comparison
+                .right
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| comparison.right.display().to_string());
+            status.push_str(&format!(
+                " [vs {}: {} left only, {} right only, {}{} changed]",
+                right,
+                comparison.only_left,
+                comparison.only_right,
+                comparison.changed,
+                if comparison.truncated { "+" } else { "" }
+            ));
+        }
+        if nav.filters.quick_filter != QuickFilter::All {
+            status.push_str(&tr_with(
+                " [filter: {filter}]",
+                &[("filter", &nav.filters.quick_filter.label())],
+            ));
+        }
+        if !nav.live_filter.is_empty() && !self.live_filter_open {
+            status.push_str(&tr_with(
+                " [live: {filter} | Esc: clear]",
+                &[("filter", &nav.live_filter)],
+            ));
+        }
+        if !nav.marked.is_empty() {
+            let outside = nav.marks_outside_root();
+            if outside > 0 {
+                status.push_str(&tr_with(
+                    " [{count} marked, {outside} outside the root]",
+                    &[("count", &nav.marked.len()), ("outside", &outside)],
+                ));
+            } else {
+                status.push_str(&tr_with(
+                    " [{count} marked]",
+                    &[("count", &nav.marked.len())],
+                ));
+            }
+        }
+        if let Some(clipboard) = &file_ops.clipboard {
+            let badge = match clipboard.mode {
+                TransferMode::Copy => " [{count} yanked | p: paste]",
+                TransferMode::Move => " [{count} cut | p: paste]",
+            };
+            status.push_str(&tr_with(badge, &[("count", &clipboard.paths.len())]));
+        }
+        let title = format!("{}{}", status, title);
+
         // Check tree cursor color settings - "dim" means no color/background, just dimming
         let tree_cursor_color_str = Config::get_color(&config.appearance.colors.tree_cursor_color);
         let tree_cursor_bg_color_str =
@@ -358,7 +1103,12 @@ impl UI {
                 Block::default()
                     .borders(Borders::ALL)
                     .title(title)
-                    .border_style(Style::default().fg(main_border_color))
+                    .border_style(Style::default().fg(self.pane_border_color(
+                        Focus::Tree,
+                        main_border_color,
+                        config,
+                    )))
+                    .border_type(self.pane_border_type(Focus::Tree))
                     .style(Style::default().bg(background_color)),
             )
             .highlight_style(highlight_style)
@@ -395,32 +1145,180 @@ impl UI {
         frame.render_widget(paragraph, area);
     }
 
+    fn render_goto_bar(&self, frame: &mut Frame, area: Rect, goto: &GotoPrompt, config: &Config) {
+        let selected_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.selected_color));
+        let panel_border_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.panel_border_color,
+        ));
+
+        let title = match &goto.error {
+            Some(error) => tr_with(" Go to path - {error} ", &[("error", error)]),
+            None => {
+                tr(" Go to path (Tab/Shift+Tab: complete | Enter: go | Esc: cancel) ").to_string()
+            }
+        };
+
+        let paragraph = Paragraph::new(format!("Path: {}█", goto.input))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title)
+                    .border_style(Style::default().fg(panel_border_color)),
+            )
+            .style(Style::default().fg(selected_color));
+
+        frame.render_widget(paragraph, area);
+        self.render_completion_popup(frame, area, &goto.completion, config);
+    }
+
+    fn render_live_filter_bar(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        nav: &Navigation,
+        config: &Config,
+    ) {
+        let selected_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.selected_color));
+        let panel_border_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.panel_border_color,
+        ));
+
+        // The root is always listed
+        let title = tr_with(
+            " Live filter: {count} shown | ↑↓: move | Enter: keep | Esc: clear ",
+            &[("count", &nav.flat_list.len().saturating_sub(1))],
+        );
+        let paragraph = Paragraph::new(format!("Filter: {}█", nav.live_filter))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title)
+                    .border_style(Style::default().fg(panel_border_color)),
+            )
+            .style(Style::default().fg(selected_color));
+
+        frame.render_widget(paragraph, area);
+    }
+
+    fn render_command_bar(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        prompt: &CommandPrompt,
+        config: &Config,
+    ) {
+        let selected_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.selected_color));
+        let panel_border_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.panel_border_color,
+        ));
+
+        let title = match &prompt.error {
+            Some(error) => tr_with(" Plugin command - {error} ", &[("error", error)]),
+            None => tr(" Plugin command (Tab/Shift+Tab: complete | Enter: run | Esc: cancel) ")
+                .to_string(),
+        };
+
+        let paragraph = Paragraph::new(format!("@{}█", prompt.input))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title)
+                    .border_style(Style::default().fg(panel_border_color)),
+            )
+            .style(Style::default().fg(selected_color));
+
+        frame.render_widget(paragraph, area);
+        self.render_completion_popup(frame, area, &prompt.completion, config);
+    }
+
+    /// Candidate list floating just above a prompt's input bar
+    fn render_completion_popup(
+        &self,
+        frame: &mut Frame,
+        anchor: Rect,
+        completion: &Completion,
+        config: &Config,
+    ) {
+        if !completion.is_active() || anchor.y == 0 {
+            return;
+        }
+
+        let file_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.file_color));
+        let panel_border_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.panel_border_color,
+        ));
+        let background_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.background_color,
+        ));
+
+        let candidates = completion.candidates();
+        let title = format!(" {} matches (Tab: next) ", candidates.len());
+        let widest = candidates
+            .iter()
+            .map(|c| c.label.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(title.chars().count());
+        let width = (widest as u16 + 5).min(anchor.width);
+        let height = (candidates.len().min(POPUP_HEIGHT) as u16 + 2).min(anchor.y);
+        let popup_area = Rect {
+            x: anchor.x,
+            y: anchor.y - height,
+            width,
+            height,
+        };
+
+        let items: Vec<ListItem> = candidates
+            .iter()
+            .map(|c| ListItem::new(c.label.as_str()))
+            .collect();
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title)
+                    .border_style(Style::default().fg(panel_border_color)),
+            )
+            .style(Style::default().fg(file_color).bg(background_color))
+            .highlight_style(cursor_highlight_style(config))
+            .highlight_symbol(">> ")
+            .highlight_spacing(HighlightSpacing::Always);
+
+        let mut state = ListState::default();
+        state.select(completion.selected());
+
+        frame.render_widget(Clear, popup_area);
+        frame.render_stateful_widget(list, popup_area, &mut state);
+    }
+
     fn render_search_results(
         &self,
         frame: &mut Frame,
         area: Rect,
         search: &Search,
-        root: &TreeNodeRef,
+        nav: &Navigation,
         config: &Config,
     ) {
-        let root_path = root.borrow().path.clone();
+        let root_path = nav.root().path.clone();
         let root_parent = root_path.parent().unwrap_or(&root_path);
 
         let file_color =
             Config::parse_color(Config::get_color(&config.appearance.colors.file_color));
         let highlight_color =
             Config::parse_color(Config::get_color(&config.appearance.colors.highlight_color));
-        let panel_border_color = Config::parse_color(Config::get_color(
-            &config.appearance.colors.panel_border_color,
-        ));
 
         let dir_color =
             Config::parse_color(Config::get_color(&config.appearance.colors.directory_color));
 
-        let items: Vec<ListItem> = search
-            .results
+        let visible = search.visible();
+        let mut items: Vec<ListItem> = visible
             .iter()
-            .map(|result| {
+            .map(|&index| {
+                let result = &search.results[index];
                 let display_path = result
                     .path
                     .strip_prefix(root_parent)
@@ -430,39 +1328,43 @@ impl UI {
 
                 let base_color = if result.is_dir { dir_color } else { file_color };
 
-                // In fuzzy mode with match indices, highlight matching characters
-                if search.fuzzy_mode && result.match_indices.is_some() {
-                    let mut spans = Vec::new();
-                    let chars: Vec<char> = display_path.chars().collect();
-                    let indices = result.match_indices.as_ref().unwrap();
-                    let mut last_idx = 0;
-
-                    for &match_idx in indices {
-                        // Add text before the match
-                        if match_idx > last_idx {
-                            let text: String = chars[last_idx..match_idx].iter().collect();
-                            spans.push(Span::styled(text, Style::default().fg(base_color)));
-                        }
-
-                        // Add highlighted character
-                        if match_idx < chars.len() {
-                            let text: String = chars[match_idx..match_idx + 1].iter().collect();
-                            spans.push(Span::styled(
-                                text,
-                                Style::default()
-                                    .fg(highlight_color)
-                                    .add_modifier(Modifier::BOLD),
-                            ));
-                        }
+                // Results marked in the panel or the tree get the tree's mark indicator,
+                // kept in its own span so fuzzy match indices still line up with the path
+                let mut spans = Vec::new();
+                if search.marked.contains(&result.path) || nav.is_marked(&result.path) {
+                    spans.push(Span::styled(
+                        "* ",
+                        Style::default().fg(base_color).add_modifier(Modifier::BOLD),
+                    ));
+                }
 
-                        last_idx = match_idx + 1;
+                // Content match: path:line, then the line with the match underlined
+                if let Some(content) = &result.content {
+                    spans.push(Span::styled(
+                        format!("{}:{}  ", display_path, content.line),
+                        Style::default().fg(base_color),
+                    ));
+                    spans.extend(underline_match(&content.spans, content.range.clone()));
+                    let folded = search.folded_count(index);
+                    if folded > 0 {
+                        spans.push(Span::styled(
+                            format!("  (+{} more)", folded),
+                            Style::default().fg(base_color).add_modifier(Modifier::DIM),
+                        ));
                     }
+                    return ListItem::new(Line::from(spans));
+                }
 
-                    // Add remaining text after last match
-                    if last_idx < chars.len() {
-                        let text: String = chars[last_idx..].iter().collect();
-                        spans.push(Span::styled(text, Style::default().fg(base_color)));
-                    }
+                // In fuzzy mode with match indices, highlight matching characters
+                if let (true, Some(indices)) = (search.fuzzy_mode, result.match_indices.as_ref()) {
+                    spans.extend(highlight_chars(
+                        &display_path,
+                        indices,
+                        Style::default().fg(base_color),
+                        Style::default()
+                            .fg(highlight_color)
+                            .add_modifier(Modifier::BOLD),
+                    ));
 
                     // Add score at the end
                     if let Some(score) = result.score {
@@ -471,59 +1373,109 @@ impl UI {
                             Style::default().fg(base_color),
                         ));
                     }
-
-                    ListItem::new(Line::from(spans))
                 } else {
                     // Normal mode or no match indices - just display path with optional score
-                    let display_text = if search.fuzzy_mode && result.score.is_some() {
-                        format!("{} [{}]", display_path, result.score.unwrap())
-                    } else {
-                        display_path
+                    let display_text = match result.score {
+                        Some(score) if search.fuzzy_mode => format!("{} [{}]", display_path, score),
+                        _ => display_path,
                     };
+                    spans.push(Span::styled(display_text, Style::default().fg(base_color)));
 
-                    ListItem::new(display_text).style(Style::default().fg(base_color))
+                    if let Some(detail) = &result.detail {
+                        spans.push(Span::styled(
+                            format!("  {}", detail),
+                            Style::default().fg(base_color).add_modifier(Modifier::DIM),
+                        ));
+                    }
                 }
+
+                ListItem::new(Line::from(spans))
             })
             .collect();
+        // Matches over max_search_results, counted but not kept
+        if search.more() > 0 {
+            items.push(ListItem::new(Span::styled(
+                tr_with(
+                    "+{count} more (refine your query)",
+                    &[("count", &search.more())],
+                ),
+                Style::default().fg(file_color).add_modifier(Modifier::DIM),
+            )));
+        }
 
         let mut state = ListState::default();
-        state.select(Some(search.selected));
+        state.select(visible.iter().position(|&index| index == search.selected));
 
         // Show search status in title
+        let name = match search.scan {
+            Some(kind) => kind.label(),
+            None if search.is_searching => tr("Search"),
+            None => tr("Search Results"),
+        };
         let title = if search.is_searching {
-            format!(
-                " Search: {} found | Scanning... {} dirs | Esc: cancel ",
-                search.results.len(),
-                search.scanned_count
+            // Where the walk is and how fast it goes, so a slow one doesn't look hung
+            let dir = search.current_dir.as_ref().map_or(String::new(), |dir| {
+                let dir = dir.strip_prefix(root_parent).unwrap_or(dir);
+                keep_end(
+                    &dir.display().to_string(),
+                    (area.width / 3).max(12) as usize,
+                )
+            });
+            let rate = search
+                .rate()
+                .map_or_else(|| "-".to_string(), |rate| rate.to_string());
+            tr_with(
+                " {name}: {count} found | Scanning {dir} | {dirs} dirs, {rate}/s | Esc: cancel ",
+                &[
+                    ("name", &name),
+                    ("count", &search.results.len()),
+                    ("dir", &dir),
+                    ("dirs", &search.scanned_count),
+                    ("rate", &rate),
+                ],
             )
         } else {
-            format!(
-                " Search Results: {} found | Enter: select | Tab: focus | Esc: close ",
-                search.results.len()
+            let marked = if search.marked.is_empty() {
+                String::new()
+            } else {
+                tr_with(
+                    " [{count} marked]",
+                    &[("count", &search.marked_paths().len())],
+                )
+            };
+            tr_with(
+                " {name}: {count} found{marked} | Enter: select | Tab: focus{fold} | Esc: close ",
+                &[
+                    ("name", &name),
+                    ("count", &search.results.len()),
+                    ("marked", &marked),
+                    (
+                        "fold",
+                        &if search.has_content_matches() {
+                            tr(" | h/l: fold")
+                        } else {
+                            ""
+                        },
+                    ),
+                ],
             )
         };
 
-        let border_style = Style::default().fg(panel_border_color);
-
-        // Check cursor color setting - "dim" means no color highlight, just dimming
-        let cursor_color_str = Config::get_color(&config.appearance.colors.cursor_color);
-        let cursor_highlight_style = if cursor_color_str.to_lowercase() == "dim" {
-            Style::default().add_modifier(Modifier::DIM)
-        } else {
-            let cursor_color = Config::parse_color(cursor_color_str);
-            Style::default()
-                .fg(cursor_color)
-                .add_modifier(Modifier::BOLD)
-        };
+        let panel_border_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.panel_border_color,
+        ));
+        let border_style =
+            Style::default().fg(self.pane_border_color(Focus::Results, panel_border_color, config));
 
         let list = List::new(items)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
                     .title(title)
-                    .border_style(border_style),
+                    .border_style(border_style)
+                    .border_type(self.pane_border_type(Focus::Results)),
             )
-            .highlight_style(cursor_highlight_style)
+            .highlight_style(cursor_highlight_style(config))
             .highlight_symbol(">> ");
 
         frame.render_stateful_widget(list, area, &mut state);
@@ -537,38 +1489,157 @@ impl UI {
         max_lines: usize,
         fullscreen: bool,
         config: &Config,
+        plugins: &Plugins,
+    ) -> anyhow::Result<()> {
+        self.load_preview(
+            file_viewer,
+            path,
+            max_lines,
+            fullscreen,
+            config,
+            false,
+            plugins,
+        )
+    }
+
+    /// Read the file shown in the viewer again, keeping the scroll position, marks
+    /// and search; a viewer scrolled to the end stays at the (new) end
+    pub fn reload_file_for_viewer(
+        &self,
+        file_viewer: &mut FileViewer,
+        max_lines: usize,
+        fullscreen: bool,
+        config: &Config,
+        plugins: &Plugins,
+    ) -> anyhow::Result<()> {
+        let path = file_viewer.current_path.clone();
+        if path.as_os_str().is_empty() {
+            return Ok(());
+        }
+        let visible_height = self.viewer_area_height.saturating_sub(4) as usize;
+        let scroll = file_viewer.scroll;
+        let at_end = scroll > 0 && scroll >= file_viewer.max_scroll(visible_height);
+
+        preview_cache::forget(&path);
+        self.load_file_for_viewer(file_viewer, &path, max_lines, fullscreen, config, plugins)?;
+        let max_scroll = file_viewer.max_scroll(visible_height);
+        file_viewer.scroll = if at_end {
+            max_scroll
+        } else {
+            scroll.min(max_scroll)
+        };
+        if !file_viewer.search_query.is_empty() {
+            file_viewer.refresh_search();
+        }
+        Ok(())
+    }
+
+    /// Like `load_file_for_viewer`, but reading and highlighting happen in the
+    /// background once the selection rests (for moving the selection)
+    pub fn request_file_for_viewer(
+        &self,
+        file_viewer: &mut FileViewer,
+        path: &std::path::Path,
+        max_lines: usize,
+        fullscreen: bool,
+        config: &Config,
+        plugins: &Plugins,
+    ) -> anyhow::Result<()> {
+        self.load_preview(
+            file_viewer,
+            path,
+            max_lines,
+            fullscreen,
+            config,
+            true,
+            plugins,
+        )
+    }
+
+    fn load_preview(
+        &self,
+        file_viewer: &mut FileViewer,
+        path: &std::path::Path,
+        max_lines: usize,
+        fullscreen: bool,
+        config: &Config,
+        background: bool,
+        plugins: &Plugins,
     ) -> anyhow::Result<()> {
+        // A git view of the shown entry stays when it's reloaded (resize, wrap, fullscreen)
+        if let Some(view) = file_viewer.git_view {
+            if file_viewer.current_path == path {
+                return self.load_git_view(file_viewer, path, view, fullscreen);
+            }
+        }
+
         let enable_highlighting = config.appearance.enable_syntax_highlighting;
         let theme = &config.appearance.syntax_theme;
 
-        if fullscreen {
-            // For fullscreen, use terminal width (accounting for borders and line numbers)
-            // Line numbers take ~6 chars, borders take 2, leave some margin
-            let max_width = if file_viewer.show_line_numbers {
-                self.terminal_width.saturating_sub(8) as usize
-            } else {
-                self.terminal_width.saturating_sub(2) as usize
-            };
-            file_viewer.load_file_with_width(
+        let max_width = self.viewer_text_width(file_viewer, fullscreen);
+
+        // A plugin previewer replaces the built-in preview (not for skipped paths)
+        let plugin_text = if file_viewer.limits.skips(path) {
+            None
+        } else {
+            plugins.preview(path)
+        };
+
+        if let Some(text) = plugin_text {
+            file_viewer.load_text(path, &text, Some(max_width));
+        } else if background {
+            file_viewer.load_file_in_background(
                 path,
                 Some(max_width),
                 max_lines,
                 enable_highlighting,
                 theme,
-            )
+            );
+            return Ok(());
         } else {
-            // For split view, calculate available width based on split position
-            let max_width = self
-                .terminal_width
-                .saturating_sub(self.split_position * self.terminal_width / 100)
-                .saturating_sub(4) as usize;
             file_viewer.load_file_with_width(
                 path,
                 Some(max_width),
                 max_lines,
                 enable_highlighting,
                 theme,
-            )
+            )?;
+        }
+        // Line numbers (narrowing the text) may have been restored with the file
+        file_viewer.wrap_width = self.viewer_text_width(file_viewer, fullscreen);
+        Ok(())
+    }
+
+    /// Show git log or blame output for `path` in the viewer
+    pub fn load_git_view(
+        &self,
+        file_viewer: &mut FileViewer,
+        path: &std::path::Path,
+        view: GitView,
+        fullscreen: bool,
+    ) -> anyhow::Result<()> {
+        let text = view.output(path)?;
+        let max_width = self.viewer_text_width(file_viewer, fullscreen);
+        file_viewer.load_text(path, &text, Some(max_width));
+        file_viewer.git_view = Some(view);
+        Ok(())
+    }
+
+    /// Columns available for file content in the viewer
+    pub fn viewer_text_width(&self, file_viewer: &FileViewer, fullscreen: bool) -> usize {
+        if fullscreen {
+            // For fullscreen, use terminal width (accounting for borders and line numbers)
+            // Line numbers take ~6 chars, borders take 2, leave some margin
+            if file_viewer.show_line_numbers {
+                self.terminal_width.saturating_sub(8) as usize
+            } else {
+                self.terminal_width.saturating_sub(2) as usize
+            }
+        } else {
+            // For split view, calculate available width based on split position
+            self.terminal_width
+                .saturating_sub(self.split_position * self.terminal_width / 100)
+                .saturating_sub(4) as usize
         }
     }
 
@@ -609,10 +1680,8 @@ impl UI {
         let content_height = viewer_area.height.saturating_sub(2) as usize;
 
         // Avoid cloning entire content - use references instead
-        let help_content;
         let content_to_display: &[String] = if show_help {
-            help_content = get_help_content();
-            &help_content
+            &HELP_CONTENT
         } else {
             &file_viewer.content
         };
@@ -629,224 +1698,116 @@ impl UI {
 
         // Use highlighted content if available, otherwise fall back to plain text
         let use_highlighting = !file_viewer.highlighted_content.is_empty() && !show_help;
+        let border_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.border_color));
 
-        let mut visible_lines: Vec<Line> = if use_highlighting {
-            // Use pre-highlighted content
-            file_viewer
-                .highlighted_content
-                .iter()
-                .enumerate()
-                .skip(file_viewer.scroll)
-                .take(lines_to_show)
-                .map(|(idx, line)| {
-                    // idx already contains scroll offset after enumerate().skip()
-                    let line_idx = idx; // 0-indexed absolute position in content
-                    let line_num = line_idx + 1; // 1-indexed for display
-                    let is_match = file_viewer.line_has_match(line_idx);
-                    let is_current = file_viewer.is_current_match(line_idx);
-                    let is_selected = file_viewer.is_line_selected(line_idx);
-                    let is_visual_cursor =
-                        file_viewer.visual_mode && file_viewer.visual_cursor == line_idx;
-
-                    let mut spans = Vec::new();
-
-                    // Add line numbers if enabled
-                    if show_numbers {
-                        let border_color = Config::parse_color(Config::get_color(
-                            &config.appearance.colors.border_color,
-                        ));
-                        let num_style = if is_visual_cursor {
-                            Style::default()
-                                .fg(Color::White)
-                                .bg(Color::Blue)
-                                .add_modifier(Modifier::BOLD)
-                        } else if is_selected {
-                            Style::default().fg(Color::White).bg(Color::DarkGray)
-                        } else if is_current {
-                            Style::default()
-                                .fg(file_search_highlight_color)
-                                .add_modifier(Modifier::BOLD)
-                        } else if is_match {
-                            Style::default().fg(file_search_highlight_color)
-                        } else {
-                            Style::default().fg(border_color)
-                        };
-                        spans.push(Span::styled(format!("{:4} ", line_num), num_style));
-                    }
+        // Content holds whole lines of the file; long ones are wrapped into rows here
+        // Spans borrow the text of the lines, and the buffer for a line's spans is reused
+        let mut visible_lines: Vec<Line> = Vec::with_capacity(lines_to_show + 2);
+        let mut shown_lines = 0;
+        let mut styled: Vec<Span> = Vec::new();
+        for (line_idx, line) in content_to_display
+            .iter()
+            .enumerate()
+            .skip(file_viewer.scroll)
+        {
+            if visible_lines.len() >= lines_to_show {
+                break;
+            }
+            shown_lines += 1;
+            let is_match = file_viewer.line_has_match(line_idx);
+            let is_current = file_viewer.is_current_match(line_idx);
+            let is_selected = file_viewer.is_line_selected(line_idx);
+            let is_visual_cursor = file_viewer.visual_mode && file_viewer.visual_cursor == line_idx;
+
+            // Patch and conflict lines get their own colors instead of syntect's
+            let line_kind = file_viewer
+                .line_kinds
+                .get(line_idx)
+                .copied()
+                .flatten()
+                .filter(|_| !show_help);
+            styled.clear();
+            match (line_kind, file_viewer.highlighted_content.get(line_idx)) {
+                (Some(kind), _) => {
+                    styled.push(Span::styled(line.as_str(), line_kind_style(kind, config)))
+                }
+                (None, Some(highlighted)) if use_highlighting => styled.extend(
+                    highlighted
+                        .spans
+                        .iter()
+                        .map(|span| Span::styled(span.content.as_ref(), span.style)),
+                ),
+                _ => styled.push(Span::raw(line.as_str())),
+            }
 
-                    // Add line content with search query highlighting
-                    if is_match && !file_viewer.search_query.is_empty() {
-                        // Highlight only the search query within the line
-                        let query_lower = file_viewer.search_query.to_lowercase();
-
-                        for span in &line.spans {
-                            let text = &span.content;
-                            let text_lower = text.to_lowercase();
-
-                            if let Some(match_pos) = text_lower.find(&query_lower) {
-                                // Split text into: before | match | after
-                                let before = &text[..match_pos];
-                                let matched =
-                                    &text[match_pos..match_pos + file_viewer.search_query.len()];
-                                let after = &text[match_pos + file_viewer.search_query.len()..];
-
-                                // Apply visual selection style if needed
-                                let base_style = if is_visual_cursor {
-                                    span.style.bg(Color::Blue)
-                                } else if is_selected {
-                                    span.style.bg(Color::DarkGray)
-                                } else {
-                                    span.style
-                                };
-
-                                // Before match
-                                if !before.is_empty() {
-                                    spans.push(Span::styled(before.to_string(), base_style));
-                                }
-
-                                // Matched text
-                                let match_style = if is_current {
-                                    base_style.add_modifier(Modifier::REVERSED | Modifier::BOLD)
-                                } else {
-                                    base_style
-                                        .bg(file_search_highlight_color)
-                                        .add_modifier(Modifier::BOLD)
-                                };
-                                spans.push(Span::styled(matched.to_string(), match_style));
-
-                                // After match
-                                if !after.is_empty() {
-                                    spans.push(Span::styled(after.to_string(), base_style));
-                                }
-                            } else {
-                                // No match in this span, use original style with visual selection
-                                let base_style = if is_visual_cursor {
-                                    span.style.bg(Color::Blue)
-                                } else if is_selected {
-                                    span.style.bg(Color::DarkGray)
-                                } else {
-                                    span.style
-                                };
-                                spans.push(Span::styled(span.content.clone(), base_style));
-                            }
-                        }
+            // Apply the visual selection, then mark every occurrence of the query
+            if is_visual_cursor || is_selected {
+                let background = if is_visual_cursor {
+                    Color::Blue
+                } else {
+                    Color::DarkGray
+                };
+                for span in &mut styled {
+                    span.style = span.style.bg(background);
+                }
+            }
+            if is_match {
+                let matches = match_ranges(line, &file_viewer.search_query);
+                styled = restyle_ranges(&styled, &matches, |style| {
+                    if is_current {
+                        style.add_modifier(Modifier::REVERSED | Modifier::BOLD)
                     } else {
-                        // No match or no query - use original styling with visual selection
-                        for span in &line.spans {
-                            let base_style = if is_visual_cursor {
-                                span.style.bg(Color::Blue)
-                            } else if is_selected {
-                                span.style.bg(Color::DarkGray)
-                            } else {
-                                span.style
-                            };
-                            spans.push(Span::styled(span.content.clone(), base_style));
-                        }
+                        style
+                            .bg(file_search_highlight_color)
+                            .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
                     }
+                });
+            }
 
-                    Line::from(spans)
-                })
-                .collect()
-        } else {
-            // Fallback to plain text
-            content_to_display
-                .iter()
-                .enumerate()
-                .skip(file_viewer.scroll)
-                .take(lines_to_show)
-                .map(|(idx, line)| {
-                    // idx already contains scroll offset after enumerate().skip()
-                    let line_idx = idx; // 0-indexed absolute position in content
-                    let line_num = line_idx + 1; // 1-indexed for display
-                    let is_match = file_viewer.line_has_match(line_idx);
-                    let is_current = file_viewer.is_current_match(line_idx);
-                    let is_selected = file_viewer.is_line_selected(line_idx);
-                    let is_visual_cursor =
-                        file_viewer.visual_mode && file_viewer.visual_cursor == line_idx;
-
-                    let mut spans = Vec::new();
-
-                    // Add line numbers if enabled
-                    if show_numbers {
-                        let border_color = Config::parse_color(Config::get_color(
-                            &config.appearance.colors.border_color,
-                        ));
-                        let num_style = if is_visual_cursor {
-                            Style::default()
-                                .fg(Color::White)
-                                .bg(Color::Blue)
-                                .add_modifier(Modifier::BOLD)
-                        } else if is_selected {
-                            Style::default().fg(Color::White).bg(Color::DarkGray)
-                        } else if is_current {
-                            Style::default()
-                                .fg(file_search_highlight_color)
-                                .add_modifier(Modifier::BOLD)
-                        } else if is_match {
-                            Style::default().fg(file_search_highlight_color)
-                        } else {
-                            Style::default().fg(border_color)
-                        };
-                        spans.push(Span::styled(format!("{:4} ", line_num), num_style));
-                    }
+            let num_style = if is_visual_cursor {
+                Style::default()
+                    .fg(Color::White)
+                    .bg(Color::Blue)
+                    .add_modifier(Modifier::BOLD)
+            } else if is_selected {
+                Style::default().fg(Color::White).bg(Color::DarkGray)
+            } else if is_current {
+                Style::default()
+                    .fg(file_search_highlight_color)
+                    .add_modifier(Modifier::BOLD)
+            } else if is_match {
+                Style::default().fg(file_search_highlight_color)
+            } else {
+                Style::default().fg(border_color)
+            };
 
-                    // Determine base style for visual selection
-                    let base_style = if is_visual_cursor {
-                        Style::default().bg(Color::Blue)
-                    } else if is_selected {
-                        Style::default().bg(Color::DarkGray)
-                    } else {
-                        Style::default()
+            // Help text isn't wrapped
+            let rows = if show_help {
+                std::iter::once(0..line.len()).collect()
+            } else {
+                file_viewer.rows(line_idx)
+            };
+            for (row_idx, row) in rows.into_iter().enumerate() {
+                if visible_lines.len() >= lines_to_show {
+                    break;
+                }
+                let mut spans = Vec::new();
+                // The line number goes on the first row of a line only
+                if show_numbers {
+                    let number = match file_viewer.line_number(line_idx) {
+                        Some(number) if row_idx == 0 => format!("{:4} ", number),
+                        _ => "     ".to_string(),
                     };
-
-                    // Add line content with search query highlighting
-                    if is_match && !file_viewer.search_query.is_empty() {
-                        // Highlight only the search query within the line
-                        let text = line.as_str();
-                        let text_lower = text.to_lowercase();
-                        let query_lower = file_viewer.search_query.to_lowercase();
-
-                        if let Some(match_pos) = text_lower.find(&query_lower) {
-                            // Split text into: before | match | after
-                            let before = &text[..match_pos];
-                            let matched =
-                                &text[match_pos..match_pos + file_viewer.search_query.len()];
-                            let after = &text[match_pos + file_viewer.search_query.len()..];
-
-                            // Before match
-                            if !before.is_empty() {
-                                spans.push(Span::styled(before.to_string(), base_style));
-                            }
-
-                            // Matched text
-                            let match_style = if is_current {
-                                base_style.add_modifier(Modifier::REVERSED | Modifier::BOLD)
-                            } else {
-                                base_style
-                                    .bg(file_search_highlight_color)
-                                    .add_modifier(Modifier::BOLD)
-                            };
-                            spans.push(Span::styled(matched.to_string(), match_style));
-
-                            // After match
-                            if !after.is_empty() {
-                                spans.push(Span::styled(after.to_string(), base_style));
-                            }
-                        } else {
-                            // Shouldn't happen, but fallback to normal
-                            spans.push(Span::styled(line.as_str(), base_style));
-                        }
-                    } else {
-                        // No match or no query - normal text with visual selection style
-                        spans.push(Span::styled(line.as_str(), base_style));
-                    }
-
-                    Line::from(spans)
-                })
-                .collect()
-        };
+                    spans.push(Span::styled(number, num_style));
+                }
+                spans.extend(slice_spans(&styled, row));
+                visible_lines.push(Line::from(spans));
+            }
+        }
 
         // Add separator and file info at the end (only if not help)
+        let mut footer: Vec<Line> = Vec::new();
         if !show_help && !file_viewer.current_path.as_os_str().is_empty() {
             let file_info = file_viewer.format_file_info();
 
@@ -867,14 +1828,11 @@ impl UI {
                 file_info
             };
 
-            let border_color =
-                Config::parse_color(Config::get_color(&config.appearance.colors.border_color));
-
-            visible_lines.push(Line::from(Span::styled(
+            footer.push(Line::from(Span::styled(
                 separator,
                 Style::default().fg(border_color),
             )));
-            visible_lines.push(Line::from(Span::styled(
+            footer.push(Line::from(Span::styled(
                 file_info_padded,
                 Style::default().fg(border_color),
             )));
@@ -891,55 +1849,80 @@ impl UI {
         };
 
         let title = if show_help {
-            format!(" Help{} ", scroll_info)
+            format!("{}{} ", tr(" Help"), scroll_info)
         } else if is_fullscreen {
             // Fullscreen mode - simplified title
-            let mode_indicator = if file_viewer.visual_mode {
-                " [VISUAL MODE]"
+            let mode_indicator = if file_viewer.mark_key == Some(MarkKey::Set) {
+                tr(" [SET MARK]")
+            } else if file_viewer.mark_key == Some(MarkKey::Jump) {
+                tr(" [GO TO MARK]")
+            } else if file_viewer.visual_mode {
+                tr(" [VISUAL MODE]")
+            } else if file_viewer.git_view == Some(GitView::Log) {
+                tr(" [GIT LOG]")
+            } else if file_viewer.git_view == Some(GitView::Blame) {
+                tr(" [GIT BLAME]")
             } else if file_viewer.tail_mode {
-                " [TAIL MODE]"
+                tr(" [TAIL MODE]")
             } else if file_viewer.total_lines.is_some()
                 && file_viewer.total_lines.unwrap() > file_viewer.content.len()
             {
-                " [HEAD MODE]"
+                tr(" [HEAD MODE]")
             } else {
                 ""
             };
 
             // Add search match info if there are results or in search mode
             let search_info = if !file_viewer.search_results.is_empty() {
-                format!(" | {} | n/N: next/prev ", file_viewer.get_match_info())
+                format!(
+                    " | {}{}",
+                    file_viewer.get_match_info(),
+                    tr(" | n/N: next/prev ")
+                )
             } else if file_viewer.search_mode && !file_viewer.search_query.is_empty() {
-                " | No matches ".to_string()
+                format!(" | {} ", tr("No matches"))
             } else {
                 String::new()
             };
 
             // Add hints for toggles (hide in visual mode)
-            let hints = if file_viewer.visual_mode {
-                " - j/k: select | y: copy | Esc: cancel"
+            let hints = if file_viewer.visual_mode && file_viewer.git_view.is_some() {
+                tr(" - j/k: select | y: copy commit hashes | Esc: cancel").to_string()
+            } else if file_viewer.visual_mode {
+                tr(" - j/k: select | y: copy | Esc: cancel").to_string()
             } else {
                 let line_numbers_hint = if file_viewer.show_line_numbers {
-                    " | l: hide lines"
+                    tr(" | l: hide lines")
                 } else {
-                    " | l: show lines"
+                    tr(" | l: show lines")
                 };
 
                 let wrap_hint = if file_viewer.wrap_lines {
-                    " | w: truncate"
+                    tr(" | w: truncate")
                 } else {
-                    " | w: wrap"
+                    tr(" | w: wrap")
                 };
 
-                &format!(" - V: visual | /: search | j/k: scroll | Ctrl+j/k: next/prev file{}{} | q: back | Esc: exit", line_numbers_hint, wrap_hint)
+                tr_with(
+                    " - V: visual | /: search | j/k: scroll | Ctrl+j/k: next/prev file{lines}{wrap} | q: back | Esc: exit",
+                    &[("lines", &line_numbers_hint), ("wrap", &wrap_hint)],
+                )
             };
 
             format!(
-                " File Viewer (Fullscreen{}{}){}{}",
-                mode_indicator, hints, search_info, scroll_info
+                "{}{}{}){}{}",
+                tr(" File Viewer (Fullscreen"),
+                mode_indicator,
+                hints,
+                search_info,
+                scroll_info
             )
         } else {
-            format!(" File Viewer{} ", scroll_info)
+            let git_info = file_viewer
+                .git_view
+                .map(|view| format!(" [{}]", view.label()))
+                .unwrap_or_default();
+            format!("{}{}{} ", tr(" File Viewer"), git_info, scroll_info)
         };
 
         // In fullscreen mode, only show top and bottom borders (no sides)
@@ -949,15 +1932,49 @@ impl UI {
             Borders::ALL
         };
 
-        let paragraph = Paragraph::new(visible_lines).block(
-            Block::default()
-                .borders(borders)
-                .title(title)
-                .border_style(Style::default().fg(main_border_color))
-                .style(Style::default().bg(background_color)),
-        );
-
-        frame.render_widget(paragraph, viewer_area);
+        let block = Block::default()
+            .borders(borders)
+            .title(title)
+            .border_style(Style::default().fg(self.pane_border_color(
+                Focus::Viewer,
+                main_border_color,
+                config,
+            )))
+            .border_type(self.pane_border_type(Focus::Viewer))
+            .style(Style::default().bg(background_color));
+        let inner = block.inner(viewer_area);
+        frame.render_widget(block, viewer_area);
+
+        // The minimap gets the last column of the text rows to itself; the footer
+        // below them keeps the full width
+        let scrollable = content_to_display.len() > shown_lines;
+        let show_minimap = is_fullscreen
+            && config.appearance.show_minimap
+            && !show_help
+            && (scrollable || !file_viewer.search_results.is_empty());
+        let text_rows = (visible_lines.len() as u16).min(inner.height);
+        let text_area = Rect {
+            width: inner.width.saturating_sub(u16::from(show_minimap)),
+            height: text_rows,
+            ..inner
+        };
+        let footer_area = Rect {
+            y: inner.y + text_rows,
+            height: inner.height - text_rows,
+            ..inner
+        };
+        frame.render_widget(Paragraph::new(visible_lines), text_area);
+        frame.render_widget(Paragraph::new(footer), footer_area);
+
+        if show_minimap {
+            let minimap_area = Rect {
+                x: inner.right().saturating_sub(1),
+                y: inner.y,
+                width: 1,
+                height: lines_to_show.min(inner.height as usize) as u16,
+            };
+            self.render_minimap(frame, minimap_area, file_viewer, shown_lines, config);
+        }
 
         // Render file search bar if in search mode
         if let Some(search_area) = search_bar_area {
@@ -965,6 +1982,51 @@ impl UI {
         }
     }
 
+    /// Where the search matches and the visible lines are in the whole file
+    fn render_minimap(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        file_viewer: &FileViewer,
+        shown_lines: usize,
+        config: &Config,
+    ) {
+        let border_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.border_color));
+        let match_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.file_search_highlight_color,
+        ));
+
+        let lines: Vec<Line> = file_viewer
+            .minimap(area.height as usize, shown_lines)
+            .into_iter()
+            .map(|row| {
+                let symbol = if row.current_match {
+                    "◆"
+                } else if row.has_match {
+                    "•"
+                } else {
+                    "│"
+                };
+                let style = if row.has_match {
+                    Style::default()
+                        .fg(match_color)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(border_color)
+                };
+                // The visible part is a solid bar, with its matches still marked
+                let style = if row.in_view {
+                    style.add_modifier(Modifier::REVERSED)
+                } else {
+                    style
+                };
+                Line::from(Span::styled(symbol, style))
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+
     fn render_file_search_bar(
         &self,
         frame: &mut Frame,
@@ -1029,7 +2091,7 @@ impl UI {
                     .iter()
                     .skip(bookmarks.scroll_offset)
                     .map(|bookmark| {
-                        let name = bookmark.name.as_deref().unwrap_or("(unnamed)");
+                        let name = bookmark.name.as_deref().unwrap_or(tr("(unnamed)"));
                         let path_str = bookmark.path.display().to_string();
 
                         let text = format!("{:<12} → {:<20} ({})", bookmark.key, name, path_str);
@@ -1037,10 +2099,13 @@ impl UI {
                     })
                     .collect();
 
-                let count_text = if !all_bookmarks.is_empty() {
-                    format!(" Existing Bookmarks ({}) ", all_bookmarks.len())
+                let count_text = if all_bookmarks.is_empty() {
+                    tr(" Existing Bookmarks ").to_string()
                 } else {
-                    " Existing Bookmarks ".to_string()
+                    tr_with(
+                        " Existing Bookmarks ({count}) ",
+                        &[("count", &all_bookmarks.len())],
+                    )
                 };
 
                 let list = List::new(items).block(
@@ -1054,8 +2119,8 @@ impl UI {
             }
 
             // Render input bar at the bottom
-            let input_text = format!("Bookmark name: {}█", bookmarks.get_input());
-            let title = " Create Bookmark (Enter: save | Esc: cancel | Ctrl+j/k/↑↓: scroll list) ";
+            let input_text = format!("{}{}█", tr("Bookmark name: "), bookmarks.get_input());
+            let title = tr(" Create Bookmark (Enter: save | Tab: complete | Esc: cancel | Ctrl+j/k/↑↓: scroll list) ");
 
             let paragraph = Paragraph::new(input_text)
                 .block(
@@ -1071,17 +2136,21 @@ impl UI {
                 );
 
             frame.render_widget(paragraph, input_area);
+            self.render_completion_popup(frame, input_area, &bookmarks.completion, config);
         } else {
             // Selection mode - list with navigation
             let filtered = bookmarks.get_filtered_bookmarks();
 
             if filtered.is_empty() {
                 // No bookmarks - show message
-                let title = " Bookmarks (Press 'm' to create | Esc: close) ";
+                let title = tr(" Bookmarks (Press 'm' to create | Esc: close) ");
                 let message = if bookmarks.filter_mode {
-                    format!("No bookmarks match filter: '{}'", bookmarks.get_input())
+                    tr_with(
+                        "No bookmarks match filter: '{filter}'",
+                        &[("filter", &bookmarks.get_input())],
+                    )
                 } else {
-                    "No bookmarks saved yet".to_string()
+                    tr("No bookmarks saved yet").to_string()
                 };
 
                 let paragraph = Paragraph::new(message)
@@ -1098,20 +2167,43 @@ impl UI {
                 // Has bookmarks - show list with navigation
                 let error_color =
                     Config::parse_color(Config::get_color(&config.appearance.colors.error_color));
+                let highlight_style = Style::default()
+                    .fg(Config::parse_color(Config::get_color(
+                        &config.appearance.colors.highlight_color,
+                    )))
+                    .add_modifier(Modifier::BOLD);
                 let items: Vec<ListItem> = filtered
                     .iter()
                     .enumerate()
                     .map(|(idx, bookmark)| {
-                        let name = bookmark.name.as_deref().unwrap_or("(unnamed)");
+                        let name = bookmark.name.as_deref().unwrap_or(tr("(unnamed)"));
                         let path_str = bookmark.path.display().to_string();
 
                         // Check if this bookmark is marked for deletion
                         let is_marked = bookmarks.pending_deletion_index == Some(idx);
                         let prefix = if is_marked { "[DEL] " } else { "" };
-                        let text = format!(
-                            "{}{:<12} → {:<20} ({})",
-                            prefix, bookmark.key, name, path_str
-                        );
+
+                        // Key and name padded into columns, with the characters the
+                        // filter matched highlighted
+                        let filter_match = bookmarks.filter_match(idx).cloned().unwrap_or_default();
+                        let column = |text: &str, indices: &[usize], width: usize| {
+                            let mut spans =
+                                highlight_chars(text, indices, Style::default(), highlight_style);
+                            let padding = width.saturating_sub(text.chars().count());
+                            spans.push(Span::raw(" ".repeat(padding)));
+                            spans
+                        };
+                        let mut spans = vec![Span::raw(prefix)];
+                        spans.extend(column(&bookmark.key, &filter_match.key, 12));
+                        spans.push(Span::raw(" → "));
+                        let name_indices = if bookmark.name.is_some() {
+                            filter_match.name.as_slice()
+                        } else {
+                            &[]
+                        };
+                        spans.extend(column(name, name_indices, 20));
+                        spans.push(Span::raw(format!(" ({})", path_str)));
+                        let text = Line::from(spans);
 
                         // Use error color for marked bookmarks
                         let style = if is_marked {
@@ -1127,33 +2219,32 @@ impl UI {
                 let mut state = ListState::default();
                 state.select(Some(bookmarks.selected_index));
 
-                let mode_hint = if bookmarks.filter_mode {
-                    format!("Filter: {}", bookmarks.get_input())
-                } else {
-                    format!("{}/{}", bookmarks.selected_index + 1, filtered.len())
-                };
-
                 let deletion_hint = if bookmarks.is_marked_for_deletion() {
-                    " | d: confirm delete"
+                    tr(" | d: confirm delete")
                 } else {
-                    " | d: delete"
+                    tr(" | d: delete")
                 };
 
                 let hint = if bookmarks.filter_mode {
-                    format!(" {} | Tab: nav | Enter: select | Esc: cancel ", mode_hint)
+                    tr_with(
+                        " Filter: {filter} | Tab: nav | Enter: select | Esc: cancel ",
+                        &[("filter", &bookmarks.get_input())],
+                    )
                 } else {
-                    format!(" Bookmarks: {} | ↑↓/jk: move{} | Tab: filter | Enter: select | Esc: cancel ", mode_hint, deletion_hint)
+                    tr_with(
+                        " Bookmarks: {position}/{count} | ↑↓/jk: move{delete} | Tab: filter | Enter: select | Esc: cancel ",
+                        &[
+                            ("position", &(bookmarks.selected_index + 1)),
+                            ("count", &filtered.len()),
+                            ("delete", &deletion_hint),
+                        ],
+                    )
                 };
-
-                // Check cursor color setting - "dim" means no color highlight, just dimming
-                let cursor_color_str = Config::get_color(&config.appearance.colors.cursor_color);
-                let cursor_highlight_style = if cursor_color_str.to_lowercase() == "dim" {
-                    Style::default().add_modifier(Modifier::DIM)
+                // Picking the directory to compare with (`=`)
+                let hint = if bookmarks.compare_mode {
+                    format!("{}{}", tr(" Compare with |"), hint)
                 } else {
-                    let cursor_color = Config::parse_color(cursor_color_str);
-                    Style::default()
-                        .fg(cursor_color)
-                        .add_modifier(Modifier::BOLD)
+                    hint
                 };
 
                 let list = List::new(items)
@@ -1161,15 +2252,505 @@ impl UI {
                         Block::default()
                             .borders(Borders::ALL)
                             .title(hint)
-                            .border_style(Style::default().fg(panel_border_color)),
+                            .border_style(Style::default().fg(self.pane_border_color(
+                                Focus::Bookmarks,
+                                panel_border_color,
+                                config,
+                            )))
+                            .border_type(self.pane_border_type(Focus::Bookmarks)),
                     )
-                    .highlight_style(cursor_highlight_style)
+                    .highlight_style(cursor_highlight_style(config))
                     .highlight_symbol(">> ");
 
                 frame.render_stateful_widget(list, area, &mut state);
             }
         }
     }
+
+    fn render_conflict_prompt(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        file_ops: &FileOps,
+        config: &Config,
+    ) {
+        let Some(conflict) = file_ops.paste.as_ref().and_then(|p| p.conflict.as_ref()) else {
+            return;
+        };
+
+        let error_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.error_color));
+        let file_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.file_color));
+        let panel_border_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.panel_border_color,
+        ));
+
+        let lines = vec![
+            Line::from(Span::styled(
+                format!("'{}' already exists", conflict.target.display()),
+                Style::default().fg(error_color),
+            )),
+            Line::from(format!("Source: {}", conflict.source.display())),
+            Line::from(""),
+            Line::from("o: overwrite | s: skip | r: rename (O/S/R: same for all remaining)"),
+        ];
+
+        let paragraph = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Paste conflict (Esc: abort paste) ")
+                    .border_style(Style::default().fg(panel_border_color)),
+            )
+            .style(Style::default().fg(file_color));
+
+        frame.render_widget(paragraph, area);
+    }
+
+    fn render_copy_menu(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        copy_menu: &CopyPathMenu,
+        config: &Config,
+    ) {
+        let file_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.file_color));
+        let panel_border_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.panel_border_color,
+        ));
+
+        let items: Vec<ListItem> = copy_menu
+            .options
+            .iter()
+            .enumerate()
+            .map(|(idx, (format, text))| {
+                ListItem::new(format!("{}. {:<16} {}", idx + 1, format.label(), text))
+                    .style(Style::default().fg(file_color))
+            })
+            .collect();
+
+        let mut state = ListState::default();
+        state.select(Some(copy_menu.selected_index));
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Copy path | ↑↓/jk: move | 1-9/Enter: copy | c: copy absolute | Esc: cancel ")
+                    .border_style(Style::default().fg(panel_border_color)),
+            )
+            .highlight_style(cursor_highlight_style(config))
+            .highlight_symbol(">> ");
+
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn render_drives_panel(&self, frame: &mut Frame, area: Rect, drives: &Drives, config: &Config) {
+        let file_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.file_color));
+        let panel_border_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.panel_border_color,
+        ));
+
+        let items: Vec<ListItem> = drives
+            .list()
+            .iter()
+            .map(|drive| {
+                ListItem::new(drive.display().to_string()).style(Style::default().fg(file_color))
+            })
+            .collect();
+
+        let mut state = ListState::default();
+        state.select(Some(drives.selected_index));
+
+        let hint = format!(
+            " Drives: {}/{} | ↑↓/jk: move | Enter: open | Esc: cancel ",
+            drives.selected_index + 1,
+            drives.list().len()
+        );
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(hint)
+                    .border_style(Style::default().fg(panel_border_color)),
+            )
+            .highlight_style(cursor_highlight_style(config))
+            .highlight_symbol(">> ");
+
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn render_export_menu(&self, frame: &mut Frame, area: Rect, config: &Config) {
+        let file_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.file_color));
+        let panel_border_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.panel_border_color,
+        ));
+        let menu = &self.export_menu;
+
+        let items: Vec<ListItem> = menu
+            .options
+            .iter()
+            .enumerate()
+            .map(|(idx, (format, destination))| {
+                let destination = match destination {
+                    Destination::Clipboard => "clipboard".to_string(),
+                    Destination::File(file) => file.display().to_string(),
+                    Destination::Stdout => "stdout on exit".to_string(),
+                };
+                ListItem::new(format!(
+                    "{}. {:<9} to {}",
+                    idx + 1,
+                    format.label(),
+                    destination
+                ))
+                .style(Style::default().fg(file_color))
+            })
+            .collect();
+
+        let mut state = ListState::default();
+        state.select(Some(menu.selected_index));
+
+        let hint = format!(
+            " Export tree | ↑↓/jk: move | 1-9/Enter: export | i: icons {} | Esc: cancel ",
+            if menu.icons { "on" } else { "off" }
+        );
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(hint)
+                    .border_style(Style::default().fg(panel_border_color)),
+            )
+            .highlight_style(cursor_highlight_style(config))
+            .highlight_symbol(">> ");
+
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn render_clipboard_history(&self, frame: &mut Frame, area: Rect, config: &Config) {
+        let file_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.file_color));
+        let panel_border_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.panel_border_color,
+        ));
+        let history = &self.clipboard_history;
+
+        let items: Vec<ListItem> = history
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(index, text)| {
+                let number = if index < 9 {
+                    format!("{} ", index + 1)
+                } else {
+                    "  ".to_string()
+                };
+                // Snippets from the viewer can span lines; show the first one
+                let mut lines = text.lines();
+                let first = lines.next().unwrap_or_default().to_string();
+                let more = lines.count();
+                let mut spans = vec![
+                    Span::styled(number, Style::default().add_modifier(Modifier::DIM)),
+                    Span::styled(first, Style::default().fg(file_color)),
+                ];
+                if more > 0 {
+                    spans.push(Span::styled(
+                        format!("  [+{} lines]", more),
+                        Style::default().add_modifier(Modifier::DIM),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let mut state = ListState::default();
+        state.select(Some(history.selected_index));
+
+        let hint = format!(
+            " Clipboard history: {}/{} | ↑↓/jk: move | 1-9/Enter: copy | Esc: cancel ",
+            (history.selected_index + 1).min(history.entries.len()),
+            history.entries.len()
+        );
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(hint)
+                    .border_style(Style::default().fg(panel_border_color)),
+            )
+            .highlight_style(cursor_highlight_style(config))
+            .highlight_symbol(">> ");
+
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn render_marks_panel(&self, frame: &mut Frame, area: Rect, nav: &Navigation, config: &Config) {
+        let file_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.file_color));
+        let panel_border_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.panel_border_color,
+        ));
+        let panel = &self.marks_panel;
+        let root = &nav.root().path;
+
+        // Marks below the root are shown relative to it, the others in full and dimmed
+        let items: Vec<ListItem> = panel
+            .entries
+            .iter()
+            .map(|path| match path.strip_prefix(root) {
+                Ok(relative) if !relative.as_os_str().is_empty() => ListItem::new(Span::styled(
+                    relative.display().to_string(),
+                    Style::default().fg(file_color),
+                )),
+                _ => ListItem::new(Span::styled(
+                    path.display().to_string(),
+                    Style::default().add_modifier(Modifier::DIM),
+                )),
+            })
+            .collect();
+
+        let mut state = ListState::default();
+        state.select(Some(panel.selected_index));
+
+        let title = tr_with(
+            " Marks: {count} | Enter: reveal | d: unmark | D: clear all | Esc: close ",
+            &[("count", &panel.entries.len())],
+        );
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title)
+                    .border_style(Style::default().fg(panel_border_color)),
+            )
+            .highlight_style(cursor_highlight_style(config))
+            .highlight_symbol(">> ");
+
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn render_filter_picker(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        active: &QuickFilter,
+        config: &Config,
+    ) {
+        let file_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.file_color));
+        let panel_border_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.panel_border_color,
+        ));
+        let picker = &self.filter_picker;
+
+        let items: Vec<ListItem> = picker
+            .filters
+            .iter()
+            .enumerate()
+            .map(|(index, filter)| {
+                let number = if index < 9 {
+                    format!("{} ", index + 1)
+                } else {
+                    "  ".to_string()
+                };
+                let is_active = active.is_saved(filter);
+                ListItem::new(Line::from(vec![
+                    Span::styled(number, Style::default().add_modifier(Modifier::DIM)),
+                    Span::styled(
+                        if is_active { "* " } else { "  " },
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(filter.name.clone(), Style::default().fg(file_color)),
+                    Span::styled(
+                        format!("  {}", filter.patterns.join(" ")),
+                        Style::default().add_modifier(Modifier::DIM),
+                    ),
+                ]))
+            })
+            .collect();
+
+        let mut state = ListState::default();
+        state.select(Some(picker.selected_index));
+
+        let hint = " Saved filters (* active) | ↑↓/jk: move | 1-9/Enter: toggle | Esc: cancel ";
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(hint)
+                    .border_style(Style::default().fg(panel_border_color)),
+            )
+            .highlight_style(cursor_highlight_style(config))
+            .highlight_symbol(">> ");
+
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn render_layout_picker(&self, frame: &mut Frame, area: Rect, config: &Config) {
+        let file_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.file_color));
+        let selected_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.selected_color));
+        let panel_border_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.panel_border_color,
+        ));
+        let picker = &self.layout_picker;
+
+        let (list_area, input_area) = if picker.naming.is_some() {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(3), Constraint::Length(3)])
+                .split(area);
+            (chunks[0], Some(chunks[1]))
+        } else {
+            (area, None)
+        };
+
+        let items: Vec<ListItem> = if picker.layouts.is_empty() {
+            vec![ListItem::new("No saved layouts (s: save the current one)")
+                .style(Style::default().add_modifier(Modifier::DIM))]
+        } else {
+            picker
+                .layouts
+                .iter()
+                .enumerate()
+                .map(|(index, named)| {
+                    let number = if index < 9 {
+                        format!("{} ", index + 1)
+                    } else {
+                        "  ".to_string()
+                    };
+                    let mut shown = vec![if named.show_files { "files" } else { "tree" }];
+                    if named.show_sizes {
+                        shown.push("sizes");
+                    }
+                    ListItem::new(Line::from(vec![
+                        Span::styled(number, Style::default().add_modifier(Modifier::DIM)),
+                        Span::styled(named.name.clone(), Style::default().fg(file_color)),
+                        Span::styled(
+                            format!(
+                                "  {} | tree {}% | panel {}%",
+                                shown.join(" + "),
+                                named.layout.split_position,
+                                named.layout.bottom_panel_split_position
+                            ),
+                            Style::default().add_modifier(Modifier::DIM),
+                        ),
+                    ]))
+                })
+                .collect()
+        };
+
+        let mut state = ListState::default();
+        if !picker.layouts.is_empty() {
+            state.select(Some(picker.selected_index));
+        }
+
+        let hint = " Layouts | 1-9/Enter: restore | s: save current | d: delete | Esc: cancel ";
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(hint)
+                    .border_style(Style::default().fg(panel_border_color)),
+            )
+            .highlight_style(cursor_highlight_style(config))
+            .highlight_symbol(">> ");
+
+        frame.render_stateful_widget(list, list_area, &mut state);
+
+        if let (Some(name), Some(input_area)) = (&picker.naming, input_area) {
+            let paragraph = Paragraph::new(format!("Layout name: {}█", name))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(" Save Layout (Enter: save | Esc: cancel) ")
+                        .border_style(Style::default().fg(panel_border_color)),
+                )
+                .style(
+                    Style::default()
+                        .fg(selected_color)
+                        .add_modifier(Modifier::BOLD),
+                );
+            frame.render_widget(paragraph, input_area);
+        }
+    }
+
+    fn render_projects_panel(&self, frame: &mut Frame, area: Rect, config: &Config) {
+        let dir_color =
+            Config::parse_color(Config::get_color(&config.appearance.colors.directory_color));
+        let panel_border_color = Config::parse_color(Config::get_color(
+            &config.appearance.colors.panel_border_color,
+        ));
+        let projects = &self.projects;
+
+        let items: Vec<ListItem> = if projects.is_loading() {
+            vec![ListItem::new("Looking for projects...")
+                .style(Style::default().add_modifier(Modifier::DIM))]
+        } else if projects.list().is_empty() {
+            vec![
+                ListItem::new("No projects found (Cargo.toml, package.json, .git, ...)")
+                    .style(Style::default().add_modifier(Modifier::DIM)),
+            ]
+        } else {
+            projects
+                .list()
+                .iter()
+                .enumerate()
+                .map(|(index, project)| {
+                    let number = if index < 9 {
+                        format!("{} ", index + 1)
+                    } else {
+                        "  ".to_string()
+                    };
+                    ListItem::new(Line::from(vec![
+                        Span::styled(number, Style::default().add_modifier(Modifier::DIM)),
+                        Span::styled(
+                            projects.display_path(project),
+                            Style::default().fg(dir_color),
+                        ),
+                        Span::styled(
+                            format!("  [{}]", project.kinds.join(", ")),
+                            Style::default().add_modifier(Modifier::DIM),
+                        ),
+                    ]))
+                })
+                .collect()
+        };
+
+        let mut state = ListState::default();
+        if !projects.list().is_empty() {
+            state.select(Some(projects.selected_index));
+        }
+
+        let hint = format!(
+            " Projects: {}/{} | ↑↓/jk: move | 1-9/Enter: go | Esc: cancel ",
+            (projects.selected_index + 1).min(projects.list().len()),
+            projects.list().len()
+        );
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(hint)
+                    .border_style(Style::default().fg(panel_border_color)),
+            )
+            .highlight_style(cursor_highlight_style(config))
+            .highlight_symbol(">> ");
+
+        frame.render_stateful_widget(list, area, &mut state);
+    }
 }
 
 /// Load help content from HELP.txt file (embedded at compile time)
@@ -1185,3 +2766,155 @@ pub fn get_help_content() -> Vec<String> {
     // Split by lines and convert to Vec<String>
     HELP_TEXT.lines().map(|line| line.to_string()).collect()
 }
+
+/// Help lines, split once for rendering
+static HELP_CONTENT: Lazy<Vec<String>> = Lazy::new(get_help_content);
+
+/// Style of the selected row in lists and panels: the cursor color in bold, or only
+/// dimmed when `cursor_color = "dim"`
+fn cursor_highlight_style(config: &Config) -> Style {
+    let cursor_color_str = Config::get_color(&config.appearance.colors.cursor_color);
+    if cursor_color_str.to_lowercase() == "dim" {
+        Style::default().add_modifier(Modifier::DIM)
+    } else {
+        let cursor_color = Config::parse_color(cursor_color_str);
+        Style::default()
+            .fg(cursor_color)
+            .add_modifier(Modifier::BOLD)
+    }
+}
+
+/// Split highlighted snippet spans so the bytes in `range` are underlined
+fn underline_match(spans: &[Span<'static>], range: std::ops::Range<usize>) -> Vec<Span<'static>> {
+    restyle_ranges(spans, &[range], |style| {
+        style.add_modifier(Modifier::UNDERLINED)
+    })
+}
+
+/// Split `spans` at the edges of `ranges` (bytes of their text) and apply
+/// `restyle` to the parts inside them; matches may cross span boundaries
+fn restyle_ranges<'a>(
+    spans: &[Span<'a>],
+    ranges: &[std::ops::Range<usize>],
+    restyle: impl Fn(Style) -> Style,
+) -> Vec<Span<'a>> {
+    let mut result = Vec::with_capacity(spans.len() + 2 * ranges.len());
+    let mut offset = 0;
+    for span in spans {
+        let text = span.content.as_ref();
+        let end = offset + text.len();
+        let mut cuts = vec![offset, end];
+        for range in ranges
+            .iter()
+            .filter(|range| range.start < end && range.end > offset)
+        {
+            cuts.push(range.start.clamp(offset, end));
+            cuts.push(range.end.clamp(offset, end));
+        }
+        if cuts.len() == 2 {
+            result.push(span.clone());
+        } else {
+            cuts.sort_unstable();
+            cuts.dedup();
+            for part in cuts.windows(2) {
+                let piece = part[0] - offset..part[1] - offset;
+                if text.get(piece.clone()).is_none() {
+                    continue;
+                }
+                let inside = ranges.iter().any(|range| range.contains(&part[0]));
+                let style = if inside {
+                    restyle(span.style)
+                } else {
+                    span.style
+                };
+                result.push(Span::styled(sub_text(&span.content, piece), style));
+            }
+        }
+        offset = end;
+    }
+    result
+}
+
+/// Colors of patch and conflict lines, taken from the theme
+fn line_kind_style(kind: LineKind, config: &Config) -> Style {
+    let colors = &config.appearance.colors;
+    let color = |color: &Option<String>| Config::parse_color(Config::get_color(color));
+    match kind {
+        LineKind::Added => Style::default().fg(color(&colors.executable_color)),
+        LineKind::Removed => Style::default().fg(color(&colors.error_color)),
+        LineKind::Hunk => Style::default().fg(color(&colors.selected_color)),
+        LineKind::Header => Style::default().add_modifier(Modifier::BOLD),
+        LineKind::ConflictMarker => Style::default()
+            .fg(color(&colors.permission_warning_color))
+            .add_modifier(Modifier::BOLD),
+    }
+}
+
+/// `text` in `style`, with the characters at `indices` (ascending) in `highlight`
+fn highlight_chars(
+    text: &str,
+    indices: &[usize],
+    style: Style,
+    highlight: Style,
+) -> Vec<Span<'static>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut spans = Vec::new();
+    let mut last_idx = 0;
+    for &match_idx in indices.iter().filter(|&&idx| idx < chars.len()) {
+        if match_idx > last_idx {
+            spans.push(Span::styled(
+                chars[last_idx..match_idx].iter().collect::<String>(),
+                style,
+            ));
+        }
+        spans.push(Span::styled(chars[match_idx].to_string(), highlight));
+        last_idx = match_idx + 1;
+    }
+    if last_idx < chars.len() {
+        spans.push(Span::styled(
+            chars[last_idx..].iter().collect::<String>(),
+            style,
+        ));
+    }
+    spans
+}
+
+/// The last `max` characters of `text`, after a `…` when it had more
+fn keep_end(text: &str, max: usize) -> String {
+    let count = text.chars().count();
+    if count <= max {
+        return text.to_string();
+    }
+    let tail: String = text.chars().skip(count - max + 1).collect();
+    format!("…{}", tail)
+}
+
+/// The parts of `spans` inside the bytes `range` of their text (one wrapped row)
+fn slice_spans<'a>(spans: &[Span<'a>], range: std::ops::Range<usize>) -> Vec<Span<'a>> {
+    let mut result = Vec::with_capacity(spans.len());
+    let mut offset = 0;
+    for span in spans {
+        let text = span.content.as_ref();
+        let end = offset + text.len();
+        let start = range.start.clamp(offset, end) - offset;
+        let stop = range.end.clamp(offset, end) - offset;
+        if start == 0 && stop == text.len() {
+            result.push(span.clone());
+        } else if text.get(start..stop).is_some_and(|part| !part.is_empty()) {
+            result.push(Span::styled(
+                sub_text(&span.content, start..stop),
+                span.style,
+            ));
+        }
+        offset = end;
+    }
+    result
+}
+
+/// The bytes `range` of a span's text, borrowing from the line the span borrows from
+fn sub_text<'a>(content: &Cow<'a, str>, range: std::ops::Range<usize>) -> Cow<'a, str> {
+    match content {
+        Cow::Borrowed(text) => Cow::Borrowed(&text[range]),
+        Cow::Owned(text) => Cow::Owned(text[range].to_string()),
+    }
+}
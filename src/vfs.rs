@@ -0,0 +1,356 @@
+use crate::platform::{self, FileId};
+use crate::wakeup;
+use crossbeam_channel::Receiver;
+use once_cell::sync::Lazy;
+use std::fmt::Debug;
+use std::fs;
+use std::io::{self, Read, Seek};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::SystemTime;
+
+/// What the tree, preview, search and sizes need to know about an entry
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VfsMetadata {
+    pub is_dir: bool,
+    pub is_file: bool,
+    pub is_symlink: bool,
+    pub len: u64,
+    pub modified: Option<SystemTime>,
+    pub mode: u32, // Permission bits (0 when unknown or on Windows)
+    pub readonly: bool,
+    pub file_id: Option<FileId>, // Device, inode and link count, where there are any
+}
+
+impl VfsMetadata {
+    /// Device (filesystem) of the entry; mountpoints are where it changes
+    pub fn device(&self) -> Option<u64> {
+        self.file_id.map(|id| id.device)
+    }
+}
+
+/// An open file of a provider; tail mode seeks from the end
+pub trait VfsFile: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> VfsFile for T {}
+
+/// Where the entries shown come from: the local disk, or an archive, a remote host, ...
+/// Tree loading, preview, search, scans and directory sizes only go through this trait,
+/// so a backend only has to list, stat and open its entries
+pub trait VfsProvider: Debug + Send + Sync {
+    /// Paths of the entries of a directory; an entry that can't be read is an error
+    /// of its own and doesn't fail the listing
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<io::Result<PathBuf>>>;
+
+    /// Metadata of the entry, following symlinks
+    fn metadata(&self, path: &Path) -> io::Result<VfsMetadata>;
+
+    /// Metadata of the entry itself (a symlink is not followed)
+    fn symlink_metadata(&self, path: &Path) -> io::Result<VfsMetadata>;
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn VfsFile>>;
+
+    /// Target of a symlink (providers without links have none)
+    fn read_link(&self, _path: &Path) -> io::Result<PathBuf> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    /// Whether the paths are local ones that editors and file operations can work with
+    fn is_local(&self) -> bool {
+        false
+    }
+
+    /// Whether listing `path` waits on the network; the tree then has a worker
+    /// `prefetch` it before expanding it, so the UI doesn't wait
+    fn is_slow(&self, _path: &Path) -> bool {
+        false
+    }
+
+    /// List `path` for the next `read_dir` of it (called on a worker thread)
+    fn prefetch(&self, _path: &Path) {}
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.metadata(path).is_ok_and(|metadata| metadata.is_dir)
+    }
+
+    /// Hidden unless show_hidden is on (dotfiles by default)
+    fn is_hidden(&self, path: &Path) -> bool {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with('.'))
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.open(path)?.read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// The first `len` bytes of a file (for detecting its type)
+    fn read_head(&self, path: &Path, len: usize) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(len);
+        self.open(path)?.take(len as u64).read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+/// Shared handle to a provider; nodes, viewers and worker threads each keep one
+pub type Vfs = Arc<dyn VfsProvider>;
+
+/// The local filesystem
+#[derive(Debug, Default)]
+pub struct LocalFs;
+
+impl LocalFs {
+    fn convert(metadata: &fs::Metadata) -> VfsMetadata {
+        VfsMetadata {
+            is_dir: metadata.is_dir(),
+            is_file: metadata.is_file(),
+            is_symlink: metadata.is_symlink(),
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+            mode: platform::file_mode(metadata),
+            readonly: metadata.permissions().readonly(),
+            file_id: platform::file_id(metadata),
+        }
+    }
+}
+
+impl VfsProvider for LocalFs {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<io::Result<PathBuf>>> {
+        Ok(fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect())
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<VfsMetadata> {
+        fs::metadata(path).map(|metadata| Self::convert(&metadata))
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<VfsMetadata> {
+        fs::symlink_metadata(path).map(|metadata| Self::convert(&metadata))
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn VfsFile>> {
+        Ok(Box::new(fs::File::open(path)?))
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        fs::read_link(path)
+    }
+
+    fn is_local(&self) -> bool {
+        true
+    }
+
+    // The Windows hidden attribute counts too
+    fn is_hidden(&self, path: &Path) -> bool {
+        platform::is_hidden(path)
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+}
+
+/// A directory of a slow provider being listed on a worker thread
+#[derive(Debug)]
+pub struct PendingListing {
+    pub path: PathBuf,
+    receiver: Receiver<()>,
+}
+
+impl PendingListing {
+    /// Start listing `path` with `vfs.prefetch`
+    pub fn start(vfs: &Vfs, path: &Path) -> Self {
+        let (tx, rx) = wakeup::unbounded();
+        let (vfs, worker_path) = (Arc::clone(vfs), path.to_path_buf());
+        thread::spawn(move || {
+            vfs.prefetch(&worker_path);
+            let _ = tx.send(());
+        });
+        Self {
+            path: path.to_path_buf(),
+            receiver: rx,
+        }
+    }
+
+    /// Whether the listing arrived, so reading the directory doesn't wait anymore
+    pub fn is_done(&self) -> bool {
+        !self.receiver.is_empty()
+    }
+}
+
+static LOCAL: Lazy<Vfs> = Lazy::new(|| Arc::new(LocalFs));
+
+/// The local filesystem provider (what everything uses unless given another one)
+pub fn local() -> Vfs {
+    Arc::clone(&LOCAL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter_set::FilterSet;
+    use crate::tree_node::{Tree, TreeNode};
+    use std::collections::BTreeMap;
+    use std::io::Cursor;
+
+    /// Files held in memory, directories implied by their paths
+    #[derive(Debug)]
+    struct MemoryFs(BTreeMap<PathBuf, &'static str>);
+
+    impl VfsProvider for MemoryFs {
+        fn read_dir(&self, path: &Path) -> io::Result<Vec<io::Result<PathBuf>>> {
+            let mut entries: Vec<PathBuf> = self
+                .0
+                .keys()
+                .filter_map(|file| {
+                    let first = file.strip_prefix(path).ok()?.components().next()?;
+                    Some(path.join(first))
+                })
+                .collect();
+            entries.dedup();
+            Ok(entries.into_iter().map(Ok).collect())
+        }
+
+        fn metadata(&self, path: &Path) -> io::Result<VfsMetadata> {
+            match self.0.get(path) {
+                Some(text) => Ok(VfsMetadata {
+                    is_file: true,
+                    len: text.len() as u64,
+                    ..VfsMetadata::default()
+                }),
+                None if self.0.keys().any(|file| file.starts_with(path)) => Ok(VfsMetadata {
+                    is_dir: true,
+                    ..VfsMetadata::default()
+                }),
+                None => Err(io::ErrorKind::NotFound.into()),
+            }
+        }
+
+        fn symlink_metadata(&self, path: &Path) -> io::Result<VfsMetadata> {
+            self.metadata(path)
+        }
+
+        fn open(&self, path: &Path) -> io::Result<Box<dyn VfsFile>> {
+            let text = self.0.get(path).ok_or(io::ErrorKind::NotFound)?;
+            Ok(Box::new(Cursor::new(text.as_bytes())))
+        }
+    }
+
+    #[test]
+    fn test_tree_from_a_provider() {
+        let files = [
+            ("/archive/src/main.rs", "fn main() {}\n"),
+            ("/archive/README", "hello\n"),
+            ("/archive/.hidden", ""),
+        ];
+        let vfs: Vfs = Arc::new(MemoryFs(
+            files
+                .iter()
+                .map(|(path, text)| (PathBuf::from(path), *text))
+                .collect(),
+        ));
+
+        let root = TreeNode::new_in(Arc::clone(&vfs), PathBuf::from("/archive"), 0).unwrap();
+        assert!(root.is_dir);
+        let mut tree = Tree::new(root);
+        tree.load_children(tree.root(), true, &FilterSet::default(), false)
+            .unwrap();
+        let names: Vec<&str> = tree
+            .root_node()
+            .children
+            .iter()
+            .map(|&child| tree[child].name.as_str())
+            .collect();
+        assert_eq!(names, ["src", "README"]);
+
+        // Children read from the same provider
+        let src = tree.root_node().children[0];
+        tree.load_children(src, true, &FilterSet::default(), false)
+            .unwrap();
+        assert_eq!(tree[tree[src].children[0]].name, "main.rs");
+
+        let path = Path::new("/archive/src/main.rs");
+        assert_eq!(vfs.read(path).unwrap(), b"fn main() {}\n");
+        assert_eq!(vfs.read_head(path, 2).unwrap(), b"fn");
+    }
+
+    /// A provider whose directories are only read once prefetched, like a remote one
+    #[derive(Debug)]
+    struct SlowFs {
+        files: MemoryFs,
+        prefetched: std::sync::Mutex<Vec<PathBuf>>,
+    }
+
+    impl VfsProvider for SlowFs {
+        fn read_dir(&self, path: &Path) -> io::Result<Vec<io::Result<PathBuf>>> {
+            self.files.read_dir(path)
+        }
+
+        fn metadata(&self, path: &Path) -> io::Result<VfsMetadata> {
+            self.files.metadata(path)
+        }
+
+        fn symlink_metadata(&self, path: &Path) -> io::Result<VfsMetadata> {
+            self.files.metadata(path)
+        }
+
+        fn open(&self, path: &Path) -> io::Result<Box<dyn VfsFile>> {
+            self.files.open(path)
+        }
+
+        fn is_slow(&self, path: &Path) -> bool {
+            !self
+                .prefetched
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|done| done == path)
+        }
+
+        fn prefetch(&self, path: &Path) {
+            self.prefetched.lock().unwrap().push(path.to_path_buf());
+        }
+    }
+
+    #[test]
+    fn test_slow_directories_expand_once_listed() {
+        let files = MemoryFs(
+            [("/bucket/logs/today.txt", "")]
+                .into_iter()
+                .map(|(path, text)| (PathBuf::from(path), text))
+                .collect(),
+        );
+        let vfs: Vfs = Arc::new(SlowFs {
+            files,
+            prefetched: std::sync::Mutex::new(vec![PathBuf::from("/bucket")]),
+        });
+        let mut nav = crate::navigation::Navigation::builder("/bucket")
+            .vfs(vfs)
+            .show_files(true)
+            .build()
+            .unwrap();
+
+        // Toggling doesn't wait for the listing
+        let logs = Path::new("/bucket/logs");
+        assert_eq!(nav.toggle_node(logs, true).unwrap(), None);
+        assert_eq!(nav.flat_list.len(), 2);
+        assert!(nav.pending_listing.is_some());
+
+        let started = std::time::Instant::now();
+        while nav.poll_listing(true).is_none() {
+            assert!(
+                started.elapsed() < std::time::Duration::from_secs(5),
+                "listing hung"
+            );
+            thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert!(nav.pending_listing.is_none());
+        assert_eq!(nav.flat_list.len(), 3);
+        assert_eq!(nav.tree[nav.flat_list[2]].name, "today.txt");
+    }
+}
@@ -0,0 +1,177 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::logging;
+
+/// Files remembered at most; the least recently viewed are forgotten first
+const MAX_FILES: usize = 500;
+
+/// How the viewer showed a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ViewState {
+    /// Line of the file (1-based) at the top
+    pub line: usize,
+    pub tail_mode: bool,
+    pub wrap_lines: bool,
+    pub show_line_numbers: bool,
+}
+
+/// A file's view state as saved in viewer_state.toml
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedState {
+    path: PathBuf,
+    #[serde(flatten)]
+    state: ViewState,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SavedStates {
+    #[serde(default)]
+    files: Vec<SavedState>,
+}
+
+/// View state of the files viewed this session, so going back to a file resumes
+/// where it was left (kept between runs with `remember_viewer_state`)
+/// Files viewed the default way aren't remembered
+#[derive(Debug, Clone)]
+pub struct ViewStates {
+    /// State of a file not viewed before (from the config)
+    pub defaults: ViewState,
+    files: VecDeque<(PathBuf, ViewState)>, // Most recently viewed first
+    file_path: Option<PathBuf>,            // Where the states are saved on exit
+    changed: bool,                         // Since they were loaded or saved
+}
+
+impl ViewStates {
+    pub fn new(defaults: ViewState) -> Self {
+        Self {
+            defaults,
+            files: VecDeque::new(),
+            file_path: None,
+            changed: false,
+        }
+    }
+
+    /// File the states are saved to (~/.local/state/dtree/viewer_state.toml on Linux)
+    pub fn file() -> Option<PathBuf> {
+        logging::state_dir().map(|dir| dir.join("viewer_state.toml"))
+    }
+
+    /// How `path` was viewed last (None if the default way)
+    pub fn get(&self, path: &Path) -> Option<ViewState> {
+        self.files
+            .iter()
+            .find(|(remembered, _)| remembered == path)
+            .map(|&(_, state)| state)
+    }
+
+    /// Remember how `path` is viewed
+    pub fn remember(&mut self, path: &Path, state: ViewState) {
+        let index = self
+            .files
+            .iter()
+            .position(|(remembered, _)| remembered == path);
+        if index.is_none() && state == self.defaults {
+            return;
+        }
+        if let Some(index) = index {
+            self.files.remove(index);
+        }
+        if state != self.defaults {
+            self.files.push_front((path.to_path_buf(), state));
+            self.files.truncate(MAX_FILES);
+        }
+        self.changed = true;
+    }
+
+    /// Read the states saved in `path` (if any) and save them there on exit
+    pub fn load(&mut self, path: PathBuf) {
+        if let Ok(content) = fs::read_to_string(&path) {
+            match toml::from_str::<SavedStates>(&content) {
+                Ok(saved) => {
+                    self.files = saved
+                        .files
+                        .into_iter()
+                        .take(MAX_FILES)
+                        .map(|saved| (saved.path, saved.state))
+                        .collect();
+                }
+                Err(e) => tracing::warn!("ignoring {}: {}", path.display(), e),
+            }
+        }
+        self.file_path = Some(path);
+        self.changed = false;
+    }
+
+    /// Save the states if they changed since they were loaded
+    pub fn save(&mut self) -> Result<()> {
+        let Some(path) = &self.file_path else {
+            return Ok(());
+        };
+        if !self.changed {
+            return Ok(());
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let saved = SavedStates {
+            files: self
+                .files
+                .iter()
+                // Paths TOML can't hold (not UTF-8) aren't saved
+                .filter(|(path, _)| path.to_str().is_some())
+                .map(|(path, state)| SavedState {
+                    path: path.clone(),
+                    state: *state,
+                })
+                .collect(),
+        };
+        let content = toml::to_string(&saved).context("Failed to serialize viewer state")?;
+        fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+        self.changed = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFAULTS: ViewState = ViewState {
+        line: 1,
+        tail_mode: false,
+        wrap_lines: true,
+        show_line_numbers: false,
+    };
+
+    #[test]
+    fn test_states_are_saved_and_loaded() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let file = temp_dir.path().join("viewer_state.toml");
+        let log = Path::new("/var/log/app.log");
+        let tail = ViewState {
+            line: 120,
+            tail_mode: true,
+            ..DEFAULTS
+        };
+
+        let mut states = ViewStates::new(DEFAULTS);
+        states.load(file.clone());
+        states.remember(log, tail);
+        // Viewed the default way: nothing to remember
+        states.remember(Path::new("/etc/hosts"), DEFAULTS);
+        states.save().unwrap();
+
+        let mut loaded = ViewStates::new(DEFAULTS);
+        loaded.load(file);
+        assert_eq!(loaded.get(log), Some(tail));
+        assert_eq!(loaded.get(Path::new("/etc/hosts")), None);
+
+        // Going back to the defaults forgets the file
+        loaded.remember(log, DEFAULTS);
+        assert!(loaded.files.is_empty());
+    }
+}
@@ -0,0 +1,59 @@
+use crossbeam_channel::{Receiver, SendError, Sender};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set while the event loop runs; background workers don't signal anything otherwise
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// A wake-up was sent that the event loop hasn't taken yet (more would only repeat it)
+static PENDING: AtomicBool = AtomicBool::new(false);
+
+/// Whether `notify` wakes the event loop on this platform
+/// Elsewhere the loop keeps looking at running workers every frame
+pub const SUPPORTED: bool = cfg!(unix);
+
+/// Let background workers wake the event loop from now on
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Wake the event loop waiting for input, so it picks up a worker's messages right away
+/// A SIGWINCH to dtree itself ends crossterm's wait with a resize event
+pub fn notify() {
+    if !ENABLED.load(Ordering::Relaxed) || PENDING.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    #[cfg(unix)]
+    // SAFETY: raise only sends a signal to this process; crossterm handles SIGWINCH
+    unsafe {
+        libc::raise(libc::SIGWINCH);
+    }
+}
+
+/// Whether a wake-up came since the last call
+pub fn take() -> bool {
+    PENDING.swap(false, Ordering::AcqRel)
+}
+
+/// Sending half of a channel that wakes the event loop after each message
+#[derive(Debug)]
+pub struct WakingSender<T>(Sender<T>);
+
+impl<T> WakingSender<T> {
+    pub fn send(&self, message: T) -> Result<(), SendError<T>> {
+        let result = self.0.send(message);
+        notify();
+        result
+    }
+}
+
+impl<T> Clone for WakingSender<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// An unbounded channel for a worker's messages to the event loop
+pub fn unbounded<T>() -> (WakingSender<T>, Receiver<T>) {
+    let (sender, receiver) = crossbeam_channel::unbounded();
+    (WakingSender(sender), receiver)
+}
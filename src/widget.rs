@@ -0,0 +1,310 @@
+use crate::file_icons;
+use crate::navigation::Navigation;
+use crate::tree_node::TreeNode;
+use anyhow::Result;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEventKind};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Position, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, List, ListItem, ListState, StatefulWidget},
+};
+use std::path::PathBuf;
+
+/// Arrow or file type icon in front of a node's name (a warning sign for unreadable ones)
+pub fn node_icon(node: &TreeNode, icons: bool) -> String {
+    if node.has_error {
+        // Error indicator always shows, regardless of icon settings
+        return "⚠ ".to_string();
+    }
+    let file_icon = if icons {
+        file_icons::get_icon(&node.path, node.is_dir, true)
+    } else {
+        ""
+    };
+    // Arrows without icons, or when there's no icon for the entry
+    if file_icon.trim().is_empty() {
+        match (node.is_dir, node.is_expanded) {
+            (true, true) => "▼ ".to_string(),
+            (true, false) => "▶ ".to_string(),
+            (false, _) => "  ".to_string(),
+        }
+    } else {
+        format!("{}  ", file_icon) // Two spaces after icon for better readability
+    }
+}
+
+/// What an event forwarded to `DtreeState::handle_event` did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DtreeOutcome {
+    /// Not an event the tree uses; the application can handle it
+    Ignored,
+    /// Selection moved, a directory was expanded or collapsed, or the root changed
+    Changed,
+    /// Enter on a file
+    Opened(PathBuf),
+    /// A directory couldn't be read
+    Failed(String),
+}
+
+/// State of a `DtreeWidget`: the tree, its selection and scroll position
+pub struct DtreeState {
+    pub nav: Navigation,
+    pub show_files: bool,
+    list_state: ListState,
+    area: Rect, // Rows of the last render, for mouse clicks
+}
+
+impl DtreeState {
+    pub fn new(nav: Navigation, show_files: bool) -> Self {
+        Self {
+            nav,
+            show_files,
+            list_state: ListState::default(),
+            area: Rect::default(),
+        }
+    }
+
+    pub fn selected(&self) -> Option<&TreeNode> {
+        self.nav.get_selected_node()
+    }
+
+    /// Let the tree handle a key or mouse event, with dtree's default keys:
+    /// j/k and arrows move, l/h and Right/Left expand and collapse, Enter goes into
+    /// a directory (or opens a file), u/Backspace goes to the parent
+    pub fn handle_event(&mut self, event: &Event) -> Result<DtreeOutcome> {
+        match event {
+            Event::Key(key) if key.kind != KeyEventKind::Release => self.handle_key(key),
+            Event::Mouse(mouse) => {
+                if !self.area.contains(Position::new(mouse.column, mouse.row)) {
+                    return Ok(DtreeOutcome::Ignored);
+                }
+                match mouse.kind {
+                    MouseEventKind::ScrollDown => self.nav.move_down(),
+                    MouseEventKind::ScrollUp => self.nav.move_up(),
+                    MouseEventKind::Down(MouseButton::Left) => {
+                        let row = self.list_state.offset() + (mouse.row - self.area.y) as usize;
+                        if row >= self.nav.flat_list.len() {
+                            return Ok(DtreeOutcome::Ignored);
+                        }
+                        // A click on the selected directory expands or collapses it
+                        if row == self.nav.selected {
+                            return self.toggle_selected();
+                        }
+                        self.nav.selected = row;
+                    }
+                    _ => return Ok(DtreeOutcome::Ignored),
+                }
+                Ok(DtreeOutcome::Changed)
+            }
+            _ => Ok(DtreeOutcome::Ignored),
+        }
+    }
+
+    fn handle_key(&mut self, key: &KeyEvent) -> Result<DtreeOutcome> {
+        let page = (self.area.height as usize).max(1);
+        let last = self.nav.flat_list.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => self.nav.move_down(),
+            KeyCode::Char('k') | KeyCode::Up => self.nav.move_up(),
+            KeyCode::PageDown => self.nav.selected = (self.nav.selected + page).min(last),
+            KeyCode::PageUp => self.nav.selected = self.nav.selected.saturating_sub(page),
+            KeyCode::Home => self.nav.selected = 0,
+            KeyCode::End => self.nav.selected = last,
+            KeyCode::Char('l') | KeyCode::Right | KeyCode::Char('h') | KeyCode::Left => {
+                return self.toggle_selected();
+            }
+            KeyCode::Enter => {
+                let Some(node) = self.nav.get_selected_node() else {
+                    return Ok(DtreeOutcome::Ignored);
+                };
+                let path = node.path.clone();
+                if !node.is_dir {
+                    return Ok(DtreeOutcome::Opened(path));
+                }
+                if let Some(error) = self.nav.go_to_directory(path, self.show_files)? {
+                    return Ok(DtreeOutcome::Failed(error));
+                }
+            }
+            KeyCode::Char('u') | KeyCode::Backspace => self.nav.go_to_parent(self.show_files)?,
+            _ => return Ok(DtreeOutcome::Ignored),
+        }
+        Ok(DtreeOutcome::Changed)
+    }
+
+    fn toggle_selected(&mut self) -> Result<DtreeOutcome> {
+        let Some(node) = self.nav.get_selected_node().filter(|node| node.is_dir) else {
+            return Ok(DtreeOutcome::Ignored);
+        };
+        let path = node.path.clone();
+        Ok(match self.nav.toggle_node(&path, self.show_files)? {
+            Some(error) => DtreeOutcome::Failed(error),
+            None => DtreeOutcome::Changed,
+        })
+    }
+}
+
+/// A directory tree pane for other ratatui applications, drawn from a `DtreeState`
+/// Events go to `DtreeState::handle_event`
+#[derive(Debug, Clone)]
+pub struct DtreeWidget<'a> {
+    block: Option<Block<'a>>,
+    icons: bool,
+    directory_style: Style,
+    file_style: Style,
+    error_style: Style,
+    highlight_style: Style,
+}
+
+impl Default for DtreeWidget<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> DtreeWidget<'a> {
+    /// dtree's default colors, arrows instead of icons
+    pub fn new() -> Self {
+        Self {
+            block: None,
+            icons: false,
+            directory_style: Style::default().fg(Color::Gray),
+            file_style: Style::default().fg(Color::White),
+            error_style: Style::default().fg(Color::Red),
+            highlight_style: Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::REVERSED),
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    /// File type icons from a nerd font in place of the arrows
+    pub fn icons(mut self, icons: bool) -> Self {
+        self.icons = icons;
+        self
+    }
+
+    pub fn directory_style(mut self, style: Style) -> Self {
+        self.directory_style = style;
+        self
+    }
+
+    pub fn file_style(mut self, style: Style) -> Self {
+        self.file_style = style;
+        self
+    }
+
+    /// Directories that couldn't be read
+    pub fn error_style(mut self, style: Style) -> Self {
+        self.error_style = style;
+        self
+    }
+
+    /// The selected row
+    pub fn highlight_style(mut self, style: Style) -> Self {
+        self.highlight_style = style;
+        self
+    }
+}
+
+impl StatefulWidget for DtreeWidget<'_> {
+    type State = DtreeState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut DtreeState) {
+        state.area = self.block.as_ref().map_or(area, |block| block.inner(area));
+
+        let items: Vec<ListItem> = state
+            .nav
+            .visible_nodes()
+            .map(|node| {
+                let text = format!(
+                    "{}{}{}",
+                    "  ".repeat(node.depth),
+                    node_icon(node, self.icons),
+                    node.name
+                );
+                let style = if node.has_error {
+                    self.error_style
+                } else if node.is_dir {
+                    self.directory_style
+                } else {
+                    self.file_style
+                };
+                ListItem::new(text).style(style)
+            })
+            .collect();
+
+        state.list_state.select(Some(state.nav.selected));
+        let mut list = List::new(items).highlight_style(self.highlight_style);
+        if let Some(block) = self.block {
+            list = list.block(block);
+        }
+        StatefulWidget::render(list, area, buf, &mut state.list_state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyModifiers, MouseEvent};
+    use ratatui::widgets::Borders;
+
+    fn key(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_widget_renders_and_handles_events() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("project");
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "").unwrap();
+        std::fs::write(root.join("Cargo.toml"), "").unwrap();
+
+        let nav = Navigation::builder(&root).show_files(true).build().unwrap();
+        let mut state = DtreeState::new(nav, true);
+        let area = Rect::new(0, 0, 20, 6);
+        let mut buf = Buffer::empty(area);
+        let widget = DtreeWidget::new().block(Block::default().borders(Borders::ALL));
+        widget.clone().render(area, &mut buf, &mut state);
+        let row = |buf: &Buffer, y| {
+            (1..19)
+                .map(|x| buf[(x, y)].symbol())
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        };
+        assert_eq!(row(&buf, 1), "▼ project");
+        assert_eq!(row(&buf, 2), "  ▶ src");
+
+        assert_eq!(
+            state.handle_event(&key(KeyCode::Down)).unwrap(),
+            DtreeOutcome::Changed
+        );
+        state.handle_event(&key(KeyCode::Right)).unwrap();
+        let mut buf = Buffer::empty(area);
+        widget.clone().render(area, &mut buf, &mut state);
+        assert_eq!(row(&buf, 3), "      lib.rs");
+
+        // A click on a file's row selects it, Enter opens it
+        let click = Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: 5,
+            row: 3,
+            modifiers: KeyModifiers::NONE,
+        });
+        assert_eq!(state.handle_event(&click).unwrap(), DtreeOutcome::Changed);
+        assert_eq!(
+            state.handle_event(&key(KeyCode::Enter)).unwrap(),
+            DtreeOutcome::Opened(root.join("src/lib.rs"))
+        );
+        assert_eq!(
+            state.handle_event(&key(KeyCode::Char('x'))).unwrap(),
+            DtreeOutcome::Ignored
+        );
+    }
+}
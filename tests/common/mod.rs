@@ -0,0 +1,14 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use dtree_tui::search::Search;
+
+/// Poll until the running search or scan is done
+pub fn wait_for_search(search: &mut Search) {
+    let started = Instant::now();
+    while search.is_searching {
+        assert!(started.elapsed() < Duration::from_secs(5), "search hung");
+        search.poll_results();
+        thread::sleep(Duration::from_millis(5));
+    }
+}
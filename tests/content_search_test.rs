@@ -0,0 +1,64 @@
+use std::path::{Path, PathBuf};
+
+use dtree_tui::content_search::ContentMatch;
+use dtree_tui::search::{Search, SearchResult};
+
+/// A content match result for line `line` of `path`, the match being `needle`
+fn content_result(path: &str, line: usize, text: &str, needle: &str) -> SearchResult {
+    let start = text.find(needle).unwrap();
+    let path = PathBuf::from(path);
+    let content = ContentMatch::new(&path, line, text, start..start + needle.len(), None);
+    SearchResult {
+        path,
+        is_dir: false,
+        score: None,
+        match_indices: None,
+        detail: None,
+        content: Some(content),
+    }
+}
+
+#[test]
+fn test_content_match_keeps_line_column_and_match() {
+    let result = content_result("/src/main.rs", 3, "    // TODO: more  ", "TODO");
+    let content = result.content.unwrap();
+    assert_eq!((content.line, content.column), (3, 8));
+    // The snippet is trimmed, the range still points at the match
+    assert_eq!(content.text(), "// TODO: more");
+    assert_eq!(&content.text()[content.range.clone()], "TODO");
+
+    // With a theme the snippet is highlighted by the file's syntax
+    let path = Path::new("/src/main.rs");
+    let highlighted = ContentMatch::new(path, 1, "let x = 1;", 4..5, Some("base16-ocean.dark"));
+    assert!(highlighted.spans.len() > 1);
+    assert_eq!(highlighted.text(), "let x = 1;");
+}
+
+#[test]
+fn test_content_matches_collapse_per_file() {
+    let mut search = Search::new();
+    search.results = vec![
+        content_result("/a.txt", 1, "x", "x"),
+        content_result("/a.txt", 2, "x", "x"),
+        content_result("/a.txt", 3, "x", "x"),
+        content_result("/b.txt", 1, "x", "x"),
+    ];
+    search.show_results = true;
+    assert!(search.has_content_matches());
+    assert_eq!(search.get_results_count(), 4);
+
+    // Folding from the last match of a.txt selects its first one
+    search.set_selected(2);
+    search.collapse_group();
+    assert_eq!(search.selected, 0);
+    assert_eq!(search.get_results_count(), 2);
+    assert_eq!(search.folded_count(0), 2);
+    search.move_down();
+    assert_eq!(search.get_selected_result(), Some(PathBuf::from("/b.txt")));
+    assert_eq!(search.selected_line(), Some(1));
+
+    search.move_up();
+    search.expand_group();
+    assert_eq!(search.get_results_count(), 4);
+    assert_eq!(search.folded_count(0), 0);
+}
@@ -0,0 +1,622 @@
+use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEventKind};
+use ratatui::style::Modifier;
+use std::fs;
+use std::time::Duration;
+use tempfile::TempDir;
+
+use dtree_tui::app::App;
+use dtree_tui::bookmarks::Bookmarks;
+use dtree_tui::clipboard;
+use dtree_tui::config::{ActionSteps, Config};
+use dtree_tui::driver::Driver;
+use dtree_tui::launcher;
+
+fn driver(temp_dir: &TempDir) -> Driver {
+    driver_with_config(temp_dir, Config::default())
+}
+
+fn driver_with_config(temp_dir: &TempDir, config: Config) -> Driver {
+    let root = temp_dir.path().join("root");
+    let bookmarks = Bookmarks::open(temp_dir.path().join("bookmarks.json")).unwrap();
+    let app = App::with_config(root, config, bookmarks).unwrap();
+    Driver::new(app, 100, 30).unwrap()
+}
+
+#[test]
+fn test_search_select_second_result_and_view() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("root");
+    fs::create_dir_all(root.join("a")).unwrap();
+    fs::create_dir_all(root.join("b")).unwrap();
+    fs::write(root.join("a/notes.txt"), "notes in a\n").unwrap();
+    fs::write(root.join("b/notes.txt"), "notes in b\n").unwrap();
+
+    let mut driver = driver(&temp_dir);
+    assert!(driver.screen().unwrap().contains("a"));
+
+    // Show files, search (results get the focus) and open the second result in the viewer
+    driver
+        .press(KeyCode::Char('s'))
+        .unwrap()
+        .press(KeyCode::Char('/'))
+        .unwrap()
+        .type_text("notes")
+        .unwrap()
+        .press(KeyCode::Enter)
+        .unwrap();
+    let screen = driver
+        .wait_for("Search Results: 2 found", Duration::from_secs(10))
+        .unwrap()
+        .screen()
+        .unwrap();
+
+    // Results arrive in the order the search finds them
+    let second = screen
+        .lines()
+        .filter(|line| line.contains("notes.txt"))
+        .nth(1)
+        .unwrap();
+    let (dir, other) = if second.contains("a/notes.txt") {
+        ("a", "b")
+    } else {
+        ("b", "a")
+    };
+
+    driver
+        .press(KeyCode::Char('j'))
+        .unwrap()
+        .press(KeyCode::Enter)
+        .unwrap();
+
+    let screen = driver.screen().unwrap();
+    assert!(screen.contains(&format!("notes in {}", dir)), "{}", screen);
+    assert!(!screen.contains(&format!("notes in {}", other)));
+    assert!(driver.exit().is_none());
+
+    // q exits with the directory to cd into; keys after exit are refused
+    driver.press(KeyCode::Char('q')).unwrap();
+    let exit_dir = driver.exit().unwrap().as_ref().unwrap();
+    assert!(exit_dir.ends_with(format!("root/{}", dir)));
+    assert!(driver.press(KeyCode::Char('j')).is_err());
+}
+
+#[test]
+fn test_custom_actions_chain_built_ins() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("root");
+    fs::create_dir_all(root.join("a/deep")).unwrap();
+    fs::create_dir_all(root.join("b")).unwrap();
+    fs::write(root.join("b/readme.txt"), "hello from b\n").unwrap();
+
+    let mut config = Config::default();
+    config.actions.insert(
+        "ctrl+g".to_string(),
+        ActionSteps::Many(vec![
+            "goto:b/readme.txt".to_string(),
+            "toggle_files".to_string(),
+        ]),
+    );
+    config.actions.insert(
+        "ctrl+b".to_string(),
+        ActionSteps::One("cd_bookmark:deep".to_string()),
+    );
+    config.actions.insert(
+        "ctrl+x".to_string(),
+        ActionSteps::Many(vec!["goto:missing".to_string(), "quit".to_string()]),
+    );
+    let mut driver = driver_with_config(&temp_dir, config);
+    driver
+        .app()
+        .bookmarks
+        .add("deep".to_string(), root.join("a/deep"), None)
+        .unwrap();
+
+    // goto reveals the file with the preview shown, then toggle_files hides the files again
+    driver
+        .press_with(KeyCode::Char('g'), KeyModifiers::CONTROL)
+        .unwrap();
+    assert!(driver.app().root_path().ends_with("root/b"));
+    assert!(!driver.screen().unwrap().contains("hello from b"));
+
+    driver
+        .press_with(KeyCode::Char('b'), KeyModifiers::CONTROL)
+        .unwrap();
+    assert!(driver.app().root_path().ends_with("root/a/deep"));
+
+    // A failing step ends the chain (quit is not run)
+    driver
+        .press_with(KeyCode::Char('x'), KeyModifiers::CONTROL)
+        .unwrap();
+    assert!(driver.exit().is_none());
+    assert!(driver
+        .screen()
+        .unwrap()
+        .contains("no such file or directory"));
+}
+
+#[test]
+fn test_mark_all_results_and_step_through_them() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("root");
+    fs::create_dir_all(root.join("a")).unwrap();
+    fs::create_dir_all(root.join("b")).unwrap();
+    fs::write(root.join("a/notes.txt"), "notes in a\n").unwrap();
+    fs::write(root.join("b/notes.txt"), "notes in b\n").unwrap();
+    fs::write(root.join("other.txt"), "other\n").unwrap();
+
+    let mut driver = driver(&temp_dir);
+    driver
+        .press(KeyCode::Char('s'))
+        .unwrap()
+        .press(KeyCode::Char('/'))
+        .unwrap()
+        .type_text("notes")
+        .unwrap()
+        .press(KeyCode::Enter)
+        .unwrap()
+        .wait_for("Search Results: 2 found", Duration::from_secs(10))
+        .unwrap();
+
+    // `a` in the results panel marks every result
+    let screen = driver.press(KeyCode::Char('a')).unwrap().screen().unwrap();
+    assert!(
+        screen.contains("Marked 2 result(s) (2 marked)"),
+        "{}",
+        screen
+    );
+    assert_eq!(screen.matches("* root/").count(), 2, "{}", screen);
+
+    // n/N step through the results from the tree, showing each one in the viewer
+    let screen = driver
+        .press(KeyCode::Tab)
+        .unwrap()
+        .press(KeyCode::Char('n'))
+        .unwrap()
+        .screen()
+        .unwrap();
+    let second = if screen.contains("notes in a") {
+        "a"
+    } else {
+        "b"
+    };
+    let first = if second == "a" { "b" } else { "a" };
+    assert!(!screen.contains(&format!("notes in {}", first)));
+
+    let screen = driver.press(KeyCode::Char('N')).unwrap().screen().unwrap();
+    assert!(
+        screen.contains(&format!("notes in {}", first)),
+        "{}",
+        screen
+    );
+    assert!(!screen.contains(&format!("notes in {}", second)));
+}
+
+#[test]
+fn test_mark_results_and_add_them_to_the_tree_marks() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("root");
+    fs::create_dir_all(root.join("a")).unwrap();
+    fs::create_dir_all(root.join("b")).unwrap();
+    fs::write(root.join("a/notes.txt"), "notes in a\n").unwrap();
+    fs::write(root.join("b/notes.txt"), "notes in b\n").unwrap();
+
+    let mut driver = driver(&temp_dir);
+    driver
+        .press(KeyCode::Char('s'))
+        .unwrap()
+        .press(KeyCode::Char('/'))
+        .unwrap()
+        .type_text("notes")
+        .unwrap()
+        .press(KeyCode::Enter)
+        .unwrap()
+        .wait_for("Search Results: 2 found", Duration::from_secs(10))
+        .unwrap();
+
+    // Space marks a result in the panel; again unmarks it
+    let screen = driver.press(KeyCode::Char(' ')).unwrap().screen().unwrap();
+    assert!(screen.contains("2 found [1 marked]"), "{}", screen);
+    assert_eq!(screen.matches("* root/").count(), 1, "{}", screen);
+    let screen = driver
+        .press(KeyCode::Char('k'))
+        .unwrap()
+        .press(KeyCode::Char(' '))
+        .unwrap()
+        .screen()
+        .unwrap();
+    assert!(!screen.contains("[1 marked]"), "{}", screen);
+
+    // `a` adds only the marked results to the tree's marks
+    let screen = driver
+        .press(KeyCode::Char('k'))
+        .unwrap()
+        .press(KeyCode::Char(' '))
+        .unwrap()
+        .press(KeyCode::Char('a'))
+        .unwrap()
+        .screen()
+        .unwrap();
+    assert!(
+        screen.contains("Marked 1 result(s) (1 marked)"),
+        "{}",
+        screen
+    );
+    assert!(!screen.contains("2 found [1 marked]"), "{}", screen);
+}
+
+#[test]
+fn test_marked_results_stay_marked_until_the_editor_opens() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("root");
+    fs::create_dir_all(root.join("a")).unwrap();
+    fs::create_dir_all(root.join("b")).unwrap();
+    fs::write(root.join("a/notes.txt"), "notes in a\n").unwrap();
+    fs::write(root.join("b/notes.txt"), "notes in b\n").unwrap();
+
+    let mut config = Config::default();
+    config.behavior.editor = "nano".to_string();
+    config.behavior.nvim_remote = false;
+    let mut driver = driver_with_config(&temp_dir, config);
+    driver
+        .press(KeyCode::Char('s'))
+        .unwrap()
+        .press(KeyCode::Char('/'))
+        .unwrap()
+        .type_text("notes")
+        .unwrap()
+        .press(KeyCode::Enter)
+        .unwrap()
+        .wait_for("Search Results: 2 found", Duration::from_secs(10))
+        .unwrap()
+        .press(KeyCode::Char(' '))
+        .unwrap()
+        .press(KeyCode::Char('e'))
+        .unwrap();
+
+    // The editor runs after exit: the result is still marked until it has started
+    let marker = driver.exit().cloned().flatten().unwrap();
+    let paths = launcher::decode_editor_all(&marker.to_string_lossy()).unwrap();
+    assert_eq!(paths.len(), 1);
+    assert!(paths[0].ends_with("notes.txt"), "{:?}", paths);
+    let screen = driver.screen().unwrap();
+    assert!(screen.contains("2 found [1 marked]"), "{}", screen);
+    driver.app().unmark(&paths);
+    let screen = driver.screen().unwrap();
+    assert!(!screen.contains("[1 marked]"), "{}", screen);
+}
+
+#[test]
+fn test_root_at_result_directory() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("root");
+    fs::create_dir_all(root.join("far/away/below")).unwrap();
+    fs::write(root.join("far/away/below/needle.txt"), "needle\n").unwrap();
+
+    let mut driver = driver(&temp_dir);
+    driver
+        .press(KeyCode::Char('s'))
+        .unwrap()
+        .press(KeyCode::Char('/'))
+        .unwrap()
+        .type_text("needle")
+        .unwrap()
+        .press(KeyCode::Enter)
+        .unwrap()
+        .wait_for("Search Results: 1 found", Duration::from_secs(10))
+        .unwrap();
+
+    // `R` roots the tree at the result's directory, with the result selected
+    driver.press(KeyCode::Char('R')).unwrap();
+    assert_eq!(driver.app().root_path(), root.join("far/away/below"));
+    let screen = driver.screen().unwrap();
+    assert!(
+        screen
+            .lines()
+            .any(|line| line.contains(">> ") && line.contains("needle.txt")),
+        "{}",
+        screen
+    );
+}
+
+#[test]
+fn test_live_filter_narrows_the_tree() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("root");
+    fs::create_dir_all(root.join("docs")).unwrap();
+    fs::write(root.join("alpha.txt"), "a\n").unwrap();
+    fs::write(root.join("beta.txt"), "b\n").unwrap();
+
+    let mut driver = driver(&temp_dir);
+    driver
+        .press(KeyCode::Char('s'))
+        .unwrap()
+        .press(KeyCode::Char('\\'))
+        .unwrap()
+        .type_text("alp")
+        .unwrap();
+    let screen = driver.screen().unwrap();
+    assert!(screen.contains("alpha.txt"), "{}", screen);
+    assert!(!screen.contains("beta.txt"), "{}", screen);
+    assert!(!screen.contains("docs"), "{}", screen);
+
+    // Enter keeps the filter; Esc then clears it
+    driver.press(KeyCode::Enter).unwrap();
+    let screen = driver.screen().unwrap();
+    assert!(screen.contains("[live: alp"), "{}", screen);
+    assert!(!screen.contains("beta.txt"), "{}", screen);
+
+    driver.press(KeyCode::Esc).unwrap();
+    let screen = driver.screen().unwrap();
+    assert!(screen.contains("beta.txt"), "{}", screen);
+    assert!(screen.contains("docs"), "{}", screen);
+}
+
+#[test]
+fn test_marks_survive_root_changes_and_are_reviewed_in_a_panel() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("root");
+    fs::create_dir_all(root.join("docs")).unwrap();
+    fs::write(root.join("docs/guide.md"), "guide\n").unwrap();
+    fs::write(root.join("notes.txt"), "notes\n").unwrap();
+
+    // Mark notes.txt, then make docs the root
+    let mut driver = driver(&temp_dir);
+    driver
+        .press(KeyCode::Char('s'))
+        .unwrap()
+        .press(KeyCode::Char('j'))
+        .unwrap()
+        .press(KeyCode::Char('j'))
+        .unwrap()
+        .press(KeyCode::Char(' '))
+        .unwrap()
+        .press(KeyCode::Char('k'))
+        .unwrap()
+        .press(KeyCode::Enter)
+        .unwrap();
+    assert_eq!(driver.app().root_path(), root.join("docs"));
+    // Without files (and their viewer) the tree's title has room for the count
+    driver.press(KeyCode::Char('s')).unwrap();
+    let screen = driver.screen().unwrap();
+    assert!(
+        screen.contains("[1 marked, 1 outside the root]"),
+        "{}",
+        screen
+    );
+    driver.press(KeyCode::Char('s')).unwrap();
+
+    // The panel lists the mark; Enter goes back to it
+    driver.press(KeyCode::Char('M')).unwrap();
+    let screen = driver.screen().unwrap();
+    assert!(screen.contains("Marks: 1"), "{}", screen);
+    driver.press(KeyCode::Enter).unwrap();
+    assert_eq!(driver.app().root_path(), root);
+    let screen = driver.screen().unwrap();
+    assert!(
+        screen
+            .lines()
+            .any(|line| line.contains(">> ") && line.contains("* ") && line.contains("notes.txt")),
+        "{}",
+        screen
+    );
+
+    // Unmarking the last one closes the panel
+    driver
+        .press(KeyCode::Char('M'))
+        .unwrap()
+        .press(KeyCode::Char('d'))
+        .unwrap();
+    let screen = driver.screen().unwrap();
+    assert!(!screen.contains("Marks:"), "{}", screen);
+    assert!(!screen.contains("marked"), "{}", screen);
+}
+
+#[test]
+fn test_hover_underlines_a_row_and_middle_click_copies_its_path() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("root");
+    fs::create_dir_all(root.join("alpha")).unwrap();
+    fs::create_dir_all(root.join("beta")).unwrap();
+
+    // Rows: border, root, alpha, beta
+    let mut driver = driver(&temp_dir);
+    driver.screen().unwrap();
+    driver.mouse(MouseEventKind::Moved, 10, 3).unwrap();
+    assert!(driver
+        .style_at(10, 3)
+        .unwrap()
+        .add_modifier
+        .contains(Modifier::UNDERLINED));
+    assert!(!driver
+        .style_at(10, 2)
+        .unwrap()
+        .add_modifier
+        .contains(Modifier::UNDERLINED));
+
+    driver
+        .mouse(MouseEventKind::Down(MouseButton::Middle), 10, 3)
+        .unwrap();
+    assert!(clipboard::history().contains(&root.join("beta").display().to_string()));
+    let screen = driver.screen().unwrap();
+    assert!(
+        screen
+            .lines()
+            .any(|line| line.contains(">> ") && line.contains("root")),
+        "{}",
+        screen
+    );
+
+    // A left click selects the row the same way
+    driver.click(10, 3).unwrap();
+    let screen = driver.screen().unwrap();
+    assert!(
+        screen
+            .lines()
+            .any(|line| line.contains(">> ") && line.contains("beta")),
+        "{}",
+        screen
+    );
+}
+
+#[test]
+fn test_copy_key_again_copies_the_absolute_path() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("root");
+    fs::create_dir_all(root.join("docs")).unwrap();
+
+    // Moving in the menu doesn't change what the repeated key copies
+    let mut driver = driver(&temp_dir);
+    driver
+        .press(KeyCode::Char('j'))
+        .unwrap()
+        .press(KeyCode::Char('c'))
+        .unwrap()
+        .press(KeyCode::Char('j'))
+        .unwrap()
+        .press(KeyCode::Char('c'))
+        .unwrap();
+    assert_eq!(
+        clipboard::history()
+            .iter()
+            .find(|text| text.contains("docs")),
+        Some(&root.join("docs").display().to_string())
+    );
+}
+
+#[test]
+fn test_reveal_collapses_the_tree_to_the_viewed_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("root");
+    fs::create_dir_all(root.join("docs/guide")).unwrap();
+    fs::create_dir_all(root.join("src/ui")).unwrap();
+    fs::write(root.join("docs/guide/intro.md"), "intro\n").unwrap();
+    fs::write(root.join("src/ui/tree.rs"), "fn tree() {}\n").unwrap();
+
+    let mut config = Config::default();
+    config.appearance.split_position = 50;
+    let mut driver = driver_with_config(&temp_dir, config);
+    driver
+        .app()
+        .set_selection(&root.join("docs/guide/intro.md"))
+        .unwrap();
+    driver
+        .app()
+        .set_selection(&root.join("src/ui/tree.rs"))
+        .unwrap();
+    assert!(driver.screen().unwrap().contains("intro.md"));
+
+    // From the fullscreen viewer back to the tree with only the file's ancestry open
+    driver
+        .press(KeyCode::Char('v'))
+        .unwrap()
+        .press(KeyCode::Char('T'))
+        .unwrap();
+    let screen = driver.screen().unwrap();
+    assert!(screen.contains(">> ") && screen.contains("tree.rs"));
+    assert!(screen.contains("▶ docs"));
+    assert!(!screen.contains("intro.md"));
+    assert!(!screen.contains("Fullscreen"));
+}
+
+#[test]
+fn test_z_places_the_selection_in_the_tree() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("root");
+    fs::create_dir_all(&root).unwrap();
+    for i in 10..70 {
+        fs::create_dir(root.join(format!("dir{}", i))).unwrap();
+    }
+
+    let mut driver = driver(&temp_dir);
+    driver.app().set_selection(&root.join("dir40")).unwrap();
+    let first_row = |driver: &mut Driver| {
+        let screen = driver.screen().unwrap();
+        screen.lines().nth(1).unwrap().to_string()
+    };
+    assert!(!first_row(&mut driver).contains("dir40"));
+
+    driver
+        .press(KeyCode::Char('z'))
+        .unwrap()
+        .press(KeyCode::Char('t'))
+        .unwrap();
+    assert!(first_row(&mut driver).contains("dir40"));
+
+    // Moving within the screen keeps the placement
+    driver.press(KeyCode::Char('j')).unwrap();
+    assert!(first_row(&mut driver).contains("dir40"));
+}
+
+#[test]
+fn test_two_key_sequence_with_hints() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("root");
+    fs::create_dir_all(&root).unwrap();
+    fs::write(root.join("notes.txt"), "notes\n").unwrap();
+
+    let mut config = Config::default();
+    config.keybindings.search = vec!["/".to_string(), "s f".to_string()];
+    let mut driver = driver_with_config(&temp_dir, config);
+
+    // s waits for the second key and shows what can follow
+    driver.press(KeyCode::Char('s')).unwrap();
+    let screen = driver.screen().unwrap();
+    assert!(screen.contains("s … (Esc: cancel)") && screen.contains("f  search"));
+    assert!(!screen.contains("notes.txt"));
+
+    driver.press(KeyCode::Char('f')).unwrap();
+    let screen = driver.screen().unwrap();
+    assert!(screen.contains("Search: ") && !screen.contains("f  search"));
+    driver.press(KeyCode::Esc).unwrap();
+
+    // Esc cancels; another key runs s on its own first (show files), then itself
+    driver
+        .press(KeyCode::Char('s'))
+        .unwrap()
+        .press(KeyCode::Esc)
+        .unwrap();
+    assert!(driver.exit().is_none());
+    assert!(!driver.screen().unwrap().contains("notes.txt"));
+    driver
+        .press(KeyCode::Char('s'))
+        .unwrap()
+        .press(KeyCode::Char('j'))
+        .unwrap();
+    let screen = driver.screen().unwrap();
+    assert!(screen.contains(">> ") && screen.contains("notes.txt"));
+    assert!(!screen.contains("Esc: cancel"));
+}
+
+#[test]
+fn test_compare_in_the_background_within_the_restriction() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("root");
+    fs::create_dir_all(root.join("inner")).unwrap();
+    fs::write(root.join("a.txt"), "a").unwrap();
+    let other = temp_dir.path().join("other");
+    fs::create_dir_all(&other).unwrap();
+    fs::write(other.join("b.txt"), "b").unwrap();
+
+    let mut compared = driver(&temp_dir);
+    compared.app().set_comparison(&other).unwrap();
+    compared
+        .wait_for("b.txt [righ", Duration::from_secs(10))
+        .unwrap();
+
+    // With --restrict, the other side must be inside the root too
+    let mut restricted = driver(&temp_dir);
+    restricted.app().set_restricted().unwrap();
+    let error = restricted.app().set_comparison(&other).unwrap_err();
+    assert!(
+        error.to_string().contains("outside the restricted"),
+        "{}",
+        error
+    );
+    restricted
+        .app()
+        .set_comparison(&root.join("inner"))
+        .unwrap();
+    restricted
+        .wait_for("a.txt [left", Duration::from_secs(10))
+        .unwrap();
+}
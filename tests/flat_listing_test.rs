@@ -0,0 +1,117 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+use dtree_tui::flat_listing::FlatSort;
+use dtree_tui::navigation::Navigation;
+
+fn visible_names(nav: &Navigation) -> Vec<String> {
+    nav.flat_list
+        .iter()
+        .skip(1)
+        .map(|&id| nav.tree[id].name.clone())
+        .collect()
+}
+
+fn selected_path(nav: &Navigation) -> PathBuf {
+    nav.get_selected_node().unwrap().path.clone()
+}
+
+/// Show the flat listing once it was scanned in the background
+fn wait_for_listing(nav: &mut Navigation) {
+    let started = Instant::now();
+    while nav.pending_flat_listing.is_some() {
+        assert!(started.elapsed() < Duration::from_secs(10), "scan hung");
+        nav.poll_flat_listing();
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+fn select(nav: &mut Navigation, path: &Path) {
+    nav.selected = nav
+        .flat_list
+        .iter()
+        .position(|&id| nav.tree[id].path == path)
+        .unwrap();
+}
+
+#[test]
+fn test_flat_listing_lists_sorts_and_reveals_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().to_path_buf();
+    fs::create_dir_all(root.join("a/b")).unwrap();
+    fs::create_dir_all(root.join(".hidden")).unwrap();
+    fs::write(root.join("a/b/deep.txt"), "1234567890").unwrap();
+    fs::write(root.join("a/mid.txt"), "12345").unwrap();
+    fs::write(root.join("top.txt"), "").unwrap();
+    fs::write(root.join(".hidden/secret.txt"), "").unwrap();
+
+    let mut nav = Navigation::new(root.clone(), false, false, true).unwrap();
+    nav.enter_flat_listing().unwrap();
+    // The tree stays until the files were scanned
+    assert!(nav.flat_listing.is_none());
+    wait_for_listing(&mut nav);
+    let sep = std::path::MAIN_SEPARATOR;
+    assert_eq!(
+        visible_names(&nav),
+        [
+            format!("a{sep}b{sep}deep.txt"),
+            format!("a{sep}mid.txt"),
+            "top.txt".to_string()
+        ]
+    );
+
+    // Largest first, keeping the selected file selected
+    select(&mut nav, &root.join("a/mid.txt"));
+    nav.cycle_flat_sort();
+    assert_eq!(nav.flat_listing.as_ref().unwrap().sort, FlatSort::Size);
+    assert_eq!(visible_names(&nav)[0], format!("a{sep}b{sep}deep.txt"));
+    assert_eq!(selected_path(&nav), root.join("a/mid.txt"));
+
+    // Files created meanwhile show up on reload
+    fs::write(root.join("new.txt"), "").unwrap();
+    nav.reload_tree(true).unwrap();
+    assert_eq!(visible_names(&nav).len(), 3);
+    wait_for_listing(&mut nav);
+    assert_eq!(visible_names(&nav).len(), 4);
+    assert_eq!(nav.flat_listing.as_ref().unwrap().sort, FlatSort::Size);
+
+    // Back in the tree, the selected file is revealed
+    select(&mut nav, &root.join("a/b/deep.txt"));
+    nav.leave_flat_listing(true).unwrap();
+    assert!(nav.flat_listing.is_none());
+    assert_eq!(selected_path(&nav), root.join("a/b/deep.txt"));
+    assert_eq!(nav.tree[nav.flat_list[nav.selected]].name, "deep.txt");
+}
+
+#[test]
+fn test_changing_root_leaves_flat_listing() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().to_path_buf();
+    fs::create_dir_all(root.join("a/b")).unwrap();
+    fs::write(root.join("a/b/file.txt"), "").unwrap();
+
+    let mut nav = Navigation::new(root.clone(), true, true, true).unwrap();
+    nav.enter_flat_listing().unwrap();
+    wait_for_listing(&mut nav);
+    nav.go_to_directory(root.join("a"), true).unwrap();
+    assert!(nav.flat_listing.is_none());
+    assert!(!nav.is_virtual());
+    assert_eq!(visible_names(&nav), ["b"]);
+
+    // Going up leaves the listing for the tree it was opened from
+    nav.enter_flat_listing().unwrap();
+    wait_for_listing(&mut nav);
+    nav.go_to_parent(true).unwrap();
+    assert!(nav.flat_listing.is_none());
+    assert_eq!(nav.root().path, root);
+    assert_eq!(visible_names(&nav), ["a", "b"]);
+
+    // A scan still running for the old root is dropped
+    nav.enter_flat_listing().unwrap();
+    nav.go_to_directory(root.join("a"), true).unwrap();
+    assert!(nav.pending_flat_listing.is_none());
+    assert!(nav.flat_listing.is_none());
+}
@@ -0,0 +1,44 @@
+use dtree_tui::ui::{Focus, UI};
+
+#[test]
+fn test_focus_cycles_over_visible_panes() {
+    let mut ui = UI::new();
+    assert_eq!(ui.focus, Focus::Tree);
+
+    // Only the tree: nothing to move to
+    ui.cycle_focus(true, false, false);
+    assert_eq!(ui.focus, Focus::Tree);
+
+    // Tree, viewer and results; Shift+Tab goes backwards and wraps
+    ui.cycle_focus(true, true, true);
+    assert_eq!(ui.focus, Focus::Viewer);
+    ui.cycle_focus(true, true, true);
+    assert_eq!(ui.focus, Focus::Results);
+    ui.cycle_focus(true, true, true);
+    assert_eq!(ui.focus, Focus::Tree);
+    ui.cycle_focus(false, true, true);
+    assert_eq!(ui.focus, Focus::Results);
+
+    // Hidden panes are skipped
+    ui.focus = Focus::Tree;
+    ui.cycle_focus(true, false, true);
+    assert_eq!(ui.focus, Focus::Results);
+}
+
+#[test]
+fn test_focus_leaves_closed_panes() {
+    let mut ui = UI::new();
+    ui.focus = Focus::Results;
+    ui.sync_focus(true, true, false);
+    assert_eq!(ui.focus, Focus::Results);
+
+    // The bookmarks panel takes the focus while open and gives it to the tree afterwards
+    ui.sync_focus(true, true, true);
+    assert_eq!(ui.focus, Focus::Bookmarks);
+    ui.sync_focus(true, true, false);
+    assert_eq!(ui.focus, Focus::Tree);
+
+    ui.focus = Focus::Viewer;
+    ui.sync_focus(false, true, false);
+    assert_eq!(ui.focus, Focus::Tree);
+}
@@ -0,0 +1,80 @@
+use std::fs;
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+use dtree_tui::git::GitignoreMode;
+use dtree_tui::navigation::Navigation;
+
+fn visible_names(nav: &Navigation) -> Vec<String> {
+    nav.flat_list
+        .iter()
+        .skip(1)
+        .map(|&id| nav.tree[id].name.clone())
+        .collect()
+}
+
+/// Apply the ignored entries once git listed them in the background
+fn wait_for_ignored(nav: &mut Navigation) {
+    let started = Instant::now();
+    while nav.pending_ignored.is_some() {
+        assert!(started.elapsed() < Duration::from_secs(10), "git hung");
+        nav.poll_ignored();
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn test_gitignore_hides_or_dims_ignored_entries() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().to_path_buf();
+    let initialized = Command::new("git")
+        .current_dir(&root)
+        .args(["init", "-q"])
+        .status()
+        .is_ok_and(|status| status.success());
+    // Nothing to test without git
+    if !initialized {
+        return;
+    }
+    fs::write(root.join(".gitignore"), "target/\n*.log\n").unwrap();
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(root.join("src/main.rs"), "").unwrap();
+    fs::write(root.join("src/debug.log"), "").unwrap();
+    fs::create_dir_all(root.join("target/debug")).unwrap();
+    fs::write(root.join("target/debug/app"), "").unwrap();
+
+    // Hidden entries (.git, .gitignore) are left out to keep the listing short
+    let mut nav = Navigation::new(root.clone(), true, false, true).unwrap();
+    nav.toggle_node(&root.join("src"), true).unwrap();
+    nav.toggle_node(&root.join("target"), true).unwrap();
+    let all = ["src", "debug.log", "main.rs", "target", "debug"];
+    assert_eq!(visible_names(&nav), all);
+    assert!(!nav.is_dimmed(&root.join("target")));
+
+    nav.set_gitignore(GitignoreMode::from_config("hide"));
+    // Nothing is left out until git listed the ignored entries
+    assert_eq!(visible_names(&nav), all);
+    wait_for_ignored(&mut nav);
+    assert_eq!(visible_names(&nav), ["src", "main.rs"]);
+
+    // Dimmed entries stay visible, including everything inside an ignored directory
+    nav.set_gitignore(GitignoreMode::from_config("dim"));
+    assert_eq!(visible_names(&nav), all);
+    assert!(nav.is_dimmed(&root.join("target")));
+    assert!(nav.is_dimmed(&root.join("target/debug/app")));
+    assert!(nav.is_dimmed(&root.join("src/debug.log")));
+    assert!(!nav.is_dimmed(&root.join("src/main.rs")));
+
+    // Files appearing later are picked up on reload
+    fs::write(root.join("build.log"), "").unwrap();
+    nav.set_gitignore(GitignoreMode::Hide);
+    nav.reload_tree(true).unwrap();
+    wait_for_ignored(&mut nav);
+    assert!(!visible_names(&nav).contains(&"build.log".to_string()));
+
+    nav.set_gitignore(GitignoreMode::from_config("unknown"));
+    assert_eq!(nav.gitignore, GitignoreMode::Show);
+    assert!(!nav.is_dimmed(&root.join("target")));
+}
@@ -0,0 +1,111 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+use dtree_tui::app::App;
+use dtree_tui::bookmarks::Bookmarks;
+use dtree_tui::config::Config;
+use dtree_tui::layout::{Layout, LayoutPicker};
+
+fn app(temp_dir: &TempDir, layout_file: &Path) -> App {
+    let root = temp_dir.path().join("root");
+    let bookmarks = Bookmarks::open(temp_dir.path().join("bookmarks.json")).unwrap();
+    let mut app = App::with_config(root, Config::default(), bookmarks).unwrap();
+    app.restore_layout(layout_file.to_path_buf());
+    app
+}
+
+fn press(app: &mut App, code: KeyCode) {
+    app.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+        .unwrap();
+}
+
+fn ctrl(app: &mut App, code: KeyCode) {
+    app.handle_key(KeyEvent::new(code, KeyModifiers::CONTROL))
+        .unwrap();
+}
+
+#[test]
+fn test_split_resized_with_keys_is_restored() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join("root/src")).unwrap();
+    let layout_file = temp_dir.path().join("state/layout.toml");
+
+    // Nothing to save until the layout changes
+    let mut first = app(&temp_dir, &layout_file);
+    first.save_layout().unwrap();
+    assert!(!layout_file.exists());
+
+    // No divider without the file viewer
+    ctrl(&mut first, KeyCode::Right);
+    first
+        .handle_key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::NONE))
+        .unwrap();
+    ctrl(&mut first, KeyCode::Right);
+    ctrl(&mut first, KeyCode::Right);
+    first.save_layout().unwrap();
+    let saved = Layout::load(&layout_file).unwrap();
+    assert_eq!(saved.split_position, 30);
+    assert_eq!(saved.bottom_panel_split_position, 70);
+
+    // The next run starts from the saved position; the range is clamped
+    let mut second = app(&temp_dir, &layout_file);
+    second
+        .handle_key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::NONE))
+        .unwrap();
+    for _ in 0..5 {
+        ctrl(&mut second, KeyCode::Left);
+    }
+    second.save_layout().unwrap();
+    assert_eq!(Layout::load(&layout_file).unwrap().split_position, 20);
+}
+
+#[test]
+fn test_named_layout_is_saved_and_restored() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join("root/src")).unwrap();
+    let layout_file = temp_dir.path().join("state/layout.toml");
+    let layouts_file = temp_dir.path().join("state/layouts.toml");
+
+    // Files shown next to a wider tree, saved as "log-review"
+    let mut app = app(&temp_dir, &layout_file);
+    app.load_named_layouts(layouts_file.clone());
+    press(&mut app, KeyCode::Char('s'));
+    ctrl(&mut app, KeyCode::Right);
+    ctrl(&mut app, KeyCode::Right);
+    press(&mut app, KeyCode::Char('L'));
+    press(&mut app, KeyCode::Char('s'));
+    for c in "log-review".chars() {
+        press(&mut app, KeyCode::Char(c));
+    }
+    press(&mut app, KeyCode::Enter);
+    press(&mut app, KeyCode::Esc);
+
+    let mut saved = LayoutPicker::default();
+    saved.load(layouts_file.clone());
+    let named = &saved.layouts[0];
+    assert_eq!(named.name, "log-review");
+    assert_eq!(named.layout.split_position, 30);
+    assert!(named.show_files);
+
+    // Back to the tree only, then restore the layout from the picker
+    ctrl(&mut app, KeyCode::Left);
+    press(&mut app, KeyCode::Char('s'));
+    press(&mut app, KeyCode::Char('L'));
+    press(&mut app, KeyCode::Char('1'));
+    app.save_layout().unwrap();
+    let restored = Layout::load(&layout_file).unwrap();
+    assert_eq!(restored, named.layout);
+
+    // Files are shown again: saving under the same name keeps the layout as it was
+    press(&mut app, KeyCode::Char('L'));
+    press(&mut app, KeyCode::Char('s'));
+    for c in "log-review".chars() {
+        press(&mut app, KeyCode::Char(c));
+    }
+    press(&mut app, KeyCode::Enter);
+    let mut resaved = LayoutPicker::default();
+    resaved.load(layouts_file);
+    assert_eq!(resaved.layouts, saved.layouts);
+}
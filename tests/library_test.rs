@@ -0,0 +1,117 @@
+use std::fs;
+use tempfile::TempDir;
+
+use std::sync::Arc;
+
+use dtree_tui::search::Search;
+use dtree_tui::{
+    dir_size, dir_size_excluding, vfs, Excludes, Navigation, ScanOptions, SearchStream,
+};
+
+mod common;
+use common::wait_for_search;
+
+#[test]
+fn test_embedding_the_engine() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("project");
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+    fs::write(root.join("README.md"), "# project\n").unwrap();
+    fs::write(root.join(".env"), "").unwrap();
+
+    let mut nav = Navigation::builder(&root).show_files(true).build().unwrap();
+    nav.toggle_node(&root.join("src"), true).unwrap();
+    let rows: Vec<(usize, &str)> = nav
+        .visible_nodes()
+        .map(|node| (node.depth, node.name.as_str()))
+        .collect();
+    assert_eq!(
+        rows,
+        [(0, "project"), (1, "src"), (2, "main.rs"), (1, "README.md")]
+    );
+
+    // Hidden entries only when asked for, directories only by default
+    let nav = Navigation::builder(&root)
+        .show_hidden(true)
+        .build()
+        .unwrap();
+    assert_eq!(nav.visible_nodes().len(), 2);
+    let nav = Navigation::builder(&root)
+        .show_files(true)
+        .show_hidden(true)
+        .build()
+        .unwrap();
+    assert!(nav.visible_nodes().any(|node| node.name == ".env"));
+
+    let options = ScanOptions::default();
+    let found: Vec<_> = SearchStream::start(vfs::local(), &root, "main", false, true, options)
+        .map(|result| result.path)
+        .collect();
+    assert_eq!(found, [root.join("src/main.rs")]);
+
+    assert_eq!(dir_size(vfs::local().as_ref(), &root, false), (23, false));
+}
+
+#[test]
+fn test_excluded_directories_are_found_but_not_gone_into() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("project");
+    fs::create_dir_all(root.join("node_modules/left-pad")).unwrap();
+    fs::write(
+        root.join("node_modules/left-pad/index.js"),
+        "module.exports\n",
+    )
+    .unwrap();
+    fs::write(root.join("index.js"), "require()\n").unwrap();
+
+    let exclude = Arc::new(Excludes::new(&["node_modules".to_string()]).unwrap());
+    let options = ScanOptions {
+        exclude: Arc::clone(&exclude),
+        ..ScanOptions::default()
+    };
+    let found: Vec<_> = SearchStream::start(vfs::local(), &root, "index", false, true, options)
+        .map(|result| result.path)
+        .collect();
+    assert_eq!(found, [root.join("index.js")]);
+    // The excluded directory itself is still found
+    let options = ScanOptions {
+        exclude: Arc::clone(&exclude),
+        ..ScanOptions::default()
+    };
+    let found: Vec<_> = SearchStream::start(vfs::local(), &root, "node", false, true, options)
+        .map(|result| result.path)
+        .collect();
+    assert_eq!(found, [root.join("node_modules")]);
+
+    let local = vfs::local();
+    assert_eq!(dir_size(local.as_ref(), &root, false), (25, false));
+    assert_eq!(
+        dir_size_excluding(local.as_ref(), &root, false, &exclude),
+        (10, true)
+    );
+    // Sizing an excluded directory itself still counts what's in it
+    let modules = root.join("node_modules");
+    assert_eq!(
+        dir_size_excluding(local.as_ref(), &modules, false, &exclude),
+        (15, false)
+    );
+    // What the tree shows is found, even inside an excluded directory
+    let left_pad = root.join("node_modules/left-pad/index.js");
+    let mut nav = Navigation::builder(&root)
+        .show_files(true)
+        .exclude(exclude)
+        .build()
+        .unwrap();
+    nav.reveal(&left_pad, true).unwrap();
+    let mut search = Search::new();
+    search.enter_mode();
+    for c in "index".chars() {
+        search.add_char(c);
+    }
+    search.perform_search(&nav.tree, true, nav.scan_options());
+    wait_for_search(&mut search);
+    let mut found: Vec<_> = search.results.iter().map(|result| &result.path).collect();
+    found.sort();
+    assert_eq!(found, [&root.join("index.js"), &left_pad]);
+}
@@ -0,0 +1,50 @@
+#![cfg(unix)]
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::TempDir;
+
+use dtree_tui::tree_node::TreeNode;
+
+fn node_with_mode(path: &Path, mode: u32) -> TreeNode {
+    fs::set_permissions(path, fs::Permissions::from_mode(mode)).unwrap();
+    TreeNode::new(path.to_path_buf(), 1).unwrap()
+}
+
+#[test]
+fn test_executable_and_permission_warnings() {
+    let temp_dir = TempDir::new().unwrap();
+    let script = temp_dir.path().join("run.sh");
+    fs::write(&script, "#!/bin/sh\n").unwrap();
+    let dir = temp_dir.path().join("shared");
+    fs::create_dir(&dir).unwrap();
+
+    let plain = node_with_mode(&script, 0o644);
+    assert!(!plain.is_executable());
+    assert_eq!(plain.permission_warning(), None);
+
+    let executable = node_with_mode(&script, 0o755);
+    assert!(executable.is_executable());
+    assert_eq!(executable.permission_warning(), None);
+
+    assert_eq!(
+        node_with_mode(&script, 0o666).permission_warning(),
+        Some("o+w")
+    );
+    // Setting setuid/setgid needs no privileges for files we own, but some
+    // filesystems drop the bits - only check what was actually set
+    let setuid = node_with_mode(&script, 0o4755);
+    if setuid.mode & 0o4000 != 0 {
+        assert_eq!(setuid.permission_warning(), Some("setuid"));
+    }
+
+    // Directories: execute bits mean searchable, and sticky world-writable ones are normal
+    let searchable = node_with_mode(&dir, 0o755);
+    assert!(!searchable.is_executable());
+    assert_eq!(
+        node_with_mode(&dir, 0o777).permission_warning(),
+        Some("o+w")
+    );
+    assert_eq!(node_with_mode(&dir, 0o1777).permission_warning(), None);
+}
@@ -0,0 +1,158 @@
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+use dtree_tui::navigation::Navigation;
+use dtree_tui::quick_filter::QuickFilter;
+use dtree_tui::search::Search;
+
+mod common;
+use common::wait_for_search;
+
+fn visible_names(nav: &Navigation) -> Vec<String> {
+    nav.flat_list
+        .iter()
+        .skip(1)
+        .map(|&id| nav.tree[id].name.clone())
+        .collect()
+}
+
+fn selected_path(nav: &Navigation) -> PathBuf {
+    nav.get_selected_node().unwrap().path.clone()
+}
+
+/// Search the tree for `query` like `/` does, to completion; the names found, sorted
+fn search_names(nav: &Navigation, query: &str) -> Vec<String> {
+    let mut search = Search::new();
+    search.enter_mode();
+    for c in query.chars() {
+        search.add_char(c);
+    }
+    search.perform_search(&nav.tree, true, nav.scan_options());
+    wait_for_search(&mut search);
+    let mut names: Vec<String> = search
+        .results
+        .iter()
+        .map(|result| {
+            result
+                .path
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_quick_filter_hides_other_file_types() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().to_path_buf();
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(root.join("src/main.rs"), "").unwrap();
+    fs::write(root.join("src/logo.PNG"), "").unwrap();
+    fs::write(root.join("notes.md"), "").unwrap();
+    fs::write(root.join("photo.jpg"), "").unwrap();
+
+    let mut nav = Navigation::new(root.clone(), true, true, true).unwrap();
+    nav.toggle_node(&root.join("src"), true).unwrap();
+    assert_eq!(
+        visible_names(&nav),
+        ["src", "logo.PNG", "main.rs", "notes.md", "photo.jpg"]
+    );
+
+    // Directories stay so matching files inside them can be reached
+    nav.selected = 2;
+    nav.set_quick_filter(QuickFilter::Images);
+    assert_eq!(visible_names(&nav), ["src", "logo.PNG", "photo.jpg"]);
+
+    // The selected file is filtered out - its directory is selected instead
+    nav.set_quick_filter(QuickFilter::Code);
+    assert_eq!(visible_names(&nav), ["src", "main.rs"]);
+    nav.selected = 2;
+    nav.set_quick_filter(QuickFilter::Documents);
+    assert_eq!(visible_names(&nav), ["src", "notes.md"]);
+    assert_eq!(selected_path(&nav), root.join("src"));
+
+    // Collapsing and expanding keeps the filter
+    nav.toggle_node(&root.join("src"), true).unwrap();
+    nav.toggle_node(&root.join("src"), true).unwrap();
+    assert_eq!(visible_names(&nav), ["src", "notes.md"]);
+    nav.set_quick_filter(QuickFilter::Directories);
+    assert_eq!(visible_names(&nav), ["src"]);
+
+    nav.set_quick_filter(QuickFilter::Directories.next().next().next().next());
+    assert_eq!(nav.filters.quick_filter, QuickFilter::All);
+    assert_eq!(visible_names(&nav).len(), 5);
+}
+
+#[test]
+fn test_search_finds_only_what_the_tree_shows() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join(".work"); // Hidden itself, searched anyway
+    fs::create_dir_all(root.join("src/assets")).unwrap();
+    fs::write(root.join("src/app.rs"), "").unwrap();
+    fs::write(root.join("src/assets/app.png"), "").unwrap();
+    fs::create_dir_all(root.join(".cache")).unwrap();
+    fs::write(root.join(".cache/app.rs"), "").unwrap();
+
+    // Loaded (quick search) and not yet loaded (deep search) entries alike
+    let mut nav = Navigation::new(root.clone(), true, false, false).unwrap();
+    nav.toggle_node(&root.join("src"), true).unwrap();
+    assert_eq!(search_names(&nav, "app"), ["app.png", "app.rs"]);
+
+    nav.set_quick_filter(QuickFilter::Code);
+    assert_eq!(search_names(&nav, "app"), ["app.rs"]);
+    assert_eq!(search_names(&nav, "/ap"), ["app.rs"]);
+    nav.set_quick_filter(QuickFilter::Directories);
+    assert_eq!(search_names(&nav, "a"), ["assets"]);
+
+    let nav = Navigation::new(root.clone(), true, true, false).unwrap();
+    assert_eq!(search_names(&nav, "app"), ["app.png", "app.rs", "app.rs"]);
+}
+
+#[test]
+fn test_live_filter_keeps_matches_and_their_directories() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().to_path_buf();
+    fs::create_dir_all(root.join("src/ui")).unwrap();
+    fs::write(root.join("src/ui/tree.rs"), "").unwrap();
+    fs::write(root.join("src/main.rs"), "").unwrap();
+    fs::write(root.join("README.md"), "").unwrap();
+
+    let mut nav = Navigation::new(root.clone(), true, false, false).unwrap();
+    nav.toggle_node(&root.join("src"), true).unwrap();
+    nav.toggle_node(&root.join("src/ui"), true).unwrap();
+    assert_eq!(
+        visible_names(&nav),
+        ["src", "ui", "tree.rs", "main.rs", "README.md"]
+    );
+
+    // Case-insensitive; only loaded entries are narrowed
+    nav.set_live_filter("TREE");
+    assert_eq!(visible_names(&nav), ["src", "ui", "tree.rs"]);
+    assert_eq!(selected_path(&nav), root.join("src/ui/tree.rs"));
+
+    // Collapsing and expanding keeps the filter
+    nav.toggle_node(&root.join("src"), true).unwrap();
+    assert_eq!(visible_names(&nav), ["src"]);
+    nav.toggle_node(&root.join("src"), true).unwrap();
+    assert_eq!(visible_names(&nav), ["src", "ui", "tree.rs"]);
+
+    nav.set_live_filter("");
+    assert_eq!(visible_names(&nav).len(), 5);
+    assert_eq!(selected_path(&nav), root.join("src/ui/tree.rs"));
+
+    // Matches in sibling directories each keep their own directory
+    fs::create_dir_all(root.join("a")).unwrap();
+    fs::create_dir_all(root.join("c")).unwrap();
+    fs::write(root.join("a/m.txt"), "").unwrap();
+    fs::write(root.join("c/m.txt"), "").unwrap();
+    nav.reload_tree(true).unwrap();
+    nav.toggle_node(&root.join("a"), true).unwrap();
+    nav.toggle_node(&root.join("c"), true).unwrap();
+    nav.set_live_filter("m.txt");
+    assert_eq!(visible_names(&nav), ["a", "m.txt", "c", "m.txt"]);
+}
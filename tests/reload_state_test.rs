@@ -0,0 +1,121 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+use dtree_tui::navigation::Navigation;
+
+fn selected_path(nav: &Navigation) -> PathBuf {
+    nav.get_selected_node().unwrap().path.clone()
+}
+
+fn select(nav: &mut Navigation, path: &Path) {
+    nav.selected = nav
+        .flat_list
+        .iter()
+        .position(|&id| nav.tree[id].path == path)
+        .unwrap();
+}
+
+#[test]
+fn test_reload_keeps_expansion_and_selection() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().to_path_buf();
+    fs::create_dir_all(root.join("a/b/c")).unwrap();
+    fs::write(root.join("a/b/file.txt"), "").unwrap();
+
+    let mut nav = Navigation::new(root.clone(), false, true, true).unwrap();
+    nav.toggle_node(&root.join("a"), false).unwrap();
+    nav.toggle_node(&root.join("a/b"), false).unwrap();
+    select(&mut nav, &root.join("a/b/c"));
+
+    // Showing files reloads the tree - nested expansion and selection survive
+    nav.reload_tree(true).unwrap();
+    assert!(nav.expanded_paths().contains(&root.join("a/b")));
+    assert_eq!(selected_path(&nav), root.join("a/b/c"));
+
+    // Hiding files while one is selected falls back to its directory
+    select(&mut nav, &root.join("a/b/file.txt"));
+    nav.reload_tree(false).unwrap();
+    assert_eq!(selected_path(&nav), root.join("a/b"));
+}
+
+#[test]
+fn test_go_to_parent_keeps_previous_root_expanded() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().to_path_buf();
+    fs::create_dir_all(root.join("a/b/c")).unwrap();
+
+    let mut nav = Navigation::new(root.join("a"), false, true, true).unwrap();
+    nav.toggle_node(&root.join("a/b"), false).unwrap();
+
+    nav.go_to_parent(false).unwrap();
+    assert_eq!(nav.root().path, root);
+    assert!(nav.expanded_paths().contains(&root.join("a")));
+    assert!(nav.expanded_paths().contains(&root.join("a/b")));
+    assert_eq!(selected_path(&nav), root.join("a"));
+}
+
+/// Check that every visible entry is found at its row
+fn assert_rows_findable(nav: &mut Navigation, context: &str) {
+    let paths: Vec<PathBuf> = nav
+        .flat_list
+        .iter()
+        .map(|&id| nav.tree[id].path.clone())
+        .collect();
+    for (row, path) in paths.iter().enumerate() {
+        nav.select_path_or_ancestor(path);
+        assert_eq!(nav.selected, row, "{} {}", path.display(), context);
+    }
+}
+
+#[test]
+fn test_expand_and_collapse_keep_paths_findable() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().to_path_buf();
+    for dir in ["a/x/y", "b/z", "c"] {
+        fs::create_dir_all(root.join(dir)).unwrap();
+    }
+
+    let mut nav = Navigation::new(root.clone(), false, true, true).unwrap();
+    let steps = ["b", "a", "a/x", "b", "a", "a", "b", "b"];
+    for step in steps {
+        nav.toggle_node(&root.join(step), false).unwrap();
+        assert_rows_findable(&mut nav, &format!("after toggling {}", step));
+    }
+
+    // Several changes before a lookup are caught up with at once
+    for step in ["a/x", "b", "a", "c"] {
+        nav.toggle_node(&root.join(step), false).unwrap();
+    }
+    assert_rows_findable(&mut nav, "after several toggles");
+
+    // A collapsed directory's children fall back to it
+    nav.toggle_node(&root.join("b"), false).unwrap();
+    nav.select_path_or_ancestor(&root.join("b/z"));
+    assert_eq!(selected_path(&nav), root.join("b"));
+}
+
+#[test]
+fn test_marks_survive_root_changes_until_their_entries_are_gone() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().to_path_buf();
+    fs::create_dir_all(root.join("a/b")).unwrap();
+    fs::write(root.join("a/kept.txt"), "").unwrap();
+    fs::write(root.join("a/b/gone.txt"), "").unwrap();
+
+    let mut nav = Navigation::new(root.join("a"), true, true, true).unwrap();
+    nav.toggle_mark(&root.join("a/kept.txt"));
+    nav.toggle_mark(&root.join("a/b/gone.txt"));
+
+    // Re-rooting below and going back up keeps both, counting those left outside
+    nav.go_to_directory(root.join("a/b"), true).unwrap();
+    assert_eq!(nav.marks_outside_root(), 1);
+    nav.go_to_parent(true).unwrap();
+    assert_eq!(nav.marks_outside_root(), 0);
+    assert!(nav.is_marked(&root.join("a/b/gone.txt")));
+
+    // A reload drops the marks on deleted entries
+    fs::remove_file(root.join("a/b/gone.txt")).unwrap();
+    nav.reload_tree(true).unwrap();
+    assert_eq!(nav.marked_or_selected(), [root.join("a/kept.txt")]);
+}
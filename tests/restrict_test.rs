@@ -0,0 +1,54 @@
+use std::fs;
+use tempfile::TempDir;
+
+use dtree_tui::navigation::Navigation;
+
+#[test]
+fn test_restricted_navigation_stays_inside_root() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("project");
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::create_dir_all(temp_dir.path().join("outside")).unwrap();
+
+    let mut nav = Navigation::new(root.clone(), false, true, true).unwrap();
+    nav.restrict_to_root(false).unwrap();
+
+    // Going up from the restricted root does nothing
+    nav.go_to_parent(false).unwrap();
+    assert_eq!(nav.root().path, root);
+
+    // Directories outside are refused with an error message
+    let error = nav
+        .go_to_directory(temp_dir.path().join("outside"), false)
+        .unwrap();
+    assert!(error.is_some());
+    assert_eq!(nav.root().path, root);
+
+    // Going down and back up to the root still works
+    assert!(nav
+        .go_to_directory(root.join("src"), false)
+        .unwrap()
+        .is_none());
+    nav.go_to_parent(false).unwrap();
+    assert_eq!(nav.root().path, root);
+}
+
+#[test]
+#[cfg(unix)]
+fn test_restricted_navigation_blocks_symlink_escape() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("project");
+    fs::create_dir_all(&root).unwrap();
+    fs::create_dir_all(temp_dir.path().join("secret")).unwrap();
+    std::os::unix::fs::symlink(temp_dir.path().join("secret"), root.join("link")).unwrap();
+
+    let mut nav = Navigation::new(root.clone(), false, true, true).unwrap();
+    nav.restrict_to_root(false).unwrap();
+
+    assert!(!nav.follow_symlinks);
+    assert!(!nav.is_within_restriction(&root.join("link")));
+    assert!(nav
+        .go_to_directory(root.join("link"), false)
+        .unwrap()
+        .is_some());
+}
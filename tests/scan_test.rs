@@ -0,0 +1,209 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+use dtree_tui::filter_set::FilterSet;
+use dtree_tui::scan::{self, ScanKind, ScanOptions, TOP_FILES};
+use dtree_tui::search::{Search, SearchMessage};
+use dtree_tui::{vfs, wakeup};
+
+mod common;
+use common::wait_for_search;
+
+/// Run a scan to completion
+fn run_scan(root: &Path, kind: ScanKind, options: ScanOptions) -> Search {
+    let mut search = Search::new();
+    search.start_scan(vfs::local(), kind, root, options);
+    wait_for_search(&mut search);
+    assert!(search.show_results);
+    search
+}
+
+/// Found paths relative to `root`
+fn scan(root: &Path, kind: ScanKind, options: ScanOptions) -> Vec<PathBuf> {
+    run_scan(root, kind, options)
+        .results
+        .iter()
+        .map(|result| result.path.strip_prefix(root).unwrap().to_path_buf())
+        .collect()
+}
+
+#[test]
+fn test_empty_scan_finds_empty_dirs_and_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("build/cache")).unwrap();
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+    fs::write(root.join("src/mod.rs"), "").unwrap();
+    fs::create_dir_all(root.join("keep")).unwrap();
+    fs::write(root.join("keep/.gitkeep"), "").unwrap();
+    fs::create_dir_all(root.join(".hidden/empty")).unwrap();
+
+    let options = ScanOptions::default();
+    let dirs_only = scan(
+        root,
+        ScanKind::Empty {
+            include_files: false,
+        },
+        options.clone(),
+    );
+    // A parent of an empty directory isn't empty; hidden entries count but aren't listed
+    assert_eq!(dirs_only, [PathBuf::from("build/cache")]);
+
+    let with_files = scan(
+        root,
+        ScanKind::Empty {
+            include_files: true,
+        },
+        options.clone(),
+    );
+    assert_eq!(
+        with_files,
+        [PathBuf::from("build/cache"), PathBuf::from("src/mod.rs")]
+    );
+
+    let with_hidden = scan(
+        root,
+        ScanKind::Empty {
+            include_files: true,
+        },
+        ScanOptions {
+            filters: FilterSet::new(true),
+            ..options
+        },
+    );
+    assert_eq!(
+        with_hidden,
+        [
+            PathBuf::from(".hidden/empty"),
+            PathBuf::from("build/cache"),
+            PathBuf::from("keep/.gitkeep"),
+            PathBuf::from("src/mod.rs"),
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_broken_symlink_scan_lists_dangling_links() {
+    use std::os::unix::fs::symlink;
+
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("docs")).unwrap();
+    fs::write(root.join("docs/readme.md"), "hi").unwrap();
+    symlink("readme.md", root.join("docs/ok.md")).unwrap();
+    symlink("gone.md", root.join("docs/dangling.md")).unwrap();
+    symlink(root.join("missing"), root.join("old")).unwrap();
+    // A link to a directory is not followed into, so links can't loop
+    symlink(root, root.join("docs/loop")).unwrap();
+
+    let search = run_scan(root, ScanKind::BrokenSymlinks, ScanOptions::default());
+    let found: Vec<(PathBuf, Option<String>)> = search
+        .results
+        .iter()
+        .map(|result| {
+            (
+                result.path.strip_prefix(root).unwrap().to_path_buf(),
+                result.detail.clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (
+                PathBuf::from("docs/dangling.md"),
+                Some("-> gone.md".to_string())
+            ),
+            (
+                PathBuf::from("old"),
+                Some(format!("-> {}", root.join("missing").display()))
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_largest_files_keeps_top_n_by_size() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("data/raw")).unwrap();
+    for i in 0..TOP_FILES + 5 {
+        fs::write(
+            root.join(format!("data/raw/{:02}.bin", i)),
+            vec![0u8; i * 10],
+        )
+        .unwrap();
+    }
+    fs::write(root.join("data/big.iso"), vec![0u8; 4096]).unwrap();
+
+    let search = run_scan(root, ScanKind::LargestFiles, ScanOptions::default());
+    assert_eq!(search.results.len(), TOP_FILES);
+
+    // Biggest first, with the size as detail
+    let first = &search.results[0];
+    assert_eq!(first.path, root.join("data/big.iso"));
+    assert_eq!(first.detail.as_deref(), Some("4.0K"));
+    let last = &search.results[TOP_FILES - 1];
+    assert_eq!(last.path, root.join("data/raw/06.bin"));
+    assert_eq!(last.detail.as_deref(), Some("60B"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_hardlink_scan_finds_every_link() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("a/b")).unwrap();
+    fs::write(root.join("a/original.txt"), "shared").unwrap();
+    fs::write(root.join("a/copy.txt"), "shared").unwrap();
+    fs::hard_link(root.join("a/original.txt"), root.join("a/b/link.txt")).unwrap();
+    fs::hard_link(root.join("a/original.txt"), root.join("top.txt")).unwrap();
+
+    let metadata = fs::metadata(root.join("a/original.txt")).unwrap();
+    let id = dtree_tui::platform::file_id(&metadata).unwrap();
+    assert_eq!(id.links, 3);
+
+    // A copy with the same content is another file
+    let found = scan(root, ScanKind::Hardlinks(id), ScanOptions::default());
+    assert_eq!(
+        found,
+        [
+            PathBuf::from("a/b/link.txt"),
+            PathBuf::from("a/original.txt"),
+            PathBuf::from("top.txt"),
+        ]
+    );
+}
+
+#[test]
+fn test_cancelled_scan_stops_without_results() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    for i in 0..50 {
+        fs::write(root.join(format!("{i:02}.txt")), "").unwrap();
+    }
+
+    let (result_tx, result_rx) = wakeup::unbounded();
+    let (cancel_tx, cancel_rx) = crossbeam_channel::bounded(1);
+    cancel_tx.send(()).unwrap();
+    let kind = ScanKind::Empty {
+        include_files: true,
+    };
+    scan::run(
+        vfs::local().as_ref(),
+        kind,
+        root,
+        ScanOptions::default(),
+        &result_tx,
+        &cancel_rx,
+    );
+    assert!(
+        result_rx
+            .try_iter()
+            .all(|message| !matches!(message, SearchMessage::Found(_))),
+        "a cancelled scan sent results"
+    );
+}
@@ -0,0 +1,71 @@
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+use dtree_tui::navigation::Navigation;
+
+fn visible_paths(nav: &Navigation) -> Vec<PathBuf> {
+    nav.flat_list
+        .iter()
+        .map(|&id| nav.tree[id].path.clone())
+        .collect()
+}
+
+#[test]
+fn test_virtual_tree_contains_only_listed_paths() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("src/ui")).unwrap();
+    fs::write(root.join("src/main.rs"), "").unwrap();
+    fs::write(root.join("src/ui/render.rs"), "").unwrap();
+    fs::write(root.join("src/unlisted.rs"), "").unwrap();
+    fs::write(root.join("README.md"), "").unwrap();
+
+    let paths = vec![
+        root.join("README.md"),
+        root.join("./src/ui/render.rs"),
+        root.join("src/ui/../main.rs"),
+        root.join("src/../src/main.rs"), // Same file once normalized
+        root.join("missing.txt"),        // Dropped: doesn't exist
+    ];
+    let nav = Navigation::from_paths(&paths, true, true).unwrap();
+
+    assert!(nav.is_virtual());
+    assert_eq!(nav.root().path, root);
+
+    // Fully expanded, directories first, intermediate "ui" directory added
+    assert_eq!(
+        visible_paths(&nav),
+        vec![
+            root.to_path_buf(),
+            root.join("src"),
+            root.join("src/ui"),
+            root.join("src/ui/render.rs"),
+            root.join("src/main.rs"),
+            root.join("README.md"),
+        ]
+    );
+}
+
+#[test]
+fn test_virtual_tree_reload_keeps_list() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::write(root.join("a.txt"), "").unwrap();
+    fs::write(root.join("b.txt"), "").unwrap();
+
+    let mut nav = Navigation::from_paths(&[root.join("a.txt")], true, true).unwrap();
+    nav.reload_tree(true).unwrap();
+    nav.go_to_parent(true).unwrap();
+
+    assert_eq!(
+        visible_paths(&nav),
+        vec![root.to_path_buf(), root.join("a.txt")]
+    );
+}
+
+#[test]
+fn test_virtual_tree_rejects_empty_list() {
+    let temp_dir = TempDir::new().unwrap();
+    assert!(Navigation::from_paths(&[temp_dir.path().join("nope")], true, true).is_err());
+}
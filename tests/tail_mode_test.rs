@@ -208,3 +208,41 @@ fn test_can_use_tail_mode() {
         "Empty path should not support tail mode"
     );
 }
+
+#[test]
+fn test_head_and_tail_in_one_view() {
+    let temp_dir = TempDir::new().unwrap();
+    let test_file = temp_dir.path().join("test.txt");
+
+    let mut file = File::create(&test_file).unwrap();
+    for i in 1..=100 {
+        writeln!(file, "Line {}", i).unwrap();
+    }
+
+    let mut file_viewer = dtree_tui::file_viewer::FileViewer::new();
+    file_viewer.limits.head_tail_lines = 3;
+    file_viewer
+        .load_file_with_width(&test_file, Some(80), 20, false, "base16-ocean.dark")
+        .unwrap();
+
+    assert_eq!(
+        file_viewer.content,
+        [
+            "Line 1",
+            "Line 2",
+            "Line 3",
+            "[... 94 lines omitted ...]",
+            "Line 98",
+            "Line 99",
+            "Line 100",
+        ]
+    );
+    assert_eq!(file_viewer.line_number(3), None);
+    assert_eq!(file_viewer.line_number(4), Some(98));
+
+    // Files within max_file_lines are shown whole
+    file_viewer
+        .load_file_with_width(&test_file, Some(80), 100, false, "base16-ocean.dark")
+        .unwrap();
+    assert_eq!(file_viewer.content.len(), 100);
+}